@@ -0,0 +1,147 @@
+//! # サンプルの性能計測
+//!
+//! プラグイン化済みのサンプルに負荷の高いシナリオを組み込み、ウィンドウなしで
+//! 決まったフレーム数だけ実行して、1フレームにかかった時間の統計を取る。
+//! 結果は `benchmark_results/<日時>.json` と `.csv` に書き出すので、
+//! Bevyの更新などの前後で比べられる。
+//!
+//! ```bash
+//! cargo run -p benchmarks --release -- --frames 600 --warmup 60
+//! cargo run -p benchmarks --release -- --scenario primitives_50k_points
+//! ```
+
+mod report;
+mod scenarios;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use bevy::app::PluginsState;
+use bevy::prelude::*;
+
+use report::{FrameStats, Report, ScenarioResult};
+use scenarios::{SCENARIOS, Scenario};
+
+/// `--frames`を指定しなかったときに計測するフレーム数
+const DEFAULT_FRAMES: u32 = 600;
+
+/// `--warmup`を指定しなかったときに計測から除くフレーム数(読み込みや初期化の分)
+const DEFAULT_WARMUP: u32 = 60;
+
+/// 結果の出力先
+const RESULT_DIR: &str = "benchmark_results";
+
+/// コマンドライン引数で指定する計測の設定
+struct Options {
+    frames: u32,              // 計測するフレーム数
+    warmup: u32,              // 計測の前に実行するフレーム数
+    scenario: Option<String>, // 指定されたシナリオだけを実行する
+}
+
+fn main() {
+    let options = options_from_args(std::env::args().skip(1));
+    let scenarios: Vec<&Scenario> = SCENARIOS
+        .iter()
+        .filter(|scenario| {
+            options
+                .scenario
+                .as_deref()
+                .is_none_or(|name| scenario.name == name)
+        })
+        .collect();
+    if scenarios.is_empty() {
+        let names: Vec<_> = SCENARIOS.iter().map(|scenario| scenario.name).collect();
+        panic!("不明なシナリオです(指定できるもの: {})", names.join(", "));
+    }
+
+    let results = scenarios
+        .into_iter()
+        .map(|scenario| run_scenario(scenario, &options))
+        .collect();
+    let report = Report::new(options.frames, options.warmup, results);
+
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let stem = PathBuf::from(RESULT_DIR).join(timestamp);
+    match report.write(&stem) {
+        Ok(()) => println!("{}", report.summary()),
+        Err(err) => panic!("{}に結果を書き出せません: {err}", stem.display()),
+    }
+}
+
+/// コマンドライン引数から計測の設定を読み取る
+fn options_from_args(mut args: impl Iterator<Item = String>) -> Options {
+    let mut options = Options {
+        frames: DEFAULT_FRAMES,
+        warmup: DEFAULT_WARMUP,
+        scenario: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                options.frames = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--frames には正の整数を指定してください");
+            }
+            "--warmup" => {
+                options.warmup = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--warmup には0以上の整数を指定してください");
+            }
+            "--scenario" => {
+                let name = args.next().expect("--scenario にはシナリオ名を指定してください");
+                options.scenario = Some(name);
+            }
+            _ => panic!("不明な引数です: {arg}"),
+        }
+    }
+
+    options
+}
+
+/// シナリオを1つ実行し、フレーム時間の統計を返す
+fn run_scenario(scenario: &Scenario, options: &Options) -> ScenarioResult {
+    println!("計測を開始します: {}", scenario.name);
+
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::headless_plugins(None).set(AssetPlugin {
+        // アセットは各サンプルのディレクトリから読み込む
+        file_path: demo_dir(scenario).join("assets").to_string_lossy().into_owned(),
+        ..default()
+    }));
+    (scenario.setup)(&mut app);
+
+    // `App::run`の代わりに、ここで1フレームずつ更新して時間を測る
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+    for _ in 0..options.warmup {
+        app.update();
+    }
+    let frame_times: Vec<Duration> = (0..options.frames)
+        .map(|_| {
+            let start = Instant::now();
+            app.update();
+            start.elapsed()
+        })
+        .collect();
+
+    let entities = app.world().entities().len();
+    ScenarioResult {
+        name: scenario.name,
+        description: scenario.description,
+        entities,
+        stats: FrameStats::from_frame_times(&frame_times),
+    }
+}
+
+/// サンプルのクレートのディレクトリ
+fn demo_dir(scenario: &Scenario) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(scenario.demo)
+}
@@ -0,0 +1,140 @@
+//! 計測結果の集計と、JSON・CSVへの書き出し
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// 1回の計測全体の結果
+#[derive(Serialize)]
+pub struct Report {
+    pub timestamp: String,      // 計測した日時
+    pub profile: &'static str,  // ビルドの種類(debugかrelease)
+    pub frames: u32,            // 計測したフレーム数
+    pub warmup: u32,            // 計測から除いたフレーム数
+    pub scenarios: Vec<ScenarioResult>,
+}
+
+/// シナリオごとの結果
+#[derive(Serialize)]
+pub struct ScenarioResult {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub entities: u32, // 計測終了時のエンティティ数
+    pub stats: FrameStats,
+}
+
+/// フレーム時間の統計(ミリ秒)
+#[derive(Serialize, Default)]
+pub struct FrameStats {
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub mean_fps: f64, // 平均フレーム時間から求めたFPS
+}
+
+impl FrameStats {
+    /// 各フレームにかかった時間から統計を求める
+    pub fn from_frame_times(frame_times: &[Duration]) -> Self {
+        if frame_times.is_empty() {
+            return Self::default();
+        }
+
+        let mut millis: Vec<f64> = frame_times
+            .iter()
+            .map(|time| time.as_secs_f64() * 1000.0)
+            .collect();
+        millis.sort_by(f64::total_cmp);
+
+        let mean_ms = millis.iter().sum::<f64>() / millis.len() as f64;
+        Self {
+            mean_ms,
+            median_ms: percentile(&millis, 0.5),
+            p95_ms: percentile(&millis, 0.95),
+            p99_ms: percentile(&millis, 0.99),
+            min_ms: millis[0],
+            max_ms: millis[millis.len() - 1],
+            mean_fps: 1000.0 / mean_ms,
+        }
+    }
+}
+
+/// 昇順に並んだ値の百分位数(最も近い順位の値を使う)
+fn percentile(sorted: &[f64], ratio: f64) -> f64 {
+    let rank = (ratio * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Report {
+    pub fn new(frames: u32, warmup: u32, scenarios: Vec<ScenarioResult>) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+            frames,
+            warmup,
+            scenarios,
+        }
+    }
+
+    /// `<stem>.json`と`<stem>.csv`に書き出す
+    pub fn write(&self, stem: &Path) -> std::io::Result<()> {
+        if let Some(dir) = stem.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(stem.with_extension("json"), json)?;
+        std::fs::write(stem.with_extension("csv"), self.to_csv())?;
+        Ok(())
+    }
+
+    /// シナリオごとに1行のCSV
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "scenario,profile,frames,entities,mean_ms,median_ms,p95_ms,p99_ms,min_ms,max_ms,mean_fps\n",
+        );
+        for result in &self.scenarios {
+            let stats = &result.stats;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.1}",
+                result.name,
+                self.profile,
+                self.frames,
+                result.entities,
+                stats.mean_ms,
+                stats.median_ms,
+                stats.p95_ms,
+                stats.p99_ms,
+                stats.min_ms,
+                stats.max_ms,
+                stats.mean_fps,
+            );
+        }
+        csv
+    }
+
+    /// 端末に表示する結果の一覧
+    pub fn summary(&self) -> String {
+        let mut summary = format!("計測結果({}ビルド、{}フレーム)\n", self.profile, self.frames);
+        for result in &self.scenarios {
+            let _ = writeln!(
+                summary,
+                "{:<28} mean {:>8.3} ms  p95 {:>8.3} ms  p99 {:>8.3} ms  ({:.1} fps)",
+                result.name,
+                result.stats.mean_ms,
+                result.stats.p95_ms,
+                result.stats.p99_ms,
+                result.stats.mean_fps,
+            );
+        }
+        summary
+    }
+}
@@ -0,0 +1,116 @@
+//! 計測するシナリオの一覧
+//!
+//! 各シナリオはサンプルのプラグインを組み込み、負荷を上げるための設定やシステムを追加する。
+
+use bevy::prelude::*;
+use examina_clone::{Enemy, ExaminaClonePlugin, Waypoint};
+use moving_3d_cube::{InfiniteWorld, Moving3dCubePlugin};
+use primitives::{PrimitivesPlugin, SpawnQueue, SpawningMode};
+use sample_common::AppState;
+
+/// 計測するシナリオ
+pub struct Scenario {
+    pub name: &'static str,        // シナリオ名(`--scenario`で指定する)
+    pub demo: &'static str,        // 計測対象のサンプルのパッケージ名(アセットの読み込み元)
+    pub description: &'static str, // レポートに載せる説明
+    pub setup: fn(&mut App),       // サンプルのプラグインと負荷の設定を追加する
+}
+
+/// すべてのシナリオ
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "primitives_50k_points",
+        demo: "primitives",
+        description: "Sample 50,000 points on the primitive shapes and keep them alive.",
+        setup: |app| {
+            app.add_plugins(PrimitivesPlugin)
+                .add_systems(Update, queue_points.run_if(run_once));
+        },
+    },
+    Scenario {
+        name: "moving_3d_cube_chunks",
+        demo: "moving_3d_cube",
+        description: "Stream 121 chunks (render distance 5) while the player crosses a chunk every 10 frames.",
+        setup: |app| {
+            app.add_plugins(Moving3dCubePlugin)
+                .insert_resource(InfiniteWorld {
+                    chunk_size: CHUNK_SIZE,
+                    render_distance: 5,
+                })
+                .add_systems(Update, walk_player);
+        },
+    },
+    Scenario {
+        name: "examina_clone_20_enemies",
+        demo: "examina_clone",
+        description: "20 enemies patrolling and checking their field of view every frame.",
+        setup: |app| {
+            app.add_plugins(ExaminaClonePlugin)
+                .add_systems(Startup, (spawn_enemies, skip_menu))
+                .add_systems(Update, hide_player);
+        },
+    },
+];
+
+/// `primitives`で生成するポイントの数
+const POINT_COUNT: usize = 50_000;
+
+/// `moving_3d_cube`のチャンクのサイズ
+const CHUNK_SIZE: f32 = 20.0;
+
+/// `examina_clone`の敵の数(サンプルが配置する1体を含む)
+const ENEMY_COUNT: usize = 20;
+
+/// ポイントの生成を予約し、自動削除を止めるシステム
+/// (サンプルの`Startup`で初期化されたリソースを上書きするので、最初のフレームで実行する)
+fn queue_points(mut commands: Commands) {
+    commands.insert_resource(SpawnQueue(POINT_COUNT));
+    commands.insert_resource(SpawningMode::Manual); // 手動モードでは上限を超えても削除されない
+}
+
+/// プレイヤーを10フレームで1チャンク分ずつ移動させるシステム
+fn walk_player(mut players: Query<&mut Transform, With<moving_3d_cube::Player>>) {
+    for mut transform in players.iter_mut() {
+        transform.translation.x += CHUNK_SIZE / 10.0;
+    }
+}
+
+/// 巡回する敵を追加するシステム(5列に並べ、それぞれ四角形の経路を回る)
+fn spawn_enemies(mut commands: Commands) {
+    for index in 1..ENEMY_COUNT {
+        let center = Vec3::new(
+            (index % 5) as f32 * 12.0 - 24.0,
+            1.0,
+            (index / 5) as f32 * 12.0 - 18.0,
+        );
+        let patrol_points: Vec<Waypoint> = [(3.0, 3.0), (-3.0, 3.0), (-3.0, -3.0), (3.0, -3.0)]
+            .into_iter()
+            .map(|(x, z)| Waypoint::new(center + Vec3::new(x, 0.0, z)))
+            .collect();
+        let initial_position = patrol_points[0].position;
+        commands.spawn((
+            Enemy {
+                vision_range: 10.0,
+                vision_angle: 45.0,
+                patrol_points,
+                current_patrol_index: 0,
+                speed: 4.0,
+                initial_position,
+                initial_rotation: Quat::IDENTITY,
+            },
+            Transform::from_translation(initial_position),
+        ));
+    }
+}
+
+/// メニューを飛ばしてすぐにプレイを始めるシステム
+fn skip_menu(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Loading);
+}
+
+/// 敵に見つからないように、プレイヤーを敵の視界の外へ移動させるシステム
+fn hide_player(mut players: Query<&mut Transform, Added<examina_clone::Player>>) {
+    for mut transform in players.iter_mut() {
+        transform.translation = Vec3::new(0.0, 1.0, 200.0);
+    }
+}
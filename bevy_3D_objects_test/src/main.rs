@@ -3,11 +3,29 @@ use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_trenchbroom::class::builtin::*;
 use bevy_trenchbroom::prelude::*;
+use sample_common::{
+    ActionAxes, ActionMap, ActionsPlugin, AppState, AppStatePlugin, LoadingAssets, Locale,
+    LocalizationPlugin, PostFxPlugin, PostFxSettings, ScreenshotPlugin, TimeControlPlugin,
+};
 
-fn main() {
+fn main() -> AppExit {
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(sample_common::headless::default_plugins()) // --headless時はウィンドウなし
         .add_plugins(PhysicsPlugins::default())
+        .add_plugins(ScreenshotPlugin::new("bevy_3D_objects_test")) // F12でスクリーンショット
+        .add_plugins(ActionsPlugin::new(action_map())) // キーボードとゲームパッドの操作
+        .add_plugins(TimeControlPlugin) // Pで一時停止、-/=でスローモーション
+        .add_plugins(AppStatePlugin::new("title")) // メニュー・読み込み中・一時停止の画面
+        .add_plugins(
+            LocalizationPlugin::new()
+                .bundle(Locale::En, include_str!("../assets/locale/en.ron"))
+                .bundle(Locale::Ja, include_str!("../assets/locale/ja.ron")),
+        ) // 画面の文章の英語・日本語(F2で切り替え)
+        .add_plugins(PostFxPlugin) // トーンマッピングやMSAA(F3で設定パネル)
+        .insert_resource(PostFxSettings {
+            bloom_intensity: 0.0, // ブルームは最初は無効([ / ]で調整できる)
+            ..default()
+        })
         .add_plugins(
             TrenchBroomPlugins(
                 TrenchBroomConfig::new("bevy_3D_objects_test")
@@ -21,7 +39,7 @@ fn main() {
         .add_systems(
             Update,
             (
-                player_movement,
+                player_movement.run_if(in_state(AppState::Playing)), // メニューや一時停止中は操作を受け付けない
                 debug_loaded_entities,
                 debug_scene_loading,
                 debug_info_player_start,
@@ -29,7 +47,7 @@ fn main() {
                 camera_follow_player,
             ),
         )
-        .run();
+        .run()
 }
 
 // プレイヤーのスポーンポイントを定義
@@ -67,14 +85,36 @@ fn debug_info_player_start(query: Query<(&InfoPlayerStart, &Transform), Added<In
 #[derive(Component)]
 struct Player;
 
+/// プレイヤーの操作(WASDと十字キー・左スティックに割り当てる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PlayerAction {
+    Forward,
+    Back,
+    TurnLeft,
+    TurnRight,
+}
+
+fn action_map() -> ActionMap<PlayerAction> {
+    ActionMap::new()
+        .key(PlayerAction::Forward, KeyCode::KeyW)
+        .key(PlayerAction::Back, KeyCode::KeyS)
+        .key(PlayerAction::TurnLeft, KeyCode::KeyA)
+        .key(PlayerAction::TurnRight, KeyCode::KeyD)
+        .movement(
+            PlayerAction::Forward,
+            PlayerAction::Back,
+            PlayerAction::TurnLeft,
+            PlayerAction::TurnRight,
+        )
+}
+
 #[derive(Component)]
 struct MainCamera;
 
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut loading: ResMut<LoadingAssets>,
 ) {
     commands.spawn((AmbientLight {
         color: Color::WHITE,
@@ -82,8 +122,10 @@ fn setup(
         affects_lightmapped_meshes: false,
     },));
 
-    // 後でマップをロードする
-    commands.spawn(SceneRoot(asset_server.load("maps/complete_map.map#Scene")));
+    // マップを読み込む(読み込み終わるまではロード画面を表示する)
+    let map: Handle<Scene> = asset_server.load("maps/complete_map.map#Scene");
+    loading.add(map.clone());
+    commands.spawn(SceneRoot(map));
 
     // カメラを俯瞰位置に追加
     commands.spawn((
@@ -117,7 +159,7 @@ fn setup(
 
 /// プレイヤーの動きとカメラの追従を制御
 fn player_movement(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    axes: Res<ActionAxes>,
     mut query: Query<(&mut LinearVelocity, &mut Transform), With<Player>>,
 		time: Res<Time>,
 ) {
@@ -128,30 +170,25 @@ fn player_movement(
     let move_speed = 5.0;
     let rotate_speed = 2.0;
 
-    // 移動（前進W・後退S）
-    let mut move_direction = 0.0;
-    if keyboard_input.pressed(KeyCode::KeyW) { move_direction += 1.0; }
-    if keyboard_input.pressed(KeyCode::KeyS) { move_direction -= 1.0; }
+    // 移動（前進W・後退S、スティックの上下）
+    let move_direction = axes.movement.y;
 
     let forward = player_transform.forward();
     linear_velocity.0 = forward * move_speed * move_direction;
 
-    // 左右回転（A/D）
-    if keyboard_input.pressed(KeyCode::KeyA) {
-        player_transform.rotate_y(rotate_speed * time.delta_secs());
-    }
-    if keyboard_input.pressed(KeyCode::KeyD) {
-        player_transform.rotate_y(-rotate_speed * time.delta_secs());
+    // 左右回転（A/D、スティックの左右）
+    if axes.movement.x != 0.0 {
+        player_transform.rotate_y(-axes.movement.x * rotate_speed * time.delta_secs());
     }
     println!("プレイヤーの位置: {:?}", player_transform.translation);
 }
 
 fn camera_follow_player(
-    player_query: Query<(&Transform, &LinearVelocity), (With<Player>, Without<MainCamera>)>,
+    player_query: Query<&Transform, (With<Player>, Without<MainCamera>)>,
     mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<Player>)>,
     time: Res<Time>,
 ) {
-    let Ok((player_transform, player_velocity)) = player_query.single() else {
+    let Ok(player_transform) = player_query.single() else {
         return;
     };
     let Ok(mut camera_transform) = camera_query.single_mut() else {
@@ -1,16 +1,52 @@
 use bevy::prelude::*;
+use sample_common::snapshot::save_path;
+use sample_common::{ScreenshotPlugin, Snapshot};
 
-fn main() {
+/// 配置したオブジェクトを保存するファイル名
+const SAVE_NAME: &str = "placed_objects";
+
+fn main() -> AppExit {
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(sample_common::headless::default_plugins()) // --headless時はウィンドウなし
+        .add_plugins(ScreenshotPlugin::new("camera_3d_viewport")) // F12でスクリーンショット
+				.register_type::<PlacedObject>() // 保存・復元のために型を登録
+				.add_observer(add_placed_object_visuals) // 配置・復元されたオブジェクトに見た目を付ける
 				.add_systems(Startup, setup)
-				.add_systems(Update, draw_cursor)
-        .run();
+				.add_systems(Update, (draw_cursor, place_object, save_and_load))
+        .run()
+}
+
+/// オブジェクトを配置できる面の最大の傾き(度)
+const MAX_PLACEMENT_SLOPE: f32 = 30.0;
+
+/// カーソルが指している面上の点と、その面の法線
+struct SurfaceHit {
+	point: Vec3,
+	normal: Vec3,
+}
+
+impl SurfaceHit {
+	/// 面の傾き(水平からの角度、度)
+	fn slope(&self) -> f32 {
+		self.normal.angle_between(Vec3::Y).to_degrees()
+	}
+
+	/// オブジェクトを配置できる傾きかどうか
+	fn is_placeable(&self) -> bool {
+		self.slope() <= MAX_PLACEMENT_SLOPE
+	}
+
+	/// カーソルの色(平らなほど緑、配置できる上限に近いほど赤、配置できない面では赤)
+	fn marker_color(&self) -> Color {
+		let ratio = (self.slope() / MAX_PLACEMENT_SLOPE).min(1.0);
+		Srgba::GREEN.mix(&Srgba::RED, ratio).into()
+	}
 }
 
 fn draw_cursor(
 	camera_query: Single<(&Camera, &GlobalTransform)>,
-	ground: Single<&GlobalTransform, With<Ground>>,
+	mut ray_cast: MeshRayCast,
+	grounds: Query<(), With<Ground>>,
 	windows: Query<&Window>, // window情報
 	mut gizmos: Gizmos,
 ) {
@@ -23,44 +59,137 @@ fn draw_cursor(
 	// 値にアクセスするためにデリファレンスを使用
 	let (camera, camera_transform) = *camera_query;
 
-	// カーソルがウィンドウにない場合は何もしない
-	// Someはバリアントで、値が存在する場合にのみ処理を続ける
-	let Some(cursor_position) = windows.cursor_position() else {
+	// カーソルが地面を指していない場合は何もしない
+	let Some(hit) = cursor_to_surface(camera, camera_transform, windows, &mut ray_cast, &grounds)
+	else {
 		return;
 	};
 
+	// Gizmosを使用してカーソル位置に円を描画
+	// 求めた交点座標のわずか上に円を描く
+	let rotation = Quat::from_rotation_arc(Vec3::Z, hit.normal); // 円が面の法線方向を向くよう回転
+	let color = hit.marker_color();
+	gizmos.circle(Isometry3d::new(hit.point + hit.normal * 0.01, rotation), 0.2, color);
+
+	// 配置できない面では円の中に×印を描く
+	if !hit.is_placeable() {
+		let center = hit.point + hit.normal * 0.01;
+		for corner in [Vec3::new(1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0)] {
+			let offset = rotation * corner * 0.14;
+			gizmos.line(center - offset, center + offset, color);
+		}
+	}
+}
+
+/// カーソルが指している地面(`Ground`の付いたメッシュ)上の点と法線を求める
+fn cursor_to_surface(
+	camera: &Camera,
+	camera_transform: &GlobalTransform,
+	window: &Window,
+	ray_cast: &mut MeshRayCast,
+	grounds: &Query<(), With<Ground>>,
+) -> Option<SurfaceHit> {
+	// カーソルがウィンドウにない場合は何もしない
+	// Someはバリアントで、値が存在する場合にのみ処理を続ける
+	let cursor_position = window.cursor_position()?;
+
 	// 矢印のポイントを基準にカーソル位置をワールド座標に変換
 	// 光線を生成
-	let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+	let ray = camera.viewport_to_world(camera_transform, cursor_position).ok()?;
+
+	// 光線と最初に交差する地面のメッシュを求める(配置したオブジェクトなどは無視する)
+	let filter = |entity| grounds.contains(entity);
+	let settings = MeshRayCastSettings::default().with_filter(&filter);
+	let (_, hit) = ray_cast.cast_ray(ray, &settings).first()?;
+
+	Some(SurfaceHit {
+		point: hit.point,
+		normal: hit.normal.normalize_or(Vec3::Y),
+	})
+}
+
+/// カーソルで指せる地面(平面と坂)
+# [derive(Component)]
+struct Ground;
+
+/// クリックで地面に配置したオブジェクト(保存の対象)
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+struct PlacedObject;
+
+/// 配置するオブジェクトのメッシュとマテリアル
+#[derive(Resource)]
+struct PlacedObjectAssets {
+	mesh: Handle<Mesh>,
+	material: Handle<StandardMaterial>,
+}
+
+/// 配置したオブジェクトの保存内容(位置だけを保存する)
+fn placed_objects_snapshot() -> Snapshot {
+	Snapshot::new().allow::<Transform>()
+}
+
+/// 左クリックでカーソル位置にオブジェクトを配置するシステム
+fn place_object(
+	mut commands: Commands,
+	mouse: Res<ButtonInput<MouseButton>>,
+	camera_query: Single<(&Camera, &GlobalTransform)>,
+	mut ray_cast: MeshRayCast,
+	grounds: Query<(), With<Ground>>,
+	windows: Query<&Window>,
+) {
+	if !mouse.just_pressed(MouseButton::Left) {
+		return;
+	}
+	let Ok(window) = windows.single() else {
 		return;
 	};
-
-	// 光線と地面が交差する距離を計算する
-	let Some(distance) = ray.intersect_plane(ground.translation(), InfinitePlane3d::new(ground.up())) else {
+	let (camera, camera_transform) = *camera_query;
+	let Some(hit) = cursor_to_surface(camera, camera_transform, window, &mut ray_cast, &grounds)
+	else {
 		return;
 	};
 
-	let point = ray.get_point(distance);
+	// 傾きが急すぎる面には配置しない
+	if !hit.is_placeable() {
+		info!("傾きが{:.0}度の面には配置できません(上限は{MAX_PLACEMENT_SLOPE}度)", hit.slope());
+		return;
+	}
 
-	// Gizmosを使用してカーソル位置に円を描画
-	// 求めた交点座標のわずか上に円を描く
-	gizmos.circle(
-		Isometry3d::new(point + ground.up() * 0.01,
-		// デフォルトのZ軸方向に円を描くところを、地面の法線方向に円が向くよう回転
-		Quat::from_rotation_arc(Vec3::Z, ground.up().as_vec3()),
-	),
-	0.2,
-	Color::WHITE,
-	);
+	// 見た目はオブザーバーで付けるので、ここでは位置と向きだけを決める
+	commands.spawn((
+		PlacedObject,
+		Transform::from_translation(hit.point + hit.normal * 0.25) // 地面に埋まらないよう少し上げる
+			.with_rotation(Quat::from_rotation_arc(Vec3::Y, hit.normal)), // 面に沿って置く
+	));
 }
 
-# [derive(Component)]
-struct Ground;
+/// 配置・復元されたオブジェクトにメッシュとマテリアルを付けるオブザーバー
+fn add_placed_object_visuals(
+	trigger: Trigger<OnAdd, PlacedObject>,
+	mut commands: Commands,
+	assets: Res<PlacedObjectAssets>,
+) {
+	commands.entity(trigger.target()).insert((
+		Mesh3d(assets.mesh.clone()),
+		MeshMaterial3d(assets.material.clone()),
+	));
+}
+
+/// F5で配置したオブジェクトを保存し、F9で読み込むシステム
+fn save_and_load(mut commands: Commands, keyboard: Res<ButtonInput<KeyCode>>) {
+	let path = save_path("camera_3d_viewport", SAVE_NAME);
+	if keyboard.just_pressed(KeyCode::F5) {
+		commands.queue(placed_objects_snapshot().save_command::<PlacedObject>(path));
+	} else if keyboard.just_pressed(KeyCode::F9) {
+		commands.queue(placed_objects_snapshot().load_command::<PlacedObject>(path));
+	}
+}
 
 fn setup(
 	mut commands: Commands,
 	mut meshes: ResMut<Assets<Mesh>>,
-	mut materials: RetMut<Assets<StandardMaterial>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
 	commands.spawn((
 		// デフォルトの平面メッシュを使用して地面を作成
@@ -69,11 +198,31 @@ fn setup(
 
 		// 地面のマテリアルを設定
 		// 色を設定するためにStandardMaterialを使用
-		StandardMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+		MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
 		// Groundコンポーネントを追加して識別
 		Ground,
 	));
 
+	// 傾きの違う坂を置く(緩やかな坂には配置でき、急な坂には配置できない)
+	let ground_material = materials.add(Color::srgb(0.3, 0.5, 0.3));
+	let ramp_mesh = meshes.add(Cuboid::new(4.0, 0.2, 6.0));
+	for (x, angle) in [(-5.0, 15.0_f32), (5.0, 45.0_f32)] {
+		let rotation = Quat::from_rotation_x(angle.to_radians());
+		commands.spawn((
+			Mesh3d(ramp_mesh.clone()),
+			MeshMaterial3d(ground_material.clone()),
+			// 坂の低い側の端が地面に付くように持ち上げる
+			Transform::from_xyz(x, 3.0 * angle.to_radians().sin(), -4.0).with_rotation(rotation),
+			Ground,
+		));
+	}
+
+	// 配置するオブジェクトは同じメッシュとマテリアルを使い回す
+	commands.insert_resource(PlacedObjectAssets {
+		mesh: meshes.add(Cuboid::from_length(0.5)),
+		material: materials.add(Color::srgb(0.8, 0.6, 0.2)),
+	});
+
 	// ライトを追加
 	commands.spawn((
 		// デフォルトの方向ライトを使用
@@ -0,0 +1,430 @@
+//! 警報
+//!
+//! 倒れている敵が見つかったり、プレイヤーがレベルの仕掛け線(`Tripwire`)に触れたり、
+//! 監視カメラ(`SecurityCamera`)に映ったりすると警報が出る。ほかの仕掛けからは、`RaiseAlarm`を送って警報を出す。
+//! 警報が出ている間は、起きている敵はみな速く動き、視界が広がり、プレイヤーが最後に確認された場所へ集まる。
+//! 設定の時間が経つと、最後の`alarm_decay_time`秒で少しずつ元に戻って解ける。
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use sample_common::{AppState, LocalizedText};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AlertState, BodyDiscovered, DebugLog, Enemy, GameState, Hiding, Investigating, KnockedOut,
+    LevelEntity, LevelLayout, PLAYER_RADIUS, Player, StealthConfig, discover_bodies,
+    enemy_vision_system, line_of_sight, vision_cone_mesh,
+};
+
+/// 仕掛け線を張る高さ(足首の高さ)
+const TRIPWIRE_HEIGHT: f32 = 0.15;
+
+/// 監視カメラが左右に首を振って元に戻るまでの時間(秒)
+const CAMERA_SWEEP_TIME: f32 = 8.0;
+
+/// 監視カメラの視界の扇形を描く高さ(地面の少し上)
+const CAMERA_CONE_HEIGHT: f32 = 0.05;
+
+/// 警報のプラグイン
+pub(crate) struct AlarmPlugin;
+
+impl Plugin for AlarmPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AlarmLevel>()
+            .add_event::<RaiseAlarm>()
+            .add_systems(Startup, spawn_alarm_text)
+            .add_systems(
+                Update,
+                (
+                    reset_alarm,
+                    trip_wires,
+                    watch_cameras,
+                    raise_alarm.after(discover_bodies),
+                    converge_on_alarm.after(enemy_vision_system), // 見つけた場所へ、次のフレームから向かう
+                    decay_alarm,
+                    update_alarm_text,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// 警報の状態
+/// 出ている間は、敵の速さと視界に倍率を掛け、敵を`last_known`へ向かわせる
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct AlarmLevel {
+    pub remaining: f32,           // 警報が解けるまでの時間(秒、0なら出ていない)
+    pub last_known: Option<Vec3>, // プレイヤーが最後に確認された場所(敵が集まる)
+}
+
+impl AlarmLevel {
+    /// 警報が出ているか
+    pub fn is_raised(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    /// `position`を最後に確認された場所として、警報を出す(出ていれば時間を戻す)
+    pub fn raise(&mut self, position: Vec3, config: &StealthConfig) {
+        self.remaining = config.alarm_time;
+        self.last_known = Some(position);
+    }
+
+    /// 警報の強さ(0〜1)
+    /// 解ける前の`alarm_decay_time`秒で、1から0へ下がっていく
+    pub fn intensity(&self, config: &StealthConfig) -> f32 {
+        if !self.is_raised() {
+            0.0
+        } else if config.alarm_decay_time <= 0.0 {
+            1.0
+        } else {
+            (self.remaining / config.alarm_decay_time).min(1.0)
+        }
+    }
+
+    /// 敵の巡回と調べに行くときの速度の倍率
+    pub fn speed_ratio(&self, config: &StealthConfig) -> f32 {
+        1.0 + (config.alarm_speed_ratio - 1.0) * self.intensity(config)
+    }
+
+    /// 敵の視界範囲と視界角度の倍率
+    pub fn vision_ratio(&self, config: &StealthConfig) -> f32 {
+        1.0 + (config.alarm_vision_ratio - 1.0) * self.intensity(config)
+    }
+}
+
+/// 警報を出すイベント(`position`は、プレイヤーを確認した、または異常のあった場所)
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct RaiseAlarm {
+    pub position: Vec3,
+}
+
+/// レベルに張る仕掛け線(地面に沿って`from`から`to`まで、足首の高さに張る。高さの値は使わない)
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TripwirePlace {
+    pub from: Vec3, // 線の一方の端
+    pub to: Vec3,   // 線のもう一方の端
+}
+
+/// 仕掛け線のコンポーネント
+/// プレイヤーが触れると警報を出し、切れる(レベルを配置し直すまで、もう鳴らない)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Tripwire {
+    pub from: Vec3,    // 線の一方の端
+    pub to: Vec3,      // 線のもう一方の端
+    pub tripped: bool, // もう切れたか
+}
+
+impl Tripwire {
+    pub fn new(place: &TripwirePlace) -> Self {
+        Self {
+            from: place.from,
+            to: place.to,
+            tripped: false,
+        }
+    }
+
+    /// `point`にいるプレイヤーの体が線に触れているか(地面に沿った距離で調べる)
+    pub fn touches(&self, point: Vec3) -> bool {
+        let (from, to, point) = (self.from.xz(), self.to.xz(), point.xz());
+        let along = to - from;
+        let t = if along.length_squared() > 0.0 {
+            ((point - from).dot(along) / along.length_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        point.distance(from + along * t) <= PLAYER_RADIUS
+    }
+}
+
+/// 仕掛け線を張る(細い赤い線で、通り抜けられる)
+pub(crate) fn spawn_tripwire(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    place: &TripwirePlace,
+) {
+    let along = (place.to - place.from).with_y(0.0);
+    let center = (place.from + place.to) / 2.0;
+    commands.spawn((
+        Tripwire::new(place),
+        Mesh3d(meshes.add(Cuboid::new(along.length(), 0.02, 0.02))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.1, 0.1),
+            emissive: LinearRgba::rgb(1.5, 0.1, 0.1), // 暗い時間帯でも、よく見れば分かるように
+            ..default()
+        })),
+        Transform::from_xyz(center.x, TRIPWIRE_HEIGHT, center.z)
+            .with_rotation(Quat::from_rotation_y((-along.z).atan2(along.x))),
+        LevelEntity,
+    ));
+}
+
+/// レベルに置く監視カメラ(`facing`の向きを中心に、左右に`sweep`度ずつ首を振る)
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct SecurityCameraPlace {
+    pub position: Vec3, // カメラの位置(壁の高いところ)
+    pub facing: Vec3,   // 首振りの中心の向き(地面に沿った向き。高さの値は使わない)
+    pub range: f32,     // 見える距離(地面に沿った距離)
+    pub angle: f32,     // 視界角度(度)
+    #[serde(default)]
+    pub sweep: f32, // 中心から左右に首を振る角度(度、0なら動かない)
+}
+
+/// 監視カメラのコンポーネント
+/// 視界に映ったプレイヤーに遮るものがなければ、映っている間ずっと警報を出し続ける(敵に居場所を伝える)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct SecurityCamera {
+    pub position: Vec3, // カメラの位置
+    pub facing: Vec3,   // 首振りの中心の向き(長さ1の水平な向き)
+    pub range: f32,     // 見える距離(地面に沿った距離)
+    pub angle: f32,     // 視界角度(度)
+    pub sweep: f32,     // 中心から左右に首を振る角度(度)
+    pub time: f32,      // 首を振り始めてからの時間(秒)
+    pub spotted: bool,  // 今プレイヤーが映っているか
+}
+
+impl SecurityCamera {
+    pub fn new(place: &SecurityCameraPlace) -> Self {
+        Self {
+            position: place.position,
+            facing: place.facing.with_y(0.0).normalize_or(Vec3::NEG_Z),
+            range: place.range,
+            angle: place.angle,
+            sweep: place.sweep,
+            time: 0.0,
+            spotted: false,
+        }
+    }
+
+    /// 今向いている水平の向き(`CAMERA_SWEEP_TIME`秒で左右に1往復する)
+    pub fn heading(&self) -> Vec3 {
+        let swing = (self.time * TAU / CAMERA_SWEEP_TIME).sin() * self.sweep.to_radians();
+        Quat::from_rotation_y(swing) * self.facing
+    }
+
+    /// `point`が視界の範囲と視界角の中にあるか(地面に沿って調べ、遮るものは調べない)
+    pub fn sees(&self, point: Vec3) -> bool {
+        let to_point = (point - self.position).with_y(0.0);
+        to_point.length() <= self.range
+            && self.heading().angle_between(to_point).to_degrees() < self.angle / 2.0
+    }
+}
+
+/// 監視カメラを置く(壁に付けた黒い箱で、地面に視界の扇形を描く)
+pub(crate) fn spawn_security_camera(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    place: &SecurityCameraPlace,
+) {
+    let camera = SecurityCamera::new(place);
+    commands
+        .spawn((
+            camera,
+            Mesh3d(meshes.add(Cuboid::new(0.25, 0.2, 0.4))),
+            MeshMaterial3d(materials.add(Color::srgb(0.15, 0.15, 0.18))),
+            Transform::from_translation(place.position).with_rotation(camera_rotation(&camera)),
+            LevelEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Mesh3d(meshes.add(vision_cone_mesh(place.range, place.angle))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: AlertState::Suspicious.cone_color(),
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true, // 暗い時間帯でも見えるようにする
+                    double_sided: true,
+                    cull_mode: None, // 裏側からも見えるようにする
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, CAMERA_CONE_HEIGHT - place.position.y, 0.0),
+                bevy::pbr::NotShadowCaster, // 地面に影を落とさない
+            ));
+        });
+}
+
+/// 監視カメラの今の向きを前方(-Z方向)にする、Y軸まわりの回転
+fn camera_rotation(camera: &SecurityCamera) -> Quat {
+    let heading = camera.heading();
+    Quat::from_rotation_y(f32::atan2(-heading.x, -heading.z))
+}
+
+/// 警報の表示のマーカーコンポーネント
+#[derive(Component)]
+struct AlarmText;
+
+/// `RaiseAlarm`と、倒れている敵が見つかったときに警報を出すシステム
+pub fn raise_alarm(
+    config: Res<StealthConfig>,
+    mut alarm: ResMut<AlarmLevel>,
+    mut raises: EventReader<RaiseAlarm>,
+    mut bodies: EventReader<BodyDiscovered>,
+    mut log: ResMut<DebugLog>,
+) {
+    let positions = raises
+        .read()
+        .map(|raise| raise.position)
+        .chain(bodies.read().map(|body| body.position));
+    for position in positions {
+        if !alarm.is_raised() {
+            log.push("Alarm raised!");
+        }
+        alarm.raise(position, &config);
+    }
+}
+
+/// プレイヤーがまだ切れていない仕掛け線に触れたら、その場所で警報を出すシステム
+pub fn trip_wires(
+    game_state: Res<GameState>,
+    players: Query<&Transform, With<Player>>,
+    mut wires: Query<&mut Tripwire>,
+    mut raises: EventWriter<RaiseAlarm>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let Ok(player_transform) = players.single() else {
+        return;
+    };
+    let position = player_transform.translation;
+    for mut wire in &mut wires {
+        if wire.tripped || !wire.touches(position) {
+            continue;
+        }
+        wire.tripped = true;
+        raises.write(RaiseAlarm { position });
+        log.push("Tripped a wire");
+    }
+}
+
+/// 監視カメラの首を振り、視界に映ったプレイヤーに遮るものがなければ、その場所で警報を出すシステム
+/// 隠れ場所でしゃがんでいるプレイヤーは映らない
+pub fn watch_cameras(
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    players: Query<(Entity, &Transform, Option<&Hiding>), With<Player>>,
+    mut cameras: Query<(&mut SecurityCamera, &mut Transform), Without<Player>>,
+    rapier_context: ReadRapierContext,
+    mut raises: EventWriter<RaiseAlarm>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let player = players.single().ok();
+    let rapier_context = rapier_context.single().ok();
+    for (mut camera, mut transform) in &mut cameras {
+        camera.time += time.delta_secs();
+        transform.rotation = camera_rotation(&camera);
+
+        let spotted = player.is_some_and(|(player, player_transform, hiding)| {
+            !hiding.is_some_and(|hiding| hiding.crouched)
+                && camera.sees(player_transform.translation)
+                && line_of_sight(
+                    rapier_context.as_ref(),
+                    camera.position,
+                    None,
+                    player,
+                    player_transform,
+                )
+        });
+        if spotted && !camera.spotted {
+            log.push("Spotted by a camera");
+        }
+        camera.spotted = spotted;
+        if let Some((_, player_transform, _)) = player.filter(|_| spotted) {
+            raises.write(RaiseAlarm {
+                position: player_transform.translation,
+            });
+        }
+    }
+}
+
+/// 警報が出ている間、最後に確認された場所が変わるたびに、起きている敵をみなそこへ向かわせるシステム
+/// 着いた敵は、警報が解けるまで辺りを調べる
+pub fn converge_on_alarm(
+    mut commands: Commands,
+    alarm: Res<AlarmLevel>,
+    game_state: Res<GameState>,
+    guards: Query<(Entity, &Transform), (With<Enemy>, Without<KnockedOut>)>,
+    mut sent: Local<Option<Vec3>>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let target = alarm.last_known.filter(|_| alarm.is_raised());
+    if *sent == target {
+        return;
+    }
+    *sent = target;
+    let Some(target) = target else {
+        return;
+    };
+    for (guard, transform) in &guards {
+        commands.entity(guard).insert(Investigating {
+            target: target.with_y(transform.translation.y),
+            remaining: alarm.remaining,
+        });
+    }
+}
+
+/// 警報の残り時間を減らし、0になったら解くシステム
+pub fn decay_alarm(
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut alarm: ResMut<AlarmLevel>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing || !alarm.is_raised() {
+        return;
+    }
+    alarm.remaining = (alarm.remaining - time.delta_secs()).max(0.0);
+    if !alarm.is_raised() {
+        alarm.last_known = None;
+        log.push("Alarm cleared");
+    }
+}
+
+/// やり直したり、チェックポイントから再開したり、レベルが変わったりしたら警報を解くシステム
+fn reset_alarm(game_state: Res<GameState>, level: Res<LevelLayout>, mut alarm: ResMut<AlarmLevel>) {
+    let restarted = game_state.is_changed() && *game_state == GameState::Playing;
+    if restarted || level.is_changed() {
+        alarm.set_if_neq(AlarmLevel::default());
+    }
+}
+
+/// 警報の表示を作る(警報が出ている間だけ表示する)
+fn spawn_alarm_text(mut commands: Commands) {
+    commands.spawn((
+        LocalizedText::new("alarm"),
+        TextColor(Color::srgb(1.0, 0.2, 0.1)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(96.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        AlarmText,
+    ));
+}
+
+/// 警報が出ている間、残り時間を表示するシステム
+fn update_alarm_text(
+    alarm: Res<AlarmLevel>,
+    mut texts: Query<(&mut LocalizedText, &mut Visibility), With<AlarmText>>,
+) {
+    for (mut text, mut visibility) in &mut texts {
+        if !alarm.is_raised() {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let seconds = alarm.remaining.ceil() as u32;
+        text.set_if_neq(LocalizedText::new("alarm").with_arg("time", seconds));
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
@@ -0,0 +1,183 @@
+//! 周りの音(ラジオや機械)
+//!
+//! レベルに置いた音源は、音を繰り返し流し続ける(カメラからの距離と向きで、音量と左右の聞こえ方が変わる)。
+//! 音源の`radius`以内でプレイヤーが立てた音(足音、着地の音、石が落ちた音)は、
+//! 聞こえる半径が`noise_masking_ratio`倍に狭まり、敵に気づかれにくくなる。
+
+use bevy::prelude::*;
+use bevy_kira_audio::{
+    Audio, AudioControl, AudioInstance, AudioTween, PlaybackState, SpatialAudioEmitter,
+    SpatialAudioPlugin, SpatialRadius,
+};
+use bevy_rapier3d::prelude::*;
+use sample_common::AppState;
+use serde::{Deserialize, Serialize};
+
+use crate::{GameSounds, LevelEntity, NoiseEvent, StealthConfig};
+
+/// 音源の音がカメラに聞こえる距離(音をかき消す範囲の半径に掛ける)
+const AUDIBLE_RATIO: f32 = 2.5;
+
+/// 周りの音のプラグイン
+pub(crate) struct AmbientNoisePlugin;
+
+impl Plugin for AmbientNoisePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SpatialAudioPlugin) // カメラ(`SpatialAudioReceiver`)からの位置で音量と左右を変える
+            .add_observer(start_ambient_sound)
+            .add_observer(stop_ambient_sound)
+            .add_systems(Update, pause_ambient_sounds);
+    }
+}
+
+/// 音源の種類(流す音と見た目が変わる)
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NoiseKind {
+    Radio,     // ラジオ(雑音と途切れ途切れの声)
+    Machinery, // 機械(低いうなりと規則的な打音)
+}
+
+impl NoiseKind {
+    /// 置物の大きさ(各辺の長さ)
+    fn size(self) -> Vec3 {
+        match self {
+            NoiseKind::Radio => Vec3::new(0.5, 0.3, 0.25),
+            NoiseKind::Machinery => Vec3::new(1.5, 1.2, 1.5),
+        }
+    }
+
+    /// 置物の色
+    fn color(self) -> Color {
+        match self {
+            NoiseKind::Radio => Color::srgb(0.15, 0.15, 0.2), // 黒っぽいラジオ
+            NoiseKind::Machinery => Color::srgb(0.35, 0.4, 0.3), // 緑がかった灰色の機械
+        }
+    }
+}
+
+/// レベルに置く、周りの音を立てる音源
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct NoiseSourcePlace {
+    pub kind: NoiseKind, // 音源の種類
+    pub position: Vec3,  // 置物の中心の位置
+    pub radius: f32,     // プレイヤーの音をかき消す範囲の半径(地面に沿った距離)
+}
+
+/// 周りの音を立てる音源のコンポーネント
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AmbientNoise {
+    pub kind: NoiseKind, // 音源の種類
+    pub radius: f32,     // プレイヤーの音をかき消す範囲の半径
+}
+
+impl AmbientNoise {
+    /// `point`で立てた音をかき消すか(地面に沿った距離で比べる)
+    pub fn masks(&self, transform: &Transform, point: Vec3) -> bool {
+        (point - transform.translation).xz().length() <= self.radius
+    }
+}
+
+/// 敵に`noise`が聞こえる半径
+/// どれかの音源のかき消す範囲の中で立てた音は、`noise_masking_ratio`倍にする(範囲が重なっていても1回だけ掛ける)
+pub fn masked_noise_radius<'a>(
+    noise: &NoiseEvent,
+    sources: impl IntoIterator<Item = (&'a Transform, &'a AmbientNoise)>,
+    config: &StealthConfig,
+) -> f32 {
+    let masked = sources
+        .into_iter()
+        .any(|(transform, source)| source.masks(transform, noise.position));
+    if masked {
+        noise.radius * config.noise_masking_ratio
+    } else {
+        noise.radius
+    }
+}
+
+/// 音源を置く(通り抜けられず、敵の視界も遮る)
+pub(crate) fn spawn_noise_source(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    place: &NoiseSourcePlace,
+) {
+    let size = place.kind.size();
+    let half_size = size / 2.0;
+    commands.spawn((
+        AmbientNoise {
+            kind: place.kind,
+            radius: place.radius,
+        },
+        RigidBody::Fixed,
+        Collider::cuboid(half_size.x, half_size.y, half_size.z),
+        Mesh3d(meshes.add(Cuboid::from_size(size))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: place.kind.color(),
+            ..default()
+        })),
+        Transform::from_translation(place.position),
+        LevelEntity,
+    ));
+}
+
+/// 音源が置かれたら、その音を止めた状態で繰り返し流し始める(`pause_ambient_sounds`がゲーム中だけ鳴らす)
+fn start_ambient_sound(
+    trigger: Trigger<OnAdd, AmbientNoise>,
+    mut commands: Commands,
+    audio: Res<Audio>,
+    sounds: Res<GameSounds>,
+    sources: Query<&AmbientNoise>,
+) {
+    let entity = trigger.target();
+    let Ok(source) = sources.get(entity) else {
+        return;
+    };
+    let sound = match source.kind {
+        NoiseKind::Radio => sounds.radio.clone(),
+        NoiseKind::Machinery => sounds.machinery.clone(),
+    };
+    let instance = audio.play(sound).looped().paused().handle();
+    commands.entity(entity).insert((
+        SpatialAudioEmitter {
+            instances: vec![instance],
+        },
+        SpatialRadius {
+            radius: source.radius * AUDIBLE_RATIO,
+        },
+    ));
+}
+
+/// 音源が消えたら(レベルを配置し直したときなど)、その音を止める
+fn stop_ambient_sound(
+    trigger: Trigger<OnRemove, AmbientNoise>,
+    emitters: Query<&SpatialAudioEmitter>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+) {
+    let Ok(emitter) = emitters.get(trigger.target()) else {
+        return;
+    };
+    for handle in &emitter.instances {
+        if let Some(instance) = instances.get_mut(handle) {
+            instance.stop(AudioTween::default());
+        }
+    }
+}
+
+/// ゲーム中だけ音源の音を鳴らし、メニューや一時停止中は止めておくシステム
+fn pause_ambient_sounds(
+    app_state: Res<State<AppState>>,
+    emitters: Query<&SpatialAudioEmitter, With<AmbientNoise>>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+) {
+    let playing = *app_state.get() == AppState::Playing;
+    for handle in emitters.iter().flat_map(|emitter| &emitter.instances) {
+        let Some(instance) = instances.get_mut(handle) else {
+            continue; // まだ流し始めていない
+        };
+        match instance.state() {
+            PlaybackState::Paused { .. } if playing => instance.resume(AudioTween::default()),
+            PlaybackState::Playing { .. } if !playing => instance.pause(AudioTween::default()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! クリアの記録(レベルごとの最短タイムと最高得点)
+//!
+//! クリアするたびに記録を更新し、`saves/examina_clone/best_times.ron`に保存する。
+//! 起動時にこのファイルを読み込む。Webではファイルに読み書きできないので、記録はその回だけ残る。
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::RunStats;
+
+/// 記録を保存するファイル(実行時のカレントディレクトリからの相対パス)
+const BEST_TIMES_PATH: &str = "saves/examina_clone/best_times.ron";
+
+/// レベルごとのクリアの記録
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct BestTimes {
+    pub levels: BTreeMap<String, BestRecord>, // レベルのファイルのパスごとの記録
+}
+
+/// 1つのレベルの記録(タイムと得点は別々のプレイのものでもよい)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct BestRecord {
+    pub time: f32,  // 最短のクリアタイム(秒)
+    pub score: u32, // 最高の得点
+}
+
+impl BestTimes {
+    /// 記録を保存するファイルのパス
+    pub fn path() -> PathBuf {
+        PathBuf::from(BEST_TIMES_PATH)
+    }
+
+    /// `level`の記録(まだクリアしていなければNone)
+    pub fn get(&self, level: &str) -> Option<BestRecord> {
+        self.levels.get(level).copied()
+    }
+
+    /// `level`をクリアしたときの記録で更新する
+    /// 最短タイムか最高得点のどちらかを更新したらtrue(初めてのクリアも含む)
+    pub fn record(&mut self, level: &str, stats: &RunStats) -> bool {
+        let score = stats.score();
+        match self.levels.get_mut(level) {
+            Some(best) => {
+                let improved = stats.elapsed < best.time || score > best.score;
+                best.time = best.time.min(stats.elapsed);
+                best.score = best.score.max(score);
+                improved
+            }
+            None => {
+                self.levels.insert(
+                    level.to_owned(),
+                    BestRecord {
+                        time: stats.elapsed,
+                        score,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// `path`から記録を読み込む(ファイルがないときや読めないときは、記録なし)
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|source| ron::from_str(&source).ok())
+            .unwrap_or_default()
+    }
+
+    /// `path`に記録を書き込む(ディレクトリがなければ作る)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Webではファイルに書き込めない
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self, _path: &Path) -> Result<(), Box<dyn Error>> {
+        Err(format!("{BEST_TIMES_PATH}に書き込めません(Webビルド)").into())
+    }
+}
+
+/// 起動時に、保存してある記録を読み込むシステム
+pub(crate) fn load_best_times(mut commands: Commands) {
+    commands.insert_resource(BestTimes::load(&BestTimes::path()));
+}
@@ -0,0 +1,305 @@
+//! チェックポイント
+//!
+//! レベルに置いたチェックポイントを見つからずに通ると、その時点の状態を`CheckpointData`に記録し、
+//! 見つかった後のリスタートでは最初からではなくそこから再開する。
+//! F5キーとF9キー(ゲームパッドではSelectとMode)では、プレイヤーと敵の状態をファイルに保存・読み込みする。
+
+use bevy::prelude::*;
+use sample_common::snapshot::save_path;
+use sample_common::{AppState, Snapshot};
+
+use crate::keycards::{spawn_door, spawn_keycard};
+use crate::{
+    DebugLog, Detection, Door, DoorPlace, Enemy, GameOverUI, GameState, Inventory, Investigating,
+    Keycard, KeycardPlace, KnockedOut, LevelEntity, LevelLayout, PatrolPause, Player, PlayerAction,
+    Stamina, TimeOfDay, VerticalVelocity, enemy_vision_system, enter_level, restart_game,
+};
+
+/// チェックポイントを保存するファイル名
+const CHECKPOINT_NAME: &str = "checkpoint";
+
+/// チェックポイントのプラグイン
+pub(crate) struct CheckpointsPlugin;
+
+impl Plugin for CheckpointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CheckpointData>()
+            .register_type::<Checkpointed>() // チェックポイントの保存・復元のために型を登録
+            .add_systems(
+                Update,
+                (
+                    reach_checkpoint.after(enemy_vision_system), // 見つからずに通ったチェックポイントで状態を記録する
+                    restore_checkpoint.before(restart_game), // 見つかったら通ったチェックポイントから再開する
+                    handle_checkpoints,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                clear_checkpoint
+                    .after(enter_level)
+                    .run_if(resource_changed::<LevelLayout>),
+            );
+    }
+}
+
+/// チェックポイントに保存する内容(プレイヤーと敵の位置、敵の巡回状態、時刻)
+fn checkpoint_snapshot() -> Snapshot {
+    Snapshot::new()
+        .allow::<Transform>()
+        .allow::<Player>()
+        .allow::<Enemy>()
+        .allow_resource::<TimeOfDay>()
+}
+
+/// チェックポイントの領域のコンポーネント(`Transform`の位置を中心とする円柱。高さは問わない)
+/// プレイヤーが初めて中に入ったときに、その時点の状態を`CheckpointData`に記録する
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CheckpointZone {
+    pub radius: f32,   // 半径
+    pub reached: bool, // もう通ったか(通ったチェックポイントでは記録し直さない)
+}
+
+impl CheckpointZone {
+    /// `point`がこのチェックポイントの中にあるか(地面に沿った距離で調べる)
+    pub fn contains(&self, transform: &Transform, point: Vec3) -> bool {
+        (point - transform.translation).xz().length() <= self.radius
+    }
+}
+
+/// 最後に通ったチェックポイントで記録した状態
+/// 見つかったときは、最初からではなくここから再開する(リスタートやレベルの配置し直しで消える)
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct CheckpointData {
+    pub saved: Option<CheckpointState>, // まだチェックポイントを通っていなければNone
+}
+
+/// チェックポイントを通った時点の状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointState {
+    pub player_position: Vec3,
+    pub guards: Vec<GuardState>,
+    pub inventory: Inventory,
+    pub keycards: Vec<KeycardPlace>, // まだ拾っていないカードキー
+    pub doors: Vec<DoorPlace>,       // まだ開けていないドア
+    pub time_of_day: TimeOfDay,
+}
+
+/// チェックポイントを通った時点の敵の状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardState {
+    pub entity: Entity,
+    pub transform: Transform,
+    pub patrol_index: usize, // 次に向かう巡回地点
+}
+
+/// チェックポイントに保存するエンティティのマーカーコンポーネント
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct Checkpointed;
+
+/// チェックポイントの領域を置く(レベルの配置で使う)
+pub(crate) fn spawn_checkpoint_zone(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Cylinder::new(1.0, 0.05))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgba(0.3, 0.8, 1.0, 0.5), // 半透明の水色(ゴールと見分けられるように)
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        })),
+        Transform::from_translation(Vec3::new(position.x, 0.03, position.z)), // 地面の少し上
+        CheckpointZone {
+            radius: 1.0,
+            reached: false,
+        },
+        LevelEntity,
+    ));
+}
+
+/// レベルを配置し直したら(リスタートやファイルの保存で)、通ったチェックポイントの記録を消すシステム
+fn clear_checkpoint(mut checkpoint: ResMut<CheckpointData>) {
+    checkpoint.saved = None;
+}
+
+/// プレイヤーが見つからずにまだ通っていないチェックポイントの中に入ったら、
+/// その時点のプレイヤーの位置・敵の状態・持ち物・残っているカードキーとドア・時刻を記録するシステム
+pub fn reach_checkpoint(
+    game_state: Res<GameState>,
+    players: Query<&Transform, With<Player>>,
+    mut zones: Query<(&Transform, &mut CheckpointZone)>,
+    guards: Query<(Entity, &Transform, &Enemy)>,
+    keycards: Query<(&Transform, &Keycard)>,
+    doors: Query<(&Transform, &Door)>,
+    inventory: Res<Inventory>,
+    time_of_day: Res<TimeOfDay>,
+    mut checkpoint: ResMut<CheckpointData>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let Ok(player_transform) = players.single() else {
+        return;
+    };
+    let position = player_transform.translation;
+    let Some(mut zone) = zones
+        .iter_mut()
+        .find(|(transform, zone)| !zone.reached && zone.contains(transform, position))
+        .map(|(_, zone)| zone)
+    else {
+        return;
+    };
+    zone.reached = true;
+
+    checkpoint.saved = Some(CheckpointState {
+        player_position: position,
+        guards: guards
+            .iter()
+            .map(|(entity, transform, enemy)| GuardState {
+                entity,
+                transform: *transform,
+                patrol_index: enemy.current_patrol_index,
+            })
+            .collect(),
+        inventory: inventory.clone(),
+        keycards: keycards
+            .iter()
+            .map(|(transform, keycard)| KeycardPlace {
+                key: keycard.key.clone(),
+                position: transform.translation,
+            })
+            .collect(),
+        doors: doors
+            .iter()
+            .map(|(transform, door)| DoorPlace {
+                key: door.key.clone(),
+                position: transform.translation,
+                size: door.half_size * 2.0,
+            })
+            .collect(),
+        time_of_day: *time_of_day,
+    });
+    log.push("Checkpoint reached");
+}
+
+/// 見つかった後にRキー(ゲームパッドではStartかA)を押すと、最後に通ったチェックポイントから再開するシステム
+/// プレイヤーと敵を記録した位置に戻し、持ち物と時刻、カードキーとドアも記録したときの状態に戻す
+/// (チェックポイントを通っていなければ何もせず、`restart_game`が最初からやり直す)
+pub fn restore_checkpoint(
+    actions: Res<ButtonInput<PlayerAction>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut game_state: ResMut<GameState>,
+    checkpoint: Res<CheckpointData>,
+    game_over_query: Query<Entity, With<GameOverUI>>,
+    mut players: Query<(&mut Transform, &mut VerticalVelocity), (With<Player>, Without<Enemy>)>,
+    mut guards: Query<
+        (&mut Transform, &mut Enemy, &mut Detection, &mut PatrolPause),
+        Without<Player>,
+    >,
+    investigators: Query<Entity, Or<(With<Investigating>, With<KnockedOut>)>>,
+    items: Query<Entity, Or<(With<Keycard>, With<Door>)>>,
+    mut inventory: ResMut<Inventory>,
+    mut stamina: ResMut<Stamina>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::GameOver || !actions.just_pressed(PlayerAction::Restart) {
+        return;
+    }
+    let Some(saved) = &checkpoint.saved else {
+        return;
+    };
+
+    for entity in &game_over_query {
+        commands.entity(entity).despawn();
+    }
+    if let Ok((mut transform, mut velocity)) = players.single_mut() {
+        transform.translation = saved.player_position;
+        velocity.0 = 0.0;
+    }
+    // 検知ゲージと立ち止まっている状態は記録しないので、空にする
+    for (_, _, mut detection, mut pause) in &mut guards {
+        detection.level = 0.0;
+        *pause = PatrolPause::default();
+    }
+    for state in &saved.guards {
+        // 記録した後にレベルから消えた敵は飛ばす
+        if let Ok((mut transform, mut enemy, _, _)) = guards.get_mut(state.entity) {
+            *transform = state.transform;
+            enemy.current_patrol_index = state.patrol_index;
+        }
+    }
+    for entity in &investigators {
+        commands
+            .entity(entity)
+            .remove::<(Investigating, KnockedOut)>();
+    }
+    // 記録した後に拾ったカードキーと開けたドアを元に戻す
+    for entity in &items {
+        commands.entity(entity).despawn();
+    }
+    for keycard in &saved.keycards {
+        spawn_keycard(&mut commands, &mut meshes, &mut materials, keycard);
+    }
+    for door in &saved.doors {
+        spawn_door(&mut commands, &mut meshes, &mut materials, door);
+    }
+    *inventory = saved.inventory.clone();
+    *time_of_day = saved.time_of_day;
+    *stamina = Stamina::default();
+    *game_state = GameState::Playing;
+    log.push("Restarted from the checkpoint");
+}
+
+/// チェックポイントの保存と、チェックポイントからの再開を行うシステム
+fn handle_checkpoints(
+    actions: Res<ButtonInput<PlayerAction>>,
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    game_over_query: Query<Entity, With<GameOverUI>>,
+    investigators: Query<Entity, Or<(With<Investigating>, With<KnockedOut>)>>,
+    mut detections: Query<&mut Detection>,
+    mut velocities: Query<&mut VerticalVelocity>,
+) {
+    let path = save_path("examina_clone", CHECKPOINT_NAME);
+
+    if actions.just_pressed(PlayerAction::SaveCheckpoint) {
+        // 見つかった状態は保存しない
+        if *game_state == GameState::Playing {
+            commands.queue(checkpoint_snapshot().save_command::<Checkpointed>(path));
+        }
+    } else if actions.just_pressed(PlayerAction::LoadCheckpoint) {
+        if !path.exists() {
+            info!("チェックポイントがまだ保存されていません(F5で保存します)");
+            return;
+        }
+        commands.queue(checkpoint_snapshot().load_command::<Checkpointed>(path));
+
+        // ゲームオーバー中やゴールした後でも、チェックポイントからやり直せる
+        for entity in game_over_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        // 音を調べている状態と気絶している状態は保存しないので、読み込んだ敵は巡回に戻す
+        for entity in &investigators {
+            commands
+                .entity(entity)
+                .remove::<(Investigating, KnockedOut)>();
+        }
+        // 検知ゲージも保存しないので、空にする
+        for mut detection in &mut detections {
+            detection.level = 0.0;
+        }
+        // 上下の速さも保存しないので、読み込んだ位置で止める
+        for mut velocity in &mut velocities {
+            velocity.0 = 0.0;
+        }
+        *game_state = GameState::Playing;
+    }
+}
@@ -0,0 +1,210 @@
+//! デバッグ表示(F1キーで表示・非表示)
+//!
+//! 画面の左側に、プレイヤーの速さと状態、カメラの距離、敵ごとの状態・プレイヤーまでの距離と角度を表示する。
+//! その下には、システムが`DebugLog`に書いた最近のログを並べる(古いものから消える)。
+//! 毎フレームのコンソールへの出力の代わりに使う。
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    CameraController, Crouching, Detection, Enemy, Hiding, Investigating, PatrolPause, Player,
+    PlayerAction, PlayerSpeed, Sprinting,
+};
+
+/// デバッグ表示を切り替えるキー
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+
+/// デバッグ表示の背景色
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// デバッグ表示のプラグイン
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugLog>()
+            .init_resource::<DebugOverlay>()
+            .add_systems(Startup, spawn_debug_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    update_debug_overlay.run_if(debug_overlay_visible),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// デバッグ表示に並べる、最近のログ
+#[derive(Resource, Debug, Default)]
+pub struct DebugLog {
+    lines: VecDeque<String>,
+}
+
+impl DebugLog {
+    /// 残しておくログの行数
+    pub const CAPACITY: usize = 8;
+
+    /// ログを1行追加する(多すぎれば古いものから消す)
+    pub fn push(&mut self, line: impl Into<String>) {
+        if self.lines.len() == Self::CAPACITY {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.into());
+    }
+
+    /// 残っているログ(古いものから)
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+}
+
+/// デバッグ表示の状態
+#[derive(Resource, Debug, Default)]
+struct DebugOverlay {
+    visible: bool, // 表示しているか
+}
+
+/// デバッグ表示のテキストのマーカーコンポーネント
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+fn debug_overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+/// 画面の左側に、デバッグ表示のテキストを作る(最初は表示しない)
+fn spawn_debug_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            left: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND_COLOR),
+        Visibility::Hidden,
+        DebugOverlayText,
+    ));
+}
+
+/// F1キーでデバッグ表示を切り替えるシステム
+fn toggle_debug_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut texts: Query<&mut Visibility, With<DebugOverlayText>>,
+) {
+    if !keyboard.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    overlay.visible = !overlay.visible;
+    for mut visibility in &mut texts {
+        *visibility = if overlay.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// デバッグ表示の内容を毎フレーム作り直すシステム(表示している間だけ動かす)
+fn update_debug_overlay(
+    actions: Res<ButtonInput<PlayerAction>>,
+    log: Res<DebugLog>,
+    players: Query<
+        (
+            &Transform,
+            Option<&PlayerSpeed>,
+            Has<Crouching>,
+            Has<Sprinting>,
+            Has<Hiding>,
+        ),
+        With<Player>,
+    >,
+    enemies: Query<(
+        Option<&Name>,
+        &Transform,
+        &Enemy,
+        &Detection,
+        Option<&Investigating>,
+        Option<&PatrolPause>,
+    )>,
+    cameras: Query<&CameraController>,
+    mut texts: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    let mut text = String::new();
+    let player = players.single().ok();
+    let player_position = player.map(|(transform, ..)| transform.translation);
+    if let Some((transform, speed, crouching, sprinting, hiding)) = player {
+        let p = transform.translation;
+        let _ = write!(
+            text,
+            "Player  speed {:.1} m/s  pos ({:.1}, {:.1}, {:.1})",
+            speed.map_or(0.0, |speed| speed.0),
+            p.x,
+            p.y,
+            p.z
+        );
+        for (flag, label) in [
+            (actions.pressed(PlayerAction::Sneak), "sneaking"),
+            (crouching, "crouching"),
+            (sprinting, "sprinting"),
+            (hiding, "hiding"),
+        ] {
+            if flag {
+                let _ = write!(text, "  [{label}]");
+            }
+        }
+        text.push('\n');
+    }
+    if let Ok(camera) = cameras.single() {
+        let _ = writeln!(text, "Camera  distance {:.1}", camera.distance);
+    }
+    for (name, transform, enemy, detection, investigating, pause) in &enemies {
+        let name = name.map_or("Enemy", Name::as_str);
+        let paused = pause.filter(|pause| pause.remaining > 0.0);
+        let state = match (investigating, paused) {
+            (Some(investigating), _) => {
+                format!("investigating ({:.1}s)", investigating.remaining)
+            }
+            (None, _) if enemy.patrol_points.is_empty() => "standing".to_owned(),
+            (None, Some(pause)) => format!("looking around ({:.1}s)", pause.remaining),
+            (None, None) => format!("patrol -> #{}", enemy.current_patrol_index + 1),
+        };
+        let _ = write!(
+            text,
+            "{name}  {state}  detection {:.0}%",
+            detection.level * 100.0
+        );
+        if let Some(player_position) = player_position {
+            let to_player = player_position - transform.translation;
+            let angle = transform
+                .forward()
+                .angle_between(to_player.normalize_or_zero())
+                .to_degrees();
+            let _ = write!(text, "  dist {:.1}  angle {angle:.0}°", to_player.length());
+        }
+        text.push('\n');
+    }
+    text.push_str("--- log ---");
+    for line in log.lines() {
+        text.push('\n');
+        text.push_str(line);
+    }
+
+    for mut overlay in &mut texts {
+        if overlay.0 != text {
+            overlay.0.clone_from(&text);
+        }
+    }
+}
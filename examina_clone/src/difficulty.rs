@@ -0,0 +1,132 @@
+//! 難易度(やさしい・ふつう・むずかしい)
+//!
+//! メニュー画面で左右キーを押して選ぶ。敵の視界範囲・視界角度・移動速度と、検知ゲージが溜まる速さに倍率を掛ける。
+//! 倍率は`apply_config`が敵に反映し、検知ゲージの速さは`enemy_vision_system`が掛ける。
+
+use bevy::prelude::*;
+use sample_common::{AppState, LocalizedText};
+
+use crate::PlayerAction;
+
+/// 難易度(メニューで選ぶ。プレイ中は変えない)
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy, // 敵の視界が狭く、動きが遅く、なかなか気づかない
+    #[default]
+    Normal, // 設定ファイルとレベルの値のまま
+    Hard, // 敵の視界が広く、動きが速く、すぐに気づく
+}
+
+impl Difficulty {
+    /// 選べる難易度(メニューに並べる順)
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    /// 敵の視界範囲の倍率
+    pub fn vision_range_ratio(self) -> f32 {
+        match self {
+            Self::Easy => 0.8,
+            Self::Normal => 1.0,
+            Self::Hard => 1.25,
+        }
+    }
+
+    /// 敵の視界角度の倍率
+    pub fn vision_angle_ratio(self) -> f32 {
+        match self {
+            Self::Easy => 0.8,
+            Self::Normal => 1.0,
+            Self::Hard => 1.2,
+        }
+    }
+
+    /// 敵の巡回と調べに行くときの速度の倍率(巡回地点ごとの速度にも掛ける)
+    pub fn speed_ratio(self) -> f32 {
+        match self {
+            Self::Easy => 0.8,
+            Self::Normal => 1.0,
+            Self::Hard => 1.25,
+        }
+    }
+
+    /// 検知ゲージが溜まる速さの倍率
+    pub fn detection_ratio(self) -> f32 {
+        match self {
+            Self::Easy => 0.6,
+            Self::Normal => 1.0,
+            Self::Hard => 1.5,
+        }
+    }
+
+    /// 表示に使うテキストのキー
+    fn key(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+/// 難易度と、メニューでの難易度の選択のプラグイン
+pub(crate) struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(OnEnter(AppState::Menu), spawn_difficulty_select)
+            .add_systems(
+                Update,
+                (select_difficulty, update_difficulty_text)
+                    .chain()
+                    .run_if(in_state(AppState::Menu)),
+            );
+    }
+}
+
+/// メニューの難易度の表示のマーカーコンポーネント
+#[derive(Component)]
+struct DifficultyText;
+
+/// メニューの上の方に、選んでいる難易度を表示する
+fn spawn_difficulty_select(mut commands: Commands) {
+    commands.spawn((
+        LocalizedText::new("difficulty").with_key_arg("level", Difficulty::Normal.key()),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Percent(10.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        GlobalZIndex(1), // メニューの背景より手前に
+        DifficultyText,
+        StateScoped(AppState::Menu),
+    ));
+}
+
+/// 左右キーで難易度を選ぶシステム(端では止まる)
+fn select_difficulty(actions: Res<ButtonInput<PlayerAction>>, mut difficulty: ResMut<Difficulty>) {
+    let index = Difficulty::ALL
+        .iter()
+        .position(|level| *level == *difficulty)
+        .unwrap_or(1);
+    let index = if actions.just_pressed(PlayerAction::Left) {
+        index.saturating_sub(1)
+    } else if actions.just_pressed(PlayerAction::Right) {
+        (index + 1).min(Difficulty::ALL.len() - 1)
+    } else {
+        return;
+    };
+    difficulty.set_if_neq(Difficulty::ALL[index]);
+}
+
+/// 選んでいる難易度の表示を更新するシステム
+fn update_difficulty_text(
+    difficulty: Res<Difficulty>,
+    mut texts: Query<&mut LocalizedText, With<DifficultyText>>,
+) {
+    for mut text in &mut texts {
+        text.set_if_neq(LocalizedText::new("difficulty").with_key_arg("level", difficulty.key()));
+    }
+}
@@ -0,0 +1,184 @@
+//! 操作できるものと、その案内
+//!
+//! カードキー・ドア・隠れ場所・背後を取れている敵など、操作できるものには`Interactable`を付ける。
+//! プレイヤーの正面の、手の届く範囲にあるいちばん近いものの案内(「E: 〜」)をHUDに表示し、
+//! 「操作する」ボタン(EキーかYボタン)を押すと、そのものへ`InteractionEvent`を送る。
+//! 何が起こるかは、イベントを受け取るシステム(`pick_up_keycards`・`open_doors`・`update_hiding`・`take_down`)が決める。
+
+use bevy::prelude::*;
+use sample_common::{AppState, LocalizedText};
+
+use crate::{Facing, GameState, INTERACT_RANGE, Player, PlayerAction, player_input};
+
+/// プレイヤーの向きから、この角度(度)以内にあるものだけを操作できる
+pub const INTERACT_ANGLE: f32 = 60.0;
+
+/// 操作できるもののプラグイン
+pub(crate) struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InteractionEvent>()
+            .init_resource::<InteractionFocus>()
+            .add_systems(Startup, spawn_interact_text)
+            .add_systems(
+                Update,
+                (interact.after(player_input), update_interact_text)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// 操作できるもののコンポーネント
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Interactable {
+    pub action: LocalizedText, // 操作の名前(案内の「E: 」に続ける文章)
+    pub range: f32,            // 操作できる距離(地面に沿って、`half_size`の面から測る)
+    pub half_size: Vec3,       // 大きさの半分(ドアや隠れ場所のように大きいもの。小さいものは0)
+    pub enabled: bool,         // 今操作できるか(falseなら選ばず、案内も出さない)
+}
+
+impl Interactable {
+    /// `INTERACT_RANGE`まで近づくと`action`の操作ができる、小さなもの
+    pub fn new(action: LocalizedText) -> Self {
+        Self {
+            action,
+            range: INTERACT_RANGE,
+            half_size: Vec3::ZERO,
+            enabled: true,
+        }
+    }
+
+    /// 大きさの半分を指定する(その面から距離を測る)
+    pub fn with_half_size(mut self, half_size: Vec3) -> Self {
+        self.half_size = half_size;
+        self
+    }
+
+    /// 操作できる距離を指定する(0なら中に入らないと操作できない)
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// 操作できるかを指定する
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// `point`からこのものまでの、地面に沿った向き(長さが距離。中にいれば0)
+    pub fn offset(&self, transform: &Transform, point: Vec3) -> Vec2 {
+        let local = (point - transform.translation).xz();
+        local.clamp(-self.half_size.xz(), self.half_size.xz()) - local
+    }
+
+    /// 案内の文章(「E: 」に操作の名前を続ける)
+    pub fn prompt(&self) -> LocalizedText {
+        let mut prompt =
+            LocalizedText::new("interact_prompt").with_key_arg("action", self.action.key.clone());
+        prompt.args.extend(self.action.args.iter().cloned());
+        prompt
+    }
+}
+
+/// 操作したときに、操作したものへ送るイベント
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractionEvent {
+    pub player: Entity, // 操作したプレイヤー
+    pub target: Entity, // 操作したもの
+}
+
+/// 今案内を出している(ボタンを押すと操作する)もの
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InteractionFocus(pub Option<Entity>);
+
+/// 操作の案内のマーカーコンポーネント
+#[derive(Component)]
+struct InteractText;
+
+/// `position`にいて`facing`を向いているプレイヤーが操作できるもののうち、いちばん近いもの
+/// 操作できる距離以内で、向きから`INTERACT_ANGLE`度以内にあるものを選ぶ(中に入っているものは向きを問わない)
+pub fn interaction_target<'a>(
+    position: Vec3,
+    facing: Dir3,
+    targets: impl IntoIterator<Item = (Entity, &'a Transform, &'a Interactable)>,
+) -> Option<Entity> {
+    let forward = facing.xz();
+    targets
+        .into_iter()
+        .filter(|(_, _, interactable)| interactable.enabled)
+        .filter_map(|(entity, transform, interactable)| {
+            let offset = interactable.offset(transform, position);
+            let distance = offset.length();
+            let in_front = distance <= f32::EPSILON
+                || forward.angle_to(offset).abs().to_degrees() <= INTERACT_ANGLE;
+            (distance <= interactable.range && in_front).then_some((distance, entity))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, entity)| entity)
+}
+
+/// プレイヤーが操作できるものを選び、「操作する」ボタンが押されたら、そのものへ`InteractionEvent`を送るシステム
+pub fn interact(
+    actions: Res<ButtonInput<PlayerAction>>,
+    game_state: Res<GameState>,
+    players: Query<(Entity, &Transform, &Facing), With<Player>>,
+    targets: Query<(Entity, &Transform, &Interactable)>,
+    mut focus: ResMut<InteractionFocus>,
+    mut events: EventWriter<InteractionEvent>,
+) {
+    let player = players
+        .single()
+        .ok()
+        .filter(|_| *game_state == GameState::Playing);
+    let target = player.and_then(|(_, transform, facing)| {
+        interaction_target(transform.translation, facing.0, &targets)
+    });
+    focus.set_if_neq(InteractionFocus(target));
+    let (Some((player, _, _)), Some(target)) = (player, target) else {
+        return;
+    };
+    if actions.just_pressed(PlayerAction::Interact) {
+        events.write(InteractionEvent { player, target });
+    }
+}
+
+/// 操作の案内を作る(操作できるものがあるときだけ表示する)
+fn spawn_interact_text(mut commands: Commands) {
+    commands.spawn((
+        LocalizedText::new("interact_prompt"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        InteractText,
+    ));
+}
+
+/// 選ばれているものの操作の案内を表示するシステム
+fn update_interact_text(
+    focus: Res<InteractionFocus>,
+    targets: Query<&Interactable>,
+    mut texts: Query<(&mut LocalizedText, &mut Visibility), With<InteractText>>,
+) {
+    let prompt = focus
+        .0
+        .and_then(|target| targets.get(target).ok())
+        .map(Interactable::prompt);
+    for (mut text, mut visibility) in &mut texts {
+        match &prompt {
+            Some(prompt) => {
+                text.set_if_neq(prompt.clone());
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! カードキーと鍵のかかったドア
+//!
+//! レベルに置いたカードキーを操作すると拾って`Inventory`に入れ、同じ種類のカードキーを持っていれば
+//! ドアを操作して開けられる(消える)。開けるまでのドアは壁と同じように通れず、敵の視界も遮る。
+//! 拾ったカードキーと開けたドアは、リスタートで元に戻る。
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use sample_common::{AppState, LocalizedText};
+use serde::{Deserialize, Serialize};
+
+use crate::{Interactable, InteractionEvent, LevelEntity, interact};
+
+/// カードキーとドアのプラグイン
+pub(crate) struct KeycardsPlugin;
+
+impl Plugin for KeycardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .add_systems(Startup, spawn_inventory_text)
+            .add_systems(
+                Update,
+                (
+                    update_door_actions.before(interact), // 操作の名前を持っているカードキーに合わせる
+                    (
+                        (pick_up_keycards, open_doors),
+                        update_inventory_text.run_if(resource_changed::<Inventory>),
+                    )
+                        .chain()
+                        .after(interact), // カードキーを拾い、ドアを開ける
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// レベルに置くカードキー
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct KeycardPlace {
+    pub key: String,    // カードキーの種類(同じ種類のドアを開けられる)
+    pub position: Vec3, // 置く位置
+}
+
+/// レベルに置く、鍵のかかったドア(開けるまでは壁と同じように通れず、敵の視界も遮る)
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct DoorPlace {
+    pub key: String,    // 開けるのに必要なカードキーの種類
+    pub position: Vec3, // 中心の位置
+    pub size: Vec3,     // 各辺の長さ
+}
+
+/// 拾えるカードキーのコンポーネント
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Keycard {
+    pub key: String, // カードキーの種類
+}
+
+/// 鍵のかかったドアのコンポーネント(`Transform`の位置を中心とする、軸に沿った直方体)
+/// 同じ種類のカードキーを持って近くで操作すると開く(消える)
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Door {
+    pub key: String,     // 開けるのに必要なカードキーの種類
+    pub half_size: Vec3, // 各辺の長さの半分
+}
+
+impl Door {
+    /// `point`からドアの面までの、地面に沿った距離(ドアの中なら0)
+    pub fn distance(&self, transform: &Transform, point: Vec3) -> f32 {
+        ((point - transform.translation).xz().abs() - self.half_size.xz())
+            .max(Vec2::ZERO)
+            .length()
+    }
+}
+
+/// 拾ったカードキーの持ち物(リスタートで空になる)
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    pub keycards: Vec<String>, // 拾った順のカードキーの種類
+}
+
+impl Inventory {
+    /// `key`の種類のカードキーを持っているか
+    pub fn has(&self, key: &str) -> bool {
+        self.keycards.iter().any(|keycard| keycard == key)
+    }
+}
+
+/// 持っているカードキーの表示のマーカーコンポーネント
+#[derive(Component)]
+struct InventoryText;
+
+/// 拾えるカードキーのコンポーネント(近くで操作すると拾う)
+pub fn keycard(key: &str) -> impl Bundle {
+    (
+        Keycard {
+            key: key.to_owned(),
+        },
+        Interactable::new(LocalizedText::new("pickup_action").with_arg("key", key)),
+    )
+}
+
+/// 鍵のかかったドアのコンポーネント(ドアの面の近くで操作すると、合うカードキーがあれば開く)
+pub fn door(key: &str, half_size: Vec3) -> impl Bundle {
+    (
+        Door {
+            key: key.to_owned(),
+            half_size,
+        },
+        Interactable::new(LocalizedText::new("door_locked_action").with_arg("key", key))
+            .with_half_size(half_size),
+    )
+}
+
+/// カードキーを置く(レベルの配置と、チェックポイントからの再開で使う)
+pub(crate) fn spawn_keycard(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    place: &KeycardPlace,
+) {
+    commands.spawn((
+        keycard(&place.key),
+        Mesh3d(meshes.add(Cuboid::new(0.3, 0.05, 0.2))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.6, 1.0),
+            emissive: LinearRgba::rgb(0.4, 1.2, 2.0), // 暗い時間帯でも見つけられるように光らせる
+            ..default()
+        })),
+        Transform::from_translation(place.position),
+        LevelEntity,
+    ));
+}
+
+/// 鍵のかかったドアを置く(レベルの配置と、チェックポイントからの再開で使う)
+pub(crate) fn spawn_door(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    place: &DoorPlace,
+) {
+    let half_size = place.size / 2.0;
+    commands.spawn((
+        door(&place.key, half_size),
+        RigidBody::Fixed,
+        Collider::cuboid(half_size.x, half_size.y, half_size.z),
+        Mesh3d(meshes.add(Cuboid::from_size(place.size))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.15, 0.3, 0.55), // 青いドア(カードキーと同じ色味)
+            ..default()
+        })),
+        Transform::from_translation(place.position),
+        LevelEntity,
+    ));
+}
+
+/// 持っているカードキーの表示を作るシステム(1枚以上持っているときだけ表示する)
+fn spawn_inventory_text(mut commands: Commands) {
+    commands.spawn((
+        LocalizedText::new("inventory"),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        InventoryText,
+    ));
+}
+
+/// 操作されたカードキーを拾うシステム
+/// 拾ったカードキーは`Inventory`に入り、消える(リスタートで元に戻る)
+pub fn pick_up_keycards(
+    mut commands: Commands,
+    mut interactions: EventReader<InteractionEvent>,
+    keycards: Query<&Keycard>,
+    mut inventory: ResMut<Inventory>,
+) {
+    for interaction in interactions.read() {
+        let Ok(keycard) = keycards.get(interaction.target) else {
+            continue;
+        };
+        commands.entity(interaction.target).despawn();
+        if !inventory.has(&keycard.key) {
+            inventory.keycards.push(keycard.key.clone());
+        }
+    }
+}
+
+/// 操作されたドアを、同じ種類のカードキーを持っていれば開ける(消す)システム(リスタートで元に戻る)
+pub fn open_doors(
+    mut commands: Commands,
+    mut interactions: EventReader<InteractionEvent>,
+    doors: Query<&Door>,
+    inventory: Res<Inventory>,
+) {
+    for interaction in interactions.read() {
+        if doors
+            .get(interaction.target)
+            .is_ok_and(|door| inventory.has(&door.key))
+        {
+            commands.entity(interaction.target).despawn();
+        }
+    }
+}
+
+/// ドアの操作の名前を、合うカードキーを持っているかに合わせて変えるシステム
+/// 持っていれば「開ける」を、なければ必要なカードキーを表示する
+fn update_door_actions(inventory: Res<Inventory>, mut doors: Query<(&Door, &mut Interactable)>) {
+    for (door, mut interactable) in &mut doors {
+        let key = if inventory.has(&door.key) {
+            "door_action"
+        } else {
+            "door_locked_action"
+        };
+        let action = LocalizedText::new(key).with_arg("key", &door.key);
+        if interactable.action != action {
+            interactable.action = action;
+        }
+    }
+}
+
+/// 持っているカードキーの表示を更新するシステム(何も持っていなければ隠す)
+fn update_inventory_text(
+    inventory: Res<Inventory>,
+    mut texts: Query<(&mut LocalizedText, &mut Visibility), With<InventoryText>>,
+) {
+    for (mut text, mut visibility) in &mut texts {
+        if inventory.keycards.is_empty() {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        *text = LocalizedText::new("inventory").with_arg("keys", inventory.keycards.join(", "));
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
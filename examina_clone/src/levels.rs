@@ -0,0 +1,160 @@
+//! レベルの順番と切り替え
+//!
+//! レベルは`LEVELS`の順に遊ぶ。ゴールした後にRキーを押すと、次のレベルのファイルを読み込む。
+//! メニュー画面では、上下キーで最初に遊ぶレベルを選べる(各レベルの記録も表示する)。
+//! 別のレベルに切り替わったら、前のレベルの敵・投げた石・結果画面を消し、プレイヤーを開始位置に戻す。
+
+use bevy::prelude::*;
+use sample_common::{AppState, LoadConfig, LocalizedText};
+
+use crate::{
+    BestTimes, DebugLog, Enemy, GameOverUI, GameState, Hiding, Inventory, LevelLayout, Player,
+    PlayerAction, Rock, RunStats, Stamina, VerticalVelocity, format_run_time,
+};
+
+/// 遊ぶ順に並べたレベルのファイル(assetsディレクトリからの相対パス)
+pub const LEVELS: &[&str] = &["levels/level1.config.ron", "levels/level2.config.ron"];
+
+/// 選択中のレベルの文字色
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// 今遊んでいるレベル(`LEVELS`の番号)
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct CurrentLevel(pub usize);
+
+impl CurrentLevel {
+    /// レベルのファイルのパス
+    pub fn path(self) -> &'static str {
+        LEVELS[self.0]
+    }
+
+    /// 次のレベル(最後のレベルならNone)
+    pub fn next(self) -> Option<Self> {
+        (self.0 + 1 < LEVELS.len()).then_some(Self(self.0 + 1))
+    }
+}
+
+/// レベルの順番と切り替え、メニューのレベル選択のプラグイン
+pub(crate) struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .add_systems(OnEnter(AppState::Menu), spawn_level_select)
+            .add_systems(
+                Update,
+                (
+                    switch_level.run_if(resource_changed::<CurrentLevel>),
+                    (select_level, update_level_select)
+                        .chain()
+                        .run_if(in_state(AppState::Menu)),
+                ),
+            );
+    }
+}
+
+/// メニューのレベル選択の項目(`LEVELS`の番号)
+#[derive(Component)]
+struct LevelSelectItem(usize);
+
+/// 今のレベルが変わったら、そのレベルのファイルを読み込むシステム
+/// (読み込んだら`LevelLayout`が変わり、`enter_level`と`build_level`が配置し直す)
+fn switch_level(current: Res<CurrentLevel>, mut commands: Commands) {
+    commands.queue(LoadConfig::<LevelLayout>::new(current.path()));
+}
+
+/// メニューの下の方に、レベルの一覧を表示する
+fn spawn_level_select(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Percent(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            GlobalZIndex(1), // メニューの背景より手前に
+            StateScoped(AppState::Menu),
+        ))
+        .with_children(|parent| {
+            parent.spawn(LocalizedText::new("level_select"));
+            for index in 0..LEVELS.len() {
+                parent.spawn((
+                    LocalizedText::new("level_uncleared"),
+                    LevelSelectItem(index),
+                ));
+            }
+        });
+}
+
+/// 上下キーで最初に遊ぶレベルを選ぶシステム(端まで行くと反対側に戻る)
+fn select_level(actions: Res<ButtonInput<PlayerAction>>, mut current: ResMut<CurrentLevel>) {
+    let count = LEVELS.len();
+    if actions.just_pressed(PlayerAction::Down) {
+        current.0 = (current.0 + 1) % count;
+    }
+    if actions.just_pressed(PlayerAction::Up) {
+        current.0 = (current.0 + count - 1) % count;
+    }
+}
+
+/// レベルの一覧に記録を表示し、選んでいるレベルを色で示すシステム
+fn update_level_select(
+    current: Res<CurrentLevel>,
+    best_times: Res<BestTimes>,
+    mut items: Query<(&LevelSelectItem, &mut LocalizedText, &mut TextColor)>,
+) {
+    for (item, mut text, mut color) in &mut items {
+        let number = item.0 + 1;
+        text.set_if_neq(match best_times.get(LEVELS[item.0]) {
+            Some(best) => LocalizedText::new("level_cleared")
+                .with_arg("number", number)
+                .with_arg("best_time", format_run_time(best.time))
+                .with_arg("best_score", best.score),
+            None => LocalizedText::new("level_uncleared").with_arg("number", number),
+        });
+        color.set_if_neq(TextColor(if item.0 == current.0 {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        }));
+    }
+}
+
+/// 別のレベルを読み込んだら、前のレベルの敵・投げた石・結果画面を消し、プレイヤーと記録を最初に戻すシステム
+/// `build_level`より前に動かす(敵は新しいレベルの配置で作り直す)。
+/// 同じレベルのファイルを編集して読み込み直したときは何もしない
+pub fn enter_level(
+    mut commands: Commands,
+    current: Res<CurrentLevel>,
+    level: Res<LevelLayout>,
+    mut entered: Local<CurrentLevel>,
+    leftovers: Query<Entity, Or<(With<Enemy>, With<Rock>, With<GameOverUI>)>>,
+    mut players: Query<(Entity, &mut Transform, &mut VerticalVelocity), With<Player>>,
+    mut game_state: ResMut<GameState>,
+    mut stats: ResMut<RunStats>,
+    mut inventory: ResMut<Inventory>,
+    mut stamina: ResMut<Stamina>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *entered == *current {
+        return;
+    }
+    *entered = *current;
+    for entity in &leftovers {
+        commands.entity(entity).despawn();
+    }
+    for (entity, mut transform, mut velocity) in &mut players {
+        commands.entity(entity).remove::<Hiding>();
+        transform.translation = level.player_start;
+        velocity.0 = 0.0;
+    }
+    *stats = RunStats::default();
+    *inventory = Inventory::default();
+    *stamina = Stamina::default();
+    game_state.set_if_neq(GameState::Playing);
+    log.push(format!("Level {} started", current.0 + 1));
+}
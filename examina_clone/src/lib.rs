@@ -0,0 +1,3068 @@
+//! 敵の視界を避けながら進むステルスゲームのサンプル
+
+use std::time::Duration;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use bevy::render::mesh::primitives::Capsule3dMeshBuilder;
+use bevy_kira_audio::{
+    Audio, AudioApp, AudioChannel, AudioControl, AudioPlugin, AudioSource, AudioTween,
+    SpatialAudioReceiver,
+};
+use bevy_rapier3d::prelude::*;
+use sample_common::{
+    ActionAxes, ActionMap, ActionsPlugin, AppState, AppStatePlugin, ConfigPlugin, CrashWatchPlugin,
+    LoadingAssets, Locale, LocalizationPlugin, LocalizedText, Rumble, ScreenshotPlugin, TimeControlPlugin,
+};
+use serde::{Deserialize, Serialize};
+
+mod alarm;
+mod ambient_noise;
+mod best_times;
+mod checkpoints;
+mod debug_overlay;
+mod difficulty;
+mod interaction;
+mod keycards;
+mod levels;
+mod minimap;
+mod route_editor;
+mod sprint;
+mod takedown;
+
+pub use alarm::{
+    AlarmLevel, RaiseAlarm, SecurityCamera, SecurityCameraPlace, Tripwire, TripwirePlace,
+    converge_on_alarm, decay_alarm, raise_alarm, trip_wires, watch_cameras,
+};
+pub use ambient_noise::{AmbientNoise, NoiseKind, NoiseSourcePlace, masked_noise_radius};
+pub use best_times::{BestRecord, BestTimes};
+pub use checkpoints::{
+    CheckpointData, CheckpointState, CheckpointZone, Checkpointed, GuardState, reach_checkpoint,
+    restore_checkpoint,
+};
+pub use debug_overlay::{DebugLog, DebugOverlayPlugin, DebugOverlayText};
+pub use difficulty::Difficulty;
+pub use interaction::{
+    INTERACT_ANGLE, Interactable, InteractionEvent, InteractionFocus, interact, interaction_target,
+};
+pub use keycards::{
+    Door, DoorPlace, Inventory, Keycard, KeycardPlace, door, keycard, open_doors, pick_up_keycards,
+};
+pub use levels::{CurrentLevel, LEVELS, enter_level};
+pub use minimap::{
+    MINIMAP_EXTENT, MINIMAP_SIZE, Minimap, MinimapDot, MinimapIcon, minimap_point, toggle_minimap,
+    update_minimap,
+};
+pub use route_editor::{PICK_RADIUS, RouteEditorPlugin, level_to_ron, pick_waypoint};
+pub use sprint::{Sprinting, Stamina, update_sprint};
+pub use takedown::{
+    BodyDiscovered, KnockedOut, discover_bodies, hide_knocked_out_cones, take_down,
+    takedown_target, update_knocked_out, update_takedown_targets,
+};
+
+/// 高さを省略した壁の高さ(m)
+pub const WALL_HEIGHT: f32 = 2.5;
+
+/// 厚さを省略した壁の厚さ(m)
+pub const WALL_THICKNESS: f32 = 0.3;
+
+/// カードキーを拾ったり、ドアを開けたりできる距離(地面に沿った距離、m)
+pub const INTERACT_RANGE: f32 = 1.5;
+
+/// カメラが見るプレイヤーの位置の高さ(プレイヤーの中心から上、m)
+const CAMERA_PIVOT_HEIGHT: f32 = 0.5;
+
+/// 壁に当てるときのカメラの大きさ(球の半径、m)
+const CAMERA_RADIUS: f32 = 0.3;
+
+/// 敵の中心から目までの高さ(カプセルの中心から上に、頭の少し下)
+pub const ENEMY_EYE_HEIGHT: f32 = 0.7;
+
+/// 足音を1回鳴らすまでに歩く距離(m)
+pub const FOOTSTEP_STRIDE: f32 = 0.8;
+
+/// 1フレームでこれより遠くへ動いたときは、リスタートなどで移動したとみなして歩いた距離に数えない(m)
+const MAX_STEP_PER_FRAME: f32 = 2.0;
+
+/// 投げる石の半径
+const ROCK_RADIUS: f32 = 0.15;
+
+/// 石が最初に何かに当たってから消えるまでの時間(秒)
+const ROCK_LIFETIME: f32 = 5.0;
+
+/// クリアしたときの得点の満点(ここから時間・気づかれた回数・投げた石の数で減らす)
+const BASE_SCORE: f32 = 10_000.0;
+
+/// 1秒かかるごとに減る得点
+const SCORE_PER_SECOND: f32 = 10.0;
+
+/// 1回気づかれるごとに減る得点
+const SCORE_PER_DETECTION: f32 = 500.0;
+
+/// 石を1個投げるごとに減る得点
+const SCORE_PER_DISTRACTION: f32 = 100.0;
+
+/// 検知ゲージの目の表示の幅(ピクセル、高さはその半分)
+const EYE_WIDTH: f32 = 64.0;
+
+/// 明るさの宝石の表示の大きさ(ピクセル)
+const LIGHT_GEM_SIZE: f32 = 28.0;
+
+/// プレイヤーのカプセルの半径
+const PLAYER_RADIUS: f32 = 0.4;
+
+/// 立っているプレイヤーのカプセルの、半球の中心から中心までの長さの半分(高さ1.8mの半分)
+const PLAYER_HALF_HEIGHT: f32 = 0.9;
+
+/// しゃがんでいるプレイヤーのカプセルの、半球の中心から中心までの長さの半分(立っているときの半分)
+const CROUCH_HALF_HEIGHT: f32 = 0.45;
+
+/// 視界の扇形を置く高さ(敵の中心からの相対位置。地面の少し上に重ねる)
+const VISION_CONE_HEIGHT: f32 = -0.95;
+
+/// 視界の扇形の普段の不透明度
+const VISION_CONE_ALPHA: f32 = 0.2;
+
+/// 検知ゲージが溜まっている間、扇形の不透明度を最大でどれだけ上げるか
+const VISION_CONE_PULSE_ALPHA: f32 = 0.25;
+
+/// 検知ゲージが溜まっている間、扇形が点滅する速さ(1秒あたりの回数)
+const VISION_CONE_PULSE_RATE: f32 = 3.0;
+
+/// ゲームオーバーなどの状態を管理するリソース
+#[derive(Resource, Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub enum GameState {
+    #[default]
+    Playing, // ゲームプレイ中
+    GameOver, // ゲームオーバー状態
+    Victory,  // 見つからずにゴールにたどり着いた状態
+}
+
+/// プレイヤーの操作(キーボードとゲームパッドの両方に割り当てる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerAction {
+    Up,      // 前に進む
+    Down,    // 後ろに下がる
+    Left,    // 左に進む
+    Right,   // 右に進む
+    Sneak,   // スニーキング(ゆっくり移動)
+    ZoomIn,  // カメラを近づける
+    ZoomOut, // カメラを遠ざける
+    Restart, // ゲームオーバー後にやり直す
+    SaveCheckpoint, // チェックポイントを保存する
+    LoadCheckpoint, // チェックポイントから再開する
+    Crouch,         // しゃがむ(押している間)
+    Throw,          // 石を投げる
+    Interact,       // 近くのものを操作する(カードキーを拾う・ドアを開ける・隠れ場所に出入りする・敵を気絶させる)
+    Minimap,        // ミニマップの表示・非表示
+    Jump,           // ジャンプする
+    Sprint,         // ダッシュする(スタミナを使う)
+}
+
+/// 操作の割り当て
+/// ゲームパッドでは、左スティック(と十字キー)で移動、右スティックでカメラを回し、
+/// 左トリガーでスニーキング、左スティックの押し込みでダッシュ、Aボタンでジャンプ(ゲームオーバー後はやり直す)、
+/// Yボタンで近くのものを操作する(カードキーを拾う・ドアを開ける・隠れ場所に出入りする・背後から敵を気絶させる)。
+/// 十字キーは移動に使うので、ほかの操作には割り当てない
+pub fn action_map() -> ActionMap<PlayerAction> {
+    ActionMap::new()
+        .key(PlayerAction::Up, KeyCode::ArrowUp)
+        .key(PlayerAction::Down, KeyCode::ArrowDown)
+        .key(PlayerAction::Left, KeyCode::ArrowLeft)
+        .key(PlayerAction::Right, KeyCode::ArrowRight)
+        .movement(
+            PlayerAction::Up,
+            PlayerAction::Down,
+            PlayerAction::Left,
+            PlayerAction::Right,
+        )
+        .key(PlayerAction::Sneak, KeyCode::ShiftLeft)
+        .button(PlayerAction::Sneak, GamepadButton::LeftTrigger2)
+        .key(PlayerAction::ZoomIn, KeyCode::KeyQ)
+        .button(PlayerAction::ZoomIn, GamepadButton::RightTrigger)
+        .key(PlayerAction::ZoomOut, KeyCode::KeyZ)
+        .button(PlayerAction::ZoomOut, GamepadButton::LeftTrigger)
+        .key(PlayerAction::Restart, KeyCode::KeyR)
+        .button(PlayerAction::Restart, GamepadButton::Start)
+        .button(PlayerAction::Restart, GamepadButton::South)
+        .key(PlayerAction::SaveCheckpoint, KeyCode::F5)
+        .button(PlayerAction::SaveCheckpoint, GamepadButton::Select)
+        .key(PlayerAction::LoadCheckpoint, KeyCode::F9)
+        .button(PlayerAction::LoadCheckpoint, GamepadButton::Mode)
+        .key(PlayerAction::Crouch, KeyCode::KeyC)
+        .button(PlayerAction::Crouch, GamepadButton::East)
+        .key(PlayerAction::Throw, KeyCode::KeyG)
+        .button(PlayerAction::Throw, GamepadButton::RightTrigger2)
+        .key(PlayerAction::Interact, KeyCode::KeyE)
+        .button(PlayerAction::Interact, GamepadButton::North)
+        .key(PlayerAction::Minimap, KeyCode::KeyM)
+        .button(PlayerAction::Minimap, GamepadButton::RightThumb)
+        .key(PlayerAction::Jump, KeyCode::Space)
+        .button(PlayerAction::Jump, GamepadButton::South) // やり直しと同じボタン(やり直しより先に調べるので、再開したフレームには跳ばない)
+        .key(PlayerAction::Sprint, KeyCode::ControlLeft)
+        .button(PlayerAction::Sprint, GamepadButton::LeftThumb)
+}
+
+/// `assets/config/examina_clone.config.ron`から読み込む設定
+/// 実行中にファイルを保存すると、すぐに反映される
+#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct StealthConfig {
+    pub player_speed: f32,          // プレイヤーの移動速度
+    pub sneak_ratio: f32,           // スニーキング中の速度の倍率
+    pub crouch_ratio: f32,          // しゃがんでいる間の速度の倍率
+    pub crouch_vision_ratio: f32,   // しゃがんだプレイヤーを見つけられる距離の倍率(視界範囲に掛ける)
+    pub enemy_speed: f32,           // 敵の移動速度
+    pub vision_range: f32,          // 敵の視界範囲
+    pub vision_angle: f32,          // 敵の視界角度(度)
+    pub run_noise_radius: f32,      // 走ったときの足音が聞こえる半径
+    pub sneak_noise_radius: f32,    // スニーキング中の足音が聞こえる半径(0なら音を立てない)
+    pub landing_noise_radius: f32,  // 着地したときの音が聞こえる半径
+    pub investigate_time: f32,      // 敵が音のした場所で辺りを調べる時間(秒)
+    pub detection_time: f32,        // 視界の端で検知ゲージが満ちる時間(秒、0以下ならすぐ見つかる)
+    pub detection_drain_time: f32,  // 見えなくなってから、満ちた検知ゲージが空になる時間(秒)
+    pub footstep_volume: f32,       // プレイヤーが設定の速度で歩いたときの足音の音量(0〜1)
+    pub enemy_footstep_volume: f32, // すぐそばを歩く敵の足音の音量(0〜1)
+    pub enemy_footstep_range: f32,  // 敵の足音が聞こえ始める、プレイヤーからの距離
+    pub throw_speed: f32,           // 投げた石の速さ
+    pub throw_cooldown: f32,        // 次の石を投げられるまでの時間(秒)
+    pub rock_noise_radius: f32,     // 石が落ちた音が聞こえる半径
+    pub day_light: f32,             // 昼の明かりのないところの明るさ(0〜1)
+    pub night_light: f32,           // 夜の明かりのないところの明るさ(0〜1)
+    pub dark_vision_ratio: f32,     // 真っ暗なところのプレイヤーを見つけられる距離の倍率(視界範囲に掛ける)
+    pub gravity: f32,               // プレイヤーにかかる重力加速度(m/s²)
+    pub jump_speed: f32,            // ジャンプした瞬間の上向きの速さ(m/s)
+    pub sprint_ratio: f32,          // ダッシュ中の速度の倍率
+    pub sprint_noise_radius: f32,   // ダッシュ中の足音が聞こえる半径
+    pub stamina_drain_time: f32,    // ダッシュし続けて満タンのスタミナが空になる時間(秒、0以下なら減らない)
+    pub stamina_regen_time: f32,    // ダッシュしていない間に空のスタミナが満タンになる時間(秒)
+    pub walk_vision_ratio: f32,     // 歩いているプレイヤーを見つけられる距離の倍率(ダッシュ中は視界範囲いっぱい)
+    pub sneak_vision_ratio: f32,    // スニーキング中と止まっているプレイヤーを見つけられる距離の倍率
+    pub turn_speed: f32,            // プレイヤーが動く向きへ体を回す速さ(1秒あたり、0以下ならすぐに向く)
+    pub takedown_range: f32,        // 背後から敵を気絶させられる距離(地面に沿った距離、m)
+    pub takedown_angle: f32,        // 敵の正面からこの角度(度)より後ろにいると、背後を取れる
+    pub takedown_time: f32,         // 気絶した敵が起き上がるまでの時間(秒、0以下なら起き上がらない)
+    pub alarm_time: f32,            // 警報が出てから解けるまでの時間(秒)
+    pub alarm_decay_time: f32,      // 警報が解ける前に、敵の速さと視界が少しずつ元に戻る時間(秒)
+    pub alarm_speed_ratio: f32,     // 警報が出ている間の敵の速度の倍率
+    pub alarm_vision_ratio: f32,    // 警報が出ている間の敵の視界範囲と視界角度の倍率
+    pub waypoint_pause: f32,        // 敵が巡回地点で立ち止まって見回す時間(秒、0なら立ち止まらない)
+    pub look_around_angle: f32,     // 立ち止まった敵が左右に首を振る角度(度)
+    pub noise_masking_ratio: f32,   // 周りの音(ラジオや機械)の近くでプレイヤーが立てた音が聞こえる半径の倍率
+}
+
+impl Default for StealthConfig {
+    fn default() -> Self {
+        Self {
+            player_speed: 5.0,
+            sneak_ratio: 0.3,
+            crouch_ratio: 0.5,
+            crouch_vision_ratio: 0.5,
+            enemy_speed: 4.0,
+            vision_range: 10.0,
+            vision_angle: 45.0,
+            run_noise_radius: 8.0,
+            sneak_noise_radius: 0.0,
+            landing_noise_radius: 12.0,
+            investigate_time: 3.0,
+            detection_time: 1.5,
+            detection_drain_time: 3.0,
+            footstep_volume: 0.6,
+            enemy_footstep_volume: 0.8,
+            enemy_footstep_range: 15.0,
+            throw_speed: 10.0,
+            throw_cooldown: 1.0,
+            rock_noise_radius: 10.0,
+            day_light: 0.6,
+            night_light: 0.2,
+            dark_vision_ratio: 0.4,
+            gravity: 20.0,
+            jump_speed: 7.0,
+            sprint_ratio: 1.8,
+            sprint_noise_radius: 14.0,
+            stamina_drain_time: 4.0,
+            stamina_regen_time: 6.0,
+            walk_vision_ratio: 0.7,
+            sneak_vision_ratio: 0.3,
+            turn_speed: 10.0,
+            takedown_range: 1.5,
+            takedown_angle: 120.0,
+            takedown_time: 20.0,
+            alarm_time: 20.0,
+            alarm_decay_time: 5.0,
+            alarm_speed_ratio: 1.3,
+            alarm_vision_ratio: 1.25,
+            waypoint_pause: 2.0,
+            look_around_angle: 60.0,
+            noise_masking_ratio: 0.3,
+        }
+    }
+}
+
+impl StealthConfig {
+    /// 明かりのないところの明るさ(昼と夜で変わる)
+    pub fn ambient_light(&self, period: DayPeriod) -> f32 {
+        match period {
+            DayPeriod::Day => self.day_light,
+            DayPeriod::Night => self.night_light,
+        }
+    }
+
+    /// 明るさ(0〜1)に応じた、プレイヤーを見つけられる距離の倍率
+    /// 真っ暗なら`dark_vision_ratio`、明るいほど1に近づく
+    pub fn light_vision_ratio(&self, light: f32) -> f32 {
+        self.dark_vision_ratio + (1.0 - self.dark_vision_ratio) * light.clamp(0.0, 1.0)
+    }
+
+    /// プレイヤーの水平の速さ(m/s)に応じた、プレイヤーを見つけられる距離の倍率
+    /// スニーキングの速さ以下(止まっているときも)なら`sneak_vision_ratio`、歩く速さで`walk_vision_ratio`、
+    /// ダッシュの速さ以上なら1になり、その間は速さに合わせて変わる
+    pub fn speed_vision_ratio(&self, speed: f32) -> f32 {
+        let sneak_speed = self.player_speed * self.sneak_ratio;
+        let walk_speed = self.player_speed;
+        let sprint_speed = self.player_speed * self.sprint_ratio;
+        if speed <= sneak_speed {
+            self.sneak_vision_ratio
+        } else if speed < walk_speed {
+            let t = (speed - sneak_speed) / (walk_speed - sneak_speed);
+            self.sneak_vision_ratio.lerp(self.walk_vision_ratio, t)
+        } else if speed < sprint_speed {
+            let t = (speed - walk_speed) / (sprint_speed - walk_speed);
+            self.walk_vision_ratio.lerp(1.0, t)
+        } else {
+            1.0
+        }
+    }
+
+    /// 足音が聞こえる半径(`amount`はスティックの倒し具合など、0〜1の移動の強さ)
+    pub fn footstep_noise_radius(&self, sneaking: bool, amount: f32) -> f32 {
+        let radius = if sneaking {
+            self.sneak_noise_radius
+        } else {
+            self.run_noise_radius
+        };
+        radius * amount.clamp(0.0, 1.0)
+    }
+
+    /// プレイヤーが見えている間に検知ゲージが溜まる速さ(1秒あたり)
+    /// `distance`と`angle`は、視界範囲と視界角の半分に対する割合(0〜1)。
+    /// 視界の端では`detection_time`秒で、目の前の正面ではその3分の1の時間で満ちる
+    pub fn detection_rate(&self, distance: f32, angle: f32) -> f32 {
+        if self.detection_time <= 0.0 {
+            return f32::INFINITY;
+        }
+        let closeness = 1.0 - distance.clamp(0.0, 1.0);
+        let centered = 1.0 - angle.clamp(0.0, 1.0);
+        (1.0 + closeness + centered) / self.detection_time
+    }
+}
+
+/// `assets/levels/level1.config.ron`から読み込むレベルの内容
+/// プレイヤーの開始位置・ゴール・障害物・壁と、敵ごとの配置と時間帯別の巡回経路を定義する。
+/// 実行中にファイルを保存すると、すぐに反映される(`build_level`で配置し直す)
+#[derive(Asset, Resource, Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct LevelLayout {
+    pub day_length: f32,                // 昼と夜を合わせた1日の長さ(秒、0以下なら時間が進まない)
+    pub start_hour: f32,                // 読み込んだときの時刻(0〜24時)
+    pub player_start: Vec3,             // プレイヤーの開始位置(リスタートでもここに戻る)
+    pub goal: Option<Vec3>,             // ゴールの位置(Noneなら置かない)
+    pub checkpoints: Vec<Vec3>,         // チェックポイントの位置(通ると、見つかったときにそこから再開する)
+    pub obstacles: Vec<Obstacle>,       // 障害物(敵の視界を遮る)
+    pub walls: Vec<WallPlace>,          // 壁(端から端まで立て、通り抜けられず、敵の視界も遮る)
+    pub hiding_spots: Vec<HidingPlace>, // 隠れ場所(ロッカーや茂み)
+    pub light_zones: Vec<LightPlace>,   // 明かり(照らされたところでは遠くから見つかる)
+    pub noise_sources: Vec<NoiseSourcePlace>, // 周りの音を立てる音源(近くで立てた音が聞こえにくくなる)
+    pub keycards: Vec<KeycardPlace>,    // 拾えるカードキー
+    pub doors: Vec<DoorPlace>,          // 対応するカードキーを持っていると開けられるドア
+    pub tripwires: Vec<TripwirePlace>,  // 仕掛け線(プレイヤーが触れると警報が出る)
+    pub cameras: Vec<SecurityCameraPlace>, // 監視カメラ(プレイヤーが映ると警報が出る)
+    pub guards: Vec<GuardSchedule>,     // 敵ごとの配置と巡回の予定
+}
+
+impl Default for LevelLayout {
+    /// ファイルを読み込むまでは、昼夜を問わず四角形の経路を巡回する
+    fn default() -> Self {
+        Self {
+            day_length: 120.0,
+            start_hour: 8.0,
+            player_start: Vec3::new(0.0, 1.0, 8.0),
+            goal: None,
+            checkpoints: Vec::new(),
+            obstacles: vec![Obstacle {
+                position: Vec3::new(0.0, 5.0, 0.0), // 落下するキューブ
+                size: Vec3::ONE,
+                dynamic: true,
+            }],
+            walls: Vec::new(),
+            hiding_spots: Vec::new(),
+            light_zones: Vec::new(),
+            noise_sources: Vec::new(),
+            keycards: Vec::new(),
+            doors: Vec::new(),
+            tripwires: Vec::new(),
+            cameras: Vec::new(),
+            guards: vec![GuardSchedule {
+                name: "Enemy".to_owned(),
+                spawn: Vec3::new(5.0, 1.0, 5.0),
+                speed: None,
+                vision_range: None,
+                vision_angle: None,
+                routes: vec![PatrolRoute {
+                    time: PatrolTime::Always,
+                    waypoints: vec![
+                        Waypoint::new(Vec3::new(5.0, 1.0, 5.0)),   // 1. 右前
+                        Waypoint::new(Vec3::new(-5.0, 1.0, 5.0)),  // 2. 左前
+                        Waypoint::new(Vec3::new(-5.0, 1.0, -5.0)), // 3. 左後
+                        Waypoint::new(Vec3::new(5.0, 1.0, -5.0)),  // 4. 右後
+                    ],
+                }],
+            }],
+        }
+    }
+}
+
+impl LevelLayout {
+    /// 指定した名前の敵が、指定した時間帯に巡回する経路
+    /// 時間帯が一致する経路を優先し、なければ`Always`の経路を使う
+    pub fn route_for(&self, name: &str, period: DayPeriod) -> Option<&PatrolRoute> {
+        let guard = self.guards.iter().find(|guard| guard.name == name)?;
+        guard
+            .routes
+            .iter()
+            .find(|route| route.time == PatrolTime::from(period))
+            .or_else(|| {
+                guard
+                    .routes
+                    .iter()
+                    .find(|route| route.time == PatrolTime::Always)
+            })
+    }
+
+    /// `route_for`と同じ経路を、書き換えられるように返す
+    pub fn route_for_mut(&mut self, name: &str, period: DayPeriod) -> Option<&mut PatrolRoute> {
+        let guard = self.guards.iter_mut().find(|guard| guard.name == name)?;
+        let index = guard
+            .routes
+            .iter()
+            .position(|route| route.time == PatrolTime::from(period))
+            .or_else(|| {
+                guard
+                    .routes
+                    .iter()
+                    .position(|route| route.time == PatrolTime::Always)
+            })?;
+        guard.routes.get_mut(index)
+    }
+}
+
+/// 1体の敵の配置と巡回の予定(`Name`が一致する敵に適用する)
+/// 速度と視界は敵ごとに変えられ、省略すると設定ファイル(`StealthConfig`)の値を使う
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GuardSchedule {
+    pub name: String, // 敵の名前
+    #[serde(default)]
+    pub spawn: Vec3, // 敵の初期位置(最初の巡回地点の方を向く)
+    pub routes: Vec<PatrolRoute>, // 時間帯ごとの巡回経路
+    #[serde(default)]
+    pub speed: Option<f32>, // 敵の移動速度
+    #[serde(default)]
+    pub vision_range: Option<f32>, // 敵の視界範囲
+    #[serde(default)]
+    pub vision_angle: Option<f32>, // 敵の視界角度(度)
+}
+
+impl GuardSchedule {
+    /// この敵の視界範囲・視界角度・移動速度(省略した値は設定ファイルの値)
+    pub fn parameters(&self, config: &StealthConfig) -> (f32, f32, f32) {
+        (
+            self.vision_range.unwrap_or(config.vision_range),
+            self.vision_angle.unwrap_or(config.vision_angle),
+            self.speed.unwrap_or(config.enemy_speed),
+        )
+    }
+
+    /// 初期位置で向く向き(初期位置と違う最初の巡回地点の方。なければ-Z方向)
+    pub fn spawn_rotation(&self) -> Quat {
+        self.routes
+            .iter()
+            .flat_map(|route| &route.waypoints)
+            .find(|waypoint| waypoint.position.distance(self.spawn) > 0.2)
+            .map_or(Quat::IDENTITY, |waypoint| {
+                Transform::from_translation(self.spawn)
+                    .looking_at(waypoint.position, Vec3::Y)
+                    .rotation
+            })
+    }
+}
+
+/// レベルに置く直方体の障害物
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Obstacle {
+    pub position: Vec3, // 中心の位置
+    pub size: Vec3,     // 各辺の長さ
+    #[serde(default)]
+    pub dynamic: bool, // 重力で落ちたり押されたりするか(falseなら固定)
+}
+
+/// レベルに置く壁(地面に`from`から`to`まで立てる。高さの値は使わない)
+/// 高さと厚さは省略すると`WALL_HEIGHT`と`WALL_THICKNESS`
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WallPlace {
+    pub from: Vec3, // 壁の一方の端
+    pub to: Vec3,   // 壁のもう一方の端
+    #[serde(default)]
+    pub height: Option<f32>, // 壁の高さ
+    #[serde(default)]
+    pub thickness: Option<f32>, // 壁の厚さ
+}
+
+impl WallPlace {
+    /// 高さと厚さが標準の壁
+    pub fn new(from: Vec3, to: Vec3) -> Self {
+        Self {
+            from,
+            to,
+            height: None,
+            thickness: None,
+        }
+    }
+
+    /// 壁の直方体の各辺の長さ(長さ・高さ・厚さ)
+    /// 端に厚さの半分ずつ足して、つないだ壁の角に隙間ができないようにする
+    pub fn size(&self) -> Vec3 {
+        let thickness = self.thickness.unwrap_or(WALL_THICKNESS);
+        let length = (self.to - self.from).with_y(0.0).length();
+        Vec3::new(
+            length + thickness,
+            self.height.unwrap_or(WALL_HEIGHT),
+            thickness,
+        )
+    }
+
+    /// 壁の直方体の置き方(中心が地面から高さの半分の位置で、長さの向きが`from`から`to`)
+    pub fn transform(&self) -> Transform {
+        let along = (self.to - self.from).with_y(0.0);
+        let center = (self.from + self.to) / 2.0;
+        Transform::from_xyz(center.x, self.size().y / 2.0, center.z)
+            .with_rotation(Quat::from_rotation_y((-along.z).atan2(along.x)))
+    }
+}
+
+/// レベルに置く隠れ場所(ロッカーや茂み。入れるように、コライダーは付けない)
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct HidingPlace {
+    pub position: Vec3, // 中心の位置
+    pub size: Vec3,     // 各辺の長さ
+}
+
+/// レベルに置く明かり(街灯や照明)
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct LightPlace {
+    pub position: Vec3,  // 明かりの位置
+    pub radius: f32,     // 照らす範囲の半径(地面に沿った距離)
+    pub brightness: f32, // 中心の明るさ(0〜1、端に向かって暗くなる)
+}
+
+/// 巡回経路
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PatrolRoute {
+    #[serde(default)]
+    pub time: PatrolTime,         // この経路を使う時間帯
+    pub waypoints: Vec<Waypoint>, // 順番に巡回する地点
+}
+
+/// 巡回経路を使う時間帯
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PatrolTime {
+    #[default]
+    Always, // 昼も夜も使う
+    Day,    // 昼だけ使う
+    Night,  // 夜だけ使う
+}
+
+impl From<DayPeriod> for PatrolTime {
+    fn from(period: DayPeriod) -> Self {
+        match period {
+            DayPeriod::Day => PatrolTime::Day,
+            DayPeriod::Night => PatrolTime::Night,
+        }
+    }
+}
+
+/// 巡回経路の地点
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub struct Waypoint {
+    pub position: Vec3, // 地点の位置
+    #[serde(default)]
+    pub speed: Option<f32>, // この地点へ向かうときの速度(Noneなら敵の移動速度)
+    #[serde(default)]
+    pub pause: Option<f32>, // この地点で立ち止まって見回す時間(秒、Noneなら設定ファイルの値)
+}
+
+impl Waypoint {
+    /// 敵の移動速度で向かい、設定ファイルの時間だけ立ち止まる地点
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            speed: None,
+            pause: None,
+        }
+    }
+
+    /// この地点へ向かうときの速度を指定する
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// この地点で立ち止まって見回す時間を指定する(0なら立ち止まらない)
+    pub fn with_pause(mut self, pause: f32) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+}
+
+/// 昼か夜か
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayPeriod {
+    Day,   // 6時から18時まで
+    Night, // 18時から翌6時まで
+}
+
+/// ゲーム内の時刻(`LevelLayout::day_length`秒で1日が過ぎる)
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct TimeOfDay {
+    pub hours: f32, // 0〜24時
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self { hours: 8.0 }
+    }
+}
+
+impl TimeOfDay {
+    /// 現在の時間帯
+    pub fn period(&self) -> DayPeriod {
+        if (6.0..18.0).contains(&self.hours) {
+            DayPeriod::Day
+        } else {
+            DayPeriod::Night
+        }
+    }
+}
+
+/// ゲームオーバーと結果画面のUIのマーカーコンポーネント(やり直すときに消す)
+#[derive(Component)]
+pub struct GameOverUI;
+
+/// レベルのファイルから配置した障害物とゴールのマーカーコンポーネント
+/// (レベルが変わったら消して配置し直す。敵は名前で対応付けて残す)
+#[derive(Component)]
+struct LevelEntity;
+
+/// ゴールの領域のコンポーネント(`Transform`の位置を中心とする円柱。高さは問わない)
+/// プレイヤーが見つからずに中に入ると、クリアになる
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GoalZone {
+    pub radius: f32, // 半径
+}
+
+impl GoalZone {
+    /// `point`がこのゴールの中にあるか(地面に沿った距離で調べる)
+    pub fn contains(&self, transform: &Transform, point: Vec3) -> bool {
+        (point - transform.translation).xz().length() <= self.radius
+    }
+}
+
+/// 今回のプレイの記録(リスタートで最初からになる。チェックポイントから再開しても続ける)
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct RunStats {
+    pub elapsed: f32,      // プレイしている時間(秒、ゲームオーバー中やメニューでは進めない)
+    pub detections: u32,   // 敵に気づかれ始めた回数
+    pub distractions: u32, // 気をそらすために投げた石の数
+}
+
+impl RunStats {
+    /// クリアしたときの得点(速く、気づかれず、石を使わないほど高い。0より小さくはならない)
+    pub fn score(&self) -> u32 {
+        let penalty = self.elapsed * SCORE_PER_SECOND
+            + self.detections as f32 * SCORE_PER_DETECTION
+            + self.distractions as f32 * SCORE_PER_DISTRACTION;
+        (BASE_SCORE - penalty).max(0.0).round() as u32
+    }
+}
+
+/// かかった時間の表示(分:秒)
+fn format_run_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.0).floor();
+    let seconds = seconds - minutes * 60.0;
+    format!("{minutes}:{seconds:04.1}")
+}
+
+/// 時刻の表示のマーカーコンポーネント
+#[derive(Component)]
+struct ClockText;
+
+/// プレイしている時間の表示のマーカーコンポーネント
+#[derive(Component)]
+struct RunTimerText;
+
+/// 検知ゲージの目の表示(白目)のマーカーコンポーネント(どれかの敵のゲージが溜まっている間だけ表示する)
+#[derive(Component)]
+struct DetectionEye;
+
+/// 検知ゲージの目の瞳のマーカーコンポーネント(いちばん溜まったゲージに合わせて大きく、赤くなる)
+#[derive(Component)]
+struct DetectionPupil;
+
+/// 隠れ場所に入っている間の表示(「隠れている」)のマーカーコンポーネント
+#[derive(Component)]
+struct HidingText;
+
+/// プレイヤーの明るさの表示(宝石)のマーカーコンポーネント(明るいところにいるほど明るく光る)
+#[derive(Component)]
+struct LightGem;
+
+/// 隠れ場所の領域のコンポーネント(`Transform`の位置を中心とする、軸に沿った直方体)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct HidingSpot {
+    pub half_size: Vec3, // 各辺の長さの半分
+}
+
+impl HidingSpot {
+    /// `point`がこの隠れ場所の中にあるか
+    pub fn contains(&self, transform: &Transform, point: Vec3) -> bool {
+        (point - transform.translation)
+            .abs()
+            .cmple(self.half_size)
+            .all()
+    }
+}
+
+/// プレイヤーが隠れ場所に入っている状態のコンポーネント
+/// 隠れ場所の中でしゃがんでいる(`Crouching`)間は、敵の視界に入っても見つからない
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Hiding {
+    pub spot: Entity,   // 入っている隠れ場所
+    pub crouched: bool, // しゃがんでいるか
+}
+
+/// 明かりの照らす範囲のコンポーネント(`Transform`の位置を中心とする円。高さは問わない)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct LightZone {
+    pub radius: f32,     // 照らす範囲の半径
+    pub brightness: f32, // 中心の明るさ(0〜1)
+}
+
+impl LightZone {
+    /// `point`での明るさ(中心から範囲の端まで、まっすぐ暗くなる)
+    pub fn light_at(&self, transform: &Transform, point: Vec3) -> f32 {
+        if self.radius <= 0.0 {
+            return 0.0;
+        }
+        let distance = (point - transform.translation).xz().length();
+        self.brightness * (1.0 - distance / self.radius).max(0.0)
+    }
+}
+
+/// プレイヤーの立っている場所の明るさのコンポーネント(0〜1、`update_illumination`が毎フレーム求める)
+/// 暗いところほど、敵は近づかないとプレイヤーを見つけられない
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Illumination {
+    pub level: f32, // 明るさ(0なら真っ暗、1なら明るい)
+}
+
+impl Default for Illumination {
+    /// 求めるまでは明るいところにいるものとする
+    fn default() -> Self {
+        Self { level: 1.0 }
+    }
+}
+
+/// プレイヤーキャラクターのコンポーネント
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+#[require(Footsteps, Illumination, VerticalVelocity, Facing)]
+pub struct Player;
+
+/// プレイヤーが向こうとしている水平の向き(`player_input`が動いている方向に変え、`turn_player`が体をそちらへ回す)
+/// 止まっている間は最後に動いた向きのまま。背後から近づく操作や、物陰から覗く操作で使う
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Facing(pub Dir3);
+
+impl Default for Facing {
+    /// 最初は-Z方向(敵と同じ、Transformの前方)を向く
+    fn default() -> Self {
+        Self(Dir3::NEG_Z)
+    }
+}
+
+impl Facing {
+    /// この向きを前方にする、Y軸まわりの回転
+    pub fn rotation(self) -> Quat {
+        Quat::from_rotation_y(f32::atan2(-self.0.x, -self.0.z))
+    }
+}
+
+/// プレイヤーが今のフレームに動こうとしている水平の速さ(m/s、`player_input`が求める)
+/// 速く動いているほど、敵に遠くから見つかる。このコンポーネントのないプレイヤーは、速さで見つかりやすさを変えない
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerSpeed(pub f32);
+
+/// プレイヤーの上下方向の速さ(m/s、上が正)
+/// 接地していない間は重力で下向きに速くなり、ジャンプすると設定の速さで上に向かう
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct VerticalVelocity(pub f32);
+
+/// しゃがんでいるプレイヤーのマーカーコンポーネント(しゃがむボタンを押している間だけ付く)
+/// しゃがんでいる間は遅くなり、敵は視界範囲より近づかないと見つけられない
+#[derive(Component, Debug, Default)]
+pub struct Crouching;
+
+/// 敵キャラクターのコンポーネント
+/// 背後を取れている間だけ操作でき(`Interactable`)、操作すると気絶する
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[require(Detection, Footsteps, PatrolPause, Interactable = takedown::takedown_interactable())]
+pub struct Enemy {
+    pub vision_range: f32,            // 敵の視界範囲
+    pub vision_angle: f32,            // 敵の視界角度
+    pub patrol_points: Vec<Waypoint>, // パトロールポイント(レベルの巡回の予定から設定する)
+    pub current_patrol_index: usize,  // 現在のパトロールポイントのインデックス
+    pub speed: f32,                   // 敵の移動速度
+    pub initial_position: Vec3,       // 敵の初期位置
+    pub initial_rotation: Quat,       // 敵の初期向き
+}
+
+/// 敵がプレイヤーに気づきかけている度合い(検知ゲージ)のコンポーネント
+/// プレイヤーが視界に入っている間は溜まり(近いほど、視界の中心に近いほど速い)、見えなくなると減る。
+/// どれかの敵のゲージが満ちるとゲームオーバーになる
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub level: f32, // 0(気づいていない)〜1(見つかった)
+}
+
+/// 歩いた距離から足音を鳴らすためのコンポーネント(プレイヤーと敵に付く)
+/// 前のフレームからの移動距離を足していき、`FOOTSTEP_STRIDE`を超えるたびに足音を1回鳴らす
+/// (速く動くほど足音の間隔が短くなる)
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Footsteps {
+    pub travelled: f32,              // 前の足音から歩いた距離
+    pub last_position: Option<Vec3>, // 前のフレームの位置
+}
+
+/// プレイヤーが投げた石のコンポーネント
+/// 最初に地面や壁に当たったときに音(`NoiseEvent`)を立て、近くの敵がその場所を調べに行く。
+/// 当たってから`ROCK_LIFETIME`秒で消える
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Rock {
+    pub landed: bool, // 何かに当たって音を立てたか
+    pub elapsed: f32, // 当たってからの時間(秒)
+}
+
+/// 足音を鳴らす合図(`update_footsteps`が送り、`play_footsteps`が効果音を鳴らす)
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct FootstepEvent {
+    pub enemy: bool,        // 敵の足音か(falseならプレイヤーの足音)
+    pub volume: f32,        // 音量(0〜1)
+    pub playback_rate: f32, // 再生速度(プレイヤーが速く動くほど少し高い音にする)
+}
+
+/// 効果音と音楽の合図(`play_audio_cues`が効果音を鳴らす)
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioCue {
+    Suspicion, // 敵がプレイヤーに気づき始めた(検知ゲージが溜まり始めた)
+    Detected,  // 検知ゲージが満ちて見つかった
+    BodyFound, // 敵が倒れている仲間を見つけた(プレイヤーに気づいたわけではない)
+}
+
+/// 流す音楽
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTrack {
+    Menu,     // タイトル画面
+    GameOver, // ゲームオーバー
+}
+
+/// 今の画面とゲームの状態で流す音楽(ゲーム中は音楽を流さない)
+pub fn music_for(app_state: &AppState, game_state: &GameState) -> Option<MusicTrack> {
+    match (app_state, game_state) {
+        (AppState::Menu, _) => Some(MusicTrack::Menu),
+        (AppState::Playing | AppState::Paused, GameState::GameOver) => Some(MusicTrack::GameOver),
+        _ => None,
+    }
+}
+
+/// 音楽を流すチャンネル(効果音と別に止めたり、音量を変えたりする)
+#[derive(Resource)]
+struct MusicChannel;
+
+/// 効果音と音楽
+#[derive(Resource)]
+struct GameSounds {
+    footstep: Handle<AudioSource>,        // プレイヤーの軽い足音
+    enemy_footstep: Handle<AudioSource>,  // 敵の重い足音
+    suspicion: Handle<AudioSource>,       // 敵が気づき始めたときの音
+    detected: Handle<AudioSource>,        // 見つかったときの大きな音
+    menu_music: Handle<AudioSource>,      // タイトル画面の音楽(ループする)
+    game_over_music: Handle<AudioSource>, // ゲームオーバーの音楽(ループする)
+    radio: Handle<AudioSource>,           // ラジオの音(ループする)
+    machinery: Handle<AudioSource>,       // 機械の音(ループする)
+}
+
+/// プレイヤーが立てた音(足音や着地の音)
+/// 音のした位置から`radius`以内にいる敵が聞きつけて、その場所を調べに行く
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct NoiseEvent {
+    pub position: Vec3, // 音のした位置
+    pub radius: f32,    // 音が聞こえる半径
+}
+
+/// 音を聞きつけた敵が、その場所を調べている状態のコンポーネント
+/// 音のした場所へ向かい、着いたら`remaining`秒だけ辺りを調べてから巡回に戻る
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Investigating {
+    pub target: Vec3,   // 調べに行く場所(敵の高さに合わせる)
+    pub remaining: f32, // 着いてから調べる残り時間(秒)
+}
+
+/// 巡回地点に着いた敵が立ち止まり、左右を見回している状態のコンポーネント
+/// `remaining`が0より大きい間は、着いたときの向き(`facing`)を中心に首を振り、0になったら次の地点へ向かう
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PatrolPause {
+    pub remaining: f32, // 立ち止まっている残り時間(秒、0なら巡回中)
+    pub duration: f32,  // 立ち止まる時間(秒)
+    pub facing: Quat,   // 着いたときの向き(見回す中心)
+}
+
+impl Default for PatrolPause {
+    fn default() -> Self {
+        Self {
+            remaining: 0.0,
+            duration: 0.0,
+            facing: Quat::IDENTITY,
+        }
+    }
+}
+
+impl PatrolPause {
+    /// 見回している向き
+    /// 立ち止まっている間に、左へ`angle`度、右へ`angle`度と1往復して、着いたときの向きに戻る
+    pub fn rotation(&self, angle: f32) -> Quat {
+        if self.duration <= 0.0 {
+            return self.facing;
+        }
+        let progress = 1.0 - self.remaining / self.duration;
+        let sweep = angle.to_radians() * (progress * std::f32::consts::TAU).sin();
+        self.facing * Quat::from_rotation_y(sweep)
+    }
+}
+
+/// 敵の視界(範囲と角度)を地面に表す半透明の扇形のコンポーネント(敵の子)
+/// 敵と一緒に回転し、作ったときの範囲と角度が敵の設定と変わったらメッシュを作り直す
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct VisionCone {
+    pub range: f32, // 扇形の半径(敵の視界範囲)
+    pub angle: f32, // 扇形の中心角(敵の視界角度、度)
+}
+
+/// プレイヤーの周りを回るカメラの向きと距離を管理するコンポーネント
+/// マウスの右ボタンを押しながら動かすか、右スティックで回す
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraController {
+    pub yaw: f32,          // 水平方向の回転(ラジアン、0ならプレイヤーの後ろ(+Z側)から-Z方向を見る)
+    pub pitch: f32,        // 見下ろす角度(ラジアン)
+    pub min_pitch: f32,    // 見下ろす角度の最小値(地面すれすれまで下げない)
+    pub max_pitch: f32,    // 見下ろす角度の最大値(真上まで上げない)
+    pub distance: f32,     // プレイヤーからの距離(壁があればそれより近づく)
+    pub min_distance: f32, // 最小距離
+    pub max_distance: f32, // 最大距離
+    pub zoom_speed: f32,   // ズーム速度
+    pub mouse_speed: f32,  // マウスを1ピクセル動かしたときに回る角度(ラジアン)
+    pub stick_speed: f32,  // 右スティックを倒しきったときに回る速さ(ラジアン/秒)
+}
+
+/// カメラコントローラーのデフォルト値
+impl Default for CameraController {
+    /// 高さ3m、距離8m(水平)ほどの後ろから見下ろす
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.35,
+            min_pitch: 0.05,
+            max_pitch: 1.3,
+            distance: 8.5,
+            min_distance: 3.0,
+            max_distance: 20.0,
+            zoom_speed: 5.0,
+            mouse_speed: 0.005,
+            stick_speed: 2.5,
+        }
+    }
+}
+
+impl CameraController {
+    /// 注視点から見たカメラの方向(長さ1)
+    pub fn direction(&self) -> Vec3 {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, -self.pitch, 0.0) * Vec3::Z
+    }
+}
+
+/// ステルスゲームのサンプル本体のプラグイン
+/// ウィンドウ関連のDefaultPluginsは含まないので、main側で追加する
+pub struct ExaminaClonePlugin;
+
+impl Plugin for ExaminaClonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            AudioPlugin,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            ScreenshotPlugin::new("examina_clone"), // F12でスクリーンショット
+            ActionsPlugin::new(action_map()),       // キーボードとゲームパッドの操作
+            ConfigPlugin::<StealthConfig>::new("config/examina_clone.config.ron"), // 速度や視界の設定
+            ConfigPlugin::<LevelLayout>::new(LEVELS[0]), // 敵の巡回の予定(最初のレベルから)
+            TimeControlPlugin, // Pで一時停止、-/=でスローモーション
+            AppStatePlugin::new("title"), // メニュー・読み込み中・一時停止の画面
+            LocalizationPlugin::new()
+                .bundle(Locale::En, include_str!("../assets/locale/en.ron"))
+                .bundle(Locale::Ja, include_str!("../assets/locale/ja.ron")), // F2で英語・日本語を切り替え
+            CrashWatchPlugin::<GameState>::default(), // クラッシュレポートにゲームオーバーかどうかを含める
+            RouteEditorPlugin, // F6で巡回経路を表示し、地点をドラッグして動かす
+            levels::LevelsPlugin, // ゴールしたら次のレベルへ、メニューでレベルを選ぶ
+            difficulty::DifficultyPlugin, // メニューで難易度を選ぶ
+            DebugOverlayPlugin, // F1でプレイヤーと敵の状態とログを表示
+            (
+                interaction::InteractionPlugin, // 近くのものの案内を出し、Eで操作する
+                takedown::TakedownPlugin, // 背後を取れている敵を操作すると気絶させる
+                alarm::AlarmPlugin, // 倒れている敵が見つかったり、仕掛け線に触れたり、監視カメラに映ったりすると警報が出て、敵が集まる
+                ambient_noise::AmbientNoisePlugin, // ラジオや機械の音が鳴り、近くで立てた音をかき消す
+                keycards::KeycardsPlugin, // カードキーを拾い、合うドアを開ける
+                minimap::MinimapPlugin, // Mでミニマップを表示・非表示
+                sprint::SprintPlugin, // 左Ctrlでスタミナを使ってダッシュする
+                checkpoints::CheckpointsPlugin, // 通ったチェックポイントから再開し、F5/F9で保存・読み込みする
+            ), // プラグインのタプルは15個までなので、まとめる
+        ))
+        .init_resource::<GameState>() // ゲーム状態の初期化
+        .add_event::<NoiseEvent>() // プレイヤーが立てた音
+        .add_event::<FootstepEvent>() // 足音の効果音
+        .add_event::<AudioCue>() // 気づかれたときと見つかったときの効果音
+        .add_audio_channel::<MusicChannel>()
+        .init_resource::<TimeOfDay>()
+        .init_resource::<RunStats>()
+        .init_resource::<BestTimes>()
+        .register_type::<Player>() // チェックポイントの保存・復元のために型を登録
+        .register_type::<Enemy>()
+        .register_type::<TimeOfDay>()
+        .add_systems(Startup, (setup_scene, load_sounds, best_times::load_best_times))
+        .add_systems(
+            Update,
+            (
+                (update_crouch, resize_crouching_player)
+                    .chain()
+                    .before(player_input), // しゃがむ・立ち上がる
+                (player_input, apply_gravity, turn_player)
+                    .chain()
+                    .before(restore_checkpoint)
+                    .before(restart_game), // 移動に重力とジャンプを足し、動く向きへ回る(やり直しの前に)
+                apply_config.before(enemy_vision_system),
+                (
+                    reset_time_of_day.run_if(resource_changed::<LevelLayout>),
+                    advance_time_of_day,
+                    apply_patrol_schedule,
+                    enemy_patrol_system,
+                )
+                    .chain(),
+                update_hiding_actions.before(interact), // 操作の名前を今の状態に合わせる
+                update_hiding
+                    .after(interact)
+                    .before(enemy_vision_system), // 隠れ場所への出入り
+                update_hiding_text.after(update_hiding),
+                (throw_rock, rock_impacts)
+                    .chain()
+                    .before(enemy_hearing_system), // 石を投げて、落ちた場所で音を立てる
+                (emit_player_noise, enemy_hearing_system, enemy_investigate_system)
+                    .chain()
+                    .after(player_input)
+                    .before(enemy_vision_system), // 足音を聞きつけた敵が音のした場所を調べる
+                enemy_vision_system,
+                update_illumination.before(enemy_vision_system), // プレイヤーの立っている場所の明るさ
+                (update_detection_eye, update_light_gem, emit_detection_cues)
+                    .after(enemy_vision_system),
+                (
+                    track_run_stats.after(emit_detection_cues),
+                    reach_goal,
+                    finish_mission.run_if(resource_changed::<GameState>),
+                )
+                    .chain()
+                    .after(enemy_vision_system), // 記録を付け、見つからずにゴールしたらクリアにして得点を出す
+                update_run_timer.run_if(resource_changed::<RunStats>),
+                update_clock,
+                (orbit_camera, camera_follow_player)
+                    .chain()
+                    .after(player_input), // プレイヤーの周りを回るカメラ
+                camera_zoom,
+                restart_game, // 見つかったら最初から(チェックポイントを通っていれば`restore_checkpoint`が再開する)
+                rumble_on_game_over.run_if(resource_changed::<GameState>),
+                (update_footsteps, play_footsteps)
+                    .chain()
+                    .after(enemy_patrol_system)
+                    .after(enemy_investigate_system), // 歩いた距離に合わせて足音を鳴らす
+            )
+                .run_if(in_state(AppState::Playing)), // メニューや一時停止中はゲームを進めない
+        )
+        .add_systems(
+            Update,
+            (enter_level, build_level)
+                .chain()
+                .run_if(resource_changed::<LevelLayout>)
+                .before(apply_config), // レベルのファイルから障害物・敵・ゴールを配置する(読み込み中も配置する)
+        )
+        .add_systems(Update, update_vision_cones.after(apply_config)) // 敵の視界の扇形を付け、設定に合わせて作り直す
+        .add_systems(
+            Update,
+            tint_vision_cones
+                .after(update_vision_cones)
+                .after(enemy_vision_system), // 警戒の段階に合わせて扇形の色を変える
+        )
+        .add_systems(Update, (play_audio_cues, update_music)); // 効果音と音楽(メニューでも鳴らす)
+
+        // コライダーの線表示はネイティブ向けのデバッグ機能(Webビルドでは無効)
+        #[cfg(feature = "debug_render")]
+        app.add_plugins(RapierDebugRenderPlugin::default());
+    }
+}
+
+fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<LevelLayout>,
+) {
+    // 地面(静的オブジェクト)
+    commands.spawn((
+        RigidBody::Fixed, // 静的リジットボディ
+        Collider::cuboid(50.0, 0.1, 50.0),
+        Mesh3d(meshes.add(Cuboid::new(100.0, 0.2, 100.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.5, 0.5), // 緑色の地面
+            ..default()
+        })),
+        Transform::from_xyz(0.0, -0.1, 0.0),
+    ));
+
+    // プレイヤーキャラクター(動的リジットボディ)
+    // 障害物・敵・ゴールはレベルのファイルから`build_level`で配置する
+    commands.spawn((
+        Player,
+        PlayerSpeed::default(), // 動く速さで敵に見つかる距離が変わる
+        Name::new("Player"), // チェックポイントの復元時の対応付けに使う
+        Checkpointed,
+        RigidBody::KinematicPositionBased, // 動的リジットボディ
+        Collider::capsule_y(PLAYER_HALF_HEIGHT, PLAYER_RADIUS), // 高さ1.8m（半分の0.9）、半径0.4m
+        KinematicCharacterController::default(), // キャラクターコントローラー
+        Transform::from_translation(level.player_start), // レベルの開始位置
+        Mesh3d(meshes.add(player_mesh(PLAYER_HALF_HEIGHT))), // プレイヤーのメッシュ
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.8, 0.2), // 緑色のプレイヤー
+            ..default()
+        })),
+    ))
+    .with_children(|parent| {
+        // 向いている方向が分かるように、顔の前に付けるバイザー(しゃがんでもカプセルからはみ出ない高さ)
+        parent.spawn((
+            Mesh3d(meshes.add(Cuboid::new(0.5, 0.15, 0.1))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.1, 0.1, 0.1),
+                ..default()
+            })),
+            Transform::from_xyz(0.0, 0.3, -PLAYER_RADIUS),
+        ));
+    });
+
+    // 時刻の表示
+    commands.spawn((
+        LocalizedText::new("clock"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        ClockText,
+    ));
+
+    // プレイしている時間(時刻の下)
+    commands.spawn((
+        LocalizedText::new("run_timer").with_arg("time", format_run_time(0.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        RunTimerText,
+    ));
+
+    // 検知ゲージの目(画面の上の中央。敵が気づきかけている間だけ表示する)
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(EYE_WIDTH),
+                height: Val::Px(EYE_WIDTH / 2.0),
+                margin: UiRect::left(Val::Px(-EYE_WIDTH / 2.0)), // 中央に揃える
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.95, 0.95, 0.9)),
+            BorderRadius::MAX, // 楕円の白目
+            Visibility::Hidden,
+            DetectionEye,
+        ))
+        .with_children(|eye| {
+            eye.spawn((
+                Node::default(), // 大きさはupdate_detection_eyeで変える
+                BackgroundColor(Color::BLACK),
+                BorderRadius::MAX,
+                DetectionPupil,
+            ));
+        });
+
+    // 隠れ場所の表示(隠れ場所に入っているときだけ表示する。入る・出るの案内は操作の案内に出す)
+    commands.spawn((
+        LocalizedText::new("hiding"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        HidingText,
+    ));
+
+    // 明るさの宝石(画面の下の中央。暗いところでは暗く、明るいところでは明るく光る)
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            left: Val::Percent(50.0),
+            width: Val::Px(LIGHT_GEM_SIZE),
+            height: Val::Px(LIGHT_GEM_SIZE),
+            margin: UiRect::left(Val::Px(-LIGHT_GEM_SIZE / 2.0)), // 中央に揃える
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(light_gem_color(1.0)),
+        BorderColor(Color::srgb(0.3, 0.3, 0.35)),
+        BorderRadius::MAX,
+        LightGem,
+    ));
+
+    // カメラの設定
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 5.0, 10.0) // カメラの位置
+            .looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y), // カメラの向き設定
+        CameraController::default(), // カメラコントローラーの初期化
+        SpatialAudioReceiver,        // 周りの音はカメラの位置で聞く
+    ));
+
+    // ライトの設定
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true, // シャドウを有効にする
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0) // ライトの位置
+            .looking_at(Vec3::ZERO, Vec3::Y), // ライトの向き設定
+    ));
+}
+
+/// プレイヤーのカプセルのメッシュ(`half_height`はコライダーと同じ、半球の中心から中心までの長さの半分)
+fn player_mesh(half_height: f32) -> Mesh {
+    Capsule3dMeshBuilder::new(
+        /* radius */ PLAYER_RADIUS,
+        /* height between hemisphere centers */ half_height * 2.0,
+        /* longitudes */ 16,
+        /* latitudes */ 8,
+    )
+    .build()
+}
+
+/// レベルのファイルから障害物・壁・明かり・カードキー・ドア・ゴール・敵を配置するシステム(レベルが変わるたびに実行する)
+/// 障害物・壁・明かり・カードキー・ドア・ゴールは作り直し、敵は名前で対応付けて、いない敵を追加し、レベルから消えた敵を消す
+/// (残った敵の巡回経路は`apply_patrol_schedule`が変える)。プレイヤーは開始位置が変わったときだけ移動する
+pub fn build_level(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<LevelLayout>,
+    config: Res<StealthConfig>,
+    built: Query<Entity, With<LevelEntity>>,
+    mut guards: Query<(Entity, &Name, &mut Enemy)>,
+    mut players: Query<&mut Transform, With<Player>>,
+    mut player_start: Local<Option<Vec3>>,
+) {
+    for entity in &built {
+        commands.entity(entity).despawn();
+    }
+    for obstacle in &level.obstacles {
+        let half_size = obstacle.size / 2.0;
+        commands.spawn((
+            if obstacle.dynamic {
+                RigidBody::Dynamic
+            } else {
+                RigidBody::Fixed
+            },
+            Collider::cuboid(half_size.x, half_size.y, half_size.z),
+            Mesh3d(meshes.add(Cuboid::from_size(obstacle.size))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: if obstacle.dynamic {
+                    Color::srgb(0.8, 0.2, 0.2) // 赤色のキューブ
+                } else {
+                    Color::srgb(0.4, 0.35, 0.3) // 茶色の壁
+                },
+                ..default()
+            })),
+            Transform::from_translation(obstacle.position),
+            LevelEntity,
+        ));
+    }
+    for wall in &level.walls {
+        let half_size = wall.size() / 2.0;
+        commands.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(half_size.x, half_size.y, half_size.z),
+            Mesh3d(meshes.add(Cuboid::from_size(wall.size()))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.55, 0.55, 0.6), // 灰色のコンクリートの壁
+                ..default()
+            })),
+            wall.transform(),
+            LevelEntity,
+        ));
+    }
+    for place in &level.hiding_spots {
+        commands.spawn((
+            hiding_spot(place.size / 2.0),
+            Mesh3d(meshes.add(Cuboid::from_size(place.size))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.2, 0.5, 0.25, 0.5), // 半透明の緑(中のプレイヤーが見える)
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })),
+            Transform::from_translation(place.position),
+            LevelEntity,
+        ));
+    }
+    for light in &level.light_zones {
+        commands.spawn((
+            LightZone {
+                radius: light.radius,
+                brightness: light.brightness,
+            },
+            PointLight {
+                intensity: 400_000.0 * light.brightness,
+                range: light.radius * 2.0, // 照らす範囲の端まで光が届くように
+                ..default()
+            },
+            Mesh3d(meshes.add(Sphere::new(0.15))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(1.0, 0.95, 0.7),
+                emissive: LinearRgba::rgb(4.0, 3.6, 2.4), // 電球
+                ..default()
+            })),
+            Transform::from_translation(light.position),
+            LevelEntity,
+        ));
+    }
+    for place in &level.noise_sources {
+        ambient_noise::spawn_noise_source(&mut commands, &mut meshes, &mut materials, place);
+    }
+    for keycard in &level.keycards {
+        keycards::spawn_keycard(&mut commands, &mut meshes, &mut materials, keycard);
+    }
+    for door in &level.doors {
+        keycards::spawn_door(&mut commands, &mut meshes, &mut materials, door);
+    }
+    for place in &level.tripwires {
+        alarm::spawn_tripwire(&mut commands, &mut meshes, &mut materials, place);
+    }
+    for place in &level.cameras {
+        alarm::spawn_security_camera(&mut commands, &mut meshes, &mut materials, place);
+    }
+    for &position in &level.checkpoints {
+        checkpoints::spawn_checkpoint_zone(&mut commands, &mut meshes, &mut materials, position);
+    }
+    if let Some(goal) = level.goal {
+        commands.spawn((
+            Mesh3d(meshes.add(Cylinder::new(1.0, 0.05))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(1.0, 0.85, 0.2),
+                emissive: LinearRgba::rgb(2.0, 1.6, 0.3), // 暗い時間帯でも見えるように光らせる
+                ..default()
+            })),
+            Transform::from_translation(Vec3::new(goal.x, 0.03, goal.z)), // 地面の少し上
+            GoalZone { radius: 1.0 },
+            LevelEntity,
+        ));
+    }
+
+    // 敵: 名前の一致する敵は初期位置だけ変え、レベルにない敵は消す
+    for (entity, name, mut enemy) in &mut guards {
+        match level.guards.iter().find(|guard| guard.name == name.as_str()) {
+            Some(guard) => {
+                let rotation = guard.spawn_rotation();
+                if (enemy.initial_position, enemy.initial_rotation) != (guard.spawn, rotation) {
+                    enemy.initial_position = guard.spawn;
+                    enemy.initial_rotation = rotation;
+                }
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    for guard in &level.guards {
+        if guards.iter().any(|(_, name, _)| name.as_str() == guard.name) {
+            continue;
+        }
+        spawn_guard(&mut commands, &mut meshes, &mut materials, guard, &config);
+    }
+
+    // プレイヤー: 開始位置が変わったら移動する(最初の配置はsetup_sceneで開始位置に置いている)
+    if player_start.is_some_and(|start| start != level.player_start) {
+        for mut transform in &mut players {
+            transform.translation = level.player_start;
+        }
+    }
+    *player_start = Some(level.player_start);
+}
+
+/// 隠れ場所のコンポーネント(中に入っているときだけ操作でき、操作すると出入りする)
+pub fn hiding_spot(half_size: Vec3) -> impl Bundle {
+    (
+        HidingSpot { half_size },
+        Interactable::new(LocalizedText::new("hide_action"))
+            .with_half_size(half_size)
+            .with_range(0.0),
+    )
+}
+
+/// レベルの予定から敵を配置する(巡回経路は`apply_patrol_schedule`が時間帯に合わせて設定する)
+fn spawn_guard(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    guard: &GuardSchedule,
+    config: &StealthConfig,
+) {
+    let rotation = guard.spawn_rotation(); // 初期向き
+    let (vision_range, vision_angle, speed) = guard.parameters(config);
+    let enemy_entity = commands
+        .spawn((
+            Enemy {
+                vision_range,
+                vision_angle,
+                patrol_points: Vec::new(), // 巡回経路はレベルの予定から設定する
+                current_patrol_index: 0,
+                speed,                         // 敵の移動速度
+                initial_position: guard.spawn, // 敵の初期位置
+                initial_rotation: rotation,    // 敵の初期向き
+            },
+            Name::new(guard.name.clone()), // レベルの予定とチェックポイントの復元時の対応付けに使う
+            Checkpointed,
+            RigidBody::KinematicPositionBased, // 動的リジットボディ
+            Collider::capsule_y(0.9, 0.4),
+            Transform::from_translation(guard.spawn).with_rotation(rotation),
+            Mesh3d(
+                meshes.add(
+                    Capsule3dMeshBuilder::new(
+                        /* radius */ 0.4, /* height between hemisphere centers */ 1.8,
+                        /* longitudes */ 16, /* latitudes */ 8,
+                    )
+                    .build(),
+                ),
+            ),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.8, 0.2, 0.2), // 赤色の敵
+                ..default()
+            })),
+        ))
+        .id();
+
+    // 敵の前面に「目」のようなマーカーを追加(子エンティティとして);
+    commands.entity(enemy_entity).with_children(|parent| {
+        parent.spawn((
+            Mesh3d(meshes.add(Sphere::new(0.15))), // 小さな球を目として使用
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.2, 0.2, 0.8), // 青色の目
+                ..default()
+            })),
+            Transform::from_xyz(0.0, 0.5, -0.4), // 敵の前面に配置
+        ));
+    });
+}
+
+/// しゃがむボタン(CキーかB)を押している間、プレイヤーに`Crouching`を付けるシステム
+pub fn update_crouch(
+    mut commands: Commands,
+    actions: Res<ButtonInput<PlayerAction>>,
+    game_state: Res<GameState>,
+    players: Query<(Entity, Has<Crouching>), With<Player>>,
+) {
+    // ゲームオーバー中やゴールした後は、ボタンを離しても姿勢を変えない
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let pressed = actions.pressed(PlayerAction::Crouch);
+    for (player, crouching) in &players {
+        if pressed && !crouching {
+            commands.entity(player).insert(Crouching);
+        } else if !pressed && crouching {
+            commands.entity(player).remove::<Crouching>();
+        }
+    }
+}
+
+/// しゃがみ始めたプレイヤーのコライダーとメッシュを低くし、立ち上がったら元に戻すシステム
+/// (足元の高さが変わらないように、中心の高さも同じだけ動かす)
+fn resize_crouching_player(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut stood_up: RemovedComponents<Crouching>,
+    crouched: Query<(), Added<Crouching>>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Collider,
+            &mut Mesh3d,
+            Has<Crouching>,
+        ),
+        With<Player>,
+    >,
+) {
+    let stood_up: Vec<Entity> = stood_up.read().collect();
+    for (entity, mut transform, mut collider, mut mesh, crouching) in &mut players {
+        let changed = if crouching {
+            crouched.contains(entity)
+        } else {
+            stood_up.contains(&entity)
+        };
+        if !changed {
+            continue;
+        }
+        let (half_height, offset) = if crouching {
+            (CROUCH_HALF_HEIGHT, CROUCH_HALF_HEIGHT - PLAYER_HALF_HEIGHT)
+        } else {
+            (PLAYER_HALF_HEIGHT, PLAYER_HALF_HEIGHT - CROUCH_HALF_HEIGHT)
+        };
+        *collider = Collider::capsule_y(half_height, PLAYER_RADIUS);
+        mesh.0 = meshes.add(player_mesh(half_height));
+        transform.translation.y += offset;
+    }
+}
+
+/// プレイヤー入力システム
+pub fn player_input(
+    actions: Res<ButtonInput<PlayerAction>>,
+    axes: Res<ActionAxes>,
+    mut query: Query<
+        (
+            &mut KinematicCharacterController,
+            &mut PlayerSpeed,
+            &mut Facing,
+            Has<Crouching>,
+            Has<Sprinting>,
+        ),
+        With<Player>,
+    >,
+    camera_query: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    config: Res<StealthConfig>,
+) {
+    // プレイ中でない(ゲームオーバーかゴールした後の)場合は何もしない
+    if *game_state != GameState::Playing {
+        return; // ゲームオーバー状態ではプレイヤー入力を無視
+    }
+
+    // 動きを制御するための変数
+    let Ok(camera_transform) = camera_query.single() else {
+        return; // カメラが存在しない場合は何もしない
+    };
+
+    // カメラの前方向と右方向を取得（Vec3に変換）
+    let forward = camera_transform.forward().as_vec3();
+    let right = camera_transform.right().as_vec3();
+    // 矢印キー・十字キー・左スティックの入力をカメラ基準の方向に変換
+    let mut direction = forward * axes.movement.y + right * axes.movement.x;
+
+    direction.y = 0.0; // 垂直方向の動きを無効化
+
+    // スニーキング判定
+    let is_sneaking = actions.pressed(PlayerAction::Sneak);
+    let base_speed = config.player_speed;
+    let speed = if is_sneaking {
+        base_speed * config.sneak_ratio // 設定した倍率の速度
+    } else {
+        base_speed
+    };
+
+    if direction.length_squared() > 0.0 {
+        // 動いている方向を向く(体は`turn_player`が少しずつ回す)
+        let heading = Dir3::new(direction).ok();
+        // スティックを浅く倒したときはゆっくり進む
+        direction = direction.normalize() * axes.movement.length() * speed * time.delta_secs();
+
+        for (mut controller, mut player_speed, mut facing, crouching, sprinting) in &mut query {
+            // しゃがんでいる間とダッシュしている間は、さらに設定した倍率の速度
+            let ratio = if crouching {
+                config.crouch_ratio
+            } else if sprinting {
+                config.sprint_ratio
+            } else {
+                1.0
+            };
+            controller.translation = Some(direction * ratio);
+            // 敵の視界検知で使う速さ(1秒あたり)
+            player_speed.set_if_neq(PlayerSpeed(
+                axes.movement.length().min(1.0) * speed * ratio,
+            ));
+            if let Some(heading) = heading {
+                facing.set_if_neq(Facing(heading));
+            }
+        }
+    } else {
+        for (mut controller, mut player_speed, _, _, _) in &mut query {
+            controller.translation = Some(Vec3::ZERO);
+            player_speed.set_if_neq(PlayerSpeed(0.0));
+        }
+    }
+}
+
+/// プレイヤーの体を`Facing`の向きへ滑らかに回すシステム
+/// 向きの差が1秒あたり`turn_speed`の割合で縮まる(フレームの長さによらず同じ速さで回る)
+pub fn turn_player(
+    time: Res<Time>,
+    config: Res<StealthConfig>,
+    mut players: Query<(&mut Transform, &Facing), With<Player>>,
+) {
+    let t = if config.turn_speed > 0.0 {
+        1.0 - (-config.turn_speed * time.delta_secs()).exp()
+    } else {
+        1.0
+    };
+    for (mut transform, facing) in &mut players {
+        let target = facing.rotation();
+        if transform.rotation != target {
+            transform.rotation = transform.rotation.slerp(target, t);
+        }
+    }
+}
+
+/// 重力とジャンプのシステム
+/// キャラクターコントローラーの前フレームの結果で接地を判定し、上下の速さを重力で変えて、
+/// `player_input`が決めた水平の移動量に上下の移動量を足す
+pub fn apply_gravity(
+    actions: Res<ButtonInput<PlayerAction>>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    config: Res<StealthConfig>,
+    mut query: Query<
+        (
+            &mut KinematicCharacterController,
+            &mut VerticalVelocity,
+            Option<&KinematicCharacterControllerOutput>,
+            Has<Crouching>,
+        ),
+        With<Player>,
+    >,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    for (mut controller, mut velocity, output, crouching) in &mut query {
+        let grounded = output.is_some_and(|output| output.grounded);
+        if grounded && velocity.0 < 0.0 {
+            velocity.0 = 0.0; // 着地したら落ちるのをやめる
+        }
+        // 上昇中に天井に当たったら、上昇をやめる
+        let hit_ceiling = output.is_some_and(|output| {
+            output.desired_translation.y > 0.0
+                && output.effective_translation.y < output.desired_translation.y * 0.5
+        });
+        if velocity.0 > 0.0 && hit_ceiling {
+            velocity.0 = 0.0;
+        }
+        // しゃがんでいる間は跳べない
+        if grounded && !crouching && actions.just_pressed(PlayerAction::Jump) {
+            velocity.0 = config.jump_speed;
+        }
+
+        // 接地している間も少しずつ下に押し付けて、接地の判定を保つ
+        velocity.0 -= config.gravity * delta;
+        let translation = controller.translation.unwrap_or(Vec3::ZERO);
+        controller.translation = Some(translation + Vec3::Y * velocity.0 * delta);
+    }
+}
+
+/// 隠れ場所に出入りするシステム
+/// 隠れ場所の中で操作すると入り、もう一度操作するか隠れ場所から出ると出る。
+/// 入っている間は、しゃがんでいるかを`Hiding`に記録する
+pub fn update_hiding(
+    mut commands: Commands,
+    mut interactions: EventReader<InteractionEvent>,
+    game_state: Res<GameState>,
+    mut players: Query<(Entity, &Transform, Option<&mut Hiding>, Has<Crouching>), With<Player>>,
+    spots: Query<(&Transform, &HidingSpot)>,
+) {
+    if *game_state != GameState::Playing {
+        interactions.clear();
+        return;
+    }
+    for interaction in interactions.read() {
+        if !spots.contains(interaction.target) {
+            continue;
+        }
+        let Ok((player, _, hiding, crouched)) = players.get(interaction.player) else {
+            continue;
+        };
+        if hiding.is_some() {
+            commands.entity(player).remove::<Hiding>();
+        } else {
+            commands.entity(player).insert(Hiding {
+                spot: interaction.target,
+                crouched,
+            });
+        }
+    }
+    for (player, transform, hiding, crouched) in &mut players {
+        let Some(mut hiding) = hiding else {
+            continue;
+        };
+        // 隠れ場所がレベルの読み込みで消えたときも出る
+        let inside = spots
+            .get(hiding.spot)
+            .is_ok_and(|(spot_transform, spot)| spot.contains(spot_transform, transform.translation));
+        if !inside {
+            commands.entity(player).remove::<Hiding>();
+        } else if hiding.crouched != crouched {
+            hiding.crouched = crouched;
+        }
+    }
+}
+
+/// 隠れ場所の操作の名前を、プレイヤーが入っているかに合わせて変えるシステム(入っていれば「出る」)
+fn update_hiding_actions(
+    players: Query<&Hiding, With<Player>>,
+    mut spots: Query<(Entity, &mut Interactable), With<HidingSpot>>,
+) {
+    for (spot, mut interactable) in &mut spots {
+        let occupied = players.iter().any(|hiding| hiding.spot == spot);
+        let action = LocalizedText::new(if occupied { "leave_action" } else { "hide_action" });
+        if interactable.action != action {
+            interactable.action = action;
+        }
+    }
+}
+
+/// 隠れ場所に入っている間、隠れている状態(しゃがんでいれば見つからない)を表示するシステム
+fn update_hiding_text(
+    players: Query<Option<&Hiding>, With<Player>>,
+    mut texts: Query<(&mut LocalizedText, &mut Visibility), With<HidingText>>,
+) {
+    let Ok(hiding) = players.single() else {
+        return;
+    };
+    let key = hiding.map(|hiding| if hiding.crouched { "hidden" } else { "hiding" });
+    for (mut text, mut visibility) in &mut texts {
+        match key {
+            Some(key) => {
+                text.set_if_neq(LocalizedText::new(key));
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+/// 設定ファイルとレベルの敵ごとの視界と速度に難易度の倍率を掛けて、敵に反映するシステム
+/// 設定・レベル・難易度が変わったときと、チェックポイントの読み込みで敵が上書きされたときに反映する
+/// (レベルに予定のない敵は、設定ファイルの値を使う)
+pub fn apply_config(
+    config: Res<StealthConfig>,
+    level: Res<LevelLayout>,
+    difficulty: Res<Difficulty>,
+    mut enemies: Query<(Option<&Name>, &mut Enemy)>,
+) {
+    for (name, mut enemy) in &mut enemies {
+        if !config.is_changed()
+            && !level.is_changed()
+            && !difficulty.is_changed()
+            && !enemy.is_changed()
+        {
+            continue;
+        }
+        let guard =
+            name.and_then(|name| level.guards.iter().find(|guard| guard.name == name.as_str()));
+        let (range, angle, speed) = match guard {
+            Some(guard) => guard.parameters(&config),
+            None => (config.vision_range, config.vision_angle, config.enemy_speed),
+        };
+        let (range, angle, speed) = (
+            range * difficulty.vision_range_ratio(),
+            angle * difficulty.vision_angle_ratio(),
+            speed * difficulty.speed_ratio(),
+        );
+        if (enemy.vision_range, enemy.vision_angle, enemy.speed) != (range, angle, speed) {
+            enemy.vision_range = range;
+            enemy.vision_angle = angle;
+            enemy.speed = speed;
+        }
+    }
+}
+
+/// 敵キャラクターの視界検知システム
+/// 距離と角度が視界に入っていても、敵の目からプレイヤーへのレイが先に壁や障害物に当たれば検知しない
+/// 隠れ場所でしゃがんでいるプレイヤー(`Hiding`)は検知しない
+/// しゃがんでいるプレイヤー(`Crouching`)は、視界範囲に設定の倍率を掛けた距離まで近づかないと検知しない
+/// ゆっくり動いているプレイヤーほど近づかないと検知しない(`PlayerSpeed`の速さに応じた倍率を掛ける)
+/// 視界に入っている間は敵ごとの検知ゲージ(`Detection`)を溜め、見えない間は減らし、満ちたらゲームオーバーにする
+/// (溜まる速さには難易度の倍率を掛ける。警報が出ている間は視界が広がり、見えた場所を`AlarmLevel`に残す)
+pub fn enemy_vision_system(
+    player_query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Hiding>,
+            Has<Crouching>,
+            &Illumination,
+            Option<&PlayerSpeed>,
+        ),
+        With<Player>,
+    >,
+    mut enemy_query: Query<(Entity, &Transform, &Enemy, &mut Detection), Without<KnockedOut>>,
+    rapier_context: ReadRapierContext,
+    config: Res<StealthConfig>,
+    difficulty: Res<Difficulty>,
+    mut alarm: ResMut<AlarmLevel>,
+    time: Res<Time>,
+    mut game_state: ResMut<GameState>,
+    mut log: ResMut<DebugLog>,
+    mut commands: Commands,
+) {
+    let Ok((player, player_transform, hiding, crouching, illumination, speed)) =
+        player_query.single()
+    else {
+        return; // プレイヤーが存在しない場合は何もしない
+    };
+    // 隠れ場所でしゃがんでいる間は見つからない
+    let concealed = hiding.is_some_and(|hiding| hiding.crouched);
+    let rapier_context = rapier_context.single().ok();
+    let crouch_ratio = if crouching {
+        config.crouch_vision_ratio
+    } else {
+        1.0
+    };
+    // 暗いところほど、ゆっくり動いているほど近づかないと見えない
+    let speed_ratio = speed.map_or(1.0, |speed| config.speed_vision_ratio(speed.0));
+    let range_ratio =
+        crouch_ratio * config.light_vision_ratio(illumination.level) * speed_ratio;
+    // 警報が出ている間は、視界範囲も視界角度も広がる
+    let alarm_ratio = alarm.vision_ratio(&config);
+
+    // プレイ中でない(ゲームオーバーかゴールした後の)場合は何もしない
+    if *game_state != GameState::Playing {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    let mut detected = false;
+
+    // 敵キャラクターの情報を取得
+    for (enemy_entity, enemy_transform, enemy, mut detection) in enemy_query.iter_mut() {
+        // プレイヤーと敵の位置を取得
+        let enemy_forward = enemy_transform.forward();
+        let to_player = player_transform.translation - enemy_transform.translation;
+
+        // プレイヤーとの距離を計算
+        let distance_to_player = to_player.length();
+        let range = enemy.vision_range * range_ratio * alarm_ratio;
+
+        // プレイヤーとの角度を計算
+        let to_player_direction = to_player.normalize();
+        let angle_to_player = enemy_forward
+            .angle_between(to_player_direction)
+            .to_degrees();
+        let half_angle = enemy.vision_angle * alarm_ratio / 2.0;
+
+        // 視界に入っているかを判定(範囲と視界角の中で、目からプレイヤーまで遮るものがない)
+        let in_view = !concealed
+            && distance_to_player <= range
+            && angle_to_player < half_angle
+            && has_line_of_sight(
+                rapier_context.as_ref(),
+                enemy_entity,
+                enemy_transform,
+                player,
+                player_transform,
+            );
+
+        if !in_view {
+            // 見えない間はゲージを減らす
+            if detection.level > 0.0 {
+                let drain = if config.detection_drain_time > 0.0 {
+                    delta / config.detection_drain_time
+                } else {
+                    1.0
+                };
+                detection.level = (detection.level - drain).max(0.0);
+            }
+            continue;
+        }
+
+        // 警報が出ている間は、見えた場所に敵を集める
+        if alarm.is_raised() {
+            alarm.last_known = Some(player_transform.translation);
+        }
+
+        // 見えている間はゲージを溜める(近いほど、視界の中心に近いほど速い)
+        let rate = config.detection_rate(distance_to_player / range, angle_to_player / half_angle)
+            * difficulty.detection_ratio();
+        detection.level = if rate.is_infinite() {
+            1.0
+        } else {
+            (detection.level + rate * delta).min(1.0)
+        };
+        if detection.level >= 1.0 && !detected {
+            // プレイヤーが視界内にいる場合の処理
+            log.push(format!(
+                "Detected at {distance_to_player:.2}m, {angle_to_player:.2}° - Game over"
+            ));
+            detected = true;
+        }
+    }
+
+    if detected {
+        *game_state = GameState::GameOver; // ゲームオーバー状態に変更
+        spawn_game_over_ui(&mut commands); // ゲームオーバーのUIを表示
+    }
+}
+
+/// 検知ゲージの変化から効果音の合図を送るシステム
+/// 空だったゲージが溜まり始めたら`Suspicion`、満ちたら`Detected`を送る(同じフレームに何体いても1回だけ)
+pub fn emit_detection_cues(
+    enemies: Query<(Entity, &Detection), Changed<Detection>>,
+    mut previous: Local<EntityHashMap<f32>>,
+    mut cues: EventWriter<AudioCue>,
+) {
+    let mut suspicion = false;
+    let mut detected = false;
+    for (entity, detection) in &enemies {
+        let before = previous.insert(entity, detection.level).unwrap_or(0.0);
+        if before < 1.0 && detection.level >= 1.0 {
+            detected = true;
+        } else if before <= 0.0 && detection.level > 0.0 {
+            suspicion = true;
+        }
+    }
+    if detected {
+        cues.write(AudioCue::Detected);
+    } else if suspicion {
+        cues.write(AudioCue::Suspicion);
+    }
+}
+
+/// 効果音の合図に合わせて効果音を鳴らすシステム
+fn play_audio_cues(audio: Res<Audio>, sounds: Res<GameSounds>, mut cues: EventReader<AudioCue>) {
+    for cue in cues.read() {
+        let (sound, volume) = match cue {
+            AudioCue::Suspicion => (sounds.suspicion.clone(), 0.6),
+            AudioCue::Detected => (sounds.detected.clone(), 1.0),
+            AudioCue::BodyFound => (sounds.suspicion.clone(), 0.9), // 気づいたときの音を大きめに
+        };
+        audio.play(sound).with_volume(volume);
+    }
+}
+
+/// 画面とゲームの状態に合わせて音楽を切り替えるシステム(前の音楽はフェードアウトして止める)
+fn update_music(
+    app_state: Res<State<AppState>>,
+    game_state: Res<GameState>,
+    sounds: Res<GameSounds>,
+    music: Res<AudioChannel<MusicChannel>>,
+    mut playing: Local<Option<MusicTrack>>,
+) {
+    let track = music_for(app_state.get(), &game_state);
+    if *playing == track {
+        return;
+    }
+    *playing = track;
+    music
+        .stop()
+        .fade_out(AudioTween::linear(Duration::from_millis(500)));
+    let Some(track) = track else {
+        return;
+    };
+    let sound = match track {
+        MusicTrack::Menu => sounds.menu_music.clone(),
+        MusicTrack::GameOver => sounds.game_over_music.clone(),
+    };
+    music
+        .play(sound)
+        .looped()
+        .with_volume(0.5)
+        .fade_in(AudioTween::linear(Duration::from_millis(500)));
+}
+
+/// プレイヤーの立っている場所の明るさを求めるシステム
+/// 昼夜で変わる明かりのないところの明るさに、届いている明かりの明るさを足す(1を超えない)
+pub fn update_illumination(
+    config: Res<StealthConfig>,
+    time_of_day: Res<TimeOfDay>,
+    lights: Query<(&Transform, &LightZone)>,
+    mut players: Query<(&Transform, &mut Illumination), With<Player>>,
+) {
+    let ambient = config.ambient_light(time_of_day.period());
+    for (transform, mut illumination) in &mut players {
+        let lit: f32 = lights
+            .iter()
+            .map(|(light_transform, light)| light.light_at(light_transform, transform.translation))
+            .sum();
+        let level = (ambient + lit).clamp(0.0, 1.0);
+        if illumination.level != level {
+            illumination.level = level;
+        }
+    }
+}
+
+/// 明るさの宝石の色(暗いと沈んだ色、明るいと黄色がかった白)
+fn light_gem_color(level: f32) -> Color {
+    Color::srgb(0.08, 0.08, 0.12).mix(&Color::srgb(1.0, 0.95, 0.7), level)
+}
+
+/// プレイヤーの明るさに合わせて、明るさの宝石の色を変えるシステム
+fn update_light_gem(
+    players: Query<&Illumination, (With<Player>, Changed<Illumination>)>,
+    mut gems: Query<&mut BackgroundColor, With<LightGem>>,
+) {
+    let Ok(illumination) = players.single() else {
+        return;
+    };
+    for mut color in &mut gems {
+        color.0 = light_gem_color(illumination.level);
+    }
+}
+
+/// いちばん溜まった検知ゲージに合わせて、目の表示の瞳の大きさと色を変えるシステム
+/// (どの敵のゲージも空なら目を隠す)
+fn update_detection_eye(
+    enemies: Query<&Detection>,
+    mut eyes: Query<&mut Visibility, With<DetectionEye>>,
+    mut pupils: Query<(&mut Node, &mut BackgroundColor), With<DetectionPupil>>,
+) {
+    let level = enemies
+        .iter()
+        .map(|detection| detection.level)
+        .fold(0.0, f32::max);
+    for mut visibility in &mut eyes {
+        visibility.set_if_neq(if level > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    // 瞳は白目の高さまで大きくなり、黄色から赤に変わる
+    let size = Val::Px(EYE_WIDTH / 2.0 * (0.2 + 0.8 * level));
+    let color = Color::srgb(1.0, 0.8, 0.1).mix(&Color::srgb(0.9, 0.05, 0.05), level);
+    for (mut node, mut background) in &mut pupils {
+        node.width = size;
+        node.height = size;
+        background.0 = color;
+    }
+}
+
+/// 敵の目からプレイヤーへのレイを飛ばし、最初に当たったのがプレイヤーなら見えている
+/// (物理のワールドがないときは、遮るものがないとみなす)
+fn has_line_of_sight(
+    context: Option<&RapierContext>,
+    enemy: Entity,
+    enemy_transform: &Transform,
+    player: Entity,
+    player_transform: &Transform,
+) -> bool {
+    let eye = enemy_transform.translation + Vec3::Y * ENEMY_EYE_HEIGHT;
+    line_of_sight(context, eye, Some(enemy), player, player_transform)
+}
+
+/// `eye`からプレイヤーへのレイを飛ばし、最初に当たったのがプレイヤーなら見えている
+/// (`exclude`のコライダーには当てない。物理のワールドがないときは、遮るものがないとみなす)
+fn line_of_sight(
+    context: Option<&RapierContext>,
+    eye: Vec3,
+    exclude: Option<Entity>,
+    player: Entity,
+    player_transform: &Transform,
+) -> bool {
+    let Some(context) = context else {
+        return true;
+    };
+    let to_player = player_transform.translation - eye;
+    let mut filter = QueryFilter::default().exclude_sensors();
+    if let Some(exclude) = exclude {
+        filter = filter.exclude_collider(exclude); // 見ている者のコライダーには当てない
+    }
+    let hit = context.cast_ray(
+        eye,
+        to_player.normalize_or_zero(),
+        to_player.length(),
+        true,
+        filter,
+    );
+    hit.map(|(entity, _)| entity) == Some(player)
+}
+
+/// 敵の前方(-Z方向)に広がる、地面と平行な扇形のメッシュ
+/// `angle`は中心角(度)で、扇形の中心線が敵の向きと重なる
+pub fn vision_cone_mesh(range: f32, angle: f32) -> Mesh {
+    // CircularSectorは+Y方向に広がるXY平面の扇形なので、X軸の周りに倒して-Z方向に向ける
+    Mesh::from(CircularSector::new(range, angle.to_radians() / 2.0))
+        .rotated_by(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2))
+}
+
+/// 視界の扇形がない敵に扇形を付け、視界の範囲や角度が変わった敵の扇形を作り直すシステム
+/// (設定ファイルやチェックポイントの読み込みで`Enemy`が変わったときだけ調べる)
+pub fn update_vision_cones(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    enemies: Query<(Entity, &Enemy, Option<&Children>), Changed<Enemy>>,
+    mut cones: Query<(&mut VisionCone, &Mesh3d)>,
+) {
+    for (entity, enemy, children) in &enemies {
+        let target = VisionCone {
+            range: enemy.vision_range,
+            angle: enemy.vision_angle,
+        };
+        let mut found = false;
+        for child in children.into_iter().flatten() {
+            let Ok((mut cone, mesh)) = cones.get_mut(*child) else {
+                continue;
+            };
+            found = true;
+            if *cone != target {
+                *cone = target;
+                if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                    *mesh = vision_cone_mesh(target.range, target.angle);
+                }
+            }
+        }
+        if found {
+            continue;
+        }
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                target,
+                Mesh3d(meshes.add(vision_cone_mesh(target.range, target.angle))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: AlertState::Calm.cone_color(), // 色は`tint_vision_cones`が警戒の段階に合わせて変える
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true, // 暗い時間帯でも見えるようにする
+                    double_sided: true,
+                    cull_mode: None, // 裏側からも見えるようにする
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, VISION_CONE_HEIGHT, 0.0), // 地面の少し上
+                bevy::pbr::NotShadowCaster, // 地面に影を落とさない
+            ));
+        });
+    }
+}
+
+/// 敵の警戒の段階(視界の扇形の色に表す)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    Calm,       // 巡回している(緑)
+    Suspicious, // 気づきかけている、または音や倒れている敵を調べている(黄)
+    Alerted,    // プレイヤーを見つけた、または警報が出ている(赤)
+}
+
+impl AlertState {
+    /// 検知ゲージと、調べに行っているか、警報が出ているかから警戒の段階を決める
+    pub fn of(detection: &Detection, investigating: bool, alarm_raised: bool) -> Self {
+        if alarm_raised || detection.level >= 1.0 {
+            AlertState::Alerted
+        } else if investigating || detection.level > 0.0 {
+            AlertState::Suspicious
+        } else {
+            AlertState::Calm
+        }
+    }
+
+    /// 視界の扇形の色(半透明)
+    pub fn cone_color(self) -> Color {
+        match self {
+            AlertState::Calm => Color::srgba(0.2, 0.9, 0.3, VISION_CONE_ALPHA),
+            AlertState::Suspicious => Color::srgba(1.0, 0.85, 0.1, VISION_CONE_ALPHA),
+            AlertState::Alerted => Color::srgba(1.0, 0.2, 0.1, VISION_CONE_ALPHA),
+        }
+    }
+}
+
+/// 視界の扇形の不透明度
+/// 検知ゲージが溜まっている間(`filling`)は、`elapsed`秒に合わせて濃くなったり薄くなったりする
+pub fn vision_cone_alpha(filling: bool, elapsed: f32) -> f32 {
+    if !filling {
+        return VISION_CONE_ALPHA;
+    }
+    let wave = 0.5 - 0.5 * (elapsed * VISION_CONE_PULSE_RATE * std::f32::consts::TAU).cos();
+    VISION_CONE_ALPHA + VISION_CONE_PULSE_ALPHA * wave
+}
+
+/// 敵の警戒の段階に合わせて視界の扇形の色を変え、検知ゲージが溜まっている間は点滅させるシステム
+pub fn tint_vision_cones(
+    time: Res<Time>,
+    alarm: Res<AlarmLevel>,
+    guards: Query<(Entity, &Detection, Has<Investigating>, &Children), With<Enemy>>,
+    cones: Query<&MeshMaterial3d<StandardMaterial>, With<VisionCone>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut previous: Local<EntityHashMap<f32>>,
+) {
+    for (entity, detection, investigating, children) in &guards {
+        let before = previous.insert(entity, detection.level).unwrap_or(0.0);
+        let filling = detection.level > before;
+        let color = AlertState::of(detection, investigating, alarm.is_raised())
+            .cone_color()
+            .with_alpha(vision_cone_alpha(filling, time.elapsed_secs()));
+        for child in children {
+            let Ok(material) = cones.get(*child) else {
+                continue;
+            };
+            // 色が変わらないときはアセットを書き換えない(書き換えるとGPUへ送り直すため)
+            let unchanged = materials
+                .get(&material.0)
+                .is_none_or(|current| current.base_color == color);
+            if unchanged {
+                continue;
+            }
+            if let Some(current) = materials.get_mut(&material.0) {
+                current.base_color = color;
+            }
+        }
+    }
+}
+
+/// 敵キャラクターのパトロールシステム
+/// 音を調べている敵(`Investigating`)は巡回しない。
+/// 地点に着いたら、その地点の時間(指定がなければ`waypoint_pause`)だけ立ち止まって左右を見回してから、次の地点へ向かう
+pub fn enemy_patrol_system(
+    mut enemy_query: Query<
+        (Option<&Name>, &mut Transform, &mut Enemy, &mut PatrolPause),
+        (Without<Investigating>, Without<KnockedOut>),
+    >,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    difficulty: Res<Difficulty>,
+    config: Res<StealthConfig>,
+    alarm: Res<AlarmLevel>,
+    mut log: ResMut<DebugLog>,
+) {
+    // プレイ中でない(ゲームオーバーかゴールした後の)場合は何もしない
+    if *game_state != GameState::Playing {
+        return; // ゲームオーバー状態ではパトロールしない
+    }
+
+    // 敵キャラクターの情報を取得
+    for (name, mut transform, mut enemy, mut pause) in enemy_query.iter_mut() {
+        // パトロールポイントが空の場合は何もしない
+        if enemy.patrol_points.is_empty() {
+            continue;
+        }
+
+        // 立ち止まっている間は、その場で左右を見回す
+        if pause.remaining > 0.0 {
+            pause.remaining = (pause.remaining - time.delta_secs()).max(0.0);
+            transform.rotation = pause.rotation(config.look_around_angle);
+            continue;
+        }
+
+        // 現在のパトロールポイントを取得(地点ごとの速度の指定がなければ敵の移動速度)
+        // 敵の移動速度には`apply_config`が難易度の倍率を掛けているので、地点ごとの速度にだけ掛ける
+        // (警報が出ている間は、どちらも速くなる)
+        let waypoint = enemy.patrol_points[enemy.current_patrol_index];
+        let target_point = waypoint.position;
+        let speed = waypoint
+            .speed
+            .map_or(enemy.speed, |speed| speed * difficulty.speed_ratio())
+            * alarm.speed_ratio(&config);
+
+        // 目標位置までのベクトルを計算
+        let direction = (target_point - transform.translation).normalize();
+        // 目標位置までの距離を計算
+        let distance_to_target = transform.translation.distance(target_point);
+
+        // 目標位置に近づいたら次のパトロールポイントへ移動
+        if distance_to_target < 0.2 {
+            let reached = enemy.current_patrol_index;
+            enemy.current_patrol_index = (reached + 1) % enemy.patrol_points.len();
+            // 着いた地点で立ち止まる(時間が0なら、すぐに次の地点へ向かう)
+            let duration = waypoint.pause.unwrap_or(config.waypoint_pause);
+            if duration > 0.0 {
+                *pause = PatrolPause {
+                    remaining: duration,
+                    duration,
+                    facing: transform.rotation,
+                };
+            }
+            // 着いたときだけデバッグ表示のログに書く(地点の番号は1から数える)
+            log.push(format!(
+                "{} reached #{}, heading to #{}",
+                name.map_or("Enemy", Name::as_str),
+                reached + 1,
+                enemy.current_patrol_index + 1
+            ));
+        } else {
+            // 目標位置に向かって移動
+            transform.translation += direction * speed * time.delta_secs();
+            // 敵の向きを目標位置に向ける
+            transform.look_at(target_point, Vec3::Y);
+        }
+    }
+}
+
+/// プレイヤーの足音と着地の音を立てるシステム
+/// スニーキング中は足音が小さく(設定で0なら音を立てず)、スティックを浅く倒したときも小さくなる。
+/// ダッシュ中は足音が遠くまで聞こえる
+fn emit_player_noise(
+    actions: Res<ButtonInput<PlayerAction>>,
+    axes: Res<ActionAxes>,
+    players: Query<
+        (
+            &Transform,
+            Option<&KinematicCharacterControllerOutput>,
+            Has<Sprinting>,
+        ),
+        With<Player>,
+    >,
+    config: Res<StealthConfig>,
+    game_state: Res<GameState>,
+    mut was_grounded: Local<Option<bool>>,
+    mut noises: EventWriter<NoiseEvent>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let Ok((transform, output, sprinting)) = players.single() else {
+        return;
+    };
+    let position = transform.translation;
+
+    // 足音(動いている間は毎フレーム)
+    let amount = axes.movement.length();
+    if amount > 0.0 {
+        let radius = if sprinting {
+            config.sprint_noise_radius * amount.clamp(0.0, 1.0)
+        } else {
+            let sneaking = actions.pressed(PlayerAction::Sneak);
+            config.footstep_noise_radius(sneaking, amount)
+        };
+        if radius > 0.0 {
+            noises.write(NoiseEvent { position, radius });
+        }
+    }
+
+    // 着地の音(キャラクターコントローラーが接地していなかった状態から接地したとき)
+    let grounded = output.is_some_and(|output| output.grounded);
+    if grounded && *was_grounded == Some(false) && config.landing_noise_radius > 0.0 {
+        noises.write(NoiseEvent {
+            position,
+            radius: config.landing_noise_radius,
+        });
+    }
+    *was_grounded = output.map(|_| grounded);
+}
+
+/// 効果音と音楽の読み込みを始める(読み込み中の画面で読み込みを待つ)
+fn load_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let sounds = GameSounds {
+        footstep: asset_server.load("sounds/footstep.wav"),
+        enemy_footstep: asset_server.load("sounds/enemy_footstep.wav"),
+        suspicion: asset_server.load("sounds/suspicion.wav"),
+        detected: asset_server.load("sounds/detected.wav"),
+        menu_music: asset_server.load("sounds/menu_music.wav"),
+        game_over_music: asset_server.load("sounds/game_over_music.wav"),
+        radio: asset_server.load("sounds/radio.wav"),
+        machinery: asset_server.load("sounds/machinery.wav"),
+    };
+    for handle in [
+        &sounds.footstep,
+        &sounds.enemy_footstep,
+        &sounds.suspicion,
+        &sounds.detected,
+        &sounds.menu_music,
+        &sounds.game_over_music,
+        &sounds.radio,
+        &sounds.machinery,
+    ] {
+        loading.add(handle.clone());
+    }
+    commands.insert_resource(sounds);
+}
+
+/// プレイヤーと敵が歩いた距離を数え、歩幅ごとに足音の合図を送るシステム
+/// プレイヤーの足音は速く動くほど大きく高くなり、スニーキング中としゃがんでいる間は鳴らさない。
+/// 敵の足音はプレイヤーに近いほど大きく、`enemy_footstep_range`より遠ければ鳴らさない
+pub fn update_footsteps(
+    actions: Res<ButtonInput<PlayerAction>>,
+    config: Res<StealthConfig>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    players: Query<&Transform, With<Player>>,
+    mut walkers: Query<(&Transform, &mut Footsteps, Has<Player>, Has<Crouching>)>,
+    mut footsteps: EventWriter<FootstepEvent>,
+) {
+    let delta = time.delta_secs();
+    let listener = players.single().ok().map(|transform| transform.translation);
+    let sneaking = actions.pressed(PlayerAction::Sneak);
+    for (transform, mut steps, is_player, crouching) in &mut walkers {
+        let position = transform.translation;
+        let Some(previous) = steps.last_position.replace(position) else {
+            continue; // 最初のフレームは位置を覚えるだけ
+        };
+        if *game_state != GameState::Playing || delta <= 0.0 {
+            continue;
+        }
+        let moved = (position - previous).with_y(0.0).length();
+        if moved > MAX_STEP_PER_FRAME {
+            continue;
+        }
+        steps.travelled += moved;
+        if steps.travelled < FOOTSTEP_STRIDE {
+            continue;
+        }
+        steps.travelled %= FOOTSTEP_STRIDE;
+
+        let event = if is_player {
+            if sneaking || crouching {
+                continue;
+            }
+            let speed_ratio = (moved / delta / config.player_speed).clamp(0.0, 1.0);
+            FootstepEvent {
+                enemy: false,
+                volume: config.footstep_volume * speed_ratio,
+                playback_rate: 0.8 + 0.4 * speed_ratio,
+            }
+        } else {
+            let Some(listener) = listener else {
+                continue;
+            };
+            let closeness = 1.0 - position.distance(listener) / config.enemy_footstep_range;
+            if closeness <= 0.0 {
+                continue;
+            }
+            FootstepEvent {
+                enemy: true,
+                volume: config.enemy_footstep_volume * closeness,
+                playback_rate: 1.0,
+            }
+        };
+        footsteps.write(event);
+    }
+}
+
+/// 足音の合図に合わせて効果音を鳴らすシステム
+fn play_footsteps(
+    audio: Res<Audio>,
+    sounds: Res<GameSounds>,
+    mut footsteps: EventReader<FootstepEvent>,
+) {
+    for step in footsteps.read() {
+        let sound = if step.enemy {
+            sounds.enemy_footstep.clone()
+        } else {
+            sounds.footstep.clone()
+        };
+        audio
+            .play(sound)
+            .with_volume(step.volume as f64)
+            .with_playback_rate(step.playback_rate as f64);
+    }
+}
+
+/// 投げた石の物理のコンポーネント(`velocity`の速さで飛び、当たったら衝突のイベントを送る)
+pub fn rock(velocity: Vec3) -> impl Bundle {
+    (
+        Rock::default(),
+        RigidBody::Dynamic,
+        Collider::ball(ROCK_RADIUS),
+        ActiveEvents::COLLISION_EVENTS,
+        Velocity::linear(velocity),
+    )
+}
+
+/// 「投げる」ボタン(GキーかRT)で、カメラの向いている方へ石を投げるシステム
+/// (少し上向きに投げる。続けて投げられないように、`throw_cooldown`秒待つ)
+fn throw_rock(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    actions: Res<ButtonInput<PlayerAction>>,
+    config: Res<StealthConfig>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    players: Query<&Transform, With<Player>>,
+    cameras: Query<&Transform, (With<Camera3d>, Without<Player>)>,
+    mut stats: ResMut<RunStats>,
+    mut last_throw: Local<Option<f32>>,
+) {
+    if *game_state != GameState::Playing || !actions.just_pressed(PlayerAction::Throw) {
+        return;
+    }
+    let now = time.elapsed_secs();
+    if last_throw.is_some_and(|last| now - last < config.throw_cooldown) {
+        return;
+    }
+    let (Ok(player), Ok(camera)) = (players.single(), cameras.single()) else {
+        return;
+    };
+    let forward = camera.forward().with_y(0.0).normalize_or_zero();
+    if forward == Vec3::ZERO {
+        return; // 真上や真下を向いたカメラでは投げる方向が決まらない
+    }
+    *last_throw = Some(now);
+    stats.distractions += 1; // 得点を計算するために、投げた石を数える
+
+    // プレイヤーの少し前の、胸の高さから投げる
+    let position =
+        player.translation + forward * (PLAYER_RADIUS + ROCK_RADIUS * 2.0) + Vec3::Y * 0.5;
+    let velocity = (forward + Vec3::Y * 0.5).normalize() * config.throw_speed;
+    commands.spawn((
+        rock(velocity),
+        Mesh3d(meshes.add(Sphere::new(ROCK_RADIUS))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.45, 0.42, 0.4), // 灰色の石
+            ..default()
+        })),
+        Transform::from_translation(position),
+    ));
+}
+
+/// 石が最初に何かに当たったら、その場所で音を立て、当たってからしばらくしたら消すシステム
+pub fn rock_impacts(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut rocks: Query<(Entity, &Transform, &mut Rock)>,
+    config: Res<StealthConfig>,
+    time: Res<Time>,
+    mut noises: EventWriter<NoiseEvent>,
+) {
+    for collision in collisions.read() {
+        let CollisionEvent::Started(a, b, _) = collision else {
+            continue;
+        };
+        for entity in [*a, *b] {
+            let Ok((_, transform, mut rock)) = rocks.get_mut(entity) else {
+                continue;
+            };
+            if rock.landed {
+                continue; // 跳ねて何度も当たっても、音は最初の1回だけ
+            }
+            rock.landed = true;
+            noises.write(NoiseEvent {
+                position: transform.translation,
+                radius: config.rock_noise_radius,
+            });
+        }
+    }
+
+    for (entity, _, mut rock) in &mut rocks {
+        if !rock.landed {
+            continue;
+        }
+        rock.elapsed += time.delta_secs();
+        if rock.elapsed >= ROCK_LIFETIME {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// 音が聞こえる範囲にいる敵に、音のした場所を調べさせるシステム
+/// すでに調べている敵は、新しく聞いた音の場所に向かい直す。
+/// 周りの音(`AmbientNoise`)の近くで立てた音は、聞こえる範囲が狭まる
+pub fn enemy_hearing_system(
+    mut commands: Commands,
+    mut noises: EventReader<NoiseEvent>,
+    enemies: Query<(Entity, &Transform), (With<Enemy>, Without<KnockedOut>)>,
+    sources: Query<(&Transform, &AmbientNoise)>,
+    config: Res<StealthConfig>,
+    game_state: Res<GameState>,
+) {
+    if *game_state != GameState::Playing {
+        noises.clear();
+        return;
+    }
+    for noise in noises.read() {
+        let radius = masked_noise_radius(noise, &sources, &config);
+        for (entity, transform) in &enemies {
+            if transform.translation.distance(noise.position) > radius {
+                continue;
+            }
+            let target = Vec3::new(noise.position.x, transform.translation.y, noise.position.z);
+            commands.entity(entity).insert(Investigating {
+                target,
+                remaining: config.investigate_time,
+            });
+        }
+    }
+}
+
+/// 音を調べている敵を、音のした場所に向かわせ、着いたら辺りを調べてから巡回に戻すシステム
+/// (警報が出ている間は速く向かう)
+pub fn enemy_investigate_system(
+    mut commands: Commands,
+    mut enemies: Query<(
+        Entity,
+        &mut Transform,
+        &Enemy,
+        &mut Investigating,
+        &mut PatrolPause,
+    )>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    config: Res<StealthConfig>,
+    alarm: Res<AlarmLevel>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    for (entity, mut transform, enemy, mut investigating, mut pause) in &mut enemies {
+        // 巡回地点で立ち止まっていても、調べに行くときはやめる
+        pause.remaining = 0.0;
+        let to_target = investigating.target - transform.translation;
+        if to_target.length() >= 0.2 {
+            // 音のした方を向いて、そこへ向かう
+            transform.look_at(investigating.target, Vec3::Y);
+            let speed = enemy.speed * alarm.speed_ratio(&config);
+            let step = to_target.normalize() * speed * time.delta_secs();
+            transform.translation += step.clamp_length_max(to_target.length());
+            continue;
+        }
+        // 着いたら辺りを調べ、時間が来たら巡回に戻る(最も近い地点からではなく、向かっていた地点から)
+        investigating.remaining -= time.delta_secs();
+        if investigating.remaining <= 0.0 {
+            commands.entity(entity).remove::<Investigating>();
+        }
+    }
+}
+
+/// レベルが読み込まれた・更新されたときに、時刻をレベルの開始時刻にするシステム
+fn reset_time_of_day(level: Res<LevelLayout>, mut time_of_day: ResMut<TimeOfDay>) {
+    time_of_day.hours = level.start_hour.rem_euclid(24.0);
+}
+
+/// ゲーム内の時刻を進めるシステム
+fn advance_time_of_day(
+    time: Res<Time>,
+    level: Res<LevelLayout>,
+    game_state: Res<GameState>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    // ゲームオーバー中やゴールした後、1日の長さが指定されていない場合は時間を止める
+    if *game_state != GameState::Playing || level.day_length <= 0.0 {
+        return;
+    }
+    let hours = time_of_day.hours + time.delta_secs() * 24.0 / level.day_length;
+    time_of_day.hours = hours.rem_euclid(24.0);
+}
+
+/// 現在の時間帯の巡回経路を、名前が一致する敵に設定するシステム
+/// 経路が変わったときは、最も近い地点から巡回を始める
+pub fn apply_patrol_schedule(
+    level: Res<LevelLayout>,
+    time_of_day: Res<TimeOfDay>,
+    mut enemies: Query<(&Name, &Transform, &mut Enemy)>,
+) {
+    let period = time_of_day.period();
+    for (name, transform, mut enemy) in enemies.iter_mut() {
+        let Some(route) = level.route_for(name.as_str(), period) else {
+            continue; // 予定のない敵は、今の経路のまま
+        };
+        if enemy.patrol_points == route.waypoints {
+            continue;
+        }
+
+        info!("{name}の巡回経路を{period:?}の経路に切り替えます");
+        let position = transform.translation;
+        enemy.current_patrol_index = route
+            .waypoints
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.position
+                    .distance_squared(position)
+                    .total_cmp(&b.position.distance_squared(position))
+            })
+            .map_or(0, |(index, _)| index);
+        enemy.patrol_points = route.waypoints.clone();
+    }
+}
+
+/// 時刻の表示を更新するシステム
+fn update_clock(
+    time_of_day: Res<TimeOfDay>,
+    mut clocks: Query<&mut LocalizedText, With<ClockText>>,
+) {
+    let hours = time_of_day.hours as u32;
+    let minutes = (time_of_day.hours.fract() * 60.0) as u32;
+    let period = match time_of_day.period() {
+        DayPeriod::Day => "day",
+        DayPeriod::Night => "night",
+    };
+    for mut text in clocks.iter_mut() {
+        text.set_if_neq(
+            LocalizedText::new("clock")
+                .with_arg("time", format!("{hours:02}:{minutes:02}"))
+                .with_key_arg("period", period),
+        );
+    }
+}
+
+/// マウスの右ボタンを押しながら動かすか、右スティックを倒して、カメラをプレイヤーの周りで回すシステム
+pub fn orbit_camera(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    axes: Res<ActionAxes>,
+    time: Res<Time>,
+    mut camera_query: Query<&mut CameraController>,
+) {
+    for mut controller in &mut camera_query {
+        // スティックを上に倒すと見上げる(見下ろす角度が小さくなる)
+        let mut turn =
+            Vec2::new(axes.look.x, -axes.look.y) * controller.stick_speed * time.delta_secs();
+        if mouse_buttons.pressed(MouseButton::Right) {
+            turn += mouse_motion.delta * controller.mouse_speed;
+        }
+        if turn == Vec2::ZERO {
+            continue;
+        }
+        controller.yaw -= turn.x; // 右に動かすと右を向く
+        controller.pitch =
+            (controller.pitch + turn.y).clamp(controller.min_pitch, controller.max_pitch);
+    }
+}
+
+/// カメラ追従システム
+/// プレイヤーの少し上を注視点にして、`CameraController`の向きと距離の位置に置く。
+/// 注視点からカメラまで球を飛ばし、壁や地面に当たったらその手前に寄せる(カメラが壁にめり込まない)
+pub fn camera_follow_player(
+    player_query: Query<&Transform, (With<Player>, Without<Camera3d>)>,
+    mut camera_query: Query<(&mut Transform, &CameraController), (With<Camera3d>, Without<Player>)>,
+    rapier_context: ReadRapierContext,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return; // プレイヤーが存在しない場合は何もしない
+    };
+    let Ok((mut camera_transform, camera_controller)) = camera_query.single_mut() else {
+        return; // カメラが存在しない場合は何もしない
+    };
+
+    let pivot = player_transform.translation + Vec3::Y * CAMERA_PIVOT_HEIGHT;
+    let direction = camera_controller.direction();
+
+    // 固定された物(壁・地面・ドア)だけに当てる(敵や石に当たってカメラが跳ねないように)
+    let filter = QueryFilter::only_fixed().exclude_sensors();
+    let hit = rapier_context.single().ok().and_then(|context| {
+        context.cast_shape(
+            pivot,
+            Quat::IDENTITY,
+            direction,
+            &Collider::ball(CAMERA_RADIUS),
+            ShapeCastOptions::with_max_time_of_impact(camera_controller.distance),
+            filter,
+        )
+    });
+    let target = hit.map_or(camera_controller.distance, |(_, hit)| hit.time_of_impact);
+
+    // 離れるときは滑らかに、壁に遮られて近づくときはすぐに動かす
+    let current = camera_transform.translation.distance(pivot);
+    let distance = if target < current {
+        target
+    } else {
+        current.lerp(target, 0.1)
+    };
+    camera_transform.translation = pivot + direction * distance;
+
+    // カメラの向きを注視点に向ける
+    camera_transform.look_at(pivot, Vec3::Y);
+}
+
+/// カメラズームシステム
+fn camera_zoom(
+    actions: Res<ButtonInput<PlayerAction>>,
+    mut camera_query: Query<&mut CameraController, With<Camera3d>>,
+    time: Res<Time>,
+) {
+    let Ok(mut camera_controller) = camera_query.single_mut() else {
+        return; // カメラコントローラーが存在しない場合は何もしない
+    };
+
+    let zoom_delta = camera_controller.zoom_speed * time.delta_secs();
+
+    // ズームイン(Q)
+    if actions.pressed(PlayerAction::ZoomIn) {
+        camera_controller.distance = (camera_controller.distance - zoom_delta).clamp(
+            camera_controller.min_distance,
+            camera_controller.max_distance,
+        );
+    }
+
+    // ズームアウト(Z)
+    if actions.pressed(PlayerAction::ZoomOut) {
+        camera_controller.distance = (camera_controller.distance + zoom_delta).clamp(
+            camera_controller.min_distance,
+            camera_controller.max_distance,
+        );
+    }
+}
+
+/// 今回のプレイの記録を付けるシステム
+/// プレイ中の時間を進め、敵が気づき始めた合図(`AudioCue::Suspicion`)の回数を数える
+/// (倒れている仲間を見つけた合図`AudioCue::BodyFound`は、プレイヤーに気づいたわけではないので数えない)
+pub fn track_run_stats(
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut cues: EventReader<AudioCue>,
+    mut stats: ResMut<RunStats>,
+) {
+    let noticed = cues
+        .read()
+        .filter(|cue| **cue == AudioCue::Suspicion)
+        .count();
+    stats.detections += noticed as u32;
+    if *game_state == GameState::Playing {
+        stats.elapsed += time.delta_secs();
+    }
+}
+
+/// プレイヤーが見つからずにゴールの中に入ったら、クリアにして結果画面を表示するシステム
+pub fn reach_goal(
+    player_query: Query<&Transform, With<Player>>,
+    goals: Query<(&Transform, &GoalZone)>,
+    mut game_state: ResMut<GameState>,
+) {
+    // 見つかった後にゴールに入っても、クリアにしない
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    if goals
+        .iter()
+        .any(|(transform, goal)| goal.contains(transform, player_transform.translation))
+    {
+        *game_state = GameState::Victory;
+    }
+}
+
+/// クリアしたら、得点を出して記録を更新し、結果画面を表示するシステム
+/// 更新した記録はファイルに保存する
+fn finish_mission(
+    game_state: Res<GameState>,
+    current: Res<CurrentLevel>,
+    stats: Res<RunStats>,
+    mut best_times: ResMut<BestTimes>,
+    mut log: ResMut<DebugLog>,
+    mut commands: Commands,
+) {
+    if *game_state != GameState::Victory {
+        return;
+    }
+    log.push(format!(
+        "Mission complete in {:.1}s (noticed {}, rocks {}, score {})",
+        stats.elapsed,
+        stats.detections,
+        stats.distractions,
+        stats.score()
+    ));
+    let new_record = best_times.record(current.path(), &stats);
+    if new_record {
+        if let Err(err) = best_times.save(&BestTimes::path()) {
+            warn!("クリアの記録を保存できません: {err}");
+        }
+    }
+    if let Some(best) = best_times.get(current.path()) {
+        let has_next = current.next().is_some();
+        spawn_results_ui(&mut commands, &stats, best, new_record, has_next);
+    }
+}
+
+/// プレイしている時間の表示を更新するシステム
+fn update_run_timer(
+    stats: Res<RunStats>,
+    mut texts: Query<&mut LocalizedText, With<RunTimerText>>,
+) {
+    for mut text in &mut texts {
+        text.set_if_neq(
+            LocalizedText::new("run_timer").with_arg("time", format_run_time(stats.elapsed)),
+        );
+    }
+}
+
+/// クリアしたときの結果画面(かかった時間・気づかれた回数・投げた石の数・得点と、このレベルの記録)を表示する
+/// 記録を更新したときは「新記録」と表示し、次のレベルがあればRキーで次へ進むと案内する
+fn spawn_results_ui(
+    commands: &mut Commands,
+    stats: &RunStats,
+    best: BestRecord,
+    new_record: bool,
+    has_next: bool,
+) {
+    commands.spawn((
+        LocalizedText::new(if new_record { "victory_new_record" } else { "victory" })
+            .with_arg("time", format_run_time(stats.elapsed))
+            .with_arg("detections", stats.detections)
+            .with_arg("distractions", stats.distractions)
+            .with_arg("score", stats.score())
+            .with_arg("best_time", format_run_time(best.time))
+            .with_arg("best_score", best.score)
+            .with_key_arg("next", if has_next { "next_level" } else { "play_again" }),
+        TextFont {
+            font_size: 40.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.3, 1.0, 0.4)), // 緑色のテキスト
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(30.0),
+            top: Val::Percent(25.0), // 記録の行が多いので、ゲームオーバーより上から
+            ..default()
+        },
+        GameOverUI, // リスタートで消す
+    ));
+}
+
+/// ゲームオーバー表示システム
+fn spawn_game_over_ui(commands: &mut Commands) {
+    // ゲームオーバーのUIを表示するためのコードをここに追加
+    // 例えば、テキストやボタンを表示するなど
+    commands.spawn((
+        LocalizedText::new("game_over"),
+        TextFont {
+            font_size: 50.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.0, 0.0)), // 赤色のテキスト
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(30.0),
+            top: Val::Percent(40.0),
+            ..default()
+        },
+        GameOverUI, // GameOverUIコンポーネントを追加
+    ));
+}
+
+/// ゲーム再スタートシステム
+fn restart_game(
+    actions: Res<ButtonInput<PlayerAction>>,
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    game_over_query: Query<Entity, With<GameOverUI>>,
+    mut player_query: Query<
+        (&mut Transform, &mut VerticalVelocity),
+        (With<Player>, Without<Enemy>),
+    >,
+    mut enemy_query: Query<
+        (&mut Transform, &mut Enemy, &mut Detection, &mut PatrolPause),
+        Without<Player>,
+    >,
+    investigators: Query<Entity, Or<(With<Investigating>, With<KnockedOut>)>>,
+    mut level: ResMut<LevelLayout>,
+    mut stats: ResMut<RunStats>,
+    mut inventory: ResMut<Inventory>,
+    mut stamina: ResMut<Stamina>,
+    checkpoint: Res<CheckpointData>,
+    mut current: ResMut<CurrentLevel>,
+    mut log: ResMut<DebugLog>,
+) {
+    // ゲームオーバーかゴールした後でない場合は何もしない
+    if *game_state == GameState::Playing {
+        return;
+    }
+    // チェックポイントを通った後に見つかったときは、restore_checkpointがそこから再開する
+    if *game_state == GameState::GameOver && checkpoint.saved.is_some() {
+        return;
+    }
+    // Rキー(ゲームパッドではStartかA)が押された場合の処理
+    if actions.just_pressed(PlayerAction::Restart) {
+        // ゴールした後に次のレベルがあれば、それを読み込む(読み込んだらenter_levelが最初の状態に戻す)
+        if *game_state == GameState::Victory {
+            if let Some(next) = current.next() {
+                *current = next;
+                return;
+            }
+        }
+        for entity in game_over_query.iter() {
+            commands.entity(entity).despawn(); // ゲームオーバーや結果画面のUIを削除
+        }
+
+        // ゲーム状態をPlayingに戻す
+        if let Ok((mut player_transform, mut velocity)) = player_query.single_mut() {
+            // プレイヤーの位置を初期位置にリセット
+            player_transform.translation = level.player_start;
+            velocity.0 = 0.0; // 落ちている途中でも止める
+        }
+
+        // 敵キャラクターの位置を初期位置にリセット
+        for (mut enemy_transform, mut enemy, mut detection, mut pause) in enemy_query.iter_mut() {
+            enemy_transform.translation = enemy.initial_position; // 敵の初期位置に戻す
+            enemy.current_patrol_index = 0; // パトロールポイントのインデックスをリセット
+            detection.level = 0.0; // 検知ゲージを空にする
+            *pause = PatrolPause::default(); // 立ち止まっていても歩き出す
+
+            // 敵の向きを初期位置に向ける
+            if !enemy.patrol_points.is_empty() {
+                enemy_transform.rotation = enemy.initial_rotation; // 敵の初期向きに戻す
+            } else {
+                // パトロールポイントがない場合は外側を向ける
+                enemy_transform.rotation = Quat::from_rotation_y(std::f32::consts::PI);
+            }
+        }
+        // 音を調べていた敵も、気絶していた敵も巡回に戻す
+        for entity in &investigators {
+            commands
+                .entity(entity)
+                .remove::<(Investigating, KnockedOut)>();
+        }
+        *stats = RunStats::default(); // 記録も最初から
+        *stamina = Stamina::default(); // スタミナも満タンに戻す
+        // 拾ったカードキーを手放し、開けたドアも元に戻すため、レベルを配置し直す(時刻も最初に戻る)
+        *inventory = Inventory::default();
+        level.set_changed();
+        *game_state = GameState::Playing; // ゲーム状態をPlayingに戻す
+        log.push("Game restarted");
+    }
+}
+
+/// 敵に見つかったときにゲームパッドを振動させるシステム
+fn rumble_on_game_over(game_state: Res<GameState>, mut rumble: EventWriter<Rumble>) {
+    if *game_state == GameState::GameOver {
+        rumble.write(Rumble::heavy(0.5));
+    }
+}
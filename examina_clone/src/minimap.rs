@@ -0,0 +1,193 @@
+//! 画面の右下のミニマップ
+//!
+//! 上から見下ろした、巡回地点・ゴール・敵とその向き・プレイヤーの位置を点で描く。
+//! 「ミニマップ」ボタン(Mキーか右スティックの押し込み)で表示・非表示を切り替える。
+
+use bevy::prelude::*;
+use sample_common::AppState;
+
+use crate::{Enemy, GoalZone, Player, PlayerAction};
+
+/// ミニマップの一辺の長さ(ピクセル)
+pub const MINIMAP_SIZE: f32 = 160.0;
+
+/// ミニマップに映す範囲(原点から端までの距離、m)
+pub const MINIMAP_EXTENT: f32 = 18.0;
+
+/// ミニマップのプラグイン
+pub(crate) struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap).add_systems(
+            Update,
+            (toggle_minimap, update_minimap)
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// ミニマップの枠のマーカーコンポーネント(点は子として置く)
+#[derive(Component)]
+pub struct Minimap;
+
+/// ミニマップに描く点の種類(後の種類ほど上に重ねる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapIcon {
+    Waypoint, // 敵の巡回地点
+    Goal,     // ゴール
+    Guard,    // 敵
+    Facing,   // 敵の向いている方向(敵の点の前に置く)
+    Player,   // プレイヤー
+}
+
+impl MinimapIcon {
+    /// 点の大きさ(ピクセル)
+    fn size(self) -> f32 {
+        match self {
+            MinimapIcon::Waypoint | MinimapIcon::Facing => 4.0,
+            MinimapIcon::Goal => 12.0,
+            MinimapIcon::Guard | MinimapIcon::Player => 8.0,
+        }
+    }
+
+    /// 点の色(ゲームの中の色に合わせる)
+    fn color(self) -> Color {
+        match self {
+            MinimapIcon::Waypoint => Color::srgba(0.2, 0.8, 1.0, 0.6),
+            MinimapIcon::Goal => Color::srgb(1.0, 0.85, 0.2),
+            MinimapIcon::Guard | MinimapIcon::Facing => Color::srgb(1.0, 0.25, 0.2),
+            MinimapIcon::Player => Color::srgb(0.2, 0.9, 0.2),
+        }
+    }
+}
+
+/// ミニマップの点のコンポーネント(`update_minimap`が位置と種類を毎フレーム合わせる)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct MinimapDot(pub MinimapIcon);
+
+/// ワールドの位置に対応する、ミニマップの左上からの位置(ピクセル)
+/// 上から見下ろして、-Z方向(カメラの奥)を上にする。範囲の外は端に寄せる
+pub fn minimap_point(position: Vec3) -> Vec2 {
+    let ratio = (position.xz() / MINIMAP_EXTENT).clamp(Vec2::splat(-1.0), Vec2::ONE);
+    (ratio * 0.5 + 0.5) * MINIMAP_SIZE
+}
+
+/// ミニマップの枠を作るシステム(画面の右下。点は`update_minimap`で置く)
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            right: Val::Px(12.0),
+            width: Val::Px(MINIMAP_SIZE),
+            height: Val::Px(MINIMAP_SIZE),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.7)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.45)),
+        Visibility::Inherited,
+        Minimap,
+    ));
+}
+
+/// 「ミニマップ」ボタン(Mキーか右スティックの押し込み)でミニマップを表示・非表示にするシステム
+pub fn toggle_minimap(
+    actions: Res<ButtonInput<PlayerAction>>,
+    mut minimaps: Query<&mut Visibility, With<Minimap>>,
+) {
+    if !actions.just_pressed(PlayerAction::Minimap) {
+        return;
+    }
+    for mut visibility in &mut minimaps {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// ミニマップに、巡回地点・ゴール・敵とその向き・プレイヤーの点を置くシステム
+/// 点は使い回し、足りなければ追加して、余ったら消す(プレイヤー・敵・ゴール・巡回地点の順に上に重ねる)
+pub fn update_minimap(
+    mut commands: Commands,
+    minimaps: Query<(Entity, &Visibility), With<Minimap>>,
+    players: Query<&Transform, With<Player>>,
+    enemies: Query<(&Transform, &Enemy)>,
+    goals: Query<&Transform, With<GoalZone>>,
+    mut dots: Query<(
+        Entity,
+        &mut MinimapDot,
+        &mut Node,
+        &mut BackgroundColor,
+        &mut ZIndex,
+    )>,
+) {
+    let Ok((minimap, visibility)) = minimaps.single() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return; // 隠している間は点を動かさない
+    }
+
+    let mut icons = Vec::new();
+    for (_, enemy) in &enemies {
+        for waypoint in &enemy.patrol_points {
+            icons.push((minimap_point(waypoint.position), MinimapIcon::Waypoint));
+        }
+    }
+    for transform in &goals {
+        icons.push((minimap_point(transform.translation), MinimapIcon::Goal));
+    }
+    for (transform, _) in &enemies {
+        let position = minimap_point(transform.translation);
+        let facing = transform.forward().xz().normalize_or_zero();
+        icons.push((position, MinimapIcon::Guard));
+        icons.push((position + facing * 7.0, MinimapIcon::Facing)); // 敵の点の少し前
+    }
+    for transform in &players {
+        icons.push((minimap_point(transform.translation), MinimapIcon::Player));
+    }
+
+    let mut dots = dots.iter_mut();
+    for (position, icon) in icons {
+        let size = icon.size();
+        let left = Val::Px(position.x - size / 2.0);
+        let top = Val::Px(position.y - size / 2.0);
+        match dots.next() {
+            Some((_, mut dot, mut node, mut color, mut z_index)) => {
+                if dot.0 != icon {
+                    dot.0 = icon;
+                    node.width = Val::Px(size);
+                    node.height = Val::Px(size);
+                    color.0 = icon.color();
+                    *z_index = ZIndex(icon as i32);
+                }
+                node.left = left;
+                node.top = top;
+            }
+            None => {
+                commands.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left,
+                        top,
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        ..default()
+                    },
+                    BackgroundColor(icon.color()),
+                    BorderRadius::MAX,
+                    ZIndex(icon as i32),
+                    MinimapDot(icon),
+                    ChildOf(minimap),
+                ));
+            }
+        }
+    }
+    for (entity, _, _, _, _) in dots {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,316 @@
+//! 巡回経路の表示と編集(デバッグ用)
+//!
+//! F6キーで、敵ごとの巡回経路(`Enemy::patrol_points`)を線と番号付きの地点で表示する。
+//! 表示中は、地点をマウスの左ボタンでつかみ、地面の上をドラッグして動かせる。
+//! 離したときに、今の時間帯の経路を今遊んでいるレベルのファイル(アセットのディレクトリの`levels/`の中)に書き戻す。
+//! ファイルの先頭のコメントは残すが、行ごとのコメントは消える。Webではファイルに書き込めない。
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::{CurrentLevel, Enemy, LevelLayout, TimeOfDay, Waypoint};
+
+/// 巡回経路の表示を切り替えるキー
+const TOGGLE_KEY: KeyCode = KeyCode::F6;
+
+/// 地点をつかめる、カーソルからの距離(地面に沿った距離、m)
+pub const PICK_RADIUS: f32 = 0.8;
+
+/// 巡回経路の線と地点を描く高さ(地面の少し上)
+const ROUTE_HEIGHT: f32 = 0.1;
+
+/// 巡回経路の線の色
+const ROUTE_COLOR: Color = Color::srgb(0.2, 0.8, 1.0);
+
+/// 敵が次に向かう地点の色
+const NEXT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// つかんでいる地点の色
+const DRAG_COLOR: Color = Color::srgb(1.0, 0.3, 0.8);
+
+/// 巡回経路の表示と編集のプラグイン
+pub struct RouteEditorPlugin;
+
+impl Plugin for RouteEditorPlugin {
+    fn build(&self, app: &mut App) {
+        // アセットのディレクトリは、ランチャーに組み込まれていればサンプルのディレクトリになる
+        let asset_dir = app
+            .get_added_plugins::<AssetPlugin>()
+            .first()
+            .map_or_else(
+                || AssetPlugin::default().file_path,
+                |plugin| plugin.file_path.clone(),
+            )
+            .into();
+        app.insert_resource(RouteEditor {
+            asset_dir,
+            ..default()
+        })
+        .add_systems(
+            Update,
+            (
+                toggle_route_editor,
+                (drag_waypoints, draw_patrol_routes).run_if(route_editor_visible),
+                update_waypoint_labels, // 表示していないときは番号を消す
+            )
+                .chain(),
+        );
+    }
+}
+
+/// 巡回経路の表示と編集の状態
+#[derive(Resource, Debug, Default)]
+struct RouteEditor {
+    visible: bool,                     // 巡回経路を表示しているか
+    dragging: Option<(Entity, usize)>, // つかんでいる敵と、その地点の番号
+    asset_dir: PathBuf,                // アセットのディレクトリ(`AssetPlugin::file_path`)
+}
+
+/// 地点の番号の表示のマーカーコンポーネント
+#[derive(Component)]
+struct WaypointLabel;
+
+/// `point`からいちばん近い、`PICK_RADIUS`以内の地点(敵と、その地点の番号)
+/// 高さは比べず、地面に沿った距離で選ぶ
+pub fn pick_waypoint<'a>(
+    routes: impl IntoIterator<Item = (Entity, &'a [Waypoint])>,
+    point: Vec3,
+) -> Option<(Entity, usize)> {
+    routes
+        .into_iter()
+        .flat_map(|(entity, waypoints)| {
+            waypoints.iter().enumerate().map(move |(index, waypoint)| {
+                let distance = (waypoint.position - point).xz().length();
+                (distance, entity, index)
+            })
+        })
+        .filter(|(distance, _, _)| *distance <= PICK_RADIUS)
+        .min_by(|(a, _, _), (b, _, _)| a.total_cmp(b))
+        .map(|(_, entity, index)| (entity, index))
+}
+
+/// レベルをRONの文章にする(`header`はファイルの先頭に残すコメント)
+pub fn level_to_ron(level: &LevelLayout, header: &str) -> Result<String, ron::Error> {
+    let body = ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::default())?;
+    Ok(format!("{header}{body}\n"))
+}
+
+/// 地面の少し上の、線と地点を描く位置
+fn on_ground(position: Vec3) -> Vec3 {
+    Vec3::new(position.x, ROUTE_HEIGHT, position.z)
+}
+
+fn route_editor_visible(editor: Res<RouteEditor>) -> bool {
+    editor.visible
+}
+
+/// F6キーで巡回経路の表示を切り替えるシステム
+fn toggle_route_editor(keyboard: Res<ButtonInput<KeyCode>>, mut editor: ResMut<RouteEditor>) {
+    if keyboard.just_pressed(TOGGLE_KEY) {
+        editor.visible = !editor.visible;
+        editor.dragging = None;
+        info!("巡回経路の表示: {}", editor.visible);
+    }
+}
+
+/// カーソルの下の地面(高さ0の平面)の位置
+fn cursor_ground_point(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    let cursor = window.cursor_position()?;
+    let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
+
+/// 地点をマウスでつかんで動かし、離したらレベルのファイルに書き戻すシステム
+/// レベルに経路のある敵の地点だけを動かせる(敵の`patrol_points`と、レベルの今の時間帯の経路を一緒に変える)
+fn drag_waypoints(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    time_of_day: Res<TimeOfDay>,
+    current: Res<CurrentLevel>,
+    mut level: ResMut<LevelLayout>,
+    mut enemies: Query<(Entity, &Name, &mut Enemy)>,
+    mut editor: ResMut<RouteEditor>,
+) {
+    if mouse.just_released(MouseButton::Left) {
+        if editor.dragging.take().is_some() {
+            match save_level(&level, &editor.asset_dir, current.path()) {
+                Ok(path) => info!("巡回経路を{}に保存しました", path.display()),
+                Err(err) => warn!("巡回経路を保存できません: {err}"),
+            }
+        }
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    let Some(point) = cursor_ground_point(window, camera, camera_transform) else {
+        return;
+    };
+    let period = time_of_day.period();
+
+    if mouse.just_pressed(MouseButton::Left) {
+        let routes = enemies
+            .iter()
+            .filter(|(_, name, _)| level.route_for(name.as_str(), period).is_some())
+            .map(|(entity, _, enemy)| (entity, enemy.patrol_points.as_slice()));
+        editor.dragging = pick_waypoint(routes, point);
+    }
+    let Some((entity, index)) = editor.dragging else {
+        return;
+    };
+    let Ok((_, name, mut enemy)) = enemies.get_mut(entity) else {
+        editor.dragging = None; // つかんでいる間に敵が消えた
+        return;
+    };
+    let Some(waypoint) = enemy.patrol_points.get(index) else {
+        editor.dragging = None;
+        return;
+    };
+    // 高さは変えずに、地面に沿って動かす
+    let position = Vec3::new(point.x, waypoint.position.y, point.z);
+    if waypoint.position == position {
+        return;
+    }
+    enemy.patrol_points[index].position = position;
+
+    // 障害物などを配置し直さないように、変更を知らせずにレベルを書き換える
+    // (巡回経路は`apply_patrol_schedule`が毎フレームレベルに合わせるので、敵の経路と同じにしておく)
+    let route = level
+        .bypass_change_detection()
+        .route_for_mut(name.as_str(), period);
+    if let Some(waypoint) = route.and_then(|route| route.waypoints.get_mut(index)) {
+        waypoint.position = position;
+    }
+}
+
+/// 動かした経路をレベルのファイルに書き戻す(先頭のコメントは残す)
+/// 保存したファイルは再読み込みされるが、内容がリソースと同じなので配置し直さない
+#[cfg(not(target_arch = "wasm32"))]
+fn save_level(
+    level: &LevelLayout,
+    asset_dir: &Path,
+    level_path: &str,
+) -> Result<PathBuf, Box<dyn Error>> {
+    // 相対パスは実行ファイル(`cargo run`ではクレート)のディレクトリから(絶対パスならそのまま)
+    let path = bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join(asset_dir)
+        .join(level_path);
+    let source = std::fs::read_to_string(&path).unwrap_or_default();
+    let header: String = source
+        .lines()
+        .take_while(|line| line.starts_with("//"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    std::fs::write(&path, level_to_ron(level, &header)?)?;
+    Ok(path)
+}
+
+/// Webではファイルに書き込めない
+#[cfg(target_arch = "wasm32")]
+fn save_level(
+    _level: &LevelLayout,
+    _asset_dir: &Path,
+    level_path: &str,
+) -> Result<PathBuf, Box<dyn Error>> {
+    Err(format!("{level_path}に書き込めません(Webビルド)").into())
+}
+
+/// 敵ごとの巡回経路を、地点を結ぶ線と球で描くシステム
+/// 敵が次に向かう地点とつかんでいる地点は、色を変える
+fn draw_patrol_routes(
+    mut gizmos: Gizmos,
+    editor: Res<RouteEditor>,
+    enemies: Query<(Entity, &Enemy)>,
+) {
+    for (entity, enemy) in &enemies {
+        let points: Vec<Vec3> = enemy
+            .patrol_points
+            .iter()
+            .map(|waypoint| on_ground(waypoint.position))
+            .collect();
+        if points.len() >= 2 {
+            // 最後の地点から最初の地点に戻る
+            gizmos.linestrip(points.iter().chain(points.first()).copied(), ROUTE_COLOR);
+        }
+        for (index, point) in points.iter().enumerate() {
+            let color = if editor.dragging == Some((entity, index)) {
+                DRAG_COLOR
+            } else if index == enemy.current_patrol_index {
+                NEXT_COLOR
+            } else {
+                ROUTE_COLOR
+            };
+            gizmos.sphere(Isometry3d::from_translation(*point), 0.25, color);
+        }
+    }
+}
+
+/// 巡回経路の地点の番号(1から数える)を、画面上の地点の位置に表示するシステム
+/// 番号の表示は使い回し、足りなければ追加して、余ったら消す
+fn update_waypoint_labels(
+    mut commands: Commands,
+    editor: Res<RouteEditor>,
+    enemies: Query<&Enemy>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut labels: Query<(Entity, &mut Text, &mut Node), With<WaypointLabel>>,
+) {
+    let mut numbers = Vec::new();
+    let camera = cameras.single().ok().filter(|_| editor.visible);
+    if let Some((camera, camera_transform)) = camera {
+        for enemy in &enemies {
+            for (index, waypoint) in enemy.patrol_points.iter().enumerate() {
+                let position = on_ground(waypoint.position);
+                if let Ok(viewport) = camera.world_to_viewport(camera_transform, position) {
+                    numbers.push((viewport, index + 1));
+                }
+            }
+        }
+    }
+
+    let mut labels = labels.iter_mut();
+    for (viewport, number) in numbers {
+        // 地点の球に重ならないように、右上にずらす
+        let left = Val::Px(viewport.x + 8.0);
+        let top = Val::Px(viewport.y - 24.0);
+        match labels.next() {
+            Some((_, mut text, mut node)) => {
+                let number = number.to_string();
+                if text.0 != number {
+                    text.0 = number;
+                }
+                node.left = left;
+                node.top = top;
+            }
+            None => {
+                commands.spawn((
+                    Text::new(number.to_string()),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(ROUTE_COLOR),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left,
+                        top,
+                        ..default()
+                    },
+                    WaypointLabel,
+                ));
+            }
+        }
+    }
+    for (entity, _, _) in labels {
+        commands.entity(entity).despawn();
+    }
+}
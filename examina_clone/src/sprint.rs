@@ -0,0 +1,174 @@
+//! ダッシュとスタミナ
+//!
+//! ダッシュボタン(左CtrlかL3)を押して動いている間は速くなるが、足音が遠くまで聞こえ、スタミナが減る。
+//! スタミナを使い切ると、満タンに戻るまでダッシュできない。残りのスタミナは、減っている間だけバーで表示する。
+
+use bevy::prelude::*;
+use sample_common::{ActionAxes, AppState};
+
+use crate::{
+    Crouching, GameState, LIGHT_GEM_SIZE, Player, PlayerAction, StealthConfig, player_input,
+    update_crouch,
+};
+
+/// スタミナのバーの幅(ピクセル)
+const STAMINA_BAR_WIDTH: f32 = 120.0;
+
+/// ダッシュとスタミナのプラグイン
+pub(crate) struct SprintPlugin;
+
+impl Plugin for SprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stamina>()
+            .add_systems(Startup, spawn_stamina_bar)
+            .add_systems(
+                Update,
+                (
+                    update_sprint.after(update_crouch).before(player_input),
+                    update_stamina_bar.run_if(resource_changed::<Stamina>),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// ダッシュに使うスタミナ(リスタートで満タンに戻る)
+/// 使い切ると、満タンに戻るまでダッシュできない
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Stamina {
+    pub current: f32,    // 残りのスタミナ(0〜1)
+    pub exhausted: bool, // 使い切ってから、まだ満タンに戻っていないか
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: 1.0,
+            exhausted: false,
+        }
+    }
+}
+
+/// ダッシュしているプレイヤーのマーカーコンポーネント(ダッシュボタンを押して動いている間だけ付く)
+/// ダッシュしている間は速くなるが、足音が遠くまで聞こえ、スタミナが減る
+#[derive(Component, Debug, Default)]
+pub struct Sprinting;
+
+/// スタミナのバーの枠のマーカーコンポーネント(スタミナが減っている間だけ表示する)
+#[derive(Component)]
+struct StaminaBar;
+
+/// スタミナのバーの中身のマーカーコンポーネント(残りのスタミナに合わせて短くなる)
+#[derive(Component)]
+struct StaminaFill;
+
+/// ダッシュボタン(左CtrlかL3)を押して動いている間、プレイヤーに`Sprinting`を付けてスタミナを減らすシステム
+/// スニーキング中やしゃがんでいる間、スタミナを使い切ってから満タンに戻るまではダッシュできない。
+/// ダッシュしていない間(歩いている間や、スニーキング中も)はスタミナが戻る
+pub fn update_sprint(
+    mut commands: Commands,
+    actions: Res<ButtonInput<PlayerAction>>,
+    axes: Res<ActionAxes>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    config: Res<StealthConfig>,
+    mut stamina: ResMut<Stamina>,
+    players: Query<(Entity, Has<Sprinting>, Has<Crouching>), With<Player>>,
+) {
+    // ゲームオーバー中やゴールした後は、スタミナも変えない
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let delta = time.delta_secs();
+    let wants_sprint = actions.pressed(PlayerAction::Sprint)
+        && !actions.pressed(PlayerAction::Sneak)
+        && axes.movement != Vec2::ZERO;
+    for (player, sprinting, crouching) in &players {
+        let mut sprint = wants_sprint && !crouching && !stamina.exhausted;
+        if sprint {
+            if config.stamina_drain_time > 0.0 {
+                stamina.current = (stamina.current - delta / config.stamina_drain_time).max(0.0);
+            }
+            if stamina.current <= 0.0 {
+                stamina.exhausted = true; // 使い切ったので、満タンに戻るまで休む
+                sprint = false;
+            }
+        } else if stamina.current < 1.0 {
+            let regen = if config.stamina_regen_time > 0.0 {
+                delta / config.stamina_regen_time
+            } else {
+                1.0
+            };
+            stamina.current = (stamina.current + regen).min(1.0);
+            if stamina.current >= 1.0 {
+                stamina.exhausted = false;
+            }
+        }
+
+        if sprint && !sprinting {
+            commands.entity(player).insert(Sprinting);
+        } else if !sprint && sprinting {
+            commands.entity(player).remove::<Sprinting>();
+        }
+    }
+}
+
+/// スタミナのバーを作るシステム(明るさの宝石の上。スタミナが減っている間だけ表示する)
+fn spawn_stamina_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(LIGHT_GEM_SIZE + 20.0),
+                left: Val::Percent(50.0),
+                width: Val::Px(STAMINA_BAR_WIDTH),
+                height: Val::Px(8.0),
+                margin: UiRect::left(Val::Px(-STAMINA_BAR_WIDTH / 2.0)), // 中央に揃える
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.7)),
+            BorderColor(Color::srgb(0.4, 0.4, 0.45)),
+            Visibility::Hidden,
+            StaminaBar,
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(100.0), // 長さはupdate_stamina_barで変える
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(stamina_color(false)),
+                StaminaFill,
+            ));
+        });
+}
+
+/// スタミナのバーの色(使い切って休んでいる間は赤くする)
+fn stamina_color(exhausted: bool) -> Color {
+    if exhausted {
+        Color::srgb(0.9, 0.3, 0.2)
+    } else {
+        Color::srgb(0.3, 0.85, 0.4)
+    }
+}
+
+/// 残りのスタミナに合わせて、スタミナのバーの長さと色を変えるシステム
+fn update_stamina_bar(
+    stamina: Res<Stamina>,
+    mut bars: Query<&mut Visibility, With<StaminaBar>>,
+    mut fills: Query<(&mut Node, &mut BackgroundColor), With<StaminaFill>>,
+) {
+    for mut visibility in &mut bars {
+        *visibility = if stamina.current < 1.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden // 満タンのときは隠す
+        };
+    }
+    for (mut node, mut color) in &mut fills {
+        node.width = Val::Percent(stamina.current * 100.0);
+        color.0 = stamina_color(stamina.exhausted);
+    }
+}
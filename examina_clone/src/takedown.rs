@@ -0,0 +1,256 @@
+//! 背後からのテイクダウン
+//!
+//! 気づいていない敵の背後に近づくと操作の案内が出て、Eキー(ゲームパッドではYボタン)を押すと、敵は倒れて気を失う。
+//! 気を失った敵は何も見ず、何も聞かず、設定の時間が経つと起き上がって巡回に戻る(0以下ならずっと倒れたまま)。
+//! ほかの敵が倒れている敵を見つけると`BodyDiscovered`を送り、起きている敵はみなその場所を調べに行く。
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use sample_common::{AppState, LocalizedText};
+
+use crate::{
+    AudioCue, DebugLog, Detection, Enemy, GameState, Hiding, Interactable, InteractionEvent,
+    Investigating, Player, StealthConfig, VisionCone, enemy_hearing_system,
+    enemy_investigate_system, enemy_patrol_system, enemy_vision_system, has_line_of_sight,
+    interact,
+};
+
+/// 倒れるまでにかかる時間(秒)
+const FALL_TIME: f32 = 0.4;
+
+/// 倒れた敵の中心の高さ(カプセルが地面に横たわる高さ)
+const LYING_HEIGHT: f32 = 0.4;
+
+/// 背後からのテイクダウンのプラグイン
+pub(crate) struct TakedownPlugin;
+
+impl Plugin for TakedownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BodyDiscovered>().add_systems(
+            Update,
+            (
+                update_takedown_targets.before(interact), // 背後を取れている敵だけを操作できるようにする
+                (take_down, update_knocked_out, discover_bodies)
+                    .chain()
+                    .after(interact)
+                    .before(enemy_vision_system)
+                    .before(enemy_patrol_system)
+                    .before(enemy_hearing_system)
+                    .before(enemy_investigate_system), // 倒れた敵は、そのフレームから見ず、聞かず、動かない
+                hide_knocked_out_cones.after(discover_bodies),
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// 気を失って倒れている敵のコンポーネント
+/// 外すと(時間が来るか、やり直すと)起き上がる
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct KnockedOut {
+    pub standing: Transform, // 倒される前の姿勢(起き上がるときに戻す)
+    pub elapsed: f32,        // 倒されてからの時間(秒)
+    pub discovered: bool,    // ほかの敵に見つかったか(見つかるのは1回だけ)
+}
+
+/// ほかの敵が、倒れている敵を見つけたときのイベント
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct BodyDiscovered {
+    pub finder: Entity, // 見つけた敵
+    pub body: Entity,   // 倒れている敵
+    pub position: Vec3, // 倒れている場所
+}
+
+/// `player`から背後を取れている、気づいていない敵のうち、いちばん近いもの
+/// 敵の正面から`takedown_angle`度より後ろで、`takedown_range`以内にいて、検知ゲージが空で音を調べていない敵だけを選ぶ
+pub fn takedown_target<'a>(
+    player: Vec3,
+    guards: impl IntoIterator<Item = (Entity, &'a Transform, &'a Detection, bool)>,
+    config: &StealthConfig,
+) -> Option<Entity> {
+    guards
+        .into_iter()
+        .filter(|(_, _, detection, investigating)| detection.level <= 0.0 && !investigating)
+        .filter_map(|(entity, transform, _, _)| {
+            let to_player = (player - transform.translation).with_y(0.0);
+            let distance = to_player.length();
+            let angle = transform
+                .forward()
+                .with_y(0.0)
+                .angle_between(to_player)
+                .to_degrees();
+            (distance <= config.takedown_range && angle >= config.takedown_angle)
+                .then_some((distance, entity))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, entity)| entity)
+}
+
+/// 敵に付ける、テイクダウンの操作(`update_takedown_targets`が、背後を取れている間だけ操作できるようにする)
+pub(crate) fn takedown_interactable() -> Interactable {
+    Interactable::new(LocalizedText::new("takedown_action")).with_enabled(false)
+}
+
+/// 隠れていないプレイヤーが背後を取れている敵(`takedown_target`)だけを、操作できるようにするシステム
+pub fn update_takedown_targets(
+    config: Res<StealthConfig>,
+    players: Query<&Transform, (With<Player>, Without<Hiding>)>,
+    guards: Query<
+        (Entity, &Transform, &Detection, Has<Investigating>),
+        (With<Enemy>, Without<KnockedOut>),
+    >,
+    mut interactables: Query<(Entity, &mut Interactable), With<Enemy>>,
+) {
+    let target = players
+        .single()
+        .ok()
+        .and_then(|player| takedown_target(player.translation, &guards, &config));
+    for (guard, mut interactable) in &mut interactables {
+        let enabled = target == Some(guard);
+        if interactable.enabled != enabled || interactable.range != config.takedown_range {
+            interactable.enabled = enabled;
+            interactable.range = config.takedown_range;
+        }
+    }
+}
+
+/// 操作された敵を気絶させるシステム
+/// 検知ゲージを空にして音を調べるのもやめさせ、倒れる動きは`update_knocked_out`が進める
+pub fn take_down(
+    mut commands: Commands,
+    mut interactions: EventReader<InteractionEvent>,
+    game_state: Res<GameState>,
+    guards: Query<&Transform, (With<Enemy>, Without<KnockedOut>)>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing {
+        interactions.clear();
+        return;
+    }
+    for interaction in interactions.read() {
+        let Ok(transform) = guards.get(interaction.target) else {
+            continue;
+        };
+        commands
+            .entity(interaction.target)
+            .remove::<Investigating>()
+            .insert((
+                KnockedOut {
+                    standing: *transform,
+                    elapsed: 0.0,
+                    discovered: false,
+                },
+                Detection::default(),
+            ));
+        log.push("Took down a guard");
+    }
+}
+
+/// 気を失った敵を倒し、時間が来たら起き上がらせるシステム
+/// 倒れる間は前のめりに少しずつ傾け、起き上がるときは倒される前の姿勢に戻す
+pub fn update_knocked_out(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<StealthConfig>,
+    game_state: Res<GameState>,
+    mut guards: Query<(Entity, &mut Transform, &mut KnockedOut)>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    for (entity, mut transform, mut knocked_out) in &mut guards {
+        knocked_out.elapsed += time.delta_secs();
+        if config.takedown_time > 0.0 && knocked_out.elapsed >= config.takedown_time {
+            *transform = knocked_out.standing;
+            commands.entity(entity).remove::<KnockedOut>();
+            continue;
+        }
+        let standing = knocked_out.standing;
+        let lying = Transform {
+            translation: standing.translation.with_y(LYING_HEIGHT),
+            rotation: standing.rotation * Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+            ..standing
+        };
+        let t = (knocked_out.elapsed / FALL_TIME).min(1.0);
+        transform.set_if_neq(Transform {
+            translation: standing.translation.lerp(lying.translation, t),
+            rotation: standing.rotation.slerp(lying.rotation, t),
+            ..standing
+        });
+    }
+}
+
+/// 起きている敵が、視界の中に倒れている敵を見つけたら、起きている敵みなにその場所を調べさせるシステム
+pub fn discover_bodies(
+    mut commands: Commands,
+    config: Res<StealthConfig>,
+    game_state: Res<GameState>,
+    rapier_context: ReadRapierContext,
+    guards: Query<(Entity, &Transform, &Enemy), Without<KnockedOut>>,
+    mut bodies: Query<(Entity, &Transform, &mut KnockedOut)>,
+    mut discovered: EventWriter<BodyDiscovered>,
+    mut cues: EventWriter<AudioCue>,
+    mut log: ResMut<DebugLog>,
+) {
+    if *game_state != GameState::Playing {
+        return;
+    }
+    let rapier_context = rapier_context.single().ok();
+    for (body, body_transform, mut knocked_out) in &mut bodies {
+        if knocked_out.discovered {
+            continue;
+        }
+        let finder = guards.iter().find(|(guard, transform, enemy)| {
+            let to_body = body_transform.translation - transform.translation;
+            let angle = transform
+                .forward()
+                .angle_between(to_body.normalize_or_zero())
+                .to_degrees();
+            to_body.length() <= enemy.vision_range
+                && angle < enemy.vision_angle / 2.0
+                && has_line_of_sight(
+                    rapier_context.as_ref(),
+                    *guard,
+                    transform,
+                    body,
+                    body_transform,
+                )
+        });
+        let Some((finder, _, _)) = finder else {
+            continue;
+        };
+        knocked_out.discovered = true;
+        let position = body_transform.translation;
+        for (guard, transform, _) in &guards {
+            commands.entity(guard).insert(Investigating {
+                target: position.with_y(transform.translation.y),
+                remaining: config.investigate_time,
+            });
+        }
+        discovered.write(BodyDiscovered {
+            finder,
+            body,
+            position,
+        });
+        cues.write(AudioCue::BodyFound);
+        log.push("A guard found a body!");
+    }
+}
+
+/// 気を失った敵の視界の扇形を隠し、起き上がったら表示し直すシステム
+pub fn hide_knocked_out_cones(
+    guards: Query<(&Children, Has<KnockedOut>), With<Enemy>>,
+    mut cones: Query<&mut Visibility, With<VisionCone>>,
+) {
+    for (children, knocked_out) in &guards {
+        for child in children {
+            if let Ok(mut visibility) = cones.get_mut(*child) {
+                visibility.set_if_neq(if knocked_out {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                });
+            }
+        }
+    }
+}
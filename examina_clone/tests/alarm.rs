@@ -0,0 +1,255 @@
+//! 警報が出ると敵が速く動き、視界が広がり、最後に確認された場所へ集まり、時間が経つと元に戻ることと、
+//! 仕掛け線に触れたり監視カメラに映ったりすると警報が出ることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy, spawn_player};
+use examina_clone::{
+    AlarmLevel, BodyDiscovered, GameState, Hiding, Investigating, RaiseAlarm, SecurityCamera,
+    SecurityCameraPlace, StealthConfig, Tripwire, TripwirePlace, converge_on_alarm, decay_alarm,
+    enemy_vision_system, raise_alarm, trip_wires, watch_cameras,
+};
+
+/// 1フレームを0.1秒として、仕掛け線・監視カメラ・警報を出す・敵を集める・警報を解くシステムだけを動かすApp
+fn alarm_app(config: StealthConfig) -> App {
+    let mut app = StealthApp::new().config(config).frame_time(100).start();
+    app.add_event::<RaiseAlarm>()
+        .add_event::<BodyDiscovered>()
+        .add_systems(
+            Update,
+            (
+                trip_wires,
+                watch_cameras,
+                raise_alarm,
+                converge_on_alarm,
+                decay_alarm,
+            )
+                .chain(),
+        );
+    app
+}
+
+#[test]
+fn alarm_strength_eases_off_before_it_clears() {
+    let config = StealthConfig {
+        alarm_time: 10.0,
+        alarm_decay_time: 4.0,
+        alarm_speed_ratio: 1.5,
+        alarm_vision_ratio: 2.0,
+        ..default()
+    };
+    let mut alarm = AlarmLevel::default();
+    assert!(!alarm.is_raised());
+    assert_eq!(alarm.speed_ratio(&config), 1.0);
+    assert_eq!(alarm.vision_ratio(&config), 1.0);
+
+    alarm.raise(Vec3::new(1.0, 0.0, 2.0), &config);
+    assert!(alarm.is_raised());
+    assert_eq!(alarm.last_known, Some(Vec3::new(1.0, 0.0, 2.0)));
+    assert_eq!(alarm.speed_ratio(&config), 1.5);
+    assert_eq!(alarm.vision_ratio(&config), 2.0);
+
+    // 解ける前の4秒で、少しずつ元に戻る
+    alarm.remaining = 2.0;
+    assert_eq!(alarm.speed_ratio(&config), 1.25);
+    assert_eq!(alarm.vision_ratio(&config), 1.5);
+}
+
+#[test]
+fn guards_converge_on_the_alarm_and_it_clears_after_a_while() {
+    let mut app = alarm_app(StealthConfig {
+        alarm_time: 1.0,
+        ..default()
+    });
+    let guards: Vec<_> = [Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)]
+        .into_iter()
+        .map(|position| spawn_enemy(&mut app, position))
+        .collect();
+
+    app.world_mut().send_event(RaiseAlarm {
+        position: Vec3::new(5.0, 3.0, -5.0),
+    });
+    app.update();
+    assert!(app.world().resource::<AlarmLevel>().is_raised());
+    for guard in &guards {
+        let investigating = app.world().get::<Investigating>(*guard).unwrap();
+        // 自分の高さのまま、その場所へ向かう
+        assert_eq!(investigating.target, Vec3::new(5.0, 0.0, -5.0));
+    }
+
+    for _ in 0..10 {
+        app.update();
+    }
+    let alarm = app.world().resource::<AlarmLevel>();
+    assert!(!alarm.is_raised());
+    assert_eq!(alarm.last_known, None);
+}
+
+#[test]
+fn discovered_body_raises_the_alarm() {
+    let mut app = alarm_app(StealthConfig::default());
+    let guard = spawn_enemy(&mut app, Vec3::ZERO);
+
+    app.world_mut().send_event(BodyDiscovered {
+        finder: guard,
+        body: guard,
+        position: Vec3::new(0.0, 0.0, 3.0),
+    });
+    app.update();
+    assert_eq!(
+        app.world().resource::<AlarmLevel>().last_known,
+        Some(Vec3::new(0.0, 0.0, 3.0))
+    );
+}
+
+/// (-2, 0, -3)から(2, 0, -3)まで張った仕掛け線
+fn tripwire() -> Tripwire {
+    Tripwire::new(&TripwirePlace {
+        from: Vec3::new(-2.0, 0.0, -3.0),
+        to: Vec3::new(2.0, 0.0, -3.0),
+    })
+}
+
+#[test]
+fn tripwire_is_touched_along_its_whole_length() {
+    let wire = tripwire();
+
+    // 高さは比べず、プレイヤーの体の半径の内側なら触れている
+    assert!(wire.touches(Vec3::new(0.0, 1.0, -3.0)));
+    assert!(wire.touches(Vec3::new(1.5, 1.0, -2.7)));
+    assert!(wire.touches(Vec3::new(2.3, 1.0, -3.0)));
+    // 線の横や端の先で離れていれば触れない
+    assert!(!wire.touches(Vec3::new(0.0, 1.0, -2.0)));
+    assert!(!wire.touches(Vec3::new(3.0, 1.0, -3.0)));
+}
+
+#[test]
+fn touching_a_tripwire_raises_the_alarm_once() {
+    let mut app = alarm_app(StealthConfig::default());
+    let wire = app.world_mut().spawn(tripwire()).id();
+    let player = spawn_player(&mut app, Vec3::new(0.0, 1.0, 0.0));
+
+    app.update();
+    assert!(!app.world().resource::<AlarmLevel>().is_raised());
+
+    // 触れた場所で警報が出て、線が切れる
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = Vec3::new(1.0, 1.0, -3.2);
+    app.update();
+    assert_eq!(
+        app.world().resource::<AlarmLevel>().last_known,
+        Some(Vec3::new(1.0, 1.0, -3.2))
+    );
+    assert!(app.world().get::<Tripwire>(wire).unwrap().tripped);
+
+    // 切れた線に触れ直しても、もう鳴らない
+    *app.world_mut().resource_mut::<AlarmLevel>() = AlarmLevel::default();
+    app.update();
+    assert!(!app.world().resource::<AlarmLevel>().is_raised());
+}
+
+/// 原点の上から-Z方向を向いた、見える距離5・視界角度40度で、左右に`sweep`度ずつ首を振る監視カメラ
+fn security_camera(sweep: f32) -> SecurityCamera {
+    SecurityCamera::new(&SecurityCameraPlace {
+        position: Vec3::new(0.0, 2.4, 0.0),
+        facing: Vec3::NEG_Z,
+        range: 5.0,
+        angle: 40.0,
+        sweep,
+    })
+}
+
+#[test]
+fn security_camera_sees_what_its_sweep_points_at() {
+    let mut camera = security_camera(90.0);
+
+    // 高さは比べず、地面に沿った距離と向きで調べる
+    assert!(camera.sees(Vec3::new(0.0, 1.0, -3.0)));
+    assert!(!camera.sees(Vec3::new(0.0, 1.0, -6.0)));
+    assert!(!camera.sees(Vec3::new(-3.0, 1.0, 0.0)));
+
+    // 8秒で1往復するので、2秒後には左(-X方向)を向いている
+    camera.time = 2.0;
+    assert!(camera.heading().abs_diff_eq(Vec3::NEG_X, 1e-5));
+    assert!(camera.sees(Vec3::new(-3.0, 1.0, 0.0)));
+    assert!(!camera.sees(Vec3::new(0.0, 1.0, -3.0)));
+}
+
+#[test]
+fn security_camera_raises_the_alarm_while_the_player_is_in_view() {
+    let mut app = alarm_app(StealthConfig::default());
+    let camera = app
+        .world_mut()
+        .spawn((security_camera(0.0), Transform::default()))
+        .id();
+    let player = spawn_player(&mut app, Vec3::new(3.0, 1.0, 0.0));
+
+    app.update();
+    assert!(!app.world().resource::<AlarmLevel>().is_raised());
+
+    // 映った場所で警報が出る
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = Vec3::new(0.0, 1.0, -3.0);
+    app.update();
+    assert_eq!(
+        app.world().resource::<AlarmLevel>().last_known,
+        Some(Vec3::new(0.0, 1.0, -3.0))
+    );
+    assert!(app.world().get::<SecurityCamera>(camera).unwrap().spotted);
+
+    // 隠れ場所でしゃがんでいれば映らない
+    app.world_mut().entity_mut(player).insert(Hiding {
+        spot: Entity::PLACEHOLDER,
+        crouched: true,
+    });
+    *app.world_mut().resource_mut::<AlarmLevel>() = AlarmLevel::default();
+    app.update();
+    assert!(!app.world().resource::<AlarmLevel>().is_raised());
+    assert!(!app.world().get::<SecurityCamera>(camera).unwrap().spotted);
+}
+
+/// 原点で-Z方向を向いた敵と、`position`にいるプレイヤーを置いて、1フレームだけ視界検知を動かす
+fn vision_app(alarm: AlarmLevel, position: Vec3) -> App {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.insert_resource(alarm)
+        .add_systems(Update, enemy_vision_system);
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, position);
+    app.update();
+    app
+}
+
+fn raised() -> AlarmLevel {
+    let mut alarm = AlarmLevel::default();
+    alarm.raise(Vec3::ZERO, &StealthConfig::default());
+    alarm
+}
+
+#[test]
+fn alarm_widens_the_guards_vision() {
+    // 視界角45度の端の外(正面から約27度)にいるプレイヤーは、警報が出ていると見つかる
+    let side = Vec3::new(2.5, 0.0, -5.0);
+    let calm = vision_app(AlarmLevel::default(), side);
+    assert_eq!(*calm.world().resource::<GameState>(), GameState::Playing);
+    let alarmed = vision_app(raised(), side);
+    assert_eq!(
+        *alarmed.world().resource::<GameState>(),
+        GameState::GameOver
+    );
+}
+
+#[test]
+fn sighting_during_the_alarm_updates_the_last_known_position() {
+    let mut alarm = raised();
+    alarm.last_known = Some(Vec3::new(20.0, 0.0, 20.0));
+    let app = vision_app(alarm, Vec3::new(0.0, 0.0, -1.0));
+    assert_eq!(
+        app.world().resource::<AlarmLevel>().last_known,
+        Some(Vec3::new(0.0, 0.0, -1.0))
+    );
+}
@@ -0,0 +1,85 @@
+//! ラジオや機械の音の近くで立てた音が、敵に聞こえにくくなることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_enemy};
+use examina_clone::{
+    AmbientNoise, Investigating, NoiseEvent, NoiseKind, StealthConfig, enemy_hearing_system,
+    masked_noise_radius,
+};
+
+fn radio(radius: f32) -> AmbientNoise {
+    AmbientNoise {
+        kind: NoiseKind::Radio,
+        radius,
+    }
+}
+
+/// 音を聞きつけるシステムだけを動かし、原点に敵を、(10, 0, 0)にラジオを置いたApp
+fn hearing_app() -> (App, Entity) {
+    let mut app = StealthApp::new()
+        .config(StealthConfig {
+            noise_masking_ratio: 0.3,
+            ..default()
+        })
+        .build();
+    app.add_event::<NoiseEvent>()
+        .add_systems(Update, enemy_hearing_system);
+    app.world_mut()
+        .spawn((radio(3.0), Transform::from_xyz(10.0, 0.5, 0.0)));
+    let guard = spawn_enemy(&mut app, Vec3::ZERO);
+    (app, guard)
+}
+
+#[test]
+fn noise_inside_a_source_is_heard_from_a_shorter_distance() {
+    let config = StealthConfig {
+        noise_masking_ratio: 0.5,
+        ..default()
+    };
+    let sources = [
+        (Transform::from_xyz(0.0, 0.0, 0.0), radio(3.0)),
+        (Transform::from_xyz(1.0, 0.0, 0.0), radio(3.0)),
+    ];
+    let radius = |position: Vec3| {
+        masked_noise_radius(
+            &NoiseEvent {
+                position,
+                radius: 8.0,
+            },
+            sources
+                .iter()
+                .map(|(transform, source)| (transform, source)),
+            &config,
+        )
+    };
+
+    // 範囲の中(高さは比べない)。範囲が重なっていても、掛けるのは1回だけ
+    assert_eq!(radius(Vec3::new(0.5, 2.0, 0.0)), 4.0);
+    // 範囲の外
+    assert_eq!(radius(Vec3::new(0.0, 0.0, 5.0)), 8.0);
+}
+
+#[test]
+fn guard_does_not_hear_footsteps_masked_by_a_radio() {
+    let (mut app, guard) = hearing_app();
+    // ラジオのそばの足音は、8mではなく2.4mまでしか聞こえない
+    app.world_mut().send_event(NoiseEvent {
+        position: Vec3::new(8.0, 0.0, 0.0),
+        radius: 8.0,
+    });
+    app.update();
+    assert!(app.world().get::<Investigating>(guard).is_none());
+}
+
+#[test]
+fn guard_hears_the_same_footsteps_away_from_the_radio() {
+    let (mut app, guard) = hearing_app();
+    app.world_mut().send_event(NoiseEvent {
+        position: Vec3::new(-6.0, 0.0, 0.0),
+        radius: 8.0,
+    });
+    app.update();
+    assert!(app.world().get::<Investigating>(guard).is_some());
+}
@@ -0,0 +1,89 @@
+//! 検知ゲージの変化から効果音の合図が送られることと、状態に合わせて流す音楽を確認するテスト
+
+use bevy::prelude::*;
+use examina_clone::{AudioCue, Detection, GameState, MusicTrack, emit_detection_cues, music_for};
+use sample_common::AppState;
+
+/// 送られた効果音の合図
+#[derive(Resource, Default)]
+struct Heard(Vec<AudioCue>);
+
+fn record(mut cues: EventReader<AudioCue>, mut heard: ResMut<Heard>) {
+    heard.0.extend(cues.read().copied());
+}
+
+/// 検知ゲージから合図を送るシステムだけを動かすAppを作る
+fn cues_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<Heard>()
+        .add_event::<AudioCue>()
+        .add_systems(Update, (emit_detection_cues, record).chain());
+    app
+}
+
+/// 敵のゲージを変えて1フレーム進め、その間に送られた合図を返す
+fn set_levels(app: &mut App, levels: &[(Entity, f32)]) -> Vec<AudioCue> {
+    for (entity, level) in levels {
+        app.world_mut().get_mut::<Detection>(*entity).unwrap().level = *level;
+    }
+    app.update();
+    std::mem::take(&mut app.world_mut().resource_mut::<Heard>().0)
+}
+
+#[test]
+fn suspicion_then_detection_cues_play_once() {
+    let mut app = cues_app();
+    let first = app.world_mut().spawn(Detection::default()).id();
+    let second = app.world_mut().spawn(Detection::default()).id();
+    app.update();
+
+    // 2体が同時に気づき始めても、合図は1回
+    assert_eq!(
+        set_levels(&mut app, &[(first, 0.2), (second, 0.1)]),
+        vec![AudioCue::Suspicion]
+    );
+    // 溜まり続けている間は鳴らさない
+    assert!(set_levels(&mut app, &[(first, 0.6)]).is_empty());
+    assert_eq!(
+        set_levels(&mut app, &[(first, 1.0)]),
+        vec![AudioCue::Detected]
+    );
+
+    // 空に戻ってから、また気づき始めたら鳴らす
+    assert!(set_levels(&mut app, &[(second, 0.0)]).is_empty());
+    assert_eq!(
+        set_levels(&mut app, &[(second, 0.3)]),
+        vec![AudioCue::Suspicion]
+    );
+}
+
+#[test]
+fn instant_detection_plays_only_the_stinger() {
+    let mut app = cues_app();
+    let enemy = app.world_mut().spawn(Detection::default()).id();
+    app.update();
+
+    assert_eq!(
+        set_levels(&mut app, &[(enemy, 1.0)]),
+        vec![AudioCue::Detected]
+    );
+}
+
+#[test]
+fn music_follows_the_menu_and_game_over() {
+    assert_eq!(
+        music_for(&AppState::Menu, &GameState::Playing),
+        Some(MusicTrack::Menu)
+    );
+    assert_eq!(music_for(&AppState::Playing, &GameState::Playing), None);
+    assert_eq!(music_for(&AppState::Loading, &GameState::Playing), None);
+    assert_eq!(
+        music_for(&AppState::Playing, &GameState::GameOver),
+        Some(MusicTrack::GameOver)
+    );
+    assert_eq!(
+        music_for(&AppState::Paused, &GameState::GameOver),
+        Some(MusicTrack::GameOver)
+    );
+}
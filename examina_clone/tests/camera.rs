@@ -0,0 +1,165 @@
+//! プレイヤーの周りを回るカメラが、マウスとスティックで回り、壁の手前で止まることを確認するテスト
+
+use std::f32::consts::FRAC_PI_2;
+use std::time::Duration;
+
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use examina_clone::{CameraController, Player, camera_follow_player, orbit_camera};
+use sample_common::ActionAxes;
+
+/// 判定の誤差
+const EPSILON: f32 = 1e-4;
+
+/// カメラを回すシステムだけを動かす最小構成のAppを作る(1フレームを0.1秒とする)
+fn orbit_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .init_resource::<ButtonInput<MouseButton>>()
+        .init_resource::<AccumulatedMouseMotion>()
+        .init_resource::<ActionAxes>()
+        .add_systems(Update, orbit_camera);
+    app.world_mut().spawn(CameraController::default());
+    app.update(); // 最初のフレームは経過時間が0なので、ここで進めておく
+    app
+}
+
+/// 物理のワールド(Rapier)と、原点のプレイヤーとカメラを入れたAppを作る
+fn follow_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::headless_plugins(None))
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_systems(Update, camera_follow_player);
+    app.finish();
+    app.cleanup();
+    app.world_mut().spawn((Player, Transform::default()));
+    app.world_mut().spawn((
+        Camera3d::default(),
+        CameraController::default(),
+        Transform::from_xyz(0.0, 5.0, 10.0),
+    ));
+    app
+}
+
+fn controller(app: &mut App) -> CameraController {
+    let mut query = app.world_mut().query::<&CameraController>();
+    *query.single(app.world()).unwrap()
+}
+
+fn camera_transform(app: &mut App) -> Transform {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>();
+    *query.single(app.world()).unwrap()
+}
+
+#[test]
+fn direction_follows_yaw_and_pitch() {
+    let mut controller = CameraController {
+        yaw: 0.0,
+        pitch: 0.0,
+        ..default()
+    };
+    assert!(controller.direction().abs_diff_eq(Vec3::Z, EPSILON));
+
+    // 見下ろすほど、カメラは上に来る
+    controller.pitch = 0.5;
+    let direction = controller.direction();
+    assert!((direction.y - 0.5_f32.sin()).abs() < EPSILON);
+    assert!(direction.z > 0.0);
+
+    // 左回りに90度回すと、+X側から見る
+    controller.yaw = FRAC_PI_2;
+    controller.pitch = 0.0;
+    assert!(controller.direction().abs_diff_eq(Vec3::X, EPSILON));
+}
+
+#[test]
+fn mouse_orbits_only_while_the_right_button_is_held() {
+    let mut app = orbit_app();
+    let start = controller(&mut app);
+
+    app.world_mut()
+        .resource_mut::<AccumulatedMouseMotion>()
+        .delta = Vec2::new(100.0, 20.0);
+    app.update();
+    assert_eq!(controller(&mut app), start);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<MouseButton>>()
+        .press(MouseButton::Right);
+    app.update();
+    let turned = controller(&mut app);
+    // 右に動かすと右を向き、下に動かすと見下ろす
+    assert!((turned.yaw - (start.yaw - 100.0 * start.mouse_speed)).abs() < EPSILON);
+    assert!((turned.pitch - (start.pitch + 20.0 * start.mouse_speed)).abs() < EPSILON);
+}
+
+#[test]
+fn stick_orbits_and_pitch_stays_within_limits() {
+    let mut app = orbit_app();
+    let start = controller(&mut app);
+
+    app.world_mut().resource_mut::<ActionAxes>().look = Vec2::new(1.0, 0.0);
+    app.update();
+    let turned = controller(&mut app);
+    assert!((turned.yaw - (start.yaw - start.stick_speed * 0.1)).abs() < EPSILON);
+
+    // 下に倒し続けても、真上からは見下ろさない
+    app.world_mut().resource_mut::<ActionAxes>().look = Vec2::new(0.0, -1.0);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(controller(&mut app).pitch, start.max_pitch);
+
+    // 上に倒し続けても、地面すれすれまでしか下げない
+    app.world_mut().resource_mut::<ActionAxes>().look = Vec2::new(0.0, 1.0);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(controller(&mut app).pitch, start.min_pitch);
+}
+
+#[test]
+fn camera_settles_at_its_distance_looking_at_the_player() {
+    let mut app = follow_app();
+    let controller = controller(&mut app);
+
+    for _ in 0..100 {
+        app.update();
+    }
+
+    let camera = camera_transform(&mut app);
+    let pivot = Vec3::Y * 0.5;
+    assert!((camera.translation.distance(pivot) - controller.distance).abs() < 0.01);
+    assert!(
+        camera
+            .forward()
+            .abs_diff_eq(-controller.direction(), EPSILON)
+    );
+}
+
+#[test]
+fn wall_behind_the_player_pulls_the_camera_in() {
+    let mut app = follow_app();
+    // プレイヤーの後ろ(+Z側)4mの壁
+    app.world_mut().spawn((
+        RigidBody::Fixed,
+        Collider::cuboid(10.0, 10.0, 0.25),
+        Transform::from_xyz(0.0, 0.0, 4.0),
+    ));
+
+    for _ in 0..100 {
+        app.update();
+    }
+
+    // カメラは壁の手前の面(z = 3.75)より前にいる
+    let camera = camera_transform(&mut app);
+    assert!(camera.translation.z < 3.75, "{camera:?}");
+    assert!(camera.translation.z > 3.0, "{camera:?}");
+}
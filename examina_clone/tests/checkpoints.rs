@@ -0,0 +1,172 @@
+//! チェックポイントを通ると状態が記録され、見つかったときにそこから再開することを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_player};
+use examina_clone::{
+    CheckpointData, CheckpointState, Door, DoorPlace, Enemy, GameState, GuardSchedule, Inventory,
+    Keycard, KeycardPlace, LevelLayout, PlayerAction, Stamina, TimeOfDay, build_level,
+    reach_checkpoint, restore_checkpoint,
+};
+
+/// 手前にチェックポイント、その先にカードキーとドア、奥に見張りが1体いるレベル
+fn level() -> LevelLayout {
+    LevelLayout {
+        player_start: Vec3::new(0.0, 1.0, 8.0),
+        checkpoints: vec![Vec3::new(0.0, 0.0, 3.0)],
+        obstacles: Vec::new(),
+        keycards: vec![KeycardPlace {
+            key: "A".to_owned(),
+            position: Vec3::new(5.0, 0.5, 0.0),
+        }],
+        doors: vec![DoorPlace {
+            key: "A".to_owned(),
+            position: Vec3::new(0.0, 1.0, -5.0),
+            size: Vec3::new(3.0, 2.0, 0.5),
+        }],
+        guards: vec![GuardSchedule {
+            name: "Guard".to_owned(),
+            spawn: Vec3::new(0.0, 1.0, -10.0),
+            speed: None,
+            vision_range: None,
+            vision_angle: None,
+            routes: Vec::new(),
+        }],
+        ..default()
+    }
+}
+
+/// レベルの配置と、チェックポイントの記録・再開だけを動かすApp(メッシュとマテリアルのアセットを使う)
+fn checkpoint_app() -> (App, Entity) {
+    let mut app = StealthApp::new().headless().build();
+    app.insert_resource(level())
+        .init_resource::<TimeOfDay>()
+        .init_resource::<Inventory>()
+        .init_resource::<Stamina>()
+        .init_resource::<CheckpointData>()
+        .add_systems(
+            Update,
+            (
+                build_level.run_if(resource_changed::<LevelLayout>),
+                reach_checkpoint,
+                restore_checkpoint,
+            )
+                .chain(),
+        );
+    let player = spawn_player(&mut app, Vec3::new(0.0, 1.0, 8.0));
+    app.update();
+    (app, player)
+}
+
+fn move_to(app: &mut App, entity: Entity, position: Vec3) {
+    app.world_mut()
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation = position;
+}
+
+fn guard(app: &mut App) -> Entity {
+    app.world_mut()
+        .query_filtered::<Entity, With<Enemy>>()
+        .single(app.world())
+        .unwrap()
+}
+
+fn count<T: Component>(app: &mut App) -> usize {
+    app.world_mut().query::<&T>().iter(app.world()).count()
+}
+
+/// 記録したチェックポイントの状態
+fn saved(app: &App) -> CheckpointState {
+    app.world()
+        .resource::<CheckpointData>()
+        .saved
+        .clone()
+        .expect("checkpoint should be saved")
+}
+
+fn press_restart(app: &mut App) {
+    let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    input.release(PlayerAction::Restart);
+    input.clear();
+    input.press(PlayerAction::Restart);
+    app.update();
+}
+
+#[test]
+fn entering_a_checkpoint_records_the_state_once() {
+    let (mut app, player) = checkpoint_app();
+    assert_eq!(app.world().resource::<CheckpointData>().saved, None);
+
+    move_to(&mut app, player, Vec3::new(0.0, 1.0, 3.0));
+    app.update();
+    let saved = saved(&app);
+    assert_eq!(saved.player_position, Vec3::new(0.0, 1.0, 3.0));
+    assert_eq!(saved.guards.len(), 1);
+    assert_eq!(saved.keycards.len(), 1);
+    assert_eq!(saved.doors.len(), 1);
+
+    // 通ったチェックポイントの中を動いても、記録し直さない
+    move_to(&mut app, player, Vec3::new(0.5, 1.0, 3.0));
+    app.update();
+    let saved = saved(&app);
+    assert_eq!(saved.player_position, Vec3::new(0.0, 1.0, 3.0));
+}
+
+#[test]
+fn dying_restarts_from_the_last_checkpoint() {
+    let (mut app, player) = checkpoint_app();
+    move_to(&mut app, player, Vec3::new(0.0, 1.0, 3.0));
+    app.update();
+
+    // チェックポイントの後でカードキーを拾ってドアを開け、見張りが動いて時間が過ぎてから見つかる
+    let keycard = app
+        .world_mut()
+        .query_filtered::<Entity, With<Keycard>>()
+        .single(app.world())
+        .unwrap();
+    let door = app
+        .world_mut()
+        .query_filtered::<Entity, With<Door>>()
+        .single(app.world())
+        .unwrap();
+    app.world_mut().despawn(keycard);
+    app.world_mut().despawn(door);
+    app.world_mut()
+        .resource_mut::<Inventory>()
+        .keycards
+        .push("A".to_owned());
+    let guard = guard(&mut app);
+    move_to(&mut app, guard, Vec3::new(4.0, 1.0, -2.0));
+    move_to(&mut app, player, Vec3::new(0.0, 1.0, -7.0));
+    app.world_mut().resource_mut::<TimeOfDay>().hours = 20.0;
+    *app.world_mut().resource_mut::<GameState>() = GameState::GameOver;
+
+    press_restart(&mut app);
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+    let position = |app: &App, entity| app.world().get::<Transform>(entity).unwrap().translation;
+    assert_eq!(position(&app, player), Vec3::new(0.0, 1.0, 3.0));
+    assert_eq!(position(&app, guard), Vec3::new(0.0, 1.0, -10.0));
+    assert!(app.world().resource::<Inventory>().keycards.is_empty());
+    assert_eq!(count::<Keycard>(&mut app), 1);
+    assert_eq!(count::<Door>(&mut app), 1);
+    assert_eq!(app.world().resource::<TimeOfDay>(), &TimeOfDay::default());
+}
+
+#[test]
+fn without_a_checkpoint_restore_does_nothing() {
+    let (mut app, player) = checkpoint_app();
+    move_to(&mut app, player, Vec3::new(0.0, 1.0, -7.0));
+    *app.world_mut().resource_mut::<GameState>() = GameState::GameOver;
+
+    press_restart(&mut app);
+
+    // 最初からのやり直しはrestart_gameに任せる
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+    assert_eq!(
+        app.world().get::<Transform>(player).unwrap().translation,
+        Vec3::new(0.0, 1.0, -7.0)
+    );
+}
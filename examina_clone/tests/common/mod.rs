@@ -0,0 +1,135 @@
+//! テストで共通に使う、敵のシステムを動かすのに必要なリソースを入れたAppと、敵・プレイヤーの生成
+//!
+//! テストごとに使う関数が違うので、使わないものがあっても警告しない
+
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use examina_clone::{
+    AlarmLevel, DebugLog, Difficulty, Enemy, GameState, Player, PlayerAction, StealthConfig,
+    action_map,
+};
+use sample_common::ActionsPlugin;
+
+/// ゲームの状態・難易度・警報・ログ・操作・敵の設定を入れたAppの設定
+/// (テストするシステムは、組み立てたAppに追加する)
+#[derive(Default)]
+pub struct StealthApp {
+    config: StealthConfig,        // 敵の設定
+    frame_time: Option<Duration>, // 1フレームの経過時間(Noneなら実際の経過時間)
+    headless: bool,               // MinimalPluginsの代わりにヘッドレスのプラグインを使うか
+    physics: bool,                // 物理のワールド(Rapier)を入れるか
+    keyboard: bool,               // キーの入力を操作の割り当て(`action_map`)でアクションにするか
+}
+
+impl StealthApp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 敵の設定を変える
+    pub fn config(mut self, config: StealthConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 1フレームの経過時間をミリ秒で固定する
+    pub fn frame_time(mut self, millis: u64) -> Self {
+        self.frame_time = Some(Duration::from_millis(millis));
+        self
+    }
+
+    /// メッシュやマテリアルのアセットを使えるように、ヘッドレスのプラグインで組み立てる
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+
+    /// 物理のワールド(Rapier)を入れて、レイで遮るものを調べられるようにする
+    pub fn physics(mut self) -> Self {
+        self.headless = true;
+        self.physics = true;
+        self
+    }
+
+    /// `ButtonInput<KeyCode>`に押したキーを、ゲームと同じ割り当てでアクションと移動入力にする
+    pub fn keyboard(mut self) -> Self {
+        self.keyboard = true;
+        self
+    }
+
+    /// リソースを入れるだけで、フレームは進めないAppを作る
+    pub fn build(self) -> App {
+        let mut app = App::new();
+        if self.headless {
+            app.add_plugins(sample_common::headless::headless_plugins(None));
+        } else {
+            app.add_plugins(MinimalPlugins);
+        }
+        if self.physics {
+            app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        }
+        if self.keyboard {
+            app.init_resource::<ButtonInput<KeyCode>>()
+                .add_plugins(ActionsPlugin::new(action_map()));
+        }
+        if let Some(frame_time) = self.frame_time {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+        }
+        app.init_resource::<GameState>()
+            .init_resource::<Difficulty>()
+            .init_resource::<AlarmLevel>()
+            .init_resource::<DebugLog>()
+            .init_resource::<ButtonInput<PlayerAction>>()
+            .insert_resource(self.config);
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    /// 最初のフレームまで進めたAppを作る(最初のフレームは経過時間が0なので、先に進めておく)
+    pub fn start(self) -> App {
+        let mut app = self.build();
+        app.update();
+        app
+    }
+}
+
+/// 検知ゲージを使わず、視界に入った瞬間に見つかる設定
+pub fn instant_detection() -> StealthConfig {
+    StealthConfig {
+        detection_time: 0.0,
+        ..default()
+    }
+}
+
+/// `position`から巡回せずに見張る、視界範囲10・視界角度45度・速度4の敵
+pub fn enemy(position: Vec3) -> Enemy {
+    Enemy {
+        vision_range: 10.0,
+        vision_angle: 45.0,
+        patrol_points: Vec::new(),
+        current_patrol_index: 0,
+        speed: 4.0,
+        initial_position: position,
+        initial_rotation: Quat::IDENTITY,
+    }
+}
+
+/// `position`にいて-Z方向を向いた敵(`enemy`)を生成する
+pub fn spawn_enemy(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((enemy(position), Transform::from_translation(position)))
+        .id()
+}
+
+/// `position`にプレイヤーを生成する
+pub fn spawn_player(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((Player, Transform::from_translation(position)))
+        .id()
+}
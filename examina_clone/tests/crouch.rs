@@ -0,0 +1,68 @@
+//! しゃがむ操作と、しゃがんでいるプレイヤーが敵に見つかりにくくなることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy, spawn_player};
+use examina_clone::{Crouching, GameState, PlayerAction, enemy_vision_system, update_crouch};
+
+/// しゃがむ操作と視界検知だけを動かす最小構成のAppを作る
+fn crouch_app() -> App {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.add_systems(Update, (update_crouch, enemy_vision_system).chain());
+    app
+}
+
+fn set_crouch(app: &mut App, pressed: bool) {
+    let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    if pressed {
+        input.press(PlayerAction::Crouch);
+    } else {
+        input.release(PlayerAction::Crouch);
+    }
+}
+
+#[test]
+fn crouch_button_is_held_to_crouch() {
+    let mut app = crouch_app();
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, 20.0));
+
+    set_crouch(&mut app, true);
+    app.update();
+    assert!(app.world().get::<Crouching>(player).is_some());
+
+    set_crouch(&mut app, false);
+    app.update();
+    assert!(app.world().get::<Crouching>(player).is_none());
+}
+
+#[test]
+fn crouching_player_must_come_closer_to_be_detected() {
+    // 視界範囲10に倍率0.5を掛けて、5より遠ければ見つからない
+    let mut app = crouch_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -7.0));
+
+    set_crouch(&mut app, true);
+    app.update();
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .z = -4.0;
+    app.update();
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn standing_player_is_detected_at_full_range() {
+    let mut app = crouch_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -7.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
@@ -0,0 +1,92 @@
+//! F1キーのデバッグ表示に、プレイヤーと敵の状態と最近のログが出ることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::enemy;
+use examina_clone::{
+    DebugLog, DebugOverlayPlugin, DebugOverlayText, Player, PlayerAction, PlayerSpeed,
+};
+
+/// デバッグ表示だけを動かすApp
+fn overlay_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, DebugOverlayPlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<ButtonInput<PlayerAction>>();
+    app.world_mut().spawn((
+        Player,
+        PlayerSpeed(3.5),
+        Transform::from_xyz(0.0, 1.0, -5.0),
+    ));
+    app.world_mut().spawn((
+        Name::new("Sentry"),
+        enemy(Vec3::new(0.0, 1.0, 0.0)),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+    ));
+    app.update();
+    app
+}
+
+fn press_toggle(app: &mut App) {
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release(KeyCode::F1);
+    keyboard.clear();
+    keyboard.press(KeyCode::F1);
+    app.update();
+}
+
+/// デバッグ表示のテキストと表示・非表示
+fn overlay(app: &mut App) -> (String, Visibility) {
+    let (text, visibility) = app
+        .world_mut()
+        .query_filtered::<(&Text, &Visibility), With<DebugOverlayText>>()
+        .single(app.world())
+        .unwrap();
+    (text.0.clone(), *visibility)
+}
+
+#[test]
+fn log_keeps_only_the_latest_lines() {
+    let mut log = DebugLog::default();
+    for index in 0..DebugLog::CAPACITY + 3 {
+        log.push(format!("line {index}"));
+    }
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), DebugLog::CAPACITY);
+    assert_eq!(lines.first(), Some(&"line 3"));
+    assert_eq!(
+        lines.last().copied(),
+        Some(format!("line {}", DebugLog::CAPACITY + 2).as_str())
+    );
+}
+
+#[test]
+fn overlay_is_hidden_until_toggled() {
+    let mut app = overlay_app();
+    assert_eq!(overlay(&mut app), (String::new(), Visibility::Hidden));
+
+    press_toggle(&mut app);
+    let (_, visibility) = overlay(&mut app);
+    assert_eq!(visibility, Visibility::Inherited);
+
+    press_toggle(&mut app);
+    let (_, visibility) = overlay(&mut app);
+    assert_eq!(visibility, Visibility::Hidden);
+}
+
+#[test]
+fn overlay_shows_player_guards_and_log() {
+    let mut app = overlay_app();
+    app.world_mut()
+        .resource_mut::<DebugLog>()
+        .push("Sentry reached #1, heading to #2");
+    press_toggle(&mut app);
+
+    let (text, _) = overlay(&mut app);
+    assert!(text.contains("speed 3.5 m/s"), "{text}");
+    // 敵は-Z方向を向いていて、プレイヤーは真正面の5m先にいる
+    assert!(text.contains("Sentry  standing"), "{text}");
+    assert!(text.contains("dist 5.0  angle 0°"), "{text}");
+    assert!(text.ends_with("Sentry reached #1, heading to #2"), "{text}");
+}
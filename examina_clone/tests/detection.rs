@@ -0,0 +1,78 @@
+//! 敵ごとの検知ゲージが、見えている間に溜まり、満ちたときだけゲームオーバーになることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_enemy, spawn_player};
+use examina_clone::{Detection, GameState, StealthConfig, enemy_vision_system};
+
+/// 1フレームを0.1秒として、視界検知システムだけを動かすAppを作る
+fn detection_app() -> App {
+    let mut app = StealthApp::new().frame_time(100).start();
+    app.add_systems(Update, enemy_vision_system);
+    app
+}
+
+fn level(app: &App, enemy: Entity) -> f32 {
+    app.world().get::<Detection>(enemy).unwrap().level
+}
+
+fn game_state(app: &App) -> GameState {
+    app.world().resource::<GameState>().clone()
+}
+
+#[test]
+fn gauge_fills_while_seen_and_only_a_full_gauge_ends_the_game() {
+    let mut app = detection_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    app.update();
+    let first = level(&app, enemy);
+    assert!(first > 0.0 && first < 1.0, "level: {first}");
+    assert_eq!(game_state(&app), GameState::Playing);
+
+    app.update();
+    assert!(level(&app, enemy) > first);
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(level(&app, enemy), 1.0);
+    assert_eq!(game_state(&app), GameState::GameOver);
+}
+
+#[test]
+fn gauge_drains_when_the_player_leaves_the_view() {
+    let mut app = detection_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -8.0));
+
+    app.update();
+    app.update();
+    let seen = level(&app, enemy);
+    assert!(seen > 0.0);
+
+    // 敵の後ろに回ると減っていき、空になる
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = Vec3::new(0.0, 0.0, 5.0);
+    app.update();
+    assert!(level(&app, enemy) < seen);
+    for _ in 0..40 {
+        app.update();
+    }
+    assert_eq!(level(&app, enemy), 0.0);
+    assert_eq!(game_state(&app), GameState::Playing);
+}
+
+#[test]
+fn closer_and_centered_players_fill_the_gauge_faster() {
+    let config = StealthConfig::default();
+
+    assert!(config.detection_rate(0.1, 0.0) > config.detection_rate(0.9, 0.0));
+    assert!(config.detection_rate(0.5, 0.0) > config.detection_rate(0.5, 0.9));
+    // 視界の端では設定した時間で満ちる
+    assert!((config.detection_rate(1.0, 1.0) * config.detection_time - 1.0).abs() < 1e-5);
+}
@@ -0,0 +1,81 @@
+//! 難易度に合わせて、敵の視界・速度と検知ゲージの溜まる速さが変わることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_enemy, spawn_player};
+use examina_clone::{
+    Detection, Difficulty, Enemy, LevelLayout, StealthConfig, apply_config, enemy_vision_system,
+};
+
+/// 1フレームを0.1秒として、設定の反映と視界検知だけを動かすAppを作る
+fn difficulty_app(difficulty: Difficulty) -> (App, Entity) {
+    let mut app = StealthApp::new().frame_time(100).build();
+    app.insert_resource(difficulty)
+        .init_resource::<LevelLayout>()
+        .add_systems(Update, (apply_config, enemy_vision_system).chain());
+    app.update(); // 最初のフレームは経過時間が0なので、先に進めておく
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    (app, enemy)
+}
+
+/// 敵の視界範囲・視界角度・速度
+fn parameters(app: &App, enemy: Entity) -> (f32, f32, f32) {
+    let enemy = app.world().get::<Enemy>(enemy).unwrap();
+    (enemy.vision_range, enemy.vision_angle, enemy.speed)
+}
+
+#[test]
+fn harder_difficulties_scale_every_ratio_up() {
+    for ratio in [
+        Difficulty::vision_range_ratio,
+        Difficulty::vision_angle_ratio,
+        Difficulty::speed_ratio,
+        Difficulty::detection_ratio,
+    ] {
+        assert!(ratio(Difficulty::Easy) < ratio(Difficulty::Normal));
+        assert_eq!(ratio(Difficulty::Normal), 1.0);
+        assert!(ratio(Difficulty::Normal) < ratio(Difficulty::Hard));
+    }
+}
+
+#[test]
+fn guards_use_the_difficulty_ratios() {
+    let config = StealthConfig::default();
+    let (mut app, enemy) = difficulty_app(Difficulty::Normal);
+    app.update();
+    assert_eq!(
+        parameters(&app, enemy),
+        (config.vision_range, config.vision_angle, config.enemy_speed)
+    );
+
+    // 難易度を変えると、すぐに敵に反映する
+    app.insert_resource(Difficulty::Hard);
+    app.update();
+    let hard = Difficulty::Hard;
+    assert_eq!(
+        parameters(&app, enemy),
+        (
+            config.vision_range * hard.vision_range_ratio(),
+            config.vision_angle * hard.vision_angle_ratio(),
+            config.enemy_speed * hard.speed_ratio(),
+        )
+    );
+}
+
+#[test]
+fn detection_fills_faster_on_harder_difficulties() {
+    let gauge = |difficulty| {
+        let (mut app, enemy) = difficulty_app(difficulty);
+        spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+        app.update();
+        app.world().get::<Detection>(enemy).unwrap().level
+    };
+    let easy = gauge(Difficulty::Easy);
+    let normal = gauge(Difficulty::Normal);
+    let hard = gauge(Difficulty::Hard);
+    assert!(
+        0.0 < easy && easy < normal && normal < hard,
+        "{easy} {normal} {hard}"
+    );
+}
@@ -0,0 +1,67 @@
+//! プレイヤーの体が、動いている向きへ滑らかに回ることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_player};
+use examina_clone::{Facing, StealthConfig, turn_player};
+
+/// 1フレームを0.1秒として、体を回すシステムだけを動かすApp
+fn facing_app(config: StealthConfig) -> (App, Entity) {
+    let mut app = StealthApp::new().config(config).frame_time(100).start();
+    app.add_systems(Update, turn_player);
+    let player = spawn_player(&mut app, Vec3::ZERO);
+    (app, player)
+}
+
+fn face(app: &mut App, player: Entity, direction: Dir3) {
+    app.world_mut().get_mut::<Facing>(player).unwrap().0 = direction;
+}
+
+/// プレイヤーの体の前方
+fn forward(app: &App, player: Entity) -> Vec3 {
+    app.world()
+        .get::<Transform>(player)
+        .unwrap()
+        .forward()
+        .as_vec3()
+}
+
+#[test]
+fn facing_rotation_points_forward_along_the_direction() {
+    for direction in [Dir3::NEG_Z, Dir3::Z, Dir3::X, Dir3::NEG_X] {
+        let rotation = Facing(direction).rotation();
+        assert!((rotation * Vec3::NEG_Z).distance(*direction) < 1e-5);
+    }
+}
+
+#[test]
+fn player_turns_smoothly_toward_the_facing() {
+    let (mut app, player) = facing_app(StealthConfig::default());
+    assert_eq!(
+        *app.world().get::<Facing>(player).unwrap(),
+        Facing(Dir3::NEG_Z)
+    );
+    face(&mut app, player, Dir3::X);
+
+    // 1フレームでは向ききらず、少しずつ回る
+    app.update();
+    let angle = forward(&app, player).angle_between(Vec3::X).to_degrees();
+    assert!(0.0 < angle && angle < 89.0, "{angle}");
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(forward(&app, player).distance(Vec3::X) < 0.01);
+}
+
+#[test]
+fn zero_turn_speed_faces_immediately() {
+    let (mut app, player) = facing_app(StealthConfig {
+        turn_speed: 0.0,
+        ..default()
+    });
+    face(&mut app, player, Dir3::Z);
+    app.update();
+    assert!(forward(&app, player).distance(Vec3::Z) < 1e-5);
+}
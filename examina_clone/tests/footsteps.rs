@@ -0,0 +1,108 @@
+//! 歩いた距離と速さに合わせて足音の合図が送られることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_enemy, spawn_player};
+use examina_clone::{
+    FOOTSTEP_STRIDE, FootstepEvent, PlayerAction, StealthConfig, update_footsteps,
+};
+
+/// 送られた足音の合図
+#[derive(Resource, Default)]
+struct Heard(Vec<FootstepEvent>);
+
+fn record(mut footsteps: EventReader<FootstepEvent>, mut heard: ResMut<Heard>) {
+    heard.0.extend(footsteps.read().copied());
+}
+
+/// 1フレームを0.1秒として、足音の合図を送るシステムだけを動かすAppを作る
+fn footsteps_app() -> App {
+    let mut app = StealthApp::new().frame_time(100).start();
+    app.init_resource::<Heard>()
+        .add_event::<FootstepEvent>()
+        .add_systems(Update, (update_footsteps, record).chain());
+    app
+}
+
+/// `entity`を1フレームに`step`ずつ`frames`フレーム動かし、その間に送られた足音の合図を返す
+fn walk(app: &mut App, entity: Entity, step: Vec3, frames: usize) -> Vec<FootstepEvent> {
+    app.update(); // 今の位置を覚えさせる
+    app.world_mut().resource_mut::<Heard>().0.clear();
+    for _ in 0..frames {
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation += step;
+        app.update();
+    }
+    std::mem::take(&mut app.world_mut().resource_mut::<Heard>().0)
+}
+
+#[test]
+fn player_steps_once_per_stride_and_louder_when_faster() {
+    let config = StealthConfig::default();
+    let mut app = footsteps_app();
+    let player = spawn_player(&mut app, Vec3::ZERO);
+
+    // 設定の速度(1フレームに0.5m)で4m歩く
+    let step = config.player_speed * 0.1;
+    let fast = walk(&mut app, player, Vec3::X * step, 8);
+    let expected = step * 8.0 / FOOTSTEP_STRIDE;
+    assert!((fast.len() as f32 - expected).abs() <= 1.0, "{fast:?}");
+    assert!(fast.iter().all(|footstep| !footstep.enemy));
+    assert!((fast[0].volume - config.footstep_volume).abs() < 1e-5);
+
+    // 半分の速さでは、小さく低い足音になる
+    let slow = walk(&mut app, player, Vec3::X * step / 2.0, 8);
+    assert!(!slow.is_empty());
+    assert!(slow[0].volume < fast[0].volume);
+    assert!(slow[0].playback_rate < fast[0].playback_rate);
+}
+
+#[test]
+fn sneaking_player_makes_no_footsteps() {
+    let mut app = footsteps_app();
+    let player = spawn_player(&mut app, Vec3::ZERO);
+    app.world_mut()
+        .resource_mut::<ButtonInput<PlayerAction>>()
+        .press(PlayerAction::Sneak);
+
+    assert!(walk(&mut app, player, Vec3::X * 0.5, 8).is_empty());
+}
+
+#[test]
+fn teleporting_is_not_counted_as_walking() {
+    let mut app = footsteps_app();
+    let player = spawn_player(&mut app, Vec3::ZERO);
+
+    assert!(walk(&mut app, player, Vec3::X * 20.0, 1).is_empty());
+}
+
+#[test]
+fn enemy_footsteps_get_louder_as_the_guard_approaches() {
+    let config = StealthConfig::default();
+    let mut app = footsteps_app();
+    spawn_player(&mut app, Vec3::ZERO);
+
+    // 聞こえる距離より遠い敵の足音は鳴らない
+    let far = spawn_enemy(
+        &mut app,
+        Vec3::new(0.0, 0.0, -config.enemy_footstep_range - 5.0),
+    );
+    assert!(walk(&mut app, far, Vec3::X * 0.4, 8).is_empty());
+
+    let distant = spawn_enemy(&mut app, Vec3::new(0.0, 0.0, -12.0));
+    let distant_steps = walk(&mut app, distant, Vec3::X * 0.4, 8);
+    let near = spawn_enemy(&mut app, Vec3::new(0.0, 0.0, -3.0));
+    let near_steps = walk(&mut app, near, Vec3::X * 0.4, 8);
+
+    assert!(!distant_steps.is_empty() && !near_steps.is_empty());
+    assert!(
+        distant_steps
+            .iter()
+            .chain(&near_steps)
+            .all(|footstep| footstep.enemy)
+    );
+    assert!(near_steps[0].volume > distant_steps[0].volume);
+}
@@ -0,0 +1,118 @@
+//! ゲームパッドで移動・カメラ・スニーキング・操作ができ、キーボードと一緒に使えることを確認するテスト
+
+use bevy::input::gamepad::GamepadInput;
+use bevy::prelude::*;
+use examina_clone::{PlayerAction, action_map};
+use sample_common::{ActionAxes, ActionsPlugin};
+
+/// 操作の割り当てだけを動かす最小構成のAppと、つないだゲームパッド
+fn gamepad_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<ButtonInput<KeyCode>>()
+        .add_plugins(ActionsPlugin::new(action_map()));
+    let gamepad = app.world_mut().spawn(Gamepad::default()).id();
+    (app, gamepad)
+}
+
+fn gamepad_mut(app: &mut App, gamepad: Entity) -> Mut<'_, Gamepad> {
+    app.world_mut().get_mut::<Gamepad>(gamepad).unwrap()
+}
+
+fn set_analog(app: &mut App, gamepad: Entity, input: impl Into<GamepadInput>, value: f32) {
+    gamepad_mut(app, gamepad).analog_mut().set(input, value);
+}
+
+fn actions(app: &App) -> &ButtonInput<PlayerAction> {
+    app.world().resource::<ButtonInput<PlayerAction>>()
+}
+
+fn axes(app: &App) -> &ActionAxes {
+    app.world().resource::<ActionAxes>()
+}
+
+#[test]
+fn sticks_move_the_player_and_orbit_the_camera() {
+    let (mut app, gamepad) = gamepad_app();
+    set_analog(&mut app, gamepad, GamepadAxis::LeftStickY, 0.5);
+    set_analog(&mut app, gamepad, GamepadAxis::RightStickX, -1.0);
+
+    app.update();
+
+    // 浅く倒したぶんだけの移動入力になる
+    assert_eq!(axes(&app).movement, Vec2::new(0.0, 0.5));
+    assert_eq!(axes(&app).look, Vec2::new(-1.0, 0.0));
+}
+
+#[test]
+fn trigger_sneaks_and_north_button_interacts() {
+    let (mut app, gamepad) = gamepad_app();
+    let mut pad = gamepad_mut(&mut app, gamepad);
+    pad.digital_mut().press(GamepadButton::LeftTrigger2);
+    pad.digital_mut().press(GamepadButton::North);
+
+    app.update();
+
+    assert!(actions(&app).pressed(PlayerAction::Sneak));
+    assert!(actions(&app).just_pressed(PlayerAction::Interact));
+}
+
+#[test]
+fn south_button_jumps_and_restarts() {
+    let (mut app, gamepad) = gamepad_app();
+    gamepad_mut(&mut app, gamepad)
+        .digital_mut()
+        .press(GamepadButton::South);
+
+    app.update();
+
+    assert!(actions(&app).just_pressed(PlayerAction::Jump));
+    // Aボタンはやり直しにも割り当てる(restart_gameはプレイ中には何もしない)
+    assert!(actions(&app).just_pressed(PlayerAction::Restart));
+    assert!(!actions(&app).pressed(PlayerAction::Interact));
+}
+
+#[test]
+fn dpad_only_moves_the_player() {
+    let (mut app, gamepad) = gamepad_app();
+    let mut pad = gamepad_mut(&mut app, gamepad);
+    pad.digital_mut().press(GamepadButton::DPadUp);
+    pad.digital_mut().press(GamepadButton::DPadDown);
+    pad.digital_mut().press(GamepadButton::DPadLeft);
+
+    app.update();
+
+    let pressed: Vec<PlayerAction> = actions(&app).get_pressed().copied().collect();
+    assert!(
+        pressed.iter().all(|action| matches!(
+            action,
+            PlayerAction::Up | PlayerAction::Down | PlayerAction::Left | PlayerAction::Right
+        )),
+        "{pressed:?}"
+    );
+    assert_eq!(axes(&app).movement, Vec2::new(-1.0, 0.0));
+}
+
+#[test]
+fn keyboard_and_gamepad_work_together() {
+    let (mut app, gamepad) = gamepad_app();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::ArrowRight);
+    set_analog(&mut app, gamepad, GamepadAxis::LeftStickY, 1.0);
+
+    app.update();
+
+    // 矢印キーとスティックを合わせた入力(長さは1まで)
+    let movement = axes(&app).movement;
+    assert!(movement.abs_diff_eq(Vec2::new(1.0, 1.0).normalize(), 1e-5));
+
+    // キーボードだけでも同じ操作になる
+    set_analog(&mut app, gamepad, GamepadAxis::LeftStickY, 0.0);
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::ShiftLeft);
+    app.update();
+    assert!(actions(&app).pressed(PlayerAction::Sneak));
+    assert_eq!(axes(&app).movement, Vec2::new(1.0, 0.0));
+}
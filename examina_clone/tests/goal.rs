@@ -0,0 +1,85 @@
+//! 見つからずにゴールに入るとクリアになり、かかった時間と気づかれた回数が記録されることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_player};
+use examina_clone::{AudioCue, GameState, GoalZone, RunStats, reach_goal, track_run_stats};
+
+/// 1フレームを0.1秒として、記録とゴールの判定だけを動かすAppを作る
+fn goal_app() -> App {
+    let mut app = StealthApp::new().frame_time(100).start();
+    app.init_resource::<RunStats>()
+        .add_event::<AudioCue>()
+        .add_systems(Update, (track_run_stats, reach_goal).chain());
+    app.world_mut().spawn((
+        GoalZone { radius: 1.0 },
+        Transform::from_xyz(0.0, 0.03, -12.0),
+    ));
+    app
+}
+
+fn move_player(app: &mut App, player: Entity, position: Vec3) {
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = position;
+}
+
+fn game_state(app: &App) -> GameState {
+    app.world().resource::<GameState>().clone()
+}
+
+fn stats(app: &App) -> RunStats {
+    app.world().resource::<RunStats>().clone()
+}
+
+#[test]
+fn reaching_the_goal_wins_and_stops_the_clock() {
+    let mut app = goal_app();
+    let player = spawn_player(&mut app, Vec3::ZERO);
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(game_state(&app), GameState::Playing);
+    assert!((stats(&app).elapsed - 0.5).abs() < 1e-3);
+
+    // 立っている高さはゴールの円柱と違っても、地面に沿って中にいればクリア
+    move_player(&mut app, player, Vec3::new(0.5, 0.9, -11.5));
+    app.update();
+    assert_eq!(game_state(&app), GameState::Victory);
+
+    let finished = stats(&app).elapsed;
+    app.update();
+    assert_eq!(stats(&app).elapsed, finished);
+}
+
+#[test]
+fn entering_the_goal_after_being_caught_does_not_win() {
+    let mut app = goal_app();
+    let player = spawn_player(&mut app, Vec3::ZERO);
+    *app.world_mut().resource_mut::<GameState>() = GameState::GameOver;
+
+    move_player(&mut app, player, Vec3::new(0.0, 0.0, -12.0));
+    app.update();
+
+    assert_eq!(game_state(&app), GameState::GameOver);
+}
+
+#[test]
+fn each_time_a_guard_starts_noticing_is_counted() {
+    let mut app = goal_app();
+    spawn_player(&mut app, Vec3::ZERO);
+
+    app.world_mut().send_event(AudioCue::Suspicion);
+    app.update();
+    app.world_mut().send_event(AudioCue::Suspicion);
+    app.update();
+    // 倒れている仲間を見つけても、プレイヤーに気づいたことにはならない
+    app.world_mut().send_event(AudioCue::BodyFound);
+    app.update();
+    app.update();
+
+    assert_eq!(stats(&app).detections, 2);
+}
@@ -0,0 +1,140 @@
+//! レベルの複数の敵が、それぞれの巡回経路・速度・視界で配置されることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::StealthApp;
+use examina_clone::{
+    Enemy, GuardSchedule, LevelLayout, PatrolRoute, Player, StealthConfig, TimeOfDay, Waypoint,
+    apply_config, apply_patrol_schedule, build_level,
+};
+
+/// `spawn`から`to`へ往復する敵(速度と視界は設定ファイルの値)
+fn guard(name: &str, spawn: Vec3, to: Vec3) -> GuardSchedule {
+    GuardSchedule {
+        name: name.to_owned(),
+        spawn,
+        routes: vec![PatrolRoute {
+            time: default(),
+            waypoints: vec![Waypoint::new(spawn), Waypoint::new(to)],
+        }],
+        speed: None,
+        vision_range: None,
+        vision_angle: None,
+    }
+}
+
+/// 設定ファイルの値を使う敵と、速度と視界を変えた敵がいるレベル
+fn level() -> LevelLayout {
+    LevelLayout {
+        obstacles: Vec::new(),
+        guards: vec![
+            guard(
+                "Patrol",
+                Vec3::new(5.0, 1.0, 5.0),
+                Vec3::new(-5.0, 1.0, 5.0),
+            ),
+            GuardSchedule {
+                speed: Some(2.0),
+                vision_range: Some(14.0),
+                vision_angle: Some(30.0),
+                ..guard(
+                    "Sentry",
+                    Vec3::new(-6.0, 1.0, -10.0),
+                    Vec3::new(6.0, 1.0, -10.0),
+                )
+            },
+        ],
+        ..default()
+    }
+}
+
+/// レベルの配置と、敵の設定・巡回経路の反映だけを動かすApp(メッシュとマテリアルのアセットを使う)
+fn guards_app() -> App {
+    let mut app = StealthApp::new().headless().build();
+    app.insert_resource(level())
+        .init_resource::<TimeOfDay>()
+        .add_systems(
+            Update,
+            (
+                build_level.run_if(resource_changed::<LevelLayout>),
+                apply_config,
+                apply_patrol_schedule,
+            )
+                .chain(),
+        );
+    app.world_mut()
+        .spawn((Player, Transform::from_xyz(0.0, 1.0, 8.0)));
+    app.update();
+    app.update(); // 生成した敵に設定と巡回経路を反映する
+    app
+}
+
+/// 名前の敵の視界範囲・視界角度・速度と巡回地点
+fn enemy(app: &mut App, name: &str) -> (f32, f32, f32, Vec<Vec3>) {
+    app.world_mut()
+        .query::<(&Name, &Enemy)>()
+        .iter(app.world())
+        .find(|(enemy_name, _)| enemy_name.as_str() == name)
+        .map(|(_, enemy)| {
+            (
+                enemy.vision_range,
+                enemy.vision_angle,
+                enemy.speed,
+                enemy
+                    .patrol_points
+                    .iter()
+                    .map(|point| point.position)
+                    .collect(),
+            )
+        })
+        .unwrap()
+}
+
+#[test]
+fn each_guard_has_its_own_route_and_parameters() {
+    let mut app = guards_app();
+    let config = StealthConfig::default();
+
+    assert_eq!(
+        enemy(&mut app, "Patrol"),
+        (
+            config.vision_range,
+            config.vision_angle,
+            config.enemy_speed,
+            vec![Vec3::new(5.0, 1.0, 5.0), Vec3::new(-5.0, 1.0, 5.0)],
+        )
+    );
+    assert_eq!(
+        enemy(&mut app, "Sentry"),
+        (
+            14.0,
+            30.0,
+            2.0,
+            vec![Vec3::new(-6.0, 1.0, -10.0), Vec3::new(6.0, 1.0, -10.0)],
+        )
+    );
+}
+
+#[test]
+fn config_changes_keep_guard_overrides() {
+    let mut app = guards_app();
+
+    {
+        let mut config = app.world_mut().resource_mut::<StealthConfig>();
+        config.vision_range = 6.0;
+        config.enemy_speed = 5.0;
+    }
+    app.update();
+
+    // 設定ファイルの値を使う敵だけが変わる
+    assert_eq!(enemy(&mut app, "Patrol").0, 6.0);
+    assert_eq!(enemy(&mut app, "Patrol").2, 5.0);
+    assert_eq!(enemy(&mut app, "Sentry").0, 14.0);
+    assert_eq!(enemy(&mut app, "Sentry").2, 2.0);
+
+    // レベルで速度を外すと、設定ファイルの値に戻る
+    app.world_mut().resource_mut::<LevelLayout>().guards[1].speed = None;
+    app.update();
+    assert_eq!(enemy(&mut app, "Sentry").2, 5.0);
+}
@@ -0,0 +1,76 @@
+//! プレイヤーの立てた音を敵が聞きつけて調べに行くことを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, spawn_enemy};
+use examina_clone::{
+    Investigating, NoiseEvent, StealthConfig, enemy_hearing_system, enemy_investigate_system,
+};
+
+/// 音を聞いて調べに行くシステムだけを動かす最小構成のAppを作る
+fn hearing_app() -> App {
+    let mut app = StealthApp::new().build();
+    app.add_event::<NoiseEvent>().add_systems(
+        Update,
+        (enemy_hearing_system, enemy_investigate_system).chain(),
+    );
+    app
+}
+
+fn make_noise(app: &mut App, position: Vec3, radius: f32) {
+    app.world_mut().send_event(NoiseEvent { position, radius });
+}
+
+#[test]
+fn sneaking_is_quiet_and_running_is_loud() {
+    let config = StealthConfig::default();
+    assert_eq!(config.footstep_noise_radius(true, 1.0), 0.0);
+    assert_eq!(
+        config.footstep_noise_radius(false, 1.0),
+        config.run_noise_radius
+    );
+    // スティックを浅く倒すと足音も小さい
+    assert!(config.footstep_noise_radius(false, 0.5) < config.run_noise_radius);
+}
+
+#[test]
+fn enemy_in_range_turns_toward_the_noise() {
+    let mut app = hearing_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    make_noise(&mut app, Vec3::new(4.0, 0.0, 0.0), 8.0);
+    app.update();
+    app.update();
+
+    let investigating = *app.world().get::<Investigating>(enemy).unwrap();
+    assert_eq!(investigating.target, Vec3::new(4.0, 0.0, 0.0));
+    let forward = app.world().get::<Transform>(enemy).unwrap().forward();
+    assert!(forward.dot(Vec3::X) > 0.99);
+}
+
+#[test]
+fn enemy_out_of_range_does_not_hear() {
+    let mut app = hearing_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    make_noise(&mut app, Vec3::new(0.0, 0.0, 20.0), 8.0);
+    app.update();
+
+    assert!(app.world().get::<Investigating>(enemy).is_none());
+}
+
+#[test]
+fn enemy_returns_to_patrol_after_investigating() {
+    let mut app = hearing_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    // 敵のすぐそばの音なので、着いた状態で調べ始める
+    make_noise(&mut app, Vec3::new(0.1, 0.0, 0.0), 8.0);
+    app.update();
+    assert!(app.world().get::<Investigating>(enemy).is_some());
+
+    app.world_mut()
+        .get_mut::<Investigating>(enemy)
+        .unwrap()
+        .remaining = 0.0;
+    app.update();
+    assert!(app.world().get::<Investigating>(enemy).is_none());
+}
@@ -0,0 +1,101 @@
+//! 隠れ場所に出入りし、中でしゃがんでいる間は敵に見つからないことを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy, spawn_player};
+use examina_clone::{
+    GameState, Hiding, InteractionEvent, InteractionFocus, PlayerAction, enemy_vision_system,
+    hiding_spot, interact, update_crouch, update_hiding,
+};
+
+/// しゃがむ操作・操作・隠れ場所への出入り・視界検知だけを動かす最小構成のAppを作る
+/// (-5の位置に隠れ場所がある)
+fn hiding_app() -> App {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.init_resource::<InteractionFocus>()
+        .add_event::<InteractionEvent>()
+        .add_systems(
+            Update,
+            (update_crouch, interact, update_hiding, enemy_vision_system).chain(),
+        );
+    app.world_mut().spawn((
+        hiding_spot(Vec3::new(0.5, 1.0, 0.5)),
+        Transform::from_xyz(0.0, 0.0, -5.0),
+    ));
+    app
+}
+
+/// 操作を押した状態で1フレーム進め、押した直後の状態を消す(押し続けている状態は残す)
+fn update_with(app: &mut App, actions: &[PlayerAction]) {
+    {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+        input.release_all();
+        input.clear();
+        for action in actions {
+            input.press(*action);
+        }
+    }
+    app.update();
+}
+
+fn hiding(app: &App, player: Entity) -> Option<Hiding> {
+    app.world().get::<Hiding>(player).copied()
+}
+
+#[test]
+fn crouching_in_hiding_spot_is_not_detected() {
+    let mut app = hiding_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    update_with(&mut app, &[PlayerAction::Interact, PlayerAction::Crouch]);
+
+    assert!(hiding(&app, player).is_some_and(|hiding| hiding.crouched));
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+}
+
+#[test]
+fn standing_in_hiding_spot_is_detected() {
+    let mut app = hiding_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    update_with(&mut app, &[PlayerAction::Interact]);
+
+    assert!(hiding(&app, player).is_some_and(|hiding| !hiding.crouched));
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn interact_button_outside_spot_does_nothing() {
+    let mut app = hiding_app();
+    let player = spawn_player(&mut app, Vec3::new(3.0, 0.0, -5.0));
+
+    update_with(&mut app, &[PlayerAction::Interact, PlayerAction::Crouch]);
+
+    assert_eq!(hiding(&app, player), None);
+}
+
+#[test]
+fn player_leaves_by_button_or_by_walking_out() {
+    let mut app = hiding_app();
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    // もう一度押すと出る
+    update_with(&mut app, &[PlayerAction::Interact]);
+    assert!(hiding(&app, player).is_some());
+    update_with(&mut app, &[PlayerAction::Interact]);
+    assert_eq!(hiding(&app, player), None);
+
+    // 隠れ場所の外に出ても出る
+    update_with(&mut app, &[PlayerAction::Interact]);
+    assert!(hiding(&app, player).is_some());
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 2.0;
+    update_with(&mut app, &[]);
+    assert_eq!(hiding(&app, player), None);
+}
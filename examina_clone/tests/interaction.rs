@@ -0,0 +1,107 @@
+//! プレイヤーの正面の、手の届く範囲にあるいちばん近いものを操作できることを確認するテスト
+
+use bevy::prelude::*;
+use examina_clone::{INTERACT_RANGE, Interactable, interaction_target};
+use sample_common::LocalizedText;
+use sample_common::locale::TextArg;
+
+fn pickup() -> Interactable {
+    Interactable::new(LocalizedText::new("pickup_action").with_arg("key", "A"))
+}
+
+#[test]
+fn nearest_target_in_front_is_chosen() {
+    let near = Transform::from_xyz(0.0, 0.0, -1.0);
+    let far = Transform::from_xyz(0.5, 0.0, -1.4);
+    let behind = Transform::from_xyz(0.0, 0.0, 0.5);
+    let interactable = pickup();
+    let targets = [
+        (Entity::from_raw(1), &far, &interactable),
+        (Entity::from_raw(2), &near, &interactable),
+        (Entity::from_raw(3), &behind, &interactable),
+    ];
+
+    // -Z方向を向いていれば、正面の近い方を選ぶ(高さは比べない)
+    assert_eq!(
+        interaction_target(Vec3::new(0.0, 1.0, 0.0), Dir3::NEG_Z, targets),
+        Some(Entity::from_raw(2))
+    );
+    // 振り向けば、後ろにあったものを選ぶ
+    assert_eq!(
+        interaction_target(Vec3::ZERO, Dir3::Z, targets),
+        Some(Entity::from_raw(3))
+    );
+    // 真横にあるものは選ばない
+    assert_eq!(interaction_target(Vec3::ZERO, Dir3::X, targets), None);
+}
+
+#[test]
+fn target_out_of_range_or_disabled_is_ignored() {
+    let transform = Transform::from_xyz(0.0, 0.0, -(INTERACT_RANGE + 0.5));
+    let interactable = pickup();
+    let target = |interactable: &Interactable| {
+        interaction_target(
+            Vec3::ZERO,
+            Dir3::NEG_Z,
+            [(Entity::from_raw(1), &transform, interactable)],
+        )
+    };
+
+    assert_eq!(target(&interactable), None);
+    assert_eq!(
+        target(&interactable.clone().with_range(INTERACT_RANGE + 1.0)),
+        Some(Entity::from_raw(1))
+    );
+    assert_eq!(
+        target(
+            &interactable
+                .with_range(INTERACT_RANGE + 1.0)
+                .with_enabled(false)
+        ),
+        None
+    );
+}
+
+#[test]
+fn large_target_is_measured_from_its_face() {
+    // 幅4mのドア。中心は正面から外れていても、面が正面の近くにあれば選ぶ
+    let door = Interactable::new(LocalizedText::new("door_action"))
+        .with_half_size(Vec3::new(2.0, 1.0, 0.25));
+    let transform = Transform::from_xyz(1.5, 0.0, -1.0);
+    assert_eq!(
+        interaction_target(
+            Vec3::ZERO,
+            Dir3::NEG_Z,
+            [(Entity::from_raw(1), &transform, &door)]
+        ),
+        Some(Entity::from_raw(1))
+    );
+
+    // 範囲が0の隠れ場所は、中に入っているときだけ選ぶ(向きは問わない)
+    let spot = Interactable::new(LocalizedText::new("hide_action"))
+        .with_half_size(Vec3::new(0.5, 1.0, 0.5))
+        .with_range(0.0);
+    let transform = Transform::from_xyz(0.0, 0.0, -1.0);
+    let target = |position| {
+        interaction_target(
+            position,
+            Dir3::NEG_Z,
+            [(Entity::from_raw(1), &transform, &spot)],
+        )
+    };
+    assert_eq!(target(Vec3::new(0.0, 0.0, -1.2)), Some(Entity::from_raw(1)));
+    assert_eq!(target(Vec3::new(0.0, 0.0, 0.0)), None);
+}
+
+#[test]
+fn prompt_embeds_the_action_and_its_arguments() {
+    let prompt = pickup().prompt();
+    assert_eq!(prompt.key, "interact_prompt");
+    assert_eq!(
+        prompt.args,
+        vec![
+            ("action", TextArg::Key("pickup_action".to_owned())),
+            ("key", TextArg::Literal("A".to_owned())),
+        ]
+    );
+}
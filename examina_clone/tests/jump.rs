@@ -0,0 +1,106 @@
+//! 重力で落ち、接地しているときだけジャンプできることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use common::StealthApp;
+use examina_clone::{
+    Crouching, Player, PlayerAction, StealthConfig, VerticalVelocity, apply_gravity,
+};
+
+/// 1フレームを0.1秒として、重力とジャンプのシステムだけを動かすAppを作る
+fn jump_app() -> App {
+    let mut app = StealthApp::new()
+        .config(StealthConfig {
+            gravity: 10.0,
+            jump_speed: 5.0,
+            ..default()
+        })
+        .frame_time(100)
+        .start();
+    app.add_systems(Update, apply_gravity);
+    app
+}
+
+/// 前フレームのキャラクターコントローラーの結果が`grounded`だったプレイヤーを生成する
+fn spawn_player(app: &mut App, grounded: bool) -> Entity {
+    app.world_mut()
+        .spawn((
+            Player,
+            KinematicCharacterController::default(),
+            KinematicCharacterControllerOutput {
+                grounded,
+                ..default()
+            },
+        ))
+        .id()
+}
+
+fn velocity(app: &App, player: Entity) -> f32 {
+    app.world().get::<VerticalVelocity>(player).unwrap().0
+}
+
+fn press_jump(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<PlayerAction>>()
+        .press(PlayerAction::Jump);
+}
+
+#[test]
+fn player_falls_faster_while_in_the_air() {
+    let mut app = jump_app();
+    let player = spawn_player(&mut app, false);
+
+    app.update();
+    app.update();
+
+    // 0.1秒ごとに重力10で1ずつ速くなる
+    assert!((velocity(&app, player) + 2.0).abs() < 1e-4);
+    let controller = app
+        .world()
+        .get::<KinematicCharacterController>(player)
+        .unwrap();
+    assert!(controller.translation.unwrap().y < 0.0);
+}
+
+#[test]
+fn jump_starts_only_on_the_ground() {
+    let mut app = jump_app();
+    let grounded = spawn_player(&mut app, true);
+    let airborne = spawn_player(&mut app, false);
+
+    press_jump(&mut app);
+    app.update();
+
+    // 跳んだ瞬間の速さから、そのフレームの重力のぶんだけ遅くなる
+    assert!((velocity(&app, grounded) - 4.0).abs() < 1e-4);
+    assert!(velocity(&app, airborne) < 0.0);
+}
+
+#[test]
+fn crouching_player_cannot_jump() {
+    let mut app = jump_app();
+    let player = spawn_player(&mut app, true);
+    app.world_mut().entity_mut(player).insert(Crouching);
+
+    press_jump(&mut app);
+    app.update();
+
+    assert!(velocity(&app, player) <= 0.0);
+}
+
+#[test]
+fn landing_stops_the_fall() {
+    let mut app = jump_app();
+    let player = spawn_player(&mut app, true);
+    app.world_mut()
+        .get_mut::<VerticalVelocity>(player)
+        .unwrap()
+        .0 = -8.0;
+
+    app.update();
+
+    // 接地していれば、押し付けるぶんの小さな速さしか残らない
+    assert!((velocity(&app, player) + 1.0).abs() < 1e-4);
+}
@@ -0,0 +1,136 @@
+//! カードキーを拾って持ち物に入れ、同じ種類のカードキーでだけドアを開けられることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::StealthApp;
+use examina_clone::{
+    Facing, GameState, INTERACT_RANGE, InteractionEvent, InteractionFocus, Inventory, Player,
+    PlayerAction, door, interact, keycard, open_doors, pick_up_keycards,
+};
+
+/// カードキーとドアの操作だけを動かす最小構成のAppを作る
+fn keycard_app() -> App {
+    let mut app = StealthApp::new().build();
+    app.init_resource::<Inventory>()
+        .init_resource::<InteractionFocus>()
+        .add_event::<InteractionEvent>()
+        .add_systems(Update, (interact, (pick_up_keycards, open_doors)).chain());
+    app
+}
+
+/// `facing`を向いたプレイヤーを`position`に置く
+fn spawn_player(app: &mut App, position: Vec3, facing: Dir3) -> Entity {
+    app.world_mut()
+        .spawn((
+            Player,
+            Facing(facing),
+            Transform::from_translation(position),
+        ))
+        .id()
+}
+
+fn spawn_keycard(app: &mut App, key: &str, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((keycard(key), Transform::from_translation(position)))
+        .id()
+}
+
+/// 幅4m、厚さ0.5mのドアを`position`に置く
+fn spawn_door(app: &mut App, key: &str, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((
+            door(key, Vec3::new(2.0, 1.0, 0.25)),
+            Transform::from_translation(position),
+        ))
+        .id()
+}
+
+/// 「操作する」ボタンを押して1フレーム進める
+fn press_interact(app: &mut App) {
+    let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    input.release(PlayerAction::Interact);
+    input.clear();
+    input.press(PlayerAction::Interact);
+    app.update();
+}
+
+fn move_player(app: &mut App, player: Entity, position: Vec3) {
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = position;
+}
+
+fn keycards(app: &App) -> Vec<String> {
+    app.world().resource::<Inventory>().keycards.clone()
+}
+
+#[test]
+fn nearby_keycard_is_picked_up() {
+    let mut app = keycard_app();
+    let player = spawn_player(&mut app, Vec3::new(0.0, 1.0, 0.0), Dir3::X);
+    let near = spawn_keycard(&mut app, "A", Vec3::new(1.0, 0.5, 0.0));
+    let far = spawn_keycard(&mut app, "B", Vec3::new(INTERACT_RANGE + 1.0, 0.5, 0.0));
+
+    press_interact(&mut app);
+    assert_eq!(keycards(&app), vec!["A".to_owned()]);
+    assert!(app.world().get_entity(near).is_err());
+
+    // 遠くのカードキーは、近づくまで拾えない
+    press_interact(&mut app);
+    assert!(app.world().get_entity(far).is_ok());
+    move_player(&mut app, player, Vec3::new(INTERACT_RANGE + 1.0, 1.0, 0.0));
+    press_interact(&mut app);
+    assert_eq!(keycards(&app), vec!["A".to_owned(), "B".to_owned()]);
+}
+
+#[test]
+fn door_opens_only_with_the_matching_keycard() {
+    let mut app = keycard_app();
+    // ドアの中心からは離れていても、ドアの面の近くなら操作できる
+    spawn_player(&mut app, Vec3::new(1.5, 1.0, -1.0), Dir3::NEG_Z);
+    let door = spawn_door(&mut app, "A", Vec3::new(0.0, 1.0, -2.0));
+    app.world_mut()
+        .resource_mut::<Inventory>()
+        .keycards
+        .push("B".to_owned());
+
+    press_interact(&mut app);
+    assert!(app.world().get_entity(door).is_ok());
+
+    // 合うカードキーを拾ってから操作すると開く(ドアより近くにカードキーがあれば、先に拾う)
+    spawn_keycard(&mut app, "A", Vec3::new(1.5, 0.5, -1.5));
+    press_interact(&mut app);
+    assert!(app.world().get_entity(door).is_ok());
+    press_interact(&mut app);
+    assert!(app.world().get_entity(door).is_err());
+}
+
+#[test]
+fn nothing_happens_after_the_game_is_over() {
+    let mut app = keycard_app();
+    spawn_player(&mut app, Vec3::ZERO, Dir3::X);
+    let keycard = spawn_keycard(&mut app, "A", Vec3::new(0.5, 0.0, 0.0));
+    *app.world_mut().resource_mut::<GameState>() = GameState::GameOver;
+
+    press_interact(&mut app);
+
+    assert!(keycards(&app).is_empty());
+    assert!(app.world().get_entity(keycard).is_ok());
+}
+
+#[test]
+fn keycard_behind_the_player_is_not_picked_up() {
+    let mut app = keycard_app();
+    spawn_player(&mut app, Vec3::ZERO, Dir3::NEG_Z);
+    let keycard = spawn_keycard(&mut app, "A", Vec3::new(0.0, 0.0, 1.0));
+
+    press_interact(&mut app);
+    assert!(keycards(&app).is_empty());
+    assert!(app.world().get_entity(keycard).is_ok());
+    assert_eq!(
+        *app.world().resource::<InteractionFocus>(),
+        InteractionFocus(None)
+    );
+}
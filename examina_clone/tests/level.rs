@@ -0,0 +1,214 @@
+//! レベルのファイルから障害物・敵・ゴールを配置し、変更に合わせて配置し直すことを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use common::{StealthApp, spawn_player};
+use examina_clone::{
+    Enemy, Goal, GuardSchedule, LevelLayout, Obstacle, PatrolRoute, Player, WALL_HEIGHT,
+    WALL_THICKNESS, WallPlace, Waypoint, build_level,
+};
+
+/// 壁が2つ、敵が1体、ゴールのあるレベル
+fn level() -> LevelLayout {
+    LevelLayout {
+        player_start: Vec3::new(0.0, 1.0, 6.0),
+        goal: Some(Vec3::new(0.0, 0.0, -10.0)),
+        obstacles: vec![
+            Obstacle {
+                position: Vec3::new(-2.0, 1.0, 0.0),
+                size: Vec3::new(1.0, 2.0, 4.0),
+                dynamic: false,
+            },
+            Obstacle {
+                position: Vec3::new(2.0, 1.0, 0.0),
+                size: Vec3::new(1.0, 2.0, 4.0),
+                dynamic: false,
+            },
+        ],
+        guards: vec![guard("North", Vec3::new(0.0, 1.0, -5.0))],
+        ..default()
+    }
+}
+
+/// `spawn`から右へ往復する敵
+fn guard(name: &str, spawn: Vec3) -> GuardSchedule {
+    GuardSchedule {
+        name: name.to_owned(),
+        spawn,
+        speed: None,
+        vision_range: None,
+        vision_angle: None,
+        routes: vec![PatrolRoute {
+            time: default(),
+            waypoints: vec![
+                Waypoint::new(spawn),
+                Waypoint::new(spawn + Vec3::new(4.0, 0.0, 0.0)),
+            ],
+        }],
+    }
+}
+
+/// レベルを配置するシステムだけを動かすApp(メッシュとマテリアルのアセットを使う)
+fn level_app() -> App {
+    let mut app = StealthApp::new().headless().build();
+    app.insert_resource(level())
+        .add_systems(Update, build_level.run_if(resource_changed::<LevelLayout>));
+    spawn_player(&mut app, Vec3::new(0.0, 1.0, 6.0));
+    app.update();
+    app
+}
+
+/// 配置された障害物の数(敵とプレイヤー以外のリジットボディ)
+fn obstacle_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), (With<RigidBody>, Without<Enemy>, Without<Player>)>()
+        .iter(app.world())
+        .count()
+}
+
+/// 配置された敵の名前と初期位置
+fn guards(app: &mut App) -> Vec<(String, Vec3)> {
+    let mut guards: Vec<_> = app
+        .world_mut()
+        .query::<(&Name, &Enemy)>()
+        .iter(app.world())
+        .map(|(name, enemy)| (name.to_string(), enemy.initial_position))
+        .collect();
+    guards.sort_by(|a, b| a.0.cmp(&b.0));
+    guards
+}
+
+fn goal_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<Goal>>()
+        .iter(app.world())
+        .count()
+}
+
+fn player_position(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Player>>()
+        .single(app.world())
+        .unwrap()
+        .translation
+}
+
+#[test]
+fn level_file_places_obstacles_guards_and_goal() {
+    let mut app = level_app();
+
+    assert_eq!(obstacle_count(&mut app), 2);
+    assert_eq!(
+        guards(&mut app),
+        vec![("North".to_owned(), Vec3::new(0.0, 1.0, -5.0))]
+    );
+    assert_eq!(goal_count(&mut app), 1);
+
+    // 敵は初期位置で、最初の巡回地点の方(+X)を向く
+    let forward = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Enemy>>()
+        .single(app.world())
+        .unwrap()
+        .forward();
+    assert!(forward.dot(Vec3::X) > 0.99);
+}
+
+#[test]
+fn editing_the_level_rebuilds_the_scene() {
+    let mut app = level_app();
+    let player_before = player_position(&mut app);
+
+    // 障害物を1つにし、ゴールを外し、敵を入れ替える(プレイヤーの開始位置は同じ)
+    {
+        let mut level = app.world_mut().resource_mut::<LevelLayout>();
+        level.obstacles.truncate(1);
+        level.goal = None;
+        level.guards = vec![
+            guard("North", Vec3::new(1.0, 1.0, -6.0)),
+            guard("South", Vec3::new(0.0, 1.0, 4.0)),
+        ];
+    }
+    app.update();
+
+    assert_eq!(obstacle_count(&mut app), 1);
+    assert_eq!(goal_count(&mut app), 0);
+    assert_eq!(
+        guards(&mut app),
+        vec![
+            ("North".to_owned(), Vec3::new(1.0, 1.0, -6.0)),
+            ("South".to_owned(), Vec3::new(0.0, 1.0, 4.0)),
+        ]
+    );
+    assert_eq!(player_position(&mut app), player_before);
+
+    // 開始位置を変えると、プレイヤーもそこへ移る
+    app.world_mut().resource_mut::<LevelLayout>().player_start = Vec3::new(3.0, 1.0, 3.0);
+    app.update();
+    assert_eq!(player_position(&mut app), Vec3::new(3.0, 1.0, 3.0));
+
+    // レベルから消した敵は消える
+    app.world_mut()
+        .resource_mut::<LevelLayout>()
+        .guards
+        .retain(|guard| guard.name == "South");
+    app.update();
+    assert_eq!(
+        guards(&mut app),
+        vec![("South".to_owned(), Vec3::new(0.0, 1.0, 4.0))]
+    );
+}
+
+#[test]
+fn wall_stands_between_its_ends() {
+    // X方向の壁
+    let wall = WallPlace::new(Vec3::new(-4.0, 0.0, 2.0), Vec3::new(4.0, 0.0, 2.0));
+    assert_eq!(
+        wall.size(),
+        Vec3::new(8.0 + WALL_THICKNESS, WALL_HEIGHT, WALL_THICKNESS)
+    );
+    let transform = wall.transform();
+    assert_eq!(
+        transform.translation,
+        Vec3::new(0.0, WALL_HEIGHT / 2.0, 2.0)
+    );
+    assert!((transform.rotation * Vec3::X).distance(Vec3::X) < 1e-5);
+
+    // Z方向の、高さと厚さを指定した壁(長さの向きがZ軸に沿う)
+    let wall = WallPlace {
+        height: Some(1.0),
+        thickness: Some(0.5),
+        ..WallPlace::new(Vec3::new(3.0, 0.0, 5.0), Vec3::new(3.0, 0.0, -1.0))
+    };
+    assert_eq!(wall.size(), Vec3::new(6.5, 1.0, 0.5));
+    let transform = wall.transform();
+    assert_eq!(transform.translation, Vec3::new(3.0, 0.5, 2.0));
+    assert!((transform.rotation * Vec3::X).cross(Vec3::Z).length() < 1e-5);
+}
+
+#[test]
+fn level_file_places_walls_with_colliders() {
+    let mut app = level_app();
+    app.world_mut().resource_mut::<LevelLayout>().walls = vec![
+        WallPlace::new(Vec3::new(-6.0, 0.0, 10.0), Vec3::new(6.0, 0.0, 10.0)),
+        WallPlace::new(Vec3::new(6.0, 0.0, 10.0), Vec3::new(6.0, 0.0, -12.0)),
+    ];
+    app.update();
+
+    // 障害物2つと壁2枚
+    assert_eq!(obstacle_count(&mut app), 4);
+    let mut lengths: Vec<_> = app
+        .world_mut()
+        .query_filtered::<&Collider, (With<RigidBody>, Without<Enemy>, Without<Player>)>()
+        .iter(app.world())
+        .filter_map(|collider| collider.as_cuboid())
+        .map(|cuboid| cuboid.half_extents().x * 2.0)
+        .collect();
+    lengths.sort_by(f32::total_cmp);
+    assert_eq!(
+        lengths,
+        vec![1.0, 1.0, 12.0 + WALL_THICKNESS, 22.0 + WALL_THICKNESS]
+    );
+}
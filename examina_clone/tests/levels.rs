@@ -0,0 +1,141 @@
+//! レベルを順番に遊び、別のレベルに切り替えたときに前のレベルの敵や結果画面が消えることを確認するテスト
+
+mod common;
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use common::StealthApp;
+use examina_clone::{
+    CurrentLevel, Enemy, GameOverUI, GameState, GuardSchedule, Inventory, LEVELS, LevelLayout,
+    Obstacle, Player, RunStats, Stamina, VerticalVelocity, build_level, enter_level,
+};
+
+/// `name`の敵が1体いるレベル
+fn level(player_start: Vec3, name: &str) -> LevelLayout {
+    LevelLayout {
+        player_start,
+        guards: vec![GuardSchedule {
+            name: name.to_owned(),
+            spawn: Vec3::new(0.0, 1.0, -10.0),
+            speed: None,
+            vision_range: None,
+            vision_angle: None,
+            routes: Vec::new(),
+        }],
+        ..default()
+    }
+}
+
+/// レベルの切り替えと配置だけを動かすApp(メッシュとマテリアルのアセットを使う)
+fn levels_app() -> (App, Entity) {
+    let mut app = StealthApp::new().headless().build();
+    app.insert_resource(level(Vec3::new(0.0, 1.0, 8.0), "First"))
+        .init_resource::<CurrentLevel>()
+        .init_resource::<RunStats>()
+        .init_resource::<Inventory>()
+        .init_resource::<Stamina>()
+        .add_systems(
+            Update,
+            (enter_level, build_level)
+                .chain()
+                .run_if(resource_changed::<LevelLayout>),
+        );
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Transform::from_xyz(0.0, 1.0, 8.0),
+            VerticalVelocity::default(),
+        ))
+        .id();
+    app.update();
+    (app, player)
+}
+
+/// 配置された敵の名前
+fn guard_names(app: &mut App) -> Vec<String> {
+    app.world_mut()
+        .query_filtered::<&Name, With<Enemy>>()
+        .iter(app.world())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn count<T: Component>(app: &mut App) -> usize {
+    app.world_mut().query::<&T>().iter(app.world()).count()
+}
+
+/// ゴールして結果画面を出し、記録の付いた状態にする
+fn clear_level(app: &mut App, player: Entity) {
+    app.world_mut().spawn(GameOverUI);
+    app.world_mut().insert_resource(GameState::Victory);
+    app.world_mut().resource_mut::<RunStats>().elapsed = 42.0;
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = Vec3::new(0.0, 1.0, -14.0);
+}
+
+#[test]
+fn every_level_file_parses() {
+    for path in LEVELS {
+        let file = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(path);
+        let source = std::fs::read_to_string(&file).unwrap();
+        let level: LevelLayout =
+            ron::from_str(&source).unwrap_or_else(|err| panic!("{path}: {err}"));
+        assert!(level.goal.is_some(), "{path} should have a goal");
+        assert!(!level.guards.is_empty(), "{path} should have guards");
+    }
+}
+
+#[test]
+fn levels_advance_in_order() {
+    assert_eq!(CurrentLevel::default().path(), LEVELS[0]);
+    assert_eq!(CurrentLevel(0).next(), Some(CurrentLevel(1)));
+    assert_eq!(CurrentLevel(1).path(), LEVELS[1]);
+    assert_eq!(CurrentLevel(LEVELS.len() - 1).next(), None);
+}
+
+#[test]
+fn switching_level_replaces_the_previous_level() {
+    let (mut app, player) = levels_app();
+    assert_eq!(guard_names(&mut app), ["First"]);
+    clear_level(&mut app, player);
+
+    app.insert_resource(CurrentLevel(1));
+    app.insert_resource(level(Vec3::new(0.0, 1.0, 12.0), "Second"));
+    app.update();
+
+    assert_eq!(guard_names(&mut app), ["Second"]);
+    assert_eq!(count::<GameOverUI>(&mut app), 0);
+    assert_eq!(
+        app.world().get::<Transform>(player).unwrap().translation,
+        Vec3::new(0.0, 1.0, 12.0)
+    );
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+    assert_eq!(*app.world().resource::<RunStats>(), RunStats::default());
+}
+
+#[test]
+fn reloading_the_same_level_keeps_the_run() {
+    let (mut app, player) = levels_app();
+    clear_level(&mut app, player);
+
+    // 同じレベルのファイルを編集して読み込み直しても、結果画面と記録は残る
+    app.world_mut()
+        .resource_mut::<LevelLayout>()
+        .obstacles
+        .push(Obstacle {
+            position: Vec3::new(3.0, 1.0, 0.0),
+            size: Vec3::ONE,
+            dynamic: false,
+        });
+    app.update();
+
+    assert_eq!(guard_names(&mut app), ["First"]);
+    assert_eq!(count::<GameOverUI>(&mut app), 1);
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Victory);
+    assert_eq!(app.world().resource::<RunStats>().elapsed, 42.0);
+}
@@ -0,0 +1,94 @@
+//! プレイヤーの立っている場所の明るさと、暗いところでは敵が近づかないと見つけられないことを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy, spawn_player};
+use examina_clone::{
+    GameState, Illumination, LightZone, StealthConfig, TimeOfDay, enemy_vision_system,
+    update_illumination,
+};
+
+/// 夜(22時)にして、明るさと視界検知だけを動かす最小構成のAppを作る
+fn light_app() -> App {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.insert_resource(TimeOfDay { hours: 22.0 })
+        .add_systems(Update, (update_illumination, enemy_vision_system).chain());
+    app
+}
+
+/// 半径4、中心の明るさ1の明かりを`position`に置く
+fn spawn_light(app: &mut App, position: Vec3) {
+    app.world_mut().spawn((
+        LightZone {
+            radius: 4.0,
+            brightness: 1.0,
+        },
+        Transform::from_translation(position),
+    ));
+}
+
+fn light_level(app: &App, player: Entity) -> f32 {
+    app.world().get::<Illumination>(player).unwrap().level
+}
+
+#[test]
+fn lights_brighten_the_ground_around_them() {
+    let config = instant_detection();
+    let mut app = light_app();
+    spawn_light(&mut app, Vec3::new(0.0, 3.0, 20.0));
+    let dark = spawn_player(&mut app, Vec3::new(10.0, 1.0, 20.0));
+    let edge = spawn_player(&mut app, Vec3::new(2.0, 1.0, 20.0));
+    let lit = spawn_player(&mut app, Vec3::new(0.0, 1.0, 20.0));
+
+    app.update();
+
+    assert_eq!(light_level(&app, dark), config.night_light);
+    assert!((light_level(&app, edge) - (config.night_light + 0.5)).abs() < 1e-5);
+    assert_eq!(light_level(&app, lit), 1.0);
+
+    // 昼は明かりのないところも明るい
+    app.world_mut().resource_mut::<TimeOfDay>().hours = 12.0;
+    app.update();
+    assert_eq!(light_level(&app, dark), config.day_light);
+}
+
+#[test]
+fn player_in_the_dark_must_be_closer_to_be_detected() {
+    // 夜の暗がりでは、視界範囲10に倍率0.4 + 0.6 × 0.2 = 0.52を掛けて、5.2より遠ければ見つからない
+    let mut app = light_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, -7.0));
+
+    app.update();
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .z = -5.0;
+    app.update();
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn player_under_a_light_is_detected_at_full_range() {
+    let mut app = light_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -9.0));
+    spawn_light(&mut app, Vec3::new(0.0, 3.0, -9.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn darkness_shortens_the_vision_range_down_to_the_configured_ratio() {
+    let config = StealthConfig::default();
+
+    assert_eq!(config.light_vision_ratio(0.0), config.dark_vision_ratio);
+    assert_eq!(config.light_vision_ratio(1.0), 1.0);
+    assert!(config.light_vision_ratio(0.3) < config.light_vision_ratio(0.6));
+}
@@ -0,0 +1,127 @@
+//! ミニマップに、プレイヤー・敵とその向き・巡回地点・ゴールの点が置かれ、Mで隠せることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, enemy, spawn_player};
+use examina_clone::{
+    Enemy, GoalZone, MINIMAP_EXTENT, MINIMAP_SIZE, Minimap, MinimapDot, MinimapIcon, Player,
+    PlayerAction, Waypoint, minimap_point, toggle_minimap, update_minimap,
+};
+
+/// ミニマップの枠と、ミニマップのシステムだけを動かす最小構成のAppを作る
+fn minimap_app() -> App {
+    let mut app = StealthApp::new().build();
+    app.add_systems(Update, (toggle_minimap, update_minimap).chain());
+    app.world_mut()
+        .spawn((Minimap, Node::default(), Visibility::Inherited));
+    app
+}
+
+/// (4, 1, 0)にいて-Z方向を向き、2つの地点を巡回する敵を生成する
+fn spawn_enemy(app: &mut App) -> Entity {
+    app.world_mut()
+        .spawn((
+            Enemy {
+                patrol_points: vec![
+                    Waypoint::new(Vec3::new(4.0, 1.0, 0.0)),
+                    Waypoint::new(Vec3::new(4.0, 1.0, -8.0)),
+                ],
+                ..enemy(Vec3::new(4.0, 1.0, 0.0))
+            },
+            Transform::from_xyz(4.0, 1.0, 0.0),
+        ))
+        .id()
+}
+
+/// ミニマップの点の、種類ごとの中心の位置
+fn dots(app: &mut App, icon: MinimapIcon) -> Vec<Vec2> {
+    let mut query = app.world_mut().query::<(&MinimapDot, &Node)>();
+    query
+        .iter(app.world())
+        .filter(|(dot, _)| dot.0 == icon)
+        .map(|(_, node)| {
+            let (Val::Px(left), Val::Px(top), Val::Px(size)) = (node.left, node.top, node.width)
+            else {
+                panic!("ミニマップの点の位置はピクセルで指定する");
+            };
+            Vec2::new(left, top) + size / 2.0
+        })
+        .collect()
+}
+
+#[test]
+fn world_positions_map_onto_the_minimap_with_forward_up() {
+    let center = Vec2::splat(MINIMAP_SIZE / 2.0);
+
+    assert_eq!(minimap_point(Vec3::new(0.0, 1.0, 0.0)), center);
+    // 右は右、奥(-Z)は上
+    let point = minimap_point(Vec3::new(MINIMAP_EXTENT / 2.0, 0.0, -MINIMAP_EXTENT / 2.0));
+    assert_eq!(
+        point,
+        center + Vec2::new(MINIMAP_SIZE / 4.0, -MINIMAP_SIZE / 4.0)
+    );
+    // 範囲の外は端に寄せる
+    assert_eq!(
+        minimap_point(Vec3::new(-100.0, 0.0, 100.0)),
+        Vec2::new(0.0, MINIMAP_SIZE)
+    );
+}
+
+#[test]
+fn minimap_shows_the_player_guards_routes_and_goal() {
+    let mut app = minimap_app();
+    spawn_player(&mut app, Vec3::new(0.0, 1.0, 0.0));
+    let enemy = spawn_enemy(&mut app);
+    app.world_mut().spawn((
+        GoalZone { radius: 1.0 },
+        Transform::from_xyz(0.0, 0.03, -14.0),
+    ));
+
+    app.update();
+
+    assert_eq!(
+        dots(&mut app, MinimapIcon::Player),
+        vec![Vec2::splat(MINIMAP_SIZE / 2.0)]
+    );
+    assert_eq!(dots(&mut app, MinimapIcon::Goal).len(), 1);
+    assert_eq!(dots(&mut app, MinimapIcon::Waypoint).len(), 2);
+    let guard = dots(&mut app, MinimapIcon::Guard);
+    let facing = dots(&mut app, MinimapIcon::Facing);
+    assert_eq!(guard.len(), 1);
+    assert!(guard[0].abs_diff_eq(minimap_point(Vec3::new(4.0, 1.0, 0.0)), 1e-4));
+    // -Z方向を向いているので、向きの点は敵の点の上にある
+    assert!(facing[0].y < guard[0].y);
+    assert!((facing[0].x - guard[0].x).abs() < 1e-4);
+
+    // 敵がいなくなったら、敵と巡回地点の点も消える
+    app.world_mut().despawn(enemy);
+    app.update();
+    assert!(dots(&mut app, MinimapIcon::Guard).is_empty());
+    assert!(dots(&mut app, MinimapIcon::Waypoint).is_empty());
+    assert_eq!(dots(&mut app, MinimapIcon::Player).len(), 1);
+}
+
+#[test]
+fn minimap_button_toggles_the_minimap() {
+    let mut app = minimap_app();
+    let visibility = |app: &mut App| {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&Visibility, With<Minimap>>();
+        *query.single(app.world()).unwrap()
+    };
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<PlayerAction>>()
+        .press(PlayerAction::Minimap);
+    app.update();
+    assert_eq!(visibility(&mut app), Visibility::Hidden);
+
+    let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    input.release(PlayerAction::Minimap);
+    input.clear();
+    input.press(PlayerAction::Minimap);
+    app.update();
+    assert_eq!(visibility(&mut app), Visibility::Inherited);
+}
@@ -0,0 +1,105 @@
+//! レベルの巡回の予定が、時間帯に合わせて敵に設定されることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, enemy};
+use examina_clone::{
+    DayPeriod, Enemy, GuardSchedule, LevelLayout, PatrolRoute, PatrolTime, TimeOfDay, Waypoint,
+    apply_patrol_schedule,
+};
+
+/// 昼は2地点を往復し、夜は速度を指定した別の2地点を往復するレベル
+fn level() -> LevelLayout {
+    LevelLayout {
+        guards: vec![GuardSchedule {
+            name: "Guard".to_owned(),
+            spawn: Vec3::new(5.0, 1.0, 0.0),
+            speed: None,
+            vision_range: None,
+            vision_angle: None,
+            routes: vec![
+                PatrolRoute {
+                    time: PatrolTime::Day,
+                    waypoints: vec![
+                        Waypoint::new(Vec3::new(5.0, 1.0, 0.0)),
+                        Waypoint::new(Vec3::new(-5.0, 1.0, 0.0)),
+                    ],
+                },
+                PatrolRoute {
+                    time: PatrolTime::Night,
+                    waypoints: vec![
+                        Waypoint::new(Vec3::new(0.0, 1.0, 8.0)).with_speed(2.0),
+                        Waypoint::new(Vec3::new(0.0, 1.0, -8.0)).with_speed(6.0),
+                    ],
+                },
+            ],
+        }],
+        ..default()
+    }
+}
+
+/// 巡回の予定を設定するシステムだけを動かす最小構成のAppを作る
+fn schedule_app(hours: f32) -> App {
+    let mut app = StealthApp::new().build();
+    app.insert_resource(level())
+        .insert_resource(TimeOfDay { hours })
+        .add_systems(Update, apply_patrol_schedule);
+    app
+}
+
+/// 指定した位置に、巡回経路を持たない敵を生成する
+fn spawn_guard(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((
+            enemy(position),
+            Name::new("Guard"),
+            Transform::from_translation(position),
+        ))
+        .id()
+}
+
+#[test]
+fn time_of_day_selects_period() {
+    assert_eq!(TimeOfDay { hours: 8.0 }.period(), DayPeriod::Day);
+    assert_eq!(TimeOfDay { hours: 20.0 }.period(), DayPeriod::Night);
+    assert_eq!(TimeOfDay { hours: 3.0 }.period(), DayPeriod::Night);
+}
+
+#[test]
+fn always_route_is_used_when_period_has_none() {
+    let mut level = level();
+    level.guards[0].routes[1].time = PatrolTime::Always;
+    level.guards[0].routes.swap(0, 1);
+
+    // 昼は昼専用の経路を優先し、夜は`Always`の経路を使う
+    let day = level.route_for("Guard", DayPeriod::Day).unwrap();
+    assert_eq!(day.time, PatrolTime::Day);
+    let night = level.route_for("Guard", DayPeriod::Night).unwrap();
+    assert_eq!(night.time, PatrolTime::Always);
+    assert!(level.route_for("Unknown", DayPeriod::Day).is_none());
+}
+
+#[test]
+fn route_switches_with_time_of_day() {
+    let mut app = schedule_app(8.0);
+    let guard = spawn_guard(&mut app, Vec3::new(-4.0, 1.0, 0.0));
+
+    app.update();
+
+    let enemy = app.world().get::<Enemy>(guard).unwrap();
+    assert_eq!(enemy.patrol_points, level().guards[0].routes[0].waypoints);
+    assert_eq!(enemy.current_patrol_index, 1); // 最も近い地点から始める
+
+    // 夜になると、地点ごとの速度を持つ夜の経路に切り替わる
+    app.world_mut().resource_mut::<TimeOfDay>().hours = 20.0;
+    app.world_mut()
+        .entity_mut(guard)
+        .insert(Transform::from_xyz(0.0, 1.0, 6.0));
+    app.update();
+
+    let enemy = app.world().get::<Enemy>(guard).unwrap();
+    assert_eq!(enemy.patrol_points, level().guards[0].routes[1].waypoints);
+    assert_eq!(enemy.current_patrol_index, 0);
+    assert_eq!(enemy.patrol_points[0].speed, Some(2.0));
+}
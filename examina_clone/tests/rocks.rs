@@ -0,0 +1,79 @@
+//! 投げた石が地面に落ちたときに、1回だけ音を立てることを確認するテスト
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use examina_clone::{NoiseEvent, Rock, StealthConfig, rock, rock_impacts};
+
+/// 立てられた音
+#[derive(Resource, Default)]
+struct Heard(Vec<NoiseEvent>);
+
+fn record(mut noises: EventReader<NoiseEvent>, mut heard: ResMut<Heard>) {
+    heard.0.extend(noises.read().copied());
+}
+
+/// 物理のワールド(Rapier)と地面を入れ、石の当たりを調べるApp(1フレームを1/60秒とする)
+fn rock_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::headless_plugins(None))
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 60.0,
+        )))
+        .init_resource::<StealthConfig>()
+        .init_resource::<Heard>()
+        .add_event::<NoiseEvent>()
+        .add_systems(Update, (rock_impacts, record).chain());
+    app.finish();
+    app.cleanup();
+    app.world_mut().spawn((
+        RigidBody::Fixed,
+        Collider::cuboid(10.0, 0.1, 10.0),
+        Transform::from_xyz(0.0, -0.1, 0.0),
+    ));
+    app
+}
+
+#[test]
+fn rock_makes_one_noise_where_it_lands() {
+    let mut app = rock_app();
+    let thrown = app
+        .world_mut()
+        .spawn((
+            rock(Vec3::new(2.0, 0.0, 0.0)),
+            Transform::from_xyz(0.0, 2.0, 0.0),
+        ))
+        .id();
+
+    // 2mの高さから落ちて、1秒ほどで地面に着く
+    for _ in 0..120 {
+        app.update();
+    }
+
+    let heard = &app.world().resource::<Heard>().0;
+    assert_eq!(heard.len(), 1, "{heard:?}");
+    let config = StealthConfig::default();
+    assert_eq!(heard[0].radius, config.rock_noise_radius);
+    // 横に飛びながら落ちた場所(地面の近く)で音がする
+    assert!(heard[0].position.x > 0.5);
+    assert!(heard[0].position.y < 0.5);
+    assert!(app.world().get::<Rock>(thrown).unwrap().landed);
+}
+
+#[test]
+fn rock_is_removed_after_it_has_landed_for_a_while() {
+    let mut app = rock_app();
+    let thrown = app
+        .world_mut()
+        .spawn((rock(Vec3::ZERO), Transform::from_xyz(0.0, 0.5, 0.0)))
+        .id();
+
+    for _ in 0..60 * 7 {
+        app.update();
+    }
+
+    assert!(app.world().get_entity(thrown).is_err());
+}
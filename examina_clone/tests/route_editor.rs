@@ -0,0 +1,116 @@
+//! 巡回経路の編集で、カーソルの近くの地点をつかみ、動かした経路をレベルのファイルに書き戻せることを確認するテスト
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use examina_clone::{
+    DayPeriod, GuardSchedule, LevelLayout, PICK_RADIUS, PatrolRoute, PatrolTime, Waypoint,
+    level_to_ron, pick_waypoint,
+};
+
+fn route(time: PatrolTime, points: &[Vec3]) -> PatrolRoute {
+    PatrolRoute {
+        time,
+        waypoints: points.iter().copied().map(Waypoint::new).collect(),
+    }
+}
+
+/// 昼だけの経路と、いつでも使う経路を持つ敵が1体いるレベル
+fn level() -> LevelLayout {
+    LevelLayout {
+        guards: vec![GuardSchedule {
+            name: "Guard".to_owned(),
+            spawn: Vec3::new(0.0, 1.0, 0.0),
+            speed: None,
+            vision_range: None,
+            vision_angle: Some(30.0),
+            routes: vec![
+                route(
+                    PatrolTime::Day,
+                    &[Vec3::new(0.0, 1.0, 0.0), Vec3::new(4.0, 1.0, 0.0)],
+                ),
+                route(
+                    PatrolTime::Always,
+                    &[Vec3::new(0.0, 1.0, -4.0), Vec3::new(0.0, 1.0, 4.0)],
+                ),
+            ],
+        }],
+        ..default()
+    }
+}
+
+#[test]
+fn nearest_waypoint_within_reach_is_picked() {
+    let first = Entity::from_raw(1);
+    let second = Entity::from_raw(2);
+    let a = [
+        Waypoint::new(Vec3::new(0.0, 1.0, 0.0)),
+        Waypoint::new(Vec3::new(3.0, 1.0, 0.0)),
+    ];
+    let b = [Waypoint::new(Vec3::new(3.4, 1.0, 0.0))];
+    let routes = || [(first, &a[..]), (second, &b[..])];
+
+    // 高さは比べない(地面の上のカーソルでも、高さ1の地点をつかめる)
+    assert_eq!(
+        pick_waypoint(routes(), Vec3::new(0.1, 0.0, 0.1)),
+        Some((first, 0))
+    );
+    assert_eq!(
+        pick_waypoint(routes(), Vec3::new(3.3, 0.0, 0.0)),
+        Some((second, 0))
+    );
+    assert_eq!(
+        pick_waypoint(routes(), Vec3::new(3.1, 0.0, 0.0)),
+        Some((first, 1))
+    );
+    assert_eq!(
+        pick_waypoint(routes(), Vec3::new(0.0, 0.0, PICK_RADIUS + 0.1)),
+        None
+    );
+}
+
+#[test]
+fn route_for_mut_edits_the_route_in_use() {
+    let mut level = level();
+
+    level
+        .route_for_mut("Guard", DayPeriod::Day)
+        .unwrap()
+        .waypoints[1]
+        .position
+        .x = 6.0;
+    level
+        .route_for_mut("Guard", DayPeriod::Night)
+        .unwrap()
+        .waypoints[0]
+        .position
+        .z = -6.0;
+
+    let day = level.route_for("Guard", DayPeriod::Day).unwrap();
+    assert_eq!(day.waypoints[1].position, Vec3::new(6.0, 1.0, 0.0));
+    // 夜の経路がないので、いつでも使う経路が変わる
+    let night = level.route_for("Guard", DayPeriod::Night).unwrap();
+    assert_eq!(night.time, PatrolTime::Always);
+    assert_eq!(night.waypoints[0].position, Vec3::new(0.0, 1.0, -6.0));
+    assert!(level.route_for_mut("Nobody", DayPeriod::Day).is_none());
+}
+
+#[test]
+fn saved_level_reads_back_the_same_with_its_header() {
+    let level = level();
+    let header = "// レベルのファイル\n// 2行目\n";
+
+    let text = level_to_ron(&level, header).unwrap();
+
+    assert!(text.starts_with(header));
+    assert_eq!(ron::from_str::<LevelLayout>(&text).unwrap(), level);
+}
+
+#[test]
+fn shipped_level_survives_being_written_back() {
+    let source = include_str!("../assets/levels/level1.config.ron");
+    let level: LevelLayout = ron::from_str(source).unwrap();
+
+    let text = level_to_ron(&level, "").unwrap();
+
+    assert_eq!(ron::from_str::<LevelLayout>(&text).unwrap(), level);
+}
@@ -0,0 +1,65 @@
+//! クリアしたときの得点と、レベルごとの記録の更新・保存を確認するテスト
+
+use examina_clone::{BestRecord, BestTimes, RunStats};
+
+fn stats(elapsed: f32, detections: u32, distractions: u32) -> RunStats {
+    RunStats {
+        elapsed,
+        detections,
+        distractions,
+    }
+}
+
+#[test]
+fn score_drops_with_time_detections_and_rocks() {
+    let perfect = stats(0.0, 0, 0).score();
+    let slow = stats(60.0, 0, 0).score();
+    let noticed = stats(0.0, 2, 0).score();
+    let rocks = stats(0.0, 0, 3).score();
+
+    assert!(slow < perfect);
+    assert!(noticed < perfect);
+    assert!(rocks < perfect);
+    // 気づかれるのは、石を1個投げるより大きく減る
+    assert!(stats(0.0, 1, 0).score() < stats(0.0, 0, 1).score());
+    // どれだけ時間がかかっても、0より小さくはならない
+    assert_eq!(stats(100_000.0, 50, 50).score(), 0);
+}
+
+#[test]
+fn best_time_and_score_are_kept_separately() {
+    let mut best = BestTimes::default();
+    assert!(best.record("level", &stats(90.0, 0, 0)));
+    assert_eq!(best.get("level").unwrap().time, 90.0);
+
+    // 遅くて得点も低いクリアでは更新しない
+    assert!(!best.record("level", &stats(120.0, 1, 0)));
+    // 速いが気づかれたクリアでは、タイムだけ更新する
+    let before = best.get("level").unwrap().score;
+    assert!(best.record("level", &stats(80.0, 3, 0)));
+    assert_eq!(
+        best.get("level"),
+        Some(BestRecord {
+            time: 80.0,
+            score: before
+        })
+    );
+    assert_eq!(best.get("other"), None);
+}
+
+#[test]
+fn records_survive_saving_and_loading() {
+    let path = std::env::temp_dir()
+        .join(format!("examina_clone_best_times_{}", std::process::id()))
+        .join("best_times.ron");
+    let mut best = BestTimes::default();
+    best.record("levels/level1.config.ron", &stats(75.5, 1, 2));
+
+    best.save(&path).unwrap();
+    let loaded = BestTimes::load(&path);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(loaded, best);
+    // ファイルがなければ記録なし
+    assert_eq!(BestTimes::load(&path), BestTimes::default());
+}
@@ -0,0 +1,66 @@
+//! プレイヤーの動く速さに合わせて、敵に見つかる距離が変わることを確認するテスト
+//! (ダッシュ中は視界範囲いっぱい、歩いていると7割、スニーキング中と止まっているときはすぐ近くだけ)
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy};
+use examina_clone::{GameState, Player, PlayerSpeed, StealthConfig, enemy_vision_system};
+
+/// 原点で-Z方向を向いた視界範囲10の敵と、`distance`だけ前を`speed`で動くプレイヤーを置いて、
+/// 1フレームだけ視界検知を動かし、見つかったかを返す
+fn detected(speed: f32, distance: f32) -> bool {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.add_systems(Update, enemy_vision_system);
+    spawn_enemy(&mut app, Vec3::ZERO);
+    app.world_mut().spawn((
+        Player,
+        PlayerSpeed(speed),
+        Transform::from_xyz(0.0, 0.0, -distance),
+    ));
+    app.update();
+    *app.world().resource::<GameState>() == GameState::GameOver
+}
+
+#[test]
+fn vision_ratio_grows_with_speed() {
+    let config = StealthConfig::default();
+    let walk = config.player_speed;
+    let sneak = walk * config.sneak_ratio;
+    let sprint = walk * config.sprint_ratio;
+
+    assert_eq!(config.speed_vision_ratio(0.0), config.sneak_vision_ratio);
+    assert_eq!(config.speed_vision_ratio(sneak), config.sneak_vision_ratio);
+    assert_eq!(config.speed_vision_ratio(walk), config.walk_vision_ratio);
+    assert_eq!(config.speed_vision_ratio(sprint), 1.0);
+    assert_eq!(config.speed_vision_ratio(sprint * 2.0), 1.0);
+
+    // 間の速さでは、遅い方と速い方の倍率の間になる
+    let between = config.speed_vision_ratio((walk + sprint) / 2.0);
+    assert!(config.walk_vision_ratio < between && between < 1.0);
+}
+
+#[test]
+fn sprinting_player_is_spotted_at_full_range() {
+    let config = StealthConfig::default();
+    let sprint = config.player_speed * config.sprint_ratio;
+    assert!(detected(sprint, 9.0));
+    assert!(!detected(sprint, 11.0));
+}
+
+#[test]
+fn walking_player_is_spotted_at_seventy_percent() {
+    let walk = StealthConfig::default().player_speed;
+    assert!(detected(walk, 6.5));
+    assert!(!detected(walk, 7.5));
+}
+
+#[test]
+fn sneaking_or_still_player_is_spotted_only_up_close() {
+    let config = StealthConfig::default();
+    let sneak = config.player_speed * config.sneak_ratio;
+    for speed in [0.0, sneak] {
+        assert!(detected(speed, 2.5));
+        assert!(!detected(speed, 5.0));
+    }
+}
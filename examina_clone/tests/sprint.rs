@@ -0,0 +1,181 @@
+//! ダッシュでスタミナが減り、ダッシュしていない間に戻ることと、キーボードの左Ctrlでダッシュできることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use common::StealthApp;
+use examina_clone::{
+    Crouching, Player, PlayerAction, PlayerSpeed, Sprinting, Stamina, StealthConfig, player_input,
+    update_sprint,
+};
+use sample_common::ActionAxes;
+
+/// 1フレームを0.1秒として、ダッシュのシステムだけを動かすAppを作る
+/// (1秒ダッシュすると空になり、2秒休むと満タンに戻る)
+fn sprint_app() -> App {
+    let mut app = StealthApp::new()
+        .config(StealthConfig {
+            stamina_drain_time: 1.0,
+            stamina_regen_time: 2.0,
+            ..default()
+        })
+        .frame_time(100)
+        .start();
+    app.init_resource::<ActionAxes>()
+        .init_resource::<Stamina>()
+        .add_systems(Update, update_sprint);
+    app
+}
+
+fn spawn_player(app: &mut App) -> Entity {
+    app.world_mut().spawn(Player).id()
+}
+
+/// ダッシュボタンを押して前に動かす(`pressed`がfalseならボタンを離して歩く)
+fn sprint_forward(app: &mut App, pressed: bool) {
+    app.world_mut().resource_mut::<ActionAxes>().movement = Vec2::Y;
+    let mut input = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    if pressed {
+        input.press(PlayerAction::Sprint);
+    } else {
+        input.release(PlayerAction::Sprint);
+    }
+}
+
+fn stamina(app: &App) -> Stamina {
+    app.world().resource::<Stamina>().clone()
+}
+
+fn update_frames(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+#[test]
+fn sprinting_drains_stamina() {
+    let mut app = sprint_app();
+    let player = spawn_player(&mut app);
+
+    sprint_forward(&mut app, true);
+    update_frames(&mut app, 5);
+
+    assert!(app.world().get::<Sprinting>(player).is_some());
+    assert!((stamina(&app).current - 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn sprint_button_without_moving_does_not_sprint() {
+    let mut app = sprint_app();
+    let player = spawn_player(&mut app);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<PlayerAction>>()
+        .press(PlayerAction::Sprint);
+    update_frames(&mut app, 3);
+
+    assert!(app.world().get::<Sprinting>(player).is_none());
+    assert_eq!(stamina(&app), Stamina::default());
+}
+
+#[test]
+fn crouching_or_sneaking_player_cannot_sprint() {
+    let mut app = sprint_app();
+    let player = spawn_player(&mut app);
+    app.world_mut().entity_mut(player).insert(Crouching);
+
+    sprint_forward(&mut app, true);
+    app.update();
+    assert!(app.world().get::<Sprinting>(player).is_none());
+
+    app.world_mut().entity_mut(player).remove::<Crouching>();
+    app.world_mut()
+        .resource_mut::<ButtonInput<PlayerAction>>()
+        .press(PlayerAction::Sneak);
+    app.update();
+    assert!(app.world().get::<Sprinting>(player).is_none());
+}
+
+#[test]
+fn exhausted_player_rests_until_stamina_is_full() {
+    let mut app = sprint_app();
+    let player = spawn_player(&mut app);
+
+    sprint_forward(&mut app, true);
+    update_frames(&mut app, 11);
+    assert!(stamina(&app).exhausted);
+    assert!(app.world().get::<Sprinting>(player).is_none());
+
+    // ボタンを押し続けていても、休んでいる間はダッシュせずにスタミナが戻る
+    update_frames(&mut app, 10);
+    assert!(stamina(&app).exhausted);
+    assert!(stamina(&app).current > 0.4);
+    assert!(app.world().get::<Sprinting>(player).is_none());
+
+    // 満タンに戻ると、またダッシュできる
+    sprint_forward(&mut app, false);
+    update_frames(&mut app, 15);
+    assert_eq!(stamina(&app), Stamina::default());
+    sprint_forward(&mut app, true);
+    app.update();
+    assert!(app.world().get::<Sprinting>(player).is_some());
+}
+
+#[test]
+fn walking_regenerates_stamina() {
+    let mut app = sprint_app();
+    let player = spawn_player(&mut app);
+
+    sprint_forward(&mut app, true);
+    update_frames(&mut app, 4);
+    sprint_forward(&mut app, false);
+    update_frames(&mut app, 4);
+
+    assert!(app.world().get::<Sprinting>(player).is_none());
+    assert!((stamina(&app).current - 0.8).abs() < 1e-4);
+}
+
+#[test]
+fn left_ctrl_sprints_while_moving_with_the_arrow_keys() {
+    let mut app = StealthApp::new()
+        .config(StealthConfig {
+            stamina_drain_time: 1.0,
+            ..default()
+        })
+        .frame_time(100)
+        .headless()
+        .keyboard()
+        .start();
+    app.init_resource::<Stamina>()
+        .add_systems(Update, (update_sprint, player_input).chain());
+    app.world_mut()
+        .spawn((Camera3d::default(), Transform::default()));
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            KinematicCharacterController::default(),
+            PlayerSpeed::default(),
+        ))
+        .id();
+    let speed = |app: &App| app.world().get::<PlayerSpeed>(player).unwrap().0;
+    let config = StealthConfig::default();
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::ArrowUp);
+    app.update();
+    assert!((speed(&app) - config.player_speed).abs() < 1e-4);
+
+    // Ctrlキーを押していても、矢印キーで動き続けて速くなる
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::ControlLeft);
+    app.update();
+    app.update();
+
+    assert!(app.world().get::<Sprinting>(player).is_some());
+    assert!((speed(&app) - config.player_speed * config.sprint_ratio).abs() < 1e-4);
+    assert!(stamina(&app).current < 1.0);
+}
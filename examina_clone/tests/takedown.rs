@@ -0,0 +1,216 @@
+//! 気づいていない敵の背後から気絶させ、倒れている敵をほかの敵が見つけると調べに来ることを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, enemy, spawn_enemy, spawn_player};
+use examina_clone::{
+    AudioCue, BodyDiscovered, Detection, Interactable, InteractionEvent, InteractionFocus,
+    Investigating, KnockedOut, PlayerAction, StealthConfig, discover_bodies, interact, take_down,
+    takedown_target, update_knocked_out, update_takedown_targets,
+};
+
+/// 1フレームを0.1秒として、操作・テイクダウン・倒れている敵の発見だけを動かすApp
+/// 原点に-Z方向を向いた敵を置き、その敵を返す
+fn takedown_app(config: StealthConfig) -> (App, Entity) {
+    let mut app = StealthApp::new().config(config).frame_time(100).start();
+    app.init_resource::<InteractionFocus>()
+        .add_event::<AudioCue>()
+        .add_event::<BodyDiscovered>()
+        .add_event::<InteractionEvent>()
+        .add_systems(
+            Update,
+            (
+                update_takedown_targets,
+                interact,
+                take_down,
+                update_knocked_out,
+                discover_bodies,
+            )
+                .chain(),
+        );
+    let guard = spawn_enemy(&mut app, Vec3::ZERO);
+    (app, guard)
+}
+
+/// 「操作する」ボタンを押して1フレーム進める
+fn press_interact(app: &mut App) {
+    let mut actions = app.world_mut().resource_mut::<ButtonInput<PlayerAction>>();
+    actions.release(PlayerAction::Interact);
+    actions.clear();
+    actions.press(PlayerAction::Interact);
+    app.update();
+}
+
+#[test]
+fn only_an_unaware_guard_seen_from_behind_can_be_taken_down() {
+    let config = StealthConfig::default();
+    let guard = Entity::from_raw(1);
+    let transform = Transform::default(); // -Z方向を向いている
+    let calm = Detection::default();
+    let target = |player: Vec3, detection: &Detection, investigating: bool| {
+        takedown_target(
+            player,
+            [(guard, &transform, detection, investigating)],
+            &config,
+        )
+    };
+
+    // 真後ろのすぐそば
+    assert_eq!(target(Vec3::new(0.0, 0.0, 1.0), &calm, false), Some(guard));
+    // 正面や真横からは気絶させられない
+    assert_eq!(target(Vec3::new(0.0, 0.0, -1.0), &calm, false), None);
+    assert_eq!(target(Vec3::new(1.0, 0.0, 0.0), &calm, false), None);
+    // 離れすぎ
+    assert_eq!(target(Vec3::new(0.0, 0.0, 3.0), &calm, false), None);
+    // 気づきかけている敵や、音を調べている敵は背後を取れない
+    let noticing = Detection { level: 0.2 };
+    assert_eq!(target(Vec3::new(0.0, 0.0, 1.0), &noticing, false), None);
+    assert_eq!(target(Vec3::new(0.0, 0.0, 1.0), &calm, true), None);
+}
+
+#[test]
+fn nearest_guard_behind_is_chosen() {
+    let config = StealthConfig::default();
+    let near = Transform::from_xyz(0.0, 0.0, 0.5);
+    let far = Transform::default();
+    let calm = Detection::default();
+    let target = takedown_target(
+        Vec3::new(0.0, 0.0, 1.2),
+        [
+            (Entity::from_raw(1), &far, &calm, false),
+            (Entity::from_raw(2), &near, &calm, false),
+        ],
+        &config,
+    );
+    assert_eq!(target, Some(Entity::from_raw(2)));
+}
+
+#[test]
+fn guard_taken_down_from_behind_falls_and_gets_back_up() {
+    let (mut app, guard) = takedown_app(StealthConfig {
+        takedown_time: 1.0,
+        ..default()
+    });
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, 1.0));
+
+    press_interact(&mut app);
+    assert!(app.world().get::<KnockedOut>(guard).is_some());
+    for _ in 0..5 {
+        app.update();
+    }
+    // 倒れて横になっている
+    let lying = app.world().get::<Transform>(guard).unwrap();
+    assert!(lying.up().y.abs() < 0.01, "{lying:?}");
+
+    for _ in 0..7 {
+        app.update();
+    }
+    // 時間が来たら、倒される前の姿勢で起き上がる
+    assert!(app.world().get::<KnockedOut>(guard).is_none());
+    assert_eq!(
+        *app.world().get::<Transform>(guard).unwrap(),
+        Transform::default()
+    );
+}
+
+#[test]
+fn guard_cannot_be_taken_down_from_the_front() {
+    let (mut app, guard) = takedown_app(StealthConfig::default());
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -1.0));
+
+    press_interact(&mut app);
+    assert!(app.world().get::<KnockedOut>(guard).is_none());
+}
+
+#[test]
+fn only_the_guard_seen_from_behind_can_be_interacted_with() {
+    let (mut app, guard) = takedown_app(StealthConfig::default());
+    let player = spawn_player(&mut app, Vec3::new(0.0, 0.0, 1.0));
+
+    // 背後を取れている間は操作の案内が出る
+    app.update();
+    assert!(app.world().get::<Interactable>(guard).unwrap().enabled);
+    assert_eq!(
+        *app.world().resource::<InteractionFocus>(),
+        InteractionFocus(Some(guard))
+    );
+
+    // 正面に回ると、操作できなくなる
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .z = -1.0;
+    app.update();
+    assert!(!app.world().get::<Interactable>(guard).unwrap().enabled);
+    assert_eq!(
+        *app.world().resource::<InteractionFocus>(),
+        InteractionFocus(None)
+    );
+}
+
+#[test]
+fn zero_takedown_time_keeps_the_guard_down() {
+    let (mut app, guard) = takedown_app(StealthConfig {
+        takedown_time: 0.0,
+        ..default()
+    });
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, 1.0));
+
+    press_interact(&mut app);
+    for _ in 0..100 {
+        app.update();
+    }
+    assert!(app.world().get::<KnockedOut>(guard).is_some());
+}
+
+#[test]
+fn guard_who_sees_the_body_makes_everyone_investigate() {
+    let (mut app, body) = takedown_app(StealthConfig::default());
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, 1.0));
+    press_interact(&mut app);
+
+    // 倒れている敵から離れた所で、背中を向けている敵は見つけない
+    let other = spawn_enemy(&mut app, Vec3::new(0.0, 0.0, -20.0));
+    // 倒れている敵の5m後ろで、その方を向いている敵が見つける
+    let finder = app
+        .world_mut()
+        .spawn((
+            enemy(Vec3::new(0.0, 0.0, 5.0)),
+            Transform::from_xyz(0.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ))
+        .id();
+    app.update();
+
+    let discovered: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<BodyDiscovered>>()
+        .drain()
+        .collect();
+    assert_eq!(discovered.len(), 1);
+    assert_eq!(discovered[0].finder, finder);
+    assert_eq!(discovered[0].body, body);
+    // 見つけた合図は、プレイヤーに気づいた合図とは別にする(気づかれた回数に数えない)
+    let cues: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<AudioCue>>()
+        .drain()
+        .collect();
+    assert_eq!(cues, vec![AudioCue::BodyFound]);
+    // 起きている敵はみな、倒れている場所を調べに行く(倒れている敵は調べない)
+    for guard in [finder, other] {
+        assert!(app.world().get::<Investigating>(guard).is_some());
+    }
+    assert!(app.world().get::<Investigating>(body).is_none());
+
+    // 一度見つかった敵は、何度も見つからない
+    app.update();
+    assert_eq!(
+        app.world_mut()
+            .resource_mut::<Events<BodyDiscovered>>()
+            .drain()
+            .count(),
+        0
+    );
+}
@@ -0,0 +1,117 @@
+//! 敵の視界検知システムのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use common::{StealthApp, instant_detection, spawn_enemy, spawn_player};
+use examina_clone::{Enemy, GameState, Player, enemy_vision_system};
+
+/// 視界検知システムだけを動かす最小構成のAppを作る
+fn vision_app() -> App {
+    let mut app = StealthApp::new().config(instant_detection()).build();
+    app.add_systems(Update, enemy_vision_system);
+    app
+}
+
+/// 物理のワールド(Rapier)を入れて、レイで遮るものを調べるApp
+/// コライダーは最初のフレームの物理の更新で登録されるので、数フレーム進めてから判定する
+fn physics_vision_app() -> App {
+    let mut app = StealthApp::new()
+        .config(instant_detection())
+        .physics()
+        .build();
+    app.add_systems(Update, enemy_vision_system);
+    app
+}
+
+#[test]
+fn player_in_front_is_detected() {
+    let mut app = vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn player_behind_is_not_detected() {
+    let mut app = vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, 5.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+}
+
+#[test]
+fn player_out_of_range_is_not_detected() {
+    let mut app = vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -15.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+}
+
+#[test]
+fn player_outside_vision_angle_is_not_detected() {
+    let mut app = vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    // 前方から45度ずれた位置(視界角45度の半分より外側)
+    spawn_player(&mut app, Vec3::new(3.0, 0.0, -3.0));
+
+    app.update();
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+}
+
+/// 敵とプレイヤーにコライダーを付ける(ゲームと同じカプセル)
+fn add_capsules(app: &mut App) {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<Entity, Or<(With<Enemy>, With<Player>)>>();
+    let entities: Vec<Entity> = query.iter(app.world()).collect();
+    for entity in entities {
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(Collider::capsule_y(0.9, 0.4));
+    }
+}
+
+#[test]
+fn player_in_plain_sight_is_detected_with_physics() {
+    let mut app = physics_vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+    add_capsules(&mut app);
+
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::GameOver);
+}
+
+#[test]
+fn player_behind_a_wall_is_not_detected() {
+    let mut app = physics_vision_app();
+    spawn_enemy(&mut app, Vec3::ZERO);
+    spawn_player(&mut app, Vec3::new(0.0, 0.0, -5.0));
+    add_capsules(&mut app);
+    // 敵とプレイヤーの間の壁
+    app.world_mut().spawn((
+        Collider::cuboid(2.0, 2.0, 0.1),
+        Transform::from_xyz(0.0, 0.0, -2.5),
+    ));
+
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(*app.world().resource::<GameState>(), GameState::Playing);
+}
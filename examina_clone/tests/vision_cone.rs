@@ -0,0 +1,175 @@
+//! 敵の視界を表す扇形と、警戒の段階に合わせた扇形の色のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use common::{StealthApp, spawn_enemy};
+use examina_clone::{
+    AlarmLevel, AlertState, Detection, Enemy, Investigating, StealthConfig, VisionCone,
+    tint_vision_cones, update_vision_cones, vision_cone_alpha, vision_cone_mesh,
+};
+
+/// 判定の誤差
+const EPSILON: f32 = 1e-4;
+
+/// 扇形を付けて色を変えるシステムだけを動かすApp(メッシュとマテリアルのアセットを使う)
+fn cone_app() -> App {
+    let mut app = StealthApp::new().headless().build();
+    app.add_systems(Update, (update_vision_cones, tint_vision_cones).chain());
+    app
+}
+
+/// メッシュの頂点の位置
+fn positions(mesh: &Mesh) -> Vec<Vec3> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(values)) => {
+            values.iter().map(|value| Vec3::from(*value)).collect()
+        }
+        _ => panic!("頂点の位置がありません"),
+    }
+}
+
+/// 敵の子の扇形と、そのメッシュの頂点
+fn cone_of(app: &mut App, enemy: Entity) -> (VisionCone, Vec<Vec3>) {
+    let mut cones = app.world_mut().query::<(&VisionCone, &Mesh3d, &ChildOf)>();
+    let found: Vec<_> = cones
+        .iter(app.world())
+        .filter(|(_, _, child_of)| child_of.parent() == enemy)
+        .map(|(cone, mesh, _)| (*cone, mesh.0.clone()))
+        .collect();
+    assert_eq!(found.len(), 1, "扇形は敵ごとに1つだけ付ける");
+    let (cone, handle) = found.into_iter().next().unwrap();
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    (cone, positions(meshes.get(&handle).unwrap()))
+}
+
+#[test]
+fn mesh_spreads_forward_within_the_vision_angle() {
+    let points = positions(&vision_cone_mesh(10.0, 90.0));
+    // 地面と平行で、敵の前方(-Z)に、半径以内・視界角の半分以内に広がる
+    assert!(points.iter().all(|p| p.y.abs() < EPSILON));
+    assert!(points.iter().all(|p| p.length() <= 10.0 + EPSILON));
+    assert!(
+        points
+            .iter()
+            .filter(|p| p.length() > EPSILON)
+            .all(|p| p.z < 0.0 && p.x.abs() <= -p.z + EPSILON)
+    );
+    assert!(points.iter().any(|p| (p.length() - 10.0).abs() < EPSILON));
+}
+
+#[test]
+fn each_enemy_gets_a_cone_that_follows_its_settings() {
+    let mut app = cone_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    app.update();
+
+    let (cone, points) = cone_of(&mut app, enemy);
+    assert_eq!(
+        cone,
+        VisionCone {
+            range: 10.0,
+            angle: 45.0
+        }
+    );
+    let farthest = points.iter().map(|p| p.length()).fold(0.0, f32::max);
+    assert!((farthest - 10.0).abs() < EPSILON);
+
+    // 設定が変わると、同じ扇形のメッシュを作り直す
+    app.world_mut()
+        .get_mut::<Enemy>(enemy)
+        .unwrap()
+        .vision_range = 6.0;
+    app.update();
+
+    let (cone, points) = cone_of(&mut app, enemy);
+    assert_eq!(cone.range, 6.0);
+    let farthest = points.iter().map(|p| p.length()).fold(0.0, f32::max);
+    assert!((farthest - 6.0).abs() < EPSILON);
+}
+
+/// 敵の子の扇形の色
+fn cone_color(app: &mut App, enemy: Entity) -> Color {
+    let mut cones = app
+        .world_mut()
+        .query_filtered::<(&MeshMaterial3d<StandardMaterial>, &ChildOf), With<VisionCone>>();
+    let handle = cones
+        .iter(app.world())
+        .find(|(_, child_of)| child_of.parent() == enemy)
+        .map(|(material, _)| material.0.clone())
+        .unwrap();
+    let materials = app.world().resource::<Assets<StandardMaterial>>();
+    materials.get(&handle).unwrap().base_color
+}
+
+#[test]
+fn alert_state_follows_detection_investigation_and_alarm() {
+    let calm = Detection::default();
+    let noticing = Detection { level: 0.4 };
+    let spotted = Detection { level: 1.0 };
+    assert_eq!(AlertState::of(&calm, false, false), AlertState::Calm);
+    assert_eq!(
+        AlertState::of(&noticing, false, false),
+        AlertState::Suspicious
+    );
+    assert_eq!(AlertState::of(&calm, true, false), AlertState::Suspicious);
+    assert_eq!(AlertState::of(&spotted, false, false), AlertState::Alerted);
+    assert_eq!(AlertState::of(&calm, false, true), AlertState::Alerted);
+}
+
+#[test]
+fn cone_pulses_only_while_the_gauge_is_filling() {
+    let steady = vision_cone_alpha(false, 0.0);
+    assert!((0..20).all(|i| vision_cone_alpha(false, i as f32 * 0.05) == steady));
+
+    let pulsing: Vec<_> = (0..20)
+        .map(|i| vision_cone_alpha(true, i as f32 * 0.05))
+        .collect();
+    assert!(pulsing.iter().all(|alpha| *alpha >= steady - EPSILON));
+    assert!(pulsing.iter().any(|alpha| *alpha > steady + 0.1));
+}
+
+#[test]
+fn cone_color_changes_with_the_alert_state() {
+    let mut app = cone_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    app.update(); // 扇形を付ける
+    app.update();
+    assert_eq!(cone_color(&mut app, enemy), AlertState::Calm.cone_color());
+
+    app.world_mut().entity_mut(enemy).insert(Investigating {
+        target: Vec3::new(0.0, 0.0, -5.0),
+        remaining: 3.0,
+    });
+    app.update();
+    assert_eq!(
+        cone_color(&mut app, enemy),
+        AlertState::Suspicious.cone_color()
+    );
+
+    app.world_mut()
+        .resource_mut::<AlarmLevel>()
+        .raise(Vec3::ZERO, &StealthConfig::default());
+    app.update();
+    assert_eq!(
+        cone_color(&mut app, enemy),
+        AlertState::Alerted.cone_color()
+    );
+}
+
+#[test]
+fn filling_gauge_keeps_the_alert_color() {
+    let mut app = cone_app();
+    let enemy = spawn_enemy(&mut app, Vec3::ZERO);
+    app.update();
+    for level in [0.2, 0.4, 0.6] {
+        app.world_mut().get_mut::<Detection>(enemy).unwrap().level = level;
+        app.update();
+        // 点滅しても、色味は気づきかけているときの黄色のまま
+        let color = cone_color(&mut app, enemy).to_srgba();
+        let suspicious = AlertState::Suspicious.cone_color().to_srgba();
+        assert_eq!(color.with_alpha(0.0), suspicious.with_alpha(0.0));
+        assert!(color.alpha >= suspicious.alpha - EPSILON);
+    }
+}
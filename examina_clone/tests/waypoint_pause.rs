@@ -0,0 +1,96 @@
+//! 巡回地点に着いた敵が立ち止まって左右を見回し、それから次の地点へ向かうことを確認するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{StealthApp, enemy};
+use examina_clone::{Enemy, PatrolPause, StealthConfig, Waypoint, enemy_patrol_system};
+
+/// 1フレームを0.1秒として、巡回だけを動かすApp
+/// 原点で-Z方向を向いた敵が、原点(`first`)と奥の地点を往復する
+fn patrol_app(first: Waypoint) -> (App, Entity) {
+    let mut app = StealthApp::new()
+        .config(StealthConfig {
+            waypoint_pause: 1.0,
+            look_around_angle: 60.0,
+            ..default()
+        })
+        .frame_time(100)
+        .start();
+    app.add_systems(Update, enemy_patrol_system);
+    let guard = app
+        .world_mut()
+        .spawn((
+            Enemy {
+                patrol_points: vec![first, Waypoint::new(Vec3::new(0.0, 0.0, -10.0))],
+                ..enemy(Vec3::ZERO)
+            },
+            Transform::default(),
+        ))
+        .id();
+    (app, guard)
+}
+
+fn transform(app: &App, guard: Entity) -> Transform {
+    *app.world().get::<Transform>(guard).unwrap()
+}
+
+#[test]
+fn look_around_sweeps_left_then_right_and_back() {
+    let pause = |remaining| PatrolPause {
+        remaining,
+        duration: 4.0,
+        facing: Quat::IDENTITY,
+    };
+    let yaw = |remaining| {
+        let forward = pause(remaining).rotation(60.0) * Vec3::NEG_Z;
+        // 左(-X方向)を正とする
+        (-forward.x).atan2(-forward.z).to_degrees()
+    };
+    assert!(yaw(4.0).abs() < 1e-3);
+    assert!((yaw(3.0) - 60.0).abs() < 1e-3);
+    assert!((yaw(1.0) + 60.0).abs() < 1e-3);
+    assert!(yaw(0.0).abs() < 1e-3);
+}
+
+#[test]
+fn guard_pauses_and_looks_around_before_moving_on() {
+    let (mut app, guard) = patrol_app(Waypoint::new(Vec3::ZERO));
+
+    // 最初の地点に着いて、立ち止まる
+    app.update();
+    let pause = *app.world().get::<PatrolPause>(guard).unwrap();
+    assert_eq!(pause.remaining, 1.0);
+
+    // 立ち止まっている間は動かず、首を振る
+    for _ in 0..3 {
+        app.update();
+    }
+    let looking = transform(&app, guard);
+    assert_eq!(looking.translation, Vec3::ZERO);
+    assert!(
+        looking.forward().angle_between(Vec3::NEG_Z).to_degrees() > 45.0,
+        "{looking:?}"
+    );
+
+    // 立ち止まる時間が過ぎたら、次の地点へ向かう
+    for _ in 0..9 {
+        app.update();
+    }
+    let walking = transform(&app, guard);
+    assert!(walking.translation.z < 0.0, "{walking:?}");
+    assert!(walking.forward().distance(Vec3::NEG_Z) < 1e-3);
+}
+
+#[test]
+fn zero_pause_on_a_waypoint_moves_on_immediately() {
+    let (mut app, guard) = patrol_app(Waypoint::new(Vec3::ZERO).with_pause(0.0));
+
+    app.update(); // 最初の地点に着く
+    assert_eq!(
+        app.world().get::<PatrolPause>(guard).unwrap().remaining,
+        0.0
+    );
+    app.update();
+    assert!(transform(&app, guard).translation.z < 0.0);
+}
@@ -0,0 +1,318 @@
+//! # サンプルアプリのランチャー
+//!
+//! ワークスペース内のすべてのサンプルを一覧表示し、選んだものを起動する。
+//!
+//! - プラグイン化済みのサンプルは、このバイナリ自身を `--demo <名前>` 付きで
+//!   別プロセスとして起動し、その中でプラグインを組み込んで実行する
+//! - それ以外のサンプルは、同じターゲットディレクトリにある実行ファイルを起動する
+//!
+//! サムネイルの画像は同梱していない。`assets/thumbnails/<パッケージ名>.png`を置いたサンプルだけ画像を表示し、
+//! ほかはサンプルごとの色の枠を表示する。
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use bevy::prelude::*;
+use sample_common::{Locale, LocalizationPlugin, LocalizedText, ScreenshotPlugin};
+
+/// サムネイルの表示サイズ(px)
+const THUMBNAIL_SIZE: Vec2 = Vec2::new(192.0, 108.0);
+
+/// ボタンの通常時の背景色
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.2);
+
+/// ボタンにカーソルが乗っているときの背景色
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.25, 0.25, 0.35);
+
+/// サンプルの起動方法
+#[derive(Clone, Copy)]
+enum Launch {
+    /// ランチャーに組み込んだプラグインで起動する
+    Embedded(fn(&mut App)),
+    /// 各サンプルの実行ファイルを起動する
+    Process,
+}
+
+/// ランチャーに表示するサンプルの情報
+struct DemoEntry {
+    name: &'static str, // パッケージ名(ディレクトリ名と同じ、表示名と説明文の対応表のキーにも使う)
+    accent: Color,      // サムネイルがないときの表示色
+    launch: Launch,     // 起動方法
+}
+
+impl DemoEntry {
+    /// 表示名の対応表のキー
+    fn title_key(&self) -> String {
+        format!("{}.title", self.name)
+    }
+
+    /// 説明文の対応表のキー
+    fn description_key(&self) -> String {
+        format!("{}.description", self.name)
+    }
+}
+
+/// ワークスペース内のサンプル一覧
+const DEMOS: &[DemoEntry] = &[
+    DemoEntry {
+        name: "primitives",
+        accent: Color::srgb(0.2, 0.1, 0.6),
+        launch: Launch::Embedded(|app| {
+            app.add_plugins(primitives::PrimitivesPlugin);
+        }),
+    },
+    DemoEntry {
+        name: "examina_clone",
+        accent: Color::srgb(0.6, 0.15, 0.15),
+        launch: Launch::Embedded(|app| {
+            app.add_plugins(examina_clone::ExaminaClonePlugin);
+        }),
+    },
+    DemoEntry {
+        name: "moving_3d_cube",
+        accent: Color::srgb(0.2, 0.5, 0.2),
+        launch: Launch::Embedded(|app| {
+            app.add_plugins(moving_3d_cube::Moving3dCubePlugin);
+        }),
+    },
+    DemoEntry {
+        name: "camera_3d_viewport",
+        accent: Color::srgb(0.3, 0.5, 0.3),
+        launch: Launch::Process,
+    },
+    DemoEntry {
+        name: "volumetric_fog",
+        accent: Color::srgb(0.5, 0.1, 0.1),
+        launch: Launch::Process,
+    },
+    DemoEntry {
+        name: "bevy_3D_objects_test",
+        accent: Color::srgb(0.3, 0.6, 1.0),
+        launch: Launch::Process,
+    },
+];
+
+/// 押されたときに起動するサンプルを示すコンポーネント
+#[derive(Component)]
+struct DemoButton(usize); // DEMOSのインデックス
+
+/// 起動結果を表示するテキストのマーカーコンポーネント
+#[derive(Component)]
+struct StatusText;
+
+fn main() -> AppExit {
+    // `--demo <名前>` が指定された場合は、そのサンプルを組み込んで起動する
+    if let Some(demo) = demo_from_args() {
+        return run_embedded(demo);
+    }
+
+    App::new()
+        .add_plugins(sample_common::headless::default_plugins()) // --headless時はウィンドウなし
+        .add_plugins(ScreenshotPlugin::new("launcher")) // F12でスクリーンショット
+        .add_plugins(
+            LocalizationPlugin::new()
+                .bundle(Locale::En, include_str!("../assets/locale/en.ron"))
+                .bundle(Locale::Ja, include_str!("../assets/locale/ja.ron")),
+        ) // メニューの英語・日本語(F2で切り替え)
+        .add_systems(Startup, setup_menu)
+        .add_systems(Update, handle_demo_buttons)
+        .run()
+}
+
+/// コマンドライン引数から `--demo` で指定されたサンプルを探す
+fn demo_from_args() -> Option<&'static DemoEntry> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--demo" {
+            let name = args
+                .next()
+                .expect("--demo にはサンプル名を指定してください");
+            return Some(
+                DEMOS
+                    .iter()
+                    .find(|demo| demo.name == name)
+                    .unwrap_or_else(|| panic!("不明なサンプルです: {name}")),
+            );
+        }
+    }
+    None
+}
+
+/// 組み込み済みのプラグインでサンプルを実行する
+fn run_embedded(demo: &DemoEntry) -> AppExit {
+    let Launch::Embedded(add_plugin) = demo.launch else {
+        panic!("{} はランチャーに組み込まれていません", demo.name);
+    };
+
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::default_plugins().set(AssetPlugin {
+        // アセットは各サンプルのディレクトリから読み込む
+        file_path: demo_dir(demo).join("assets").to_string_lossy().into_owned(),
+        ..default()
+    }));
+    add_plugin(&mut app);
+    app.run()
+}
+
+/// サンプルのクレートのディレクトリ
+fn demo_dir(demo: &DemoEntry) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(demo.name)
+}
+
+/// サムネイル画像のパス(launcher/assets 以下)
+fn thumbnail_path(demo: &DemoEntry) -> String {
+    format!("thumbnails/{}.png", demo.name)
+}
+
+/// メニュー画面を作成する
+fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(Camera2d);
+
+    let root = commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            overflow: Overflow::scroll_y(),
+            ..default()
+        })
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            LocalizedText::new("menu_title"),
+            TextFont {
+                font_size: 40.0,
+                ..default()
+            },
+        ));
+
+        for (index, demo) in DEMOS.iter().enumerate() {
+            parent
+                .spawn((
+                    Button,
+                    DemoButton(index),
+                    Node {
+                        width: Val::Px(720.0),
+                        column_gap: Val::Px(16.0),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(BUTTON_COLOR),
+                ))
+                .with_children(|button| {
+                    let thumbnail = Node {
+                        width: Val::Px(THUMBNAIL_SIZE.x),
+                        height: Val::Px(THUMBNAIL_SIZE.y),
+                        flex_shrink: 0.0,
+                        ..default()
+                    };
+
+                    // サムネイル画像があれば表示し、なければ色付きの枠で代用する
+                    let path = thumbnail_path(demo);
+                    if PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                        .join("assets")
+                        .join(&path)
+                        .exists()
+                    {
+                        button.spawn((ImageNode::new(asset_server.load(path)), thumbnail));
+                    } else {
+                        button.spawn((thumbnail, BackgroundColor(demo.accent)));
+                    }
+
+                    button
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(4.0),
+                            ..default()
+                        })
+                        .with_children(|column| {
+                            column.spawn((
+                                LocalizedText::new(demo.title_key()),
+                                TextFont {
+                                    font_size: 24.0,
+                                    ..default()
+                                },
+                            ));
+                            column.spawn((
+                                LocalizedText::new(demo.description_key()),
+                                TextFont {
+                                    font_size: 14.0,
+                                    ..default()
+                                },
+                                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                            ));
+                        });
+                });
+        }
+
+        parent.spawn((LocalizedText::new("status_idle"), StatusText));
+    });
+}
+
+/// ボタンの入力に応じてサンプルを起動するシステム
+fn handle_demo_buttons(
+    mut buttons: Query<(&Interaction, &DemoButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut status: Query<&mut LocalizedText, With<StatusText>>,
+) {
+    for (interaction, demo_button, mut background) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                let demo = &DEMOS[demo_button.0];
+                let message = match launch_demo(demo) {
+                    Ok(()) => {
+                        info!("{}を起動しました", demo.name);
+                        LocalizedText::new("status_launched")
+                    }
+                    Err(err) => {
+                        warn!("{}を起動できません: {err}", demo.name);
+                        LocalizedText::new("status_failed").with_arg("error", err)
+                    }
+                }
+                .with_key_arg("title", demo.title_key());
+                for mut text in status.iter_mut() {
+                    *text = message.clone();
+                }
+            }
+            Interaction::Hovered => background.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => background.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+/// サンプルを別プロセスとして起動する
+fn launch_demo(demo: &DemoEntry) -> std::io::Result<()> {
+    let launcher = std::env::current_exe()?;
+
+    let mut command = match demo.launch {
+        Launch::Embedded(_) => {
+            let mut command = Command::new(&launcher);
+            command.args(["--demo", demo.name]);
+            command
+        }
+        Launch::Process => {
+            // `cargo build --workspace` でランチャーと同じディレクトリに出力された実行ファイルを使う
+            let executable = launcher
+                .with_file_name(demo.name)
+                .with_extension(std::env::consts::EXE_EXTENSION);
+            if !executable.exists() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("run `cargo build -p {}` first", demo.name),
+                ));
+            }
+            let mut command = Command::new(executable);
+            // アセットを各サンプルのディレクトリから読み込ませる
+            command.env("BEVY_ASSET_ROOT", demo_dir(demo));
+            command
+        }
+    };
+
+    command.spawn().map(|_| ())
+}
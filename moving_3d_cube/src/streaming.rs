@@ -0,0 +1,149 @@
+//! チャンクに属するエンティティの保存と復元
+//!
+//! `ChunkResident`を付けたエンティティ(NPC・アイテム・置いたブロック・目印など)は、
+//! いるチャンクが描画距離の外に出ると`ChunkStorage`へしまわれて削除され、
+//! チャンクが再び読み込まれると、しまったときと同じ状態で生成し直される。
+//! 各機能はチャンクの読み込み・削除を気にせず、エンティティに`ChunkResident`を付けるだけでよい。
+//!
+//! 状態は`DynamicScene`として保存するので、残したいコンポーネントは
+//! `#[reflect(Component)]`付きで型を登録しておく(登録されていないコンポーネントは失われる)。
+//! 子エンティティも一緒に保存・復元される。
+
+use std::collections::HashMap;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+
+use crate::{InfiniteWorld, Player, manage_infinite_world};
+
+/// チャンクの読み込み・削除に合わせて保存・復元されるエンティティのマーカーコンポーネント
+/// 親を持たないエンティティに付ける(子エンティティは親と一緒に扱われる)
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct ChunkResident;
+
+/// 削除されたチャンクにいたエンティティの保存先
+#[derive(Resource, Default)]
+pub struct ChunkStorage {
+    chunks: HashMap<(i32, i32), Vec<DynamicScene>>, // チャンク座標ごとの保存した状態
+}
+
+impl ChunkStorage {
+    /// 指定したチャンクに保存されているエンティティの数(子エンティティを含む)
+    pub fn entity_count(&self, chunk: (i32, i32)) -> usize {
+        self.chunks.get(&chunk).map_or(0, |scenes| {
+            scenes.iter().map(|scene| scene.entities.len()).sum()
+        })
+    }
+
+    /// エンティティが保存されているチャンクの数
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+/// `ChunkResident`の保存と復元を行うプラグイン(`Moving3dCubePlugin`に含まれる)
+pub struct ChunkStreamingPlugin;
+
+impl Plugin for ChunkStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkStorage>()
+            .register_type::<ChunkResident>()
+            .add_systems(
+                Update,
+                stream_chunk_residents.after(manage_infinite_world), // 地面と同じフレームで切り替える
+            );
+    }
+}
+
+/// 描画距離の外に出たエンティティを保存して削除し、描画距離に戻ったチャンクのエンティティを復元するシステム
+pub fn stream_chunk_residents(
+    world: &mut World,
+    players: &mut QueryState<&Transform, With<Player>>,
+    residents: &mut QueryState<(Entity, &Transform), (With<ChunkResident>, Without<ChildOf>)>,
+) {
+    let Ok(player_transform) = players.single(world) else {
+        return;
+    };
+    let world_settings = world.resource::<InfiniteWorld>();
+    let center = world_settings.chunk_of(player_transform.translation);
+
+    // 描画距離の外にいるエンティティをチャンクごとにまとめる
+    let mut leaving: HashMap<(i32, i32), Vec<Entity>> = HashMap::new();
+    for (entity, transform) in residents.iter(world) {
+        let chunk = world_settings.chunk_of(transform.translation);
+        if !world_settings.is_loaded(chunk, center) {
+            leaving.entry(chunk).or_default().push(entity);
+        }
+    }
+
+    // 描画距離に入った保存済みのチャンクを取り出す
+    let arriving: Vec<(i32, i32)> = world
+        .resource::<ChunkStorage>()
+        .chunks
+        .keys()
+        .copied()
+        .filter(|chunk| world_settings.is_loaded(*chunk, center))
+        .collect();
+
+    for (chunk, entities) in leaving {
+        let mut extracted = Vec::new();
+        for entity in entities {
+            collect_with_descendants(world, entity, &mut extracted);
+        }
+        let scene = DynamicSceneBuilder::from_world(world)
+            .extract_entities(extracted.iter().copied())
+            .build();
+        for entity in &extracted {
+            if let Ok(entity) = world.get_entity_mut(*entity) {
+                entity.despawn(); // 子エンティティも一緒に削除される
+            }
+        }
+        info!(
+            "チャンク({}, {})のエンティティを{}個保存しました",
+            chunk.0,
+            chunk.1,
+            extracted.len()
+        );
+        world
+            .resource_mut::<ChunkStorage>()
+            .chunks
+            .entry(chunk)
+            .or_default()
+            .push(scene);
+    }
+
+    for chunk in arriving {
+        let Some(scenes) = world.resource_mut::<ChunkStorage>().chunks.remove(&chunk) else {
+            continue;
+        };
+        for scene in scenes {
+            // 保存したときとは別のエンティティとして生成し、親子関係などの参照は付け替える
+            let mut entity_map = EntityHashMap::default();
+            match scene.write_to_world(world, &mut entity_map) {
+                Ok(()) => info!(
+                    "チャンク({}, {})のエンティティを{}個復元しました",
+                    chunk.0,
+                    chunk.1,
+                    scene.entities.len()
+                ),
+                Err(err) => error!(
+                    "チャンク({}, {})のエンティティを復元できません: {err}",
+                    chunk.0, chunk.1
+                ),
+            }
+        }
+    }
+}
+
+/// エンティティとその子孫をすべて集める
+fn collect_with_descendants(world: &World, entity: Entity, entities: &mut Vec<Entity>) {
+    entities.push(entity);
+    let children = world
+        .get::<Children>(entity)
+        .map(|children| children.to_vec())
+        .unwrap_or_default();
+    for child in children {
+        collect_with_descendants(world, child, entities);
+    }
+}
@@ -0,0 +1,96 @@
+//! 無限世界のチャンク管理システムのテスト
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use moving_3d_cube::{ChunkObject, GroundChunk, InfiniteWorld, Player, manage_infinite_world};
+
+const CHUNK_SIZE: f32 = 20.0;
+const RENDER_DISTANCE: i32 = 2;
+
+/// チャンク管理システムだけを動かす最小構成のAppを作る
+fn chunk_app(player_position: Vec3) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .insert_resource(InfiniteWorld {
+            chunk_size: CHUNK_SIZE,
+            render_distance: RENDER_DISTANCE,
+        })
+        .add_systems(Update, manage_infinite_world);
+    app.world_mut()
+        .spawn((Player, Transform::from_translation(player_position)));
+    app
+}
+
+/// 現在存在するチャンク座標の一覧を取得する
+fn chunk_coords(app: &mut App) -> Vec<(i32, i32)> {
+    app.world_mut()
+        .query::<&GroundChunk>()
+        .iter(app.world())
+        .map(|chunk| (chunk.chunk_x, chunk.chunk_z))
+        .collect()
+}
+
+/// プレイヤーの位置を変更する
+fn move_player(app: &mut App, position: Vec3) {
+    let mut players = app
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Player>>();
+    players.single_mut(app.world_mut()).unwrap().translation = position;
+}
+
+#[test]
+fn spawns_chunks_around_player() {
+    let mut app = chunk_app(Vec3::ZERO);
+    app.update();
+
+    let coords = chunk_coords(&mut app);
+    let side = (RENDER_DISTANCE * 2 + 1) as usize;
+    assert_eq!(coords.len(), side * side);
+    assert!(
+        coords
+            .iter()
+            .all(|(x, z)| x.abs() <= RENDER_DISTANCE && z.abs() <= RENDER_DISTANCE)
+    );
+}
+
+#[test]
+fn chunks_are_not_duplicated() {
+    let mut app = chunk_app(Vec3::ZERO);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let coords = chunk_coords(&mut app);
+    let unique: HashSet<_> = coords.iter().collect();
+    assert_eq!(coords.len(), unique.len());
+}
+
+#[test]
+fn far_chunks_are_unloaded_when_player_moves() {
+    let mut app = chunk_app(Vec3::ZERO);
+    app.update();
+
+    // 5チャンク分だけ+X方向へ移動する
+    move_player(&mut app, Vec3::new(CHUNK_SIZE * 5.5, 0.0, 0.0));
+    app.update();
+
+    let coords = chunk_coords(&mut app);
+    assert!(
+        coords
+            .iter()
+            .all(|(x, z)| (x - 5).abs() <= RENDER_DISTANCE && z.abs() <= RENDER_DISTANCE)
+    );
+    assert!(coords.contains(&(5, 0)));
+
+    // チャンクに置かれたオブジェクトも一緒に削除されている
+    let objects: Vec<i32> = app
+        .world_mut()
+        .query::<&ChunkObject>()
+        .iter(app.world())
+        .map(|object| object.chunk_x)
+        .collect();
+    assert!(objects.iter().all(|x| (x - 5).abs() <= RENDER_DISTANCE));
+}
@@ -0,0 +1,92 @@
+//! チャンクの読み込み・削除に合わせて`ChunkResident`が保存・復元されることを確認するテスト
+
+use bevy::prelude::*;
+use moving_3d_cube::{ChunkResident, ChunkStorage, ChunkStreamingPlugin, InfiniteWorld, Player};
+
+const CHUNK_SIZE: f32 = 20.0;
+const RENDER_DISTANCE: i32 = 2;
+
+/// 保存・復元されることを確かめるための、状態を持つコンポーネント
+#[derive(Component, Reflect, Default, Debug, PartialEq)]
+#[reflect(Component, Default)]
+struct Coin {
+    value: u32,
+}
+
+/// チャンクの保存・復元だけを動かす最小構成のAppを作る
+fn streaming_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ChunkStreamingPlugin))
+        .register_type::<Coin>()
+        .insert_resource(InfiniteWorld {
+            chunk_size: CHUNK_SIZE,
+            render_distance: RENDER_DISTANCE,
+        });
+    app.world_mut().spawn((Player, Transform::default()));
+    app
+}
+
+/// プレイヤーの位置を変更する
+fn move_player(app: &mut App, position: Vec3) {
+    let mut players = app
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Player>>();
+    players.single_mut(app.world_mut()).unwrap().translation = position;
+}
+
+/// 存在するコインの値と位置の一覧
+fn coins(app: &mut App) -> Vec<(u32, Vec3)> {
+    app.world_mut()
+        .query::<(&Coin, &Transform)>()
+        .iter(app.world())
+        .map(|(coin, transform)| (coin.value, transform.translation))
+        .collect()
+}
+
+#[test]
+fn residents_are_stored_and_restored_with_their_state() {
+    let mut app = streaming_app();
+    let position = Vec3::new(CHUNK_SIZE * 1.5, 0.5, 0.0);
+    app.world_mut()
+        .spawn((ChunkResident, Coin { value: 7 }, Transform::from_translation(position)))
+        .with_child((Coin { value: 1 }, Transform::default()));
+    app.update();
+    assert_eq!(coins(&mut app).len(), 2);
+
+    // コインのチャンクが描画距離の外に出ると、子エンティティごと保存されて削除される
+    move_player(&mut app, Vec3::new(-CHUNK_SIZE * 5.0, 0.0, 0.0));
+    app.update();
+    assert!(coins(&mut app).is_empty());
+    assert_eq!(app.world().resource::<ChunkStorage>().entity_count((1, 0)), 2);
+
+    // 戻ってくると同じ状態で復元される
+    move_player(&mut app, Vec3::ZERO);
+    app.update();
+    let mut restored = coins(&mut app);
+    restored.sort_by_key(|(value, _)| *value);
+    assert_eq!(restored[1], (7, position));
+    assert_eq!(restored[0].0, 1);
+    assert_eq!(app.world().resource::<ChunkStorage>().chunk_count(), 0);
+
+    // 親子関係も復元されている
+    let mut children = app
+        .world_mut()
+        .query_filtered::<&ChildOf, With<Coin>>();
+    assert_eq!(children.iter(app.world()).count(), 1);
+}
+
+#[test]
+fn residents_in_loaded_chunks_are_kept() {
+    let mut app = streaming_app();
+    app.world_mut().spawn((
+        ChunkResident,
+        Coin { value: 3 },
+        Transform::from_xyz(CHUNK_SIZE * 2.5, 0.5, -CHUNK_SIZE * 1.5),
+    ));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(coins(&mut app).len(), 1);
+    assert_eq!(app.world().resource::<ChunkStorage>().chunk_count(), 0);
+}
@@ -0,0 +1,76 @@
+// サンプリングしたポイントのインスタンス描画(src/instancing.rs)
+// 1枚の四角形メッシュを、インスタンスごとの位置・スケール・発光色で、カメラに向けた円(ビルボード)として描く
+// 球体のメッシュより頂点が少なく、縁をなめらかに透明にするので、密集したところもにじんだ光の点に見える
+
+#import bevy_pbr::mesh_functions::get_world_from_local
+#import bevy_pbr::mesh_view_bindings::view
+
+// 円の半径に対する、縁を透明にし始める位置の割合(1に近いほど縁がくっきりする)
+const SOFT_EDGE: f32 = 0.35;
+
+// 画面上のポイントの半径の下限と上限(ピクセル)
+// 遠くのポイントが1ピクセルより小さくなってちらつかないように、近くのポイントが画面を覆わないようにする
+const MIN_RADIUS_PIXELS: f32 = 1.5;
+const MAX_RADIUS_PIXELS: f32 = 24.0;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+
+    @location(3) i_position_scale: vec4<f32>, // xyzが位置、wがスケール
+    @location(4) i_color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+// 中心までの奥行き(ビュー空間)での、1ピクセルのワールド座標での長さ
+fn world_per_pixel(depth: f32) -> f32 {
+    // 正射影では奥行きによらない
+    let perspective = view.clip_from_view[3][3] != 1.0;
+    let distance = select(1.0, depth, perspective);
+    return 2.0 * distance / (view.clip_from_view[1][1] * view.viewport.w);
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    // メッシュのエンティティ(原点に置いた1つだけ)の変換を使う
+    let world_from_local = get_world_from_local(0u);
+    let center = (world_from_local * vec4<f32>(vertex.i_position_scale.xyz, 1.0)).xyz;
+
+    // 距離による大きさの調整: 元の大きさ(四角形の半分の幅)が画面上で範囲外になる距離では、範囲内に収める
+    // アニメーションのスケールはその後にかけるので、生成・削除のときは0から大きさが変わる
+    let radius = abs(vertex.position.x);
+    let depth = -(view.view_from_world * vec4<f32>(center, 1.0)).z;
+    let pixels = radius / world_per_pixel(max(depth, 1e-4));
+    let attenuation = clamp(pixels, MIN_RADIUS_PIXELS, MAX_RADIUS_PIXELS) / max(pixels, 1e-6);
+    let size = vertex.i_position_scale.w * attenuation;
+
+    // カメラの右と上の向きに四角形を広げて、常にカメラの方を向ける
+    let right = view.world_from_view[0].xyz;
+    let up = view.world_from_view[1].xyz;
+    let position = center + (right * vertex.position.x + up * vertex.position.y) * size;
+
+    var out: VertexOutput;
+    out.clip_position = view.clip_from_world * vec4<f32>(position, 1.0);
+    out.color = vertex.i_color;
+    out.uv = vertex.uv;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 中心からの距離(円の縁で1)。円の外は描かない
+    let offset = in.uv * 2.0 - 1.0;
+    let distance = length(offset);
+    if distance > 1.0 {
+        discard;
+    }
+    // 縁に向かってなめらかに透明にする(乗算済みアルファで合成する)
+    let alpha = 1.0 - smoothstep(SOFT_EDGE, 1.0, distance);
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
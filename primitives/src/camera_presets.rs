@@ -0,0 +1,158 @@
+//! 数字キーで切り替えるカメラのプリセット
+//!
+//! 「1」正面・「2」真上・「3」等角(アイソメトリック)・「4」注視している図形のアップに、
+//! カメラの向き(yaw・pitch)・距離・注視点を切り替える。一瞬で切り替えると位置関係が
+//! 分からなくなるので、`CameraTransition`をカメラに付け、`update_camera`で
+//! `CAMERA_TRANSITION_TIME`秒かけて補間する。
+
+use std::f32::consts::{FRAC_PI_4, PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::demo_config::DemoConfig;
+use crate::{CameraRig, SampleAction, SampledShapes};
+
+/// プリセットへの切り替えにかける時間(秒)
+pub const CAMERA_TRANSITION_TIME: f32 = 0.5;
+
+/// 全体を見渡すプリセットのカメラの距離
+const OVERVIEW_DISTANCE: f32 = 12.0;
+
+/// 図形のアップのカメラの距離
+const CLOSE_UP_DISTANCE: f32 = 3.0;
+
+/// 真上から見るときのpitch(ちょうどπ/2だと上方向が決まらないので、マウス操作の上限と同じにする)
+const TOP_PITCH: f32 = PI / 2.01;
+
+/// カメラのプリセット
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraPreset {
+    Front,     // すべての図形を正面(+Z側)から見る
+    Top,       // すべての図形を真上から見る
+    Isometric, // すべての図形を斜め上から等角に見る
+    CloseUp,   // 注視している図形に近づく(向きは変えない)
+}
+
+/// カメラの向き・距離・注視点(CameraRigのうち補間する値)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RigPose {
+    pub yaw: f32,      // 水平方向の回転角度
+    pub pitch: f32,    // 垂直方向の回転角度
+    pub distance: f32, // 注視点からの距離
+    pub target: Vec3,  // 注視点
+}
+
+impl RigPose {
+    /// 2つの姿勢の間を補間する(yawは近い方の向きに回る)
+    pub fn lerp(self, other: RigPose, t: f32) -> RigPose {
+        let yaw_delta = (other.yaw - self.yaw + PI).rem_euclid(TAU) - PI;
+        RigPose {
+            yaw: self.yaw + yaw_delta * t,
+            pitch: self.pitch.lerp(other.pitch, t),
+            distance: self.distance.lerp(other.distance, t),
+            target: self.target.lerp(other.target, t),
+        }
+    }
+}
+
+impl CameraPreset {
+    /// プリセットの姿勢を返す(`focused`は注視している図形の位置)
+    /// 距離は設定ファイルのカメラの距離の範囲に収める
+    pub fn pose(self, current: RigPose, focused: Vec3, config: &DemoConfig) -> RigPose {
+        let overview = |yaw, pitch| RigPose {
+            yaw,
+            pitch,
+            distance: OVERVIEW_DISTANCE,
+            target: Vec3::ZERO,
+        };
+        let pose = match self {
+            CameraPreset::Front => overview(PI, 0.0),
+            CameraPreset::Top => overview(PI, TOP_PITCH),
+            // 等角投影と同じく、3つの軸が同じ角度に見える向き
+            CameraPreset::Isometric => overview(PI + FRAC_PI_4, (1.0 / 2.0_f32.sqrt()).atan()),
+            CameraPreset::CloseUp => RigPose {
+                distance: CLOSE_UP_DISTANCE,
+                target: focused,
+                ..current
+            },
+        };
+        RigPose {
+            distance: config.clamp_camera_distance(pose.distance),
+            ..pose
+        }
+    }
+}
+
+/// プリセットへ切り替え中のカメラに付けるコンポーネント
+#[derive(Component, Clone, Debug)]
+pub struct CameraTransition {
+    from: RigPose, // 切り替えを始めたときの姿勢
+    to: RigPose,   // プリセットの姿勢
+    elapsed: f32,  // 切り替えを始めてからの時間(秒)
+}
+
+impl CameraTransition {
+    /// `from`から`to`への切り替えを始める
+    pub fn new(from: RigPose, to: RigPose) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+        }
+    }
+
+    /// 時間を進めて、その時点の姿勢を返す(始めと終わりはゆっくり動く)
+    pub fn advance(&mut self, delta: f32) -> RigPose {
+        self.elapsed = (self.elapsed + delta).min(CAMERA_TRANSITION_TIME);
+        let t = self.elapsed / CAMERA_TRANSITION_TIME;
+        self.from.lerp(self.to, t * t * (3.0 - 2.0 * t))
+    }
+
+    /// 切り替えが終わったか
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= CAMERA_TRANSITION_TIME
+    }
+}
+
+/// CameraRigの現在の姿勢
+pub(crate) fn rig_pose(rig: &CameraRig) -> RigPose {
+    RigPose {
+        yaw: rig.yaw,
+        pitch: rig.pitch,
+        distance: rig.current_distance,
+        target: rig.target,
+    }
+}
+
+// 「1」〜「4」キー：カメラをプリセットの姿勢へ切り替え始めるシステム
+pub(crate) fn apply_camera_preset(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    cameras: Query<(Entity, &CameraRig)>,
+    shapes: Res<SampledShapes>,
+    config: Res<DemoConfig>,
+) {
+    let presets = [
+        (SampleAction::CameraFront, CameraPreset::Front),
+        (SampleAction::CameraTop, CameraPreset::Top),
+        (SampleAction::CameraIsometric, CameraPreset::Isometric),
+        (SampleAction::CameraCloseUp, CameraPreset::CloseUp),
+    ];
+    let Some(preset) = presets
+        .into_iter()
+        .find(|(action, _)| actions.just_pressed(*action))
+        .map(|(_, preset)| preset)
+    else {
+        return;
+    };
+
+    for (entity, rig) in &cameras {
+        let current = rig_pose(rig);
+        let focused = shapes
+            .0
+            .get(shapes.closest(rig.target))
+            .map_or(rig.target, |(_, position)| *position);
+        let transition = CameraTransition::new(current, preset.pose(current, focused, &config));
+        commands.entity(entity).insert(transition);
+    }
+}
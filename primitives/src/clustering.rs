@@ -0,0 +1,76 @@
+//! サンプリングしたポイントのクラスタリング(k-means法)
+//!
+//! 図形ごとにポイントをk個のクラスタに分け、各クラスタの重心とポイント数を求める。
+//! 一様にサンプリングしていれば、体積(境界なら面積)の大きい部分ほど多くのポイントが集まる。
+
+use bevy::math::Vec3;
+
+/// 1つのクラスタ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cluster {
+    pub centroid: Vec3, // クラスタに属するポイントの重心
+    pub count: usize,   // クラスタに属するポイントの数
+}
+
+/// k-means法でポイントをk個のクラスタに分ける
+///
+/// `initial`にk個の重心を渡すと、そこから計算を始める(前回の結果を渡すと、クラスタの順番が安定する)。
+/// それ以外の場合は、ポイントを等間隔に選んで初期の重心にする。
+/// ポイントがk個より少ない場合は、ポイントの数だけクラスタを作る。
+pub fn k_means(points: &[Vec3], initial: &[Vec3], k: usize, iterations: usize) -> Vec<Cluster> {
+    let k = k.min(points.len());
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut centroids: Vec<_> = if initial.len() == k {
+        initial.to_vec()
+    } else {
+        (0..k).map(|i| points[i * points.len() / k]).collect()
+    };
+
+    let mut assignments = vec![0; points.len()];
+    for iteration in 0..iterations.max(1) {
+        // 各ポイントを最も近い重心のクラスタに割り当てる
+        let mut changed = iteration == 0;
+        for (point, assignment) in points.iter().zip(assignments.iter_mut()) {
+            let nearest = nearest_centroid(&centroids, *point);
+            changed |= *assignment != nearest;
+            *assignment = nearest;
+        }
+        // 割り当てが変わらなくなったら終わる
+        if !changed {
+            break;
+        }
+
+        // 割り当てたポイントの平均を新しい重心にする(ポイントのないクラスタはそのまま)
+        let mut sums = vec![(Vec3::ZERO, 0); k];
+        for (point, assignment) in points.iter().zip(&assignments) {
+            sums[*assignment].0 += *point;
+            sums[*assignment].1 += 1;
+        }
+        for (centroid, (sum, count)) in centroids.iter_mut().zip(sums) {
+            if count > 0 {
+                *centroid = sum / count as f32;
+            }
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = centroids
+        .into_iter()
+        .map(|centroid| Cluster { centroid, count: 0 })
+        .collect();
+    for assignment in assignments {
+        clusters[assignment].count += 1;
+    }
+    clusters
+}
+
+/// 最も近い重心のインデックス
+fn nearest_centroid(centroids: &[Vec3], point: Vec3) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+        .map_or(0, |(index, _)| index)
+}
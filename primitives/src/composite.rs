@@ -0,0 +1,345 @@
+//! 2つの図形の和集合・共通部分(ブーリアン演算、CSG)のサンプリング
+//!
+//! 「5」キーで、カメラが注視している図形を、右隣の図形(右端なら左端の図形)との和集合 → 共通部分 → 元の図形の順に切り替える。
+//! 2つの図形は同じ中心に重ね、表示には2つのメッシュを1つにまとめたものを使う(重なった部分が濃く見える)。
+//! サンプリングは棄却法で、一方の図形から選んだ点を、もう一方の図形の内外の判定(`Shape::contains_point`)で採用するか決める。
+//! - 和集合の内部: 1つ目の図形の点はすべて、2つ目の図形の点は1つ目の外にあるものだけ(重なった部分の密度が2倍にならない)
+//! - 共通部分の内部: 1つ目の図形の点のうち、2つ目の図形の内部にあるものだけ
+//! - 境界: どちらの図形の表面の点も、和集合ならもう一方の外、共通部分ならもう一方の内部にあるものだけ
+//!
+//! どちらの図形から選ぶかは採用される部分の大きさに比例させるので、合成した図形全体で一様になる。
+//! 採用される部分の大きさ(合成した図形の体積・表面積)と重心は、合成するときにモンテカルロ法で推定しておく。
+//! 3Dの図形と平面の図形は組み合わせず、共通部分が空になる組み合わせでは元の図形に戻す。
+//! 切り替えると注視している図形のポイントは消え、合成した図形でサンプリングし直す。
+//! 表示を切り替える(Vキー)と、図形は元に戻る。
+
+use std::sync::Arc;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::math::ShapeSample;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::monte_carlo::SignedDistance;
+use crate::wireframe::WireframeOverlay;
+use crate::{
+    CameraRig, PointCounter, SampleAction, SamplePoint, SampledShapes, SamplingMode, Shape,
+    ShapeIndex, ShapeRoot,
+};
+
+/// 採用される部分の大きさと重心の推定に、図形ごとに選ぶ点の数
+const ESTIMATE_SAMPLES: usize = 4096;
+
+/// 推定に使う乱数のシード値(同じ組み合わせなら毎回同じ推定値になる)
+const ESTIMATE_SEED: u64 = 0;
+
+/// 1つの点を選ぶときに棄却法を試す回数の上限
+const MAX_ATTEMPTS: usize = 1000;
+
+/// 図形の合成を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct CompositeShapePlugin;
+
+impl Plugin for CompositeShapePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            cycle_composite
+                .after(crate::handle_keypress)
+                .after(crate::switch_view)
+                .after(crate::custom_mesh::add_custom_mesh)
+                .before(crate::spawn_points),
+        );
+    }
+}
+
+/// 2つの図形のブーリアン演算
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CsgOperation {
+    Union,        // 和集合(どちらかの図形に含まれる部分)
+    Intersection, // 共通部分(両方の図形に含まれる部分)
+}
+
+impl CsgOperation {
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            CsgOperation::Union => "shape.union",
+            CsgOperation::Intersection => "shape.intersection",
+        }
+    }
+}
+
+/// 同じ中心に重ねた2つの図形を合成した図形(`Shape::Composite`)
+pub(crate) struct CompositeShape {
+    shapes: [Shape; 2],      // 合成する図形(1つ目が注視していた図形)
+    operation: CsgOperation, // 和集合か共通部分か
+    parts: [[f32; 2]; 2],    // [内部, 境界]ごとの、それぞれの図形から採用される部分の大きさ
+    centroids: [Vec3; 2],    // [内部, 境界]の重心
+}
+
+impl CompositeShape {
+    /// 2つの図形を合成する(3Dと平面の図形の組み合わせや、空になる場合はNone)
+    pub(crate) fn new(shapes: [Shape; 2], operation: CsgOperation) -> Option<Self> {
+        if shapes[0].is_planar() != shapes[1].is_planar() {
+            return None;
+        }
+        let mut composite = Self {
+            shapes,
+            operation,
+            parts: [[0.0; 2]; 2],
+            centroids: [Vec3::ZERO; 2],
+        };
+
+        // それぞれの図形から点を選び、採用された割合と平均の位置から、部分の大きさと重心を推定する
+        let mut rng = ChaCha8Rng::seed_from_u64(ESTIMATE_SEED);
+        for interior in [true, false] {
+            let slot = slot(interior);
+            let mut weighted = Vec3::ZERO;
+            for source in 0..2 {
+                let (kept, sum) = (0..ESTIMATE_SAMPLES)
+                    .map(|_| sample_from(&composite.shapes[source], interior, &mut rng))
+                    .filter(|point| composite.keeps(source, *point, interior))
+                    .fold((0, Vec3::ZERO), |(kept, sum), point| {
+                        (kept + 1, sum + point)
+                    });
+                let part = composite.shapes[source].measure(mode(interior)) * kept as f32
+                    / ESTIMATE_SAMPLES as f32;
+                composite.parts[slot][source] = part;
+                if kept > 0 {
+                    weighted += part * sum / kept as f32;
+                }
+            }
+            let total = composite.parts[slot][0] + composite.parts[slot][1];
+            if total <= 0.0 {
+                return None;
+            }
+            composite.centroids[slot] = weighted / total;
+        }
+        Some(composite)
+    }
+
+    /// 和集合か共通部分か
+    pub(crate) fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    /// 合成する前の2つの図形
+    pub(crate) fn shapes(&self) -> &[Shape; 2] {
+        &self.shapes
+    }
+
+    /// 点が合成した図形の内部(境界を含む)にあるか
+    pub(crate) fn contains_point(&self, point: Vec3) -> bool {
+        let [first, second] = &self.shapes;
+        match self.operation {
+            CsgOperation::Union => first.contains_point(point) || second.contains_point(point),
+            CsgOperation::Intersection => {
+                first.contains_point(point) && second.contains_point(point)
+            }
+        }
+    }
+
+    /// サンプリングする範囲の大きさの推定値(内部は体積、境界は表面積)
+    pub(crate) fn measure(&self, mode: SamplingMode) -> f32 {
+        let [first, second] = self.parts[slot(mode != SamplingMode::Boundary)];
+        first + second
+    }
+
+    /// サンプリングモードでの重心の推定値
+    pub(crate) fn centroid(&self, mode: SamplingMode) -> Vec3 {
+        self.centroids[slot(mode != SamplingMode::Boundary)]
+    }
+
+    /// 表示用のMesh(2つの図形のメッシュを1つにまとめる)
+    pub(crate) fn mesh(&self) -> Mesh {
+        overlapped_mesh(self.shapes.each_ref().map(|shape| shape.mesh().build()))
+    }
+
+    /// `source`番目の図形から選んだ点を、合成した図形の点として採用するか
+    fn keeps(&self, source: usize, point: Vec3, interior: bool) -> bool {
+        let other = &self.shapes[1 - source];
+        match (self.operation, interior, source) {
+            (CsgOperation::Union, true, 0) => true,
+            (CsgOperation::Intersection, true, 1) => false, // 1つ目の図形からだけ選ぶ
+            (CsgOperation::Union, _, _) => !other.contains_point(point),
+            (CsgOperation::Intersection, _, _) => other.contains_point(point),
+        }
+    }
+
+    /// 採用される部分の大きさに比例してどちらかの図形を選び、棄却法で点を選ぶ
+    fn sample<R: Rng + ?Sized>(&self, interior: bool, rng: &mut R) -> Vec3 {
+        let [first, second] = self.parts[slot(interior)];
+        let source = if rng.gen_range(0.0..first + second) < first {
+            0
+        } else {
+            1
+        };
+        let mut point = Vec3::ZERO;
+        for _ in 0..MAX_ATTEMPTS {
+            point = sample_from(&self.shapes[source], interior, rng);
+            if self.keeps(source, point, interior) {
+                break;
+            }
+        }
+        point // 採用される部分があることは推定で確かめてあるので、上限に達することはまずない
+    }
+}
+
+impl ShapeSample for CompositeShape {
+    type Output = Vec3;
+
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        self.sample(true, rng)
+    }
+
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        self.sample(false, rng)
+    }
+}
+
+/// 和集合は距離の小さい方、共通部分は大きい方(内部の判定は正確で、外側の距離は近似になる)
+impl SignedDistance for CompositeShape {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let [first, second] = self
+            .shapes
+            .each_ref()
+            .map(|shape| shape.signed_distance(point));
+        match self.operation {
+            CsgOperation::Union => first.min(second),
+            CsgOperation::Intersection => first.max(second),
+        }
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        let [first, second] = self.shapes.each_ref().map(|shape| shape.half_extents());
+        match self.operation {
+            CsgOperation::Union => first.max(second),
+            CsgOperation::Intersection => first.min(second),
+        }
+    }
+}
+
+/// `parts`・`centroids`のインデックス(内部が0、境界が1)
+fn slot(interior: bool) -> usize {
+    if interior { 0 } else { 1 }
+}
+
+/// 内部か境界かに対応するサンプリングモード
+fn mode(interior: bool) -> SamplingMode {
+    if interior {
+        SamplingMode::Interior
+    } else {
+        SamplingMode::Boundary
+    }
+}
+
+/// 図形の内部か境界から点を1つ選ぶ
+fn sample_from<R: Rng + ?Sized>(shape: &Shape, interior: bool, rng: &mut R) -> Vec3 {
+    if interior {
+        shape.sample_interior(rng)
+    } else {
+        shape.sample_boundary(rng)
+    }
+}
+
+/// 三角形リストのメッシュの頂点の位置と法線、インデックスをつなげて1つのメッシュにする
+/// (UVなどの属性は図形ごとに違うので使わない)
+fn overlapped_mesh(meshes: [Mesh; 2]) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    for mesh in &meshes {
+        let (
+            Some(VertexAttributeValues::Float32x3(mesh_positions)),
+            Some(VertexAttributeValues::Float32x3(mesh_normals)),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+        )
+        else {
+            continue;
+        };
+        let offset = positions.len() as u32;
+        match mesh.indices() {
+            Some(mesh_indices) => {
+                indices.extend(mesh_indices.iter().map(|index| offset + index as u32))
+            }
+            None => indices.extend(offset..offset + mesh_positions.len() as u32),
+        }
+        positions.extend_from_slice(mesh_positions);
+        normals.extend_from_slice(mesh_normals);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// 図形の次の状態(和集合 → 共通部分 → 元の図形)。組み合わせられなければNone
+/// 倍率(7・8キー)を変えた図形は、合成した図形に同じ倍率をかける
+fn next_composite(shape: &Shape, neighbor: &Shape) -> Option<Shape> {
+    let next = match shape.base() {
+        Shape::Composite(composite) if composite.operation() == CsgOperation::Union => {
+            // 共通部分が空なら元の図形に戻す
+            match CompositeShape::new(composite.shapes().clone(), CsgOperation::Intersection) {
+                Some(intersection) => Shape::Composite(Arc::new(intersection)),
+                None => composite.shapes()[0].clone(),
+            }
+        }
+        Shape::Composite(composite) => composite.shapes()[0].clone(),
+        base => Shape::Composite(Arc::new(CompositeShape::new(
+            [base.clone(), neighbor.clone()],
+            CsgOperation::Union,
+        )?)),
+    };
+    Some(next.with_scale(shape.scale()))
+}
+
+// 「5」キー：注視している図形を、右隣の図形との和集合・共通部分・元の図形に切り替えるシステム
+fn cycle_composite(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    mut shapes: ResMut<SampledShapes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut counter: ResMut<PointCounter>,
+    camera_rig: Query<&CameraRig>,
+    roots: Query<(Entity, &ShapeRoot, &Mesh3d)>,
+    wireframes: Query<(&ChildOf, &Mesh3d), With<WireframeOverlay>>,
+    points: Query<(Entity, &ShapeIndex), With<SamplePoint>>,
+) {
+    if !actions.just_pressed(SampleAction::CycleComposite) || shapes.0.len() < 2 {
+        return;
+    }
+    let Ok(rig) = camera_rig.single() else {
+        return;
+    };
+    let index = shapes.closest(rig.target);
+    let neighbor = &shapes.0[(index + 1) % shapes.0.len()].0;
+    let Some(next) = next_composite(&shapes.0[index].0, neighbor) else {
+        info!("3Dの図形と平面の図形は組み合わせられません");
+        return;
+    };
+
+    let Some((root, _, mesh)) = roots.iter().find(|(_, root, _)| root.0 == index) else {
+        return;
+    };
+    crate::rebuild_shape_meshes(
+        &mut meshes,
+        mesh,
+        wireframes
+            .iter()
+            .filter(|(child_of, _)| child_of.parent() == root)
+            .map(|(_, wireframe)| wireframe),
+        &next,
+    );
+    // 前の図形でサンプリングしたポイントを消す(自動モードでは合成した図形で生成し直される)
+    crate::clear_shape_points(&mut commands, &points, &mut counter, index);
+
+    shapes.0[index].0 = next;
+    info!("{}番目の図形を切り替えました", index + 1);
+}
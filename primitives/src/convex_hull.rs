@@ -0,0 +1,400 @@
+//! 注視している図形のポイントの凸包
+//!
+//! カメラが注視している図形(矢印キーで切り替える)のポイントを包む最小の凸多面体を求め、
+//! 半透明のメッシュとして図形のエンティティの子に重ねる。ポイントが増えるほど、凸包は
+//! 図形そのものの凸包(球や直方体なら図形そのもの)に近づいていく様子が見られる。
+//! 新しいポイントが凸包の内側なら何もせず、外側のポイントがあるときだけ、凸包の頂点と
+//! 外側のポイントから計算し直す(凸包の頂点だけで元の凸包が決まるので、少ない点で済む)。
+//! 凸包の頂点になっているポイントが削除されたときは、その図形のすべてのポイントから計算し直す。
+//! 同じ平面上のポイント(2Dの図形や三角形)では、その平面上の凸多角形にする。
+//! 「U」キーで切り替える。
+
+use std::collections::HashSet;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+use crate::{CameraRig, SampleAction, SamplePoint, SampledShapes, ShapeIndex, ShapeRoot};
+
+/// 同じ平面上・凸包の内側とみなす距離
+const HULL_EPSILON: f32 = 1e-5;
+
+/// 凸包の表示を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ConvexHullPlugin;
+
+impl Plugin for ConvexHullPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConvexHull>()
+            .init_resource::<HullAssets>()
+            .add_systems(
+                Update,
+                (toggle_convex_hull, update_convex_hull)
+                    .chain()
+                    .after(crate::handle_keypress)
+                    .after(crate::switch_view)
+                    .after(crate::animate_spawning),
+            );
+    }
+}
+
+/// 注視している図形のポイントの凸包を保持するリソース
+#[derive(Resource, Default, Debug)]
+pub struct ConvexHull {
+    pub enabled: bool,                 // 凸包を表示するか(初期は非表示)
+    pub shape: Option<usize>,          // 凸包を求めた図形(SampledShapesのインデックス)
+    pub vertices: Vec<(Entity, Vec3)>, // 凸包の頂点になっているポイントと、図形の中心からの位置
+    pub triangles: Vec<[usize; 3]>,    // 凸包の面(verticesのインデックス、外側から見て反時計回り)
+    root: Option<Entity>,              // 凸包を重ねている図形のエンティティ
+}
+
+impl ConvexHull {
+    /// 凸包の頂点の位置
+    pub fn positions(&self) -> Vec<Vec3> {
+        self.vertices
+            .iter()
+            .map(|(_, position)| *position)
+            .collect()
+    }
+
+    /// 凸包の体積(平面の凸包では0)
+    pub fn volume(&self) -> f32 {
+        let positions = self.positions();
+        let signed: f32 = self
+            .triangles
+            .iter()
+            .map(|[a, b, c]| positions[*a].dot(positions[*b].cross(positions[*c])) / 6.0)
+            .sum();
+        signed.abs()
+    }
+
+    /// 点が凸包の内側(面の上を含む)にあるか
+    pub fn contains(&self, point: Vec3) -> bool {
+        hull_contains(&self.positions(), &self.triangles, point)
+    }
+
+    /// 与えたポイントから凸包を計算し直す
+    pub fn rebuild(&mut self, points: Vec<(Entity, Vec3)>) {
+        let positions: Vec<Vec3> = points.iter().map(|(_, position)| *position).collect();
+        let triangles = convex_hull(&positions);
+        if triangles.is_empty() {
+            // まだ凸包ができない(ポイントが同じ直線上にある)ときは、すべてを次の候補として残す
+            self.vertices = points;
+            self.triangles.clear();
+            return;
+        }
+
+        // 面に使われているポイントだけを頂点として残す
+        let mut remap = vec![usize::MAX; points.len()];
+        self.vertices.clear();
+        for index in triangles.iter().flatten() {
+            if remap[*index] == usize::MAX {
+                remap[*index] = self.vertices.len();
+                self.vertices.push(points[*index]);
+            }
+        }
+        self.triangles = triangles
+            .iter()
+            .map(|triangle| triangle.map(|index| remap[index]))
+            .collect();
+    }
+
+    /// 新しいポイントを加える(外側のポイントがあれば計算し直して`true`を返す)
+    pub fn extend(&mut self, points: impl IntoIterator<Item = (Entity, Vec3)>) -> bool {
+        let positions = self.positions();
+        let outside: Vec<(Entity, Vec3)> = points
+            .into_iter()
+            .filter(|(_, position)| !hull_contains(&positions, &self.triangles, *position))
+            .collect();
+        if outside.is_empty() {
+            return false;
+        }
+        let mut candidates = std::mem::take(&mut self.vertices);
+        candidates.extend(outside);
+        self.rebuild(candidates);
+        true
+    }
+}
+
+/// 点の集まりの凸包の面(三角形の頂点のインデックス。外側から見て反時計回り)を求める
+/// 3次元では逐次添加法(点を1つずつ加え、その点から見える面を取り除いて地平線の辺とつなぐ)を使い、
+/// すべての点が同じ平面上にあるときは、その平面上の凸多角形を扇形に三角形分割する
+/// 点が2個以下か、すべて同じ直線上にあるときは空になる
+pub fn convex_hull(points: &[Vec3]) -> Vec<[usize; 3]> {
+    let Some(&first) = points.first() else {
+        return Vec::new();
+    };
+    // 最初の四面体: 最も遠い点、その直線から最も遠い点、その平面から最も遠い点
+    let farthest = |distance: &dyn Fn(Vec3) -> f32| {
+        (0..points.len())
+            .map(|index| (index, distance(points[index])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap_or((0, 0.0))
+    };
+    let (b, distance) = farthest(&|point| point.distance(first));
+    if distance <= HULL_EPSILON {
+        return Vec::new();
+    }
+    let axis = (points[b] - first).normalize();
+    let (c, distance) = farthest(&|point| (point - first).reject_from_normalized(axis).length());
+    if distance <= HULL_EPSILON {
+        return Vec::new();
+    }
+    let normal = axis.cross(points[c] - first).normalize();
+    let (d, distance) = farthest(&|point| (point - first).dot(normal).abs());
+    if distance <= HULL_EPSILON {
+        return planar_hull(points, first, axis, normal);
+    }
+
+    // 四面体の面を、残りの頂点が内側(裏)になる向きにする
+    let mut faces: Vec<[usize; 3]> = Vec::new();
+    for [i, j, k, opposite] in [[0, b, c, d], [0, c, d, b], [0, d, b, c], [b, d, c, 0]] {
+        let face = [i, j, k];
+        faces.push(if face_distance(points, face, points[opposite]) > 0.0 {
+            [i, k, j]
+        } else {
+            face
+        });
+    }
+
+    for (index, point) in points.iter().enumerate() {
+        if [0, b, c, d].contains(&index) {
+            continue;
+        }
+        let (visible, hidden): (Vec<[usize; 3]>, Vec<[usize; 3]>) = faces
+            .iter()
+            .copied()
+            .partition(|face| face_distance(points, *face, *point) > HULL_EPSILON);
+        if visible.is_empty() {
+            continue; // 凸包の内側
+        }
+        // 見える面の辺のうち、逆向きの辺が見える面にないものが地平線になる
+        let edges: HashSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|[i, j, k]| [(*i, *j), (*j, *k), (*k, *i)])
+            .collect();
+        faces = hidden;
+        faces.extend(
+            edges
+                .iter()
+                .filter(|(from, to)| !edges.contains(&(*to, *from)))
+                .map(|(from, to)| [*from, *to, index]),
+        );
+    }
+    faces
+}
+
+/// 同じ平面上の点の凸多角形を、法線`normal`の向きから見て反時計回りの扇形に三角形分割する
+fn planar_hull(points: &[Vec3], origin: Vec3, axis: Vec3, normal: Vec3) -> Vec<[usize; 3]> {
+    // 平面上の座標にして、アンドリューの単調連鎖法で凸多角形の頂点を求める
+    let across = normal.cross(axis);
+    let coordinates: Vec<Vec2> = points
+        .iter()
+        .map(|point| Vec2::new((point - origin).dot(axis), (point - origin).dot(across)))
+        .collect();
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|a, b| {
+        let (a, b) = (coordinates[*a], coordinates[*b]);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    });
+    let turn = |chain: &[usize], next: usize| {
+        let (o, a) = (chain[chain.len() - 2], chain[chain.len() - 1]);
+        (coordinates[a] - coordinates[o]).perp_dot(coordinates[next] - coordinates[o])
+    };
+    let mut polygon: Vec<usize> = Vec::new();
+    for pass in [order.clone(), order.into_iter().rev().collect()] {
+        let start = polygon.len();
+        for index in pass {
+            while polygon.len() >= start + 2 && turn(&polygon, index) <= HULL_EPSILON {
+                polygon.pop();
+            }
+            polygon.push(index);
+        }
+        polygon.pop(); // 終点は次の連鎖の始点と同じ
+    }
+
+    polygon
+        .windows(2)
+        .skip(1)
+        .map(|edge| [polygon[0], edge[0], edge[1]])
+        .collect()
+}
+
+/// 面の平面から点までの符号付きの距離(面の表側が正)
+fn face_distance(points: &[Vec3], [a, b, c]: [usize; 3], point: Vec3) -> f32 {
+    let normal = (points[b] - points[a])
+        .cross(points[c] - points[a])
+        .normalize_or_zero();
+    normal.dot(point - points[a])
+}
+
+/// 点が凸包の内側(面の上を含む)にあるか
+/// 平面の凸包では、平面上にあって、いずれかの三角形に入っているかで判定する
+fn hull_contains(positions: &[Vec3], triangles: &[[usize; 3]], point: Vec3) -> bool {
+    let Some(&[a, b, c]) = triangles.first() else {
+        return false; // 凸包がまだない
+    };
+    let normal = (positions[b] - positions[a])
+        .cross(positions[c] - positions[a])
+        .normalize_or_zero();
+    let planar = positions
+        .iter()
+        .all(|position| normal.dot(position - positions[a]).abs() <= HULL_EPSILON);
+    if !planar {
+        return triangles
+            .iter()
+            .all(|face| face_distance(positions, *face, point) <= HULL_EPSILON);
+    }
+    if normal.dot(point - positions[a]).abs() > HULL_EPSILON {
+        return false;
+    }
+    triangles.iter().any(|[a, b, c]| {
+        let (a, b, c) = (positions[*a], positions[*b], positions[*c]);
+        [(a, b), (b, c), (c, a)]
+            .iter()
+            .all(|(from, to)| (*to - *from).cross(point - *from).dot(normal) >= -HULL_EPSILON)
+    })
+}
+
+/// 凸包の面から、面ごとの法線を付けた半透明表示用のメッシュを作る(面がなければ空のメッシュ)
+pub fn hull_mesh(positions: &[Vec3], triangles: &[[usize; 3]]) -> Mesh {
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    if triangles.is_empty() {
+        return mesh
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new());
+    }
+    mesh.with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        positions
+            .iter()
+            .map(|position| position.to_array())
+            .collect::<Vec<_>>(),
+    )
+    .with_inserted_indices(Indices::U32(
+        triangles
+            .iter()
+            .flatten()
+            .map(|index| *index as u32)
+            .collect(),
+    ))
+    .with_duplicated_vertices()
+    .with_computed_flat_normals()
+}
+
+/// 凸包の表示に使うメッシュとマテリアルのハンドルを保持するリソース
+#[derive(Resource)]
+struct HullAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for HullAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(hull_mesh(&[], &[]));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::srgba(0.3, 0.9, 0.6, 0.2), // 図形(青紫色)と見分けられる半透明の緑色
+                perceptual_roughness: 1.0,                    // 反射率の逆数相当
+                alpha_mode: AlphaMode::Blend,                 // 透明モード
+                cull_mode: None,                              // 裏面も描画する
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+/// 凸包のメッシュのマーカーコンポーネント(注視している図形のエンティティの子)
+#[derive(Component)]
+pub struct HullOverlay;
+
+// 「U」キー：凸包の表示・非表示を切り替えるシステム
+fn toggle_convex_hull(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    mut hull: ResMut<ConvexHull>,
+    overlays: Query<Entity, With<HullOverlay>>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleConvexHull) {
+        return;
+    }
+    let enabled = !hull.enabled;
+    // 無効にしたら、次に有効にしたときにすべてのポイントから計算し直す
+    *hull = ConvexHull {
+        enabled,
+        ..default()
+    };
+    for overlay in overlays.iter() {
+        commands.entity(overlay).despawn();
+    }
+}
+
+// 注視している図形の新しいポイントで凸包を広げ、メッシュを更新するシステム
+fn update_convex_hull(
+    mut commands: Commands,
+    mut hull: ResMut<ConvexHull>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    assets: Res<HullAssets>,
+    shapes: Res<SampledShapes>,
+    camera_rig: Query<&CameraRig>,
+    roots: Query<(Entity, &ShapeRoot)>,
+    points: Query<(Entity, &Transform, &ShapeIndex), With<SamplePoint>>,
+    new_points: Query<(Entity, &Transform, &ShapeIndex), Added<SamplePoint>>,
+    overlays: Query<(Entity, &ChildOf), With<HullOverlay>>,
+) {
+    if !hull.enabled {
+        return;
+    }
+    let Ok(camera_rig) = camera_rig.single() else {
+        return;
+    };
+    let index = shapes.closest(camera_rig.target);
+    let Some((root, _)) = roots.iter().find(|(_, root)| root.0 == index) else {
+        return;
+    };
+
+    // ポイントは図形の中心からの位置(Transform)で集めるので、図形が回転しても計算し直さなくてよい
+    let of_shape = |(entity, transform, shape_index): (Entity, &Transform, &ShapeIndex)| {
+        (shape_index.0 == index).then_some((entity, transform.translation))
+    };
+    let removed = hull
+        .vertices
+        .iter()
+        .any(|(entity, _)| !points.contains(*entity));
+    let changed = if hull.root != Some(root) || removed {
+        hull.rebuild(points.iter().filter_map(of_shape).collect());
+        true
+    } else {
+        hull.extend(new_points.iter().filter_map(of_shape))
+    };
+    hull.shape = Some(index);
+    hull.root = Some(root);
+
+    if changed {
+        if let Some(mesh) = meshes.get_mut(&assets.mesh) {
+            *mesh = hull_mesh(&hull.positions(), &hull.triangles);
+        }
+    }
+    // 凸包のメッシュを注視している図形の子にする(図形と一緒に回転する)
+    if overlays
+        .iter()
+        .any(|(_, child_of)| child_of.parent() == root)
+    {
+        return;
+    }
+    for (overlay, _) in overlays.iter() {
+        commands.entity(overlay).despawn();
+    }
+    commands.spawn((
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+        HullOverlay,
+        ChildOf(root),
+    ));
+}
@@ -0,0 +1,394 @@
+//! glTFから読み込んだ任意のメッシュのサンプリング
+//!
+//! glTFの最初のメッシュを読み込み、三角形の一覧にして`Shape::CustomMesh`として3Dの図形の最後(横一列なら右端)に並べる。
+//! 境界(表面)は、三角形を面積に比例して選んでから三角形の中の点を一様に選ぶ。
+//! 内部は、メッシュを囲む箱の中の点を選び、メッシュの内側に入ったものだけを使う(棄却法)。
+//! 内側かどうかは、点から伸ばしたレイがメッシュの表面と交わる回数の偶奇で判定するので、
+//! 穴のない閉じたメッシュ(watertight)を前提にする。
+//! 読み込むファイルはmainで`--mesh <パス>`から指定する(`assets/`からの相対パス)。
+//! 読み込みは非同期なので、読み込みが終わったフレームで図形に加える。
+
+use std::sync::Arc;
+
+use bevy::asset::{LoadState, RenderAssetUsages};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use rand::Rng;
+
+use crate::demo_config::DemoConfig;
+use crate::layout::LayoutMode;
+use crate::monte_carlo::SignedDistance;
+use crate::{SampledShapes, Shape, ShapeMaterial, ViewMode, spawn_shape_visual};
+
+/// 既定で読み込むglTF(`assets/`からの相対パス)
+pub const DEFAULT_MESH_PATH: &str = "models/spiky_ball.gltf";
+
+/// 読み込んだメッシュを、原点を中心にこの大きさ(囲む箱の最も長い辺の半分)に揃える
+/// 他の図形と同じくらいの大きさにして、隣の図形と重ならないようにする
+const MESH_HALF_SIZE: f32 = 0.8;
+
+/// 内部のサンプリングで、メッシュの内側の点が見つかるまでに試す回数
+/// 見つからなければ表面の点を使う
+const INTERIOR_ATTEMPTS: usize = 100;
+
+/// 内側かどうかを調べるレイの向き(三角形の辺や頂点をちょうど通りにくいよう、軸から少しずらす)
+const INSIDE_RAY: Vec3 = Vec3::new(1.0, 0.0123, 0.0371);
+
+/// 三角形の一覧で表した、サンプリングできるメッシュ
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleMesh {
+    triangles: Vec<Triangle3d>, // 表面の三角形
+    cumulative_areas: Vec<f32>, // 先頭からその三角形までの面積の合計(面積に比例して選ぶのに使う)
+    half_extents: Vec3,         // 原点を中心にメッシュを囲む箱の大きさの半分
+    volume: f32,                // 体積(閉じたメッシュとして計算する)
+}
+
+impl TriangleMesh {
+    /// 三角形の一覧から作る(三角形がない、または面積がなければNone)
+    pub fn new(triangles: Vec<Triangle3d>) -> Option<Self> {
+        let cumulative_areas: Vec<f32> = triangles
+            .iter()
+            .scan(0.0, |total, triangle| {
+                *total += triangle.area();
+                Some(*total)
+            })
+            .collect();
+        if cumulative_areas.last().is_none_or(|area| *area <= 0.0) {
+            return None;
+        }
+
+        let half_extents = triangles
+            .iter()
+            .flat_map(|triangle| triangle.vertices)
+            .fold(Vec3::ZERO, |extents, vertex| extents.max(vertex.abs()));
+        // 各三角形と原点でできる四面体の符号付き体積の合計(発散定理)
+        let volume = triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.vertices;
+                a.dot(b.cross(c)) / 6.0
+            })
+            .sum::<f32>()
+            .abs();
+
+        Some(Self {
+            triangles,
+            cumulative_areas,
+            half_extents,
+            volume,
+        })
+    }
+
+    /// BevyのMesh(三角形リスト)から作り、原点を中心に`MESH_HALF_SIZE`の大きさに揃える
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+        let positions: Vec<Vec3> = positions
+            .iter()
+            .map(|position| Vec3::from(*position))
+            .collect();
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..positions.len()).collect(),
+        };
+
+        // 囲む箱の中心を原点に移し、最も長い辺を揃える
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), position| (min.min(*position), max.max(*position)),
+        );
+        let center = (min + max) / 2.0;
+        let scale = MESH_HALF_SIZE / ((max - min).max_element() / 2.0).max(f32::EPSILON);
+
+        let triangles = indices
+            .chunks_exact(3)
+            .filter_map(|corners| {
+                let vertex = |index: usize| {
+                    positions
+                        .get(corners[index])
+                        .map(|position| (*position - center) * scale)
+                };
+                Some(Triangle3d::new(vertex(0)?, vertex(1)?, vertex(2)?))
+            })
+            .collect();
+        Self::new(triangles)
+    }
+
+    /// 表面積
+    pub fn area(&self) -> f32 {
+        self.cumulative_areas.last().copied().unwrap_or(0.0)
+    }
+
+    /// 体積
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// 表面の重心(三角形の重心を面積で重み付けした平均)
+    pub fn surface_centroid(&self) -> Vec3 {
+        let weighted = self
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.vertices;
+                triangle.area() * (a + b + c) / 3.0
+            })
+            .sum::<Vec3>();
+        weighted / self.area()
+    }
+
+    /// 内部の重心(原点と各三角形でできる四面体の重心を、符号付き体積で重み付けした平均)
+    pub fn volume_centroid(&self) -> Vec3 {
+        let (weighted, volume) =
+            self.triangles
+                .iter()
+                .fold((Vec3::ZERO, 0.0), |(weighted, volume), triangle| {
+                    let [a, b, c] = triangle.vertices;
+                    let signed = a.dot(b.cross(c)) / 6.0;
+                    (weighted + signed * (a + b + c) / 4.0, volume + signed)
+                });
+        if volume == 0.0 {
+            return self.surface_centroid(); // 閉じていないメッシュ
+        }
+        weighted / volume
+    }
+
+    /// 点がメッシュの内側にあるか(レイと表面の交点の数が奇数なら内側)
+    pub fn contains(&self, point: Vec3) -> bool {
+        let direction = INSIDE_RAY.normalize();
+        let crossings = self
+            .triangles
+            .iter()
+            .filter(|triangle| ray_hits_triangle(point, direction, triangle))
+            .count();
+        crossings % 2 == 1
+    }
+
+    /// 表面の点を、三角形の面積に比例した確率で一様に選ぶ
+    pub fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        let target = rng.gen_range(0.0..self.area());
+        let index = self
+            .cumulative_areas
+            .partition_point(|area| *area <= target)
+            .min(self.triangles.len() - 1);
+        self.triangles[index].sample_interior(rng)
+    }
+
+    /// 内部の点を、囲む箱の中から棄却法で選ぶ
+    pub fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        (0..INTERIOR_ATTEMPTS)
+            .map(|_| {
+                let unit = Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen());
+                (unit * 2.0 - Vec3::ONE) * self.half_extents
+            })
+            .find(|point| self.contains(*point))
+            .unwrap_or_else(|| self.sample_boundary(rng))
+    }
+
+    /// 表示用のMesh(三角形ごとに頂点を分け、面の向きの法線を付ける)
+    pub fn to_mesh(&self) -> Mesh {
+        let positions: Vec<[f32; 3]> = self
+            .triangles
+            .iter()
+            .flat_map(|triangle| triangle.vertices.map(|vertex| vertex.to_array()))
+            .collect();
+        let normals: Vec<[f32; 3]> = self
+            .triangles
+            .iter()
+            .flat_map(|triangle| {
+                let normal = triangle.normal().map_or(Vec3::Y, |normal| *normal);
+                [normal.to_array(); 3]
+            })
+            .collect();
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32((0..self.triangles.len() as u32 * 3).collect()))
+    }
+}
+
+/// モンテカルロ法での推定用に、表面までの距離(内側は負)を返す
+impl SignedDistance for TriangleMesh {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let distance = self
+            .triangles
+            .iter()
+            .map(|triangle| point.distance(closest_point_on_triangle(point, triangle)))
+            .fold(f32::INFINITY, f32::min);
+        if self.contains(point) {
+            -distance
+        } else {
+            distance
+        }
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        self.half_extents
+    }
+}
+
+/// 半直線が三角形と交わるか(Möller–Trumboreの方法)
+fn ray_hits_triangle(origin: Vec3, direction: Vec3, triangle: &Triangle3d) -> bool {
+    let [a, b, c] = triangle.vertices;
+    let (edge1, edge2) = (b - a, c - a);
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return false; // レイが三角形と平行
+    }
+    let inverse = 1.0 / determinant;
+    let to_origin = origin - a;
+    let u = to_origin.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+    let q = to_origin.cross(edge1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+    edge2.dot(q) * inverse > 0.0 // 交点がレイの前方にあるか
+}
+
+/// 三角形の上で点に最も近い位置(頂点・辺・面のどこが最も近いかで場合分けする)
+fn closest_point_on_triangle(point: Vec3, triangle: &Triangle3d) -> Vec3 {
+    let [a, b, c] = triangle.vertices;
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+/// 読み込むglTFのパスを保持するリソース(Noneなら読み込まない)
+/// mainでコマンドライン引数から挿入する。挿入されなければ読み込まない(テストで図形の数が変わらない)
+#[derive(Resource, Debug, Clone, PartialEq, Default)]
+pub struct CustomMeshSource {
+    pub path: Option<String>, // `assets/`からの相対パス
+}
+
+impl CustomMeshSource {
+    /// コマンドライン引数から読み込むglTFを決める
+    /// `--mesh <パス>`で別のglTFを、`--no-mesh`で読み込まないことを指定する(既定は`DEFAULT_MESH_PATH`)
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut source = Self {
+            path: Some(DEFAULT_MESH_PATH.to_owned()),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mesh" => {
+                    source.path = Some(
+                        args.next()
+                            .expect("--mesh にはglTFのパスを指定してください"),
+                    );
+                }
+                "--no-mesh" => source.path = None,
+                _ => {} // 他の引数(--seedなど)は無視する
+            }
+        }
+        source
+    }
+}
+
+/// 読み込み中・読み込んだメッシュを保持するリソース
+#[derive(Resource, Default)]
+pub(crate) struct CustomMesh {
+    handle: Option<Handle<Mesh>>,         // 読み込み中のglTFのメッシュ
+    pub shape: Option<Arc<TriangleMesh>>, // 読み込んで三角形の一覧にしたメッシュ
+}
+
+// 起動時にglTFの最初のメッシュの読み込みを始めるシステム
+pub(crate) fn load_custom_mesh(
+    source: Res<CustomMeshSource>,
+    asset_server: Res<AssetServer>,
+    mut custom_mesh: ResMut<CustomMesh>,
+) {
+    if let Some(path) = &source.path {
+        custom_mesh.handle = Some(asset_server.load(format!("{path}#Mesh0/Primitive0")));
+    }
+}
+
+// 読み込みが終わったら三角形の一覧にして、3Dの図形の右端に加えるシステム
+pub(crate) fn add_custom_mesh(
+    mut commands: Commands,
+    mut custom_mesh: ResMut<CustomMesh>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut shapes: ResMut<SampledShapes>,
+    view: Res<ViewMode>,
+    shape_material: Res<ShapeMaterial>,
+    config: Res<DemoConfig>,
+    layout: Res<LayoutMode>,
+) {
+    let Some(handle) = custom_mesh.handle.clone() else {
+        return;
+    };
+    if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&handle) {
+        error!("glTFのメッシュを読み込めません: {err}");
+        custom_mesh.handle = None;
+        return;
+    }
+    let Some(mesh) = meshes.get(&handle) else {
+        return; // 読み込み中
+    };
+
+    custom_mesh.handle = None;
+    let Some(triangles) = TriangleMesh::from_mesh(mesh) else {
+        error!("glTFのメッシュから三角形を取り出せません");
+        return;
+    };
+    info!(
+        "glTFのメッシュを読み込みました(三角形{}個)",
+        triangles.triangles.len()
+    );
+    let triangles = Arc::new(triangles);
+    custom_mesh.shape = Some(triangles.clone());
+
+    // 2Dの表示中なら、3Dに戻したときにswitch_viewで加える
+    if *view == ViewMode::ThreeD {
+        // 横一列以外では他の図形の位置も変わるので、ShapeLayoutPluginが図形を動かす
+        let (shape, translation) = shapes.push(Shape::CustomMesh(triangles), *layout, *view);
+        spawn_shape_visual(
+            &mut commands,
+            &mut meshes,
+            &shape_material.0,
+            shapes.0.len() - 1,
+            &shape,
+            translation,
+            config.point_colors.interior,
+        );
+    }
+}
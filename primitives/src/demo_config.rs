@@ -0,0 +1,121 @@
+//! `assets/config/primitives.config.ron`から読み込むデモの設定
+//!
+//! ポイントの最大数・1フレームの生成数・アニメーション時間・ポイントの色(とパレットの切り替え方)・カメラの距離の範囲・
+//! 図形の並べ方・カメラの自動周回の角速度・図形の中心のライトの光り方を`DemoConfig`リソースにまとめる。読み込みと実行中の再読み込みは`sample_common::ConfigPlugin`が行う。
+//! 最大数・生成数・アニメーション時間は操作パネルでも変えられるので、ファイルで値が変わった
+//! フィールドだけを`SamplingParams`と`SpawnRate`に反映する(パネルで変えた他の値はそのまま残す)。
+//! 並べ方も「F」キーで変えられるので、ファイルで値が変わったときだけ`LayoutMode`に反映する。
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::firefly_lighting::FireflyConfig;
+use crate::layout::LayoutMode;
+use crate::{SamplingMode, SamplingParams, SpawnRate};
+
+/// 設定ファイルのパス(`assets/`からの相対パス)
+pub const CONFIG_PATH: &str = "config/primitives.config.ron";
+
+/// サンプリングモードごとのポイントの色(HDRなので1を超えてよい)
+#[derive(Reflect, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct PointColors {
+    pub interior: LinearRgba,     // 内部に表示するポイントの色
+    pub boundary: LinearRgba,     // 境界(表面)に表示するポイントの色
+    pub poisson_disk: LinearRgba, // ポアソンディスクサンプリングで表示するポイントの色
+}
+
+impl Default for PointColors {
+    fn default() -> Self {
+        Self {
+            interior: LinearRgba::rgb(0.855, 1.1, 0.01),
+            boundary: LinearRgba::rgb(0.08, 0.2, 0.90),
+            poisson_disk: LinearRgba::rgb(1.0, 0.35, 0.9),
+        }
+    }
+}
+
+impl PointColors {
+    /// サンプリングモードに応じた色
+    pub fn for_mode(&self, mode: SamplingMode) -> LinearRgba {
+        match mode {
+            SamplingMode::Interior => self.interior,
+            SamplingMode::Boundary => self.boundary,
+            SamplingMode::PoissonDisk => self.poisson_disk,
+        }
+    }
+}
+
+/// `assets/config/primitives.config.ron`から読み込む設定
+/// 実行中にファイルを保存すると、すぐに反映される
+#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct DemoConfig {
+    pub max_points: usize,             // ポイントの最大数の初期値(重いときは減らす)
+    pub points_per_frame: usize,       // 1フレームあたりに生成するポイント数の初期値
+    pub animation_time: f32,           // 生成・削除アニメーションの時間(秒)の初期値
+    pub point_colors: PointColors,     // サンプリングモードごとのポイントの色
+    pub recolor_existing_points: bool, // パレット(Pキー)を切り替えたとき、既存のポイントの色も変えるか
+    pub min_camera_distance: f32,      // カメラと注視点の最小距離(図形に被らない距離)
+    pub max_camera_distance: f32,      // カメラと注視点の最大距離(全体が収まる距離)
+    pub layout: LayoutMode,            // 図形の並べ方の初期値(横一列・格子・円周)
+    pub orbit_speed: f32,              // Xキーでカメラが自動で周回する角速度(ラジアン/秒)
+    pub clear_points_on_scale: bool,   // 7・8キーで図形の大きさを変えたとき、ポイントを消すか
+    pub firefly: FireflyConfig,        // 図形の中心のライトの光り方
+}
+
+impl Default for DemoConfig {
+    fn default() -> Self {
+        Self {
+            max_points: 3000,
+            points_per_frame: 3,
+            animation_time: 1.0,
+            point_colors: PointColors::default(),
+            recolor_existing_points: true,
+            min_camera_distance: 1.0,
+            max_camera_distance: 16.0,
+            layout: LayoutMode::Row,
+            orbit_speed: 0.3,
+            clear_points_on_scale: true,
+            firefly: FireflyConfig::default(),
+        }
+    }
+}
+
+impl DemoConfig {
+    /// カメラの距離を設定の範囲に収める(範囲が逆になっていても落ちないようにする)
+    pub fn clamp_camera_distance(&self, distance: f32) -> f32 {
+        let min = self.min_camera_distance.min(self.max_camera_distance);
+        distance.clamp(min, self.max_camera_distance.max(min))
+    }
+
+    /// カメラの距離が範囲のどこにあるか(最小距離で0、最大距離で1)
+    pub fn camera_distance_ratio(&self, distance: f32) -> f32 {
+        let range = (self.max_camera_distance - self.min_camera_distance).max(f32::EPSILON);
+        ((distance - self.min_camera_distance) / range).clamp(0.0, 1.0)
+    }
+}
+
+// 設定ファイルで値が変わったフィールドだけを、操作パネルの値(SamplingParamsとSpawnRate)に反映するシステム
+// `applied`は最後に反映した設定(最初はデフォルト値で、SamplingParamsのデフォルト値と同じ)
+pub(crate) fn apply_demo_config(
+    config: Res<DemoConfig>,
+    mut params: ResMut<SamplingParams>,
+    mut rate: ResMut<SpawnRate>,
+    mut layout: ResMut<LayoutMode>,
+    mut applied: Local<DemoConfig>,
+) {
+    if config.max_points != applied.max_points {
+        params.max_points = config.max_points;
+    }
+    if config.points_per_frame != applied.points_per_frame {
+        rate.points_per_frame = config.points_per_frame;
+    }
+    if config.animation_time != applied.animation_time {
+        params.animation_time = config.animation_time;
+    }
+    if config.layout != applied.layout {
+        *layout = config.layout;
+    }
+    *applied = config.clone();
+}
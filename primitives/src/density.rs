@@ -0,0 +1,260 @@
+//! ポイントの密度によるヒートマップの色分け
+//!
+//! 各ポイントについて、半径`radius`以内にある他のポイントの数(近傍の数)を数え、
+//! その数をグラデーションの色にする。サンプリングが一様なら、図形の中でほぼ同じ色になる。
+//! ポイントは生成されてから動かないので、近傍の探索には共通の空間ハッシュ(`PointSpatialIndex`)を使い、
+//! 生成・削除されたポイントの近くだけ近傍の数を増減させる(毎フレームすべてを数え直さない)。
+//! 「H」キーか操作パネルで有効にすると、インスタンス描画の色がサンプリングモードの色から切り替わる。
+
+use std::collections::HashMap;
+
+use bevy::color::Mix;
+use bevy::prelude::*;
+
+use crate::spatial_index::PointSpatialIndex;
+use crate::{SampleAction, SamplePoint};
+
+/// 近傍とみなす距離の初期値(操作パネルで変えられる)
+pub const DENSITY_RADIUS: f32 = 0.15;
+
+/// ヒートマップの色の明るさ(ブルームで光るようにHDRの値にする)
+const DENSITY_EMISSIVE: f32 = 2.0;
+
+/// ヒートマップのグラデーション
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gradient {
+    Heat,      // 黒 → 赤 → 黄 → 白
+    Viridis,   // 紫 → 青緑 → 黄(色覚の違いがあっても見分けやすい)
+    Grayscale, // 黒 → 白
+}
+
+impl Gradient {
+    /// 操作パネルで選べるグラデーション
+    pub const ALL: [Gradient; 3] = [Gradient::Heat, Gradient::Viridis, Gradient::Grayscale];
+
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Gradient::Heat => "density.heat",
+            Gradient::Viridis => "density.viridis",
+            Gradient::Grayscale => "density.grayscale",
+        }
+    }
+
+    /// グラデーションの色(等間隔に並べ、間は線形に補間する)
+    fn stops(self) -> &'static [LinearRgba] {
+        const HEAT: [LinearRgba; 4] = [
+            LinearRgba::rgb(0.02, 0.0, 0.0),
+            LinearRgba::rgb(0.8, 0.02, 0.0),
+            LinearRgba::rgb(1.0, 0.7, 0.0),
+            LinearRgba::rgb(1.0, 1.0, 1.0),
+        ];
+        const VIRIDIS: [LinearRgba; 5] = [
+            LinearRgba::rgb(0.07, 0.0, 0.09),
+            LinearRgba::rgb(0.05, 0.06, 0.24),
+            LinearRgba::rgb(0.02, 0.23, 0.21),
+            LinearRgba::rgb(0.14, 0.52, 0.05),
+            LinearRgba::rgb(0.96, 0.83, 0.01),
+        ];
+        const GRAYSCALE: [LinearRgba; 2] = [LinearRgba::BLACK, LinearRgba::WHITE];
+        match self {
+            Gradient::Heat => &HEAT,
+            Gradient::Viridis => &VIRIDIS,
+            Gradient::Grayscale => &GRAYSCALE,
+        }
+    }
+
+    /// 0〜1の値に対応する色を返す(範囲外は端の色にする)
+    pub fn sample(self, t: f32) -> LinearRgba {
+        let stops = self.stops();
+        let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        stops[index].mix(&stops[index + 1], position - index as f32)
+    }
+}
+
+/// 近傍の数を数えたポイント
+#[derive(Debug, Clone, Copy)]
+struct CountedPoint {
+    position: Vec3, // ポイントの位置
+    neighbors: u32, // 半径以内にある他のポイントの数
+}
+
+/// ポイントごとの近傍の数を増減で管理する
+/// 近傍の探索には、すべてのポイントを入れた空間ハッシュ(`PointSpatialIndex`)を使う
+#[derive(Debug, Default)]
+pub struct DensityIndex {
+    radius: f32,                           // 近傍とみなす距離
+    points: HashMap<Entity, CountedPoint>, // ポイントごとの位置と近傍の数
+}
+
+impl DensityIndex {
+    /// 空の一覧を作る(`radius`は0より大きくする)
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius: radius.max(f32::EPSILON),
+            ..default()
+        }
+    }
+
+    /// 近傍とみなす距離
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// ポイントの数
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// ポイントが1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// ポイントの近傍の数(入っていないポイントはNone)
+    pub fn neighbors(&self, entity: Entity) -> Option<u32> {
+        self.points.get(&entity).map(|point| point.neighbors)
+    }
+
+    /// すべてのポイントの近傍の数の最大値
+    pub fn max_neighbors(&self) -> u32 {
+        self.points
+            .values()
+            .map(|point| point.neighbors)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 半径以内にある、この一覧に入っている他のポイント
+    /// 空間ハッシュにだけ入っているポイントは、後から`insert`したときに数える
+    fn neighbors_of(
+        &self,
+        entity: Entity,
+        position: Vec3,
+        index: &PointSpatialIndex,
+    ) -> Vec<Entity> {
+        index
+            .within_radius(position, self.radius)
+            .into_iter()
+            .map(|(other, _)| other)
+            .filter(|other| *other != entity && self.points.contains_key(other))
+            .collect()
+    }
+
+    /// ポイントを追加し、近くのポイントの近傍の数を1つずつ増やす
+    /// ポイントは先に`index`に入れておく
+    pub fn insert(&mut self, entity: Entity, position: Vec3, index: &PointSpatialIndex) {
+        if self.points.contains_key(&entity) {
+            return;
+        }
+        let neighbors = self.neighbors_of(entity, position, index);
+        for neighbor in &neighbors {
+            if let Some(point) = self.points.get_mut(neighbor) {
+                point.neighbors += 1;
+            }
+        }
+        self.points.insert(
+            entity,
+            CountedPoint {
+                position,
+                neighbors: neighbors.len() as u32,
+            },
+        );
+    }
+
+    /// ポイントを取り除き、近くのポイントの近傍の数を1つずつ減らす
+    pub fn remove(&mut self, entity: Entity, index: &PointSpatialIndex) {
+        let Some(point) = self.points.remove(&entity) else {
+            return;
+        };
+        for neighbor in self.neighbors_of(entity, point.position, index) {
+            if let Some(point) = self.points.get_mut(&neighbor) {
+                point.neighbors = point.neighbors.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// 密度による色分けの設定と、近傍の数を保持するリソース
+#[derive(Resource)]
+pub struct DensityColoring {
+    pub enabled: bool,           // 密度で色分けするかどうか
+    pub radius: f32,             // 近傍とみなす距離
+    pub gradient: Gradient,      // 色分けに使うグラデーション
+    index: Option<DensityIndex>, // ポイントごとの近傍の数(有効なときだけ作る)
+}
+
+impl Default for DensityColoring {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: DENSITY_RADIUS,
+            gradient: Gradient::Heat,
+            index: None,
+        }
+    }
+}
+
+impl DensityColoring {
+    /// 有効なら、ポイントごとの近傍の数を返す
+    pub fn index(&self) -> Option<&DensityIndex> {
+        self.index.as_ref().filter(|_| self.enabled)
+    }
+
+    /// 近傍の数の最大値を1とした割合から、ポイントの色を決める
+    pub fn color(&self, neighbors: u32, max_neighbors: u32) -> LinearRgba {
+        let t = neighbors as f32 / max_neighbors.max(1) as f32;
+        DENSITY_EMISSIVE * self.gradient.sample(t)
+    }
+}
+
+// 「H」キー：密度による色分けの有効・無効を切り替えるシステム
+pub(crate) fn toggle_density(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut density: ResMut<DensityColoring>,
+) {
+    if actions.just_pressed(SampleAction::ToggleDensity) {
+        density.enabled = !density.enabled;
+    }
+}
+
+// 生成・削除されたポイントの近傍の数を増減するシステム(空間ハッシュを更新した後に実行する)
+// 有効にしたときと半径を変えたときは、すべてのポイントから数え直す
+pub(crate) fn update_density_index(
+    mut density: ResMut<DensityColoring>,
+    points: Res<PointSpatialIndex>,
+    added: Query<Entity, Added<SamplePoint>>,
+    mut removed: RemovedComponents<SamplePoint>,
+) {
+    if !density.enabled {
+        removed.clear();
+        if density.index.is_some() {
+            density.index = None; // 無効の間は近傍の数を更新しない
+        }
+        return;
+    }
+
+    let radius = density.radius;
+    let DensityColoring { index, .. } = &mut *density;
+    match index {
+        Some(index) if index.radius() == radius.max(f32::EPSILON) => {
+            for entity in removed.read() {
+                index.remove(entity, &points);
+            }
+            for entity in &added {
+                if let Some(position) = points.position(entity) {
+                    index.insert(entity, position, &points);
+                }
+            }
+        }
+        _ => {
+            removed.clear();
+            let mut rebuilt = DensityIndex::new(radius);
+            for (entity, position) in points.iter() {
+                rebuilt.insert(entity, position, &points);
+            }
+            *index = Some(rebuilt);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! FPS・ポイントの数・生成キューの残りを表示するオーバーレイ
+//!
+//! F3キーで表示・非表示を切り替え、画面の右下にFPS・エンティティの数・`PointCounter`・
+//! `SpawnQueue`に残っている数と、サンプリング・生成のモードを表示する。
+//! FPSは`PointBudgetPlugin`が追加する`FrameTimeDiagnosticsPlugin`の平滑化した値を使う。
+//! 数字が毎フレーム変わると読みにくいので、表示中だけ`UPDATE_INTERVAL`秒ごとに内容を更新する。
+//! ポストプロセスの設定パネル(sample_commonの既定ではF3キー)は、このサンプルではF6キーで開く。
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use sample_common::LocalizedText;
+
+use crate::{PointCounter, SampleAction, SamplingMode, SpawnQueue, SpawningMode};
+
+/// 表示中に内容を更新する間隔(秒)
+const UPDATE_INTERVAL: f32 = 0.25;
+
+/// 診断情報のオーバーレイを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsOverlay>()
+            .add_systems(Startup, spawn_overlay)
+            .add_systems(Update, (toggle_overlay, update_overlay).chain());
+    }
+}
+
+/// オーバーレイを表示しているかを保持するリソース
+#[derive(Resource, Default, Debug)]
+pub struct DiagnosticsOverlay {
+    pub visible: bool, // 表示しているか(初期は非表示)
+}
+
+/// オーバーレイのマーカーコンポーネント
+#[derive(Component)]
+struct OverlayPanel;
+
+// オーバーレイを作るシステム(最初は表示しない)
+// Tabキーのヘルプの切り替えはTextのVisibilityを変えるので、表示・非表示はNodeのdisplayで切り替える
+fn spawn_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            OverlayPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                bottom: Val::Px(12.0),
+                right: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                LocalizedText::new("diagnostics.overlay"),
+                TextFont::from_font_size(14.0),
+            ));
+        });
+}
+
+// 「F3」キー：オーバーレイの表示・非表示を切り替えるシステム
+fn toggle_overlay(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut overlay: ResMut<DiagnosticsOverlay>,
+) {
+    if actions.just_pressed(SampleAction::ToggleDiagnostics) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+// 表示・非表示をNodeに反映し、表示中は一定間隔で内容を更新するシステム
+fn update_overlay(
+    overlay: Res<DiagnosticsOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    counter: Res<PointCounter>,
+    queue: Res<SpawnQueue>,
+    sampling: Res<SamplingMode>,
+    spawning: Res<SpawningMode>,
+    time: Res<Time<Real>>,
+    mut elapsed: Local<f32>,
+    mut panels: Query<(&mut Node, &Children), With<OverlayPanel>>,
+    mut texts: Query<&mut LocalizedText>,
+) {
+    let Ok((mut node, children)) = panels.single_mut() else {
+        return;
+    };
+    let display = if overlay.visible {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if node.display != display {
+        node.display = display;
+        *elapsed = UPDATE_INTERVAL; // 表示したらすぐに更新する
+    }
+    if !overlay.visible {
+        return;
+    }
+    *elapsed += time.delta_secs();
+    if *elapsed < UPDATE_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .map_or("-".to_string(), |fps| format!("{fps:.0}"));
+    let text = LocalizedText::new("diagnostics.overlay")
+        .with_arg("fps", fps)
+        .with_arg("entities", entities.len())
+        .with_arg("points", counter.0)
+        .with_arg("queue", queue.0)
+        .with_key_arg("sampling", sampling.name_key())
+        .with_key_arg("spawning", spawning.name_key());
+    for child in children {
+        if let Ok(mut localized) = texts.get_mut(*child) {
+            localized.set_if_neq(text.clone());
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! ポイントの生成・削除アニメーションのイージング
+//!
+//! 生成アニメーションでは進行度(0〜1)をイージング関数に通した値をスケールにし、
+//! 削除アニメーションでは進行度を逆にたどって、削除を始めたときの大きさから0まで縮める。
+//! 関数はBevyの`EaseFunction`を使い、線形・三次・バック(少し大きくなってから戻る)・
+//! エラスティック(ばねのように揺れて止まる)から「B」キーか操作パネルで選ぶ。
+
+use bevy::prelude::*;
+
+use crate::SampleAction;
+
+/// 生成・削除アニメーションで使うイージング関数
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AnimationEasing {
+    #[default]
+    Linear, // 一定の速さで大きくなる(以前と同じ)
+    Cubic,   // 最初に速く、最後はゆっくり止まる
+    Back,    // 少し大きくなりすぎてから元の大きさに戻る
+    Elastic, // ばねのように揺れながら元の大きさに落ち着く
+}
+
+impl AnimationEasing {
+    /// 「B」キーで切り替える順番(操作パネルの一覧もこの順)
+    pub const ALL: [AnimationEasing; 4] = [
+        AnimationEasing::Linear,
+        AnimationEasing::Cubic,
+        AnimationEasing::Back,
+        AnimationEasing::Elastic,
+    ];
+
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            AnimationEasing::Linear => "easing.linear",
+            AnimationEasing::Cubic => "easing.cubic",
+            AnimationEasing::Back => "easing.back",
+            AnimationEasing::Elastic => "easing.elastic",
+        }
+    }
+
+    /// 次のイージング関数(最後の次は最初に戻る)
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|easing| *easing == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 対応するBevyのイージング関数
+    pub fn function(self) -> EaseFunction {
+        match self {
+            AnimationEasing::Linear => EaseFunction::Linear,
+            AnimationEasing::Cubic => EaseFunction::CubicOut,
+            AnimationEasing::Back => EaseFunction::BackOut,
+            AnimationEasing::Elastic => EaseFunction::ElasticOut,
+        }
+    }
+
+    /// 進行度`t`(範囲外は0〜1にする)に対応するスケール(0から始まり1で終わる)
+    pub fn sample(self, t: f32) -> f32 {
+        self.function().sample_clamped(t)
+    }
+}
+
+// 「B」キー：アニメーションのイージング関数を切り替えるシステム
+pub(crate) fn cycle_easing(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut easing: ResMut<AnimationEasing>,
+) {
+    if actions.just_pressed(SampleAction::CycleEasing) {
+        *easing = easing.next();
+        info!("アニメーションのイージングを{:?}にしました", *easing);
+    }
+}
@@ -0,0 +1,159 @@
+//! サンプリングしたポイントの書き出し(PLY・CSV)
+//!
+//! 「E」キーで、その時点のすべてのポイントの位置と、どのモードでサンプリングしたかを
+//! `exports/primitives/`にPLY(MeshLabなどの点群ツール用)とCSVの2つの形式で保存する。
+//! Webではファイルシステムがないので、書き出せない旨をログに出すだけにする。
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::demo_config::{DemoConfig, PointColors};
+use crate::palette::PointPalette;
+use crate::{SampleAction, SamplePoint, SamplingMode};
+
+/// 保存先のディレクトリ(実行時のカレントディレクトリからの相対パス)
+const EXPORT_DIR: &str = "exports/primitives";
+
+/// 書き出す1つのポイント
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportedPoint {
+    pub position: Vec3,     // ワールド座標
+    pub mode: SamplingMode, // どのモードでサンプリングしたか
+}
+
+/// ポイントをPLY(ASCII)形式で書き出す。色は画面上のポイントの色(`colors`)に合わせる
+pub fn write_ply(
+    points: &[ExportedPoint],
+    colors: &PointColors,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "ply")?;
+    writeln!(out, "format ascii 1.0")?;
+    writeln!(out, "comment sampled points of the primitives demo")?;
+    writeln!(out, "element vertex {}", points.len())?;
+    for property in ["float x", "float y", "float z"] {
+        writeln!(out, "property {property}")?;
+    }
+    for property in ["uchar red", "uchar green", "uchar blue", "uchar boundary"] {
+        writeln!(out, "property {property}")?;
+    }
+    writeln!(out, "end_header")?;
+    for point in points {
+        let color = Srgba::from(point_color(colors, point.mode));
+        let [red, green, blue] =
+            [color.red, color.green, color.blue].map(|value| (value * 255.0).round() as u8);
+        writeln!(
+            out,
+            "{} {} {} {red} {green} {blue} {}",
+            point.position.x,
+            point.position.y,
+            point.position.z,
+            (point.mode == SamplingMode::Boundary) as u8
+        )?;
+    }
+    Ok(())
+}
+
+/// ポイントをCSV形式(x,y,z,kind)で書き出す
+pub fn write_csv(points: &[ExportedPoint], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "x,y,z,kind")?;
+    for point in points {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            point.position.x,
+            point.position.y,
+            point.position.z,
+            kind_name(point.mode)
+        )?;
+    }
+    Ok(())
+}
+
+/// CSVに書くサンプリングモードの名前
+pub fn kind_name(mode: SamplingMode) -> &'static str {
+    match mode {
+        SamplingMode::Interior => "interior",
+        SamplingMode::Boundary => "boundary",
+        SamplingMode::PoissonDisk => "poisson_disk",
+    }
+}
+
+/// CSVのサンプリングモードの名前を読む(`kind_name`の逆)
+pub fn parse_kind(name: &str) -> Option<SamplingMode> {
+    [
+        SamplingMode::Interior,
+        SamplingMode::Boundary,
+        SamplingMode::PoissonDisk,
+    ]
+    .into_iter()
+    .find(|mode| kind_name(*mode) == name)
+}
+
+/// 画面上のポイントの色(HDRの値は0〜1に収める)
+fn point_color(colors: &PointColors, mode: SamplingMode) -> LinearRgba {
+    let color = colors.for_mode(mode);
+    LinearRgba::rgb(color.red.min(1.0), color.green.min(1.0), color.blue.min(1.0))
+}
+
+/// 指定したディレクトリに、同じ名前のPLYとCSVのファイルを作って書き出し、そのパスを返す
+pub fn export_points(
+    dir: &Path,
+    name: &str,
+    points: &[ExportedPoint],
+    colors: &PointColors,
+) -> io::Result<[PathBuf; 2]> {
+    std::fs::create_dir_all(dir)?;
+    let ply = dir.join(format!("{name}.ply"));
+    let csv = dir.join(format!("{name}.csv"));
+    let mut file = io::BufWriter::new(std::fs::File::create(&ply)?);
+    write_ply(points, colors, &mut file)?;
+    file.flush()?;
+    let mut file = io::BufWriter::new(std::fs::File::create(&csv)?);
+    write_csv(points, &mut file)?;
+    file.flush()?;
+    Ok([ply, csv])
+}
+
+// 「E」キー：すべてのポイントをPLYとCSVに書き出すシステム
+pub(crate) fn export_on_key(
+    actions: Res<ButtonInput<SampleAction>>,
+    samples: Query<(&GlobalTransform, &SamplingMode), With<SamplePoint>>,
+    config: Res<DemoConfig>,
+    palette: Res<PointPalette>,
+) {
+    if !actions.just_pressed(SampleAction::ExportPoints) {
+        return;
+    }
+
+    if cfg!(target_arch = "wasm32") {
+        warn!("Webではポイントを書き出せません");
+        return;
+    }
+
+    let points: Vec<ExportedPoint> = samples
+        .iter()
+        .map(|(transform, mode)| ExportedPoint {
+            position: transform.translation(), // 図形の回転を含めたワールド座標
+            mode: *mode,
+        })
+        .collect();
+
+    // ファイル名は書き出した時刻(UNIX時間のミリ秒)にする
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    let name = format!("points-{millis}");
+    let colors = palette.colors(&config.point_colors); // 画面と同じパレットの色で書き出す
+    match export_points(Path::new(EXPORT_DIR), &name, &points, &colors) {
+        Ok([ply, csv]) => info!(
+            "{}個のポイントを書き出しました: {} / {}",
+            points.len(),
+            ply.display(),
+            csv.display()
+        ),
+        Err(err) => error!("ポイントを書き出せません: {err}"),
+    }
+}
@@ -0,0 +1,129 @@
+//! 図形の中心に置く蛍のようなライト
+//!
+//! 図形ごとに`PointLight`を1つ置き、ポイントが増えるほど明るく光らせる。
+//! 明るさはポイントの数と最大数(品質とフレーム時間の倍率をかけたもの)の比で決め、毎フレーム少しずつ近づける。
+//! 最大数のときの強度・最大数を超えたときに何倍まで明るくするか・近づける割合・ライトの色をパレットのどの色から取るかは
+//! 設定ファイルの`firefly`(`FireflyConfig`)で変えられるので、定数を書き換えずに光り方を調整できる。
+//! ライトは図形のエンティティの子なので、図形と一緒に動き、表示を切り替えると一緒に消える。
+
+use bevy::prelude::*;
+use sample_common::QualityLevel;
+use serde::Deserialize;
+
+use crate::demo_config::{DemoConfig, PointColors};
+use crate::palette::PointPalette;
+use crate::point_budget::PointBudget;
+use crate::{PointCounter, SamplingMode, SamplingParams};
+
+/// ライトの届く範囲(図形の大きさの倍率をかける)
+pub const LIGHT_RANGE: f32 = 4.0;
+
+/// ライトの光源の半径(図形の大きさの倍率をかける)
+pub const LIGHT_RADIUS: f32 = 0.6;
+
+/// 蛍のようなライトを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct FireflyLightingPlugin;
+
+impl Plugin for FireflyLightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_lights);
+    }
+}
+
+/// ライトの色をパレットのどの色から取るか
+#[derive(Reflect, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LightColorSource {
+    #[default]
+    Interior, // 内部のポイントの色
+    Boundary,     // 境界のポイントの色
+    PoissonDisk,  // ポアソンディスクのポイントの色
+    SamplingMode, // 今のサンプリングモード(Mキー)のポイントの色
+}
+
+/// ライトの光り方の設定(`DemoConfig::firefly`)
+#[derive(Reflect, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct FireflyConfig {
+    pub base_intensity: f32,     // ポイントの数が最大数のときの強度(ルーメン)
+    pub max_boost: f32,          // 最大数を超えたとき、最大数のときの何倍まで明るくするか
+    pub lerp_rate: f32,          // 1フレームで目標の強度に近づける割合(0〜1、1ならすぐに変わる)
+    pub color: LightColorSource, // ライトの色
+}
+
+impl Default for FireflyConfig {
+    fn default() -> Self {
+        Self {
+            base_intensity: 4_000.0,
+            max_boost: 2.0,
+            lerp_rate: 0.04,
+            color: LightColorSource::Interior,
+        }
+    }
+}
+
+impl FireflyConfig {
+    /// ポイントの数と最大数から決める目標の強度
+    pub fn target_intensity(&self, points: usize, limit: usize) -> f32 {
+        let saturation = (points as f32 / limit.max(1) as f32).min(self.max_boost);
+        self.base_intensity * saturation
+    }
+
+    /// パレットの色(`colors`)と今のサンプリングモードから決めるライトの色
+    pub fn light_color(&self, colors: &PointColors, mode: SamplingMode) -> LinearRgba {
+        match self.color {
+            LightColorSource::Interior => colors.interior,
+            LightColorSource::Boundary => colors.boundary,
+            LightColorSource::PoissonDisk => colors.poisson_disk,
+            LightColorSource::SamplingMode => colors.for_mode(mode),
+        }
+    }
+}
+
+/// ライトに付けるマーカーコンポーネント(図形のエンティティの子)
+#[derive(Component)]
+pub struct FireflyLights;
+
+/// 図形の中心に置くライト(`scale`は図形の大きさの倍率、明るさは`update_lights`で上げていく)
+pub fn firefly_light(color: LinearRgba, scale: f32) -> impl Bundle {
+    (
+        PointLight {
+            range: LIGHT_RANGE * scale, // 図形の大きさに合わせて届く範囲を変える
+            radius: LIGHT_RADIUS * scale,
+            intensity: 1.0,
+            shadows_enabled: false,
+            color: Color::LinearRgba(color),
+            ..default()
+        },
+        FireflyLights, // ライト調整用のマーカー
+    )
+}
+
+// ライトの明るさを現在のポイント数に応じて調整するシステム
+fn update_lights(
+    mut lights: Query<&mut PointLight, With<FireflyLights>>, // FireflyLightsを持つライトを取得
+    counter: Res<PointCounter>,                              // ポイント数管理リソース
+    quality: Res<QualityLevel>,                              // 描画品質
+    params: Res<SamplingParams>,                             // ポイントの最大数
+    budget: Res<PointBudget>,                                // フレーム時間に合わせた最大数の倍率
+    config: Res<DemoConfig>,                                 // 光り方の設定とポイントの色
+    palette: Res<PointPalette>,                              // ポイントの色のパレット
+    mode: Res<SamplingMode>,                                 // 今のサンプリングモード
+) {
+    let firefly = &config.firefly;
+    let limit = budget.point_limit(&params, *quality);
+    let intensity = firefly.target_intensity(counter.0, limit);
+    // 設定ファイルやパレット、サンプリングモードで色が変わったら、ライトの色も合わせる
+    let colors = palette.colors(&config.point_colors);
+    let color = Color::LinearRgba(firefly.light_color(&colors, *mode));
+
+    // 各ライトの明るさをなめらかに調整
+    for mut light in lights.iter_mut() {
+        // 現在の明るさから徐々に目標の明るさに近づける
+        light.intensity = light
+            .intensity
+            .lerp(intensity, firefly.lerp_rate.clamp(0.0, 1.0));
+        if light.color != color {
+            light.color = color;
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! 地面に落としたポイントの影(XZ平面への射影)
+//!
+//! 3Dのポイントを真下の地面に落とした「影」を、ポイントと一緒に描く。
+//! 影の並び方は、ポイントのXZ座標だけを見た分布(Y方向に足し合わせた周辺分布)になるので、
+//! 例えば球の内部では中心ほど濃く、円柱では一様になる。
+//! 影は`collect_point_instances`でポイントのインスタンスを地面の高さに移して暗くしたものなので、
+//! 大きさ(生成・削除のアニメーションのスケール)と色はポイントと同じになる。
+//! 「Z」キーで切り替える。2Dの図形はXY平面にあって地面への影が線になるので、3Dの表示のときだけ描く。
+
+use bevy::prelude::*;
+
+use crate::SampleAction;
+
+/// 影の明るさ(ポイントの色にかける)
+pub const SHADOW_BRIGHTNESS: f32 = 0.3;
+
+/// 影を地面から浮かせる高さ(地面と重なってちらつかないようにする)
+pub const SHADOW_LIFT: f32 = 0.01;
+
+/// 地面への影を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct GroundShadowPlugin;
+
+impl Plugin for GroundShadowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GroundShadows>()
+            .add_systems(Update, toggle_shadows.after(crate::handle_keypress));
+    }
+}
+
+/// 地面への影を描くかを保持するリソース
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GroundShadows {
+    pub enabled: bool, // ポイントの影を地面に描くか(初期は描かない)
+}
+
+// 「Z」キー：地面への影の表示を切り替えるシステム
+fn toggle_shadows(actions: Res<ButtonInput<SampleAction>>, mut shadows: ResMut<GroundShadows>) {
+    if actions.just_pressed(SampleAction::ToggleShadows) {
+        shadows.enabled = !shadows.enabled;
+    }
+}
@@ -0,0 +1,221 @@
+//! 注視している図形のポイントの、X・Y・Z軸ごとのヒストグラム
+//!
+//! カメラが注視している図形(矢印キーで切り替える)のポイントについて、図形の中心からの
+//! 各軸の座標を`HISTOGRAM_BINS`個の区間に分けて数え、画面の右上に棒グラフで表示する。
+//! 例えば直方体の内部を一様にサンプリングしていれば、どの軸も平らなグラフになり、
+//! 球なら中央が高い山型になる。棒はUIのNodeで、最も多い区間に合わせて高さを変えるだけにしている。
+//! 「G」キーで表示・非表示を切り替える。
+
+use bevy::prelude::*;
+use sample_common::LocalizedText;
+
+use crate::monte_carlo::SignedDistance;
+use crate::{CameraRig, SampleAction, SamplePoint, SampledShapes, ShapeIndex};
+
+/// 1つの軸を分ける区間の数
+pub const HISTOGRAM_BINS: usize = 24;
+
+/// 棒グラフの1本の幅(ピクセル)
+const BAR_WIDTH: f32 = 6.0;
+
+/// 棒グラフの高さ(ピクセル)
+const CHART_HEIGHT: f32 = 48.0;
+
+/// 軸の名前と棒の色(X: 赤, Y: 緑, Z: 青)
+const AXES: [(&str, Color); 3] = [
+    ("X", Color::srgb(0.9, 0.3, 0.3)),
+    ("Y", Color::srgb(0.3, 0.85, 0.4)),
+    ("Z", Color::srgb(0.35, 0.5, 1.0)),
+];
+
+/// `-half_extent`〜`half_extent`を`bins`個の区間に分けて、値の個数を数える
+/// 範囲外の値は端の区間に入れる
+pub fn axis_histogram(
+    values: impl Iterator<Item = f32>,
+    half_extent: f32,
+    bins: usize,
+) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    if bins == 0 || half_extent <= 0.0 {
+        return counts;
+    }
+    for value in values {
+        let fraction = (value + half_extent) / (2.0 * half_extent);
+        let bin = ((fraction * bins as f32).floor().max(0.0) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    counts
+}
+
+/// ヒストグラムの状態を保持するリソース
+#[derive(Resource, Default, PartialEq, Debug)]
+pub struct AxisHistogram {
+    pub enabled: bool,           // 表示するかどうか
+    pub shape: Option<usize>,    // 集計した図形(SampledShapesのインデックス)
+    pub counts: [Vec<usize>; 3], // X・Y・Z軸ごとの区間の個数(平面の図形ではZが空)
+}
+
+/// ヒストグラムのパネルのマーカーコンポーネント
+#[derive(Component)]
+struct HistogramPanel;
+
+/// ヒストグラムの見出しのマーカーコンポーネント
+#[derive(Component)]
+struct HistogramTitle;
+
+/// 1つの軸の行(平面の図形ではZの行を隠す)
+#[derive(Component)]
+struct HistogramRow(usize);
+
+/// 棒グラフの1本(軸と区間)
+#[derive(Component)]
+struct HistogramBar {
+    axis: usize,
+    bin: usize,
+}
+
+// ヒストグラムのパネルを作るシステム(最初は表示しない)
+pub(crate) fn spawn_histogram_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.0),
+                right: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            HistogramPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((LocalizedText::new("histogram.title"), HistogramTitle));
+            for (axis, (name, color)) in AXES.into_iter().enumerate() {
+                panel
+                    .spawn((
+                        Node {
+                            align_items: AlignItems::FlexEnd,
+                            column_gap: Val::Px(1.0),
+                            ..default()
+                        },
+                        HistogramRow(axis),
+                    ))
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(name),
+                            Node {
+                                width: Val::Px(16.0),
+                                ..default()
+                            },
+                        ));
+                        for bin in 0..HISTOGRAM_BINS {
+                            row.spawn((
+                                Node {
+                                    width: Val::Px(BAR_WIDTH),
+                                    height: Val::Px(0.0),
+                                    ..default()
+                                },
+                                BackgroundColor(color),
+                                HistogramBar { axis, bin },
+                            ));
+                        }
+                    });
+            }
+        });
+}
+
+// 「G」キー：ヒストグラムの表示・非表示を切り替えるシステム
+pub(crate) fn toggle_histogram(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut histogram: ResMut<AxisHistogram>,
+    mut panels: Query<&mut Visibility, With<HistogramPanel>>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleHistogram) {
+        return;
+    }
+
+    histogram.enabled = !histogram.enabled;
+    for mut visibility in panels.iter_mut() {
+        *visibility = if histogram.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// 注視している図形のポイントを集計し、棒グラフと見出しを更新するシステム
+pub(crate) fn update_histogram(
+    mut histogram: ResMut<AxisHistogram>,
+    shapes: Res<SampledShapes>,
+    camera_rig: Query<&CameraRig>,
+    points: Query<(&Transform, &ShapeIndex), With<SamplePoint>>,
+    mut titles: Query<&mut LocalizedText, With<HistogramTitle>>,
+    mut rows: Query<(&mut Node, &HistogramRow), Without<HistogramBar>>,
+    mut bars: Query<(&mut Node, &HistogramBar), Without<HistogramRow>>,
+) {
+    if !histogram.enabled {
+        return;
+    }
+    let Ok(camera_rig) = camera_rig.single() else {
+        return;
+    };
+    let index = shapes.closest(camera_rig.target);
+    let Some((shape, _)) = shapes.0.get(index) else {
+        return;
+    };
+
+    // 図形の中心からの座標(ポイントのTransform)を、図形を囲む箱の範囲で区間に分ける
+    // 図形の向きで見るので、図形が回転していてもヒストグラムは変わらない
+    let extents = shape.half_extents();
+    let positions: Vec<Vec3> = points
+        .iter()
+        .filter(|(_, shape_index)| shape_index.0 == index)
+        .map(|(transform, _)| transform.translation)
+        .collect();
+    let counts = [0, 1, 2].map(|axis| {
+        // 平面の図形のZ軸は集計しない
+        if extents[axis] <= 0.0 {
+            return Vec::new();
+        }
+        let values = positions.iter().map(|position| position[axis]);
+        axis_histogram(values, extents[axis], HISTOGRAM_BINS)
+    });
+    histogram.set_if_neq(AxisHistogram {
+        enabled: true,
+        shape: Some(index),
+        counts,
+    });
+
+    for mut title in titles.iter_mut() {
+        title.set_if_neq(
+            LocalizedText::new("histogram.title")
+                .with_key_arg("shape", shape.name_key())
+                .with_arg("count", positions.len()),
+        );
+    }
+    for (mut node, row) in rows.iter_mut() {
+        let display = if histogram.counts[row.0].is_empty() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+
+    // 最も多い区間を棒グラフの高さいっぱいにする
+    for (mut node, bar) in bars.iter_mut() {
+        let counts = &histogram.counts[bar.axis];
+        let max = counts.iter().copied().max().unwrap_or(0).max(1);
+        let count = counts.get(bar.bin).copied().unwrap_or(0);
+        let height = Val::Px(CHART_HEIGHT * count as f32 / max as f32);
+        if node.height != height {
+            node.height = height;
+        }
+    }
+}
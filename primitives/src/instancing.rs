@@ -0,0 +1,421 @@
+//! サンプリングしたポイントのインスタンス描画
+//!
+//! ポイントごとにMesh3dとマテリアルを付けると、エンティティごとの描画の準備が重く、
+//! 数千個が限界になる。そこでポイントのエンティティは位置とスケールだけを持ち、
+//! 描画は1枚の四角形メッシュを、ポイントの数だけインスタンスとしてまとめて描く。
+//! シェーダーで四角形をカメラに向け(ビルボード)、縁をなめらかに透明にした円にするので、
+//! 球体のメッシュより頂点とピクセルの処理が少なく、密集したところもにじんだ光の点に見える。
+//! 画面上の大きさはカメラからの距離に応じて上限と下限の間に収め、遠くのポイントも消えないようにする。
+//! 縁を透明にするため、乗算済みアルファで合成する(深度は書き込まない)。
+//! 各インスタンスの位置・スケール・色は毎フレーム`PointInstances`に集め、
+//! レンダーワールドで頂点バッファ(インスタンス単位)にして`DrawPointInstances`で描く。
+//! 生成・削除のアニメーションは、これまで通りTransformのスケールを変えるだけでよい。
+//! ポイントは図形のエンティティの子なので、位置は変換を伝播した後の`GlobalTransform`から集める。
+//!
+//! BevyのカスタムシェーダーのインスタンシングのExampleと同じ構成で、
+//! シェーダーは`assets/shaders/point_instancing.wgsl`にある。
+
+use bevy::core_pipeline::core_3d::Transparent3d;
+use bevy::ecs::query::QueryItem;
+use bevy::ecs::system::SystemParamItem;
+use bevy::ecs::system::lifetimeless::{Read, SRes};
+use bevy::pbr::{
+    MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup,
+};
+use bevy::prelude::*;
+use bevy::render::extract_component::{ExtractComponent, ExtractComponentPlugin};
+use bevy::render::mesh::allocator::MeshAllocator;
+use bevy::render::mesh::{MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_phase::{
+    AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+    RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::RenderDevice;
+use bevy::render::sync_world::MainEntity;
+use bevy::render::view::ExtractedView;
+use bevy::render::{Render, RenderApp, RenderSet};
+use bytemuck::{Pod, Zeroable};
+
+use crate::{GROUND_HEIGHT, SamplingMode, SamplingParams, ViewMode};
+use crate::demo_config::{DemoConfig, PointColors};
+use crate::density::{DensityColoring, DensityIndex};
+use crate::ground_shadow::{GroundShadows, SHADOW_BRIGHTNESS, SHADOW_LIFT};
+use crate::lifetime::{Lifetime, PointLifetime};
+use crate::palette::{PinnedPalette, PointPalette};
+
+/// インスタンス描画のシェーダー(assetsからの相対パス)
+const SHADER_ASSET_PATH: &str = "shaders/point_instancing.wgsl";
+
+/// 1つのポイントのインスタンスデータ(シェーダーのlocation(3)と(4)に渡す)
+#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq)]
+#[repr(C)]
+pub struct PointInstance {
+    pub position_scale: [f32; 4], // xyzが位置、wがスケール
+    pub color: [f32; 4],          // 発光色(HDRなので1を超えてよい)
+}
+
+impl PointInstance {
+    /// サンプリングモードに応じた色のインスタンスを作る
+    pub fn new(position: Vec3, scale: f32, mode: SamplingMode, colors: &PointColors) -> Self {
+        let glow = match mode {
+            SamplingMode::Interior => 2.5,    // 内部ポイントの発光の強さ
+            SamplingMode::Boundary => 1.5,    // 境界ポイントの発光の強さ
+            SamplingMode::PoissonDisk => 2.0, // ポアソンディスクの発光の強さ
+        };
+        Self::with_color(position, scale, glow * colors.for_mode(mode))
+    }
+
+    /// 指定した色のインスタンスを作る(密度による色分けで使う)
+    pub fn with_color(position: Vec3, scale: f32, color: LinearRgba) -> Self {
+        Self {
+            position_scale: position.extend(scale).to_array(),
+            color: color.to_f32_array(),
+        }
+    }
+
+    /// 色の明るさに`brightness`をかける(寿命の終わりに近いポイントを暗くするのに使う)
+    pub fn dimmed(mut self, brightness: f32) -> Self {
+        for channel in &mut self.color[..3] {
+            *channel *= brightness;
+        }
+        self
+    }
+
+    /// 位置のY座標を`height`にする(ポイントを真下の地面に落とした影に使う)
+    pub fn projected(mut self, height: f32) -> Self {
+        self.position_scale[1] = height;
+        self
+    }
+}
+
+/// まとめて描くポイントのインスタンスの一覧(Mesh3dと同じエンティティに付ける)
+/// Mesh3dのメッシュがインスタンスの形になる
+#[derive(Component, Clone, Default, Deref, DerefMut)]
+pub struct PointInstances(pub Vec<PointInstance>);
+
+impl ExtractComponent for PointInstances {
+    type QueryData = &'static PointInstances;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(item.clone())
+    }
+}
+
+/// ポイントのインスタンス描画を行うプラグイン(`PrimitivesPlugin`に含まれる)
+/// ヘッドレス(レンダラーなし)ではインスタンスの一覧を作るだけになる
+pub struct PointInstancingPlugin;
+
+impl Plugin for PointInstancingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<PointInstances>::default());
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_command::<Transparent3d, DrawPointInstances>()
+            .init_resource::<SpecializedMeshPipelines<PointPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_point_instances.in_set(RenderSet::QueueMeshes),
+                    prepare_instance_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<PointPipeline>();
+        }
+    }
+}
+
+/// インスタンスの一覧を描画キューに追加するシステム
+fn queue_point_instances(
+    transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    point_pipeline: Res<PointPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<PointPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    clouds: Query<(Entity, &MainEntity), With<PointInstances>>,
+    mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(&ExtractedView, &Msaa)>,
+) {
+    let draw_function = transparent_3d_draw_functions
+        .read()
+        .id::<DrawPointInstances>();
+
+    for (view, msaa) in &views {
+        let Some(transparent_phase) = transparent_render_phases.get_mut(&view.retained_view_entity)
+        else {
+            continue;
+        };
+
+        let msaa_key = MeshPipelineKey::from_msaa_samples(msaa.samples());
+        // 円の縁を透明にするので、乗算済みアルファで合成する
+        let view_key = msaa_key
+            | MeshPipelineKey::from_hdr(view.hdr)
+            | MeshPipelineKey::BLEND_PREMULTIPLIED_ALPHA;
+        let rangefinder = view.rangefinder3d();
+        for (entity, main_entity) in &clouds {
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity)
+            else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let pipeline =
+                pipelines.specialize(&pipeline_cache, &point_pipeline, key, &mesh.layout);
+            let pipeline = match pipeline {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("ポイントの描画パイプラインを作れません: {err}");
+                    continue;
+                }
+            };
+            transparent_phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::None,
+                indexed: true,
+            });
+        }
+    }
+}
+
+/// インスタンスデータを書き込んだ頂点バッファ(レンダーワールドのコンポーネント)
+#[derive(Component)]
+struct InstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+/// インスタンスの一覧からGPUの頂点バッファを作るシステム
+fn prepare_instance_buffers(
+    mut commands: Commands,
+    clouds: Query<(Entity, &PointInstances)>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, instances) in &clouds {
+        if instances.is_empty() {
+            commands.entity(entity).remove::<InstanceBuffer>(); // 空のバッファは作れない
+            continue;
+        }
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("point instance buffer"),
+            contents: bytemuck::cast_slice(instances.as_slice()),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+        commands.entity(entity).insert(InstanceBuffer {
+            buffer,
+            length: instances.len(),
+        });
+    }
+}
+
+/// メッシュの標準のパイプラインに、インスタンスの頂点バッファとシェーダーを加えたパイプライン
+#[derive(Resource)]
+struct PointPipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+}
+
+impl FromWorld for PointPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let mesh_pipeline = world.resource::<MeshPipeline>().clone();
+        Self {
+            shader: world.load_asset(SHADER_ASSET_PATH),
+            mesh_pipeline,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for PointPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: size_of::<PointInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 3, // 0〜2はメッシュの位置・法線・UVが使う
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size(),
+                    shader_location: 4,
+                },
+            ],
+        });
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = self.shader.clone();
+        }
+        Ok(descriptor)
+    }
+}
+
+/// ポイントのインスタンスを描く描画コマンド
+type DrawPointInstances = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawMeshInstanced,
+);
+
+/// メッシュを、インスタンスの頂点バッファの数だけまとめて描く
+struct DrawMeshInstanced;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = Read<InstanceBuffer>;
+
+    #[inline]
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        instance_buffer: Option<&'w InstanceBuffer>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        // 借用チェッカーのために、先に参照を取り出しておく
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity())
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(instance_buffer) = instance_buffer else {
+            return RenderCommandResult::Skip; // ポイントが1つもない
+        };
+        let Some(vertex_buffer_slice) =
+            mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_buffer_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+
+        let instances = 0..instance_buffer.length as u32;
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed {
+                index_format,
+                count,
+            } => {
+                let Some(index_buffer_slice) =
+                    mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)
+                else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_buffer_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_buffer_slice.range.start..(index_buffer_slice.range.start + count),
+                    vertex_buffer_slice.range.start as i32,
+                    instances,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_buffer_slice.range, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
+
+/// ポイントのエンティティの位置・スケール・モードから、インスタンスの一覧を作り直すシステム
+/// (生成中・削除中のポイントも含めるので、SamplePointではなくSamplingModeで探す)
+/// 図形の回転を同じフレームの描画に反映するため、PostUpdateで変換を伝播した後に実行する
+/// 密度による色分けが有効なら、モードの色の代わりに近傍の数に応じた色にする
+/// 寿命が有効なら、寿命の終わりに近いポイントほど暗くする
+/// 色はパレット(Pキー)の色で、切り替える前のパレットを付けたポイントはその色にする
+/// 地面への影(Zキー)が有効なら、3Dの表示ではポイントを地面に落として暗くしたインスタンスも加える
+pub(crate) fn collect_point_instances(
+    points: Query<(
+        Entity,
+        &Transform,
+        &GlobalTransform,
+        &SamplingMode,
+        Option<&Lifetime>,
+        Option<&PinnedPalette>,
+    )>,
+    mut clouds: Query<&mut PointInstances>,
+    density: Res<DensityColoring>,
+    lifetime: Res<PointLifetime>,
+    config: Res<DemoConfig>,
+    params: Res<SamplingParams>,
+    palette: Res<PointPalette>,
+    shadows: Res<GroundShadows>,
+    view: Res<ViewMode>,
+) {
+    let colors = palette.colors(&config.point_colors);
+    let density_index = density.index();
+    let max_neighbors = density_index.map_or(0, DensityIndex::max_neighbors);
+    for mut instances in clouds.iter_mut() {
+        instances.clear();
+        instances.extend(points.iter().map(|(entity, transform, global, mode, age, pinned)| {
+            // アニメーションのスケールに、表示の大きさの倍率(9・0キー)をかける
+            let (position, scale) = (global.translation(), transform.scale.x * params.point_size);
+            let brightness = match age {
+                Some(age) if lifetime.enabled => lifetime.brightness(age),
+                _ => 1.0,
+            };
+            let instance = match density_index {
+                // 削除中のポイントは空間ハッシュから外れているので、近傍の数を0とする
+                Some(index) => {
+                    let neighbors = index.neighbors(entity).unwrap_or(0);
+                    PointInstance::with_color(
+                        position,
+                        scale,
+                        density.color(neighbors, max_neighbors),
+                    )
+                }
+                // 切り替える前のパレットを付けたポイントは、その色のままにする
+                None => {
+                    let colors = pinned
+                        .map_or(colors, |pinned| pinned.0.colors(&config.point_colors));
+                    PointInstance::new(position, scale, *mode, &colors)
+                }
+            };
+            instance.dimmed(brightness)
+        }));
+        // 影はポイントのインスタンスの複製なので、大きさ(アニメーション)と色もポイントに合わせて変わる
+        if shadows.enabled && *view == ViewMode::ThreeD {
+            let count = instances.len();
+            instances.extend_from_within(..);
+            for shadow in &mut instances[count..] {
+                *shadow = shadow
+                    .projected(GROUND_HEIGHT + SHADOW_LIFT)
+                    .dimmed(SHADOW_BRIGHTNESS);
+            }
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! `assets/config/primitives.keys.config.ron`から読み込むキーの割り当て
+//!
+//! 操作(`SampleAction`)ごとのキーを`KeyBindings`リソースにまとめ、ここから`ActionMap`を作る。
+//! 英語配列以外のキーボードでも使えるように、ファイルを編集すればS・D・M・A・R・Tabなどを
+//! 好きなキーに変えられる(書かなかった操作は既定のキーのまま、実行中に保存しても反映される)。
+//! 操作説明の文章のキーの名前(`{reset}`など)も、ここから埋め込む。
+//! ゲームパッドのボタンの割り当ては変えない。
+
+use bevy::prelude::*;
+use bevy::reflect::Struct;
+use sample_common::{ActionMap, ConfigPlugin, LocalizedText};
+use serde::Deserialize;
+
+use crate::SampleAction;
+
+/// 設定ファイルのパス(`assets/`からの相対パス)
+pub const CONFIG_PATH: &str = "config/primitives.keys.config.ron";
+
+/// 割り当てを変えられる操作と、設定ファイルのフィールド名(操作説明の`{名前}`にも使う)
+pub const ACTIONS: [(SampleAction, &str); 47] = [
+    (SampleAction::Reset, "reset"),
+    (SampleAction::SpawnOne, "spawn_one"),
+    (SampleAction::SpawnHundred, "spawn_hundred"),
+    (SampleAction::ToggleSampling, "toggle_sampling"),
+    (SampleAction::ToggleSpawning, "toggle_spawning"),
+    (SampleAction::ToggleHelp, "toggle_help"),
+    (SampleAction::ZoomOut, "zoom_out"),
+    (SampleAction::ZoomIn, "zoom_in"),
+    (SampleAction::PreviousShape, "previous_shape"),
+    (SampleAction::NextShape, "next_shape"),
+    (SampleAction::ToggleClustering, "toggle_clustering"),
+    (SampleAction::ToggleView, "toggle_view"),
+    (SampleAction::ExportPoints, "export_points"),
+    (SampleAction::Reseed, "reseed"),
+    (SampleAction::ToggleMonteCarlo, "toggle_monte_carlo"),
+    (SampleAction::ToggleDensity, "toggle_density"),
+    (SampleAction::ToggleHistogram, "toggle_histogram"),
+    (SampleAction::ToggleProjection, "toggle_projection"),
+    (SampleAction::CameraFront, "camera_front"),
+    (SampleAction::CameraTop, "camera_top"),
+    (SampleAction::CameraIsometric, "camera_isometric"),
+    (SampleAction::CameraCloseUp, "camera_close_up"),
+    (SampleAction::TogglePause, "toggle_pause"),
+    (SampleAction::SlowDown, "slow_down"),
+    (SampleAction::SpeedUp, "speed_up"),
+    (SampleAction::ToggleRecording, "toggle_recording"),
+    (SampleAction::TogglePlayback, "toggle_playback"),
+    (SampleAction::ToggleDiagnostics, "toggle_diagnostics"),
+    (SampleAction::ToggleLifetime, "toggle_lifetime"),
+    (SampleAction::CycleEasing, "cycle_easing"),
+    (SampleAction::ToggleRotation, "toggle_rotation"),
+    (SampleAction::ToggleWireframe, "toggle_wireframe"),
+    (SampleAction::ShrinkPoints, "shrink_points"),
+    (SampleAction::GrowPoints, "grow_points"),
+    (SampleAction::ToggleConvexHull, "toggle_convex_hull"),
+    (SampleAction::SlowerSpawning, "slower_spawning"),
+    (SampleAction::FasterSpawning, "faster_spawning"),
+    (SampleAction::CyclePalette, "cycle_palette"),
+    (SampleAction::ToggleStatistics, "toggle_statistics"),
+    (SampleAction::ToggleGenerator, "toggle_generator"),
+    (SampleAction::CycleLayout, "cycle_layout"),
+    (SampleAction::ToggleShadows, "toggle_shadows"),
+    (SampleAction::ToggleTurntable, "toggle_turntable"),
+    (SampleAction::ShrinkShape, "shrink_shape"),
+    (SampleAction::GrowShape, "grow_shape"),
+    (SampleAction::CycleComposite, "cycle_composite"),
+    (SampleAction::TogglePhysics, "toggle_physics"),
+];
+
+/// キーの割り当てを読み込むプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ConfigPlugin::<KeyBindings>::new(CONFIG_PATH))
+            .add_systems(
+                Update,
+                apply_key_bindings
+                    .run_if(resource_changed::<KeyBindings>)
+                    .before(crate::handle_keypress),
+            );
+    }
+}
+
+/// 操作ごとのキーボードのキー(同じ操作に複数のキーを割り当ててもよい)
+#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub reset: Vec<KeyCode>,              // すべてのポイントを削除
+    pub spawn_one: Vec<KeyCode>,          // ポイントを1個生成予約
+    pub spawn_hundred: Vec<KeyCode>,      // ポイントを100個生成予約
+    pub toggle_sampling: Vec<KeyCode>,    // サンプリングモードの切り替え
+    pub toggle_spawning: Vec<KeyCode>,    // ポイント生成モード(自動 or 手動)の切り替え
+    pub toggle_help: Vec<KeyCode>,        // 操作説明の表示の切り替え
+    pub zoom_out: Vec<KeyCode>,           // カメラを遠ざける
+    pub zoom_in: Vec<KeyCode>,            // カメラを近づける
+    pub previous_shape: Vec<KeyCode>,     // 左の図形を注視する
+    pub next_shape: Vec<KeyCode>,         // 右の図形を注視する
+    pub toggle_clustering: Vec<KeyCode>,  // クラスタリングの切り替え
+    pub toggle_view: Vec<KeyCode>,        // 3Dと2Dの表示の切り替え
+    pub export_points: Vec<KeyCode>,      // ポイントをPLYとCSVに書き出す
+    pub reseed: Vec<KeyCode>,             // 新しいシード値でリスタート
+    pub toggle_monte_carlo: Vec<KeyCode>, // モンテカルロ法による推定の切り替え
+    pub toggle_density: Vec<KeyCode>,     // 密度による色分けの切り替え
+    pub toggle_histogram: Vec<KeyCode>,   // ヒストグラムの表示の切り替え
+    pub toggle_projection: Vec<KeyCode>,  // 透視投影と正射影の切り替え
+    pub camera_front: Vec<KeyCode>,       // カメラのプリセット(正面)
+    pub camera_top: Vec<KeyCode>,         // カメラのプリセット(真上)
+    pub camera_isometric: Vec<KeyCode>,   // カメラのプリセット(等角)
+    pub camera_close_up: Vec<KeyCode>,    // カメラのプリセット(注視している図形のアップ)
+    pub toggle_pause: Vec<KeyCode>,       // 生成・削除の一時停止
+    pub slow_down: Vec<KeyCode>,          // 生成・削除の速さを半分にする
+    pub speed_up: Vec<KeyCode>,           // 生成・削除の速さを2倍にする
+    pub toggle_recording: Vec<KeyCode>,   // 記録の開始・保存
+    pub toggle_playback: Vec<KeyCode>,    // 記録の再生の開始・停止
+    pub toggle_diagnostics: Vec<KeyCode>, // FPSやポイント数の表示の切り替え
+    pub toggle_lifetime: Vec<KeyCode>,    // 寿命による削除の切り替え
+    pub cycle_easing: Vec<KeyCode>,       // アニメーションのイージングの切り替え
+    pub toggle_rotation: Vec<KeyCode>,    // 図形の回転の切り替え
+    pub toggle_wireframe: Vec<KeyCode>,   // ワイヤーフレームの表示の切り替え
+    pub shrink_points: Vec<KeyCode>,      // ポイントの表示を小さくする
+    pub grow_points: Vec<KeyCode>,        // ポイントの表示を大きくする
+    pub toggle_convex_hull: Vec<KeyCode>, // 凸包の表示の切り替え
+    pub slower_spawning: Vec<KeyCode>,    // 1フレームあたりの生成数を半分にする
+    pub faster_spawning: Vec<KeyCode>,    // 1フレームあたりの生成数を2倍にする
+    pub cycle_palette: Vec<KeyCode>,      // ポイントの色のパレットの切り替え
+    pub toggle_statistics: Vec<KeyCode>,  // 重心・分散の表示の切り替え
+    pub toggle_generator: Vec<KeyCode>,   // 疑似乱数と準乱数の切り替え
+    pub cycle_layout: Vec<KeyCode>,       // 図形の並べ方の切り替え
+    pub toggle_shadows: Vec<KeyCode>,     // 地面への影の表示の切り替え
+    pub toggle_turntable: Vec<KeyCode>,   // カメラの自動周回の切り替え
+    pub shrink_shape: Vec<KeyCode>,       // 注視している図形を小さくする
+    pub grow_shape: Vec<KeyCode>,         // 注視している図形を大きくする
+    pub cycle_composite: Vec<KeyCode>,    // 右隣の図形との和集合・共通部分の切り替え
+    pub toggle_physics: Vec<KeyCode>,     // ポイントを地面に落とす物理モードの切り替え
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            reset: vec![KeyCode::KeyR],
+            spawn_one: vec![KeyCode::KeyS],
+            spawn_hundred: vec![KeyCode::KeyD],
+            toggle_sampling: vec![KeyCode::KeyM],
+            toggle_spawning: vec![KeyCode::KeyA],
+            toggle_help: vec![KeyCode::Tab],
+            zoom_out: vec![KeyCode::NumpadSubtract, KeyCode::Minus],
+            zoom_in: vec![KeyCode::NumpadAdd],
+            previous_shape: vec![KeyCode::ArrowLeft],
+            next_shape: vec![KeyCode::ArrowRight],
+            toggle_clustering: vec![KeyCode::KeyC],
+            toggle_view: vec![KeyCode::KeyV],
+            export_points: vec![KeyCode::KeyE],
+            reseed: vec![KeyCode::KeyN],
+            toggle_monte_carlo: vec![KeyCode::KeyI],
+            toggle_density: vec![KeyCode::KeyH],
+            toggle_histogram: vec![KeyCode::KeyG],
+            toggle_projection: vec![KeyCode::KeyO],
+            camera_front: vec![KeyCode::Digit1],
+            camera_top: vec![KeyCode::Digit2],
+            camera_isometric: vec![KeyCode::Digit3],
+            camera_close_up: vec![KeyCode::Digit4],
+            toggle_pause: vec![KeyCode::Space],
+            slow_down: vec![KeyCode::BracketLeft],
+            speed_up: vec![KeyCode::BracketRight],
+            toggle_recording: vec![KeyCode::KeyK],
+            toggle_playback: vec![KeyCode::KeyL],
+            toggle_diagnostics: vec![KeyCode::F3],
+            toggle_lifetime: vec![KeyCode::KeyT],
+            cycle_easing: vec![KeyCode::KeyB],
+            toggle_rotation: vec![KeyCode::KeyY],
+            toggle_wireframe: vec![KeyCode::KeyW],
+            shrink_points: vec![KeyCode::Digit9],
+            grow_points: vec![KeyCode::Digit0],
+            toggle_convex_hull: vec![KeyCode::KeyU],
+            slower_spawning: vec![KeyCode::PageDown],
+            faster_spawning: vec![KeyCode::PageUp],
+            cycle_palette: vec![KeyCode::KeyP],
+            toggle_statistics: vec![KeyCode::KeyJ],
+            toggle_generator: vec![KeyCode::KeyQ],
+            cycle_layout: vec![KeyCode::KeyF],
+            toggle_shadows: vec![KeyCode::KeyZ],
+            toggle_turntable: vec![KeyCode::KeyX],
+            shrink_shape: vec![KeyCode::Digit7],
+            grow_shape: vec![KeyCode::Digit8],
+            cycle_composite: vec![KeyCode::Digit5],
+            toggle_physics: vec![KeyCode::Digit6],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// 操作に割り当てたキー(ACTIONSにない操作は空)
+    pub fn keys(&self, action: SampleAction) -> &[KeyCode] {
+        ACTIONS
+            .iter()
+            .find(|(candidate, _)| *candidate == action)
+            .and_then(|(_, name)| self.field(name)?.try_downcast_ref::<Vec<KeyCode>>())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// 操作説明に表示するキーの名前(複数なら「/」でつなぎ、割り当てがなければ「-」)
+    pub fn label(&self, action: SampleAction) -> String {
+        let mut labels: Vec<String> = Vec::new();
+        for key in self.keys(action) {
+            let label = key_label(*key);
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        if labels.is_empty() {
+            return "-".to_owned();
+        }
+        labels.join("/")
+    }
+
+    /// 複数の操作に割り当てられているキー
+    pub fn conflicts(&self) -> Vec<KeyCode> {
+        let mut seen: Vec<(KeyCode, SampleAction)> = Vec::new();
+        let mut conflicts = Vec::new();
+        for (action, _) in ACTIONS {
+            for key in self.keys(action) {
+                let taken = seen
+                    .iter()
+                    .any(|(other, owner)| other == key && *owner != action);
+                if taken && !conflicts.contains(key) {
+                    conflicts.push(*key);
+                }
+                seen.push((*key, action));
+            }
+        }
+        conflicts
+    }
+
+    /// 操作説明の文章(`LocalizedText`)にキーの名前を埋め込む
+    pub fn with_labels(&self, mut text: LocalizedText) -> LocalizedText {
+        for (action, name) in ACTIONS {
+            text = text.with_arg(name, self.label(action));
+        }
+        text
+    }
+}
+
+/// 操作説明に表示するキーの名前(文字と数字のキーは「KeyR」→「R」のように短くする)
+pub fn key_label(key: KeyCode) -> String {
+    let symbol = match key {
+        KeyCode::Minus | KeyCode::NumpadSubtract => "-",
+        KeyCode::Equal => "=",
+        KeyCode::NumpadAdd => "+",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Semicolon => ";",
+        KeyCode::Quote => "'",
+        KeyCode::Backquote => "`",
+        KeyCode::Backslash => "\\",
+        KeyCode::ArrowLeft => "←",
+        KeyCode::ArrowRight => "→",
+        KeyCode::ArrowUp => "↑",
+        KeyCode::ArrowDown => "↓",
+        _ => {
+            let name = format!("{key:?}");
+            let short = name
+                .strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .unwrap_or(&name);
+            return short.to_owned();
+        }
+    };
+    symbol.to_owned()
+}
+
+// 割り当てが読み込まれた・変わったら、操作の割り当て表を作り直すシステム
+fn apply_key_bindings(
+    bindings: Res<KeyBindings>,
+    mut map: ResMut<ActionMap<SampleAction>>,
+    mut actions: ResMut<ButtonInput<SampleAction>>,
+) {
+    for key in bindings.conflicts() {
+        warn!("{key:?}キーが複数の操作に割り当てられています");
+    }
+    *map = crate::action_map(&bindings);
+    // 割り当てから外れたキーで押されたままにならないようにする
+    actions.reset_all();
+}
@@ -0,0 +1,159 @@
+//! 図形の並べ方(横一列・格子・円周)
+//!
+//! 図形を横一列に並べると、glTFのメッシュを加えたときなど図形が多いほど左右に長くなり、画面に収まらない。
+//! 「F」キーか設定ファイルの`layout`で`LayoutMode`を切り替えると、`SampledShapes`の位置を計算し直し、
+//! 図形(`ShapeRoot`)を新しい位置に動かす。ポイントとライトは図形の子なので一緒に動く。
+//! カメラが図形を注視していれば、その図形の新しい位置に注視点を移す。
+//! 3Dでは地面と平行なXZ平面に、2Dではカメラから見えるXY平面に並べる。
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{CameraRig, DISTANCE_BETWEEN_SHAPES, SampleAction, SampledShapes, ShapeRoot, ViewMode};
+
+/// 図形の並べ方を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ShapeLayoutPlugin;
+
+impl Plugin for ShapeLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayoutMode>().add_systems(
+            Update,
+            (
+                cycle_layout,
+                arrange_shapes.run_if(resource_changed::<LayoutMode>),
+                move_shape_roots.run_if(resource_changed::<SampledShapes>),
+            )
+                .chain()
+                .after(crate::handle_keypress)
+                .after(crate::custom_mesh::add_custom_mesh)
+                .after(crate::demo_config::apply_demo_config),
+        );
+    }
+}
+
+/// 図形の並べ方
+#[derive(Resource, Reflect, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LayoutMode {
+    #[default]
+    Row, // 横一列(中央を基準に左右に並べる)
+    Grid, // 格子(正方形に近い行と列に並べる)
+    Ring, // 円周(隣の図形との間隔が横一列と同じになる半径)
+}
+
+impl LayoutMode {
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            LayoutMode::Row => "layout.row",
+            LayoutMode::Grid => "layout.grid",
+            LayoutMode::Ring => "layout.ring",
+        }
+    }
+
+    /// 次の並べ方(「F」キーで切り替える順番)
+    pub fn next(self) -> Self {
+        match self {
+            LayoutMode::Row => LayoutMode::Grid,
+            LayoutMode::Grid => LayoutMode::Ring,
+            LayoutMode::Ring => LayoutMode::Row,
+        }
+    }
+
+    /// `count`個の図形の位置(並び順はShapeIndexと同じ)
+    pub fn positions(self, count: usize, view: ViewMode) -> Vec<Vec3> {
+        let spacing = DISTANCE_BETWEEN_SHAPES.x;
+        // 横方向(X)と奥行き方向の座標を、表示に合わせた平面上の位置にする
+        let plane = |x: f32, depth: f32| match view {
+            ViewMode::ThreeD => Vec3::new(x, 0.0, depth),
+            ViewMode::TwoD => Vec3::new(x, -depth, 0.0), // 2Dでは奥の行を上に置く
+        };
+        match self {
+            LayoutMode::Row => (0..count)
+                .map(|i| (i as f32 - count as f32 / 2.0) * DISTANCE_BETWEEN_SHAPES)
+                .collect(),
+            LayoutMode::Grid => {
+                let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+                let rows = count.div_ceil(columns);
+                let center = |n: usize| (n as f32 - 1.0) / 2.0;
+                (0..count)
+                    .map(|i| {
+                        let (row, column) = (i / columns, i % columns);
+                        plane(
+                            (column as f32 - center(columns)) * spacing,
+                            (row as f32 - center(rows)) * spacing,
+                        )
+                    })
+                    .collect()
+            }
+            LayoutMode::Ring => {
+                // 隣り合う図形の間の弦の長さがspacingになる半径(1個なら中央に置く)
+                let radius = if count > 1 {
+                    spacing / (2.0 * (TAU / 2.0 / count as f32).sin())
+                } else {
+                    0.0
+                };
+                (0..count)
+                    .map(|i| {
+                        let angle = TAU * i as f32 / count as f32;
+                        plane(-radius * angle.cos(), -radius * angle.sin())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// 「F」キー：図形の並べ方を切り替えるシステム
+fn cycle_layout(actions: Res<ButtonInput<SampleAction>>, mut layout: ResMut<LayoutMode>) {
+    if !actions.just_pressed(SampleAction::CycleLayout) {
+        return;
+    }
+    *layout = layout.next();
+    info!("図形の並べ方を{:?}にしました", *layout);
+}
+
+// 並べ方が変わったら図形の位置を計算し直し、注視していた図形にカメラの注視点を合わせるシステム
+fn arrange_shapes(
+    layout: Res<LayoutMode>,
+    view: Res<ViewMode>,
+    mut shapes: ResMut<SampledShapes>,
+    mut camera_rig: Query<&mut CameraRig>,
+) {
+    let positions = layout.positions(shapes.0.len(), *view);
+    if shapes
+        .0
+        .iter()
+        .map(|(_, position)| *position)
+        .eq(positions.iter().copied())
+    {
+        return; // 同じ位置なら、図形を動かすシステムを走らせない
+    }
+
+    // 図形の上にあった注視点だけを動かす(全体を見渡しているときはそのまま)
+    let focused: Vec<_> = camera_rig
+        .iter()
+        .map(|rig| {
+            let index = shapes.closest(rig.target);
+            (rig.target.distance(shapes.0[index].1) < 1e-3).then_some(index)
+        })
+        .collect();
+
+    shapes.arrange(*layout, *view);
+
+    for (mut rig, focused) in camera_rig.iter_mut().zip(focused) {
+        if let Some(index) = focused {
+            rig.target = shapes.0[index].1;
+        }
+    }
+}
+
+// 図形の位置が変わったら、図形のエンティティ(ポイントとライトの親)を動かすシステム
+fn move_shape_roots(shapes: Res<SampledShapes>, mut roots: Query<(&ShapeRoot, &mut Transform)>) {
+    for (root, mut transform) in roots.iter_mut() {
+        if let Some((_, position)) = shapes.0.get(root.0) {
+            transform.translation = *position;
+        }
+    }
+}
@@ -0,0 +1,2258 @@
+//! 3D・2Dプリミティブ図形の内部・境界をランダムサンプリングして可視化するサンプル
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::{
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseButtonInput}, // 入力イベント
+    math::prelude::*,
+    prelude::*, // Bevyの基本的なプリリュード(基本的機能とか要素とか)
+    render::camera::ScalingMode, // 2D表示の正射影カメラの表示範囲
+    render::view::{NoFrustumCulling, NoIndirectDrawing}, // ポイントのインスタンス描画に必要
+};
+use bevy_kira_audio::{
+    Audio, AudioControl, AudioInstance, AudioPlugin, AudioSource as KiraAudioSource, AudioTween,
+}; // 音声再生用のプラグイン
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sample_common::{
+    ActionAxes, ActionMap, ActionsPlugin, ConfigPlugin, CrashWatchPlugin, Locale,
+    LocalizationPlugin, LocalizedText, PauseState, PostFxKeys, PostFxPlugin, QualityLevel,
+    ScreenshotPlugin, TimeScale, time_control::is_running,
+}; // 入力・スクリーンショット・描画品質・多言語対応・クラッシュレポート・ポストプロセス・一時停止・設定ファイルの共通機能
+
+pub mod camera_presets;
+pub mod clustering;
+pub mod composite;
+pub mod convex_hull;
+pub mod custom_mesh;
+pub mod demo_config;
+pub mod density;
+pub mod diagnostics_overlay;
+pub mod easing;
+pub mod export;
+pub mod firefly_lighting;
+pub mod ground_shadow;
+pub mod histogram;
+pub mod instancing;
+pub mod key_bindings;
+pub mod layout;
+pub mod lifetime;
+pub mod monte_carlo;
+pub mod palette;
+pub mod panel;
+pub mod parallel_sampling;
+pub mod physics;
+pub mod picking;
+pub mod point_budget;
+pub mod poisson;
+pub mod quasi_random;
+pub mod replay;
+pub mod sampling;
+pub mod sampling_bench;
+pub mod screenshot_notice;
+pub mod session;
+pub mod slow_motion;
+pub mod shape_labels;
+pub mod shape_rotation;
+pub mod shape_scale;
+pub mod spatial_index;
+pub mod statistics;
+pub mod touch_controls;
+pub mod turntable;
+pub mod wireframe;
+
+use camera_presets::CameraTransition;
+use clustering::{Cluster, k_means};
+use composite::CompositeShape;
+use custom_mesh::{CustomMesh, CustomMeshSource, TriangleMesh};
+use demo_config::DemoConfig;
+use histogram::AxisHistogram;
+use density::DensityColoring;
+use easing::AnimationEasing;
+use firefly_lighting::firefly_light;
+use instancing::{PointInstances, PointInstancingPlugin, collect_point_instances};
+use key_bindings::KeyBindings;
+use layout::LayoutMode;
+use monte_carlo::{MonteCarlo, SignedDistance};
+use palette::PointPalette;
+use panel::ControlPanel;
+use parallel_sampling::ParallelSampling;
+use picking::Picking;
+use point_budget::PointBudget;
+use poisson::PoissonGrid;
+use quasi_random::{QuasiRandom, RandomGenerator};
+use replay::Replay;
+use sampling::{ExtraShapeSample, frustum_area, frustum_volume};
+use spatial_index::PointSpatialIndex;
+
+/// 図形のランダムサンプリングを可視化するサンプルのプラグイン
+/// ウィンドウ関連のDefaultPluginsは含まないので、main側で追加する
+pub struct PrimitivesPlugin;
+
+impl Plugin for PrimitivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(AudioPlugin) // 音声再生のためのプラグインを追加
+            .add_plugins(ScreenshotPlugin::new("primitives").with_recorder(30.0)) // F12で撮影、F11で録画
+            .add_plugins(ActionsPlugin::new(action_map(&KeyBindings::default()))) // キーボードとゲームパッドの操作
+            .add_plugins(key_bindings::KeyBindingsPlugin) // キーの割り当て(設定ファイルで変えると実行中に反映される)
+            .add_plugins(
+                LocalizationPlugin::new()
+                    .bundle(Locale::En, include_str!("../assets/locale/en.ron"))
+                    .bundle(Locale::Ja, include_str!("../assets/locale/ja.ron")),
+            ) // 操作説明の英語・日本語(F2で切り替え)
+            .add_plugins(CrashWatchPlugin::<PointCounter>::default()) // クラッシュレポートにポイント数を含める
+            .add_plugins(PostFxPlugin) // ブルームやトーンマッピング(F6で設定パネル)
+            .add_plugins(ConfigPlugin::<DemoConfig>::new(demo_config::CONFIG_PATH)) // ポイント数や色、カメラの距離(編集すると実行中に反映される)
+            .insert_resource(PostFxKeys {
+                bloom_down: KeyCode::Comma,
+                bloom_up: KeyCode::Period,
+                label: ", / .",
+                panel: KeyCode::F6,
+            }) // 「[」「]」は時間の倍率に、F3は診断情報に使うので、ブルームの強さは「,」「.」、設定パネルはF6にする
+            .init_resource::<PauseState>() // ポイントの生成・削除を一時停止しているか
+            .init_resource::<TimeScale>() // ポイントの生成・削除の速さの倍率
+            .init_resource::<AnimationEasing>() // 生成・削除アニメーションのイージング(初期は線形)
+            .insert_resource(ViewMode::ThreeD) // 初期は3Dの図形を表示
+            .insert_resource(SampledShapes::new(
+                ViewMode::ThreeD.shapes(),
+                LayoutMode::default(),
+                ViewMode::ThreeD,
+            )) // SampledShapesリソース(Resource)を追加(並べ方は設定ファイルやFキーで変わる)
+            .init_resource::<Clustering>() // クラスタリングの状態(初期は無効)
+            .init_resource::<MonteCarlo>() // モンテカルロ法による推定の状態(初期は無効)
+            .init_resource::<PointSpatialIndex>() // ポイントの近傍探索のための空間ハッシュ
+            .init_resource::<DensityColoring>() // 密度による色分けの設定(初期は無効)
+            .init_resource::<Picking>() // カーソルで選んだポイント
+            .init_resource::<AxisHistogram>() // 軸ごとのヒストグラム(初期は非表示)
+            .init_resource::<CameraProjection>() // カメラの投影方法(初期は透視投影)
+            .init_resource::<Replay>() // サンプリングの記録・再生(初期はどちらもしない)
+            .init_resource::<CustomMeshSource>() // 読み込むglTF(mainで--meshから挿入されていればそれを使う)
+            .init_resource::<CustomMesh>() // 読み込んだglTFのメッシュ
+            .init_resource::<SamplingParams>() // ポイントの生成・削除の設定
+            .init_resource::<SpawnRate>() // 自動モードで1フレームあたりに生成するポイント数
+            .init_resource::<ParallelSampling>() // 大量に予約したポイントの位置を並列に計算するタスク
+            .init_resource::<RngSeed>() // 乱数のシード値(mainで--seedから挿入されていればそれを使う)
+            .add_plugins(panel::ControlPanelPlugin) // 設定を変える操作パネル
+            .add_plugins(PointInstancingPlugin) // ポイントをまとめて描くインスタンス描画
+            .add_plugins(firefly_lighting::FireflyLightingPlugin) // ポイント数に応じて図形の中心のライトを明るくする
+            .add_plugins(point_budget::PointBudgetPlugin) // フレーム時間に合わせてポイントの数を調整する
+            .add_plugins(diagnostics_overlay::DiagnosticsOverlayPlugin) // F3でFPSやポイント数を表示する
+            .add_plugins(touch_controls::TouchControlsPlugin) // スマートフォンなどでのタッチ操作
+            .add_plugins(lifetime::LifetimePlugin) // Tキーで古いポイントから消していく寿命
+            .add_plugins(physics::PointPhysicsPlugin) // 6キーでポイントを図形から離して地面に落とす
+            .add_plugins(shape_rotation::ShapeRotationPlugin) // Yキーで図形をポイントごと回転させる
+            .add_plugins(wireframe::ShapeWireframePlugin) // Wキーで図形の三角形分割を線で表示する
+            .add_plugins(convex_hull::ConvexHullPlugin) // Uキーで注視している図形のポイントの凸包を表示する
+            .add_plugins(palette::PointPalettePlugin) // Pキーでポイントの色のパレットを切り替える
+            .add_plugins(statistics::SampleStatisticsPlugin) // Jキーで図形ごとのポイントの重心と分散を表示する
+            .add_plugins(quasi_random::QuasiRandomPlugin) // Qキーで疑似乱数と準乱数(Halton列)を切り替える
+            .add_plugins(layout::ShapeLayoutPlugin) // Fキーで図形の並べ方(横一列・格子・円周)を切り替える
+            .add_plugins(shape_labels::ShapeLabelsPlugin) // 図形の上に名前とポイント数のラベルを表示する
+            .add_plugins(ground_shadow::GroundShadowPlugin) // Zキーでポイントを地面に落とした影を表示する
+            .add_plugins(session::SessionPlugin) // Ctrl+S・Ctrl+Lでポイントと乱数の状態を保存・読み込む
+            .add_plugins(turntable::TurntablePlugin) // Xキーでカメラを自動で周回させる
+            .add_plugins(shape_scale::ShapeScalePlugin) // 7・8キーで注視している図形を縮小・拡大する
+            .add_plugins(composite::CompositeShapePlugin) // 5キーで注視している図形を右隣の図形と合成する
+            .add_plugins(sampling_bench::SamplingBenchPlugin) // --benchでサンプリングの速さを計測して終了する
+            .add_systems(
+                Startup,
+                (
+                    setup,
+                    setup_audio,
+                    picking::spawn_tooltip,
+                    histogram::spawn_histogram_panel,
+                    screenshot_notice::spawn_screenshot_notice,
+                    custom_mesh::load_custom_mesh,
+                ),
+            ) // 起動時にsetupシステムを実行(System)
+            .add_systems(
+                Update,
+                (
+                    handle_mouse,            // マウス入力を処理するシステム
+                    handle_keypress,         // キーボード・ゲームパッドの入力を処理するシステム
+                    handle_gamepad_camera,   // 右スティックでカメラを回転させ、トリガーでズームするシステム
+                    // 表示を切り替えたフレームでは、前の図形のポイントを消してから生成・削除する
+                    // 一時停止中(Spaceキー)は生成・削除とアニメーションを止める
+                    // 記録の再生中は、乱数による生成・削除の代わりに記録の通りに生成・削除する
+                    (
+                        demo_config::apply_demo_config.run_if(resource_changed::<DemoConfig>), // 設定ファイルの変更を反映するシステム
+                        slow_motion::handle_time_keys, // 一時停止と時間の倍率を切り替えるシステム
+                        easing::cycle_easing,          // アニメーションのイージングを切り替えるシステム
+                        handle_point_size_keys,        // ポイントの表示の大きさを変えるシステム
+                        handle_spawn_rate_keys,        // 1フレームあたりの生成数を変えるシステム
+                        replay::handle_replay_keys,    // 記録と再生を切り替えるシステム
+                        (
+                            (
+                                parallel_sampling::spawn_sampled_batches.before(spawn_points), // 並列に計算したポイントを生成するシステム
+                                spawn_points, // ポイントを生成するシステム(エンティティをランダムに生成)
+                                despawn_points, // ポイントを削除するシステム
+                                lifetime::expire_points.after(despawn_points), // 寿命が来たポイントを削除するシステム
+                                physics::fall_points
+                                    .after(despawn_points)
+                                    .after(shape_rotation::rotate_shapes), // ポイントを落とし、地面に着いたら削除するシステム
+                            )
+                                .run_if(not(replay::is_replaying)),
+                            replay::play_events.run_if(replay::is_replaying), // 記録を再生するシステム
+                            animate_spawning, // ポイントの生成アニメーションを処理するシステム(出現アニメーション)
+                            animate_despawning, // ポイントの削除アニメーションを処理するシステム(消失アニメーション)
+                        )
+                            .run_if(is_running),
+                        replay::record_events, // 生成・削除されたポイントを記録するシステム
+                    )
+                        .chain()
+                        .after(custom_mesh::add_custom_mesh), // 新しい図形のエンティティができてから、その子としてポイントを生成する
+                    (camera_presets::apply_camera_preset, update_camera)
+                        .chain()
+                        .after(handle_keypress), // カメラのプリセットへの切り替えと、カメラの位置や角度の更新
+                    adjust_volume_with_zoom, // ズームに応じてBGMの音量を調整するシステム
+                    (switch_view, custom_mesh::add_custom_mesh).chain(), // 3Dと2Dの表示の切り替えと、読み込んだglTFの追加
+                    export::export_on_key,   // ポイントをファイルに書き出すシステム
+                    screenshot_notice::update_screenshot_notice, // スクリーンショットを保存したことを表示する
+                    update_help_text.run_if(
+                        resource_changed::<RngSeed>
+                            .or(resource_changed::<KeyBindings>)
+                            .or(resource_changed::<PointPalette>)
+                            .or(resource_changed::<RandomGenerator>)
+                            .or(resource_changed::<LayoutMode>),
+                    ), // 操作説明のシード値・キー・パレット・乱数生成器・並べ方の名前を更新する
+                    rebuild_cluster_lines
+                        .run_if(resource_changed::<SampledShapes>)
+                        .after(switch_view), // クラスタの一覧の行を図形に合わせる
+                    toggle_clustering,       // クラスタリングの有効・無効を切り替えるシステム
+                    update_clusters.after(toggle_clustering).after(switch_view), // クラスタの重心と数を更新するシステム
+                    (
+                        monte_carlo::toggle_monte_carlo,
+                        monte_carlo::update_monte_carlo,
+                    )
+                        .chain()
+                        .after(switch_view), // モンテカルロ法で体積・表面積を推定するシステム
+                    (
+                        spatial_index::update_point_index, // 生成・削除されたポイントを空間ハッシュに反映する
+                        density::toggle_density,
+                        density::update_density_index,
+                    )
+                        .chain()
+                        .after(animate_spawning)
+                        .after(animate_despawning), // 密度による色分けのためにポイントの近傍を数える
+                    (picking::pick_point, picking::update_tooltip)
+                        .chain()
+                        .after(spatial_index::update_point_index)
+                        .after(update_camera)
+                        .after(switch_view), // カーソルでポイントを選び、座標を表示するシステム
+                    (histogram::toggle_histogram, histogram::update_histogram)
+                        .chain()
+                        .after(handle_keypress)
+                        .after(switch_view), // 注視している図形のポイントの軸ごとのヒストグラム
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                collect_point_instances.after(bevy::transform::TransformSystem::TransformPropagate),
+            ); // 図形の回転を反映したワールド座標で、ポイントの位置とスケールと色を描画用に集める
+    }
+}
+
+/////////// 定数定義 ///////////
+
+/// オブジェクト間の距離(間隔)
+const DISTANCE_BETWEEN_SHAPES: Vec3 = Vec3::new(2.0, 0.0, 0.0);
+
+/// 乱数のシード値の初期値(`--seed`で変えられる)
+pub const DEFAULT_SEED: u64 = 4;
+
+/// 低品質モード(WebGL2や低速な環境)でのポイントの最大数
+pub const MAX_POINTS_LOW_QUALITY: usize = 1000;
+
+/// 右スティックを倒しきったときのカメラの回転速度(ラジアン/秒)
+const STICK_ROTATION_SPEED: f32 = 2.0;
+
+/// トリガーを押し込みきったときのズームの速さ(1秒あたりに、カメラの最大距離に対して変える割合)
+/// 押し込んだ量に比例するので、軽く押すとゆっくり近づく
+const TRIGGER_ZOOM_SPEED: f32 = 0.5;
+
+/// ポアソンディスクサンプリングの最小距離の初期値(SamplingParamsで実行中に変えられる)
+pub const POISSON_RADIUS: f32 = 0.1;
+
+/// ポイントを表示する球の半径(SamplingParamsの大きさの倍率をかけて表示する)
+pub const POINT_RADIUS: f32 = 0.03;
+
+/// ポイントの大きさの倍率として選べる範囲(高解像度の画面では大きく、ズームしたときは小さくする)
+pub const POINT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+
+/// 「9」「0」キーで1回に変えるポイントの大きさの倍率
+const POINT_SIZE_STEP: f32 = 1.25;
+
+/// 1フレームに生成するポイントの最大数(残りは次のフレーム以降に生成する)
+pub const MAX_SPAWNS_PER_FRAME: usize = 1000;
+
+/// 自動モードで1フレームあたりに生成するポイント数として選べる範囲(0で生成を止める)
+pub const POINTS_PER_FRAME_RANGE: std::ops::RangeInclusive<usize> = 0..=200;
+
+/// ポアソンディスクサンプリングで1つのポイントを置くために試す候補の数
+/// すべての候補が近すぎたら、そのポイントは生成しない(図形が埋まるとそれ以上増えない)
+const POISSON_ATTEMPTS: usize = 30;
+
+/// 図形ごとに分けるクラスタの数
+pub const CLUSTERS_PER_SHAPE: usize = 4;
+
+/// k-means法の繰り返しの最大回数
+const CLUSTERING_ITERATIONS: usize = 10;
+
+/// クラスタを計算し直す間隔(秒)
+const CLUSTERING_INTERVAL: f32 = 0.5;
+
+/// クラスタの重心に表示するマーカーの色
+const CLUSTER_MARKER_COLOR: LinearRgba = LinearRgba::rgb(1.0, 0.3, 0.9);
+
+/// 地面の高さ(Y座標)
+pub const GROUND_HEIGHT: f32 = -2.5;
+
+/// 空と環境光に使用される色
+const SKY_COLOR: Color = Color::srgb(0.02, 0.06, 0.15);
+
+/// 小サイズの3Dオブジェクトの寸法
+const SMALL_3D: f32 = 0.5;
+
+/// 大サイズの3Dオブジェクトの寸法
+const BIG_3D: f32 = 1.0;
+
+// 図形の設定
+
+/// 直方体
+use once_cell::sync::Lazy;
+static CUBOID: Lazy<Cuboid> = Lazy::new(|| Cuboid::new(SMALL_3D, BIG_3D, SMALL_3D));
+
+/// 球体
+static SPHERE: Lazy<Sphere> = Lazy::new(|| Sphere {
+    radius: 1.5 * SMALL_3D,
+});
+
+/// 3Dの三角形
+static TRIANGLE_3D: Lazy<Triangle3d> = Lazy::new(|| Triangle3d {
+    vertices: [
+        // 頂点座標
+        Vec3::new(BIG_3D, -BIG_3D * 0.5, 0.0),  // 頂点1
+        Vec3::new(0.0, BIG_3D, 0.0),            // 頂点2（頂上）
+        Vec3::new(-BIG_3D, -BIG_3D * 0.5, 0.0), // 頂点3
+    ],
+});
+
+/// カプセル型(円柱の両端に半球がついた形)
+static CAPSULE_3D: Lazy<Capsule3d> = Lazy::new(|| Capsule3d {
+    radius: SMALL_3D,
+    half_length: BIG_3D,
+});
+
+/// 円柱
+static CYLINDER: Lazy<Cylinder> = Lazy::new(|| Cylinder {
+    radius: SMALL_3D,
+    half_height: SMALL_3D,
+});
+
+// 四面体（ピラミッド型）
+static TETRAHEDRON: Lazy<Tetrahedron> = Lazy::new(|| Tetrahedron {
+    vertices: [
+        // 頂点座標
+        Vec3::new(-BIG_3D, -BIG_3D * 0.67, BIG_3D * 0.5), // 頂点1
+        Vec3::new(BIG_3D, -BIG_3D * 0.67, BIG_3D * 0.5),  // 頂点2
+        Vec3::new(0.0, -BIG_3D * 0.67, -BIG_3D * 1.17),   // 頂点3（背面）
+        Vec3::new(0.0, BIG_3D, 0.0),                      // 頂点4（頂上）
+    ],
+});
+
+/// トーラス(ドーナツ型)
+static TORUS: Lazy<Torus> = Lazy::new(|| Torus {
+    minor_radius: 0.5 * SMALL_3D,
+    major_radius: 1.2 * SMALL_3D,
+});
+
+/// 円錐
+static CONE: Lazy<Cone> = Lazy::new(|| Cone {
+    radius: 1.5 * SMALL_3D,
+    height: 1.5 * BIG_3D,
+});
+
+/// 円錐台(円錐の先端を切り落とした形)
+static CONICAL_FRUSTUM: Lazy<ConicalFrustum> = Lazy::new(|| ConicalFrustum {
+    radius_top: SMALL_3D,
+    radius_bottom: 1.5 * SMALL_3D,
+    height: BIG_3D,
+});
+
+/// 円
+static CIRCLE: Lazy<Circle> = Lazy::new(|| Circle {
+    radius: 1.5 * SMALL_3D,
+});
+
+/// 長方形
+static RECTANGLE: Lazy<Rectangle> = Lazy::new(|| Rectangle::new(1.5 * SMALL_3D, 1.5 * BIG_3D));
+
+/// 円環(2つの同心円に挟まれた領域)
+static ANNULUS: Lazy<Annulus> = Lazy::new(|| Annulus::new(0.8 * SMALL_3D, 1.5 * SMALL_3D));
+
+/// 2Dの三角形
+static TRIANGLE_2D: Lazy<Triangle2d> = Lazy::new(|| {
+    Triangle2d::new(
+        Vec2::new(0.8 * BIG_3D, -0.5 * BIG_3D),  // 頂点1
+        Vec2::new(0.0, 0.9 * BIG_3D),            // 頂点2（頂上）
+        Vec2::new(-0.8 * BIG_3D, -0.5 * BIG_3D), // 頂点3
+    )
+});
+
+// コンポーネントとリソース定義→リソースはアプリケーション全体で共有されるデータ
+/// ランダムにポイントを生成するときのモードを示すリソース
+/// 内部をサンプリングするか、境界をサンプリングするか、
+/// 既存のポイントから一定の距離を空けて内部をサンプリングする(ポアソンディスク)かを決める
+/// 各ポイントにも、サンプリングしたときのモードをコンポーネントとして付ける
+#[derive(Resource, Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SamplingMode {
+    Interior,    // 内部をサンプリング
+    Boundary,    // 境界をサンプリング
+    PoissonDisk, // 既存のポイントから離れた内部の点だけをサンプリング
+}
+
+impl SamplingMode {
+    /// 画面に表示するモードの名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            SamplingMode::Interior => "panel.interior",
+            SamplingMode::Boundary => "panel.boundary",
+            SamplingMode::PoissonDisk => "panel.poisson_disk",
+        }
+    }
+}
+
+/// 3Dの図形と2Dの図形のどちらを表示するかを示すリソース
+/// 2Dでは図形をXY平面に並べ、正面から正射影カメラで見る
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViewMode {
+    ThreeD, // 3Dの図形(透視投影カメラで自由に回転できる)
+    TwoD,   // 2Dの図形(正射影カメラで正面から見る)
+}
+
+impl ViewMode {
+    /// この表示で並べる図形
+    fn shapes(self) -> Vec<Shape> {
+        match self {
+            ViewMode::ThreeD => Shape::list_all_shapes(),
+            ViewMode::TwoD => Shape::list_2d_shapes(),
+        }
+    }
+
+    /// この表示で並べる図形の大きさ(並び順はShapeIndexと同じ)
+    /// 内部のサンプリングでは体積(2Dでは面積)、境界では表面積(2Dでは周の長さ)を返す
+    pub fn measures(self, mode: SamplingMode) -> Vec<f32> {
+        self.shapes()
+            .iter()
+            .map(|shape| shape.measure(mode))
+            .collect()
+    }
+}
+
+/// 3Dの図形を透視投影と正射影のどちらで見るかを示すリソース
+/// 正射影では遠近感がなくなるので、平らな面に並んだ境界のポイントを確かめやすい
+/// (2Dの図形は常に正射影で見る)
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraProjection {
+    #[default]
+    Perspective,  // 透視投影(遠くのものほど小さく見える)
+    Orthographic, // 正射影(距離によらず同じ大きさに見える)
+}
+
+/// ポイントが自動的に生成されるかどうかを指定するリソース
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpawningMode {
+    Manual,    // 手動（自動生成しない）
+    Automatic, // 自動（継続的に自動生成）
+}
+
+impl SpawningMode {
+    /// 画面に表示するモードの名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            SpawningMode::Manual => "panel.manual",
+            SpawningMode::Automatic => "panel.automatic",
+        }
+    }
+}
+
+/// ポイントの生成・削除と表示の設定を保持するリソース(操作パネルで実行中に変えられる)
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct SamplingParams {
+    pub max_points: usize,   // 自動モードで存在できるポイントの最大数
+    pub animation_time: f32, // 生成・削除アニメーションの所要時間(秒)
+    pub poisson_radius: f32, // ポアソンディスクサンプリングでのポイント同士の最小距離
+    pub point_size: f32,     // ポイントの表示の大きさの倍率(POINT_RADIUSにかける)
+}
+
+impl Default for SamplingParams {
+    /// 最大数・アニメーション時間の初期値はDemoConfigのデフォルト値にする
+    fn default() -> Self {
+        let config = DemoConfig::default();
+        Self {
+            max_points: config.max_points,
+            animation_time: config.animation_time,
+            poisson_radius: POISSON_RADIUS,
+            point_size: 1.0,
+        }
+    }
+}
+
+impl SamplingParams {
+    /// 描画品質に応じたポイントの最大数(低品質ではMAX_POINTS_LOW_QUALITYまでに抑える)
+    pub fn point_limit(&self, quality: QualityLevel) -> usize {
+        if quality.is_low() {
+            self.max_points.min(MAX_POINTS_LOW_QUALITY)
+        } else {
+            self.max_points
+        }
+    }
+
+    /// ポイントの大きさの倍率に`factor`をかける(POINT_SIZE_RANGEの範囲に収める)
+    pub fn resize_points(&mut self, factor: f32) {
+        self.point_size =
+            (self.point_size * factor).clamp(*POINT_SIZE_RANGE.start(), *POINT_SIZE_RANGE.end());
+    }
+}
+
+/// 自動モードで1フレームあたりに生成するポイント数を保持するリソース(`spawn_points`が読む)
+/// 「PageDown」「PageUp」キーや操作パネルで変えた生成数は、フレーム時間に合わせた自動調整(`PointBudget`)で減らさない
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct SpawnRate {
+    pub points_per_frame: usize, // 1フレームあたりに生成するポイント数
+    pub user_set: bool,          // キーや操作パネルで変えたか(変えていなければ自動調整の倍率をかける)
+}
+
+impl Default for SpawnRate {
+    /// 初期値はDemoConfigのデフォルト値にする
+    fn default() -> Self {
+        Self {
+            points_per_frame: DemoConfig::default().points_per_frame,
+            user_set: false,
+        }
+    }
+}
+
+impl SpawnRate {
+    /// キーや操作パネルで選んだ生成数にする(POINTS_PER_FRAME_RANGEの範囲に収める)
+    pub fn set(&mut self, points_per_frame: usize) {
+        self.points_per_frame = points_per_frame.clamp(
+            *POINTS_PER_FRAME_RANGE.start(),
+            *POINTS_PER_FRAME_RANGE.end(),
+        );
+        self.user_set = true;
+    }
+
+    /// 生成数を2倍・半分にする(0からは1に増やす)
+    pub fn step(&mut self, faster: bool) {
+        let rate = if faster {
+            (self.points_per_frame * 2).max(1)
+        } else {
+            self.points_per_frame / 2
+        };
+        self.set(rate);
+    }
+}
+
+/// 生成するポイントの数を管理するリソース
+#[derive(Resource)]
+pub struct SpawnQueue(pub usize);
+
+/// サンプリングしたポイントのクラスタリングの状態を管理するリソース
+#[derive(Resource)]
+pub struct Clustering {
+    pub enabled: bool,               // クラスタリングを行うかどうか
+    pub clusters: Vec<Vec<Cluster>>, // 図形ごとのクラスタ(SampledShapesと同じ順番、重心は図形の中心からの位置)
+    timer: Timer,                    // 計算し直すまでの時間
+}
+
+impl Default for Clustering {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            clusters: Vec::new(),
+            timer: Timer::from_seconds(CLUSTERING_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// 現在シーン内に存在するポイントの数を追跡するリソース
+#[derive(Resource, Debug)]
+pub struct PointCounter(pub usize);
+
+/// サンプリング(ランダムポイントを生成)される図形と、それぞれ位置(オフセット)を保持するリソース
+/// 図形のリストを管理する
+#[derive(Resource)]
+struct SampledShapes(Vec<(Shape, Vec3)>); // Vec<(図形, 位置情報)>
+
+// BGMの再生を管理するためのリソース
+#[derive(Resource)]
+struct BgmHandle(Handle<AudioInstance>);
+
+impl SampledShapes {
+    /// SampledShapesを新しく作成し、指定した図形を並べ方に従って並べる
+    fn new(shapes: Vec<Shape>, layout: LayoutMode, view: ViewMode) -> Self {
+        // 図形の数に応じた位置(横一列なら、中央を基準にして左右均等な間隔)を計算
+        let translations = layout.positions(shapes.len(), view);
+
+        // 図形とそれぞれの位置情報をセットで保存して返す
+        SampledShapes(shapes.into_iter().zip(translations).collect())
+    }
+
+    /// すべての図形の位置を並べ方に従って計算し直す
+    fn arrange(&mut self, layout: LayoutMode, view: ViewMode) {
+        let translations = layout.positions(self.0.len(), view);
+        for ((_, translation), new) in self.0.iter_mut().zip(translations) {
+            *translation = new;
+        }
+    }
+
+    /// 図形を最後に加え、加えた図形と位置を返す
+    /// 横一列では右端に置いて他の図形は動かさず、それ以外では全体を並べ直す
+    fn push(&mut self, shape: Shape, layout: LayoutMode, view: ViewMode) -> (Shape, Vec3) {
+        let translation = self
+            .0
+            .last()
+            .map_or(Vec3::ZERO, |(_, last)| *last + DISTANCE_BETWEEN_SHAPES);
+        self.0.push((shape.clone(), translation));
+        if layout != LayoutMode::Row {
+            self.arrange(layout, view);
+        }
+        (shape, self.0[self.0.len() - 1].1)
+    }
+
+    /// 指定した位置に最も近い図形のインデックス(カメラが注視している図形を探すのに使う)
+    fn closest(&self, target: Vec3) -> usize {
+        self.0
+            .iter()
+            .enumerate()
+            .min_by(|(_, (_, a)), (_, (_, b))| target.distance(*a).total_cmp(&target.distance(*b)))
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// 図形の大きさに比例して図形を選ぶための分布(どの図形でもポイントの密度が同じになる)
+    fn weighted_index(&self, mode: SamplingMode) -> WeightedIndex<f32> {
+        let measures = self.0.iter().map(|(shape, _)| shape.measure(mode));
+        WeightedIndex::new(measures).expect("図形の大きさは正の値になる")
+    }
+}
+
+/// サンプリング（ランダムに点を配置）可能な図形を示す列挙型
+#[derive(Clone)]
+enum Shape {
+    Cuboid,      // 直方体
+    Sphere,      // 球体
+    Capsule,     // カプセル型
+    Cylinder,    // 円柱
+    Tetrahedron, // 四面体
+    Triangle,    // 三角形
+    Torus,       // トーラス
+    Cone,        // 円錐
+    Frustum,     // 円錐台
+    // 以下は2Dの図形(XY平面上でサンプリングする)
+    Circle,     // 円
+    Rectangle,  // 長方形
+    Annulus,    // 円環
+    Triangle2d, // 2Dの三角形
+    // glTFから読み込んだメッシュ(3Dの図形の右端に加える)
+    CustomMesh(Arc<TriangleMesh>),
+    // 注視している図形と右隣の図形の和集合・共通部分(5キー、同じ中心に重ねる)
+    Composite(Arc<CompositeShape>),
+    // 図形の大きさを変えたもの(7・8キー、中心を基準に全体を倍率で拡大・縮小する)
+    Scaled(Box<Shape>, f32),
+}
+
+impl Shape {
+    /// 画面に表示する図形の名前のキー(assets/locale以下の対応表)
+    fn name_key(&self) -> &'static str {
+        match self {
+            Shape::Cuboid => "shape.cuboid",
+            Shape::Sphere => "shape.sphere",
+            Shape::Capsule => "shape.capsule",
+            Shape::Cylinder => "shape.cylinder",
+            Shape::Tetrahedron => "shape.tetrahedron",
+            Shape::Triangle => "shape.triangle",
+            Shape::Torus => "shape.torus",
+            Shape::Cone => "shape.cone",
+            Shape::Frustum => "shape.frustum",
+            Shape::Circle => "shape.circle",
+            Shape::Rectangle => "shape.rectangle",
+            Shape::Annulus => "shape.annulus",
+            Shape::Triangle2d => "shape.triangle_2d",
+            Shape::CustomMesh(_) => "shape.custom_mesh",
+            Shape::Composite(composite) => composite.operation().name_key(),
+            Shape::Scaled(shape, _) => shape.name_key(),
+        }
+    }
+
+    /// 大きさを変える前の図形
+    fn base(&self) -> &Shape {
+        match self {
+            Shape::Scaled(shape, _) => shape.as_ref(),
+            shape => shape,
+        }
+    }
+
+    /// 図形の大きさの倍率(変えていなければ1)
+    fn scale(&self) -> f32 {
+        match self {
+            Shape::Scaled(_, scale) => *scale,
+            _ => 1.0,
+        }
+    }
+
+    /// 大きさを変える前の図形を`scale`倍にした図形(倍率は重ねずに置き換える)
+    fn with_scale(&self, scale: f32) -> Shape {
+        if scale == 1.0 {
+            self.base().clone()
+        } else {
+            Shape::Scaled(Box::new(self.base().clone()), scale)
+        }
+    }
+
+    /// 平面の図形か(内部が面積、境界が周の長さになる)
+    fn is_planar(&self) -> bool {
+        match self {
+            Shape::Triangle
+            | Shape::Circle
+            | Shape::Rectangle
+            | Shape::Annulus
+            | Shape::Triangle2d => true,
+            // 合成するのは同じ次元の図形どうし
+            Shape::Composite(composite) => composite.shapes()[0].is_planar(),
+            Shape::Scaled(shape, _) => shape.is_planar(),
+            _ => false,
+        }
+    }
+
+    /// 点が図形の内部(境界を含む)にあるか(合成した図形の棄却法に使う)
+    /// 平面の図形はXY平面上の図形として判定する(Z座標は見ない)
+    fn contains_point(&self, point: Vec3) -> bool {
+        match self {
+            // メッシュはレイとの交点の数で判定する(符号付き距離より速い)
+            Shape::CustomMesh(mesh) => mesh.contains(point),
+            Shape::Composite(composite) => composite.contains_point(point),
+            Shape::Scaled(shape, scale) => shape.contains_point(point / *scale),
+            // それ以外の図形は、符号付き距離が0以下なら内部
+            shape => shape.signed_distance(point) <= 0.0,
+        }
+    }
+
+    /// サンプリングする範囲の大きさ(内部は体積、境界は表面積)
+    /// 平面の図形は、内部を面積、境界を周の長さにする
+    fn measure(&self, mode: SamplingMode) -> f32 {
+        let interior = mode != SamplingMode::Boundary; // ポアソンディスクも内部から選ぶ
+        match (self, interior) {
+            (Shape::Cuboid, true) => CUBOID.volume(),
+            (Shape::Cuboid, false) => CUBOID.area(),
+            (Shape::Sphere, true) => SPHERE.volume(),
+            (Shape::Sphere, false) => SPHERE.area(),
+            (Shape::Capsule, true) => CAPSULE_3D.volume(),
+            (Shape::Capsule, false) => CAPSULE_3D.area(),
+            (Shape::Cylinder, true) => CYLINDER.volume(),
+            (Shape::Cylinder, false) => CYLINDER.area(),
+            (Shape::Tetrahedron, true) => TETRAHEDRON.volume(),
+            (Shape::Tetrahedron, false) => TETRAHEDRON.area(),
+            (Shape::Triangle, true) => TRIANGLE_3D.area(),
+            (Shape::Triangle, false) => TRIANGLE_3D.perimeter(),
+            (Shape::Torus, true) => TORUS.volume(),
+            (Shape::Torus, false) => TORUS.area(),
+            (Shape::Cone, true) => CONE.volume(),
+            (Shape::Cone, false) => CONE.area(),
+            (Shape::Frustum, true) => frustum_volume(&CONICAL_FRUSTUM),
+            (Shape::Frustum, false) => frustum_area(&CONICAL_FRUSTUM),
+            (Shape::Circle, true) => CIRCLE.area(),
+            (Shape::Circle, false) => CIRCLE.perimeter(),
+            (Shape::Rectangle, true) => RECTANGLE.area(),
+            (Shape::Rectangle, false) => RECTANGLE.perimeter(),
+            (Shape::Annulus, true) => ANNULUS.area(),
+            (Shape::Annulus, false) => ANNULUS.perimeter(),
+            (Shape::Triangle2d, true) => TRIANGLE_2D.area(),
+            (Shape::Triangle2d, false) => TRIANGLE_2D.perimeter(),
+            (Shape::CustomMesh(mesh), true) => mesh.volume(),
+            (Shape::CustomMesh(mesh), false) => mesh.area(),
+            // 合成するときにモンテカルロ法で推定した値
+            (Shape::Composite(composite), _) => composite.measure(mode),
+            // 体積は倍率の3乗、面積は2乗、長さは1乗で変わる
+            (Shape::Scaled(shape, scale), _) => {
+                let dimension = match (shape.is_planar(), interior) {
+                    (false, true) => 3,
+                    (false, false) | (true, true) => 2,
+                    (true, false) => 1,
+                };
+                shape.measure(mode) * scale.powi(dimension)
+            }
+        }
+    }
+}
+
+/// Meshを生成するためのビルダー構造体（どのShapeかを保持）
+struct ShapeMeshBuilder {
+    shape: Shape,
+}
+
+impl Shape {
+    /// 実装済みのすべてのShapeをVecで返す
+    fn list_all_shapes() -> Vec<Shape> {
+        vec![
+            Shape::Cuboid,
+            Shape::Sphere,
+            Shape::Capsule,
+            Shape::Cylinder,
+            Shape::Tetrahedron,
+            Shape::Triangle,
+            Shape::Torus,
+            Shape::Cone,
+            Shape::Frustum,
+        ]
+    }
+
+    /// 2D表示で並べる図形をVecで返す
+    fn list_2d_shapes() -> Vec<Shape> {
+        vec![
+            Shape::Circle,
+            Shape::Rectangle,
+            Shape::Annulus,
+            Shape::Triangle2d,
+        ]
+    }
+}
+
+/// ランダムサンプリングの処理を定義するトレイト（ShapeSample）をShapeに実装
+/// 2Dの図形はVec2を返すので、Z座標を0にしてXY平面上の点にする
+impl ShapeSample for Shape {
+    type Output = Vec3;
+
+    /// 図形の「内部」をランダムにサンプリングして1つの点を返す
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        match self {
+            Shape::Cuboid => CUBOID.sample_interior(rng),
+            Shape::Sphere => SPHERE.sample_interior(rng),
+            Shape::Capsule => CAPSULE_3D.sample_interior(rng),
+            Shape::Cylinder => CYLINDER.sample_interior(rng),
+            Shape::Tetrahedron => TETRAHEDRON.sample_interior(rng),
+            Shape::Triangle => TRIANGLE_3D.sample_interior(rng),
+            // BevyにはないサンプリングなのでExtraShapeSampleを使う
+            Shape::Torus => TORUS.sample_interior(rng),
+            Shape::Cone => CONE.sample_interior(rng),
+            Shape::Frustum => CONICAL_FRUSTUM.sample_interior(rng),
+            Shape::Circle => CIRCLE.sample_interior(rng).extend(0.0),
+            Shape::Rectangle => RECTANGLE.sample_interior(rng).extend(0.0),
+            Shape::Annulus => ANNULUS.sample_interior(rng).extend(0.0),
+            Shape::Triangle2d => TRIANGLE_2D.sample_interior(rng).extend(0.0),
+            // 囲む箱から棄却法で選ぶ
+            Shape::CustomMesh(mesh) => mesh.sample_interior(rng),
+            // 一方の図形から選んだ点を、もう一方の図形の内外で棄却する
+            Shape::Composite(composite) => composite.sample_interior(rng),
+            Shape::Scaled(shape, scale) => shape.sample_interior(rng) * *scale,
+        }
+    }
+
+    /// 図形の「境界（表面）」をランダムにサンプリングして1つの点を返す
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Output {
+        match self {
+            Shape::Cuboid => CUBOID.sample_boundary(rng),
+            Shape::Sphere => SPHERE.sample_boundary(rng),
+            Shape::Capsule => CAPSULE_3D.sample_boundary(rng),
+            Shape::Cylinder => CYLINDER.sample_boundary(rng),
+            Shape::Tetrahedron => TETRAHEDRON.sample_boundary(rng),
+            Shape::Triangle => TRIANGLE_3D.sample_boundary(rng),
+            // BevyにはないサンプリングなのでExtraShapeSampleを使う
+            Shape::Torus => TORUS.sample_boundary(rng),
+            Shape::Cone => CONE.sample_boundary(rng),
+            Shape::Frustum => CONICAL_FRUSTUM.sample_boundary(rng),
+            Shape::Circle => CIRCLE.sample_boundary(rng).extend(0.0),
+            Shape::Rectangle => RECTANGLE.sample_boundary(rng).extend(0.0),
+            Shape::Annulus => ANNULUS.sample_boundary(rng).extend(0.0),
+            Shape::Triangle2d => TRIANGLE_2D.sample_boundary(rng).extend(0.0),
+            // 三角形を面積に比例して選ぶ
+            Shape::CustomMesh(mesh) => mesh.sample_boundary(rng),
+            Shape::Composite(composite) => composite.sample_boundary(rng),
+            Shape::Scaled(shape, scale) => shape.sample_boundary(rng) * *scale,
+        }
+    }
+}
+
+/// モンテカルロ法で体積・表面積を推定するため、符号付き距離(monte_carloモジュール)をShapeに実装
+impl SignedDistance for Shape {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        match self {
+            Shape::Cuboid => CUBOID.signed_distance(point),
+            Shape::Sphere => SPHERE.signed_distance(point),
+            Shape::Capsule => CAPSULE_3D.signed_distance(point),
+            Shape::Cylinder => CYLINDER.signed_distance(point),
+            Shape::Tetrahedron => TETRAHEDRON.signed_distance(point),
+            Shape::Triangle => TRIANGLE_3D.signed_distance(point),
+            Shape::Torus => TORUS.signed_distance(point),
+            Shape::Cone => CONE.signed_distance(point),
+            Shape::Frustum => CONICAL_FRUSTUM.signed_distance(point),
+            Shape::Circle => CIRCLE.signed_distance(point),
+            Shape::Rectangle => RECTANGLE.signed_distance(point),
+            Shape::Annulus => ANNULUS.signed_distance(point),
+            Shape::Triangle2d => TRIANGLE_2D.signed_distance(point),
+            Shape::CustomMesh(mesh) => mesh.signed_distance(point),
+            Shape::Composite(composite) => composite.signed_distance(point),
+            // 縮めた座標で測った距離を倍率で戻す
+            Shape::Scaled(shape, scale) => shape.signed_distance(point / *scale) * *scale,
+        }
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        match self {
+            Shape::Cuboid => CUBOID.half_extents(),
+            Shape::Sphere => SPHERE.half_extents(),
+            Shape::Capsule => CAPSULE_3D.half_extents(),
+            Shape::Cylinder => CYLINDER.half_extents(),
+            Shape::Tetrahedron => TETRAHEDRON.half_extents(),
+            Shape::Triangle => TRIANGLE_3D.half_extents(),
+            Shape::Torus => TORUS.half_extents(),
+            Shape::Cone => CONE.half_extents(),
+            Shape::Frustum => CONICAL_FRUSTUM.half_extents(),
+            Shape::Circle => CIRCLE.half_extents(),
+            Shape::Rectangle => RECTANGLE.half_extents(),
+            Shape::Annulus => ANNULUS.half_extents(),
+            Shape::Triangle2d => TRIANGLE_2D.half_extents(),
+            Shape::CustomMesh(mesh) => mesh.half_extents(),
+            Shape::Composite(composite) => composite.half_extents(),
+            Shape::Scaled(shape, scale) => shape.half_extents() * *scale,
+        }
+    }
+}
+
+/// Mesh化（3D描画可能な形式への変換）を行うトレイト（Meshable）をShapeに実装
+impl Meshable for Shape {
+    type Output = ShapeMeshBuilder;
+
+    /// このShapeからMeshビルダーを生成する
+    fn mesh(&self) -> Self::Output {
+        ShapeMeshBuilder {
+            shape: self.clone(),
+        }
+    }
+}
+
+/// Mesh生成処理をShapeMeshBuilderに実装する
+impl MeshBuilder for ShapeMeshBuilder {
+    /// 実際にMesh（描画用オブジェクト）を構築する関数
+    fn build(&self) -> Mesh {
+        match &self.shape {
+            Shape::Cuboid => CUBOID.mesh().into(),
+            Shape::Sphere => SPHERE.mesh().into(),
+            Shape::Capsule => CAPSULE_3D.mesh().into(),
+            Shape::Cylinder => CYLINDER.mesh().into(),
+            Shape::Tetrahedron => TETRAHEDRON.mesh().into(),
+            Shape::Triangle => TRIANGLE_3D.mesh().into(),
+            Shape::Torus => TORUS.mesh().into(),
+            Shape::Cone => CONE.mesh().into(),
+            Shape::Frustum => CONICAL_FRUSTUM.mesh().into(),
+            // 2Dの図形のメッシュはXY平面上に作られる
+            Shape::Circle => CIRCLE.mesh().into(),
+            Shape::Rectangle => RECTANGLE.mesh().into(),
+            Shape::Annulus => ANNULUS.mesh().into(),
+            Shape::Triangle2d => TRIANGLE_2D.mesh().into(),
+            Shape::CustomMesh(mesh) => mesh.to_mesh(),
+            // 2つの図形のメッシュを重ねて表示する
+            Shape::Composite(composite) => composite.mesh(),
+            Shape::Scaled(shape, scale) => shape.mesh().build().scaled_by(Vec3::splat(*scale)),
+        }
+    }
+}
+
+impl ShapeMeshBuilder {
+    /// 三角形の辺を線にしたワイヤーフレームのMeshを構築する関数(図形のMeshと同じ位置に重なる)
+    fn build_wireframe(&self) -> Mesh {
+        wireframe::wireframe_mesh(&self.build())
+    }
+}
+
+/// このサンプルで使用する乱数生成器を保持するリソース
+/// 疑似乱数(ChaCha)と準乱数(Halton列)のどちらかで、`RandomGenerator`に合わせて作り直す
+#[derive(Resource, Clone)]
+enum RandomSource {
+    PseudoRandom(ChaCha8Rng), // 疑似乱数
+    QuasiRandom(QuasiRandom), // 準乱数(ポイントごとにstart_pointで次の点に進む)
+}
+
+impl RandomSource {
+    /// 種類とシード値から作る
+    fn new(generator: RandomGenerator, seed: &RngSeed) -> Self {
+        match generator {
+            RandomGenerator::PseudoRandom => RandomSource::PseudoRandom(seed.rng()),
+            RandomGenerator::QuasiRandom => RandomSource::QuasiRandom(QuasiRandom::new(seed.seed)),
+        }
+    }
+
+    /// 乱数生成器の種類
+    fn generator(&self) -> RandomGenerator {
+        match self {
+            RandomSource::PseudoRandom(_) => RandomGenerator::PseudoRandom,
+            RandomSource::QuasiRandom(_) => RandomGenerator::QuasiRandom,
+        }
+    }
+
+    /// 同じ種類のまま、シード値から始め直す
+    fn reseed(&mut self, seed: &RngSeed) {
+        *self = Self::new(self.generator(), seed);
+    }
+
+    /// 新しいポイントの乱数を使い始める(準乱数では列の次の点に進む)
+    fn start_point(&mut self) {
+        if let RandomSource::QuasiRandom(quasi) = self {
+            quasi.start_point();
+        }
+    }
+
+    /// 並列に計算するバッチ用に、`count`個のポイント分の乱数生成器を切り出す
+    /// 疑似乱数はこの乱数から取ったシード値で作り、準乱数は列の続きを渡して自分は先に進む
+    fn split(&mut self, count: usize) -> Self {
+        match self {
+            RandomSource::PseudoRandom(rng) => {
+                RandomSource::PseudoRandom(ChaCha8Rng::seed_from_u64(rng.r#gen()))
+            }
+            RandomSource::QuasiRandom(quasi) => RandomSource::QuasiRandom(quasi.split(count)),
+        }
+    }
+}
+
+impl RngCore for RandomSource {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            RandomSource::PseudoRandom(rng) => rng.next_u32(),
+            RandomSource::QuasiRandom(quasi) => quasi.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            RandomSource::PseudoRandom(rng) => rng.next_u64(),
+            RandomSource::QuasiRandom(quasi) => quasi.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            RandomSource::PseudoRandom(rng) => rng.fill_bytes(dest),
+            RandomSource::QuasiRandom(quasi) => quasi.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            RandomSource::PseudoRandom(rng) => rng.try_fill_bytes(dest),
+            RandomSource::QuasiRandom(quasi) => quasi.try_fill_bytes(dest),
+        }
+    }
+}
+
+/// 乱数生成器のシード値を保持するリソース
+/// 同じシード値から始めれば、同じ操作で同じポイントが生成される(実験を再現できる)
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct RngSeed {
+    pub seed: u64,             // 現在のシード値
+    pub reseed_on_reset: bool, // 「R」キーのリスタートで、乱数をシード値から始め直すか
+}
+
+impl Default for RngSeed {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SEED,
+            reseed_on_reset: false,
+        }
+    }
+}
+
+impl RngSeed {
+    /// コマンドライン引数からシード値の設定を読み取る
+    /// `--seed <整数>`でシード値を、`--reseed-on-reset`でリスタート時の再シードを指定する
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut seed = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    seed.seed = args
+                        .next()
+                        .and_then(|value| value.parse().ok())
+                        .expect("--seed には0以上の整数を指定してください");
+                }
+                "--reseed-on-reset" => seed.reseed_on_reset = true,
+                _ => {} // 他の引数(--headlessなど)は無視する
+            }
+        }
+        seed
+    }
+
+    /// シード値から始めた乱数生成器を作る
+    fn rng(&self) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(self.seed)
+    }
+}
+
+/// クラスタの重心を表示する球体のMeshとマテリアルを保持するリソース
+#[derive(Resource)]
+struct ClusterMarkerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// サンプリングされたポイントを示すマーカーコンポーネント
+/// マーカーコンポーネントは、特定の機能や役割を持つエンティティを示すために使用される
+/// これらがついているエンティティだけに特定の処理を適用することができる
+/// ポイントのエンティティはメッシュを持たず、instancingモジュールでまとめて描画する
+#[derive(Component)]
+pub struct SamplePoint;
+
+/// ポイントをサンプリングした図形(SampledShapesのインデックス)
+#[derive(Component)]
+pub struct ShapeIndex(pub usize);
+
+/// クラスタの重心を示すマーカーのコンポーネント
+#[derive(Component)]
+pub struct ClusterMarker;
+
+/// クラスタの一覧を表示するパネルのマーカーコンポーネント
+#[derive(Component)]
+struct ClusterPanel;
+
+/// クラスタの一覧の、図形ごとの行(SampledShapesのインデックス)
+#[derive(Component)]
+struct ClusterLine(usize);
+
+/// ポイントが生成される時のアニメーションを管理するコンポーネント
+/// マイフレームこの値を更新する
+#[derive(Component)]
+struct SpawningPoint {
+    progress: f32, // アニメーションの進行度（0.0から1.0）
+}
+
+/// ポイントが削除される時のアニメーションを管理するコンポーネント
+#[derive(Component)]
+struct DespawningPoint {
+    progress: f32,     // アニメーションの進行度（0.0から1.0）
+    from: Option<f32>, // 削除を始めたときのスケール(最初のフレームで記録する)
+}
+
+/// 図形のメッシュのマーカーコンポーネント(表示を切り替えるときに作り直す、ライトは子なので一緒に消える)
+#[derive(Component)]
+struct ShapeVisual;
+
+/// 図形ごとの親エンティティ(図形のメッシュ)に付けるコンポーネント(SampledShapesのインデックス)
+/// ポイントとクラスタの重心のマーカーはこの子にして、図形の中心からの位置に置く(図形と一緒に回転する)
+#[derive(Component)]
+pub struct ShapeRoot(pub usize);
+
+/// 図形の表示に使う半透明なマテリアルのハンドルを保持するリソース
+#[derive(Resource)]
+struct ShapeMaterial(Handle<StandardMaterial>);
+
+
+/// マウスが押されているかどうかを示すリソース(カメラ操作用)
+#[derive(Resource)]
+struct MousePressed(bool);
+
+/// カメラの動きを管理するためのコンポーネント
+#[derive(Component)]
+struct CameraRig {
+    /// カメラの水平方向（左右）の回転角度（ラジアン）
+    /// 正の値が増えると右方向から見る形になる
+    pub yaw: f32,
+
+    /// カメラの垂直方向（上下）の回転角度（ラジアン、-π/2〜π/2）
+    /// 正の値は上から下を見下ろす視点
+    pub pitch: f32,
+
+    pub current_distance: f32, // 現在のズームレベル
+    pub target_distance: f32,  // 目的のズームレベル（徐々にこれに近づける）
+
+    /// カメラが注視している、または周囲を回転する対象点の位置（3D空間座標）
+    /// これがカメラの中心点となる
+    pub target: Vec3,
+}
+
+/////////// 関数定義 ///////////
+
+/// アプリのセットアップ処理を行う関数
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>, // メッメッシュ(3D)を管理するためのAssetsリソース
+    mut materials: ResMut<Assets<StandardMaterial>>, // マテリアル(材料)を管理するためのAssetsリソース
+    shapes: Res<SampledShapes>, // サンプリング対象の図形を保持するSampledShapesリソース
+    seed: Res<RngSeed>,         // 乱数のシード値
+    config: Res<DemoConfig>,    // ポイントの色(図形のライトの色に使う)
+    bindings: Res<KeyBindings>, // 操作説明に表示するキーの割り当て
+    palette: Res<PointPalette>, // 操作説明に表示するパレットの名前
+    generator: Res<RandomGenerator>, // 疑似乱数・準乱数のどちらを使うか
+    layout: Res<LayoutMode>,         // 操作説明に表示する図形の並べ方の名前
+) {
+    // シード値を指定して乱数生成器を初期化
+    commands.insert_resource(RandomSource::new(*generator, &seed)); // 乱数生成器をリソースとして登録
+
+    // 地面となる平面作成して配置する
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(20.0, 20.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.3), // 地面の色
+            perceptual_roughness: 0.95,             // 光沢感
+            metallic: 0.0,                          // 金属感
+            ..default()
+        })),
+        Transform::from_xyz(0.0, GROUND_HEIGHT, 0.0), // 地面の位置
+    ));
+
+    // 図形表示用の半透明なマテリアルを作成
+    let shape_material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.2, 0.1, 0.6, 0.3), // 半透明の青紫色
+        metallic: 0.0,                                // 金属感なし
+        perceptual_roughness: 1.0,                    // 反射率の逆数相当
+        alpha_mode: AlphaMode::Blend,                 // 透明モード
+        cull_mode: None,                              // 裏面も描画する
+        ..default()
+    });
+
+    // 各図形を並べて配置する
+    let light_color = config.point_colors.interior;
+    spawn_shape_visuals(&mut commands, &mut meshes, &shape_material, &shapes, light_color);
+    commands.insert_resource(ShapeMaterial(shape_material));
+
+    // 全体を照らすためのグローバルなライトを配置
+    commands.spawn((
+        PointLight {
+            color: SKY_COLOR,       // 環境光の色
+            intensity: 2_000.0,     // 光の強さ
+            shadows_enabled: false, // 影はなし
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+
+    // カメラを設定して初期位置に配置する
+    commands.spawn((
+        Camera3d::default(), // デフォルトの3Dカメラを使用
+        Transform::from_xyz(-2.0, 3.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y), // カメラの初期位置
+        CameraRig {
+            yaw: 0.56,             // 水平方向の角度
+            pitch: 0.45,           // 垂直方向の角度
+            current_distance: 8.0, // 現在のズーム距離
+            target_distance: 8.0,  // 目的のズーム距離
+            target: Vec3::ZERO,    // 注視点
+        },
+        // ポイントのインスタンス描画は間接描画(indirect draw)に対応していない
+        NoIndirectDrawing,
+    ));
+
+    // ポイントを表示する四角形のMesh(シェーダーでカメラに向けた円にする)を、インスタンス描画の1つのエンティティにする
+    // インスタンスの位置はメッシュの範囲外に広がるので、視錐台カリングはしない
+    commands.spawn((
+        Mesh3d(meshes.add(Rectangle::from_length(2.0 * POINT_RADIUS))),
+        PointInstances::default(),
+        NoFrustumCulling,
+    ));
+    commands.insert_resource(ClusterMarkerAssets {
+        mesh: meshes.add(Sphere::new(0.09).mesh().ico(2).unwrap()),
+        material: materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            emissive: 3.0 * CLUSTER_MARKER_COLOR, // 重心の発光色
+            ..default()
+        }),
+    });
+
+    // ユーザー向けの操作説明テキストを画面に表示
+    commands.spawn((
+        help_text(&seed, &bindings, *palette, *generator, *layout), // 文章はassets/locale以下の対応表から読み込む
+        HelpText,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+    ));
+
+    // クラスタの一覧を画面の左下に表示(クラスタリングが有効な間だけ表示)
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                left: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            Visibility::Hidden,
+            ClusterPanel,
+        ))
+        .with_children(|panel| {
+            // 図形ごとの行はrebuild_cluster_linesで追加する
+            panel.spawn(LocalizedText::new("clusters.title").with_arg("k", CLUSTERS_PER_SHAPE));
+        });
+
+    commands.insert_resource(SpawnQueue(0)); // ポイント生成キューを初期化
+
+    commands.insert_resource(PointCounter(0)); // 現在のポイント数を初期化
+
+    commands.insert_resource(SamplingMode::Interior); // 初期は内部サンプリング
+
+    commands.insert_resource(SpawningMode::Automatic); // 初期は自動生成
+
+    commands.insert_resource(MousePressed(false)); // マウスの押下状態を初期化
+}
+
+/// 操作説明のテキストのマーカーコンポーネント
+#[derive(Component)]
+struct HelpText;
+
+/// 現在のシード値を入れた操作説明の文章
+fn help_text(
+    seed: &RngSeed,
+    bindings: &KeyBindings,
+    palette: PointPalette,
+    generator: RandomGenerator,
+    layout: LayoutMode,
+) -> LocalizedText {
+    // キーの名前は「reseed」の文章の中でも使うので、その後に置き換える
+    bindings.with_labels(
+        LocalizedText::new("help")
+            .with_arg("seed", seed.seed)
+            .with_key_arg("palette", palette.name_key())
+            .with_key_arg("generator", generator.name_key())
+            .with_key_arg("layout", layout.name_key())
+            .with_key_arg(
+                "reseed",
+                if seed.reseed_on_reset {
+                    "help.reseed_on"
+                } else {
+                    "help.reseed_off"
+                },
+            ),
+    )
+}
+
+// シード値かキーの割り当てが変わったら、操作説明の文章を更新するシステム
+fn update_help_text(
+    seed: Res<RngSeed>,
+    bindings: Res<KeyBindings>,
+    palette: Res<PointPalette>,
+    generator: Res<RandomGenerator>,
+    layout: Res<LayoutMode>,
+    mut texts: Query<&mut LocalizedText, With<HelpText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.set_if_neq(help_text(&seed, &bindings, *palette, *generator, *layout));
+    }
+}
+
+/// 図形のメッシュとポイントライトを並べて配置する関数
+fn spawn_shape_visuals(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    shape_material: &Handle<StandardMaterial>,
+    shapes: &SampledShapes,
+    light_color: LinearRgba,
+) {
+    for (index, (shape, transform)) in shapes.0.iter().enumerate() {
+        spawn_shape_visual(commands, meshes, shape_material, index, shape, *transform, light_color);
+    }
+}
+
+/// 1つの図形のメッシュとポイントライトを配置する関数(メッシュがポイントの親になる)
+fn spawn_shape_visual(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    shape_material: &Handle<StandardMaterial>,
+    index: usize,
+    shape: &Shape,
+    transform: Vec3,
+    light_color: LinearRgba,
+) {
+    // 図形を透明で表示
+    let root = commands
+        .spawn((
+            Mesh3d(meshes.add(shape.mesh())),
+            MeshMaterial3d(shape_material.clone()), // 半透明マテリアルを適用
+            Transform::from_translation(transform), // 位置を設定
+            ShapeVisual,
+            ShapeRoot(index), // ポイントの親(回転させると、ポイントも一緒に回る)
+        ))
+        .id();
+
+    // ポイントライトを図形の中心に配置(蛍の光のように)
+    // 図形の子にするので、並べ方を変えて図形が動くとライトも一緒に動く
+    commands.spawn((
+        firefly_light(light_color, shape.scale()), // 初期は内部のポイントの色
+        ChildOf(root),                             // 図形と一緒に消える
+    ));
+}
+
+/// サンプルの操作(キーボードとゲームパッドの両方に割り当てる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleAction {
+    Reset,             // すべてのポイントを削除
+    SpawnOne,          // ポイントを1個生成予約
+    SpawnHundred,      // ポイントを100個生成予約
+    ToggleSampling,    // サンプリングモード(内部 or 境界)の切り替え
+    ToggleSpawning,    // ポイント生成モード(自動 or 手動)の切り替え
+    ToggleHelp,        // ヘルプメニューの表示切り替え
+    ZoomOut,           // カメラを遠ざける
+    ZoomIn,            // カメラを近づける
+    PreviousShape,     // 左の図形を注視する
+    NextShape,         // 右の図形を注視する
+    ToggleClustering,  // クラスタリングの有効・無効を切り替える
+    ToggleView,        // 3Dと2Dの表示を切り替える
+    ExportPoints,      // ポイントをPLYとCSVに書き出す
+    Reseed,            // 新しいランダムなシード値でリスタートする
+    ToggleMonteCarlo,  // モンテカルロ法による推定の有効・無効を切り替える
+    ToggleDensity,     // 密度による色分けの有効・無効を切り替える
+    ToggleHistogram,   // 軸ごとのヒストグラムの表示を切り替える
+    ToggleProjection,  // カメラの透視投影と正射影を切り替える
+    CameraFront,       // カメラを正面からの視点にする
+    CameraTop,         // カメラを真上からの視点にする
+    CameraIsometric,   // カメラを斜め上からの等角の視点にする
+    CameraCloseUp,     // カメラを注視している図形に近づける
+    TogglePause,       // ポイントの生成・削除の一時停止を切り替える
+    SlowDown,          // ポイントの生成・削除の速さを半分にする
+    SpeedUp,           // ポイントの生成・削除の速さを2倍にする
+    ToggleRecording,   // 生成・削除の記録を開始・保存する
+    TogglePlayback,    // 最後に保存した記録の再生を開始・停止する
+    ToggleDiagnostics, // FPSやポイント数のオーバーレイの表示を切り替える
+    ToggleLifetime,    // ポイントの寿命による削除の有効・無効を切り替える
+    CycleEasing,       // 生成・削除アニメーションのイージングを切り替える
+    ToggleRotation,    // 図形(とポイント)の回転の有効・無効を切り替える
+    ToggleWireframe,   // 図形のワイヤーフレームの表示を切り替える
+    ShrinkPoints,      // ポイントの表示を小さくする
+    GrowPoints,        // ポイントの表示を大きくする
+    ToggleConvexHull,  // 注視している図形のポイントの凸包の表示を切り替える
+    SlowerSpawning,    // 1フレームあたりの生成数を半分にする
+    FasterSpawning,    // 1フレームあたりの生成数を2倍にする
+    CyclePalette,      // ポイントの色のパレットを切り替える
+    ToggleStatistics,  // 図形ごとのポイントの重心・分散の表示を切り替える
+    ToggleGenerator,   // 疑似乱数と準乱数(Halton列)を切り替える
+    CycleLayout,       // 図形の並べ方(横一列・格子・円周)を切り替える
+    ToggleShadows,     // ポイントを地面に落とした影の表示を切り替える
+    ToggleTurntable,   // カメラを自動で周回させるかを切り替える
+    ShrinkShape,       // 注視している図形を小さくする
+    GrowShape,         // 注視している図形を大きくする
+    CycleComposite,    // 注視している図形を右隣の図形との和集合・共通部分に切り替える
+    TogglePhysics,     // ポイントが図形から離れて地面に落ちる物理モードを切り替える
+    SaveSession,       // ポイントと乱数生成器の状態を保存する(Ctrl+S、割り当ては固定)
+    LoadSession,       // 保存したポイントと乱数生成器の状態を読み込む(Ctrl+L、割り当ては固定)
+}
+
+/// 操作の割り当て(キーボードはKeyBindingsから、Ctrlキーとの組み合わせとゲームパッドのボタンは固定)
+fn action_map(bindings: &KeyBindings) -> ActionMap<SampleAction> {
+    let mut map = ActionMap::new();
+    for (action, _) in key_bindings::ACTIONS {
+        for key in bindings.keys(action) {
+            map = map.key(action, *key);
+        }
+    }
+    map.ctrl_key(SampleAction::SaveSession, KeyCode::KeyS)
+        .ctrl_key(SampleAction::LoadSession, KeyCode::KeyL)
+        .button(SampleAction::Reset, GamepadButton::West)
+        .button(SampleAction::SpawnOne, GamepadButton::South)
+        .button(SampleAction::SpawnHundred, GamepadButton::East)
+        .button(SampleAction::ToggleSampling, GamepadButton::North)
+        .button(SampleAction::ToggleSpawning, GamepadButton::Select)
+        .button(SampleAction::ToggleHelp, GamepadButton::Start)
+        .button(SampleAction::PreviousShape, GamepadButton::DPadLeft)
+        .button(SampleAction::PreviousShape, GamepadButton::LeftTrigger)
+        .button(SampleAction::NextShape, GamepadButton::DPadRight)
+        .button(SampleAction::NextShape, GamepadButton::RightTrigger)
+        .button(SampleAction::ToggleClustering, GamepadButton::DPadUp)
+        .button(SampleAction::ToggleView, GamepadButton::DPadDown)
+        .button(SampleAction::ExportPoints, GamepadButton::LeftThumb)
+        .button(SampleAction::Reseed, GamepadButton::RightThumb)
+}
+
+// キーボード・ゲームパッドの入力を処理するシステム
+fn handle_keypress(
+    mut commands: Commands, // エンティティの生成・削除を行うためのコマンド
+    actions: Res<ButtonInput<SampleAction>>, // キーボード・ゲームパッドの入力状態
+    mut mode: ResMut<SamplingMode>, // サンプリングモード（内部 or 境界）
+    mut spawn_mode: ResMut<SpawningMode>, // ポイント生成モード（自動 or 手動）
+    samples: Query<Entity, With<SamplePoint>>, // 現在存在する全てのポイント
+    shapes: Res<SampledShapes>, // 配置されている図形のデータ
+    mut spawn_queue: ResMut<SpawnQueue>, // ポイント生成予約のキュー
+    mut counter: ResMut<PointCounter>, // 現在のポイント数を管理
+    mut text_menus: Query<&mut Visibility, With<Text>>, // UIテキストの表示・非表示を管理
+    mut camera_rig: Query<&mut CameraRig>, // カメラ操作用のコンポーネント
+    mut random_source: ResMut<RandomSource>, // 乱数生成器
+    mut seed: ResMut<RngSeed>, // 乱数のシード値
+    mut camera_projection: ResMut<CameraProjection>, // カメラの投影方法
+    config: Res<DemoConfig>, // カメラの距離の範囲
+) {
+    // Queryから一意のカメラリグを取得
+    let mut camera_rig = camera_rig.single_mut().unwrap();
+
+    // 「N」キー：新しいランダムなシード値にして、リスタートする
+    if actions.just_pressed(SampleAction::Reseed) {
+        seed.seed = rand::random();
+        random_source.reseed(&seed);
+        info!("乱数のシード値を{}にしました", seed.seed);
+    }
+
+    // 「R」キー：すべてのポイントを削除してリセット(設定によっては乱数もシード値から始め直す)
+    if actions.just_pressed(SampleAction::Reset) || actions.just_pressed(SampleAction::Reseed) {
+        counter.0 = 0; // ポイント数をゼロにリセット
+        for entity in &samples {
+            commands.entity(entity).despawn(); // 各ポイントを削除
+        }
+        if seed.reseed_on_reset {
+            random_source.reseed(&seed);
+        }
+    }
+
+    // 「S」キー：ポイントを1個生成予約
+    if actions.just_pressed(SampleAction::SpawnOne) {
+        spawn_queue.0 += 1;
+    }
+
+    // 「D」キー：ポイントを100個生成予約
+    if actions.just_pressed(SampleAction::SpawnHundred) {
+        spawn_queue.0 += 100;
+    }
+
+    // 「M」キー：サンプリングモード（内部 → 境界 → ポアソンディスク）を切り替え
+    if actions.just_pressed(SampleAction::ToggleSampling) {
+        *mode = match *mode {
+            SamplingMode::Interior => SamplingMode::Boundary,
+            SamplingMode::Boundary => SamplingMode::PoissonDisk,
+            SamplingMode::PoissonDisk => SamplingMode::Interior,
+        };
+    }
+
+    // 「A」キー：ポイント生成モード（自動 or 手動）を切り替え
+    if actions.just_pressed(SampleAction::ToggleSpawning) {
+        *spawn_mode = match *spawn_mode {
+            SpawningMode::Manual => SpawningMode::Automatic,
+            SpawningMode::Automatic => SpawningMode::Manual,
+        };
+    }
+
+    // 「O」キー：カメラの透視投影と正射影を切り替え
+    if actions.just_pressed(SampleAction::ToggleProjection) {
+        *camera_projection = match *camera_projection {
+            CameraProjection::Perspective => CameraProjection::Orthographic,
+            CameraProjection::Orthographic => CameraProjection::Perspective,
+        };
+    }
+
+    // 「Tab」キー：画面上のヘルプメニューの表示・非表示を切り替え
+    if actions.just_pressed(SampleAction::ToggleHelp) {
+        for mut visibility in text_menus.iter_mut() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+
+    // 「-」キー：カメラをズームアウト（距離を遠ざける）
+    if actions.just_pressed(SampleAction::ZoomOut) {
+        camera_rig.target_distance += config.max_camera_distance / 15.0;
+        // 距離の範囲制限
+        camera_rig.target_distance = config.clamp_camera_distance(camera_rig.target_distance);
+    }
+
+    // 「+」キー：カメラをズームイン（距離を近づける）
+    if actions.just_pressed(SampleAction::ZoomIn) {
+        camera_rig.target_distance -= config.max_camera_distance / 15.0;
+        // 距離の範囲制限
+        camera_rig.target_distance = config.clamp_camera_distance(camera_rig.target_distance);
+    }
+
+    // 「←」および「→」キー：カメラの注視する対象を左右の図形に切り替える
+    let left = actions.just_pressed(SampleAction::PreviousShape);
+    let right = actions.just_pressed(SampleAction::NextShape);
+
+    if left || right {
+        // 現在のターゲットに最も近い図形を検索
+        let closest = shapes.closest(camera_rig.target);
+
+        // 左キーなら1つ左の図形へ移動（可能な場合）
+        if closest > 0 && left {
+            camera_rig.target = shapes.0[closest - 1].1;
+        }
+
+        // 右キーなら1つ右の図形へ移動（可能な場合）
+        if closest < shapes.0.len() - 1 && right {
+            camera_rig.target = shapes.0[closest + 1].1;
+        }
+    }
+}
+
+// 「9」「0」キー：ポイントの表示の大きさの倍率を小さく・大きくするシステム
+fn handle_point_size_keys(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut params: ResMut<SamplingParams>,
+) {
+    let factor = if actions.just_pressed(SampleAction::ShrinkPoints) {
+        1.0 / POINT_SIZE_STEP
+    } else if actions.just_pressed(SampleAction::GrowPoints) {
+        POINT_SIZE_STEP
+    } else {
+        return;
+    };
+    params.resize_points(factor);
+}
+
+// 「PageDown」「PageUp」キー：自動モードで1フレームあたりに生成するポイント数を半分・2倍にするシステム
+fn handle_spawn_rate_keys(actions: Res<ButtonInput<SampleAction>>, mut rate: ResMut<SpawnRate>) {
+    if actions.just_pressed(SampleAction::SlowerSpawning) {
+        rate.step(false);
+    }
+    if actions.just_pressed(SampleAction::FasterSpawning) {
+        rate.step(true);
+    }
+}
+
+// マウス操作を処理し、カメラのズームや回転を行うシステム
+fn handle_mouse(
+    accumulated_mouse_motion: Res<AccumulatedMouseMotion>, // マウスの動きを蓄積したデータ
+    accumulated_mouse_scroll: Res<AccumulatedMouseScroll>, // マウスのスクロールホイールの動きを蓄積したデータ
+    mut button_events: EventReader<MouseButtonInput>,      // マウスボタンの入力イベントを取得
+    mut camera_query: Query<&mut CameraRig>, // カメラの位置や回転、ズームを管理するコンポーネント
+    mut mouse_pressed: ResMut<MousePressed>, // マウスが押されているかどうかの状態
+    control_panel: Res<ControlPanel>,        // 操作パネルの上ではカメラを動かさない
+    config: Res<DemoConfig>,                 // カメラの距離の範囲
+) {
+    // Queryから一意のカメラリグを取得
+    let mut camera_rig = camera_query.single_mut().unwrap();
+
+    // マウス左ボタンの押下・解放イベントを処理し、状態を更新
+    for button_event in button_events.read() {
+        if button_event.button != MouseButton::Left {
+            continue; // 左ボタン以外は無視
+        }
+        // 左ボタンの押下状態を更新（true: 押下中, false: 離された状態）
+        *mouse_pressed = MousePressed(button_event.state.is_pressed());
+    }
+
+    // 操作パネルのスライダーなどを操作している間は、回転もズームもしない
+    if control_panel.wants_pointer {
+        return;
+    }
+
+    // マウスホイールのスクロールによるズーム操作
+    if accumulated_mouse_scroll.delta != Vec2::ZERO {
+        // ホイールの動きを使ってズーム距離を調整
+        let mouse_scroll = accumulated_mouse_scroll.delta.y;
+        camera_rig.target_distance -= mouse_scroll / 15.0 * config.max_camera_distance;
+
+        // カメラの距離が指定範囲内に収まるよう調整
+        camera_rig.target_distance = config.clamp_camera_distance(camera_rig.target_distance);
+    }
+
+    // マウス左ボタンが押されていない場合、回転操作を行わない
+    if !mouse_pressed.0 {
+        return;
+    }
+
+    // マウスのドラッグ（動き）によるカメラ回転操作
+    if accumulated_mouse_motion.delta != Vec2::ZERO {
+        let displacement = accumulated_mouse_motion.delta;
+
+        // 水平方向の動きに応じてカメラを左右に回転（yaw）
+        camera_rig.yaw += displacement.x / 90.0;
+
+        // 垂直方向の動きに応じてカメラを上下に回転（pitch）
+        camera_rig.pitch += displacement.y / 90.0;
+
+        // 上下の回転が行き過ぎてしまわないように、ピッチ角を制限
+        camera_rig.pitch = camera_rig.pitch.clamp(-PI / 2.01, PI / 2.01);
+    }
+}
+
+// 右スティックの入力でカメラを回転させ、トリガーの押し込み量でズームするシステム
+// マウスやキーボードの操作とは別に、スティックとトリガーの量を毎フレーム足し込む
+fn handle_gamepad_camera(
+    axes: Res<ActionAxes>,                   // スティックとトリガーの入力
+    mut camera_query: Query<&mut CameraRig>, // カメラ操作用のコンポーネント
+    time: Res<Time>,                         // 経過時間
+    config: Res<DemoConfig>,                 // カメラの距離の範囲
+) {
+    if axes.look == Vec2::ZERO && axes.triggers == 0.0 {
+        return;
+    }
+
+    let mut camera_rig = camera_query.single_mut().unwrap();
+    let rotation = axes.look * STICK_ROTATION_SPEED * time.delta_secs();
+
+    // マウスのドラッグと同じ向きに回転させる(スティックを上に倒すと見上げる)
+    camera_rig.yaw += rotation.x;
+    camera_rig.pitch = (camera_rig.pitch - rotation.y).clamp(-PI / 2.01, PI / 2.01);
+
+    // 右トリガー(RT)で近づき、左トリガー(LT)で遠ざかる
+    let zoom = axes.triggers * TRIGGER_ZOOM_SPEED * config.max_camera_distance * time.delta_secs();
+    camera_rig.target_distance = config.clamp_camera_distance(camera_rig.target_distance - zoom);
+}
+
+// ポイントを新しく生成するシステム
+fn spawn_points(
+    mut commands: Commands,                  // エンティティ生成用コマンド
+    mode: ResMut<SamplingMode>,              // サンプリングモード（内部 or 境界）
+    shapes: Res<SampledShapes>,              // サンプリング対象の図形データ
+    mut random_source: ResMut<RandomSource>, // 乱数生成器のリソース
+    mut spawn_queue: ResMut<SpawnQueue>,     // ポイント生成キュー
+    mut counter: ResMut<PointCounter>,       // 現在のポイント数カウンター
+    spawn_mode: ResMut<SpawningMode>,        // ポイント生成のモード（自動 or 手動）
+    params: Res<SamplingParams>,             // ポアソンディスクの最小距離
+    rate: Res<SpawnRate>,                    // 1フレームあたりの生成数
+    budget: Res<PointBudget>,                // フレーム時間に合わせた生成数の倍率
+    time_scale: Res<TimeScale>,              // 生成の速さの倍率
+    mut pending: Local<f32>,                 // 倍率をかけて端数になった生成数
+    existing: Query<(&Transform, &ShapeIndex), With<SamplePoint>>, // ポアソンディスク用の既存のポイント(図形の中心からの位置)
+    roots: Query<(Entity, &Transform, &ShapeRoot)>, // ポイントの親にする図形のエンティティ
+    mut parallel: ResMut<ParallelSampling>,  // 大量に予約したときの位置の計算
+) {
+    // 自動生成モードの場合、毎フレーム一定数のポイントを生成
+    // マッチする場合のみ内部の処理を実行
+    if let SpawningMode::Automatic = *spawn_mode {
+        // 生成するポイント数をキューに追加(スローモーションでは数フレームに1個になることもある)
+        *pending += budget.points_per_frame(&rate) * time_scale.0;
+        let amount = pending.floor();
+        *pending -= amount;
+        spawn_queue.0 += amount as usize;
+    }
+
+    // 生成キューが0なら何もしない
+    if spawn_queue.0 == 0 {
+        return; // 生成するポイントがない場合は終了
+    }
+
+    let rng = &mut *random_source; // 乱数生成器を取得
+
+    // 1フレームで生成しきれない数を予約したら(その計算が終わるまでは追加の予約も)、位置の計算をタスクに任せる
+    // ポアソンディスクは既存のポイントとの距離を順に調べるので、このフレームで計算する
+    if *mode != SamplingMode::PoissonDisk
+        && (parallel.pending() > 0 || spawn_queue.0 >= parallel_sampling::PARALLEL_THRESHOLD)
+    {
+        let unassigned = spawn_queue.0.saturating_sub(parallel.pending());
+        parallel.dispatch(unassigned, *mode, &shapes, rng);
+        return;
+    }
+
+    let shape_weights = shapes.weighted_index(*mode); // 図形を大きさに比例して選ぶ
+    let roots = shape_roots(&roots);
+
+    // ポアソンディスクでは、図形ごとに既存のポイントを格子に入れて距離を調べる
+    let mut grids = vec![PoissonGrid::new(params.poisson_radius); shapes.0.len()];
+    if *mode == SamplingMode::PoissonDisk {
+        for (transform, shape_index) in &existing {
+            if let Some(grid) = grids.get_mut(shape_index.0) {
+                grid.insert(transform.translation);
+            }
+        }
+    }
+
+    // 無限ループ防止のため、1フレームに最大MAX_SPAWNS_PER_FRAME個までポイントを生成
+    for _ in 0..MAX_SPAWNS_PER_FRAME {
+        if spawn_queue.0 == 0 {
+            break; // 生成キューが空になったらループを抜ける
+        }
+        spawn_queue.0 -= 1; // キューから1つポイントを取り出す
+        rng.start_point(); // 準乱数では、図形の選択と位置に列の次の点を使う
+
+        // 図形と位置を、図形の大きさ(体積・表面積)に比例した確率で1つ選ぶ
+        let index = shape_weights.sample(rng);
+        let (shape, _) = &shapes.0[index];
+        // 表示を切り替えたフレームでは、親の図形がまだないことがある
+        let Some(root) = roots.get(index).copied().flatten() else {
+            continue;
+        };
+
+        // 図形の内部または境界からランダムな位置を取得(図形の中心からの位置)
+        // 列挙型のバリエーションをパターンマッチで処理
+        let sample: Vec3 = match *mode {
+            SamplingMode::Interior => shape.sample_interior(rng), // 内部の点
+            SamplingMode::Boundary => shape.sample_boundary(rng), // 境界の点
+            SamplingMode::PoissonDisk => {
+                // 既存のポイントから十分に離れた候補が見つかるまで試す
+                let grid = &mut grids[index];
+                let candidate = (0..POISSON_ATTEMPTS)
+                    .map(|_| shape.sample_interior(rng))
+                    .find(|candidate| grid.is_far_enough(*candidate));
+                let Some(candidate) = candidate else {
+                    continue; // 図形がほぼ埋まっているので生成しない
+                };
+                grid.insert(candidate);
+                candidate
+            }
+        };
+        counter.0 += 1; // 現在のポイント数を更新
+
+        // ランダム位置にポイントを生成(初期はスケール0で非表示状態)
+        // 色はサンプリングモードに応じてインスタンス描画で決まる
+        commands.spawn(new_sample_point(sample, index, *mode, root));
+    }
+}
+
+/// SampledShapesのインデックスごとの、ポイントの親にする図形のエンティティと、そのTransform
+fn shape_roots(roots: &Query<(Entity, &Transform, &ShapeRoot)>) -> Vec<Option<(Entity, Transform)>> {
+    let mut found = Vec::new();
+    for (entity, transform, root) in roots {
+        if found.len() <= root.0 {
+            found.resize(root.0 + 1, None);
+        }
+        found[root.0] = Some((entity, *transform));
+    }
+    found
+}
+
+/// 新しいポイントのコンポーネント(初期はスケール0で、生成アニメーションで大きくなる)
+/// `position`は図形の中心からの位置で、ポイントは図形のエンティティ(`root`)の子になる
+/// ワールド座標は変換の伝播(PostUpdate)を待たずに使えるように、親のTransformから計算して入れておく
+fn new_sample_point(
+    position: Vec3,
+    index: usize,
+    mode: SamplingMode,
+    (root, root_transform): (Entity, Transform),
+) -> impl Bundle {
+    let transform = Transform::from_translation(position).with_scale(Vec3::ZERO); // 初期スケールは0(非表示)
+    (
+        transform,
+        GlobalTransform::from(root_transform).mul_transform(transform), // ワールド座標での位置
+        ChildOf(root),                   // 図形と一緒に動くように、図形のエンティティの子にする
+        SamplePoint,                     // ポイントを示すマーカーコンポーネント
+        ShapeIndex(index),               // サンプリングした図形(クラスタリングに使う)
+        mode,                            // 内部・境界のどちらか(色と書き出しに使う)
+        SpawningPoint { progress: 0.0 }, // 生成アニメーション
+    )
+}
+
+/// ポイントを消滅アニメーションに移す(SamplePointを外すので、以降はポイントとして数えない)
+fn start_despawning(mut entity: EntityCommands) {
+    entity
+        .insert(DespawningPoint {
+            progress: 0.0,
+            from: None,
+        })
+        .remove::<SpawningPoint>()
+        .remove::<SamplePoint>();
+}
+
+/// 1つの図形のポイントをすべて削除アニメーションに移し、ポイント数を減らす
+/// (図形の大きさや形を変えたとき、前の図形でサンプリングしたポイントを消すのに使う)
+fn clear_shape_points(
+    commands: &mut Commands,
+    points: &Query<(Entity, &ShapeIndex), With<SamplePoint>>,
+    counter: &mut PointCounter,
+    index: usize,
+) {
+    let mut removed = 0;
+    for (entity, _) in points.iter().filter(|(_, shape)| shape.0 == index) {
+        start_despawning(commands.entity(entity));
+        removed += 1;
+    }
+    counter.0 = counter.0.saturating_sub(removed);
+}
+
+/// 図形のメッシュと子のワイヤーフレームのメッシュを、変えた後の図形で作り直す
+/// (アセットを置き換えるので、エンティティやマテリアルはそのまま使う)
+fn rebuild_shape_meshes<'a>(
+    meshes: &mut Assets<Mesh>,
+    mesh: &Mesh3d,
+    wireframes: impl Iterator<Item = &'a Mesh3d>,
+    shape: &Shape,
+) {
+    if let Some(asset) = meshes.get_mut(&mesh.0) {
+        *asset = shape.mesh().into();
+    }
+    for wireframe in wireframes {
+        if let Some(asset) = meshes.get_mut(&wireframe.0) {
+            *asset = shape.mesh().build_wireframe();
+        }
+    }
+}
+
+// ポイントを削除するシステム
+// ポイント数が上限を超えた場合、古いポイントをランダムに削除する
+fn despawn_points(
+    mut commands: Commands,                    // エンティティ削除用コマンド
+    samples: Query<Entity, With<SamplePoint>>, // 現在存在するポイントを取得
+    spawn_mode: Res<SpawningMode>,             // ポイント生成モード（自動 or 手動）
+    mut counter: ResMut<PointCounter>,         // 現在のポイント数カウンター
+    mut random_source: ResMut<RandomSource>,   // 乱数生成器
+    quality: Res<QualityLevel>,                // 描画品質(低品質なら上限を下げる)
+    params: Res<SamplingParams>,               // ポイントの最大数
+    budget: Res<PointBudget>,                  // フレーム時間に合わせた最大数の倍率
+) {
+    // 手動モードでは自動削除しない
+    if let SpawningMode::Manual = *spawn_mode {
+        return;
+    }
+
+    // ポイント数が最大許容量未満の場合は削除しない
+    let limit = budget.point_limit(&params, *quality);
+    if counter.0 < limit {
+        return;
+    }
+
+    // 乱数生成器を取得
+    let rng = &mut *random_source;
+
+    // ランダムにポイントを削除するためにスキップ数を決定
+    let skip = rng.gen_range(0..counter.0);
+
+    // 削除するポイント数を決定(最大100個まで一度に削除)
+    let despawn_amount = (counter.0 - limit).min(100);
+
+    // 実際にポイントを削除(アニメーション付き)
+    // イテレータ（Iterator）の機能で、途中の要素をスキップして指定数だけ取得する処理。
+    // スキップ数だけ飛ばして、削除するポイント数だけ取得
+    let mut removed = 0;
+    for entity in samples.iter().skip(skip).take(despawn_amount) {
+        start_despawning(commands.entity(entity));
+        removed += 1;
+    }
+
+    // 削除したポイント数をカウンターから引く
+    counter.0 -= removed;
+}
+
+// ポイント生成アニメーションを処理するシステム
+// 生成時のアニメーションで、スケールが0→1へイージング関数に沿って大きくなるようにする。
+fn animate_spawning(
+    mut commands: Commands, // エンティティ操作用コマンド
+    time: Res<Time>,        // 時間リソース
+    mut samples: Query<(Entity, &mut Transform, &mut SpawningPoint)>, // 生成中ポイントの取得
+    params: Res<SamplingParams>,  // アニメーションの所要時間
+    time_scale: Res<TimeScale>,   // アニメーションの速さの倍率
+    easing: Res<AnimationEasing>, // スケールの変化のしかた
+) {
+    let dt = time.delta_secs() * time_scale.0; // 前回のフレームからの経過時間を取得(倍率をかける)
+
+    // 各生成中ポイントに対してアニメーションを更新
+    for (entity, mut transform, mut spawning) in samples.iter_mut() {
+        spawning.progress += dt / params.animation_time; // アニメーションの進行度を更新
+        transform.scale = Vec3::splat(easing.sample(spawning.progress)); // スケールを徐々に拡大
+
+        // アニメーション完了したら生成中マーカー削除
+        if spawning.progress >= 1.0 {
+            commands.entity(entity).remove::<SpawningPoint>(); // 生成中マーカーを削除
+        }
+    }
+}
+
+// ポイントの消滅アニメーションを処理するシステム
+// 消滅時のアニメーションで、生成時のイージングを逆にたどって、スケールが0へ小さくなるようにする。
+fn animate_despawning(
+    mut commands: Commands, // エンティティ操作用コマンド
+    time: Res<Time>,        // 時間リソース
+    mut samples: Query<(Entity, &mut Transform, &mut DespawningPoint)>, // 削除中ポイントの取得
+    params: Res<SamplingParams>,  // アニメーションの所要時間
+    time_scale: Res<TimeScale>,   // アニメーションの速さの倍率
+    easing: Res<AnimationEasing>, // スケールの変化のしかた
+) {
+    // 前回のフレームからの経過時間を取得
+    let dt = time.delta_secs() * time_scale.0; // フレーム間の時間差を取得(倍率をかける)
+
+    // 各消滅中ポイントに対してアニメーションを更新
+    for (entity, mut transform, mut despawning) in samples.iter_mut() {
+        despawning.progress += dt / params.animation_time; // アニメーションの進行度を更新
+
+        // 急なサイズ変化を避けるため、削除を始めたときのスケールから縮める(生成中に削除されたポイントは小さいまま)
+        let from = *despawning.from.get_or_insert(transform.scale.x);
+
+        // スケールを徐々に縮小
+        let eased = easing.sample(1.0 - despawning.progress);
+        transform.scale = Vec3::splat((from * eased).max(0.0));
+
+        // アニメーションが完了したらエンティティを削除
+        if despawning.progress >= 1.0 {
+            commands.entity(entity).despawn(); // エンティティを削除
+        }
+    }
+}
+
+// カメラの位置や角度を更新するシステム
+fn update_camera(
+    mut commands: Commands,
+    mut camera: Query<(
+        Entity,
+        &mut Transform,
+        &mut CameraRig,
+        &mut Projection,
+        Option<&mut CameraTransition>,
+    )>,
+    view: Res<ViewMode>,
+    camera_projection: Res<CameraProjection>,
+    time: Res<Time>,
+) {
+    // 前回のフレームからの経過時間を取得
+    let delta_time = time.delta_secs();
+    // カメラ設定(CameraRig)が変更された場合にのみ更新
+    for (entity, mut transform, mut rig, mut projection, transition) in camera.iter_mut() {
+        // 表示か投影方法が切り替わったら、透視投影と正射影を切り替える
+        // 回転・ズームの操作(CameraRig)はどちらの投影でも同じように使える
+        if view.is_changed() || camera_projection.is_changed() {
+            *projection = match (*view, *camera_projection) {
+                (ViewMode::ThreeD, CameraProjection::Perspective) => {
+                    Projection::Perspective(PerspectiveProjection::default())
+                }
+                _ => Projection::Orthographic(OrthographicProjection::default_3d()),
+            };
+        }
+
+        // プリセットへの切り替え中は、向き・距離・注視点をプリセットの姿勢へ補間する
+        if let Some(mut transition) = transition {
+            let pose = transition.advance(delta_time);
+            rig.yaw = pose.yaw;
+            rig.pitch = pose.pitch;
+            rig.current_distance = pose.distance;
+            rig.target_distance = pose.distance;
+            rig.target = pose.target;
+            if transition.is_finished() {
+                commands.entity(entity).remove::<CameraTransition>();
+            }
+        }
+
+        // 2DではXY平面を正面(+Z側)から見る向きに固定する(回転の操作は無視される)
+        if *view == ViewMode::TwoD {
+            rig.yaw = PI;
+            rig.pitch = 0.0;
+        }
+
+        // current_distance を target_distance に徐々に近づける (線形補間)
+        rig.current_distance = rig
+            .current_distance
+            .lerp(rig.target_distance, delta_time * 5.0);
+        // ※ 5.0 は速度係数。この値を調整すると動きが変わります（値が大きいほど早く、小さいほどゆっくり動く）
+
+        // 注視対象から見たカメラの方向を計算
+        // Quat::from_rotation_x/y():
+        // 特定軸の回転数を表す四元数を生成
+        let looking_direction =
+            Quat::from_rotation_y(-rig.yaw) * Quat::from_rotation_x(rig.pitch) * Vec3::Z; // Y軸とX軸の回転を適用
+
+        // カメラの位置をターゲットから指定位置離れた位置に設定
+        transform.translation = rig.target - looking_direction * rig.current_distance;
+
+        // カメラがターゲットを見るように設定
+        transform.look_at(rig.target, Vec3::Y);
+
+        // 正射影ではカメラの距離で大きさが変わらないので、ズームの距離を表示範囲の高さにする
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scaling_mode = ScalingMode::FixedVertical {
+                viewport_height: rig.current_distance,
+            };
+        }
+    }
+}
+
+// 「C」キー：クラスタリングの有効・無効を切り替えるシステム
+fn toggle_clustering(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    mut clustering: ResMut<Clustering>,
+    markers: Query<Entity, With<ClusterMarker>>,
+    mut panels: Query<&mut Visibility, With<ClusterPanel>>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleClustering) {
+        return;
+    }
+
+    clustering.enabled = !clustering.enabled;
+    if clustering.enabled {
+        // 有効にしたフレームですぐに計算されるよう、計算し直すまでの時間を経過させておく
+        let duration = clustering.timer.duration();
+        clustering.timer.set_elapsed(duration);
+    } else {
+        // 無効にしたら重心のマーカーを消す
+        clustering.clusters.clear();
+        for entity in &markers {
+            commands.entity(entity).despawn();
+        }
+    }
+    for mut visibility in panels.iter_mut() {
+        *visibility = if clustering.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// 一定時間ごとに図形ごとのポイントをクラスタに分け、重心のマーカーと一覧の表示を更新するシステム
+fn update_clusters(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut clustering: ResMut<Clustering>,
+    shapes: Res<SampledShapes>,
+    samples: Query<(&Transform, &ShapeIndex), With<SamplePoint>>,
+    roots: Query<(Entity, &Transform, &ShapeRoot)>,
+    markers: Query<Entity, With<ClusterMarker>>,
+    marker_assets: Res<ClusterMarkerAssets>,
+    mut lines: Query<(&mut LocalizedText, &ClusterLine)>,
+) {
+    if !clustering.enabled || !clustering.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // 図形ごとにポイントの位置(図形の中心からの位置)を集める
+    let mut points = vec![Vec::new(); shapes.0.len()];
+    for (transform, shape_index) in &samples {
+        // 表示を切り替えた直後は、削除前の別の図形のポイントが残っていることがある
+        if let Some(points) = points.get_mut(shape_index.0) {
+            points.push(transform.translation);
+        }
+    }
+
+    // 前回の重心から計算を始めて、クラスタの順番を保つ
+    let clusters: Vec<Vec<Cluster>> = points
+        .iter()
+        .enumerate()
+        .map(|(index, points)| {
+            let initial: Vec<Vec3> = clustering
+                .clusters
+                .get(index)
+                .map(|clusters| clusters.iter().map(|cluster| cluster.centroid).collect())
+                .unwrap_or_default();
+            k_means(points, &initial, CLUSTERS_PER_SHAPE, CLUSTERING_ITERATIONS)
+        })
+        .collect();
+
+    // 重心のマーカーを作り直す(図形と一緒に回転するように、図形のエンティティの子にする)
+    for entity in &markers {
+        commands.entity(entity).despawn();
+    }
+    let roots = shape_roots(&roots);
+    for (index, shape_clusters) in clusters.iter().enumerate() {
+        let Some((root, _)) = roots.get(index).copied().flatten() else {
+            continue;
+        };
+        for cluster in shape_clusters {
+            commands.spawn((
+                Mesh3d(marker_assets.mesh.clone()),
+                MeshMaterial3d(marker_assets.material.clone()),
+                Transform::from_translation(cluster.centroid),
+                ChildOf(root),
+                ClusterMarker,
+            ));
+        }
+    }
+
+    // 図形ごとにクラスタのポイント数を表示する
+    for (mut text, line) in lines.iter_mut() {
+        // 表示を切り替えた直後は、前の図形の行が残っていることがある
+        let (Some(line_clusters), Some((shape, _))) = (clusters.get(line.0), shapes.0.get(line.0))
+        else {
+            continue;
+        };
+        let counts: Vec<String> = line_clusters
+            .iter()
+            .map(|cluster| cluster.count.to_string())
+            .collect();
+        let counts = if counts.is_empty() {
+            "-".to_owned()
+        } else {
+            counts.join(" / ")
+        };
+        text.set_if_neq(cluster_line_text(shape, &counts));
+    }
+
+    clustering.clusters = clusters;
+}
+
+// 並べる図形が変わったときに、クラスタの一覧の行を図形に合わせて作り直すシステム
+fn rebuild_cluster_lines(
+    mut commands: Commands,
+    shapes: Res<SampledShapes>,
+    panels: Query<Entity, With<ClusterPanel>>,
+    lines: Query<Entity, With<ClusterLine>>,
+) {
+    for entity in &lines {
+        commands.entity(entity).despawn();
+    }
+    for panel in &panels {
+        commands.entity(panel).with_children(|panel| {
+            for (index, (shape, _)) in shapes.0.iter().enumerate() {
+                panel.spawn((cluster_line_text(shape, "-"), ClusterLine(index)));
+            }
+        });
+    }
+}
+
+// 「V」キー：3Dと2Dの図形の表示を切り替えるシステム
+// ポイントと図形をすべて作り直し、カメラを先頭の図形の近くに戻す
+fn switch_view(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    mut view: ResMut<ViewMode>,
+    mut shapes: ResMut<SampledShapes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    shape_material: Res<ShapeMaterial>,
+    visuals: Query<Entity, With<ShapeVisual>>,
+    mut counter: ResMut<PointCounter>,
+    mut clustering: ResMut<Clustering>,
+    mut camera_rig: Query<&mut CameraRig>,
+    custom_mesh: Res<CustomMesh>,
+    config: Res<DemoConfig>,
+    layout: Res<LayoutMode>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleView) {
+        return;
+    }
+
+    *view = match *view {
+        ViewMode::ThreeD => ViewMode::TwoD,
+        ViewMode::TwoD => ViewMode::ThreeD,
+    };
+    *shapes = SampledShapes::new(view.shapes(), *layout, *view);
+    // 読み込んだglTFのメッシュは3Dの図形の最後(横一列なら右端)に並べる
+    if let (ViewMode::ThreeD, Some(mesh)) = (*view, &custom_mesh.shape) {
+        shapes.push(Shape::CustomMesh(mesh.clone()), *layout, *view);
+    }
+
+    // 前の表示の図形を消す(子のポイントとライト、クラスタの重心のマーカーも一緒に消える)
+    for entity in &visuals {
+        commands.entity(entity).despawn();
+    }
+    counter.0 = 0;
+    clustering.clusters.clear(); // 前の図形の重心から計算を始めないようにする
+
+    let light_color = config.point_colors.interior;
+    spawn_shape_visuals(&mut commands, &mut meshes, &shape_material.0, &shapes, light_color);
+
+    for mut camera_rig in camera_rig.iter_mut() {
+        camera_rig.target = Vec3::ZERO;
+    }
+}
+
+/// クラスタの一覧の1行(図形の名前と、クラスタごとのポイント数)
+fn cluster_line_text(shape: &Shape, counts: &str) -> LocalizedText {
+    LocalizedText::new("clusters.shape")
+        .with_key_arg("shape", shape.name_key())
+        .with_arg("counts", counts)
+}
+
+/// 起動時に BGM をロード＆再生してリソースに保存
+fn setup_audio(asset_server: Res<AssetServer>, audio: Res<Audio>, mut commands: Commands) {
+    // assets/sounds/Sample.mp3 をロードして再生
+    let bgm: Handle<KiraAudioSource> = asset_server.load("sounds/Sample.mp3");
+
+    // kira_audioのplay()は AudioInstanceを返す
+    let instance: Handle<AudioInstance> = audio.play(bgm).looped().handle();
+    commands.insert_resource(BgmHandle(instance));
+}
+
+/// カメラズーム（distance）に合わせて音量を調整するシステム
+fn adjust_volume_with_zoom(
+    mut audio_instances: ResMut<Assets<AudioInstance>>, // 音声インスタンスを管理するリソース
+    bgm: Res<BgmHandle>,
+    camera_query: Query<&CameraRig>,
+    config: Res<DemoConfig>, // カメラの距離の範囲
+) {
+    let rig = camera_query.single();
+
+    // distance が 設定の最小距離..最大距離 の範囲
+    // distance = MIN → volume = 1.0
+    // distance = MAX → volume = 0.0
+    let norm = 1.0 - config.camera_distance_ratio(rig.unwrap().current_distance);
+
+    // audio.instance(&bgm.0) を使ってインスタンスを取得
+    if let Some(instance) = audio_instances.get_mut(&bgm.0) {
+        instance.set_volume(norm as f64, AudioTween::linear(Duration::from_millis(300)));
+    }
+}
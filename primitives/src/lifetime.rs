@@ -0,0 +1,116 @@
+//! ポイントの寿命による「流れる」表示
+//!
+//! 有効にすると、生成されたポイントに`Lifetime`コンポーネントを付けて経過時間を数え、
+//! 寿命の後半で色を暗くしていき、`PointLifetime::seconds`秒たったら削除アニメーションに移す。
+//! 最近のサンプルだけが残るので、サンプリングが進んでいく様子を流れとして見られる。
+//! 「T」キーか操作パネルで切り替え、寿命の長さは操作パネルで実行中に変えられる。
+//! 経過時間は一時停止(Spaceキー)中は進まず、速さの倍率(`TimeScale`)に合わせて進む。
+//! 記録の再生中は記録の通りに削除するので、寿命では削除しない。
+
+use bevy::prelude::*;
+use sample_common::TimeScale;
+
+use crate::{PointCounter, SampleAction, SamplePoint};
+
+/// 寿命の初期値(秒)
+pub const DEFAULT_LIFETIME: f32 = 5.0;
+
+/// 寿命として選べる範囲(秒)
+pub const LIFETIME_RANGE: std::ops::RangeInclusive<f32> = 0.5..=30.0;
+
+/// 寿命のうち、色を暗くしていく最後の割合
+const FADE_FRACTION: f32 = 0.5;
+
+/// ポイントの寿命を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+/// 寿命が来たポイントの削除(`expire_points`)は、ほかの生成・削除と同じ順番で`PrimitivesPlugin`が実行する
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PointLifetime>().add_systems(
+            Update,
+            (toggle_lifetime, attach_lifetimes)
+                .chain()
+                .after(crate::handle_keypress),
+        );
+    }
+}
+
+/// 寿命の設定を保持するリソース
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct PointLifetime {
+    pub enabled: bool, // 寿命で削除するか(初期は無効)
+    pub seconds: f32,  // 生成されてから削除されるまでの時間(秒)
+}
+
+impl Default for PointLifetime {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds: DEFAULT_LIFETIME,
+        }
+    }
+}
+
+impl PointLifetime {
+    /// 経過時間に応じた色の明るさ(寿命の前半は1、最後に0になる)
+    pub fn brightness(&self, lifetime: &Lifetime) -> f32 {
+        let remaining = self.seconds - lifetime.age;
+        (remaining / (self.seconds * FADE_FRACTION)).clamp(0.0, 1.0)
+    }
+}
+
+/// 寿命で削除されるポイントの経過時間
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Lifetime {
+    pub age: f32, // 生成されてからの時間(秒、時間の倍率をかけた値)
+}
+
+// 「T」キー：寿命による削除の有効・無効を切り替えるシステム
+fn toggle_lifetime(actions: Res<ButtonInput<SampleAction>>, mut lifetime: ResMut<PointLifetime>) {
+    if actions.just_pressed(SampleAction::ToggleLifetime) {
+        lifetime.enabled = !lifetime.enabled;
+    }
+}
+
+// 有効な間は寿命のないポイントに寿命を付け、無効にしたらすべてのポイントから外すシステム
+// (有効にしたときにあったポイントも、そこから寿命を数える)
+fn attach_lifetimes(
+    mut commands: Commands,
+    lifetime: Res<PointLifetime>,
+    without: Query<Entity, (With<SamplePoint>, Without<Lifetime>)>,
+    with: Query<Entity, With<Lifetime>>,
+) {
+    if lifetime.enabled {
+        for entity in &without {
+            commands.entity(entity).insert(Lifetime::default());
+        }
+    } else if lifetime.is_changed() {
+        for entity in &with {
+            commands.entity(entity).remove::<Lifetime>();
+        }
+    }
+}
+
+// ポイントの経過時間を進め、寿命が来たら削除アニメーションに移すシステム
+// (削除中のポイントにもLifetimeを残して、暗くなったままにする)
+pub(crate) fn expire_points(
+    mut commands: Commands,
+    lifetime: Res<PointLifetime>,
+    mut points: Query<(Entity, &mut Lifetime), With<SamplePoint>>,
+    mut counter: ResMut<PointCounter>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    if !lifetime.enabled {
+        return;
+    }
+    let delta = time.delta_secs() * time_scale.0;
+    for (entity, mut point) in &mut points {
+        point.age += delta;
+        if point.age >= lifetime.seconds {
+            crate::start_despawning(commands.entity(entity));
+            counter.0 = counter.0.saturating_sub(1);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! モンテカルロ法による体積・表面積の推定
+//!
+//! 図形を囲む箱の中に一様にランダムな点を打ち、図形の内部に入った割合に箱の体積を掛けると、
+//! 図形の体積が推定できる(モンテカルロ積分)。境界の面積は、表面から距離`SHELL_WIDTH`以内の
+//! 薄い殻に入った割合から「殻の体積 ÷ 殻の厚さ(2 × SHELL_WIDTH)」として推定する。
+//! 点が図形の内部か、表面から近いかは、符号付き距離(内部で負、外部で正)で判定する。
+//! 平面の図形(三角形や2Dの図形)はXY平面上の長方形に点を打ち、面積と周の長さを推定する。
+//! 「I」キーで推定を始め、図形ごとの推定値と正確な値を一覧で表示する(一覧はpanelモジュール)。
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{RngSeed, SampleAction, SampledShapes, SamplingMode};
+
+/// 境界の推定に使う殻の、表面から片側の厚さ(薄いほど正確だが、殻に入る点が減る)
+pub const SHELL_WIDTH: f32 = 0.01;
+
+/// 1フレームに図形ごとに打つ点の数
+const SAMPLES_PER_FRAME: usize = 500;
+
+/// 原点を中心とした図形の符号付き距離と、図形を囲む箱
+pub trait SignedDistance {
+    /// 点から図形の表面までの距離(内部では負の値)
+    fn signed_distance(&self, point: Vec3) -> f32;
+
+    /// 図形を囲む、原点を中心とした箱の各軸の半分の長さ(平面の図形ではZを0にする)
+    fn half_extents(&self) -> Vec3;
+}
+
+impl SignedDistance for Cuboid {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let q = point.abs() - self.half_size;
+        q.max(Vec3::ZERO).length() + q.max_element().min(0.0)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        self.half_size
+    }
+}
+
+impl SignedDistance for Sphere {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        point.length() - self.radius
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        Vec3::splat(self.radius)
+    }
+}
+
+/// カプセル型。軸はY軸
+impl SignedDistance for Capsule3d {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let y = point.y.clamp(-self.half_length, self.half_length);
+        point.distance(Vec3::new(0.0, y, 0.0)) - self.radius
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        Vec3::new(self.radius, self.half_length + self.radius, self.radius)
+    }
+}
+
+/// 円柱。軸はY軸
+impl SignedDistance for Cylinder {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let q = Vec2::new(point.xz().length() - self.radius, point.y.abs() - self.half_height);
+        q.max(Vec2::ZERO).length() + q.max_element().min(0.0)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        Vec3::new(self.radius, self.half_height, self.radius)
+    }
+}
+
+/// 四面体。外側の距離は面の平面までの距離の最大値で近似する(辺と頂点の近くでは少し短くなる)
+impl SignedDistance for Tetrahedron {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let [a, b, c, d] = self.vertices;
+        // (面の3頂点, 残りの頂点)の組み合わせ
+        [(a, b, c, d), (a, b, d, c), (a, c, d, b), (b, c, d, a)]
+            .into_iter()
+            .map(|(p, q, r, opposite)| {
+                let normal = (q - p).cross(r - p).normalize();
+                // 法線を四面体の外側に向ける
+                let normal = if normal.dot(opposite - p) > 0.0 {
+                    -normal
+                } else {
+                    normal
+                };
+                normal.dot(point - p)
+            })
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        max_abs(self.vertices.into_iter())
+    }
+}
+
+/// 3Dの三角形。XY平面上にあるので、2Dの多角形として扱う
+impl SignedDistance for Triangle3d {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        polygon_distance(point.xy(), &self.vertices.map(|vertex| vertex.xy()))
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        max_abs(self.vertices.into_iter()).with_z(0.0)
+    }
+}
+
+/// トーラス。リングはXZ平面上にある
+impl SignedDistance for Torus {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let q = Vec2::new(point.xz().length() - self.major_radius, point.y);
+        q.length() - self.minor_radius
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        let outer = self.major_radius + self.minor_radius;
+        Vec3::new(outer, self.minor_radius, outer)
+    }
+}
+
+/// 円錐。先端が+Y側、底面が-Y側にある
+impl SignedDistance for Cone {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        capped_cone_distance(point, 0.5 * self.height, self.radius, 0.0)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        Vec3::new(self.radius, 0.5 * self.height, self.radius)
+    }
+}
+
+/// 円錐台。上面が+Y側、下面が-Y側にある
+impl SignedDistance for ConicalFrustum {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        capped_cone_distance(
+            point,
+            0.5 * self.height,
+            self.radius_bottom,
+            self.radius_top,
+        )
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        let radius = self.radius_top.max(self.radius_bottom);
+        Vec3::new(radius, 0.5 * self.height, radius)
+    }
+}
+
+impl SignedDistance for Circle {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        point.xy().length() - self.radius
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        Vec3::new(self.radius, self.radius, 0.0)
+    }
+}
+
+impl SignedDistance for Rectangle {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let q = point.xy().abs() - self.half_size;
+        q.max(Vec2::ZERO).length() + q.max_element().min(0.0)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        self.half_size.extend(0.0)
+    }
+}
+
+impl SignedDistance for Annulus {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        // 2つの円の真ん中の円からの距離から、円環の幅の半分を引く
+        let inner = self.inner_circle.radius;
+        let outer = self.outer_circle.radius;
+        (point.xy().length() - 0.5 * (inner + outer)).abs() - 0.5 * (outer - inner)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        let radius = self.outer_circle.radius;
+        Vec3::new(radius, radius, 0.0)
+    }
+}
+
+impl SignedDistance for Triangle2d {
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        polygon_distance(point.xy(), &self.vertices)
+    }
+
+    fn half_extents(&self) -> Vec3 {
+        max_abs(self.vertices.into_iter().map(|vertex| vertex.extend(0.0)))
+    }
+}
+
+/// 頂点の各軸の絶対値の最大値(原点を中心とした箱で頂点をすべて囲む)
+fn max_abs(vertices: impl Iterator<Item = Vec3>) -> Vec3 {
+    vertices.fold(Vec3::ZERO, |extents, vertex| extents.max(vertex.abs()))
+}
+
+/// Y軸の周りの回転体で、下面の半径が`bottom`、上面の半径が`top`の円錐台の符号付き距離
+/// (円錐は`top`を0にする)
+fn capped_cone_distance(point: Vec3, half_height: f32, bottom: f32, top: f32) -> f32 {
+    // 軸からの距離と高さの断面(台形)で考える
+    let q = Vec2::new(point.xz().length(), point.y);
+    let k1 = Vec2::new(top, half_height);
+    let k2 = Vec2::new(top - bottom, 2.0 * half_height);
+    // 上面・下面までのベクトル
+    let cap_radius = if q.y < 0.0 { bottom } else { top };
+    let to_cap = Vec2::new(q.x - q.x.min(cap_radius), q.y.abs() - half_height);
+    // 側面(斜めの辺)までのベクトル
+    let t = ((k1 - q).dot(k2) / k2.length_squared()).clamp(0.0, 1.0);
+    let to_side = q - k1 + k2 * t;
+    let sign = if to_side.x < 0.0 && to_cap.y < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    sign * to_cap.length_squared().min(to_side.length_squared()).sqrt()
+}
+
+/// 多角形の符号付き距離(辺までの最短距離に、内外判定の符号を付ける)
+fn polygon_distance(point: Vec2, vertices: &[Vec2]) -> f32 {
+    let mut distance_squared = f32::INFINITY;
+    let mut inside = false;
+    for (i, &start) in vertices.iter().enumerate() {
+        let end = vertices[(i + 1) % vertices.len()];
+        let edge = end - start;
+        let to_point = point - start;
+        let t = (to_point.dot(edge) / edge.length_squared()).clamp(0.0, 1.0);
+        distance_squared = distance_squared.min((to_point - edge * t).length_squared());
+        // 点から+X方向に伸ばした半直線が辺と交わるたびに、内外が入れ替わる
+        if (start.y > point.y) != (end.y > point.y)
+            && point.x < start.x + edge.x * (point.y - start.y) / edge.y
+        {
+            inside = !inside;
+        }
+    }
+    let distance = distance_squared.sqrt();
+    if inside { -distance } else { distance }
+}
+
+/// 1つの図形についての、モンテカルロ法の集計
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub samples: u64, // 箱の中に打った点の数
+    pub inside: u64,  // 図形の内部に入った点の数
+    pub shell: u64,   // 表面から`SHELL_WIDTH`以内に入った点の数
+    bounds: Vec3,     // 点を打つ箱の各軸の半分の長さ(殻が収まるよう少し広げる。平面ではZが0)
+}
+
+impl Estimate {
+    /// 図形を囲む箱で、まだ点を打っていない集計を作る
+    pub fn new(shape: &(impl SignedDistance + ?Sized)) -> Self {
+        let extents = shape.half_extents();
+        // 平面の図形はZ方向に広げない
+        let margin = Vec3::splat(SHELL_WIDTH).with_z(if extents.z > 0.0 {
+            SHELL_WIDTH
+        } else {
+            0.0
+        });
+        Self {
+            samples: 0,
+            inside: 0,
+            shell: 0,
+            bounds: extents + margin,
+        }
+    }
+
+    /// 点を打つ箱の体積(平面の図形では長方形の面積)
+    pub fn box_measure(&self) -> f32 {
+        (2.0 * self.bounds)
+            .to_array()
+            .into_iter()
+            .filter(|&length| length > 0.0)
+            .product()
+    }
+
+    /// 箱の中に`count`個の点を打ち、図形の内部と表面の近くに入った数を数える
+    pub fn sample<R: Rng + ?Sized>(
+        &mut self,
+        shape: &(impl SignedDistance + ?Sized),
+        count: usize,
+        rng: &mut R,
+    ) {
+        for _ in 0..count {
+            let point = Vec3::new(
+                self.bounds.x * rng.gen_range(-1.0..=1.0),
+                self.bounds.y * rng.gen_range(-1.0..=1.0),
+                self.bounds.z * rng.gen_range(-1.0..=1.0),
+            );
+            let distance = shape.signed_distance(point);
+            self.samples += 1;
+            self.inside += u64::from(distance < 0.0);
+            self.shell += u64::from(distance.abs() < SHELL_WIDTH);
+        }
+    }
+
+    /// 内部の大きさ(体積、平面では面積)の推定値
+    pub fn interior(&self) -> f32 {
+        self.fraction(self.inside) * self.box_measure()
+    }
+
+    /// 境界の大きさ(表面積、平面では周の長さ)の推定値
+    pub fn boundary(&self) -> f32 {
+        self.fraction(self.shell) * self.box_measure() / (2.0 * SHELL_WIDTH)
+    }
+
+    /// 打った点のうち`hits`個が当たった割合
+    fn fraction(&self, hits: u64) -> f32 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            hits as f32 / self.samples as f32
+        }
+    }
+}
+
+/// 図形ごとの推定値と正確な値
+#[derive(Debug, Clone)]
+pub struct ShapeEstimate {
+    pub name_key: &'static str, // 図形の名前のキー(assets/locale以下の対応表)
+    pub exact_interior: f32,    // 内部の大きさの正確な値
+    pub exact_boundary: f32,    // 境界の大きさの正確な値
+    pub estimate: Estimate,     // モンテカルロ法の集計
+}
+
+/// モンテカルロ法による推定の状態を保持するリソース
+#[derive(Resource)]
+pub struct MonteCarlo {
+    pub enabled: bool,              // 推定を行うかどうか
+    pub shapes: Vec<ShapeEstimate>, // 図形ごとの推定(SampledShapesと同じ順番)
+    rng: ChaCha8Rng,                // 推定用の乱数生成器(ポイントの生成とは別にする)
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shapes: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+impl MonteCarlo {
+    /// 集計を捨てて、図形ごとに最初から推定し直す
+    fn restart(&mut self, shapes: &SampledShapes, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.shapes = shapes
+            .0
+            .iter()
+            .map(|(shape, _)| ShapeEstimate {
+                name_key: shape.name_key(),
+                exact_interior: shape.measure(SamplingMode::Interior),
+                exact_boundary: shape.measure(SamplingMode::Boundary),
+                estimate: Estimate::new(shape),
+            })
+            .collect();
+    }
+}
+
+// 「I」キー：モンテカルロ法による推定の有効・無効を切り替えるシステム
+pub(crate) fn toggle_monte_carlo(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut monte_carlo: ResMut<MonteCarlo>,
+    shapes: Res<SampledShapes>,
+    seed: Res<RngSeed>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleMonteCarlo) {
+        return;
+    }
+
+    monte_carlo.enabled = !monte_carlo.enabled;
+    if monte_carlo.enabled {
+        monte_carlo.restart(&shapes, seed.seed);
+    }
+}
+
+// 図形ごとに点を打って推定を進めるシステム(表示を切り替えたら最初からやり直す)
+pub(crate) fn update_monte_carlo(
+    mut monte_carlo: ResMut<MonteCarlo>,
+    shapes: Res<SampledShapes>,
+    seed: Res<RngSeed>,
+) {
+    if !monte_carlo.enabled {
+        return;
+    }
+    if shapes.is_changed() {
+        monte_carlo.restart(&shapes, seed.seed);
+    }
+
+    let MonteCarlo {
+        shapes: estimates,
+        rng,
+        ..
+    } = &mut *monte_carlo;
+    for ((shape, _), estimate) in shapes.0.iter().zip(estimates.iter_mut()) {
+        estimate.estimate.sample(shape, SAMPLES_PER_FRAME, rng);
+    }
+}
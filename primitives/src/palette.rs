@@ -0,0 +1,117 @@
+//! ポイントの色のパレット
+//!
+//! 内部・境界・ポアソンディスクのポイントの色の組み合わせをいくつか用意し、「P」キーで切り替える。
+//! 最初のパレットは設定ファイルの色(`DemoConfig::point_colors`)で、ほかは決まった色を使う。
+//! 色の区別がつきにくい人でも見分けやすいように、Okabe-Itoの配色から選んだパレットも用意する。
+//! ポイントの色はインスタンス描画で毎フレーム決めるので、切り替えると既存のポイントの色も変わる。
+//! 設定ファイルの`recolor_existing_points`を`false`にすると、既存のポイントには切り替える前の
+//! パレットを`PinnedPalette`として付け、生成したときの色のまま残す。
+
+use bevy::prelude::*;
+
+use crate::demo_config::{DemoConfig, PointColors};
+use crate::{SampleAction, SamplingMode};
+
+/// パレットを切り替えるプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct PointPalettePlugin;
+
+impl Plugin for PointPalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PointPalette>()
+            .add_systems(Update, cycle_palette.after(crate::handle_keypress));
+    }
+}
+
+/// ポイントの色のパレット
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PointPalette {
+    #[default]
+    Config, // 設定ファイルの色(初期は黄緑・青・ピンク)
+    Sunset,     // オレンジ・紫・金色
+    Ocean,      // 水色・紺・薄緑
+    Colorblind, // Okabe-Itoの配色(オレンジ・青・赤紫)
+}
+
+impl PointPalette {
+    /// 「P」キーで切り替える順番
+    pub const ALL: [PointPalette; 4] = [
+        PointPalette::Config,
+        PointPalette::Sunset,
+        PointPalette::Ocean,
+        PointPalette::Colorblind,
+    ];
+
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            PointPalette::Config => "palette.config",
+            PointPalette::Sunset => "palette.sunset",
+            PointPalette::Ocean => "palette.ocean",
+            PointPalette::Colorblind => "palette.colorblind",
+        }
+    }
+
+    /// 次のパレット(最後の次は最初に戻る)
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|palette| *palette == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// サンプリングモードごとの色(`config`は設定ファイルの色で、Configのときにそのまま使う)
+    /// ブルームで光って見えるように、明るい色は1を少し超える値にしている
+    pub fn colors(self, config: &PointColors) -> PointColors {
+        match self {
+            PointPalette::Config => *config,
+            PointPalette::Sunset => PointColors {
+                interior: LinearRgba::rgb(1.3, 0.35, 0.02),
+                boundary: LinearRgba::rgb(0.5, 0.04, 0.65),
+                poisson_disk: LinearRgba::rgb(1.1, 0.85, 0.12),
+            },
+            PointPalette::Ocean => PointColors {
+                interior: LinearRgba::rgb(0.0, 0.9, 1.1),
+                boundary: LinearRgba::rgb(0.02, 0.08, 0.7),
+                poisson_disk: LinearRgba::rgb(0.45, 1.1, 0.6),
+            },
+            PointPalette::Colorblind => PointColors {
+                interior: Color::srgb_u8(0xE6, 0x9F, 0x00).to_linear(),
+                boundary: Color::srgb_u8(0x00, 0x72, 0xB2).to_linear(),
+                poisson_disk: Color::srgb_u8(0xCC, 0x79, 0xA7).to_linear(),
+            },
+        }
+    }
+}
+
+/// 切り替える前のパレットの色のまま表示するポイントに付けるコンポーネント
+/// (設定ファイルの`recolor_existing_points`が`false`のときだけ付ける)
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PinnedPalette(pub PointPalette);
+
+// 「P」キー：ポイントの色のパレットを切り替えるシステム
+// 既存のポイントの色を変えない設定なら、まだパレットを付けていないポイントに今のパレットを付ける
+fn cycle_palette(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    mut palette: ResMut<PointPalette>,
+    config: Res<DemoConfig>,
+    unpinned: Query<Entity, (With<SamplingMode>, Without<PinnedPalette>)>,
+    pinned: Query<Entity, With<PinnedPalette>>,
+) {
+    if !actions.just_pressed(SampleAction::CyclePalette) {
+        return;
+    }
+
+    if config.recolor_existing_points {
+        for entity in &pinned {
+            commands.entity(entity).try_remove::<PinnedPalette>();
+        }
+    } else {
+        for entity in &unpinned {
+            commands.entity(entity).try_insert(PinnedPalette(*palette));
+        }
+    }
+    *palette = palette.next();
+    info!("ポイントの色のパレットを{:?}にしました", *palette);
+}
@@ -0,0 +1,355 @@
+//! サンプリングの設定を実行中に変える操作パネル(bevy_egui)
+//!
+//! ポイントの最大数・1フレームあたりの生成数・アニメーションの所要時間とイージング・表示の大きさと、フレームレートに合わせた自動調整の有無と、
+//! サンプリングモード(内部・境界・ポアソンディスク)とその最小距離・生成モード(自動 or 手動)・一時停止と速さの倍率と、
+//! リスタート時に乱数をシード値から始め直すかどうかと、密度による色分けの設定と、ポイントの寿命をウィジェットで変更できる。
+//! 変更した値は`SamplingParams`などのリソースに直接書き込むので、次のフレームから反映される。
+//! 生成数を変えると、フレームレートに合わせた自動調整では減らさなくなる。
+//! モンテカルロ法による推定が有効なときは、図形ごとの推定値の一覧も別のウィンドウで表示する。
+//! 重心と分散の集計が有効なときは、図形ごとのポイントの平均・分散と解析的な重心も別のウィンドウで表示する。
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContextPass, EguiContexts, EguiPlugin, egui};
+use sample_common::locale::Translations;
+use sample_common::time_control::{MAX_TIME_SCALE, MIN_TIME_SCALE};
+use sample_common::{Locale, LocalizedText, PauseState, QualityLevel, TimeScale};
+
+use crate::density::{DensityColoring, Gradient};
+use crate::easing::AnimationEasing;
+use crate::lifetime::{LIFETIME_RANGE, PointLifetime};
+use crate::monte_carlo::MonteCarlo;
+use crate::point_budget::PointBudget;
+use crate::statistics::SampleStatistics;
+use crate::{
+    POINT_SIZE_RANGE, POINTS_PER_FRAME_RANGE, PointCounter, RngSeed, SamplingMode, SamplingParams,
+    SpawnRate, SpawningMode,
+};
+
+/// ポイントの最大数として選べる範囲(インスタンス描画なので10万個以上でも表示できる)
+const MAX_POINTS_RANGE: std::ops::RangeInclusive<usize> = 100..=200_000;
+
+/// アニメーションの所要時間(秒)として選べる範囲
+const ANIMATION_TIME_RANGE: std::ops::RangeInclusive<f32> = 0.05..=5.0;
+
+/// ポアソンディスクサンプリングの最小距離として選べる範囲
+const POISSON_RADIUS_RANGE: std::ops::RangeInclusive<f32> = 0.02..=0.5;
+
+/// 密度による色分けで近傍とみなす距離として選べる範囲
+const DENSITY_RADIUS_RANGE: std::ops::RangeInclusive<f32> = 0.05..=0.5;
+
+/// 操作パネルを表示するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ControlPanelPlugin;
+
+impl Plugin for ControlPanelPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin {
+                enable_multipass_for_primary_context: true,
+            });
+        }
+        app.init_resource::<ControlPanel>().add_systems(
+            EguiContextPass,
+            (control_panel, monte_carlo_panel, statistics_panel),
+        );
+    }
+}
+
+/// 操作パネルの状態を保持するリソース
+#[derive(Resource, Default)]
+pub struct ControlPanel {
+    pub wants_pointer: bool, // マウスがパネルを操作中か(カメラの回転・ズームをしない)
+}
+
+/// 操作パネルを表示し、変更された値をリソースに書き込むシステム
+fn control_panel(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<ControlPanel>,
+    mut params: ResMut<SamplingParams>,
+    sampling_mode: Option<ResMut<SamplingMode>>, // setupで追加されるまではない
+    spawning_mode: Option<ResMut<SpawningMode>>,
+    mut pause: ResMut<PauseState>,
+    mut time_scale: ResMut<TimeScale>,
+    counter: Res<PointCounter>,
+    mut seed: ResMut<RngSeed>,
+    mut density: ResMut<DensityColoring>,
+    (mut budget, mut rate): (ResMut<PointBudget>, ResMut<SpawnRate>), // システムの引数は16個までなので、まとめる
+    mut lifetime: ResMut<PointLifetime>,
+    mut easing: ResMut<AnimationEasing>,
+    quality: Res<QualityLevel>,
+    translations: Res<Translations>,
+    locale: Res<Locale>,
+) {
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return; // ウィンドウがない(ヘッドレス)
+    };
+    let (Some(mut sampling_mode), Some(mut spawning_mode)) = (sampling_mode, spawning_mode) else {
+        return;
+    };
+    let text = |key: &str| translations.get(*locale, key).to_owned();
+
+    // 値を変えたときだけリソースの変更として扱うため、コピーを編集する
+    let mut edited = params.clone();
+    let mut per_frame = rate.points_per_frame;
+    let mut sampling = *sampling_mode;
+    let mut spawning = *spawning_mode;
+    let mut paused = *pause == PauseState::Paused;
+    let mut scale = time_scale.0;
+    let mut reseed_on_reset = seed.reseed_on_reset;
+    let mut adaptive = budget.enabled;
+    // 自動調整した後の上限と生成数(倍率をかけた値)
+    let budget_text = translations.format(
+        *locale,
+        &LocalizedText::new("panel.budget")
+            .with_arg("points", budget.point_limit(&params, *quality))
+            .with_arg("per_frame", format!("{:.1}", budget.points_per_frame(&rate)))
+            .with_arg("fps", budget.fps.map_or("-".to_owned(), |fps| format!("{fps:.0}"))),
+    );
+    let (mut density_enabled, mut density_radius, mut gradient) =
+        (density.enabled, density.radius, density.gradient);
+    let mut edited_lifetime = *lifetime;
+    let mut edited_easing = *easing;
+
+    egui::Window::new(text("panel.title"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -12.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{}: {}", text("panel.points"), counter.0));
+            ui.add(
+                egui::Slider::new(&mut edited.max_points, MAX_POINTS_RANGE)
+                    .text(text("panel.max_points")),
+            );
+            ui.add(
+                egui::Slider::new(&mut per_frame, POINTS_PER_FRAME_RANGE)
+                    .text(text("panel.points_per_frame")),
+            );
+            ui.add(
+                egui::Slider::new(&mut edited.animation_time, ANIMATION_TIME_RANGE)
+                    .text(text("panel.animation_time")),
+            );
+            ui.add(
+                egui::Slider::new(&mut edited.point_size, POINT_SIZE_RANGE)
+                    .logarithmic(true)
+                    .text(text("panel.point_size")),
+            );
+            egui::ComboBox::from_label(text("panel.easing"))
+                .selected_text(text(edited_easing.name_key()))
+                .show_ui(ui, |ui| {
+                    for option in AnimationEasing::ALL {
+                        ui.selectable_value(&mut edited_easing, option, text(option.name_key()));
+                    }
+                });
+            ui.checkbox(&mut adaptive, text("panel.adaptive_budget"));
+            ui.add_enabled_ui(adaptive, |ui| ui.label(budget_text));
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(text("panel.sampling"));
+                ui.radio_value(&mut sampling, SamplingMode::Interior, text("panel.interior"));
+                ui.radio_value(&mut sampling, SamplingMode::Boundary, text("panel.boundary"));
+                ui.radio_value(
+                    &mut sampling,
+                    SamplingMode::PoissonDisk,
+                    text("panel.poisson_disk"),
+                );
+            });
+            ui.add_enabled(
+                sampling == SamplingMode::PoissonDisk,
+                egui::Slider::new(&mut edited.poisson_radius, POISSON_RADIUS_RANGE)
+                    .text(text("panel.poisson_radius")),
+            );
+            ui.horizontal(|ui| {
+                ui.label(text("panel.spawning"));
+                ui.radio_value(&mut spawning, SpawningMode::Automatic, text("panel.automatic"));
+                ui.radio_value(&mut spawning, SpawningMode::Manual, text("panel.manual"));
+            });
+            ui.checkbox(&mut paused, text("panel.paused"));
+            ui.add(
+                egui::Slider::new(&mut scale, MIN_TIME_SCALE..=MAX_TIME_SCALE)
+                    .logarithmic(true)
+                    .text(text("panel.time_scale")),
+            );
+
+            ui.separator();
+            ui.label(format!("{}: {}", text("panel.seed"), seed.seed));
+            ui.checkbox(&mut reseed_on_reset, text("panel.reseed_on_reset"));
+
+            ui.separator();
+            ui.checkbox(&mut density_enabled, text("panel.density"));
+            ui.add_enabled_ui(density_enabled, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut density_radius, DENSITY_RADIUS_RANGE)
+                        .text(text("panel.density_radius")),
+                );
+                egui::ComboBox::from_label(text("panel.gradient"))
+                    .selected_text(text(gradient.name_key()))
+                    .show_ui(ui, |ui| {
+                        for option in Gradient::ALL {
+                            ui.selectable_value(&mut gradient, option, text(option.name_key()));
+                        }
+                    });
+            });
+
+            ui.separator();
+            ui.checkbox(&mut edited_lifetime.enabled, text("panel.lifetime"));
+            ui.add_enabled(
+                edited_lifetime.enabled,
+                egui::Slider::new(&mut edited_lifetime.seconds, LIFETIME_RANGE)
+                    .text(text("panel.lifetime_seconds")),
+            );
+        });
+
+    params.set_if_neq(edited);
+    if rate.points_per_frame != per_frame {
+        rate.set(per_frame);
+    }
+    sampling_mode.set_if_neq(sampling);
+    spawning_mode.set_if_neq(spawning);
+    pause.set_if_neq(if paused {
+        PauseState::Paused
+    } else {
+        PauseState::Running
+    });
+    time_scale.set_if_neq(TimeScale(scale));
+    if seed.reseed_on_reset != reseed_on_reset {
+        seed.reseed_on_reset = reseed_on_reset;
+    }
+    if budget.enabled != adaptive {
+        budget.enabled = adaptive;
+    }
+    if (density.enabled, density.radius, density.gradient)
+        != (density_enabled, density_radius, gradient)
+    {
+        density.enabled = density_enabled;
+        density.radius = density_radius;
+        density.gradient = gradient;
+    }
+    lifetime.set_if_neq(edited_lifetime);
+    easing.set_if_neq(edited_easing);
+    panel.wants_pointer = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+}
+
+/// モンテカルロ法による体積・表面積の推定値と、正確な値との誤差を一覧で表示するシステム
+fn monte_carlo_panel(
+    mut contexts: EguiContexts,
+    monte_carlo: Res<MonteCarlo>,
+    translations: Res<Translations>,
+    locale: Res<Locale>,
+) {
+    if !monte_carlo.enabled {
+        return;
+    }
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return; // ウィンドウがない(ヘッドレス)
+    };
+    let text = |key: &str| translations.get(*locale, key).to_owned();
+    // 推定値(正確な値との誤差%)
+    let estimate = |value: f32, exact: f32| {
+        format!("{value:.3} ({:+.1}%)", 100.0 * (value - exact) / exact)
+    };
+
+    egui::Window::new(text("monte_carlo.title"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [12.0, -12.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            let samples = monte_carlo
+                .shapes
+                .first()
+                .map_or(0, |shape| shape.estimate.samples);
+            ui.label(format!("{}: {samples}", text("monte_carlo.samples")));
+            egui::Grid::new("monte_carlo")
+                .striped(true)
+                .show(ui, |ui| {
+                    for key in [
+                        "monte_carlo.shape",
+                        "monte_carlo.interior",
+                        "monte_carlo.exact",
+                        "monte_carlo.boundary",
+                        "monte_carlo.exact",
+                    ] {
+                        ui.strong(text(key));
+                    }
+                    ui.end_row();
+                    for shape in &monte_carlo.shapes {
+                        ui.label(text(shape.name_key));
+                        ui.label(estimate(shape.estimate.interior(), shape.exact_interior));
+                        ui.label(format!("{:.3}", shape.exact_interior));
+                        ui.label(estimate(shape.estimate.boundary(), shape.exact_boundary));
+                        ui.label(format!("{:.3}", shape.exact_boundary));
+                        ui.end_row();
+                    }
+                });
+            ui.label(text("monte_carlo.note"));
+        });
+}
+
+/// 今のサンプリングモードでの、図形ごとのポイントの平均と分散・共分散を、解析的な重心と並べて表示するシステム
+fn statistics_panel(
+    mut contexts: EguiContexts,
+    statistics: Res<SampleStatistics>,
+    sampling_mode: Option<Res<SamplingMode>>, // setupで追加されるまではない
+    translations: Res<Translations>,
+    locale: Res<Locale>,
+) {
+    let Some(mode) = sampling_mode else {
+        return;
+    };
+    if !statistics.enabled {
+        return;
+    }
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return; // ウィンドウがない(ヘッドレス)
+    };
+    let text = |key: &str| translations.get(*locale, key).to_owned();
+    let vector = |v: Vec3| format!("({:+.3}, {:+.3}, {:+.3})", v.x, v.y, v.z);
+    let title = translations.format(
+        *locale,
+        &LocalizedText::new("statistics.title").with_key_arg("sampling", mode.name_key()),
+    );
+
+    egui::Window::new(title)
+        .id(egui::Id::new("statistics"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -12.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("statistics").striped(true).show(ui, |ui| {
+                for key in [
+                    "statistics.shape",
+                    "statistics.count",
+                    "statistics.mean",
+                    "statistics.centroid",
+                    "statistics.deviation",
+                    "statistics.variance",
+                    "statistics.covariance",
+                ] {
+                    ui.strong(text(key));
+                }
+                ui.end_row();
+                for shape in &statistics.shapes {
+                    let samples = shape.samples(*mode);
+                    let centroid = shape.centroid(*mode);
+                    ui.label(text(shape.name_key));
+                    ui.label(samples.count().to_string());
+                    match samples.mean() {
+                        Some(mean) => {
+                            let covariance = samples.covariance();
+                            ui.label(vector(mean));
+                            ui.label(vector(centroid));
+                            ui.label(format!("{:.4}", mean.distance(centroid)));
+                            ui.label(vector(samples.variance()));
+                            ui.label(vector(Vec3::new(
+                                covariance.x_axis.y,
+                                covariance.y_axis.z,
+                                covariance.z_axis.x,
+                            )));
+                        }
+                        None => {
+                            ui.label("-");
+                            ui.label(vector(centroid));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.label(text("statistics.note"));
+        });
+}
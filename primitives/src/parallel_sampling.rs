@@ -0,0 +1,193 @@
+//! 大量に予約したポイントの位置を`AsyncComputeTaskPool`で並列に計算する
+//!
+//! 生成キューに`PARALLEL_THRESHOLD`個以上たまったら(Dキーの連打や、手動で大量に予約したとき)、
+//! `spawn_points`は位置を計算せずに`BATCH_SIZE`個ずつのバッチに分けてタスクに渡す。
+//! 各バッチはメインの乱数から切り出した乱数生成器を使い(疑似乱数ならメインの乱数から取ったシード値で作り、
+//! 準乱数ならHalton列の続きを受け持つ)、結果をチャンネルで返す。
+//! 受け取った位置は、バッチを送った順番に1フレームあたり最大`MAX_SPAWNS_PER_FRAME`個ずつ生成するので、
+//! 同じシード値なら同じポイントになる。
+//! ポアソンディスクは既存のポイントとの距離を順に調べるので、これまで通りメインスレッドで生成する。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bevy::math::ShapeSample;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use rand::distributions::Distribution;
+
+use crate::{
+    MAX_SPAWNS_PER_FRAME, PointCounter, RandomSource, SampledShapes, SamplingMode, ShapeRoot,
+    SpawnQueue,
+};
+
+/// 生成キューにこの数以上たまったら、位置の計算をタスクに任せる(1フレームでは生成しきれない数)
+pub const PARALLEL_THRESHOLD: usize = MAX_SPAWNS_PER_FRAME;
+
+/// 1つのタスクで計算するポイントの数
+pub const BATCH_SIZE: usize = 2000;
+
+/// タスクで計算したポイントの位置(図形のインデックスと、図形の中心からの位置)
+struct SampledBatch {
+    id: u64,                     // 送った順番(この順番に生成する)
+    samples: Vec<(usize, Vec3)>, // 計算したポイント
+}
+
+/// 計算中・生成待ちのバッチを管理するリソース
+#[derive(Resource)]
+pub struct ParallelSampling {
+    sender: Sender<SampledBatch>,                // タスクに渡す送信側
+    receiver: Mutex<Receiver<SampledBatch>>,     // 計算が終わったバッチの受信側
+    mode: SamplingMode,                          // 計算中のバッチのサンプリングモード
+    next_batch: u64,                             // 次に送るバッチの番号
+    next_spawn: u64,                             // 次に生成するバッチの番号
+    finished: BTreeMap<u64, Vec<(usize, Vec3)>>, // 計算が終わって順番を待っているバッチ
+    ready: VecDeque<(usize, Vec3)>,              // 生成中のバッチの残り
+    pending: usize,                              // タスクに渡してまだ生成していない数
+}
+
+impl Default for ParallelSampling {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+            mode: SamplingMode::Interior,
+            next_batch: 0,
+            next_spawn: 0,
+            finished: BTreeMap::new(),
+            ready: VecDeque::new(),
+            pending: 0,
+        }
+    }
+}
+
+impl ParallelSampling {
+    /// タスクに渡してまだ生成していないポイントの数(生成キューに含まれている)
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// `amount`個のポイントの位置を、バッチに分けてタスクで計算する
+    pub(crate) fn dispatch(
+        &mut self,
+        amount: usize,
+        mode: SamplingMode,
+        shapes: &SampledShapes,
+        rng: &mut RandomSource,
+    ) {
+        if amount == 0 {
+            return;
+        }
+        self.mode = mode;
+        let weights = shapes.weighted_index(mode);
+        let shapes: Arc<Vec<_>> =
+            Arc::new(shapes.0.iter().map(|(shape, _)| shape.clone()).collect());
+        let task_pool = AsyncComputeTaskPool::get();
+        let mut remaining = amount;
+        while remaining > 0 {
+            let size = remaining.min(BATCH_SIZE);
+            remaining -= size;
+            let id = self.next_batch;
+            self.next_batch += 1;
+            self.pending += size;
+
+            // 乱数の順番がタスクの実行順に左右されないように、バッチごとに乱数生成器を切り出す
+            let mut batch_rng = rng.split(size);
+            let (shapes, weights, sender) = (shapes.clone(), weights.clone(), self.sender.clone());
+            task_pool
+                .spawn(async move {
+                    let samples = (0..size)
+                        .map(|_| {
+                            // 図形を大きさに比例して選び、内部または境界から位置を選ぶ
+                            batch_rng.start_point();
+                            let index = weights.sample(&mut batch_rng);
+                            let sample = match mode {
+                                SamplingMode::Boundary => {
+                                    shapes[index].sample_boundary(&mut batch_rng)
+                                }
+                                SamplingMode::Interior | SamplingMode::PoissonDisk => {
+                                    shapes[index].sample_interior(&mut batch_rng)
+                                }
+                            };
+                            (index, sample)
+                        })
+                        .collect();
+                    // アプリの終了で受信側がなくなっていたら、結果は捨てる
+                    let _ = sender.send(SampledBatch { id, samples });
+                })
+                .detach();
+        }
+    }
+
+    /// 計算中・生成待ちのバッチをすべて捨てる(生成キューには残るので、spawn_pointsが計算し直す)
+    /// 後から届いた捨てたバッチは、番号が`next_spawn`より前なので受け取らない
+    pub fn cancel(&mut self) {
+        self.next_spawn = self.next_batch;
+        self.finished.clear();
+        self.ready.clear();
+        self.pending = 0;
+    }
+
+    // チャンネルに届いたバッチを順番待ちに移す
+    fn receive(&mut self) {
+        let receiver = self
+            .receiver
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        for batch in receiver.try_iter() {
+            if batch.id >= self.next_spawn {
+                self.finished.insert(batch.id, batch.samples);
+            }
+        }
+    }
+
+    // 送った順番で次に生成するポイント(次のバッチの計算が終わっていなければNone)
+    fn next_sample(&mut self) -> Option<(usize, Vec3)> {
+        if self.ready.is_empty() {
+            let samples = self.finished.remove(&self.next_spawn)?;
+            self.next_spawn += 1;
+            self.ready.extend(samples);
+        }
+        let sample = self.ready.pop_front()?;
+        self.pending -= 1;
+        Some(sample)
+    }
+}
+
+// 計算が終わったバッチのポイントを生成するシステム(spawn_pointsの前に動かす)
+// サンプリングモードや図形が変わったら、計算中のバッチは使わずに生成キューから計算し直す
+pub(crate) fn spawn_sampled_batches(
+    mut commands: Commands,
+    mut sampling: ResMut<ParallelSampling>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut counter: ResMut<PointCounter>,
+    mode: Res<SamplingMode>,
+    shapes: Res<SampledShapes>,
+    roots: Query<(Entity, &Transform, &ShapeRoot)>,
+) {
+    if sampling.pending() == 0 {
+        return;
+    }
+    if sampling.mode != *mode || shapes.is_changed() {
+        sampling.cancel();
+        return;
+    }
+
+    sampling.receive();
+    let roots = crate::shape_roots(&roots);
+    let mode = sampling.mode;
+    for _ in 0..MAX_SPAWNS_PER_FRAME {
+        let Some((index, sample)) = sampling.next_sample() else {
+            break;
+        };
+        spawn_queue.0 = spawn_queue.0.saturating_sub(1);
+        // 表示を切り替えたフレームでは、親の図形がまだないことがある
+        let Some(root) = roots.get(index).copied().flatten() else {
+            continue;
+        };
+        counter.0 += 1;
+        commands.spawn(crate::new_sample_point(sample, index, mode, root));
+    }
+}
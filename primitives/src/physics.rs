@@ -0,0 +1,134 @@
+//! ポイントが図形から離れて地面に落ちる物理モード
+//!
+//! 有効にすると、生成アニメーションを終えたポイントに`FallingPoint`コンポーネントを付け、
+//! 重力で速度を増やしながらワールド座標で落とす(図形を回転させても一緒には回らない)。
+//! 地面(`GROUND_HEIGHT`の高さの平面)に着いたら削除アニメーションに移すので、サンプルが降り注ぐように見える。
+//! ポイントは図形のエンティティの子のままにして、落ちた位置を図形の中心からの位置に直して入れる
+//! (表示の切り替えやリスタートでは、ほかのポイントと一緒に消える)。
+//! 「6」キーで切り替え、無効にしても落ち始めたポイントはそのまま地面まで落ちる。
+//! 一時停止(Spaceキー)中は止まり、速さの倍率(`TimeScale`)に合わせて落ちる。
+//! 記録の再生中は記録の通りに削除するので、地面に着いても削除しない。
+
+use bevy::prelude::*;
+use sample_common::TimeScale;
+
+use crate::{GROUND_HEIGHT, PointCounter, SampleAction, SamplePoint, ShapeRoot, SpawningPoint};
+
+/// 重力加速度の初期値(単位/秒²。図形の中心の高さから1秒ほどで地面に着く)
+pub const GRAVITY: f32 = 4.0;
+
+/// ポイントの物理モードを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+/// ポイントを落とす処理(`fall_points`)は、ほかの生成・削除と同じ順番で`PrimitivesPlugin`が実行する
+pub struct PointPhysicsPlugin;
+
+impl Plugin for PointPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PointPhysics>().add_systems(
+            Update,
+            (toggle_physics, attach_falling)
+                .chain()
+                .after(crate::handle_keypress)
+                .after(crate::shape_rotation::rotate_shapes),
+        );
+    }
+}
+
+/// 物理モードの設定を保持するリソース
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct PointPhysics {
+    pub enabled: bool, // ポイントを落とすか(初期は無効)
+    pub gravity: f32,  // 重力加速度(単位/秒²)
+}
+
+impl Default for PointPhysics {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gravity: GRAVITY,
+        }
+    }
+}
+
+/// 落ちているポイントのワールド座標での位置と速度
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct FallingPoint {
+    pub position: Vec3, // ワールド座標での位置
+    pub velocity: Vec3, // 速度(単位/秒)
+}
+
+// 「6」キー：物理モードの有効・無効を切り替えるシステム
+fn toggle_physics(actions: Res<ButtonInput<SampleAction>>, mut physics: ResMut<PointPhysics>) {
+    if actions.just_pressed(SampleAction::TogglePhysics) {
+        physics.enabled = !physics.enabled;
+    }
+}
+
+// 有効な間は、生成アニメーションを終えたポイントを、今のワールド座標から落とし始めるシステム
+fn attach_falling(
+    mut commands: Commands,
+    physics: Res<PointPhysics>,
+    points: Query<
+        (Entity, &Transform, &ChildOf),
+        (
+            With<SamplePoint>,
+            Without<SpawningPoint>,
+            Without<FallingPoint>,
+        ),
+    >,
+    roots: Query<&Transform, With<ShapeRoot>>,
+) {
+    if !physics.enabled {
+        return;
+    }
+    for (entity, transform, child_of) in &points {
+        let Ok(root) = roots.get(child_of.parent()) else {
+            continue;
+        };
+        commands.entity(entity).insert(FallingPoint {
+            position: root.transform_point(transform.translation),
+            velocity: Vec3::ZERO,
+        });
+    }
+}
+
+// 落ちているポイントの速度と位置を進め、地面に着いたら削除アニメーションに移すシステム
+// (削除中のポイントも地面までは落とし、地面に着いたら止める)
+pub(crate) fn fall_points(
+    mut commands: Commands,
+    physics: Res<PointPhysics>,
+    mut points: Query<(
+        Entity,
+        &mut Transform,
+        &ChildOf,
+        &mut FallingPoint,
+        Has<SamplePoint>,
+    )>,
+    roots: Query<&Transform, (With<ShapeRoot>, Without<FallingPoint>)>,
+    mut counter: ResMut<PointCounter>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    let delta = time.delta_secs() * time_scale.0;
+    for (entity, mut transform, child_of, mut falling, is_sample) in &mut points {
+        let Ok(root) = roots.get(child_of.parent()) else {
+            continue;
+        };
+        falling.velocity.y -= physics.gravity * delta;
+        let step = falling.velocity * delta;
+        falling.position += step;
+
+        if falling.position.y <= GROUND_HEIGHT {
+            falling.position.y = GROUND_HEIGHT;
+            falling.velocity = Vec3::ZERO; // 削除アニメーションの間は地面に置いたままにする
+            if is_sample {
+                crate::start_despawning(commands.entity(entity));
+                counter.0 = counter.0.saturating_sub(1);
+            }
+        }
+        // 図形が回転・移動していても、ワールド座標の位置に置く
+        transform.translation = root
+            .compute_affine()
+            .inverse()
+            .transform_point3(falling.position);
+    }
+}
@@ -0,0 +1,214 @@
+//! カーソルでポイントを選んで、座標とサンプリング元を表示する
+//!
+//! カーソルの位置からカメラの奥へ向かうレイを飛ばし、ポイントを半径`PICK_RADIUS`の球とみなして
+//! レイが最初に当たったポイントを強調表示する(空間ハッシュ`PointSpatialIndex`でレイの近くだけを調べる)。
+//! そのポイントの座標・図形・サンプリングモードと、最も近い他のポイントまでの距離を
+//! 小さなツールチップに表示し、クリックするとツールチップを固定する(もう一度クリックで解除)。
+//! 左ボタンのドラッグはカメラの回転に使うので、押してから動かさずに離したときだけクリックとみなす。
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use sample_common::LocalizedText;
+
+use crate::panel::ControlPanel;
+use crate::spatial_index::{PointSpatialIndex, ray_hit};
+use crate::{SampledShapes, SamplingMode, SamplingParams, ShapeIndex};
+
+/// ポイントを選ぶときに、ポイントとみなす球の半径(表示される球より少し大きくする)
+/// ポイントの大きさの倍率(`SamplingParams::point_size`)をかけて使う
+pub const PICK_RADIUS: f32 = 0.05;
+
+/// 押してから離すまでにこれ以上カーソルが動いたら、クリックではなくドラッグとみなす(ピクセル)
+const CLICK_TOLERANCE: f32 = 4.0;
+
+/// 強調表示の球の半径(ポイントの大きさの倍率をかけて使う)
+const HIGHLIGHT_RADIUS: f32 = 0.08;
+
+/// カーソルを合わせたポイントの強調表示の色
+const HOVER_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
+
+/// 固定したポイントの強調表示の色
+const PINNED_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+
+/// ツールチップをポイントからずらす量(ピクセル)
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+/// カーソルで選んだポイントを保持するリソース
+#[derive(Resource, Default, Debug)]
+pub struct Picking {
+    pub hovered: Option<Entity>,  // カーソルを合わせているポイント
+    pub pinned: Option<Entity>,   // クリックで固定したポイント
+    press_position: Option<Vec2>, // 左ボタンを押したときのカーソルの位置
+}
+
+impl Picking {
+    /// ツールチップを表示するポイント(固定したポイントを優先する)
+    pub fn shown(&self) -> Option<Entity> {
+        self.pinned.or(self.hovered)
+    }
+}
+
+/// ツールチップのマーカーコンポーネント
+#[derive(Component)]
+struct Tooltip;
+
+/// レイが最初に当たるポイントを返す(ポイントは半径`radius`の球とみなす)
+pub fn pick_along_ray(
+    ray: Ray3d,
+    points: impl Iterator<Item = (Entity, Vec3)>,
+    radius: f32,
+) -> Option<Entity> {
+    points
+        .filter_map(|(entity, position)| Some((entity, ray_hit(ray, position, radius)?)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+// ツールチップを作るシステム(最初は表示しない)
+pub(crate) fn spawn_tooltip(mut commands: Commands) {
+    commands
+        .spawn((
+            Tooltip,
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        ))
+        .with_children(|tooltip| {
+            tooltip.spawn((LocalizedText::new("picking.tooltip"), TextFont::from_font_size(14.0)));
+        });
+}
+
+// カーソルからレイを飛ばしてポイントを選び、クリックで固定するシステム
+pub(crate) fn pick_point(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    index: Res<PointSpatialIndex>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    control_panel: Res<ControlPanel>,
+    params: Res<SamplingParams>,
+    mut picking: ResMut<Picking>,
+) {
+    let Ok(window) = windows.single() else {
+        return; // ウィンドウがない(ヘッドレス)
+    };
+    let (camera, camera_transform) = *camera;
+
+    // 操作パネルの上やウィンドウの外では何も選ばない
+    let cursor = window.cursor_position().filter(|_| !control_panel.wants_pointer);
+    let hovered = cursor
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+        .and_then(|ray| index.cast_ray(ray, PICK_RADIUS * params.point_size))
+        .map(|(entity, _)| entity);
+    if picking.hovered != hovered {
+        picking.hovered = hovered;
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        picking.press_position = cursor;
+    }
+    if mouse.just_released(MouseButton::Left) {
+        let press = picking.press_position.take();
+        let clicked = matches!(
+            (press, cursor),
+            (Some(press), Some(release)) if press.distance(release) < CLICK_TOLERANCE
+        );
+        if clicked {
+            // 何もないところや固定中のポイントをクリックすると解除する
+            picking.pinned = match hovered {
+                Some(entity) if picking.pinned != Some(entity) => Some(entity),
+                _ => None,
+            };
+        }
+    }
+}
+
+// 選んだポイントを強調表示し、ツールチップの内容と位置を更新するシステム
+pub(crate) fn update_tooltip(
+    mut picking: ResMut<Picking>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    points: Query<(&GlobalTransform, &ShapeIndex, &SamplingMode)>,
+    shapes: Res<SampledShapes>,
+    index: Res<PointSpatialIndex>,
+    mut tooltips: Query<(&mut Node, &Children), With<Tooltip>>,
+    mut texts: Query<&mut LocalizedText>,
+    params: Res<SamplingParams>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((mut node, children)) = tooltips.single_mut() else {
+        return;
+    };
+
+    // 固定したポイントが削除されたら固定を解除する
+    if picking.pinned.is_some_and(|pinned| !points.contains(pinned)) {
+        picking.pinned = None;
+    }
+    let shown = picking
+        .shown()
+        .and_then(|entity| Some((entity, points.get(entity).ok()?)));
+    let Some((entity, (transform, shape_index, mode))) = shown else {
+        hide(&mut node);
+        return;
+    };
+
+    let position = transform.translation(); // 図形の回転を含めたワールド座標
+    let color = if picking.pinned.is_some() {
+        PINNED_COLOR
+    } else {
+        HOVER_COLOR
+    };
+    gizmos.sphere(
+        Isometry3d::from_translation(position),
+        HIGHLIGHT_RADIUS * params.point_size,
+        color,
+    );
+
+    // ポイントが画面の外(カメラの後ろ)にあるときは、ツールチップを隠す
+    let (camera, camera_transform) = *camera;
+    let Ok(screen) = camera.world_to_viewport(camera_transform, position) else {
+        hide(&mut node);
+        return;
+    };
+    let (left, top) = (
+        Val::Px(screen.x + TOOLTIP_OFFSET.x),
+        Val::Px(screen.y + TOOLTIP_OFFSET.y),
+    );
+    if (node.display, node.left, node.top) != (Display::Flex, left, top) {
+        node.display = Display::Flex;
+        node.left = left;
+        node.top = top;
+    }
+
+    let shape_key = shapes
+        .0
+        .get(shape_index.0)
+        .map_or("picking.unknown", |(shape, _)| shape.name_key());
+    // 自分自身を除いた、最も近いポイントまでの距離
+    let nearest = index
+        .k_nearest(position, 2)
+        .into_iter()
+        .find(|(other, _)| *other != entity)
+        .map_or("-".to_string(), |(_, distance)| format!("{distance:.3}"));
+    let text = LocalizedText::new("picking.tooltip")
+        .with_arg("x", format!("{:.3}", position.x))
+        .with_arg("y", format!("{:.3}", position.y))
+        .with_arg("z", format!("{:.3}", position.z))
+        .with_key_arg("shape", shape_key)
+        .with_key_arg("mode", mode.name_key())
+        .with_arg("nearest", nearest);
+    for child in children {
+        if let Ok(mut localized) = texts.get_mut(*child) {
+            localized.set_if_neq(text.clone());
+        }
+    }
+}
+
+/// ツールチップを隠す(隠れているときはNodeを変更済みにしない)
+fn hide(node: &mut Mut<Node>) {
+    if node.display != Display::None {
+        node.display = Display::None;
+    }
+}
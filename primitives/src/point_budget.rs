@@ -0,0 +1,130 @@
+//! フレーム時間に合わせたポイントの数の自動調整
+//!
+//! Web(WebGL2)や遅い環境では、設定したポイントの最大数・1フレームの生成数のままだと
+//! フレームレートが保てないことがある。`FrameTimeDiagnosticsPlugin`の平滑化したFPSを
+//! `ADJUST_INTERVAL`秒ごとに調べ、目標のFPSを下回っていれば倍率を下げ、余裕があれば少しずつ戻す。
+//! 実際の上限と生成数は、`SamplingParams`と`SpawnRate`の値にこの倍率をかけたものになる。
+//! ただし、キーや操作パネルで変えた生成数には倍率をかけない(選んだ速さのまま生成する)。
+//! 手動モードでキューに積んだポイントは、これまで通りすべて生成する。
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use sample_common::QualityLevel;
+
+use crate::{SamplingParams, SpawnRate};
+
+/// 倍率の下限(これより少なくはしない)
+pub const MIN_BUDGET_SCALE: f32 = 0.05;
+
+/// 目標のFPSを下回ったときに倍率にかける値(すぐに軽くする)
+pub const BUDGET_DECREASE: f32 = 0.8;
+
+/// 余裕があるときに倍率にかける値(ゆっくり戻す)
+pub const BUDGET_INCREASE: f32 = 1.05;
+
+/// 目標のFPSに対して、この割合を下回ったら倍率を下げる
+const LOWER_THRESHOLD: f32 = 0.9;
+
+/// 目標のFPSに対して、この割合を上回ったら倍率を上げる
+const RAISE_THRESHOLD: f32 = 1.05;
+
+/// 倍率を調整する間隔(秒)
+const ADJUST_INTERVAL: f32 = 0.5;
+
+/// 起動直後はシェーダーのコンパイルなどで遅くなるので、この秒数は調整しない
+const WARMUP_SECONDS: f32 = 5.0;
+
+/// フレーム時間を計測してポイントの数を調整するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct PointBudgetPlugin;
+
+impl Plugin for PointBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        app.init_resource::<PointBudget>()
+            .add_systems(Update, adapt_point_budget);
+    }
+}
+
+/// フレーム時間に合わせたポイントの数の倍率を保持するリソース
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct PointBudget {
+    pub enabled: bool,    // 自動で調整するか(しなければ倍率は1)
+    pub target_fps: f32,  // 保ちたいフレームレート
+    pub scale: f32,       // 最大数・生成数にかける倍率(MIN_BUDGET_SCALE〜1)
+    pub fps: Option<f32>, // 最後に計測したFPS(計測前はNone)
+}
+
+impl Default for PointBudget {
+    /// Webでは60fpsが出ないことが多いので、目標を30fpsにする
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_fps: if cfg!(target_arch = "wasm32") {
+                30.0
+            } else {
+                60.0
+            },
+            scale: 1.0,
+            fps: None,
+        }
+    }
+}
+
+impl PointBudget {
+    /// 計測したFPSに合わせて倍率を調整する
+    pub fn adjust(&mut self, fps: f32) {
+        self.fps = Some(fps);
+        if fps < self.target_fps * LOWER_THRESHOLD {
+            self.scale = (self.scale * BUDGET_DECREASE).max(MIN_BUDGET_SCALE);
+        } else if fps > self.target_fps * RAISE_THRESHOLD {
+            self.scale = (self.scale * BUDGET_INCREASE).min(1.0);
+        }
+    }
+
+    /// 現在の倍率(調整しないときは1)
+    pub fn factor(&self) -> f32 {
+        if self.enabled { self.scale } else { 1.0 }
+    }
+
+    /// 自動モードで存在できるポイントの数(描画品質による上限も含める)
+    pub fn point_limit(&self, params: &SamplingParams, quality: QualityLevel) -> usize {
+        (params.point_limit(quality) as f32 * self.factor()).round() as usize
+    }
+
+    /// 自動モードで1フレームあたりに生成するポイント数(端数は次のフレームに持ち越す)
+    /// キーや操作パネルで変えた生成数は、そのまま使う
+    pub fn points_per_frame(&self, rate: &SpawnRate) -> f32 {
+        if rate.user_set {
+            rate.points_per_frame as f32
+        } else {
+            rate.points_per_frame as f32 * self.factor()
+        }
+    }
+}
+
+// 平滑化したFPSを一定間隔で調べて、ポイントの数の倍率を調整するシステム
+fn adapt_point_budget(
+    diagnostics: Res<DiagnosticsStore>,
+    time: Res<Time<Real>>,
+    mut budget: ResMut<PointBudget>,
+    mut elapsed: Local<f32>,
+) {
+    if !budget.enabled || time.elapsed_secs() < WARMUP_SECONDS {
+        return;
+    }
+    *elapsed += time.delta_secs();
+    if *elapsed < ADJUST_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let Some(fps) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    else {
+        return;
+    };
+    budget.adjust(fps as f32);
+}
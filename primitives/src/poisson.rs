@@ -0,0 +1,55 @@
+//! ポアソンディスク(ブルーノイズ)サンプリング用の空間格子
+//!
+//! ポアソンディスクサンプリングでは、既存のポイントから半径`radius`以内に入る候補を捨てる。
+//! すべてのポイントとの距離を調べると遅いので、一辺が`radius`の立方体の格子にポイントを入れておき、
+//! 候補のいるセルと隣り合う26個のセルのポイントだけを調べる。
+
+use std::collections::HashMap;
+
+use bevy::math::{IVec3, Vec3};
+
+/// ポイントを一辺`radius`のセルに分けて保持する格子
+#[derive(Debug, Clone)]
+pub struct PoissonGrid {
+    radius: f32,                       // ポイント同士の最小距離(セルの一辺)
+    cells: HashMap<IVec3, Vec<Vec3>>, // セルの座標ごとのポイント
+}
+
+impl PoissonGrid {
+    /// 空の格子を作る(`radius`は0より大きくする)
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius: radius.max(f32::EPSILON),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// ポイントが入るセルの座標
+    fn cell(&self, point: Vec3) -> IVec3 {
+        (point / self.radius).floor().as_ivec3()
+    }
+
+    /// ポイントを格子に追加する
+    pub fn insert(&mut self, point: Vec3) {
+        self.cells.entry(self.cell(point)).or_default().push(point);
+    }
+
+    /// 候補が、すでにあるすべてのポイントから`radius`以上離れているか
+    pub fn is_far_enough(&self, candidate: Vec3) -> bool {
+        let center = self.cell(candidate);
+        let radius_squared = self.radius * self.radius;
+        (-1..=1).all(|x| {
+            (-1..=1).all(|y| {
+                (-1..=1).all(|z| {
+                    self.cells
+                        .get(&(center + IVec3::new(x, y, z)))
+                        .is_none_or(|points| {
+                            points
+                                .iter()
+                                .all(|point| point.distance_squared(candidate) >= radius_squared)
+                        })
+                })
+            })
+        })
+    }
+}
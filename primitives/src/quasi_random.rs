@@ -0,0 +1,200 @@
+//! 準乱数(Halton列)によるサンプリング
+//!
+//! 疑似乱数(ChaCha)の点は偶然かたまったり隙間ができたりするが、Halton列のような低食い違い量列
+//! (準乱数)は次の点を空いている所に置いていくので、少ない点数でも図形の中を均等に覆う。
+//! 「Q」キーで`RandomGenerator`を切り替えると、`RandomSource`を疑似乱数と準乱数の間で作り直し、
+//! 両者の埋まり方を見比べられる(既存のポイントはそのまま残す)。
+//! 準乱数では、ポイント1個ごとに`start_point`で列の次の点に進み、その点の中で乱数を使うたびに
+//! 次の次元(素数の底)の値を返す。最初の次元で図形を選び、続く次元で図形の中の位置を決める。
+//! 棄却法で何度も引き直して`PRIMES`の次元を使い切ったら、残りは疑似乱数で補う。
+//! シード値から次元ごとのずらし量(Cranley-Patterson回転)を決めるので、シード値ごとに別の列になる。
+
+use std::io::{self, Read, Write};
+
+use bevy::prelude::*;
+use rand::{Error, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::session::{read_chacha, read_f64, read_u64, write_chacha};
+use crate::{RandomSource, RngSeed, SampleAction};
+
+/// 次元ごとの底(素数)。1個のポイントで使う乱数の数より多めに用意する
+pub const PRIMES: [u64; 16] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+/// 乱数生成器の切り替えを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct QuasiRandomPlugin;
+
+impl Plugin for QuasiRandomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RandomGenerator>().add_systems(
+            Update,
+            (
+                toggle_generator,
+                apply_random_generator.run_if(resource_changed::<RandomGenerator>),
+            )
+                .chain()
+                .after(crate::handle_keypress)
+                .before(crate::spawn_points),
+        );
+    }
+}
+
+/// ポイントの位置を決める乱数生成器の種類
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RandomGenerator {
+    #[default]
+    PseudoRandom, // 疑似乱数(ChaCha8)
+    QuasiRandom, // 準乱数(Halton列)
+}
+
+impl RandomGenerator {
+    /// 画面に表示する名前のキー(assets/locale以下の対応表)
+    pub fn name_key(self) -> &'static str {
+        match self {
+            RandomGenerator::PseudoRandom => "generator.pseudo_random",
+            RandomGenerator::QuasiRandom => "generator.quasi_random",
+        }
+    }
+}
+
+/// 底`base`での`index`の根基逆関数(桁を小数点の反対側に折り返した値、[0, 1))
+/// 底2では 1, 2, 3, 4, … が 0.5, 0.25, 0.75, 0.125, … になる(van der Corput列)
+pub fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    let mut inverse = 0.0;
+    let mut scale = 1.0 / base as f64;
+    while index > 0 {
+        inverse += (index % base) as f64 * scale;
+        index /= base;
+        scale /= base as f64;
+    }
+    inverse
+}
+
+/// ずらし量を加えたHalton列を返す乱数生成器
+#[derive(Clone, Debug)]
+pub struct QuasiRandom {
+    index: u64,                   // 今の点の番号(1から)
+    dimension: usize,             // 今の点で次に使う次元
+    offsets: [f64; PRIMES.len()], // 次元ごとのずらし量(シード値から決める)
+    fallback: ChaCha8Rng,         // 次元を使い切ったときの疑似乱数
+}
+
+impl QuasiRandom {
+    /// シード値から作る(最初の`start_point`で1番目の点になる)
+    pub fn new(seed: u64) -> Self {
+        let mut fallback = ChaCha8Rng::seed_from_u64(seed);
+        let offsets = std::array::from_fn(|_| fallback.r#gen());
+        Self {
+            index: 0,
+            dimension: 0,
+            offsets,
+            fallback,
+        }
+    }
+
+    /// 列の次の点に進む(ポイント1個ごとに呼ぶ)
+    pub fn start_point(&mut self) {
+        self.index += 1;
+        self.dimension = 0;
+    }
+
+    /// 今の点の番号
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// 今の点の次の次元の値([0, 1))
+    pub fn next_f64(&mut self) -> f64 {
+        let Some(base) = PRIMES.get(self.dimension) else {
+            return self.fallback.r#gen();
+        };
+        let value = (radical_inverse(self.index, *base) + self.offsets[self.dimension]).fract();
+        self.dimension += 1;
+        value
+    }
+
+    /// 今の位置から`count`個の点を受け持つ列を切り出し、自分は`count`個先に進む
+    /// 並列に計算するバッチでも、1つずつ生成したときと同じ点の並びになる
+    pub fn split(&mut self, count: usize) -> Self {
+        let mut batch = self.clone();
+        batch.fallback = ChaCha8Rng::seed_from_u64(self.fallback.r#gen());
+        self.index += count as u64;
+        self.dimension = 0;
+        batch
+    }
+
+    /// 今の状態を書き出す(セッションの保存に使う)
+    pub fn write_state(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(&self.index.to_le_bytes())?;
+        out.write_all(&(self.dimension as u64).to_le_bytes())?;
+        for offset in self.offsets {
+            out.write_all(&offset.to_le_bytes())?;
+        }
+        write_chacha(&self.fallback, out)
+    }
+
+    /// `write_state`で書き出した状態から作る(続きの点から同じ列になる)
+    pub fn read_state(input: &mut impl Read) -> io::Result<Self> {
+        let index = read_u64(input)?;
+        let dimension = read_u64(input)? as usize;
+        let mut offsets = [0.0; PRIMES.len()];
+        for offset in &mut offsets {
+            *offset = read_f64(input)?;
+        }
+        Ok(Self {
+            index,
+            dimension,
+            offsets,
+            fallback: read_chacha(input)?,
+        })
+    }
+}
+
+/// randの分布から使えるように、[0, 1)の値を整数の全範囲に広げて返す
+impl RngCore for QuasiRandom {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (self.next_f64() * 2f64.powi(64)) as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// 「Q」キー：疑似乱数と準乱数を切り替えるシステム
+fn toggle_generator(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut generator: ResMut<RandomGenerator>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleGenerator) {
+        return;
+    }
+    *generator = match *generator {
+        RandomGenerator::PseudoRandom => RandomGenerator::QuasiRandom,
+        RandomGenerator::QuasiRandom => RandomGenerator::PseudoRandom,
+    };
+    info!("乱数生成器を{:?}にしました", *generator);
+}
+
+// 選んだ種類の乱数生成器を、シード値から作り直すシステム
+fn apply_random_generator(
+    generator: Res<RandomGenerator>,
+    seed: Res<RngSeed>,
+    mut random_source: ResMut<RandomSource>,
+) {
+    if random_source.generator() != *generator {
+        *random_source = RandomSource::new(*generator, &seed);
+    }
+}
@@ -0,0 +1,382 @@
+//! サンプリングの記録と再生
+//!
+//! 「K」キーで記録を始めると、ポイントの生成・削除を1つずつ(何フレーム目か・ポイントの番号・
+//! 図形・モード・図形の中心からの位置)記録し、もう一度「K」キーで`recordings/primitives/`にCSVで保存する。
+//! 記録を始めた時点で残っているポイントは、0フレーム目の生成として記録する。
+//! 「L」キーで最後に保存した記録を読み込み、シーンのポイントを消してから同じ順番で生成・削除し直す。
+//! 再生は記録したフレーム番号に合わせて1フレームずつ進めるので、フレームレートに関係なく
+//! 同じ並びになる(一時停止中は記録のフレームも再生も進まない)。再生中は乱数による生成・削除は止める。
+//! Webではファイルシステムがないので、記録を保存・読み込みできない旨をログに出すだけにする。
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use sample_common::PauseState;
+
+use crate::export::{kind_name, parse_kind};
+use crate::{
+    DespawningPoint, PointCounter, SampleAction, SamplePoint, SamplingMode, ShapeIndex, ShapeRoot,
+    new_sample_point, shape_roots, start_despawning,
+};
+
+/// 保存先のディレクトリ(実行時のカレントディレクトリからの相対パス)
+const RECORDING_DIR: &str = "recordings/primitives";
+
+/// CSVの見出しの行
+const HEADER: &str = "frame,event,id,shape,kind,x,y,z";
+
+/// 記録する1つの出来事
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayEvent {
+    /// ポイントを生成した
+    Spawn {
+        id: u64,            // 記録の中でのポイントの番号
+        shape: usize,       // サンプリングした図形(SampledShapesのインデックス)
+        mode: SamplingMode, // サンプリングモード
+        position: Vec3,     // 図形の中心からの位置(図形が回転していても同じ場所に再生できる)
+    },
+    /// ポイントを削除した(消滅アニメーションを始めた)
+    Despawn { id: u64 },
+}
+
+/// 何フレーム目に起きたかを付けた出来事
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordedEvent {
+    pub frame: u64,         // 記録を始めてから何フレーム目か
+    pub event: ReplayEvent, // 起きたこと
+}
+
+/// 記録・再生の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayState {
+    #[default]
+    Idle, // 記録も再生もしていない
+    Recording, // 生成・削除を記録中
+    Playing,   // 記録を再生中
+}
+
+/// 記録と再生を管理するリソース
+#[derive(Resource, Debug)]
+pub struct Replay {
+    pub state: ReplayState,     // 記録・再生の状態
+    pub dir: PathBuf,           // 記録の保存先
+    frame: u64,                 // 記録・再生を始めてから何フレーム目か
+    events: Vec<RecordedEvent>, // 記録した出来事、または再生する出来事
+    cursor: usize,              // 次に再生する出来事の位置
+    ids: HashMap<u64, Entity>,  // 再生中のポイントの番号とエンティティの対応
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            state: ReplayState::Idle,
+            dir: PathBuf::from(RECORDING_DIR),
+            frame: 0,
+            events: Vec::new(),
+            cursor: 0,
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl Replay {
+    /// 記録した出来事(再生中は再生する出来事)
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// 出来事の並びの再生を始める(最初のフレームでシーンのポイントを消す)
+    pub fn play(&mut self, events: Vec<RecordedEvent>) {
+        self.state = ReplayState::Playing;
+        self.frame = 0;
+        self.events = events;
+        self.cursor = 0;
+        self.ids.clear();
+    }
+}
+
+/// 再生中かどうか(再生中は乱数による生成・削除を止める実行条件)
+pub fn is_replaying(replay: Res<Replay>) -> bool {
+    replay.state == ReplayState::Playing
+}
+
+/// 出来事の並びをCSV形式で書き出す
+pub fn write_recording(events: &[RecordedEvent], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{HEADER}")?;
+    for recorded in events {
+        match recorded.event {
+            ReplayEvent::Spawn {
+                id,
+                shape,
+                mode,
+                position,
+            } => writeln!(
+                out,
+                "{},spawn,{id},{shape},{},{},{},{}",
+                recorded.frame,
+                kind_name(mode),
+                position.x,
+                position.y,
+                position.z
+            )?,
+            ReplayEvent::Despawn { id } => writeln!(out, "{},despawn,{id},,,,,", recorded.frame)?,
+        }
+    }
+    Ok(())
+}
+
+/// CSV形式の記録を読み込む
+pub fn read_recording(input: impl BufRead) -> io::Result<Vec<RecordedEvent>> {
+    let invalid = |line: usize, message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}行目: {message}", line + 1),
+        )
+    };
+
+    let mut events = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        if number == 0 {
+            if line != HEADER {
+                return Err(invalid(number, "見出しの行が違います"));
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').collect();
+        let [frame, event, id, shape, kind, x, y, z] = fields[..] else {
+            return Err(invalid(number, "列の数が違います"));
+        };
+        let frame = frame.parse().map_err(|_| invalid(number, "フレーム番号が不正です"))?;
+        let id = id.parse().map_err(|_| invalid(number, "ポイントの番号が不正です"))?;
+        let event = match event {
+            "spawn" => {
+                let coordinate = |value: &str| {
+                    value
+                        .parse::<f32>()
+                        .map_err(|_| invalid(number, "座標が不正です"))
+                };
+                ReplayEvent::Spawn {
+                    id,
+                    shape: shape.parse().map_err(|_| invalid(number, "図形の番号が不正です"))?,
+                    mode: parse_kind(kind).ok_or_else(|| invalid(number, "モードが不正です"))?,
+                    position: Vec3::new(coordinate(x)?, coordinate(y)?, coordinate(z)?),
+                }
+            }
+            "despawn" => ReplayEvent::Despawn { id },
+            _ => return Err(invalid(number, "出来事の種類が不正です")),
+        };
+        events.push(RecordedEvent { frame, event });
+    }
+    Ok(events)
+}
+
+/// 記録をディレクトリに書き出し、そのパスを返す
+pub fn save_recording(dir: &Path, name: &str, events: &[RecordedEvent]) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{name}.csv"));
+    let mut file = io::BufWriter::new(std::fs::File::create(&path)?);
+    write_recording(events, &mut file)?;
+    file.flush()?;
+    Ok(path)
+}
+
+/// ディレクトリの中で最後に保存した記録を読み込む(ファイル名に保存した時刻が入っている)
+pub fn load_latest_recording(dir: &Path) -> io::Result<(PathBuf, Vec<RecordedEvent>)> {
+    let latest = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "csv"))
+        .max()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "記録がありません"))?;
+    let file = io::BufReader::new(std::fs::File::open(&latest)?);
+    Ok((latest, read_recording(file)?))
+}
+
+// 「K」キーで記録の開始・保存、「L」キーで再生の開始・停止をするシステム
+pub(crate) fn handle_replay_keys(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut replay: ResMut<Replay>,
+    samples: Query<(Entity, &Transform, &ShapeIndex, &SamplingMode), With<SamplePoint>>,
+) {
+    if actions.just_pressed(SampleAction::ToggleRecording) {
+        match replay.state {
+            ReplayState::Idle => {
+                // 残っているポイントは0フレーム目に生成したことにする
+                replay.events = samples
+                    .iter()
+                    .map(|(entity, transform, index, mode)| RecordedEvent {
+                        frame: 0,
+                        event: spawned(entity, transform, index, *mode),
+                    })
+                    .collect();
+                replay.frame = 0;
+                replay.state = ReplayState::Recording;
+                info!("記録を始めました");
+            }
+            ReplayState::Recording => {
+                replay.state = ReplayState::Idle;
+                save(&replay);
+            }
+            ReplayState::Playing => {}
+        }
+    }
+
+    if actions.just_pressed(SampleAction::TogglePlayback) {
+        match replay.state {
+            ReplayState::Idle => load_and_play(&mut replay),
+            ReplayState::Playing => {
+                replay.state = ReplayState::Idle;
+                info!("再生を止めました");
+            }
+            ReplayState::Recording => {}
+        }
+    }
+}
+
+/// 記録を保存する
+fn save(replay: &Replay) {
+    if cfg!(target_arch = "wasm32") {
+        warn!("Webでは記録を保存できません");
+        return;
+    }
+
+    // ファイル名は保存した時刻(UNIX時間のミリ秒)にする
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    match save_recording(&replay.dir, &format!("session-{millis}"), &replay.events) {
+        Ok(path) => info!(
+            "{}フレーム・{}個の出来事を記録しました: {}",
+            replay.frame,
+            replay.events.len(),
+            path.display()
+        ),
+        Err(err) => error!("記録を保存できません: {err}"),
+    }
+}
+
+/// 最後に保存した記録を読み込んで再生を始める
+fn load_and_play(replay: &mut Replay) {
+    if cfg!(target_arch = "wasm32") {
+        warn!("Webでは記録を読み込めません");
+        return;
+    }
+
+    match load_latest_recording(&replay.dir) {
+        Ok((path, events)) => {
+            info!("記録を再生します: {}", path.display());
+            replay.play(events);
+        }
+        Err(err) => error!("記録を読み込めません: {err}"),
+    }
+}
+
+/// 生成したポイントの出来事
+fn spawned(
+    entity: Entity,
+    transform: &Transform,
+    index: &ShapeIndex,
+    mode: SamplingMode,
+) -> ReplayEvent {
+    ReplayEvent::Spawn {
+        id: entity.to_bits(),
+        shape: index.0,
+        mode,
+        position: transform.translation,
+    }
+}
+
+// 記録中は、このフレームに生成・削除されたポイントを記録するシステム
+// 記録していないときも毎フレーム実行して、生成・削除の検出を最新にしておく
+pub(crate) fn record_events(
+    mut replay: ResMut<Replay>,
+    pause: Res<PauseState>,
+    added: Query<(Entity, &Transform, &ShapeIndex, &SamplingMode), Added<SamplePoint>>,
+    mut removed: RemovedComponents<SamplePoint>,
+) {
+    if replay.state != ReplayState::Recording {
+        removed.clear();
+        return;
+    }
+
+    let frame = replay.frame;
+    for (entity, transform, index, mode) in &added {
+        let event = spawned(entity, transform, index, *mode);
+        replay.events.push(RecordedEvent { frame, event });
+    }
+    for entity in removed.read() {
+        let event = ReplayEvent::Despawn {
+            id: entity.to_bits(),
+        };
+        replay.events.push(RecordedEvent { frame, event });
+    }
+
+    // 一時停止中は再生も進まないので、フレームを数えない
+    if *pause == PauseState::Running {
+        replay.frame += 1;
+    }
+}
+
+// 再生中は、このフレームの出来事の通りにポイントを生成・削除するシステム
+pub(crate) fn play_events(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    mut counter: ResMut<PointCounter>,
+    samples: Query<Entity, Or<(With<SamplePoint>, With<DespawningPoint>)>>,
+    alive: Query<(), With<SamplePoint>>,
+    roots: Query<(Entity, &Transform, &ShapeRoot)>,
+) {
+    // 最初のフレームでは、記録を始めたときと同じく何もない状態にする
+    if replay.frame == 0 {
+        for entity in &samples {
+            commands.entity(entity).despawn();
+        }
+        counter.0 = 0;
+    }
+
+    let roots = shape_roots(&roots);
+    let replay = &mut *replay;
+    while let Some(recorded) = replay.events.get(replay.cursor) {
+        if recorded.frame > replay.frame {
+            break;
+        }
+        match recorded.event {
+            ReplayEvent::Spawn {
+                id,
+                shape,
+                mode,
+                position,
+            } => {
+                // 今の表示にない図形(3Dで記録して2Dで再生したときなど)のポイントは生成しない
+                if let Some(root) = roots.get(shape).copied().flatten() {
+                    let entity = commands
+                        .spawn(new_sample_point(position, shape, mode, root))
+                        .id();
+                    replay.ids.insert(id, entity);
+                    counter.0 += 1;
+                }
+            }
+            ReplayEvent::Despawn { id } => {
+                // リスタートなどで既に消えたポイントは何もしない
+                let entity = replay.ids.remove(&id).filter(|entity| alive.contains(*entity));
+                if let Some(entity) = entity {
+                    start_despawning(commands.entity(entity));
+                    counter.0 = counter.0.saturating_sub(1);
+                }
+            }
+        }
+        replay.cursor += 1;
+    }
+    replay.frame += 1;
+
+    if replay.cursor >= replay.events.len() {
+        replay.state = ReplayState::Idle;
+        info!("再生が終わりました");
+    }
+}
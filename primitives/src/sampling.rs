@@ -0,0 +1,135 @@
+//! Bevyがランダムサンプリングを提供していない図形(トーラス・円錐・円錐台)のサンプリング
+//!
+//! どの図形も中心が原点で、Y軸の周りの回転体になっている(Bevyのメッシュと同じ向き)。
+//! 内部は体積に対して、境界は表面積に対して一様になるようにサンプリングする。
+//! Bevyは円錐台の体積・表面積も提供していないので、ここで計算する。
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::math::prelude::*;
+use rand::Rng;
+
+/// `ShapeSample`と同じ形で、内部と境界からランダムな点を返すトレイト
+pub trait ExtraShapeSample {
+    /// 図形の「内部」から一様にランダムな点を返す
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3;
+
+    /// 図形の「境界（表面）」から一様にランダムな点を返す
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3;
+}
+
+/// トーラス(ドーナツ型)。リングはXZ平面上にある
+impl ExtraShapeSample for Torus {
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 管の断面(円)の点を選び、外側ほど一周が長いので、中心からの距離に比例して採用する
+        let outer = self.major_radius + self.minor_radius;
+        let section = loop {
+            let point = Circle::new(self.minor_radius).sample_interior(rng);
+            if rng.gen_range(0.0..outer) < self.major_radius + point.x {
+                break point;
+            }
+        };
+        revolve(self.major_radius + section.x, section.y, rng)
+    }
+
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 管の断面の円周上の点も、中心からの距離に比例して採用する
+        let outer = self.major_radius + self.minor_radius;
+        let section = loop {
+            let point = Circle::new(self.minor_radius).sample_boundary(rng);
+            if rng.gen_range(0.0..outer) < self.major_radius + point.x {
+                break point;
+            }
+        };
+        revolve(self.major_radius + section.x, section.y, rng)
+    }
+}
+
+/// 円錐。先端が+Y側、底面が-Y側にある
+impl ExtraShapeSample for Cone {
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 先端からの距離tの断面積はt²に比例するので、一様乱数の立方根にする
+        let t = rng.r#gen::<f32>().cbrt();
+        let disk = Circle::new(self.radius * t).sample_interior(rng);
+        Vec3::new(disk.x, self.height * (0.5 - t), disk.y)
+    }
+
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 底面と側面は面積の比で選ぶ(側面積πrl、底面積πr²)
+        let slant = self.radius.hypot(self.height);
+        if rng.gen_range(0.0..self.radius + slant) < self.radius {
+            let disk = Circle::new(self.radius).sample_interior(rng);
+            return Vec3::new(disk.x, -0.5 * self.height, disk.y);
+        }
+        // 側面の円周の長さは先端からの距離tに比例するので、一様乱数の平方根にする
+        let t = rng.r#gen::<f32>().sqrt();
+        revolve(self.radius * t, self.height * (0.5 - t), rng)
+    }
+}
+
+/// 円錐台。上面が+Y側、下面が-Y側にある
+impl ExtraShapeSample for ConicalFrustum {
+    fn sample_interior<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 高さを一様に選び、断面積(半径の2乗)に比例して採用する
+        let max_radius = self.radius_top.max(self.radius_bottom);
+        let (radius, y) = loop {
+            let (radius, y) = frustum_level(self, rng.r#gen());
+            if rng.gen_range(0.0..max_radius * max_radius) < radius * radius {
+                break (radius, y);
+            }
+        };
+        let disk = Circle::new(radius).sample_interior(rng);
+        Vec3::new(disk.x, y, disk.y)
+    }
+
+    fn sample_boundary<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        // 上面・下面・側面は面積の比で選ぶ(側面積π(r1+r2)l)
+        let slant = (self.radius_bottom - self.radius_top).hypot(self.height);
+        let top = self.radius_top * self.radius_top;
+        let bottom = self.radius_bottom * self.radius_bottom;
+        let side = (self.radius_top + self.radius_bottom) * slant;
+        let choice = rng.gen_range(0.0..top + bottom + side);
+        if choice < top {
+            let disk = Circle::new(self.radius_top).sample_interior(rng);
+            return Vec3::new(disk.x, 0.5 * self.height, disk.y);
+        }
+        if choice < top + bottom {
+            let disk = Circle::new(self.radius_bottom).sample_interior(rng);
+            return Vec3::new(disk.x, -0.5 * self.height, disk.y);
+        }
+        // 側面は高さを一様に選び、その高さの円周の長さ(半径)に比例して採用する
+        let max_radius = self.radius_top.max(self.radius_bottom);
+        let (radius, y) = loop {
+            let (radius, y) = frustum_level(self, rng.r#gen());
+            if rng.gen_range(0.0..max_radius) < radius {
+                break (radius, y);
+            }
+        };
+        revolve(radius, y, rng)
+    }
+}
+
+/// 円錐台の体積
+pub fn frustum_volume(frustum: &ConicalFrustum) -> f32 {
+    let (top, bottom) = (frustum.radius_top, frustum.radius_bottom);
+    PI * frustum.height * (top * top + top * bottom + bottom * bottom) / 3.0
+}
+
+/// 円錐台の表面積(上面・下面・側面の合計)
+pub fn frustum_area(frustum: &ConicalFrustum) -> f32 {
+    let (top, bottom) = (frustum.radius_top, frustum.radius_bottom);
+    let slant = (bottom - top).hypot(frustum.height);
+    PI * (top * top + bottom * bottom + (top + bottom) * slant)
+}
+
+/// 円錐台の高さの割合(下面が0、上面が1)から、その高さの半径とY座標を求める
+fn frustum_level(frustum: &ConicalFrustum, fraction: f32) -> (f32, f32) {
+    let radius = frustum.radius_bottom + (frustum.radius_top - frustum.radius_bottom) * fraction;
+    (radius, frustum.height * (fraction - 0.5))
+}
+
+/// Y軸からの距離と高さを指定し、Y軸の周りのランダムな角度に回した点を返す
+fn revolve<R: Rng + ?Sized>(distance: f32, y: f32, rng: &mut R) -> Vec3 {
+    let angle = rng.gen_range(0.0..TAU);
+    Vec3::new(distance * angle.cos(), y, distance * angle.sin())
+}
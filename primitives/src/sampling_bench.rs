@@ -0,0 +1,194 @@
+//! サンプリングの速さの計測(`--bench`)
+//!
+//! `--bench`を付けて起動すると、ウィンドウなしで、図形ごと・サンプリングモードごと・乱数生成器ごとに
+//! 決まった数(`--bench-samples`、既定は10000個)の点をサンプリングして時間を測り、
+//! 1秒あたりのサンプル数の表を表示して終了する。
+//! ポイントのエンティティは作らない(生成アニメーションや描画の時間を含めない)ので、
+//! 図形や乱数生成器のサンプリングの実装を変えたときに、前後の速さを比べられる。
+//! 乱数生成器はどの組み合わせでも`--seed`のシード値から始めるので、同じ引数なら同じ点をサンプリングする。
+//! ポアソンディスクは既存の点から離れた候補だけを採用するので、採用できた数で速さを求める(図形が埋まると遅くなる)。
+//! 計測するのは起動したときに並んでいる図形(読み込み中のglTFのメッシュは含めない)。
+//! アプリ全体のフレーム時間の計測は`benchmarks`クレートで行う。
+//!
+//! ```bash
+//! cargo run -p primitives --release -- --bench --bench-samples 100000
+//! ```
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::math::ShapeSample;
+use bevy::prelude::*;
+
+use crate::poisson::PoissonGrid;
+use crate::quasi_random::RandomGenerator;
+use crate::{
+    POISSON_ATTEMPTS, RandomSource, RngSeed, SampledShapes, SamplingMode, SamplingParams, Shape,
+};
+
+/// `--bench-samples`を指定しなかったときに、組み合わせごとにサンプリングする点の数
+pub const DEFAULT_BENCH_SAMPLES: usize = 10_000;
+
+/// 計測するサンプリングモード
+const MODES: [SamplingMode; 3] = [
+    SamplingMode::Interior,
+    SamplingMode::Boundary,
+    SamplingMode::PoissonDisk,
+];
+
+/// 計測する乱数生成器
+const GENERATORS: [RandomGenerator; 2] =
+    [RandomGenerator::PseudoRandom, RandomGenerator::QuasiRandom];
+
+/// サンプリングの速さの計測の設定(`--bench`を付けて起動したときだけ挿入する)
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct SamplingBench {
+    pub samples_per_shape: usize, // 図形・モード・乱数生成器の組み合わせごとにサンプリングする点の数
+}
+
+impl SamplingBench {
+    /// コマンドライン引数から計測の設定を読み取る(`--bench`がなければNone)
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        let mut enabled = false;
+        let mut bench = Self {
+            samples_per_shape: DEFAULT_BENCH_SAMPLES,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bench" => enabled = true,
+                "--bench-samples" => {
+                    bench.samples_per_shape = args
+                        .next()
+                        .and_then(|value| value.parse().ok())
+                        .filter(|samples| *samples > 0)
+                        .expect("--bench-samples には正の整数を指定してください");
+                }
+                _ => {} // 他の引数(--seedなど)は無視する
+            }
+        }
+        enabled.then_some(bench)
+    }
+}
+
+/// 1つの組み合わせの計測結果
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchResult {
+    pub shape: &'static str,        // 図形の名前のキー
+    pub mode: SamplingMode,         // サンプリングモード
+    pub generator: RandomGenerator, // 乱数生成器
+    pub samples: usize, // サンプリングできた点の数(ポアソンディスクでは指定より少ないことがある)
+    pub elapsed: Duration, // かかった時間
+}
+
+impl BenchResult {
+    /// 1秒あたりのサンプル数
+    pub fn samples_per_second(&self) -> f64 {
+        self.samples as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// 計測結果の一覧(テストで確かめられるように、終了した後も残す)
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SamplingBenchReport(pub Vec<BenchResult>);
+
+/// サンプリングの速さの計測を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+/// `SamplingBench`リソースがあるときだけ、最初のフレームで計測して終了する
+pub struct SamplingBenchPlugin;
+
+impl Plugin for SamplingBenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SamplingBenchReport>().add_systems(
+            Update,
+            run_sampling_bench.run_if(resource_exists::<SamplingBench>),
+        );
+    }
+}
+
+// すべての組み合わせを計測して表を表示し、アプリを終了するシステム
+fn run_sampling_bench(
+    bench: Res<SamplingBench>,
+    shapes: Res<SampledShapes>,
+    seed: Res<RngSeed>,
+    params: Res<SamplingParams>,
+    mut report: ResMut<SamplingBenchReport>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !report.0.is_empty() {
+        return; // 終了を待っている間に、もう一度計測しない
+    }
+    for generator in GENERATORS {
+        for mode in MODES {
+            for (shape, _) in &shapes.0 {
+                report.0.push(measure(
+                    shape,
+                    mode,
+                    generator,
+                    &seed,
+                    params.poisson_radius,
+                    bench.samples_per_shape,
+                ));
+            }
+        }
+    }
+    println!("{}", summary(&report.0));
+    exit.write(AppExit::Success);
+}
+
+/// 1つの図形を、指定したモードと乱数生成器で`count`回サンプリングして時間を測る
+fn measure(
+    shape: &Shape,
+    mode: SamplingMode,
+    generator: RandomGenerator,
+    seed: &RngSeed,
+    poisson_radius: f32,
+    count: usize,
+) -> BenchResult {
+    let mut rng = RandomSource::new(generator, seed);
+    let mut grid = PoissonGrid::new(poisson_radius);
+    let mut samples = 0;
+    let start = Instant::now();
+    for _ in 0..count {
+        rng.start_point(); // 準乱数では、点ごとに列の次の点に進む
+        let sample = match mode {
+            SamplingMode::Interior => Some(shape.sample_interior(&mut rng)),
+            SamplingMode::Boundary => Some(shape.sample_boundary(&mut rng)),
+            // spawn_pointsと同じく、既存の点から十分に離れた候補が見つかるまで試す
+            SamplingMode::PoissonDisk => (0..POISSON_ATTEMPTS)
+                .map(|_| shape.sample_interior(&mut rng))
+                .find(|candidate| grid.is_far_enough(*candidate))
+                .inspect(|candidate| grid.insert(*candidate)),
+        };
+        if let Some(sample) = sample {
+            black_box(sample); // 使わない値の計算を最適化で省かせない
+            samples += 1;
+        }
+    }
+    BenchResult {
+        shape: shape.name_key(),
+        mode,
+        generator,
+        samples,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// 計測結果の表(図形・モード・乱数生成器ごとの1秒あたりのサンプル数)
+pub fn summary(results: &[BenchResult]) -> String {
+    let mut table = format!(
+        "{:<16} {:<12} {:<14} {:>10} {:>14}\n",
+        "shape", "mode", "generator", "samples", "samples/s"
+    );
+    for result in results {
+        let _ = writeln!(
+            table,
+            "{:<16} {:<12} {:<14} {:>10} {:>14.0}",
+            result.shape.trim_start_matches("shape."),
+            format!("{:?}", result.mode),
+            format!("{:?}", result.generator),
+            result.samples,
+            result.samples_per_second(),
+        );
+    }
+    table
+}
@@ -0,0 +1,71 @@
+//! スクリーンショットを保存したことを画面に表示する
+//!
+//! F12キーでの撮影と、時刻付きのPNGの保存は`sample_common::ScreenshotPlugin`が行う。
+//! 撮影は画面に表示される最終的な画像(トーンマッピング後)を読み取るので、ブルームやHDRを
+//! 有効にしていてもそのまま写る。ここでは保存完了の`ScreenshotSaved`イベントを受けて、
+//! 画面の下に保存先を`NOTICE_TIME`秒だけ表示する(撮影した画像にはこのメッセージは写らない)。
+
+use bevy::prelude::*;
+use sample_common::LocalizedText;
+use sample_common::screenshot::ScreenshotSaved;
+
+/// 保存したことを表示しておく時間(秒)
+pub const NOTICE_TIME: f32 = 2.0;
+
+/// 保存したことを知らせるメッセージ(表示している残り時間を持つ)
+#[derive(Component)]
+pub struct ScreenshotNotice(Timer);
+
+// メッセージを作るシステム(最初は表示しない)
+// Tabキーのヘルプの切り替えはTextのVisibilityを変えるので、表示・非表示はNodeのdisplayで切り替える
+pub(crate) fn spawn_screenshot_notice(mut commands: Commands) {
+    commands
+        .spawn((
+            ScreenshotNotice(Timer::from_seconds(NOTICE_TIME, TimerMode::Once)),
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                bottom: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|notice| {
+            notice.spawn((
+                LocalizedText::new("screenshot.saved"),
+                TextFont::from_font_size(16.0),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ));
+        });
+}
+
+// スクリーンショットが保存されたらメッセージを表示し、時間が経ったら隠すシステム
+pub(crate) fn update_screenshot_notice(
+    mut saved: EventReader<ScreenshotSaved>,
+    time: Res<Time>,
+    mut notices: Query<(&mut ScreenshotNotice, &mut Node, &Children)>,
+    mut texts: Query<&mut LocalizedText>,
+) {
+    let latest = saved.read().last();
+    for (mut notice, mut node, children) in notices.iter_mut() {
+        if let Some(saved) = latest {
+            for child in children {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    *text = LocalizedText::new("screenshot.saved")
+                        .with_arg("path", saved.path.display());
+                }
+            }
+            notice.0.reset();
+            node.display = Display::Flex;
+            continue;
+        }
+
+        if node.display == Display::None {
+            continue;
+        }
+        if notice.0.tick(time.delta()).finished() {
+            node.display = Display::None;
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! ポイントのセッションの保存と読み込み
+//!
+//! 「Ctrl+S」で今あるすべてのポイント(図形・サンプリングモード・図形の中心からの位置)と、
+//! 乱数生成器の状態・シード値を`saves/primitives/session.bin`に保存し、「Ctrl+L」で読み込む。
+//! 読み込むとシーンのポイントを置き換え、乱数生成器も保存したときの続きから再開するので、
+//! 何時間もかけて溜めたポイント(境界の数百万個など)を次に起動したときにも使える。
+//! 数百万個でも小さく速く読み書きできるように、CSVではなくリトルエンディアンのバイナリにする。
+//! 読み込んだポイントが上限を超えて自動削除されないように、読み込んだら手動モードにする。
+//! 表示(3D・2D)が違うと図形の番号が合わないので、保存したときと同じ表示でだけ読み込む。
+//! Webではファイルシステムがないので、保存・読み込みできない旨をログに出すだけにする。
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::quasi_random::{QuasiRandom, RandomGenerator};
+use crate::replay::{Replay, ReplayState};
+use crate::{
+    DespawningPoint, PointCounter, RandomSource, RngSeed, SampleAction, SamplePoint, SamplingMode,
+    ShapeIndex, ShapeRoot, SpawningMode, ViewMode, new_sample_point, shape_roots,
+};
+
+/// 保存先のファイル(実行時のカレントディレクトリからの相対パス)
+const SESSION_PATH: &str = "saves/primitives/session.bin";
+
+/// ファイルの先頭に置く印(別の種類のファイルを読み込まないようにする)
+const MAGIC: &[u8; 8] = b"PRIMSESS";
+
+/// ファイルの形式の版(形式を変えたら上げる)
+const VERSION: u32 = 1;
+
+/// セッションの保存と読み込みを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionFile>().add_systems(
+            Update,
+            (save_session_on_key, load_session_on_key)
+                .chain()
+                .after(crate::handle_keypress)
+                .before(crate::spawn_points),
+        );
+    }
+}
+
+/// セッションを保存・読み込むファイルのパスを保持するリソース
+#[derive(Resource, Debug)]
+pub struct SessionFile(pub PathBuf);
+
+impl Default for SessionFile {
+    fn default() -> Self {
+        Self(PathBuf::from(SESSION_PATH))
+    }
+}
+
+/// 保存する1つのポイント
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionPoint {
+    pub shape: usize,       // サンプリングした図形(SampledShapesのインデックス)
+    pub mode: SamplingMode, // サンプリングモード
+    pub position: Vec3,     // 図形の中心からの位置
+}
+
+/// 保存するセッション
+pub struct Session {
+    pub view: ViewMode,            // 保存したときの表示(図形の番号はこの表示のもの)
+    pub seed: RngSeed,             // シード値
+    random_source: RandomSource,   // 乱数生成器の状態
+    pub points: Vec<SessionPoint>, // ポイント
+}
+
+impl Session {
+    /// シード値から始めた乱数生成器の状態で作る
+    pub fn new(
+        view: ViewMode,
+        seed: RngSeed,
+        generator: RandomGenerator,
+        points: Vec<SessionPoint>,
+    ) -> Self {
+        Self {
+            view,
+            random_source: RandomSource::new(generator, &seed),
+            seed,
+            points,
+        }
+    }
+
+    /// 乱数生成器の種類
+    pub fn generator(&self) -> RandomGenerator {
+        self.random_source.generator()
+    }
+}
+
+/// ChaCha8の状態(シード・ストリーム・位置)を書き出す
+pub(crate) fn write_chacha(rng: &ChaCha8Rng, out: &mut impl Write) -> io::Result<()> {
+    out.write_all(&rng.get_seed())?;
+    out.write_all(&rng.get_stream().to_le_bytes())?;
+    out.write_all(&rng.get_word_pos().to_le_bytes())
+}
+
+/// `write_chacha`で書き出した状態から作る
+pub(crate) fn read_chacha(input: &mut impl Read) -> io::Result<ChaCha8Rng> {
+    let mut seed = [0; 32];
+    input.read_exact(&mut seed)?;
+    let mut rng = ChaCha8Rng::from_seed(seed);
+    rng.set_stream(read_u64(input)?);
+    rng.set_word_pos(u128::from_le_bytes(read_bytes(input)?));
+    Ok(rng)
+}
+
+/// 決まった長さのバイト列を読む
+fn read_bytes<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// リトルエンディアンのu64を読む
+pub(crate) fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(input)?))
+}
+
+/// リトルエンディアンのf32を読む
+fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    Ok(f32::from_le_bytes(read_bytes(input)?))
+}
+
+/// リトルエンディアンのf64を読む
+pub(crate) fn read_f64(input: &mut impl Read) -> io::Result<f64> {
+    Ok(f64::from_le_bytes(read_bytes(input)?))
+}
+
+/// 形式が不正なときのエラー
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// サンプリングモードの番号
+fn mode_code(mode: SamplingMode) -> u8 {
+    match mode {
+        SamplingMode::Interior => 0,
+        SamplingMode::Boundary => 1,
+        SamplingMode::PoissonDisk => 2,
+    }
+}
+
+/// 番号からサンプリングモードを戻す
+fn parse_mode_code(code: u8) -> Option<SamplingMode> {
+    match code {
+        0 => Some(SamplingMode::Interior),
+        1 => Some(SamplingMode::Boundary),
+        2 => Some(SamplingMode::PoissonDisk),
+        _ => None,
+    }
+}
+
+/// セッションをバイナリ形式で書き出す
+/// 印・版・表示・シード値・乱数生成器の状態・ポイントの数の後に、ポイントごとに
+/// 図形の番号(u32)・モード(u8)・位置(f32×3)の17バイトが並ぶ
+pub fn write_session(session: &Session, out: &mut impl Write) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[match session.view {
+        ViewMode::ThreeD => 0,
+        ViewMode::TwoD => 1,
+    }])?;
+    out.write_all(&session.seed.seed.to_le_bytes())?;
+    out.write_all(&[session.seed.reseed_on_reset as u8])?;
+    match &session.random_source {
+        RandomSource::PseudoRandom(rng) => {
+            out.write_all(&[0])?;
+            write_chacha(rng, out)?;
+        }
+        RandomSource::QuasiRandom(quasi) => {
+            out.write_all(&[1])?;
+            quasi.write_state(out)?;
+        }
+    }
+    out.write_all(&(session.points.len() as u64).to_le_bytes())?;
+    for point in &session.points {
+        out.write_all(&(point.shape as u32).to_le_bytes())?;
+        out.write_all(&[mode_code(point.mode)])?;
+        for coordinate in point.position.to_array() {
+            out.write_all(&coordinate.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// バイナリ形式のセッションを読み込む
+pub fn read_session(input: &mut impl Read) -> io::Result<Session> {
+    if read_bytes::<8>(input)? != *MAGIC {
+        return Err(invalid("セッションのファイルではありません"));
+    }
+    let version = u32::from_le_bytes(read_bytes(input)?);
+    if version != VERSION {
+        return Err(invalid(&format!("対応していない版です: {version}")));
+    }
+    let view = match read_bytes::<1>(input)? {
+        [0] => ViewMode::ThreeD,
+        [1] => ViewMode::TwoD,
+        _ => return Err(invalid("表示が不正です")),
+    };
+    let seed = RngSeed {
+        seed: read_u64(input)?,
+        reseed_on_reset: read_bytes::<1>(input)? != [0],
+    };
+    let random_source = match read_bytes::<1>(input)? {
+        [0] => RandomSource::PseudoRandom(read_chacha(input)?),
+        [1] => RandomSource::QuasiRandom(QuasiRandom::read_state(input)?),
+        _ => return Err(invalid("乱数生成器の種類が不正です")),
+    };
+
+    let count = read_u64(input)?;
+    let mut points = Vec::new();
+    for _ in 0..count {
+        let shape = u32::from_le_bytes(read_bytes(input)?) as usize;
+        let [code] = read_bytes(input)?;
+        let mode = parse_mode_code(code).ok_or_else(|| invalid("モードが不正です"))?;
+        let position = Vec3::new(read_f32(input)?, read_f32(input)?, read_f32(input)?);
+        points.push(SessionPoint {
+            shape,
+            mode,
+            position,
+        });
+    }
+    Ok(Session {
+        view,
+        seed,
+        random_source,
+        points,
+    })
+}
+
+/// セッションをファイルに保存する
+pub fn save_session(path: &Path, session: &Session) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+    write_session(session, &mut file)?;
+    file.flush()
+}
+
+/// ファイルからセッションを読み込む
+pub fn load_session(path: &Path) -> io::Result<Session> {
+    read_session(&mut io::BufReader::new(std::fs::File::open(path)?))
+}
+
+// 「Ctrl+S」キー：今のポイントと乱数生成器の状態を保存するシステム
+fn save_session_on_key(
+    actions: Res<ButtonInput<SampleAction>>,
+    file: Res<SessionFile>,
+    view: Res<ViewMode>,
+    seed: Res<RngSeed>,
+    random_source: Res<RandomSource>,
+    samples: Query<(&Transform, &ShapeIndex, &SamplingMode), With<SamplePoint>>,
+) {
+    if !actions.just_pressed(SampleAction::SaveSession) {
+        return;
+    }
+    if cfg!(target_arch = "wasm32") {
+        warn!("Webではセッションを保存できません");
+        return;
+    }
+
+    let session = Session {
+        view: *view,
+        seed: seed.clone(),
+        random_source: random_source.clone(),
+        points: samples
+            .iter()
+            .map(|(transform, index, mode)| SessionPoint {
+                shape: index.0,
+                mode: *mode,
+                position: transform.translation,
+            })
+            .collect(),
+    };
+    match save_session(&file.0, &session) {
+        Ok(()) => info!(
+            "{}個のポイントを保存しました: {}",
+            session.points.len(),
+            file.0.display()
+        ),
+        Err(err) => error!("セッションを保存できません: {err}"),
+    }
+}
+
+// 「Ctrl+L」キー：保存したセッションを読み込み、ポイントと乱数生成器を置き換えるシステム
+fn load_session_on_key(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    file: Res<SessionFile>,
+    view: Res<ViewMode>,
+    replay: Res<Replay>,
+    mut seed: ResMut<RngSeed>,
+    mut generator: ResMut<RandomGenerator>,
+    mut random_source: ResMut<RandomSource>,
+    mut spawn_mode: ResMut<SpawningMode>,
+    mut counter: ResMut<PointCounter>,
+    samples: Query<Entity, Or<(With<SamplePoint>, With<DespawningPoint>)>>,
+    roots: Query<(Entity, &Transform, &ShapeRoot)>,
+) {
+    if !actions.just_pressed(SampleAction::LoadSession) {
+        return;
+    }
+    if cfg!(target_arch = "wasm32") {
+        warn!("Webではセッションを読み込めません");
+        return;
+    }
+    if replay.state == ReplayState::Playing {
+        warn!("記録の再生中はセッションを読み込めません");
+        return;
+    }
+
+    let session = match load_session(&file.0) {
+        Ok(session) => session,
+        Err(err) => {
+            error!("セッションを読み込めません: {err}");
+            return;
+        }
+    };
+    if session.view != *view {
+        error!("表示(3D・2D)が保存したときと違うので読み込めません(Vキーで切り替えてください)");
+        return;
+    }
+
+    // 今のポイントを消して、保存したポイントに置き換える
+    for entity in &samples {
+        commands.entity(entity).despawn();
+    }
+    // 今の表示にない図形(読み込んでいないglTFのメッシュなど)のポイントは生成しない
+    let roots = shape_roots(&roots);
+    counter.0 = 0;
+    for point in &session.points {
+        if let Some(root) = roots.get(point.shape).copied().flatten() {
+            commands.spawn(new_sample_point(
+                point.position,
+                point.shape,
+                point.mode,
+                root,
+            ));
+            counter.0 += 1;
+        }
+    }
+
+    *generator = session.generator();
+    *random_source = session.random_source;
+    *seed = session.seed;
+    *spawn_mode = SpawningMode::Manual;
+    info!(
+        "{}個のポイントを読み込みました(手動モードにしました): {}",
+        counter.0,
+        file.0.display()
+    );
+}
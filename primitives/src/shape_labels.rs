@@ -0,0 +1,173 @@
+//! 図形ごとのポイント数のラベル
+//!
+//! 図形の上に、図形の名前とその図形に今あるポイントの数を表示する。
+//! 全体の数(`PointCounter`)だけでは図形ごとの数がわからないので、生成されたポイントと
+//! 削除(削除アニメーションの開始)されたポイントから、図形ごとの数を`ShapePointCounts`で数える。
+//! ラベルはUIのテキストで、図形の上の位置を毎フレーム画面の座標に変換して置く(カメラを回しても正面を向く)。
+//! 表示を切り替えたりglTFのメッシュを加えたりして図形が変わったら、ラベルを作り直して数え直す。
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use sample_common::LocalizedText;
+
+use crate::{SamplePoint, SampledShapes, ShapeIndex};
+
+/// 図形の中心からラベルまでの高さ(一番背の高いカプセルの上端より少し上、図形の大きさの倍率をかける)
+const LABEL_HEIGHT: f32 = 1.7;
+
+/// 図形ごとのポイント数のラベルを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ShapeLabelsPlugin;
+
+impl Plugin for ShapeLabelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapePointCounts>().add_systems(
+            Update,
+            (
+                count_shape_points,
+                rebuild_shape_labels.run_if(resource_changed::<SampledShapes>),
+                update_shape_labels,
+            )
+                .chain()
+                .after(crate::switch_view)
+                .after(crate::animate_spawning)
+                .after(crate::animate_despawning)
+                .after(crate::update_camera),
+        );
+    }
+}
+
+/// 図形ごとの今のポイントの数(削除アニメーション中のポイントは含めない)
+#[derive(Resource, Default, Debug)]
+pub struct ShapePointCounts {
+    counts: Vec<usize>,             // 図形ごとのポイント数(SampledShapesの順)
+    points: HashMap<Entity, usize>, // 数えたポイントと、その図形のインデックス
+}
+
+impl ShapePointCounts {
+    /// 図形のポイントの数
+    pub fn get(&self, index: usize) -> usize {
+        self.counts.get(index).copied().unwrap_or_default()
+    }
+
+    /// すべての図形のポイントの数の合計
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// 図形の数に合わせて、数え直す準備をする
+    fn restart(&mut self, shapes: usize) {
+        self.counts = vec![0; shapes];
+        self.points.clear();
+    }
+
+    /// 生成されたポイントを数える
+    fn add(&mut self, entity: Entity, index: usize) {
+        let Some(count) = self.counts.get_mut(index) else {
+            return;
+        };
+        if self.points.insert(entity, index).is_none() {
+            *count += 1;
+        }
+    }
+
+    /// 削除されたポイントを数から引く
+    fn remove(&mut self, entity: Entity) {
+        if let Some(index) = self.points.remove(&entity) {
+            self.counts[index] -= 1;
+        }
+    }
+}
+
+/// 図形のラベルのコンポーネント(SampledShapesのインデックス)
+#[derive(Component)]
+pub struct ShapeLabel(pub usize);
+
+/// ラベルの文章(図形の名前とポイントの数)
+fn label_text(name_key: &str, count: usize) -> LocalizedText {
+    LocalizedText::new("labels.shape")
+        .with_key_arg("shape", name_key)
+        .with_arg("count", count)
+}
+
+// 生成・削除されたポイントを図形ごとに数えるシステム
+// 図形が変わったフレームでは、残っているポイントから数え直す
+fn count_shape_points(
+    mut counts: ResMut<ShapePointCounts>,
+    shapes: Res<SampledShapes>,
+    all_points: Query<(Entity, &ShapeIndex), With<SamplePoint>>,
+    added: Query<(Entity, &ShapeIndex), Added<SamplePoint>>,
+    mut removed: RemovedComponents<SamplePoint>,
+) {
+    for entity in removed.read() {
+        counts.remove(entity);
+    }
+    if shapes.is_changed() {
+        counts.restart(shapes.0.len());
+        for (entity, index) in &all_points {
+            counts.add(entity, index.0);
+        }
+        return;
+    }
+    for (entity, index) in &added {
+        counts.add(entity, index.0);
+    }
+}
+
+// 図形が変わったら、図形ごとのラベルを作り直すシステム(位置はupdate_shape_labelsで決める)
+fn rebuild_shape_labels(
+    mut commands: Commands,
+    shapes: Res<SampledShapes>,
+    labels: Query<Entity, With<ShapeLabel>>,
+) {
+    for entity in &labels {
+        commands.entity(entity).despawn();
+    }
+    for (index, (shape, _)) in shapes.0.iter().enumerate() {
+        commands.spawn((
+            ShapeLabel(index),
+            label_text(shape.name_key(), 0),
+            TextFont::from_font_size(14.0),
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        ));
+    }
+}
+
+// ラベルの数を更新し、図形の上の位置(画面の座標)に置くシステム
+fn update_shape_labels(
+    counts: Res<ShapePointCounts>,
+    shapes: Res<SampledShapes>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut labels: Query<(&ShapeLabel, &mut LocalizedText, &mut Node, &ComputedNode)>,
+) {
+    let (camera, camera_transform) = *camera;
+    for (label, mut text, mut node, computed) in &mut labels {
+        let Some((shape, center)) = shapes.0.get(label.0) else {
+            continue;
+        };
+        text.set_if_neq(label_text(shape.name_key(), counts.get(label.0)));
+
+        // 図形がカメラの後ろにあるときは隠す
+        let position = *center + Vec3::Y * LABEL_HEIGHT * shape.scale();
+        let Ok(screen) = camera.world_to_viewport(camera_transform, position) else {
+            if node.display != Display::None {
+                node.display = Display::None;
+            }
+            continue;
+        };
+        // ラベルの下端の中央を、図形の上の位置に合わせる
+        let size = computed.size() * computed.inverse_scale_factor();
+        let (left, top) = (Val::Px(screen.x - size.x / 2.0), Val::Px(screen.y - size.y));
+        if (node.display, node.left, node.top) != (Display::Flex, left, top) {
+            node.display = Display::Flex;
+            node.left = left;
+            node.top = top;
+        }
+    }
+}
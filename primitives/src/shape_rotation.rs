@@ -0,0 +1,75 @@
+//! 図形をポイントごとゆっくり回転させる
+//!
+//! ポイントは図形のエンティティ(`ShapeRoot`)の子として、図形の中心からの位置に生成しているので、
+//! 図形のTransformを回すだけでサンプリングしたポイントの集まりも一緒に回る。
+//! 3Dの図形はY軸の周りに、2Dの図形は画面に平行なままZ軸の周りに回す。
+//! 「Y」キーで切り替え、一時停止(Spaceキー)中は止まり、速さの倍率(`TimeScale`)に合わせて速さが変わる。
+//! 止めたときは、その向きのまま残す(表示を切り替えると、新しい図形は元の向きから始まる)。
+
+use bevy::prelude::*;
+use sample_common::TimeScale;
+use sample_common::time_control::is_running;
+
+use crate::{SampleAction, ShapeRoot, ViewMode};
+
+/// 回転の速さの初期値(ラジアン/秒)
+pub const ROTATION_SPEED: f32 = 0.4;
+
+/// 図形の回転を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ShapeRotationPlugin;
+
+impl Plugin for ShapeRotationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeRotation>().add_systems(
+            Update,
+            (toggle_rotation, rotate_shapes.run_if(is_running))
+                .chain()
+                .after(crate::handle_keypress)
+                .after(crate::switch_view),
+        );
+    }
+}
+
+/// 図形の回転の設定を保持するリソース
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct ShapeRotation {
+    pub enabled: bool, // 回転させるか(初期は無効)
+    pub speed: f32,    // 回転の速さ(ラジアン/秒)
+}
+
+impl Default for ShapeRotation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: ROTATION_SPEED,
+        }
+    }
+}
+
+// 「Y」キー：図形の回転の有効・無効を切り替えるシステム
+fn toggle_rotation(actions: Res<ButtonInput<SampleAction>>, mut rotation: ResMut<ShapeRotation>) {
+    if actions.just_pressed(SampleAction::ToggleRotation) {
+        rotation.enabled = !rotation.enabled;
+    }
+}
+
+// 有効な間、図形のエンティティを少しずつ回転させるシステム(子のポイントも一緒に回る)
+pub(crate) fn rotate_shapes(
+    rotation: Res<ShapeRotation>,
+    view: Res<ViewMode>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut roots: Query<&mut Transform, With<ShapeRoot>>,
+) {
+    if !rotation.enabled {
+        return;
+    }
+    let angle = rotation.speed * time.delta_secs() * time_scale.0;
+    let turn = match *view {
+        ViewMode::ThreeD => Quat::from_rotation_y(angle),
+        ViewMode::TwoD => Quat::from_rotation_z(angle),
+    };
+    for mut transform in roots.iter_mut() {
+        transform.rotate(turn);
+    }
+}
@@ -0,0 +1,105 @@
+//! 注視している図形の大きさの変更
+//!
+//! 「7」「8」キーで、カメラが注視している図形を中心を基準に縮小・拡大する。
+//! 倍率は`SampledShapes`の図形(`Shape::Scaled`)に持たせるので、サンプリング・体積や表面積・
+//! 符号付き距離・ワイヤーフレームなど図形を使う処理は、変えた後の大きさで動く。
+//! 図形のメッシュ(とワイヤーフレーム)は作り直し、図形の中心のライトの届く範囲も倍率に合わせる。
+//! 設定ファイルの`clear_points_on_scale`が有効なら、その図形の今のポイントを消して新しい大きさでサンプリングし直す
+//! (無効なら元の大きさでサンプリングしたポイントを残す)。
+//! 表示を切り替える(Vキー)と、図形は元の大きさに戻る。
+
+use bevy::prelude::*;
+
+use crate::demo_config::DemoConfig;
+use crate::firefly_lighting::{FireflyLights, LIGHT_RADIUS, LIGHT_RANGE};
+use crate::wireframe::WireframeOverlay;
+use crate::{
+    CameraRig, PointCounter, SampleAction, SamplePoint, SampledShapes, ShapeIndex, ShapeRoot,
+};
+
+/// 図形の大きさの倍率として選べる範囲(大きくしすぎると隣の図形と重なる)
+pub const SHAPE_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=2.0;
+
+/// 「7」「8」キーで1回に変える図形の大きさの倍率
+pub const SHAPE_SCALE_STEP: f32 = 1.25;
+
+/// 図形の大きさの変更を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ShapeScalePlugin;
+
+impl Plugin for ShapeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            scale_focused_shape
+                .after(crate::handle_keypress)
+                .after(crate::switch_view)
+                .after(crate::custom_mesh::add_custom_mesh)
+                .before(crate::spawn_points),
+        );
+    }
+}
+
+// 「7」「8」キー：注視している図形を縮小・拡大し、メッシュとライトを合わせるシステム
+fn scale_focused_shape(
+    mut commands: Commands,
+    actions: Res<ButtonInput<SampleAction>>,
+    config: Res<DemoConfig>,
+    mut shapes: ResMut<SampledShapes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut counter: ResMut<PointCounter>,
+    camera_rig: Query<&CameraRig>,
+    roots: Query<(Entity, &ShapeRoot, &Mesh3d)>,
+    wireframes: Query<(&ChildOf, &Mesh3d), With<WireframeOverlay>>,
+    mut lights: Query<(&ChildOf, &mut PointLight), With<FireflyLights>>,
+    points: Query<(Entity, &ShapeIndex), With<SamplePoint>>,
+) {
+    let factor = if actions.just_pressed(SampleAction::ShrinkShape) {
+        1.0 / SHAPE_SCALE_STEP
+    } else if actions.just_pressed(SampleAction::GrowShape) {
+        SHAPE_SCALE_STEP
+    } else {
+        return;
+    };
+    let Ok(rig) = camera_rig.single() else {
+        return;
+    };
+    let index = shapes.closest(rig.target);
+    let Some((shape, _)) = shapes.0.get(index) else {
+        return;
+    };
+    let scale =
+        (shape.scale() * factor).clamp(*SHAPE_SCALE_RANGE.start(), *SHAPE_SCALE_RANGE.end());
+    if scale == shape.scale() {
+        return; // 範囲の端では変えない(ポイントも消さない)
+    }
+    let scaled = shape.with_scale(scale);
+
+    // 図形のメッシュとワイヤーフレームを新しい大きさで作り直し、ライトの届く範囲を合わせる
+    let Some((root, _, mesh)) = roots.iter().find(|(_, root, _)| root.0 == index) else {
+        return;
+    };
+    crate::rebuild_shape_meshes(
+        &mut meshes,
+        mesh,
+        wireframes
+            .iter()
+            .filter(|(child_of, _)| child_of.parent() == root)
+            .map(|(_, wireframe)| wireframe),
+        &scaled,
+    );
+    for (_, mut light) in lights
+        .iter_mut()
+        .filter(|(child_of, _)| child_of.parent() == root)
+    {
+        light.range = LIGHT_RANGE * scale;
+        light.radius = LIGHT_RADIUS * scale;
+    }
+
+    // 元の大きさでサンプリングしたポイントを消す(自動モードでは新しい大きさで生成し直される)
+    if config.clear_points_on_scale {
+        crate::clear_shape_points(&mut commands, &points, &mut counter, index);
+    }
+
+    shapes.0[index].0 = scaled;
+    info!("{}番目の図形の大きさを{scale}倍にしました", index + 1);
+}
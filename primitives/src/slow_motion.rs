@@ -0,0 +1,31 @@
+//! ポイントの生成・削除の一時停止とスローモーション
+//!
+//! 「Space」キーで一時停止すると、`spawn_points`・`despawn_points`と2つのアニメーションのシステムが
+//! 止まる(`is_running`を実行条件にする)。「[」「]」キーで共通の`TimeScale`を半分・2倍にすると、
+//! 自動生成の速さとアニメーションの速さが変わり、ポイントの出現・消失をゆっくり観察できる。
+//! `TimeControlPlugin`と違って`Time<Virtual>`は変えないので、カメラの操作はいつもの速さのまま動く。
+
+use bevy::prelude::*;
+use sample_common::{PauseState, TimeScale};
+
+use crate::SampleAction;
+
+// 「Space」キーで一時停止・再開し、「[」「]」キーで時間の倍率を変えるシステム
+pub(crate) fn handle_time_keys(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut pause: ResMut<PauseState>,
+    mut scale: ResMut<TimeScale>,
+) {
+    if actions.just_pressed(SampleAction::TogglePause) {
+        pause.toggle();
+    }
+
+    let new_scale = if actions.just_pressed(SampleAction::SlowDown) {
+        scale.halve()
+    } else if actions.just_pressed(SampleAction::SpeedUp) {
+        scale.double()
+    } else {
+        return;
+    };
+    scale.set_if_neq(new_scale);
+}
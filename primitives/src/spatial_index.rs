@@ -0,0 +1,249 @@
+//! ポイントの近傍探索のための空間ハッシュ
+//!
+//! 存在するすべてのポイント(`SamplePoint`)を一辺`SPATIAL_CELL_SIZE`の立方体のセルに分けて
+//! `PointSpatialIndex`リソースに保持する。位置はワールド座標(`GlobalTransform`)で、
+//! 生成・削除されたポイントと、図形の回転で動いたポイントだけを毎フレーム反映する。
+//! 半径以内のポイント・近い順のk個のポイント・レイが最初に当たるポイントを、
+//! すべてのポイントを調べずに近くのセルだけから求める(密度による色分けとポイントの選択で使う)。
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::SamplePoint;
+
+/// セルの一辺の長さ(ポイントの間隔の目安より少し大きくする)
+pub const SPATIAL_CELL_SIZE: f32 = 0.2;
+
+/// ポイントをセルに分けて保持する空間ハッシュ
+#[derive(Resource, Debug, Clone)]
+pub struct PointSpatialIndex {
+    cell_size: f32,                     // セルの一辺の長さ
+    cells: HashMap<IVec3, Vec<Entity>>, // セルの座標ごとのポイント
+    positions: HashMap<Entity, Vec3>,   // ポイントごとの位置
+    bounds: Option<(IVec3, IVec3)>,     // ポイントが入ったことのあるセルの範囲
+}
+
+impl Default for PointSpatialIndex {
+    fn default() -> Self {
+        Self::new(SPATIAL_CELL_SIZE)
+    }
+}
+
+impl PointSpatialIndex {
+    /// 空の空間ハッシュを作る(`cell_size`は0より大きくする)
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            positions: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// ポイントの数
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// ポイントが1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// ポイントの位置(入っていないポイントはNone)
+    pub fn position(&self, entity: Entity) -> Option<Vec3> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// すべてのポイントとその位置(順番は決まっていない)
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        self.positions
+            .iter()
+            .map(|(entity, position)| (*entity, *position))
+    }
+
+    /// ポイントが入るセルの座標
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+
+    /// ポイントを追加する(すでに入っていれば新しい位置に移す)
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        self.remove(entity);
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(entity);
+        self.positions.insert(entity, position);
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (min.min(cell), max.max(cell)),
+            None => (cell, cell),
+        });
+    }
+
+    /// ポイントを取り除き、その位置を返す
+    pub fn remove(&mut self, entity: Entity) -> Option<Vec3> {
+        let position = self.positions.remove(&entity)?;
+        let cell = self.cell(position);
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            entities.retain(|other| *other != entity);
+            if entities.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+        if self.positions.is_empty() {
+            self.bounds = None; // 空になったら範囲を縮める(それまでは広いまま使う)
+        }
+        Some(position)
+    }
+
+    /// セルに入っているポイントと、`position`からの距離
+    fn points_in_cell(
+        &self,
+        cell: IVec3,
+        position: Vec3,
+    ) -> impl Iterator<Item = (Entity, f32)> + '_ {
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(move |entity| (*entity, self.positions[entity].distance(position)))
+    }
+
+    /// `position`から半径`radius`以内にあるポイントと、その距離(順番は決まっていない)
+    pub fn within_radius(&self, position: Vec3, radius: f32) -> Vec<(Entity, f32)> {
+        let min = self.cell(position - Vec3::splat(radius));
+        let max = self.cell(position + Vec3::splat(radius));
+        let mut found = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    found.extend(
+                        self.points_in_cell(IVec3::new(x, y, z), position)
+                            .filter(|(_, distance)| *distance <= radius),
+                    );
+                }
+            }
+        }
+        found
+    }
+
+    /// `position`に近い順に最大`k`個のポイントと、その距離
+    /// 中心のセルから1層ずつ外側のセルを調べ、k番目の距離より外側の層が遠くなったら終える
+    pub fn k_nearest(&self, position: Vec3, k: usize) -> Vec<(Entity, f32)> {
+        let Some((min, max)) = self.bounds.filter(|_| k > 0) else {
+            return Vec::new();
+        };
+        let center = self.cell(position);
+        // この層まで調べれば、ポイントの入ったことのあるセルをすべて調べたことになる
+        let last_ring = (center - min).max(max - center).max_element().max(0);
+
+        let mut found: Vec<(Entity, f32)> = Vec::new();
+        for ring in 0..=last_ring {
+            for x in -ring..=ring {
+                for y in -ring..=ring {
+                    // 層の表面のセルだけを調べる(内側は前の層で調べた)
+                    let on_side = x.abs() == ring || y.abs() == ring;
+                    let step = if on_side { 1 } else { (2 * ring).max(1) };
+                    for z in (-ring..=ring).step_by(step as usize) {
+                        found.extend(self.points_in_cell(center + IVec3::new(x, y, z), position));
+                    }
+                }
+            }
+            found.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+            found.truncate(k);
+            // まだ調べていないセルのポイントは、少なくともこの距離だけ離れている
+            let unexplored = ring as f32 * self.cell_size;
+            if found.len() == k && found[k - 1].1 <= unexplored {
+                break;
+            }
+        }
+        found
+    }
+
+    /// `position`に最も近いポイントと、その距離
+    pub fn nearest(&self, position: Vec3) -> Option<(Entity, f32)> {
+        self.k_nearest(position, 1).pop()
+    }
+
+    /// ポイントを半径`radius`の球とみなして、レイが最初に当たるポイントと、レイに沿った距離
+    /// ポイントの入ったことのあるセルの範囲をレイに沿ってセルの一辺ずつ進み、周りのセルだけを調べる
+    pub fn cast_ray(&self, ray: Ray3d, radius: f32) -> Option<(Entity, f32)> {
+        let (min, max) = self.bounds?;
+        let lower = min.as_vec3() * self.cell_size - Vec3::splat(radius);
+        let upper = (max + IVec3::ONE).as_vec3() * self.cell_size + Vec3::splat(radius);
+        // レイが範囲の箱の中を通る区間(カメラの後ろは除く)
+        let inverse = ray.direction.recip();
+        let (t0, t1) = (
+            (lower - ray.origin) * inverse,
+            (upper - ray.origin) * inverse,
+        );
+        let enter = t0.min(t1).max_element().max(0.0);
+        let exit = t0.max(t1).min_element();
+        if enter > exit {
+            return None;
+        }
+
+        // レイ上の点から半分のセルまでの位置の、半径以内にあるポイントが入り得るセルの幅
+        let reach = ((radius + self.cell_size * 0.5) / self.cell_size).ceil() as i32;
+        // 調べたセルのポイントと、そのときのレイ上の点との距離の上限
+        let margin = (reach + 1) as f32 * self.cell_size * 3.0_f32.sqrt();
+
+        let mut visited = HashSet::new();
+        let mut hit: Option<(Entity, f32)> = None;
+        let mut t = enter;
+        while t <= exit + self.cell_size * 0.5 {
+            // これより先のセルのポイントは、見つけたポイントより奥にある
+            if hit.is_some_and(|(_, along)| t - margin > along) {
+                break;
+            }
+            let center = self.cell(ray.get_point(t));
+            for x in -reach..=reach {
+                for y in -reach..=reach {
+                    for z in -reach..=reach {
+                        let cell = center + IVec3::new(x, y, z);
+                        if !visited.insert(cell) {
+                            continue;
+                        }
+                        for entity in self.cells.get(&cell).into_iter().flatten() {
+                            let Some(along) = ray_hit(ray, self.positions[entity], radius) else {
+                                continue;
+                            };
+                            if hit.is_none_or(|(_, closest)| along < closest) {
+                                hit = Some((*entity, along));
+                            }
+                        }
+                    }
+                }
+            }
+            t += self.cell_size;
+        }
+        hit
+    }
+}
+
+/// 半径`radius`の球とみなしたポイントにレイが当たるなら、レイに沿った距離を返す(カメラの後ろは除く)
+pub fn ray_hit(ray: Ray3d, position: Vec3, radius: f32) -> Option<f32> {
+    let to_point = position - ray.origin;
+    // レイ上で最もポイントに近い位置までの距離
+    let along = to_point.dot(*ray.direction);
+    let off_ray_squared = to_point.length_squared() - along * along;
+    (along >= 0.0 && off_ray_squared <= radius * radius).then_some(along)
+}
+
+// 生成・削除されたポイントと、図形と一緒に動いたポイントを空間ハッシュに反映するシステム
+// (生成したポイントには、親の図形から計算したワールド座標が入っている)
+pub(crate) fn update_point_index(
+    mut index: ResMut<PointSpatialIndex>,
+    moved: Query<(Entity, &GlobalTransform), (With<SamplePoint>, Changed<GlobalTransform>)>,
+    mut removed: RemovedComponents<SamplePoint>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, transform) in &moved {
+        let position = transform.translation();
+        if index.position(entity) != Some(position) {
+            index.insert(entity, position);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! 図形ごとのポイントの重心(平均)と分散・共分散
+//!
+//! ポイントが増えるたびに、図形とサンプリングモードごとに位置の合計と外積の合計を足し込み、
+//! 平均と分散共分散行列をその場で求める(削除されたポイントは引く)。
+//! 一様にサンプリングできていれば、平均は図形の解析的な重心に近づいていくので、
+//! 両者のずれが大きいままなら、サンプリングの偏り(実装の誤り)に気づける。
+//! 平均の位置には図形のエンティティの子としてマーカーを置き、数値は一覧で表示する(一覧はpanelモジュール)。
+//! 座標はどちらも図形の中心から測る。桁落ちを避けるため、合計はf64で持つ。
+//! 「J」キーで切り替え、有効にしたときは既存のポイントから集計し直す。
+
+use std::collections::HashMap;
+
+use bevy::math::{DMat3, DVec3};
+use bevy::prelude::*;
+
+use crate::{
+    CONE, CONICAL_FRUSTUM, SampleAction, SamplePoint, SampledShapes, SamplingMode, Shape,
+    ShapeIndex, ShapeRoot, TETRAHEDRON, TRIANGLE_2D, TRIANGLE_3D,
+};
+
+/// 重心のマーカーの半径
+const MARKER_RADIUS: f32 = 0.04;
+
+/// 重心と分散の集計を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct SampleStatisticsPlugin;
+
+impl Plugin for SampleStatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SampleStatistics>()
+            .init_resource::<CentroidMarkerAssets>()
+            .add_systems(
+                Update,
+                (toggle_statistics, track_statistics, update_centroid_markers)
+                    .chain()
+                    .after(crate::handle_keypress)
+                    .after(crate::switch_view)
+                    .after(crate::animate_spawning),
+            );
+    }
+}
+
+/// ポイントの位置の合計から求める、平均と分散共分散
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct RunningStatistics {
+    count: u64,       // ポイントの数
+    sum: DVec3,       // 位置の合計
+    sum_outer: DMat3, // 位置の外積(p pᵀ)の合計
+}
+
+impl RunningStatistics {
+    /// ポイントを加える
+    pub fn add(&mut self, position: Vec3) {
+        let p = position.as_dvec3();
+        self.count += 1;
+        self.sum += p;
+        self.sum_outer += outer(p);
+    }
+
+    /// 加えたポイントを取り除く
+    pub fn remove(&mut self, position: Vec3) {
+        if self.count == 0 {
+            return;
+        }
+        let p = position.as_dvec3();
+        self.count -= 1;
+        self.sum -= p;
+        self.sum_outer -= outer(p);
+    }
+
+    /// ポイントの数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 平均の位置(ポイントがなければNone)
+    pub fn mean(&self) -> Option<Vec3> {
+        (self.count > 0).then(|| (self.sum / self.count as f64).as_vec3())
+    }
+
+    /// 分散共分散行列(母分散、ポイントがなければ0)
+    /// E[p pᵀ] - E[p] E[p]ᵀ で求める
+    pub fn covariance(&self) -> Mat3 {
+        if self.count == 0 {
+            return Mat3::ZERO;
+        }
+        let n = self.count as f64;
+        let mean = self.sum / n;
+        (self.sum_outer * (1.0 / n) - outer(mean)).as_mat3()
+    }
+
+    /// 各軸の分散(分散共分散行列の対角成分)
+    pub fn variance(&self) -> Vec3 {
+        let covariance = self.covariance();
+        Vec3::new(
+            covariance.x_axis.x,
+            covariance.y_axis.y,
+            covariance.z_axis.z,
+        )
+    }
+
+    /// 平均の各軸の標準誤差(√(分散 / ポイントの数))
+    pub fn standard_error(&self) -> Vec3 {
+        if self.count == 0 {
+            return Vec3::ZERO;
+        }
+        let squared = self.variance().max(Vec3::ZERO) / self.count as f32;
+        Vec3::new(squared.x.sqrt(), squared.y.sqrt(), squared.z.sqrt())
+    }
+}
+
+/// 外積 p pᵀ
+fn outer(p: DVec3) -> DMat3 {
+    DMat3::from_cols(p * p.x, p * p.y, p * p.z)
+}
+
+/// サンプリングモードの並び(集計の配列の添字)
+fn mode_index(mode: SamplingMode) -> usize {
+    match mode {
+        SamplingMode::Interior => 0,
+        SamplingMode::Boundary => 1,
+        SamplingMode::PoissonDisk => 2,
+    }
+}
+
+/// 図形ごとの集計と、解析的な重心
+#[derive(Clone, Debug)]
+pub struct ShapeStatistics {
+    pub name_key: &'static str,      // 図形の名前のキー
+    centroids: [Vec3; 3],            // サンプリングモードごとの解析的な重心
+    samples: [RunningStatistics; 3], // サンプリングモードごとの集計
+}
+
+impl ShapeStatistics {
+    /// サンプリングモードでの解析的な重心(図形の中心から)
+    pub fn centroid(&self, mode: SamplingMode) -> Vec3 {
+        self.centroids[mode_index(mode)]
+    }
+
+    /// サンプリングモードでのポイントの集計
+    pub fn samples(&self, mode: SamplingMode) -> &RunningStatistics {
+        &self.samples[mode_index(mode)]
+    }
+}
+
+/// 集計したポイントの図形(SampledShapesのインデックス)、サンプリングモード、図形の中心からの位置
+type Collected = (usize, SamplingMode, Vec3);
+
+/// 図形ごとのポイントの重心と分散を保持するリソース
+#[derive(Resource, Default, Debug)]
+pub struct SampleStatistics {
+    pub enabled: bool,                  // 集計して表示するか(初期は非表示)
+    pub shapes: Vec<ShapeStatistics>,   // 図形ごとの集計(SampledShapesと同じ順番)
+    points: HashMap<Entity, Collected>, // 集計したポイント(削除されたときに引く)
+}
+
+impl SampleStatistics {
+    /// 集計を捨てて、図形の一覧から作り直す
+    fn restart(&mut self, shapes: &SampledShapes) {
+        self.points.clear();
+        self.shapes = shapes
+            .0
+            .iter()
+            .map(|(shape, _)| ShapeStatistics {
+                name_key: shape.name_key(),
+                centroids: [
+                    analytic_centroid(shape, SamplingMode::Interior),
+                    analytic_centroid(shape, SamplingMode::Boundary),
+                    analytic_centroid(shape, SamplingMode::PoissonDisk),
+                ],
+                samples: [RunningStatistics::default(); 3],
+            })
+            .collect();
+    }
+
+    /// ポイントを集計に加える
+    fn add(&mut self, entity: Entity, index: usize, mode: SamplingMode, position: Vec3) {
+        let Some(shape) = self.shapes.get_mut(index) else {
+            return;
+        };
+        shape.samples[mode_index(mode)].add(position);
+        self.points.insert(entity, (index, mode, position));
+    }
+
+    /// 削除されたポイントを集計から引く
+    fn remove(&mut self, entity: Entity) {
+        let Some((index, mode, position)) = self.points.remove(&entity) else {
+            return;
+        };
+        if let Some(shape) = self.shapes.get_mut(index) {
+            shape.samples[mode_index(mode)].remove(position);
+        }
+    }
+}
+
+/// 図形の中心から測った、サンプリングモードでの解析的な重心
+/// ポアソンディスクも内部から選ぶので、内部の重心とする
+/// (既存のポイントから離すので分布は一様ではないが、対称な図形では重心は変わらない)
+fn analytic_centroid(shape: &Shape, mode: SamplingMode) -> Vec3 {
+    let interior = mode != SamplingMode::Boundary;
+    match (shape, interior) {
+        // 中心について対称な図形
+        (
+            Shape::Cuboid
+            | Shape::Sphere
+            | Shape::Capsule
+            | Shape::Cylinder
+            | Shape::Torus
+            | Shape::Circle
+            | Shape::Rectangle
+            | Shape::Annulus,
+            _,
+        ) => Vec3::ZERO,
+        // 四面体の内部の重心は頂点の平均、表面は各面の重心を面積で重み付けした平均
+        (Shape::Tetrahedron, true) => TETRAHEDRON.vertices.iter().sum::<Vec3>() / 4.0,
+        (Shape::Tetrahedron, false) => {
+            let [a, b, c, d] = TETRAHEDRON.vertices;
+            let faces = [[a, b, c], [a, b, d], [a, c, d], [b, c, d]];
+            let area = |[p, q, r]: [Vec3; 3]| 0.5 * (q - p).cross(r - p).length();
+            let weighted = faces
+                .iter()
+                .map(|face| area(*face) * face.iter().sum::<Vec3>() / 3.0)
+                .sum::<Vec3>();
+            weighted / faces.iter().map(|face| area(*face)).sum::<f32>()
+        }
+        // 三角形の内部の重心は頂点の平均、周は各辺の中点を長さで重み付けした平均
+        (Shape::Triangle, true) => TRIANGLE_3D.vertices.iter().sum::<Vec3>() / 3.0,
+        (Shape::Triangle, false) => perimeter_centroid(TRIANGLE_3D.vertices),
+        (Shape::Triangle2d, true) => (TRIANGLE_2D.vertices.iter().sum::<Vec2>() / 3.0).extend(0.0),
+        (Shape::Triangle2d, false) => {
+            perimeter_centroid(TRIANGLE_2D.vertices.map(|vertex| vertex.extend(0.0)))
+        }
+        // 円錐(先端が+Y側)の内部の重心は先端から高さの3/4
+        (Shape::Cone, true) => Vec3::new(0.0, -0.25 * CONE.height, 0.0),
+        // 表面は、側面(先端から高さの2/3、面積πrl)と底面(面積πr²)の重み付き平均
+        (Shape::Cone, false) => {
+            let (radius, height) = (CONE.radius, CONE.height);
+            let slant = radius.hypot(height);
+            let y = (slant * (-height / 6.0) + radius * (-0.5 * height)) / (slant + radius);
+            Vec3::new(0.0, y, 0.0)
+        }
+        // 円錐台の内部の重心は、下面からh(R²+2Rr+3r²)/(4(R²+Rr+r²))の高さ(R:下面、r:上面の半径)
+        (Shape::Frustum, true) => {
+            let (top, bottom, height) = (
+                CONICAL_FRUSTUM.radius_top,
+                CONICAL_FRUSTUM.radius_bottom,
+                CONICAL_FRUSTUM.height,
+            );
+            let y = height * (bottom * bottom + 2.0 * bottom * top + 3.0 * top * top)
+                / (4.0 * (bottom * bottom + bottom * top + top * top));
+            Vec3::new(0.0, y - 0.5 * height, 0.0)
+        }
+        // 表面は、側面(下面からh(R+2r)/(3(R+r))、面積π(R+r)l)と上面・下面の重み付き平均
+        (Shape::Frustum, false) => {
+            let (top, bottom, height) = (
+                CONICAL_FRUSTUM.radius_top,
+                CONICAL_FRUSTUM.radius_bottom,
+                CONICAL_FRUSTUM.height,
+            );
+            let slant = (bottom - top).hypot(height);
+            let side = (bottom + top) * slant;
+            let side_y = height * (bottom + 2.0 * top) / (3.0 * (bottom + top));
+            let y = (side * side_y + top * top * height) / (top * top + bottom * bottom + side);
+            Vec3::new(0.0, y - 0.5 * height, 0.0)
+        }
+        (Shape::CustomMesh(mesh), true) => mesh.volume_centroid(),
+        (Shape::CustomMesh(mesh), false) => mesh.surface_centroid(),
+        // 合成した図形は、合成するときにモンテカルロ法で推定した重心
+        (Shape::Composite(composite), _) => composite.centroid(mode),
+        // 中心を基準に拡大・縮小するので、重心も倍率をかけた位置になる
+        (Shape::Scaled(shape, scale), _) => analytic_centroid(shape, mode) * *scale,
+    }
+}
+
+/// 三角形の周の重心(各辺の中点を辺の長さで重み付けした平均)
+fn perimeter_centroid([a, b, c]: [Vec3; 3]) -> Vec3 {
+    let edges = [(a, b), (b, c), (c, a)];
+    let weighted = edges
+        .iter()
+        .map(|(p, q)| p.distance(*q) * (*p + *q) / 2.0)
+        .sum::<Vec3>();
+    weighted / edges.iter().map(|(p, q)| p.distance(*q)).sum::<f32>()
+}
+
+/// 重心のマーカーのMeshとマテリアルを保持するリソース
+#[derive(Resource)]
+struct CentroidMarkerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for CentroidMarkerAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(MARKER_RADIUS));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::WHITE,
+                emissive: LinearRgba::rgb(3.0, 3.0, 3.0), // ポイントの中でも目立つように白く光らせる
+                unlit: true,                              // 影を付けない
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+/// 重心のマーカーのコンポーネント(図形のエンティティの子、SampledShapesのインデックス)
+#[derive(Component)]
+pub struct CentroidMarker(pub usize);
+
+// 「J」キー：重心と分散の集計・表示を切り替えるシステム
+// 有効にしたときは、既存のポイントから集計し直す
+fn toggle_statistics(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut statistics: ResMut<SampleStatistics>,
+    shapes: Res<SampledShapes>,
+    points: Query<(Entity, &ShapeIndex, &SamplingMode, &Transform), With<SamplePoint>>,
+) {
+    if !actions.just_pressed(SampleAction::ToggleStatistics) {
+        return;
+    }
+    statistics.enabled = !statistics.enabled;
+    if statistics.enabled {
+        statistics.restart(&shapes);
+        for (entity, index, mode, transform) in &points {
+            statistics.add(entity, index.0, *mode, transform.translation);
+        }
+    }
+}
+
+// 増えたポイントを集計に加え、削除されたポイントを集計から引くシステム
+// 図形の一覧が変わったら(2D・3Dの切り替えなど)、今あるポイントから集計し直す
+fn track_statistics(
+    mut statistics: ResMut<SampleStatistics>,
+    shapes: Res<SampledShapes>,
+    all_points: Query<(Entity, &ShapeIndex, &SamplingMode, &Transform), With<SamplePoint>>,
+    added: Query<(Entity, &ShapeIndex, &SamplingMode, &Transform), Added<SamplePoint>>,
+    mut removed: RemovedComponents<SamplePoint>,
+) {
+    if !statistics.enabled {
+        removed.clear();
+        return;
+    }
+    for entity in removed.read() {
+        statistics.remove(entity);
+    }
+    if shapes.is_changed() {
+        statistics.restart(&shapes);
+        for (entity, index, mode, transform) in &all_points {
+            statistics.add(entity, index.0, *mode, transform.translation);
+        }
+        return;
+    }
+    for (entity, index, mode, transform) in &added {
+        // 有効にしたフレームでは、toggle_statisticsで集計済み
+        if !statistics.points.contains_key(&entity) {
+            statistics.add(entity, index.0, *mode, transform.translation);
+        }
+    }
+}
+
+// 今のサンプリングモードの平均の位置に、図形ごとのマーカーを置くシステム
+fn update_centroid_markers(
+    mut commands: Commands,
+    statistics: Res<SampleStatistics>,
+    mode: Res<SamplingMode>,
+    assets: Res<CentroidMarkerAssets>,
+    roots: Query<(Entity, &Transform, &ShapeRoot)>,
+    mut markers: Query<(Entity, &CentroidMarker, &mut Transform), Without<ShapeRoot>>,
+) {
+    if !statistics.enabled {
+        for (entity, ..) in &markers {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let mut placed = vec![false; statistics.shapes.len()];
+    for (entity, marker, mut transform) in &mut markers {
+        let mean = statistics
+            .shapes
+            .get(marker.0)
+            .and_then(|shape| shape.samples(*mode).mean());
+        match mean {
+            Some(mean) => {
+                transform.translation = mean;
+                placed[marker.0] = true;
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    let roots = crate::shape_roots(&roots);
+    for (index, shape) in statistics.shapes.iter().enumerate() {
+        if placed[index] {
+            continue;
+        }
+        let (Some(mean), Some((root, _))) = (
+            shape.samples(*mode).mean(),
+            roots.get(index).copied().flatten(),
+        ) else {
+            continue;
+        };
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation(mean),
+            ChildOf(root),
+            CentroidMarker(index),
+        ));
+    }
+}
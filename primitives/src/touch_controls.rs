@@ -0,0 +1,124 @@
+//! タッチ操作によるカメラの回転・ズームと、注視する図形の切り替え
+//!
+//! マウスやキーボードのないスマートフォンのブラウザ(wasm)でも操作できるようにする。
+//! - 1本指のドラッグ: マウスのドラッグと同じようにカメラを回転させる
+//! - 2本指のピンチ: 指の間隔の比率に合わせてズームする(広げると近づく)
+//! - ダブルタップ: 注視する図形を右隣に切り替える(右端の次は左端に戻る)
+//!
+//! タッチ画面のあるデスクトップでも同じように動くので、wasmに限らず追加する。
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::demo_config::DemoConfig;
+use crate::panel::ControlPanel;
+use crate::{CameraRig, SampledShapes};
+
+/// ドラッグの移動量(ピクセル)を回転の角度に変える割合(マウスのドラッグと同じ)
+const TOUCH_ROTATION_SCALE: f32 = 1.0 / 90.0;
+
+/// 2回のタップをダブルタップとみなす間隔(秒)
+pub const DOUBLE_TAP_TIME: f32 = 0.3;
+
+/// 押してから離すまでにこれ以上指が動いたら、タップではなくドラッグとみなす(ピクセル)
+const TAP_TOLERANCE: f32 = 10.0;
+
+/// 2回のタップをダブルタップとみなす位置のずれの上限(ピクセル)
+const DOUBLE_TAP_DISTANCE: f32 = 40.0;
+
+/// タッチ操作を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_touch.before(crate::update_camera));
+    }
+}
+
+/// 最後のタップ(ダブルタップの判定に使う)
+#[derive(Clone, Copy, Debug)]
+struct LastTap {
+    time: f32,      // タップした時刻(秒)
+    position: Vec2, // タップした位置
+}
+
+// タッチの入力でカメラを回転・ズームし、ダブルタップで注視する図形を切り替えるシステム
+pub(crate) fn handle_touch(
+    touches: Res<Touches>,
+    mut camera_query: Query<&mut CameraRig>,
+    shapes: Res<SampledShapes>,
+    control_panel: Res<ControlPanel>,
+    config: Res<DemoConfig>,
+    time: Res<Time<Real>>,
+    mut last_positions: Local<HashMap<u64, Vec2>>,
+    mut last_tap: Local<Option<LastTap>>,
+) {
+    // 指の位置を前のフレームの位置と組にする(指が動かなかったフレームでも移動量を0にするため、
+    // Touchのprevious_positionではなく、このシステムで覚えた位置を使う)
+    let pressed: Vec<(Vec2, Vec2)> = touches
+        .iter()
+        .map(|touch| {
+            let position = touch.position();
+            let previous = last_positions.get(&touch.id()).copied();
+            (position, previous.unwrap_or(position))
+        })
+        .collect();
+    *last_positions = touches
+        .iter()
+        .map(|touch| (touch.id(), touch.position()))
+        .collect();
+
+    // 操作パネルを触っている間は、カメラを動かさない
+    if control_panel.wants_pointer {
+        return;
+    }
+    let Ok(mut camera_rig) = camera_query.single_mut() else {
+        return;
+    };
+
+    match pressed.as_slice() {
+        // 1本指のドラッグで回転(上下の回転は行き過ぎないように制限する)
+        [(position, previous)] => {
+            let delta = (*position - *previous) * TOUCH_ROTATION_SCALE;
+            if delta != Vec2::ZERO {
+                camera_rig.yaw += delta.x;
+                camera_rig.pitch = (camera_rig.pitch + delta.y).clamp(-PI / 2.01, PI / 2.01);
+            }
+        }
+        // 2本指のピンチで、前のフレームからの指の間隔の比率だけズーム
+        [(first, first_previous), (second, second_previous)] => {
+            let distance = first.distance(*second);
+            let previous = first_previous.distance(*second_previous);
+            if distance > 0.0 && previous > 0.0 && distance != previous {
+                let zoomed = camera_rig.target_distance * previous / distance;
+                camera_rig.target_distance = config.clamp_camera_distance(zoomed);
+            }
+        }
+        _ => {}
+    }
+
+    // ほとんど動かさずに離したらタップとみなし、短い間隔で2回続いたら図形を切り替える
+    let now = time.elapsed_secs();
+    for touch in touches.iter_just_released() {
+        if touch.distance().length() > TAP_TOLERANCE {
+            continue;
+        }
+        let position = touch.position();
+        let double_tap = last_tap.is_some_and(|tap| {
+            now - tap.time <= DOUBLE_TAP_TIME
+                && tap.position.distance(position) <= DOUBLE_TAP_DISTANCE
+        });
+        if double_tap && !shapes.0.is_empty() {
+            let next = (shapes.closest(camera_rig.target) + 1) % shapes.0.len();
+            camera_rig.target = shapes.0[next].1;
+            *last_tap = None; // 3回目のタップは新しい1回目として数える
+        } else {
+            *last_tap = Some(LastTap {
+                time: now,
+                position,
+            });
+        }
+    }
+}
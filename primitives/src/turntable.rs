@@ -0,0 +1,69 @@
+//! カメラのターンテーブル(自動で周回する)モード
+//!
+//! 「X」キーで切り替えると、カメラが注視点の周りを一定の角速度で回り続ける(`CameraRig.yaw`を進める)。
+//! 手を離したままデモを録画する(F11)ためのモードで、角速度は設定ファイルの`orbit_speed`(ラジアン/秒)で変える。
+//! マウス・タッチでドラッグしている間や右スティックを倒している間は、その操作を優先して周回を止める。
+//! ポイントの生成を一時停止(Space)しても回り続けるように、経過時間は`Time<Real>`を使う
+//! (録画中は`Time<Real>`もフレームごとの固定の時間で進むので、動画の回転は滑らかになる)。
+//! プリセットへの切り替え中と、向きが固定される2Dの表示では回さない。
+
+use bevy::prelude::*;
+use sample_common::ActionAxes;
+
+use crate::camera_presets::CameraTransition;
+use crate::demo_config::DemoConfig;
+use crate::{CameraRig, MousePressed, SampleAction, ViewMode};
+
+/// カメラのターンテーブルモードを追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct TurntablePlugin;
+
+impl Plugin for TurntablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Turntable>().add_systems(
+            Update,
+            (toggle_turntable, orbit_camera)
+                .chain()
+                .after(crate::handle_keypress)
+                .after(crate::handle_mouse)
+                .after(crate::handle_gamepad_camera)
+                .after(crate::touch_controls::handle_touch)
+                .before(crate::update_camera),
+        );
+    }
+}
+
+/// カメラを自動で周回させるかを保持するリソース
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Turntable {
+    pub enabled: bool, // カメラを自動で周回させるか(初期は止めている)
+}
+
+// 「X」キー：ターンテーブルモードを切り替えるシステム
+fn toggle_turntable(actions: Res<ButtonInput<SampleAction>>, mut turntable: ResMut<Turntable>) {
+    if actions.just_pressed(SampleAction::ToggleTurntable) {
+        turntable.enabled = !turntable.enabled;
+    }
+}
+
+// ターンテーブルモードのとき、カメラの水平方向の角度を進めるシステム
+fn orbit_camera(
+    turntable: Res<Turntable>,
+    config: Res<DemoConfig>,
+    view: Res<ViewMode>,
+    time: Res<Time<Real>>,
+    mouse_pressed: Res<MousePressed>,
+    touches: Res<Touches>,
+    axes: Res<ActionAxes>,
+    mut camera_rig: Query<&mut CameraRig, Without<CameraTransition>>,
+) {
+    if !turntable.enabled || *view == ViewMode::TwoD {
+        return;
+    }
+    // ユーザーがカメラを動かしている間は止める(離すと今の角度から続ける)
+    if mouse_pressed.0 || touches.iter().next().is_some() || axes.look != Vec2::ZERO {
+        return;
+    }
+    for mut rig in &mut camera_rig {
+        rig.yaw += config.orbit_speed * time.delta_secs();
+    }
+}
@@ -0,0 +1,165 @@
+//! 図形のメッシュのワイヤーフレーム表示
+//!
+//! 境界のサンプリングが実際の三角形分割とどう対応しているかを確かめるため、
+//! 図形のメッシュの三角形の辺を線(`LineList`)のメッシュにして、図形のエンティティの子として重ねる。
+//! `WireframePlugin`は線の描画(`POLYGON_MODE_LINE`)に対応したGPUが必要でWebGLでは使えないので、
+//! 辺の一覧から専用のメッシュを作る(`ShapeMeshBuilder::build_wireframe`)。
+//! 「W」キーで表示を切り替え、子なので図形の回転や表示の切り替え(Vキー)にもそのまま付いていく。
+
+use std::collections::HashSet;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+
+use crate::{SampleAction, SampledShapes, ShapeRoot};
+
+/// ワイヤーフレーム表示を追加するプラグイン(`PrimitivesPlugin`に含まれる)
+pub struct ShapeWireframePlugin;
+
+impl Plugin for ShapeWireframePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeWireframe>()
+            .init_resource::<WireframeMaterial>()
+            .add_systems(
+                Update,
+                (
+                    toggle_wireframe,
+                    attach_wireframes,
+                    update_wireframe_visibility,
+                )
+                    .chain()
+                    .after(crate::handle_keypress)
+                    .after(crate::switch_view)
+                    .after(crate::custom_mesh::add_custom_mesh),
+            );
+    }
+}
+
+/// ワイヤーフレームを表示しているかを保持するリソース
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShapeWireframe {
+    pub visible: bool, // 図形の三角形の辺を表示するか(初期は非表示)
+}
+
+/// ワイヤーフレームのメッシュに付けるマーカーコンポーネント(図形のエンティティの子)
+#[derive(Component)]
+pub struct WireframeOverlay;
+
+/// ワイヤーフレームの線のマテリアルのハンドルを保持するリソース
+#[derive(Resource)]
+struct WireframeMaterial(Handle<StandardMaterial>);
+
+impl FromWorld for WireframeMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.8, 0.9, 1.0), // 半透明な図形の上でも見える明るい色
+            unlit: true,                            // ライトの影響を受けない
+            ..default()
+        }))
+    }
+}
+
+/// 三角形リストのメッシュから、三角形の辺を線にしたメッシュ(`LineList`)を作る
+/// 隣り合う三角形で共有する辺は1本にまとめる(頂点の位置が同じなら同じ辺とみなす)
+pub fn wireframe_mesh(mesh: &Mesh) -> Mesh {
+    let positions: Vec<Vec3> = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions))
+            if mesh.primitive_topology() == PrimitiveTopology::TriangleList =>
+        {
+            positions
+                .iter()
+                .map(|position| Vec3::from(*position))
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    for corners in indices.chunks_exact(3) {
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            let (Some(start), Some(end)) = (positions.get(corners[a]), positions.get(corners[b]))
+            else {
+                continue;
+            };
+            // 向きによらず同じキーになるように、ビット列の小さい方を先にする
+            let (start_bits, end_bits) = (
+                start.to_array().map(f32::to_bits),
+                end.to_array().map(f32::to_bits),
+            );
+            let key = if start_bits <= end_bits {
+                (start_bits, end_bits)
+            } else {
+                (end_bits, start_bits)
+            };
+            if start_bits != end_bits && seen.insert(key) {
+                lines.push(start.to_array());
+                lines.push(end.to_array());
+            }
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, lines)
+}
+
+// 「W」キー：ワイヤーフレームの表示・非表示を切り替えるシステム
+fn toggle_wireframe(
+    actions: Res<ButtonInput<SampleAction>>,
+    mut wireframe: ResMut<ShapeWireframe>,
+) {
+    if actions.just_pressed(SampleAction::ToggleWireframe) {
+        wireframe.visible = !wireframe.visible;
+    }
+}
+
+// 新しく配置された図形(起動時・表示の切り替え・glTFの読み込み)にワイヤーフレームを付けるシステム
+fn attach_wireframes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    material: Res<WireframeMaterial>,
+    wireframe: Res<ShapeWireframe>,
+    shapes: Res<SampledShapes>,
+    roots: Query<(Entity, &ShapeRoot), Added<ShapeRoot>>,
+) {
+    for (root, index) in roots.iter() {
+        let Some((shape, _)) = shapes.0.get(index.0) else {
+            continue;
+        };
+        commands.spawn((
+            Mesh3d(meshes.add(shape.mesh().build_wireframe())),
+            MeshMaterial3d(material.0.clone()),
+            visibility(*wireframe),
+            WireframeOverlay,
+            ChildOf(root), // 図形と一緒に動く
+        ));
+    }
+}
+
+// 表示の設定が変わったら、すべてのワイヤーフレームの表示を合わせるシステム
+fn update_wireframe_visibility(
+    wireframe: Res<ShapeWireframe>,
+    mut overlays: Query<&mut Visibility, With<WireframeOverlay>>,
+) {
+    if !wireframe.is_changed() {
+        return;
+    }
+    for mut overlay in overlays.iter_mut() {
+        overlay.set_if_neq(visibility(*wireframe));
+    }
+}
+
+/// 設定に対応するワイヤーフレームの表示状態(表示するときは図形の表示に従う)
+fn visibility(wireframe: ShapeWireframe) -> Visibility {
+    if wireframe.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
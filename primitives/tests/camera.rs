@@ -0,0 +1,125 @@
+//! サンプルのカメラ操作のテスト
+
+mod common;
+
+use bevy::input::gamepad::GamepadInput;
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::CameraProjection;
+use primitives::camera_presets::CAMERA_TRANSITION_TIME;
+use sample_common::ActionAxes;
+
+/// カメラの投影方法
+fn projection(app: &mut App) -> Projection {
+    app.world_mut()
+        .query_filtered::<&Projection, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn key_toggles_orthographic_projection() {
+    let mut app = HeadlessApp::new().start();
+    assert!(matches!(projection(&mut app), Projection::Perspective(_)));
+
+    tap(&mut app, KeyCode::KeyO);
+    assert_eq!(
+        *app.world().resource::<CameraProjection>(),
+        CameraProjection::Orthographic
+    );
+    assert!(matches!(projection(&mut app), Projection::Orthographic(_)));
+
+    // 正射影でも左右の図形への切り替えでカメラが動く
+    let before = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .translation;
+    tap(&mut app, KeyCode::ArrowRight);
+    let after = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .translation;
+    assert_ne!(before, after);
+
+    tap(&mut app, KeyCode::KeyO);
+    assert!(matches!(projection(&mut app), Projection::Perspective(_)));
+}
+
+/// カメラが向いている方向
+fn camera_forward(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .forward()
+        .as_vec3()
+}
+
+#[test]
+fn number_key_moves_camera_to_preset_smoothly() {
+    // フレームの経過時間を固定して、補間の進み方を決める
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let start = camera_forward(&mut app);
+
+    // 「2」キーで真上からの視点に切り替える
+    tap(&mut app, KeyCode::Digit2);
+    let moving = camera_forward(&mut app);
+    assert!(moving.y < start.y, "カメラが下を向き始める");
+    assert!(moving.y > -0.99, "一瞬で切り替わらない");
+
+    for _ in 0..(CAMERA_TRANSITION_TIME / 0.05) as usize + 2 {
+        app.update();
+    }
+    let arrived = camera_forward(&mut app);
+    assert!(arrived.y < -0.99, "真上から見下ろしている: {arrived}");
+}
+
+/// カメラの位置
+fn camera_position(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .translation
+}
+
+#[test]
+fn gamepad_stick_orbits_and_triggers_zoom() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let gamepad = app.world_mut().spawn(Gamepad::default()).id();
+    app.update();
+    let start_forward = camera_forward(&mut app);
+    let start_position = camera_position(&mut app);
+
+    // 右トリガーを押し込むと、向きはそのままで近づく
+    let set = |app: &mut App, input: GamepadInput, value: f32| {
+        let mut entity = app.world_mut().entity_mut(gamepad);
+        entity
+            .get_mut::<Gamepad>()
+            .unwrap()
+            .analog_mut()
+            .set(input, value);
+    };
+    set(&mut app, GamepadButton::RightTrigger2.into(), 1.0);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<ActionAxes>().triggers, 1.0);
+    let zoomed_forward = camera_forward(&mut app);
+    let zoomed_position = camera_position(&mut app);
+    assert!(zoomed_forward.abs_diff_eq(start_forward, 1e-4));
+    assert!(zoomed_position.distance(start_position) > 0.1);
+
+    // トリガーを離して右スティックを倒すと、カメラが回り込む
+    set(&mut app, GamepadButton::RightTrigger2.into(), 0.0);
+    set(&mut app, GamepadAxis::RightStickX.into(), 1.0);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(!camera_forward(&mut app).abs_diff_eq(start_forward, 1e-2));
+}
@@ -0,0 +1,50 @@
+//! k-means法によるクラスタリングのテスト
+
+use bevy::prelude::*;
+use primitives::clustering::k_means;
+
+/// 中心の周りに、各軸方向へ少しずつずらしたポイントを作る
+fn blob(center: Vec3, count: usize) -> Vec<Vec3> {
+    (0..count)
+        .map(|i| {
+            let offset = (i as f32 / count as f32 - 0.5) * 0.2;
+            center + Vec3::new(offset, -offset, offset * 0.5)
+        })
+        .collect()
+}
+
+#[test]
+fn separated_groups_become_clusters() {
+    let left = Vec3::new(-5.0, 0.0, 0.0);
+    let right = Vec3::new(5.0, 1.0, 0.0);
+    let mut points = blob(left, 30);
+    points.extend(blob(right, 70));
+
+    let mut clusters = k_means(&points, &[], 2, 10);
+    clusters.sort_by(|a, b| a.centroid.x.total_cmp(&b.centroid.x));
+
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].count, 30);
+    assert_eq!(clusters[1].count, 70);
+    assert!(clusters[0].centroid.distance(left) < 0.1);
+    assert!(clusters[1].centroid.distance(right) < 0.1);
+}
+
+#[test]
+fn initial_centroids_keep_cluster_order() {
+    let points: Vec<Vec3> = [blob(Vec3::X * 3.0, 10), blob(Vec3::X * -3.0, 10)].concat();
+
+    // 前回の重心を渡すと、その順番のままクラスタが返る
+    let clusters = k_means(&points, &[Vec3::X * -2.0, Vec3::X * 2.0], 2, 10);
+    assert!(clusters[0].centroid.x < 0.0);
+    assert!(clusters[1].centroid.x > 0.0);
+}
+
+#[test]
+fn fewer_points_than_k() {
+    assert!(k_means(&[], &[], 4, 10).is_empty());
+
+    let clusters = k_means(&[Vec3::ZERO, Vec3::ONE], &[], 4, 10);
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters.iter().map(|cluster| cluster.count).sum::<usize>(), 2);
+}
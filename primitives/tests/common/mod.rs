@@ -0,0 +1,154 @@
+//! テストで共通に使う、ヘッドレスで組み立てたAppとキー入力の操作
+//!
+//! テストごとに使う関数が違うので、使わないものがあっても警告しない
+
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use primitives::demo_config::DemoConfig;
+use primitives::{PrimitivesPlugin, RngSeed, SamplingParams, SpawnQueue, SpawningMode};
+
+/// ヘッドレスでサンプル全体を組み立てるAppの設定
+#[derive(Default)]
+pub struct HeadlessApp {
+    seed: Option<RngSeed>,        // 乱数のシード値(Noneならデフォルト)
+    frame_time: Option<Duration>, // 1フレームの経過時間(Noneなら実際の経過時間)
+    manual: bool,                 // 最初のフレームの後で手動モードにするか
+    animation_time: Option<f32>,  // 生成・削除アニメーションの時間(秒。Noneならデフォルト)
+    points: usize,                // 手動モードにした後で生成するポイント数
+}
+
+impl HeadlessApp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// シード値を固定する
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(RngSeed {
+            seed,
+            reseed_on_reset: false,
+        });
+        self
+    }
+
+    /// 「R」キーのリスタートで、乱数をシード値から始め直す
+    pub fn reseed_on_reset(mut self) -> Self {
+        self.seed.get_or_insert_default().reseed_on_reset = true;
+        self
+    }
+
+    /// 1フレームの経過時間をミリ秒で固定する
+    pub fn frame_time(mut self, millis: u64) -> Self {
+        self.frame_time = Some(Duration::from_millis(millis));
+        self
+    }
+
+    /// 最初のフレームの後で手動モードにする(最初のフレームは自動モードで数個生成する)
+    pub fn manual(mut self) -> Self {
+        self.manual = true;
+        self
+    }
+
+    /// 生成・削除アニメーションの時間(秒)を変える
+    pub fn animation_time(mut self, seconds: f32) -> Self {
+        self.animation_time = Some(seconds);
+        self
+    }
+
+    /// 手動モードにした後で、`count`個のポイントを生成する
+    pub fn points(mut self, count: usize) -> Self {
+        self.manual = true;
+        self.points = count;
+        self
+    }
+
+    /// プラグインを組み立てるだけで、フレームは進めないAppを作る
+    /// (最初のフレームの前にリソースを書き換えたいテスト向け。手動モードやポイントの生成は行わない)
+    pub fn build(&self) -> App {
+        let mut app = App::new();
+        app.add_plugins(sample_common::headless::headless_plugins(None));
+        if let Some(seed) = &self.seed {
+            app.insert_resource(seed.clone());
+        }
+        app.add_plugins(PrimitivesPlugin);
+        if let Some(frame_time) = self.frame_time {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+        }
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    /// 最初のフレームまで進め、指定に応じて手動モードにしてポイントを生成したAppを作る
+    pub fn start(self) -> App {
+        let mut app = self.build();
+        app.update();
+        self.prepare(&mut app);
+        app
+    }
+
+    /// 設定ファイルを読み込み終えるまでフレームを進めたAppを作る
+    /// (後から読み込まれた値で、テストで書き換えた値が上書きされないようにする)
+    pub fn start_with_config(self) -> App {
+        let mut app = self.build();
+        for _ in 0..200 {
+            app.update();
+            if !app.world().resource::<Assets<DemoConfig>>().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!app.world().resource::<Assets<DemoConfig>>().is_empty());
+        // 読み込みのイベントを反映させる
+        app.update();
+        app.update();
+        self.prepare(&mut app);
+        app
+    }
+
+    /// 手動モードにし、アニメーションの時間を変えて、ポイントを生成する
+    fn prepare(&self, app: &mut App) {
+        if self.manual {
+            *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+        }
+        if let Some(seconds) = self.animation_time {
+            app.world_mut()
+                .resource_mut::<SamplingParams>()
+                .animation_time = seconds;
+        }
+        if self.points > 0 {
+            spawn(app, self.points);
+        }
+    }
+}
+
+/// 予約したポイントがすべて生成されるまでに回す最大のフレーム数
+const MAX_FRAMES: usize = 10_000;
+
+/// `count`個のポイントを予約し、すべて生成されるまでフレームを回す
+pub fn spawn(app: &mut App, count: usize) {
+    app.world_mut().resource_mut::<SpawnQueue>().0 = count;
+    for _ in 0..MAX_FRAMES {
+        if app.world().resource::<SpawnQueue>().0 == 0 {
+            return;
+        }
+        app.update();
+    }
+    panic!("予約したポイントが生成されない");
+}
+
+/// キーを1フレームだけ押す
+pub fn tap(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(key);
+    app.update();
+}
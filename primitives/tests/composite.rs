@@ -0,0 +1,78 @@
+//! 注視している図形と右隣の図形の合成(和集合・共通部分)のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn, tap};
+use primitives::{SamplePoint, ShapeIndex};
+
+/// 直方体の各軸の半分の長さと、右隣の球の半径(直方体は球にすっぽり入る)
+const CUBOID_HALF_SIZE: Vec3 = Vec3::new(0.25, 0.5, 0.25);
+const SPHERE_RADIUS: f32 = 0.75;
+
+/// 判定の誤差
+const EPSILON: f32 = 1e-4;
+
+/// 図形の中心から見た、その図形のポイントの位置
+fn points_of(app: &mut App, index: usize) -> Vec<Vec3> {
+    app.world_mut()
+        .query_filtered::<(&Transform, &ShapeIndex), With<SamplePoint>>()
+        .iter(app.world())
+        .filter(|(_, shape)| shape.0 == index)
+        .map(|(transform, _)| transform.translation)
+        .collect()
+}
+
+fn in_cuboid(point: Vec3) -> bool {
+    point.abs().cmple(CUBOID_HALF_SIZE + EPSILON).all()
+}
+
+#[test]
+fn five_key_cycles_union_and_intersection_with_the_neighbor() {
+    let mut app = HeadlessApp::new().seed(5).manual().start();
+    // 左端の直方体を注視する(右隣は球)
+    for _ in 0..4 {
+        tap(&mut app, KeyCode::ArrowLeft);
+    }
+    spawn(&mut app, 900);
+    assert!(!points_of(&mut app, 0).is_empty());
+
+    // 和集合: 元の図形のポイントは消え、球全体に広がる
+    tap(&mut app, KeyCode::Digit5);
+    assert!(points_of(&mut app, 0).is_empty());
+    spawn(&mut app, 900);
+    let union = points_of(&mut app, 0);
+    assert!(!union.is_empty());
+    assert!(
+        union
+            .iter()
+            .all(|point| point.length() <= SPHERE_RADIUS + EPSILON)
+    );
+    assert!(union.iter().any(|point| !in_cuboid(*point)));
+
+    // 共通部分: 直方体の内部だけになる
+    tap(&mut app, KeyCode::Digit5);
+    spawn(&mut app, 900);
+    let intersection = points_of(&mut app, 0);
+    assert!(!intersection.is_empty());
+    assert!(intersection.iter().all(|point| in_cuboid(*point)));
+
+    // もう一度押すと元の直方体に戻る
+    tap(&mut app, KeyCode::Digit5);
+    spawn(&mut app, 900);
+    let original = points_of(&mut app, 0);
+    assert!(!original.is_empty());
+    assert!(original.iter().all(|point| in_cuboid(*point)));
+}
+
+#[test]
+fn solid_and_planar_shapes_are_not_combined() {
+    let mut app = HeadlessApp::new().seed(5).manual().start();
+    // 中央の四面体を注視している(右隣は平面の三角形)
+    spawn(&mut app, 900);
+    let before = points_of(&mut app, 4);
+    assert!(!before.is_empty());
+
+    tap(&mut app, KeyCode::Digit5);
+    assert_eq!(points_of(&mut app, 4), before);
+}
@@ -0,0 +1,123 @@
+//! 注視している図形のポイントの凸包のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn, tap};
+use primitives::convex_hull::{ConvexHull, HullOverlay, convex_hull};
+use primitives::{SamplePoint, ShapeIndex, ShapeRoot};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// 一辺が1の立方体の頂点
+fn cube_corners() -> Vec<Vec3> {
+    (0..8)
+        .map(|i| Vec3::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32) - 0.5)
+        .collect()
+}
+
+/// エンティティを付けたポイント
+fn with_entities(points: &[Vec3]) -> Vec<(Entity, Vec3)> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| (Entity::from_raw(i as u32), *point))
+        .collect()
+}
+
+#[test]
+fn hull_of_a_cube_ignores_interior_points() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut points = cube_corners();
+    points.extend((0..200).map(|_| Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen()) * 0.8 - 0.4));
+
+    let mut hull = ConvexHull::default();
+    hull.rebuild(with_entities(&points));
+    assert_eq!(hull.vertices.len(), 8);
+    assert_eq!(hull.triangles.len(), 12);
+    assert!((hull.volume() - 1.0).abs() < 1e-4);
+    assert!(points.iter().all(|point| hull.contains(*point)));
+    assert!(!hull.contains(Vec3::new(0.6, 0.0, 0.0)));
+}
+
+#[test]
+fn hull_grows_only_for_outside_points() {
+    let corners = cube_corners();
+    let mut hull = ConvexHull::default();
+    hull.rebuild(with_entities(&corners[..4]));
+    // 同じ平面上の4点は正方形になる
+    assert_eq!(hull.triangles.len(), 2);
+    assert_eq!(hull.volume(), 0.0);
+    assert!(hull.contains(Vec3::new(0.0, 0.0, -0.5)));
+
+    // 内側の点では変わらない
+    let inside = (Entity::from_raw(100), Vec3::new(0.1, -0.2, -0.5));
+    assert!(!hull.extend([inside]));
+    assert_eq!(hull.vertices.len(), 4);
+
+    // 外側の点で立方体に広がる
+    assert!(hull.extend(with_entities(&corners[4..])));
+    assert_eq!(hull.vertices.len(), 8);
+    assert!((hull.volume() - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn degenerate_points_have_no_faces() {
+    assert!(convex_hull(&[]).is_empty());
+    let line: Vec<Vec3> = (0..5).map(|i| Vec3::X * i as f32).collect();
+    assert!(convex_hull(&line).is_empty());
+
+    // 面ができるまでは、すべてのポイントを残しておく
+    let mut hull = ConvexHull::default();
+    hull.rebuild(with_entities(&line));
+    assert_eq!(hull.vertices.len(), line.len());
+    assert!(hull.triangles.is_empty());
+    assert!(hull.extend([(Entity::from_raw(10), Vec3::Y)]));
+    assert_eq!(hull.triangles.len(), 1);
+}
+
+#[test]
+fn u_key_wraps_the_focused_shape_points() {
+    let mut app = HeadlessApp::new().manual().start();
+    tap(&mut app, KeyCode::KeyU);
+    assert!(app.world().resource::<ConvexHull>().enabled);
+
+    spawn(&mut app, 500);
+    let hull = app.world().resource::<ConvexHull>();
+    let shape = hull.shape.expect("注視している図形");
+    assert!(hull.volume() > 0.0);
+    let points: Vec<Vec3> = app
+        .world_mut()
+        .query_filtered::<(&Transform, &ShapeIndex), With<SamplePoint>>()
+        .iter(app.world())
+        .filter(|(_, index)| index.0 == shape)
+        .map(|(transform, _)| transform.translation)
+        .collect();
+    assert!(!points.is_empty());
+    let hull = app.world().resource::<ConvexHull>();
+    assert!(points.iter().all(|point| hull.contains(*point)));
+
+    // 凸包のメッシュは注視している図形の子になる
+    let parents: Vec<Entity> = app
+        .world_mut()
+        .query_filtered::<&ChildOf, With<HullOverlay>>()
+        .iter(app.world())
+        .map(ChildOf::parent)
+        .collect();
+    assert_eq!(parents.len(), 1);
+    assert_eq!(app.world().get::<ShapeRoot>(parents[0]).unwrap().0, shape);
+
+    // リスタートでポイントがなくなると、凸包も空になる
+    tap(&mut app, KeyCode::KeyR);
+    app.update();
+    assert!(app.world().resource::<ConvexHull>().vertices.is_empty());
+
+    tap(&mut app, KeyCode::KeyU);
+    assert!(!app.world().resource::<ConvexHull>().enabled);
+    let overlays = app
+        .world_mut()
+        .query_filtered::<(), With<HullOverlay>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(overlays, 0);
+}
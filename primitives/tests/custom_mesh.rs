@@ -0,0 +1,80 @@
+//! glTFから読み込んだメッシュのサンプリングのテスト
+
+use bevy::prelude::*;
+use primitives::custom_mesh::{CustomMeshSource, DEFAULT_MESH_PATH, TriangleMesh};
+use primitives::monte_carlo::SignedDistance;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// 判定の誤差の許容値
+const EPSILON: f32 = 1e-4;
+
+/// 1つのメッシュから取るサンプル数
+const SAMPLES: usize = 2000;
+
+/// 辺の長さ1の立方体のメッシュ(読み込むと辺の長さ1.6に揃えられる)
+fn cube() -> TriangleMesh {
+    TriangleMesh::from_mesh(&Cuboid::new(1.0, 1.0, 1.0).mesh().build()).unwrap()
+}
+
+#[test]
+fn mesh_is_centered_and_measured() {
+    let mesh = cube();
+    assert!((mesh.volume() - 1.6_f32.powi(3)).abs() < EPSILON);
+    assert!((mesh.area() - 6.0 * 1.6_f32.powi(2)).abs() < EPSILON);
+    assert!(mesh.half_extents().abs_diff_eq(Vec3::splat(0.8), EPSILON));
+
+    assert!(mesh.contains(Vec3::ZERO));
+    assert!(mesh.contains(Vec3::new(0.7, -0.7, 0.7)));
+    assert!(!mesh.contains(Vec3::new(0.9, 0.0, 0.0)));
+    assert!((mesh.signed_distance(Vec3::ZERO) + 0.8).abs() < EPSILON);
+    assert!((mesh.signed_distance(Vec3::new(1.0, 0.0, 0.0)) - 0.2).abs() < EPSILON);
+}
+
+#[test]
+fn mesh_samples_are_on_the_surface_and_inside() {
+    let mesh = cube();
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+    // 面積の等しい6つの面に、ほぼ同じ数ずつ選ばれる
+    let mut faces = [0; 6];
+    for _ in 0..SAMPLES {
+        let point = mesh.sample_boundary(&mut rng);
+        assert!(mesh.signed_distance(point).abs() < EPSILON, "{point}");
+        let extents = point.abs();
+        let axis = (0..3)
+            .max_by(|a, b| extents[*a].total_cmp(&extents[*b]))
+            .unwrap();
+        faces[axis * 2 + usize::from(point[axis] > 0.0)] += 1;
+    }
+    for count in faces {
+        assert!(count > SAMPLES / 6 / 2, "{faces:?}");
+    }
+
+    for _ in 0..SAMPLES {
+        let point = mesh.sample_interior(&mut rng);
+        assert!(mesh.contains(point), "{point}");
+    }
+}
+
+#[test]
+fn mesh_without_triangles_is_rejected() {
+    assert!(TriangleMesh::new(Vec::new()).is_none());
+    let degenerate = Triangle3d::new(Vec3::ZERO, Vec3::X, Vec3::X * 2.0);
+    assert!(TriangleMesh::new(vec![degenerate]).is_none());
+}
+
+#[test]
+fn mesh_path_is_read_from_args() {
+    let args = |list: &[&str]| CustomMeshSource::from_args(list.iter().map(|arg| arg.to_string()));
+    assert_eq!(args(&[]).path.as_deref(), Some(DEFAULT_MESH_PATH));
+    assert_eq!(
+        args(&["--seed", "7", "--mesh", "models/bunny.glb"])
+            .path
+            .as_deref(),
+        Some("models/bunny.glb")
+    );
+    assert_eq!(args(&["--no-mesh"]).path, None);
+    // 挿入されなければ読み込まない
+    assert_eq!(CustomMeshSource::default().path, None);
+}
@@ -0,0 +1,79 @@
+//! 設定ファイル(DemoConfig)の反映のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::demo_config::DemoConfig;
+use primitives::export::{ExportedPoint, write_ply};
+use primitives::layout::LayoutMode;
+use primitives::{SamplingMode, SamplingParams, SpawnRate};
+
+#[test]
+fn bundled_config_matches_the_defaults() {
+    let source = include_str!("../assets/config/primitives.config.ron");
+    let config: DemoConfig = bevy::asset::ron::from_str(source).unwrap();
+    assert_eq!(config, DemoConfig::default());
+}
+
+#[test]
+fn changed_fields_are_applied_to_sampling_params() {
+    let mut app = HeadlessApp::new().start_with_config();
+
+    // 操作パネルで変えた値は、設定ファイルで変わらなければそのまま残る
+    app.world_mut()
+        .resource_mut::<SamplingParams>()
+        .animation_time = 0.5;
+    app.world_mut().resource_mut::<DemoConfig>().max_points = 500;
+    app.update();
+
+    let params = app.world().resource::<SamplingParams>();
+    assert_eq!(params.max_points, 500);
+    assert_eq!(params.animation_time, 0.5);
+    assert_eq!(
+        app.world().resource::<SpawnRate>().points_per_frame,
+        DemoConfig::default().points_per_frame
+    );
+}
+
+#[test]
+fn changed_layout_is_applied_until_the_key_changes_it() {
+    let mut app = HeadlessApp::new().start_with_config();
+
+    app.world_mut().resource_mut::<DemoConfig>().layout = LayoutMode::Ring;
+    app.update();
+    assert_eq!(*app.world().resource::<LayoutMode>(), LayoutMode::Ring);
+
+    // Fキーで変えた並べ方は、設定ファイルの他の値が変わっても残る
+    *app.world_mut().resource_mut::<LayoutMode>() = LayoutMode::Grid;
+    app.world_mut().resource_mut::<DemoConfig>().max_points = 500;
+    app.update();
+    assert_eq!(*app.world().resource::<LayoutMode>(), LayoutMode::Grid);
+}
+
+#[test]
+fn camera_distance_is_kept_in_the_configured_range() {
+    let config = DemoConfig {
+        min_camera_distance: 2.0,
+        max_camera_distance: 10.0,
+        ..default()
+    };
+    assert_eq!(config.clamp_camera_distance(0.5), 2.0);
+    assert_eq!(config.clamp_camera_distance(20.0), 10.0);
+    assert_eq!(config.camera_distance_ratio(6.0), 0.5);
+}
+
+#[test]
+fn exported_colors_follow_the_config() {
+    let mut config = DemoConfig::default();
+    config.point_colors.interior = LinearRgba::RED;
+    let points = [ExportedPoint {
+        position: Vec3::ZERO,
+        mode: SamplingMode::Interior,
+    }];
+
+    let mut out = Vec::new();
+    write_ply(&points, &config.point_colors, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().last(), Some("0 0 0 255 0 0 0"));
+}
@@ -0,0 +1,95 @@
+//! ポイントの密度による色分けのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::density::{DensityColoring, DensityIndex, Gradient};
+use primitives::spatial_index::PointSpatialIndex;
+use primitives::{SamplePoint, SpawnQueue};
+
+/// 半径以内にある他のポイントの数をすべての組み合わせで数える
+fn brute_force_neighbors(points: &[(Entity, Vec3)], radius: f32) -> Vec<u32> {
+    points
+        .iter()
+        .map(|(entity, position)| {
+            points
+                .iter()
+                .filter(|(other, other_position)| {
+                    other != entity && other_position.distance(*position) <= radius
+                })
+                .count() as u32
+        })
+        .collect()
+}
+
+#[test]
+fn index_keeps_neighbor_counts_up_to_date() {
+    let points: Vec<(Entity, Vec3)> = (0..60)
+        .map(|i| {
+            let t = i as f32;
+            let position = Vec3::new((t * 0.37).sin(), (t * 0.73).cos(), (t * 0.11).sin()) * 0.5;
+            (Entity::from_raw(i), position)
+        })
+        .collect();
+
+    let mut spatial = PointSpatialIndex::default();
+    let mut index = DensityIndex::new(0.2);
+    for (entity, position) in &points {
+        spatial.insert(*entity, *position);
+        index.insert(*entity, *position, &spatial);
+    }
+    // 半分を取り除いても、残りのポイントの近傍の数は数え直した値と同じになる
+    for (entity, _) in points.iter().step_by(2) {
+        spatial.remove(*entity);
+        index.remove(*entity, &spatial);
+    }
+    let remaining: Vec<(Entity, Vec3)> = points.iter().skip(1).step_by(2).copied().collect();
+
+    assert_eq!(index.len(), remaining.len());
+    let expected = brute_force_neighbors(&remaining, 0.2);
+    for ((entity, _), expected) in remaining.iter().zip(expected) {
+        assert_eq!(index.neighbors(*entity), Some(expected));
+    }
+    assert_eq!(index.neighbors(points[0].0), None);
+}
+
+#[test]
+fn gradient_clamps_to_end_colors() {
+    assert_eq!(Gradient::Grayscale.sample(-1.0), LinearRgba::BLACK);
+    assert_eq!(Gradient::Grayscale.sample(2.0), LinearRgba::WHITE);
+    let middle = Gradient::Grayscale.sample(0.5);
+    assert!((middle.red - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn key_enables_density_index_for_all_points() {
+    let mut app = HeadlessApp::new().manual().start();
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyH);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyH);
+    // 有効にした後に生成したポイントも、空間ハッシュに追加される
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 500;
+    app.update();
+    app.update();
+
+    let points: Vec<(Entity, Vec3)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &GlobalTransform), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(entity, transform)| (entity, transform.translation()))
+        .collect();
+    let density = app.world().resource::<DensityColoring>();
+    let index = density.index().expect("色分けが有効になっている");
+    assert_eq!(index.len(), points.len());
+
+    let expected = brute_force_neighbors(&points, density.radius);
+    for ((entity, _), expected) in points.iter().zip(expected) {
+        assert_eq!(index.neighbors(*entity), Some(expected));
+    }
+}
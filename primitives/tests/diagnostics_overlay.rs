@@ -0,0 +1,66 @@
+//! FPS・ポイントの数・生成キューのオーバーレイのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::diagnostics_overlay::DiagnosticsOverlay;
+use primitives::{PointCounter, SpawnQueue};
+use sample_common::LocalizedText;
+use sample_common::locale::TextArg;
+
+/// 対応表のキーが`key`のテキストを探す
+fn find_text(app: &mut App, key: &str) -> (LocalizedText, Visibility) {
+    app.world_mut()
+        .query::<(&LocalizedText, Option<&Visibility>)>()
+        .iter(app.world())
+        .find(|(text, _)| text.key == key)
+        .map(|(text, visibility)| (text.clone(), visibility.copied().unwrap_or_default()))
+        .unwrap()
+}
+
+/// テキストの引数の値
+fn arg(text: &LocalizedText, name: &str) -> Option<TextArg> {
+    text.args
+        .iter()
+        .find(|(arg, _)| *arg == name)
+        .map(|(_, value)| value.clone())
+}
+
+#[test]
+fn f3_shows_point_count_and_queue() {
+    let mut app = HeadlessApp::new().manual().start();
+    // 一時停止して、キューに積んだポイントが生成されずに残るようにする
+    tap(&mut app, KeyCode::Space);
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 150;
+
+    assert!(!app.world().resource::<DiagnosticsOverlay>().visible);
+    tap(&mut app, KeyCode::F3);
+    assert!(app.world().resource::<DiagnosticsOverlay>().visible);
+
+    // 表示した直後に内容が更新される
+    let (text, _) = find_text(&mut app, "diagnostics.overlay");
+    let points = app.world().resource::<PointCounter>().0;
+    assert_eq!(
+        arg(&text, "points"),
+        Some(TextArg::Literal(points.to_string()))
+    );
+    assert_eq!(
+        arg(&text, "queue"),
+        Some(TextArg::Literal("150".to_string()))
+    );
+    assert_eq!(
+        arg(&text, "spawning"),
+        Some(TextArg::Key("panel.manual".to_string()))
+    );
+
+    // F3キーではポストプロセスの設定パネルは開かない(F6キーに移している)
+    let (_, visibility) = find_text(&mut app, "post_fx.panel");
+    assert_eq!(visibility, Visibility::Hidden);
+    tap(&mut app, KeyCode::F6);
+    let (_, visibility) = find_text(&mut app, "post_fx.panel");
+    assert_eq!(visibility, Visibility::Visible);
+
+    tap(&mut app, KeyCode::F3);
+    assert!(!app.world().resource::<DiagnosticsOverlay>().visible);
+}
@@ -0,0 +1,119 @@
+//! 生成・削除アニメーションのイージングのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::easing::AnimationEasing;
+use primitives::lifetime::PointLifetime;
+use primitives::{SamplePoint, SamplingMode, SpawnQueue};
+
+/// 1フレームを50ミリ秒、アニメーションを0.5秒(10フレーム)にした、手動モードのAppを作る
+fn headless_app() -> App {
+    HeadlessApp::new()
+        .frame_time(50)
+        .manual()
+        .animation_time(0.5)
+        .start()
+}
+
+/// 存在するポイント(生成中を含む)のスケール
+fn point_scales(app: &mut App) -> Vec<f32> {
+    app.world_mut()
+        .query_filtered::<&Transform, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|transform| transform.scale.x)
+        .collect()
+}
+
+/// 削除中のポイントのスケール
+fn despawning_scales(app: &mut App) -> Vec<f32> {
+    app.world_mut()
+        .query_filtered::<&Transform, (With<SamplingMode>, Without<SamplePoint>)>()
+        .iter(app.world())
+        .map(|transform| transform.scale.x)
+        .collect()
+}
+
+/// ポイントを生成して、アニメーションの間のスケールの最大値を返す
+fn spawn_and_track(app: &mut App) -> f32 {
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 20;
+    let mut max_scale: f32 = 0.0;
+    for _ in 0..15 {
+        app.update();
+        max_scale = point_scales(app).into_iter().fold(max_scale, f32::max);
+    }
+    max_scale
+}
+
+#[test]
+fn every_easing_starts_at_zero_and_ends_at_one() {
+    for easing in AnimationEasing::ALL {
+        assert!(easing.sample(0.0).abs() < 1e-6, "{easing:?}");
+        assert!((easing.sample(1.0) - 1.0).abs() < 1e-6, "{easing:?}");
+        // 範囲外の進行度は端の値にする
+        assert!((easing.sample(1.5) - 1.0).abs() < 1e-6, "{easing:?}");
+    }
+    assert!((AnimationEasing::Linear.sample(0.3) - 0.3).abs() < 1e-6);
+    assert!(AnimationEasing::Cubic.sample(0.3) > 0.3);
+    // バックとエラスティックは途中で1を超える
+    let overshoot =
+        |easing: AnimationEasing| (1..100).any(|i| easing.sample(i as f32 / 100.0) > 1.0);
+    assert!(overshoot(AnimationEasing::Back));
+    assert!(overshoot(AnimationEasing::Elastic));
+    assert!(!overshoot(AnimationEasing::Linear));
+    assert!(!overshoot(AnimationEasing::Cubic));
+}
+
+#[test]
+fn b_key_cycles_easing() {
+    let mut app = headless_app();
+    assert_eq!(
+        *app.world().resource::<AnimationEasing>(),
+        AnimationEasing::Linear
+    );
+    for expected in [
+        AnimationEasing::Cubic,
+        AnimationEasing::Back,
+        AnimationEasing::Elastic,
+        AnimationEasing::Linear,
+    ] {
+        tap(&mut app, KeyCode::KeyB);
+        assert_eq!(*app.world().resource::<AnimationEasing>(), expected);
+    }
+}
+
+#[test]
+fn linear_spawning_never_exceeds_full_size() {
+    let mut app = headless_app();
+    let max_scale = spawn_and_track(&mut app);
+    assert!(max_scale <= 1.0);
+    assert!(point_scales(&mut app).iter().all(|scale| *scale == 1.0));
+}
+
+#[test]
+fn back_easing_overshoots_then_settles() {
+    let mut app = headless_app();
+    *app.world_mut().resource_mut::<AnimationEasing>() = AnimationEasing::Back;
+    let max_scale = spawn_and_track(&mut app);
+    assert!(max_scale > 1.0);
+    let scales = point_scales(&mut app);
+    assert!(!scales.is_empty());
+    assert!(scales.iter().all(|scale| (scale - 1.0).abs() < 1e-6));
+
+    // 寿命で削除すると、削除アニメーションでも逆向きに1を超えてから縮む
+    *app.world_mut().resource_mut::<PointLifetime>() = PointLifetime {
+        enabled: true,
+        seconds: 0.1,
+    };
+    let mut max_despawning: f32 = 0.0;
+    for _ in 0..20 {
+        app.update();
+        max_despawning = despawning_scales(&mut app)
+            .into_iter()
+            .fold(max_despawning, f32::max);
+    }
+    assert!(max_despawning > 1.0);
+    assert!(point_scales(&mut app).is_empty());
+    assert!(despawning_scales(&mut app).is_empty());
+}
@@ -0,0 +1,63 @@
+//! ポイントのPLY・CSVへの書き出しのテスト
+
+use bevy::prelude::*;
+use primitives::SamplingMode;
+use primitives::demo_config::PointColors;
+use primitives::export::{ExportedPoint, export_points, write_csv, write_ply};
+
+fn points() -> Vec<ExportedPoint> {
+    vec![
+        ExportedPoint {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            mode: SamplingMode::Interior,
+        },
+        ExportedPoint {
+            position: Vec3::new(-0.5, 0.0, 0.25),
+            mode: SamplingMode::Boundary,
+        },
+    ]
+}
+
+#[test]
+fn ply_has_header_and_one_line_per_point() {
+    let mut out = Vec::new();
+    write_ply(&points(), &PointColors::default(), &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+
+    let (header, body) = text.split_once("end_header\n").unwrap();
+    assert!(header.starts_with("ply\nformat ascii 1.0\n"));
+    assert!(header.contains("element vertex 2\n"));
+    assert!(header.contains("property uchar boundary\n"));
+
+    let rows: Vec<Vec<&str>> = body
+        .lines()
+        .map(|line| line.split(' ').collect())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(&rows[0][..3], ["1", "2", "3"]);
+    assert_eq!(rows[0].last(), Some(&"0"));
+    assert_eq!(&rows[1][..3], ["-0.5", "0", "0.25"]);
+    assert_eq!(rows[1].last(), Some(&"1"));
+}
+
+#[test]
+fn csv_lists_positions_and_kinds() {
+    let mut out = Vec::new();
+    write_csv(&points(), &mut out).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "x,y,z,kind\n1,2,3,interior\n-0.5,0,0.25,boundary\n"
+    );
+}
+
+#[test]
+fn export_writes_both_files() {
+    let dir = std::env::temp_dir().join(format!("primitives-export-{}", std::process::id()));
+    let [ply, csv] = export_points(&dir, "points", &points(), &PointColors::default()).unwrap();
+
+    assert_eq!(ply, dir.join("points.ply"));
+    assert!(std::fs::read_to_string(&ply).unwrap().contains("element vertex 2"));
+    assert_eq!(std::fs::read_to_string(&csv).unwrap().lines().count(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,77 @@
+//! 図形の中心のライトの光り方のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::demo_config::{DemoConfig, PointColors};
+use primitives::firefly_lighting::{FireflyConfig, FireflyLights, LightColorSource};
+use primitives::point_budget::PointBudget;
+use primitives::{PointCounter, SamplingMode, SamplingParams};
+use sample_common::QualityLevel;
+
+#[test]
+fn intensity_follows_the_point_count_up_to_the_boost() {
+    let config = FireflyConfig {
+        base_intensity: 1000.0,
+        max_boost: 1.5,
+        ..default()
+    };
+    assert_eq!(config.target_intensity(0, 100), 0.0);
+    assert_eq!(config.target_intensity(50, 100), 500.0);
+    assert_eq!(config.target_intensity(100, 100), 1000.0);
+    assert_eq!(config.target_intensity(1000, 100), 1500.0);
+    // 最大数が0でも落ちない
+    assert_eq!(config.target_intensity(10, 0), 1500.0);
+}
+
+#[test]
+fn light_color_is_taken_from_the_palette() {
+    let colors = PointColors::default();
+    let color = |source| {
+        FireflyConfig {
+            color: source,
+            ..default()
+        }
+        .light_color(&colors, SamplingMode::PoissonDisk)
+    };
+    assert_eq!(color(LightColorSource::Interior), colors.interior);
+    assert_eq!(color(LightColorSource::Boundary), colors.boundary);
+    assert_eq!(color(LightColorSource::SamplingMode), colors.poisson_disk);
+}
+
+#[test]
+fn lights_use_the_configured_response() {
+    // ポイントの数と最大数が変わらないようにする
+    let mut app = HeadlessApp::new().manual().start_with_config();
+    app.world_mut().resource_mut::<PointBudget>().enabled = false;
+    app.world_mut().resource_mut::<DemoConfig>().firefly = FireflyConfig {
+        base_intensity: 100.0,
+        lerp_rate: 1.0,
+        color: LightColorSource::Boundary,
+        ..default()
+    };
+    app.update();
+    app.update();
+
+    // 近づける割合が1なので、ポイント数から決めた強度にすぐなる
+    let world = app.world();
+    let config = world.resource::<DemoConfig>();
+    let limit = world.resource::<PointBudget>().point_limit(
+        world.resource::<SamplingParams>(),
+        *world.resource::<QualityLevel>(),
+    );
+    let expected = config
+        .firefly
+        .target_intensity(world.resource::<PointCounter>().0, limit);
+    let color = Color::LinearRgba(config.point_colors.boundary);
+    let mut lights = app
+        .world_mut()
+        .query_filtered::<&PointLight, With<FireflyLights>>();
+    let lights: Vec<&PointLight> = lights.iter(app.world()).collect();
+    assert!(!lights.is_empty());
+    for light in lights {
+        assert_eq!(light.color, color);
+        assert!((light.intensity - expected).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,73 @@
+//! 地面に落としたポイントの影のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn, tap};
+use primitives::ground_shadow::{GroundShadows, SHADOW_BRIGHTNESS, SHADOW_LIFT};
+use primitives::instancing::{PointInstance, PointInstances};
+use primitives::{GROUND_HEIGHT, SamplingMode};
+
+/// 描画用に集めたインスタンス
+fn instances(app: &mut App) -> Vec<PointInstance> {
+    app.world_mut()
+        .query::<&PointInstances>()
+        .single(app.world())
+        .unwrap()
+        .0
+        .clone()
+}
+
+/// 描画するポイントの数(生成中・削除中も含む)
+fn point_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query::<&SamplingMode>()
+        .iter(app.world())
+        .count()
+}
+
+/// 手動モードでポイントを生成したヘッドレスのAppを作る
+fn app_with_points() -> App {
+    let mut app = HeadlessApp::new().points(50).start();
+    app.update();
+    app
+}
+
+#[test]
+fn z_key_adds_a_dimmed_copy_on_the_ground() {
+    let mut app = app_with_points();
+    assert_eq!(instances(&mut app).len(), point_count(&mut app));
+
+    tap(&mut app, KeyCode::KeyZ);
+    assert!(app.world().resource::<GroundShadows>().enabled);
+    let all = instances(&mut app);
+    let count = point_count(&mut app);
+    assert_eq!(all.len(), count * 2);
+
+    // 影はポイントの真下の地面にあり、大きさ(アニメーションのスケール)は同じで暗い
+    let (points, shadows) = all.split_at(count);
+    for (point, shadow) in points.iter().zip(shadows) {
+        let [x, _, z, scale] = point.position_scale;
+        assert_eq!(
+            shadow.position_scale,
+            [x, GROUND_HEIGHT + SHADOW_LIFT, z, scale]
+        );
+        for channel in 0..3 {
+            let dimmed = point.color[channel] * SHADOW_BRIGHTNESS;
+            assert!((shadow.color[channel] - dimmed).abs() < 1e-6);
+        }
+        assert_eq!(shadow.color[3], point.color[3]);
+    }
+
+    tap(&mut app, KeyCode::KeyZ);
+    assert_eq!(instances(&mut app).len(), point_count(&mut app));
+}
+
+#[test]
+fn shadows_are_only_drawn_for_3d_shapes() {
+    let mut app = app_with_points();
+    tap(&mut app, KeyCode::KeyZ);
+    tap(&mut app, KeyCode::KeyV);
+    spawn(&mut app, 20);
+    assert_eq!(instances(&mut app).len(), point_count(&mut app));
+}
@@ -0,0 +1,51 @@
+//! 軸ごとのヒストグラムのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::histogram::{AxisHistogram, HISTOGRAM_BINS, axis_histogram};
+use primitives::{SamplePoint, ShapeIndex, SpawningMode};
+
+#[test]
+fn values_are_counted_in_their_bins() {
+    let values = [-1.0, -0.6, -0.1, 0.1, 0.2, 0.99, 1.0];
+    assert_eq!(axis_histogram(values.into_iter(), 1.0, 4), vec![2, 1, 2, 2]);
+
+    // 範囲外の値は端の区間に入る
+    assert_eq!(axis_histogram([-5.0, 5.0].into_iter(), 1.0, 2), vec![1, 1]);
+    // 幅のない軸は数えない
+    assert_eq!(axis_histogram([0.0].into_iter(), 0.0, 3), vec![0, 0, 0]);
+}
+
+#[test]
+fn key_shows_histogram_of_the_focused_shape() {
+    let mut app = HeadlessApp::new().build();
+    for _ in 0..20 {
+        app.update();
+    }
+    // 集計した後にポイントが増えないよう、生成を止めておく
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    app.update();
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyG);
+    app.update();
+
+    let histogram = app.world().resource::<AxisHistogram>();
+    assert!(histogram.enabled);
+    let shape = histogram.shape.expect("注視している図形が集計されている");
+    let counts = histogram.counts.clone();
+
+    let in_shape = app
+        .world_mut()
+        .query_filtered::<&ShapeIndex, With<SamplePoint>>()
+        .iter(app.world())
+        .filter(|index| index.0 == shape)
+        .count();
+    for axis in counts {
+        assert_eq!(axis.len(), HISTOGRAM_BINS);
+        assert_eq!(axis.iter().sum::<usize>(), in_shape);
+    }
+}
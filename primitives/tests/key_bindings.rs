@@ -0,0 +1,86 @@
+//! キーの割り当て(KeyBindings)のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::reflect::Struct;
+use common::{HeadlessApp, tap};
+use primitives::key_bindings::{ACTIONS, KeyBindings, key_label};
+use primitives::{SampleAction, SamplingMode};
+use sample_common::LocalizedText;
+use sample_common::locale::TextArg;
+
+/// 操作説明の文章に埋め込んだ値
+fn help_arg(app: &mut App, name: &str) -> Option<String> {
+    let mut texts = app.world_mut().query::<&LocalizedText>();
+    texts
+        .iter(app.world())
+        .filter(|text| text.key == "help")
+        .flat_map(|text| text.args.iter())
+        .find_map(|(arg, value)| match value {
+            TextArg::Literal(value) if *arg == name => Some(value.clone()),
+            _ => None,
+        })
+}
+
+#[test]
+fn bundled_bindings_match_the_defaults() {
+    let source = include_str!("../assets/config/primitives.keys.config.ron");
+    let bindings: KeyBindings = bevy::asset::ron::from_str(source).unwrap();
+    assert_eq!(bindings, KeyBindings::default());
+}
+
+#[test]
+fn every_field_is_an_action() {
+    let bindings = KeyBindings::default();
+    assert_eq!(bindings.field_len(), ACTIONS.len());
+    for (action, name) in ACTIONS {
+        assert!(bindings.field(name).is_some(), "{name}");
+        assert!(!bindings.keys(action).is_empty(), "{name}");
+    }
+    assert!(bindings.conflicts().is_empty());
+}
+
+#[test]
+fn labels_are_short_key_names() {
+    let bindings = KeyBindings::default();
+    assert_eq!(bindings.label(SampleAction::Reset), "R");
+    assert_eq!(bindings.label(SampleAction::CameraFront), "1");
+    assert_eq!(bindings.label(SampleAction::ToggleHelp), "Tab");
+    // テンキーとメインの「-」は同じ名前なので1つにまとめる
+    assert_eq!(bindings.label(SampleAction::ZoomOut), "-");
+    assert_eq!(key_label(KeyCode::BracketLeft), "[");
+    assert_eq!(key_label(KeyCode::F3), "F3");
+}
+
+#[test]
+fn partial_config_keeps_the_other_defaults() {
+    let bindings: KeyBindings =
+        bevy::asset::ron::from_str("(spawn_one: [KeyZ], toggle_help: [F1, KeyQ])").unwrap();
+    assert_eq!(bindings.keys(SampleAction::SpawnOne), [KeyCode::KeyZ]);
+    assert_eq!(bindings.label(SampleAction::ToggleHelp), "F1/Q");
+    assert_eq!(bindings.reset, KeyBindings::default().reset);
+
+    // 既定のキーと重なると、重なったキーを報告する
+    let bindings: KeyBindings = bevy::asset::ron::from_str("(spawn_one: [KeyR])").unwrap();
+    assert_eq!(bindings.conflicts(), [KeyCode::KeyR]);
+}
+
+#[test]
+fn remapped_keys_drive_the_actions_and_the_help_text() {
+    let mut app = HeadlessApp::new().start();
+    assert_eq!(help_arg(&mut app, "toggle_sampling").as_deref(), Some("M"));
+
+    app.world_mut()
+        .resource_mut::<KeyBindings>()
+        .toggle_sampling = vec![KeyCode::KeyX];
+    app.update();
+    assert_eq!(help_arg(&mut app, "toggle_sampling").as_deref(), Some("X"));
+
+    // 元のキーでは切り替わらず、新しいキーで切り替わる
+    let before = *app.world().resource::<SamplingMode>();
+    tap(&mut app, KeyCode::KeyM);
+    assert_eq!(*app.world().resource::<SamplingMode>(), before);
+    tap(&mut app, KeyCode::KeyX);
+    assert_ne!(*app.world().resource::<SamplingMode>(), before);
+}
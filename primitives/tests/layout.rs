@@ -0,0 +1,107 @@
+//! 図形の並べ方(横一列・格子・円周)のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::layout::LayoutMode;
+use primitives::{SamplePoint, ShapeRoot, SpawnQueue, ViewMode};
+
+/// 図形のエンティティの位置(ShapeRootのインデックスの順)
+fn root_positions(app: &mut App) -> Vec<Vec3> {
+    let mut roots: Vec<(usize, Vec3)> = app
+        .world_mut()
+        .query::<(&ShapeRoot, &Transform)>()
+        .iter(app.world())
+        .map(|(root, transform)| (root.0, transform.translation))
+        .collect();
+    roots.sort_by_key(|(index, _)| *index);
+    roots.into_iter().map(|(_, position)| position).collect()
+}
+
+#[test]
+fn layouts_keep_neighbors_apart_and_stay_compact() {
+    let count = 10;
+    for view in [ViewMode::ThreeD, ViewMode::TwoD] {
+        let row = LayoutMode::Row.positions(count, view);
+        assert_eq!(row[0], Vec3::new(-10.0, 0.0, 0.0));
+        assert_eq!(row[9], Vec3::new(8.0, 0.0, 0.0));
+
+        for layout in [LayoutMode::Grid, LayoutMode::Ring] {
+            let positions = layout.positions(count, view);
+            assert_eq!(positions.len(), count);
+            // 図形同士は横一列の間隔より近づかない
+            for (i, a) in positions.iter().enumerate() {
+                for b in &positions[i + 1..] {
+                    assert!(a.distance(*b) > 2.0 - 1e-4, "{layout:?} {a} {b}");
+                }
+            }
+            // 横一列より狭い範囲に収まり、表示の平面から出ない
+            let width = positions.iter().map(|p| p.length()).fold(0.0, f32::max);
+            assert!(width < 5.0, "{layout:?} {width}");
+            for position in &positions {
+                match view {
+                    ViewMode::ThreeD => assert_eq!(position.y, 0.0),
+                    ViewMode::TwoD => assert_eq!(position.z, 0.0),
+                }
+            }
+        }
+    }
+
+    // 格子は正方形に近い行と列に、中央を基準にして並べる
+    let grid = LayoutMode::Grid.positions(9, ViewMode::ThreeD);
+    assert_eq!(grid[0], Vec3::new(-2.0, 0.0, -2.0));
+    assert_eq!(grid[4], Vec3::ZERO);
+    assert_eq!(grid[8], Vec3::new(2.0, 0.0, 2.0));
+    assert_eq!(
+        LayoutMode::Ring.positions(1, ViewMode::ThreeD),
+        [Vec3::ZERO]
+    );
+}
+
+#[test]
+fn f_key_moves_the_shapes_with_their_points() {
+    let mut app = HeadlessApp::new().manual().start();
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 100;
+    app.update();
+    let row = root_positions(&mut app);
+
+    tap(&mut app, KeyCode::KeyF);
+    assert_eq!(*app.world().resource::<LayoutMode>(), LayoutMode::Grid);
+    let grid = root_positions(&mut app);
+    assert_eq!(
+        grid,
+        LayoutMode::Grid.positions(grid.len(), ViewMode::ThreeD)
+    );
+    assert_ne!(grid, row);
+
+    // ポイントは図形の子なので、ワールド座標は新しい位置の図形の中心からの位置になる
+    let mut points = app
+        .world_mut()
+        .query_filtered::<(&Transform, &GlobalTransform, &ChildOf), With<SamplePoint>>();
+    let mut checked = 0;
+    for (local, global, parent) in points.iter(app.world()) {
+        let root = app.world().get::<Transform>(parent.parent()).unwrap();
+        let expected = root.transform_point(local.translation);
+        assert!(global.translation().abs_diff_eq(expected, 1e-4));
+        checked += 1;
+    }
+    assert!(checked > 0);
+
+    tap(&mut app, KeyCode::KeyF);
+    assert_eq!(*app.world().resource::<LayoutMode>(), LayoutMode::Ring);
+    tap(&mut app, KeyCode::KeyF);
+    assert_eq!(*app.world().resource::<LayoutMode>(), LayoutMode::Row);
+}
+
+#[test]
+fn switching_view_keeps_the_layout() {
+    let mut app = HeadlessApp::new().manual().start();
+    tap(&mut app, KeyCode::KeyF);
+    tap(&mut app, KeyCode::KeyV);
+    let positions = root_positions(&mut app);
+    assert_eq!(
+        positions,
+        LayoutMode::Grid.positions(positions.len(), ViewMode::TwoD)
+    );
+}
@@ -0,0 +1,116 @@
+//! ポイントの寿命による削除と色の明るさのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::lifetime::{Lifetime, PointLifetime};
+use primitives::{PointCounter, SamplePoint, SpawnQueue};
+
+/// 存在するポイントの数
+fn point_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+/// 寿命の付いたエンティティの数(削除中のポイントも含む)
+fn lifetime_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query::<&Lifetime>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn points_are_removed_after_their_lifetime() {
+    let mut app = HeadlessApp::new().frame_time(50).points(30).start();
+    let spawned = point_count(&mut app);
+    assert!(spawned > 0);
+    assert!(!app.world().resource::<PointLifetime>().enabled);
+    assert_eq!(lifetime_count(&mut app), 0);
+
+    app.world_mut().resource_mut::<PointLifetime>().seconds = 1.0;
+    tap(&mut app, KeyCode::KeyT);
+    assert!(app.world().resource::<PointLifetime>().enabled);
+    assert_eq!(lifetime_count(&mut app), spawned);
+
+    // 寿命の途中ではまだ残っている
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(point_count(&mut app), spawned);
+
+    // 1秒(20フレーム)たつと、すべて削除アニメーションに移る
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(point_count(&mut app), 0);
+    assert_eq!(app.world().resource::<PointCounter>().0, 0);
+
+    // 後から生成したポイントにも寿命が付く
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 10;
+    app.update();
+    app.update();
+    let respawned = point_count(&mut app);
+    assert!(respawned > 0);
+    let ages: Vec<f32> = app
+        .world_mut()
+        .query_filtered::<&Lifetime, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|lifetime| lifetime.age)
+        .collect();
+    assert_eq!(ages.len(), respawned);
+    assert!(ages.iter().all(|age| *age < 0.5));
+}
+
+#[test]
+fn lifetime_can_be_changed_at_runtime() {
+    let mut app = HeadlessApp::new().frame_time(50).points(30).start();
+    let spawned = point_count(&mut app);
+    *app.world_mut().resource_mut::<PointLifetime>() = PointLifetime {
+        enabled: true,
+        seconds: 10.0,
+    };
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(point_count(&mut app), spawned);
+
+    // 寿命を短くすると、すでに経過したポイントはすぐに削除される
+    app.world_mut().resource_mut::<PointLifetime>().seconds = 0.5;
+    app.update();
+    app.update();
+    assert_eq!(point_count(&mut app), 0);
+}
+
+#[test]
+fn disabling_removes_lifetimes_and_keeps_points() {
+    let mut app = HeadlessApp::new().frame_time(50).points(30).start();
+    let spawned = point_count(&mut app);
+    app.world_mut().resource_mut::<PointLifetime>().seconds = 1.0;
+    tap(&mut app, KeyCode::KeyT);
+    assert_eq!(lifetime_count(&mut app), spawned);
+
+    tap(&mut app, KeyCode::KeyT);
+    assert_eq!(lifetime_count(&mut app), 0);
+    for _ in 0..40 {
+        app.update();
+    }
+    assert_eq!(point_count(&mut app), spawned);
+}
+
+#[test]
+fn brightness_fades_over_the_second_half() {
+    let lifetime = PointLifetime {
+        enabled: true,
+        seconds: 4.0,
+    };
+    let brightness = |age| lifetime.brightness(&Lifetime { age });
+    assert_eq!(brightness(0.0), 1.0);
+    assert_eq!(brightness(2.0), 1.0);
+    assert!((brightness(3.0) - 0.5).abs() < 1e-6);
+    assert_eq!(brightness(4.0), 0.0);
+    assert_eq!(brightness(5.0), 0.0);
+}
@@ -0,0 +1,94 @@
+//! モンテカルロ法による体積・表面積の推定のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::ViewMode;
+use primitives::monte_carlo::{Estimate, MonteCarlo, SignedDistance};
+use primitives::sampling::{frustum_area, frustum_volume};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn signed_distance_is_negative_inside() {
+    let cone = Cone {
+        radius: 1.0,
+        height: 2.0,
+    };
+    assert!(cone.signed_distance(Vec3::new(0.0, -0.5, 0.0)) < 0.0);
+    assert!(cone.signed_distance(Vec3::new(0.9, 0.9, 0.0)) > 0.0);
+    assert!((cone.signed_distance(Vec3::new(0.0, -1.5, 0.0)) - 0.5).abs() < 1e-5);
+
+    let cuboid = Cuboid::new(2.0, 2.0, 2.0);
+    assert!((cuboid.signed_distance(Vec3::ZERO) + 1.0).abs() < 1e-5);
+    assert!((cuboid.signed_distance(Vec3::new(3.0, 0.0, 0.0)) - 2.0).abs() < 1e-5);
+
+    let triangle = Triangle2d::new(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0), Vec2::Y);
+    assert!(triangle.signed_distance(Vec3::new(0.0, 0.5, 0.0)) < 0.0);
+    assert!((triangle.signed_distance(Vec3::new(0.0, -0.5, 0.0)) - 0.5).abs() < 1e-5);
+}
+
+/// 図形の推定値が、正確な値と`tolerance`の割合以内で一致するか調べる
+fn assert_estimate(shape: &impl SignedDistance, volume: f32, area: f32, tolerance: f32) {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut estimate = Estimate::new(shape);
+    estimate.sample(shape, 400_000, &mut rng);
+
+    assert!(
+        (estimate.interior() - volume).abs() < tolerance * volume,
+        "volume {} != {volume}",
+        estimate.interior()
+    );
+    assert!(
+        (estimate.boundary() - area).abs() < tolerance * area,
+        "area {} != {area}",
+        estimate.boundary()
+    );
+}
+
+#[test]
+fn estimates_converge_to_exact_measures() {
+    let sphere = Sphere { radius: 0.75 };
+    assert_estimate(&sphere, sphere.volume(), sphere.area(), 0.05);
+
+    let torus = Torus {
+        minor_radius: 0.25,
+        major_radius: 0.6,
+    };
+    assert_estimate(&torus, torus.volume(), torus.area(), 0.05);
+
+    let frustum = ConicalFrustum {
+        radius_top: 0.5,
+        radius_bottom: 0.75,
+        height: 1.0,
+    };
+    assert_estimate(&frustum, frustum_volume(&frustum), frustum_area(&frustum), 0.05);
+
+    // 平面の図形は面積と周の長さになる
+    let circle = Circle::new(0.75);
+    assert_estimate(&circle, circle.area(), circle.perimeter(), 0.05);
+}
+
+#[test]
+fn key_starts_estimation_for_every_shape() {
+    let mut app = HeadlessApp::new().start();
+    assert!(!app.world().resource::<MonteCarlo>().enabled);
+
+    tap(&mut app, KeyCode::KeyI);
+
+    let monte_carlo = app.world().resource::<MonteCarlo>();
+    assert!(monte_carlo.enabled);
+    let shape_count = ViewMode::ThreeD
+        .measures(primitives::SamplingMode::Interior)
+        .len();
+    assert_eq!(monte_carlo.shapes.len(), shape_count);
+    let samples = monte_carlo.shapes[0].estimate.samples;
+    assert!(samples > 0);
+    assert!(
+        monte_carlo
+            .shapes
+            .iter()
+            .all(|shape| shape.estimate.samples == samples)
+    );
+}
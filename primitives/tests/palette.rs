@@ -0,0 +1,114 @@
+//! ポイントの色のパレットのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::demo_config::{DemoConfig, PointColors};
+use primitives::instancing::{PointInstance, PointInstances};
+use primitives::palette::{PinnedPalette, PointPalette};
+use primitives::{SamplePoint, SamplingMode, SpawnQueue};
+
+/// 描画用に集めたインスタンスの色(重複を除く)
+fn instance_colors(app: &mut App) -> Vec<[f32; 4]> {
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut clouds = app.world_mut().query::<&PointInstances>();
+    for instance in clouds
+        .iter(app.world())
+        .flat_map(|instances| instances.iter())
+    {
+        if !colors.contains(&instance.color) {
+            colors.push(instance.color);
+        }
+    }
+    colors
+}
+
+/// パレットで内部のポイントに付ける色
+fn interior_color(palette: PointPalette) -> [f32; 4] {
+    let colors = palette.colors(&PointColors::default());
+    PointInstance::new(Vec3::ZERO, 1.0, SamplingMode::Interior, &colors).color
+}
+
+#[test]
+fn palettes_cycle_and_differ() {
+    assert_eq!(PointPalette::default(), PointPalette::Config);
+    let mut palette = PointPalette::default();
+    for _ in 0..PointPalette::ALL.len() {
+        palette = palette.next();
+    }
+    assert_eq!(palette, PointPalette::Config);
+
+    // 最初のパレットは設定ファイルの色をそのまま使う
+    let config = PointColors::default();
+    assert_eq!(PointPalette::Config.colors(&config), config);
+    for palette in PointPalette::ALL {
+        let colors = palette.colors(&config);
+        assert_ne!(colors.interior, colors.boundary, "{palette:?}");
+        assert_ne!(colors.boundary, colors.poisson_disk, "{palette:?}");
+    }
+}
+
+#[test]
+fn p_key_recolors_existing_points() {
+    let mut app = HeadlessApp::new().points(20).start_with_config();
+    assert_eq!(
+        instance_colors(&mut app),
+        [interior_color(PointPalette::Config)]
+    );
+
+    tap(&mut app, KeyCode::KeyP);
+    assert_eq!(
+        *app.world().resource::<PointPalette>(),
+        PointPalette::Sunset
+    );
+    assert_eq!(
+        instance_colors(&mut app),
+        [interior_color(PointPalette::Sunset)]
+    );
+}
+
+#[test]
+fn existing_points_can_keep_their_palette() {
+    let mut app = HeadlessApp::new().points(20).start_with_config();
+    app.world_mut()
+        .resource_mut::<DemoConfig>()
+        .recolor_existing_points = false;
+
+    let points = app
+        .world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count();
+    tap(&mut app, KeyCode::KeyP);
+    let pinned = app
+        .world_mut()
+        .query_filtered::<&PinnedPalette, With<SamplePoint>>()
+        .iter(app.world())
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(pinned.len(), points);
+    assert!(
+        pinned
+            .iter()
+            .all(|palette| palette.0 == PointPalette::Config)
+    );
+
+    // 新しいポイントだけが今のパレットの色になる
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 5;
+    app.update();
+    let colors = instance_colors(&mut app);
+    assert_eq!(colors.len(), 2);
+    assert!(colors.contains(&interior_color(PointPalette::Config)));
+    assert!(colors.contains(&interior_color(PointPalette::Sunset)));
+
+    // 色を変える設定に戻して切り替えると、すべてのポイントが今のパレットの色になる
+    app.world_mut()
+        .resource_mut::<DemoConfig>()
+        .recolor_existing_points = true;
+    tap(&mut app, KeyCode::KeyP);
+    assert_eq!(
+        instance_colors(&mut app),
+        [interior_color(PointPalette::Ocean)]
+    );
+}
@@ -0,0 +1,98 @@
+//! 大量に予約したポイントの位置を並列に計算するテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::parallel_sampling::{PARALLEL_THRESHOLD, ParallelSampling};
+use primitives::{
+    MAX_SPAWNS_PER_FRAME, PointCounter, SamplePoint, SamplingMode, ShapeIndex, SpawnQueue,
+};
+
+/// 予約したポイントがすべて生成されるまでに回す最大のフレーム数
+const MAX_FRAMES: usize = 10_000;
+
+/// 現在シーンに存在するポイントの数を数える
+fn sample_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+/// 生成キューが空になるまでフレームを回し、1フレームで増えたポイントの最大数を返す
+fn run_until_spawned(app: &mut App) -> usize {
+    let mut most = 0;
+    for _ in 0..MAX_FRAMES {
+        if app.world().resource::<SpawnQueue>().0 == 0 {
+            return most;
+        }
+        let before = sample_count(app);
+        app.update();
+        most = most.max(sample_count(app) - before);
+    }
+    panic!("予約したポイントが生成されない");
+}
+
+/// 生成した順番の、ポイントの図形と位置
+fn samples(app: &mut App) -> Vec<(usize, Vec3, SamplingMode)> {
+    let mut points: Vec<(Entity, usize, Vec3, SamplingMode)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &ShapeIndex, &Transform, &SamplingMode), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(entity, index, transform, mode)| (entity, index.0, transform.translation, *mode))
+        .collect();
+    points.sort_by_key(|(entity, ..)| *entity);
+    points
+        .into_iter()
+        .map(|(_, index, position, mode)| (index, position, mode))
+        .collect()
+}
+
+#[test]
+fn large_queue_is_spawned_over_several_frames() {
+    let mut app = HeadlessApp::new().seed(1).manual().start();
+    let before = sample_count(&mut app);
+    let amount = PARALLEL_THRESHOLD * 5;
+    app.world_mut().resource_mut::<SpawnQueue>().0 = amount;
+    app.update();
+    // 計算をタスクに渡したフレームでは生成せず、予約した数はキューに残る
+    assert_eq!(sample_count(&mut app), before);
+    assert_eq!(app.world().resource::<ParallelSampling>().pending(), amount);
+    assert_eq!(app.world().resource::<SpawnQueue>().0, amount);
+
+    assert!(run_until_spawned(&mut app) <= MAX_SPAWNS_PER_FRAME);
+    assert_eq!(sample_count(&mut app), before + amount);
+    assert_eq!(app.world().resource::<PointCounter>().0, before + amount);
+    assert_eq!(app.world().resource::<ParallelSampling>().pending(), 0);
+}
+
+#[test]
+fn same_seed_spawns_the_same_points() {
+    let mut first = HeadlessApp::new().seed(42).manual().start();
+    let mut second = HeadlessApp::new().seed(42).manual().start();
+    for app in [&mut first, &mut second] {
+        app.world_mut().resource_mut::<SpawnQueue>().0 = PARALLEL_THRESHOLD * 3;
+        run_until_spawned(app);
+    }
+    let points = samples(&mut first);
+    assert!(points.len() >= PARALLEL_THRESHOLD * 3);
+    assert_eq!(points, samples(&mut second));
+}
+
+#[test]
+fn changing_the_mode_samples_the_queue_again() {
+    let mut app = HeadlessApp::new().seed(3).manual().start();
+    app.world_mut().resource_mut::<SpawnQueue>().0 = PARALLEL_THRESHOLD * 3;
+    app.update();
+    assert!(app.world().resource::<ParallelSampling>().pending() > 0);
+
+    // 内部で計算していたバッチは捨てて、境界で計算し直す
+    *app.world_mut().resource_mut::<SamplingMode>() = SamplingMode::Boundary;
+    run_until_spawned(&mut app);
+    let boundary = samples(&mut app)
+        .iter()
+        .filter(|(_, _, mode)| *mode == SamplingMode::Boundary)
+        .count();
+    assert_eq!(boundary, PARALLEL_THRESHOLD * 3);
+}
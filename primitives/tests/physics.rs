@@ -0,0 +1,77 @@
+//! ポイントが図形から離れて地面に落ちる物理モードのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::physics::{FallingPoint, PointPhysics};
+use primitives::{GROUND_HEIGHT, PointCounter, SamplePoint, SpawnQueue};
+
+fn run_frames(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// 存在するポイントの数
+fn point_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+/// 落ちているポイントのワールド座標での位置
+fn falling_positions(app: &mut App) -> Vec<Vec3> {
+    app.world_mut()
+        .query_filtered::<&FallingPoint, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|falling| falling.position)
+        .collect()
+}
+
+#[test]
+fn points_stay_on_their_shapes_by_default() {
+    let mut app = HeadlessApp::new().frame_time(50).points(30).start();
+    assert!(!app.world().resource::<PointPhysics>().enabled);
+    let spawned = point_count(&mut app);
+    assert!(spawned > 0);
+
+    run_frames(&mut app, 60);
+    assert!(falling_positions(&mut app).is_empty());
+    assert_eq!(point_count(&mut app), spawned);
+}
+
+#[test]
+fn six_key_makes_points_fall_and_vanish_on_the_ground() {
+    let mut app = HeadlessApp::new().frame_time(50).points(30).start();
+    run_frames(&mut app, 30); // 生成アニメーション(1秒)を終わらせる
+    let spawned = point_count(&mut app);
+    assert!(spawned > 0);
+
+    tap(&mut app, KeyCode::Digit6);
+    assert!(app.world().resource::<PointPhysics>().enabled);
+    let start = falling_positions(&mut app);
+    assert_eq!(start.len(), spawned);
+
+    // 少し進めると、ポイントは下に落ちていて、地面より下には行かない
+    run_frames(&mut app, 5);
+    let fallen = falling_positions(&mut app);
+    assert_eq!(fallen.len(), spawned);
+    assert!(fallen.iter().all(|p| p.y >= GROUND_HEIGHT));
+    let mean_height =
+        |points: &[Vec3]| points.iter().map(|p| p.y).sum::<f32>() / points.len() as f32;
+    assert!(mean_height(&fallen) < mean_height(&start) - 0.1);
+
+    // 地面に着いたポイントは削除アニメーションに移り、数えなくなる
+    run_frames(&mut app, 120);
+    assert_eq!(point_count(&mut app), 0);
+    assert_eq!(app.world().resource::<PointCounter>().0, 0);
+
+    // 無効にすると、新しいポイントは落ちない
+    tap(&mut app, KeyCode::Digit6);
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 10;
+    run_frames(&mut app, 30);
+    assert!(point_count(&mut app) > 0);
+    assert!(falling_positions(&mut app).is_empty());
+}
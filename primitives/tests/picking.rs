@@ -0,0 +1,33 @@
+//! カーソルからのレイによるポイントの選択のテスト
+
+use bevy::prelude::*;
+use primitives::picking::{PICK_RADIUS, pick_along_ray};
+
+#[test]
+fn ray_picks_the_closest_point_it_hits() {
+    let ray = Ray3d::new(Vec3::new(0.0, 0.0, 5.0), Dir3::NEG_Z);
+    let near = Entity::from_raw(1);
+    let far = Entity::from_raw(2);
+    let off_ray = Entity::from_raw(3);
+    let behind = Entity::from_raw(4);
+    let points = [
+        (far, Vec3::new(0.0, 0.01, -1.0)),
+        (near, Vec3::new(0.02, 0.0, 1.0)),
+        (off_ray, Vec3::new(0.5, 0.0, 3.0)),
+        (behind, Vec3::new(0.0, 0.0, 6.0)),
+    ];
+
+    assert_eq!(pick_along_ray(ray, points.into_iter(), PICK_RADIUS), Some(near));
+    // 手前のポイントがなければ、奥のポイントが選ばれる
+    assert_eq!(
+        pick_along_ray(ray, points.into_iter().filter(|(entity, _)| *entity != near), PICK_RADIUS),
+        Some(far)
+    );
+}
+
+#[test]
+fn ray_misses_when_no_point_is_close() {
+    let ray = Ray3d::new(Vec3::ZERO, Dir3::X);
+    let points = [(Entity::from_raw(1), Vec3::new(2.0, PICK_RADIUS * 2.0, 0.0))];
+    assert_eq!(pick_along_ray(ray, points.into_iter(), PICK_RADIUS), None);
+}
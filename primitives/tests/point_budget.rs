@@ -0,0 +1,91 @@
+//! フレーム時間に合わせたポイントの数の自動調整のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::point_budget::{BUDGET_DECREASE, MIN_BUDGET_SCALE, PointBudget};
+use primitives::{SamplePoint, SamplingParams, SpawnRate};
+use sample_common::QualityLevel;
+
+/// 現在シーンに存在するポイントの数を数える
+fn sample_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn slow_frames_lower_the_budget_and_fast_frames_raise_it() {
+    let mut budget = PointBudget {
+        target_fps: 60.0,
+        ..default()
+    };
+
+    budget.adjust(30.0);
+    assert_eq!(budget.scale, BUDGET_DECREASE);
+    assert_eq!(budget.fps, Some(30.0));
+
+    // 目標に近ければ変えない
+    budget.adjust(58.0);
+    assert_eq!(budget.scale, BUDGET_DECREASE);
+
+    // 余裕があれば少しずつ戻し、1を超えない
+    budget.adjust(120.0);
+    assert!(budget.scale > BUDGET_DECREASE);
+    for _ in 0..100 {
+        budget.adjust(120.0);
+    }
+    assert_eq!(budget.scale, 1.0);
+
+    // 下限より少なくはしない
+    for _ in 0..100 {
+        budget.adjust(5.0);
+    }
+    assert_eq!(budget.scale, MIN_BUDGET_SCALE);
+}
+
+#[test]
+fn budget_scales_the_limit_and_the_spawn_rate() {
+    let params = SamplingParams {
+        max_points: 2000,
+        ..default()
+    };
+    let rate = SpawnRate {
+        points_per_frame: 4,
+        user_set: false,
+    };
+    let mut budget = PointBudget {
+        scale: 0.25,
+        ..default()
+    };
+    assert_eq!(budget.point_limit(&params, QualityLevel::High), 500);
+    assert_eq!(budget.points_per_frame(&rate), 1.0);
+    // キーや操作パネルで選んだ生成数には倍率をかけない
+    assert_eq!(
+        budget.points_per_frame(&SpawnRate {
+            user_set: true,
+            ..rate
+        }),
+        4.0
+    );
+
+    // 自動調整をやめると設定の値に戻る
+    budget.enabled = false;
+    assert_eq!(budget.point_limit(&params, QualityLevel::High), 2000);
+    assert_eq!(budget.points_per_frame(&rate), 4.0);
+}
+
+#[test]
+fn lowered_budget_spawns_fewer_points_per_frame() {
+    let mut app = HeadlessApp::new().build();
+    app.world_mut().resource_mut::<PointBudget>().scale = 0.5;
+    for _ in 0..10 {
+        app.update();
+    }
+
+    // 1フレームの生成数の半分になる(端数は次のフレームに持ち越す)
+    let per_frame = app.world().resource::<SpawnRate>().points_per_frame;
+    assert_eq!(sample_count(&mut app), 10 * per_frame / 2);
+}
@@ -0,0 +1,65 @@
+//! ポイントの表示の大きさの倍率のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::instancing::PointInstances;
+use primitives::{POINT_SIZE_RANGE, SamplingParams};
+
+/// 描画用に集めたインスタンスのスケール
+fn instance_scales(app: &mut App) -> Vec<f32> {
+    app.world_mut()
+        .query::<&PointInstances>()
+        .iter(app.world())
+        .flat_map(|instances| instances.iter().map(|instance| instance.position_scale[3]))
+        .collect()
+}
+
+/// 今の大きさの倍率
+fn point_size(app: &App) -> f32 {
+    app.world().resource::<SamplingParams>().point_size
+}
+
+#[test]
+fn keys_scale_the_point_instances() {
+    let mut app = HeadlessApp::new()
+        .frame_time(50)
+        .animation_time(0.1)
+        .points(20)
+        .start();
+    // 生成アニメーションを終わらせる
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_eq!(point_size(&app), 1.0);
+    let scales = instance_scales(&mut app);
+    assert!(!scales.is_empty());
+    assert!(scales.iter().all(|scale| *scale == 1.0));
+
+    tap(&mut app, KeyCode::Digit0);
+    assert!(point_size(&app) > 1.0);
+    let grown = point_size(&app);
+    assert!(
+        instance_scales(&mut app)
+            .iter()
+            .all(|scale| (scale - grown).abs() < 1e-6)
+    );
+
+    // 小さくすると元の大きさに戻る
+    tap(&mut app, KeyCode::Digit9);
+    assert!((point_size(&app) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn point_size_stays_in_range() {
+    let mut params = SamplingParams::default();
+    for _ in 0..50 {
+        params.resize_points(2.0);
+    }
+    assert_eq!(params.point_size, *POINT_SIZE_RANGE.end());
+    for _ in 0..50 {
+        params.resize_points(0.5);
+    }
+    assert_eq!(params.point_size, *POINT_SIZE_RANGE.start());
+}
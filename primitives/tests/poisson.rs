@@ -0,0 +1,60 @@
+//! ポアソンディスクサンプリングのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::poisson::PoissonGrid;
+use primitives::{SamplePoint, SamplingMode, SamplingParams, ShapeIndex, SpawnQueue, SpawningMode};
+
+#[test]
+fn grid_rejects_close_candidates() {
+    let mut grid = PoissonGrid::new(0.5);
+    assert!(grid.is_far_enough(Vec3::ZERO));
+
+    grid.insert(Vec3::ZERO);
+    assert!(!grid.is_far_enough(Vec3::new(0.3, 0.0, 0.0)));
+    // 隣のセルに入る点も調べる
+    assert!(!grid.is_far_enough(Vec3::new(-0.2, -0.2, -0.2)));
+    assert!(grid.is_far_enough(Vec3::new(0.5, 0.0, 0.0)));
+    assert!(grid.is_far_enough(Vec3::new(-3.0, 2.0, 1.0)));
+}
+
+#[test]
+fn poisson_disk_points_keep_their_distance() {
+    let mut app = HeadlessApp::new().start(); // Startupでリソースを準備する
+
+    // 既存のポイントを消してから、ポアソンディスクで数フレームかけて生成する
+    let samples: Vec<Entity> = app
+        .world_mut()
+        .query_filtered::<Entity, With<SamplePoint>>()
+        .iter(app.world())
+        .collect();
+    for entity in samples {
+        app.world_mut().despawn(entity);
+    }
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    *app.world_mut().resource_mut::<SamplingMode>() = SamplingMode::PoissonDisk;
+    let radius = app.world().resource::<SamplingParams>().poisson_radius;
+    for _ in 0..3 {
+        app.world_mut().resource_mut::<SpawnQueue>().0 = 300;
+        app.update();
+    }
+
+    let points: Vec<(Vec3, usize)> = app
+        .world_mut()
+        .query_filtered::<(&Transform, &ShapeIndex), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(transform, index)| (transform.translation, index.0))
+        .collect();
+    assert!(!points.is_empty());
+
+    // 同じ図形の中のポイントは、どの2つも最小距離以上離れている
+    for (i, (a, shape_a)) in points.iter().enumerate() {
+        for (b, shape_b) in &points[i + 1..] {
+            if shape_a == shape_b {
+                assert!(a.distance(*b) >= radius - 1e-5);
+            }
+        }
+    }
+}
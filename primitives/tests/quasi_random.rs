@@ -0,0 +1,104 @@
+//! 準乱数(Halton列)によるサンプリングのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::quasi_random::{QuasiRandom, RandomGenerator, radical_inverse};
+use primitives::{SamplePoint, SpawnQueue};
+use rand::Rng;
+
+/// 生成した順番の、ポイントの位置
+fn positions(app: &mut App) -> Vec<Vec3> {
+    let mut points: Vec<(Entity, Vec3)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+    points.sort_by_key(|(entity, _)| *entity);
+    points.into_iter().map(|(_, position)| position).collect()
+}
+
+#[test]
+fn radical_inverse_mirrors_the_digits() {
+    let base_2: Vec<f64> = (1..=4).map(|index| radical_inverse(index, 2)).collect();
+    assert_eq!(base_2, [0.5, 0.25, 0.75, 0.125]);
+    let base_3: Vec<f64> = (1..=4).map(|index| radical_inverse(index, 3)).collect();
+    for (value, expected) in base_3
+        .iter()
+        .zip([1.0 / 3.0, 2.0 / 3.0, 1.0 / 9.0, 4.0 / 9.0])
+    {
+        assert!((value - expected).abs() < 1e-12);
+    }
+    assert_eq!(radical_inverse(0, 5), 0.0);
+}
+
+#[test]
+fn quasi_random_points_fill_a_grid_evenly() {
+    // 2次元の点を4×4のマスに分けて数えると、どのマスもほぼ同じ数になる
+    let mut quasi = QuasiRandom::new(1);
+    let mut cells = [0usize; 16];
+    for _ in 0..1024 {
+        quasi.start_point();
+        let (x, y): (f64, f64) = (quasi.r#gen(), quasi.r#gen());
+        assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+        cells[(x * 4.0) as usize + 4 * (y * 4.0) as usize] += 1;
+    }
+    for count in cells {
+        assert!(count.abs_diff(64) <= 6, "{cells:?}");
+    }
+}
+
+#[test]
+fn seeds_and_splits_are_reproducible() {
+    let first = |seed: u64| {
+        let mut quasi = QuasiRandom::new(seed);
+        quasi.start_point();
+        quasi.next_f64()
+    };
+    assert_eq!(first(3), first(3));
+    assert_ne!(first(3), first(4));
+
+    // 切り出した列は、1つずつ進めたときと同じ点を返し、元の列は切り出した数だけ先に進む
+    let mut sequential = QuasiRandom::new(9);
+    let mut split = sequential.clone();
+    let mut batch = split.split(10);
+    for _ in 0..10 {
+        batch.start_point();
+        sequential.start_point();
+        assert_eq!(batch.next_f64(), sequential.next_f64());
+        assert_eq!(batch.next_f64(), sequential.next_f64());
+    }
+    split.start_point();
+    sequential.start_point();
+    assert_eq!(split.index(), sequential.index());
+    assert_eq!(split.next_f64(), sequential.next_f64());
+}
+
+#[test]
+fn q_key_switches_to_reproducible_quasi_random_points() {
+    let mut pseudo = HeadlessApp::new().seed(11).manual().start();
+    let mut first = HeadlessApp::new().seed(11).manual().start();
+    let mut second = HeadlessApp::new().seed(11).manual().start();
+    for app in [&mut first, &mut second] {
+        tap(app, KeyCode::KeyQ);
+        assert_eq!(
+            *app.world().resource::<RandomGenerator>(),
+            RandomGenerator::QuasiRandom
+        );
+    }
+    for app in [&mut pseudo, &mut first, &mut second] {
+        app.world_mut().resource_mut::<SpawnQueue>().0 = 200;
+        app.update();
+    }
+    let quasi = positions(&mut first);
+    assert_eq!(quasi, positions(&mut second));
+    assert_ne!(quasi, positions(&mut pseudo));
+
+    tap(&mut first, KeyCode::KeyQ);
+    assert_eq!(
+        *first.world().resource::<RandomGenerator>(),
+        RandomGenerator::PseudoRandom
+    );
+}
@@ -0,0 +1,104 @@
+//! サンプリングの記録と再生のテスト
+
+use bevy::prelude::*;
+use primitives::replay::{
+    RecordedEvent, Replay, ReplayEvent, ReplayState, read_recording, write_recording,
+};
+use primitives::{PrimitivesPlugin, SamplePoint, SamplingMode, SamplingParams, SpawningMode};
+
+fn events() -> Vec<RecordedEvent> {
+    vec![
+        RecordedEvent {
+            frame: 0,
+            event: ReplayEvent::Spawn {
+                id: 7,
+                shape: 2,
+                mode: SamplingMode::Boundary,
+                position: Vec3::new(1.5, -0.25, 3.0),
+            },
+        },
+        RecordedEvent {
+            frame: 4,
+            event: ReplayEvent::Despawn { id: 7 },
+        },
+    ]
+}
+
+#[test]
+fn recording_round_trips_through_csv() {
+    let mut out = Vec::new();
+    write_recording(&events(), &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(
+        text,
+        "frame,event,id,shape,kind,x,y,z\n0,spawn,7,2,boundary,1.5,-0.25,3\n4,despawn,7,,,,,\n"
+    );
+
+    assert_eq!(read_recording(text.as_bytes()).unwrap(), events());
+}
+
+#[test]
+fn broken_recording_is_rejected() {
+    assert!(read_recording("x,y,z\n".as_bytes()).is_err());
+    let text = "frame,event,id,shape,kind,x,y,z\n0,spawn,1,0,sideways,0,0,0\n";
+    assert!(read_recording(text.as_bytes()).is_err());
+}
+
+/// キーを押した状態で1フレーム進め、離す
+fn press(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(key);
+}
+
+/// 現在のポイントの位置(比べやすいように並べ替える)
+fn positions(app: &mut App) -> Vec<[u32; 3]> {
+    let mut positions: Vec<[u32; 3]> = app
+        .world_mut()
+        .query_filtered::<&Transform, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|transform| transform.translation.to_array().map(f32::to_bits))
+        .collect();
+    positions.sort();
+    positions
+}
+
+#[test]
+fn playback_reproduces_the_recorded_points() {
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::headless_plugins(None))
+        .add_plugins(PrimitivesPlugin);
+    app.finish();
+    app.cleanup();
+    let dir = std::env::temp_dir().join(format!("primitives-replay-{}", std::process::id()));
+    app.world_mut().resource_mut::<Replay>().dir = dir.clone();
+    // 上限を小さくして、記録中に削除も起きるようにする
+    app.world_mut().resource_mut::<SamplingParams>().max_points = 20;
+    for _ in 0..5 {
+        app.update();
+    }
+
+    press(&mut app, KeyCode::KeyK);
+    for _ in 0..20 {
+        app.update();
+    }
+    // 保存した後にポイントが変わらないよう、生成を止めてから保存する
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    app.update();
+    press(&mut app, KeyCode::KeyK);
+    let recorded = positions(&mut app);
+    let events = app.world().resource::<Replay>().events().to_vec();
+    assert!(events.iter().any(|recorded| matches!(recorded.event, ReplayEvent::Despawn { .. })));
+
+    press(&mut app, KeyCode::KeyL);
+    assert_eq!(app.world().resource::<Replay>().state, ReplayState::Playing);
+    while app.world().resource::<Replay>().state == ReplayState::Playing {
+        app.update();
+    }
+    assert_eq!(positions(&mut app), recorded);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,89 @@
+//! トーラス・円錐・円錐台のサンプリングのテスト
+
+use bevy::prelude::*;
+use primitives::sampling::ExtraShapeSample;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// 判定の誤差の許容値
+const EPSILON: f32 = 1e-4;
+
+/// 1つの図形から取るサンプル数
+const SAMPLES: usize = 2000;
+
+fn rng() -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(42)
+}
+
+/// Y軸からの距離
+fn radial(point: Vec3) -> f32 {
+    point.xz().length()
+}
+
+#[test]
+fn torus_samples_are_inside_and_on_the_tube() {
+    let torus = Torus {
+        minor_radius: 0.25,
+        major_radius: 0.6,
+    };
+    let mut rng = rng();
+    // 管の中心の円からの距離
+    let tube_distance = |point: Vec3| Vec2::new(radial(point) - 0.6, point.y).length();
+
+    for _ in 0..SAMPLES {
+        assert!(tube_distance(torus.sample_interior(&mut rng)) <= 0.25 + EPSILON);
+        assert!((tube_distance(torus.sample_boundary(&mut rng)) - 0.25).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn cone_samples_are_inside_and_on_the_surface() {
+    let cone = Cone {
+        radius: 0.5,
+        height: 2.0,
+    };
+    let mut rng = rng();
+    // 高さyでの断面の半径(先端がy=1、底面がy=-1)
+    let radius_at = |y: f32| 0.5 * (1.0 - y) / 2.0;
+
+    let mut sum_y = 0.0;
+    for _ in 0..SAMPLES {
+        let point = cone.sample_interior(&mut rng);
+        assert!(point.y.abs() <= 1.0 + EPSILON);
+        assert!(radial(point) <= radius_at(point.y) + EPSILON);
+        sum_y += point.y;
+
+        let point = cone.sample_boundary(&mut rng);
+        let on_base = (point.y + 1.0).abs() < EPSILON && radial(point) <= 0.5 + EPSILON;
+        let on_side = (radial(point) - radius_at(point.y)).abs() < EPSILON;
+        assert!(on_base || on_side, "{point} is not on the cone");
+    }
+
+    // 円錐の重心は底面から高さの1/4の位置にある(体積に対して一様なら平均もそこに近づく)
+    let mean_y = sum_y / SAMPLES as f32;
+    assert!((mean_y - -0.5).abs() < 0.05, "mean y = {mean_y}");
+}
+
+#[test]
+fn frustum_samples_are_inside_and_on_the_surface() {
+    let frustum = ConicalFrustum {
+        radius_top: 0.25,
+        radius_bottom: 0.75,
+        height: 1.0,
+    };
+    let mut rng = rng();
+    // 高さyでの断面の半径(上面がy=0.5、下面がy=-0.5)
+    let radius_at = |y: f32| 0.75 - 0.5 * (y + 0.5);
+
+    for _ in 0..SAMPLES {
+        let point = frustum.sample_interior(&mut rng);
+        assert!(point.y.abs() <= 0.5 + EPSILON);
+        assert!(radial(point) <= radius_at(point.y) + EPSILON);
+
+        let point = frustum.sample_boundary(&mut rng);
+        let on_cap = (point.y.abs() - 0.5).abs() < EPSILON
+            && radial(point) <= radius_at(point.y) + EPSILON;
+        let on_side = (radial(point) - radius_at(point.y)).abs() < EPSILON;
+        assert!(on_cap || on_side, "{point} is not on the frustum");
+    }
+}
@@ -0,0 +1,64 @@
+//! `--bench`でのサンプリングの速さの計測のテスト
+
+use bevy::prelude::*;
+use primitives::sampling_bench::{DEFAULT_BENCH_SAMPLES, SamplingBench, SamplingBenchReport};
+use primitives::{PrimitivesPlugin, RngSeed, SamplingMode};
+
+/// 図形の数(3D)・サンプリングモードの数・乱数生成器の数
+const SHAPES: usize = 9;
+const MODES: usize = 3;
+const GENERATORS: usize = 2;
+
+fn args(values: &[&str]) -> impl Iterator<Item = String> {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+#[test]
+fn bench_is_enabled_only_with_the_flag() {
+    assert_eq!(SamplingBench::from_args(args(&["--seed", "3"])), None);
+    assert_eq!(
+        SamplingBench::from_args(args(&["--bench"])),
+        Some(SamplingBench {
+            samples_per_shape: DEFAULT_BENCH_SAMPLES
+        })
+    );
+    assert_eq!(
+        SamplingBench::from_args(args(&["--bench-samples", "500", "--bench"])),
+        Some(SamplingBench {
+            samples_per_shape: 500
+        })
+    );
+}
+
+#[test]
+fn bench_measures_every_combination_and_exits() {
+    let mut app = App::new();
+    app.add_plugins(sample_common::headless::headless_plugins(None))
+        .insert_resource(RngSeed {
+            seed: 9,
+            reseed_on_reset: false,
+        })
+        .add_plugins(PrimitivesPlugin)
+        .insert_resource(SamplingBench {
+            samples_per_shape: 50,
+        });
+    app.finish();
+    app.cleanup();
+    app.update();
+
+    assert!(app.should_exit().is_some());
+    let report = app.world().resource::<SamplingBenchReport>();
+    assert_eq!(report.0.len(), SHAPES * MODES * GENERATORS);
+    for result in &report.0 {
+        match result.mode {
+            // 内部と境界は毎回1つの点をサンプリングする
+            SamplingMode::Interior | SamplingMode::Boundary => assert_eq!(result.samples, 50),
+            SamplingMode::PoissonDisk => assert!(result.samples > 0 && result.samples <= 50),
+        }
+        assert!(result.samples_per_second() > 0.0);
+    }
+}
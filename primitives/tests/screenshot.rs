@@ -0,0 +1,36 @@
+//! スクリーンショットの保存を知らせるメッセージのテスト
+
+mod common;
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::screenshot_notice::{NOTICE_TIME, ScreenshotNotice};
+use sample_common::screenshot::ScreenshotSaved;
+
+/// メッセージの表示方法
+fn notice_display(app: &mut App) -> Display {
+    app.world_mut()
+        .query_filtered::<&Node, With<ScreenshotNotice>>()
+        .single(app.world())
+        .unwrap()
+        .display
+}
+
+#[test]
+fn saved_screenshot_shows_notice_for_a_while() {
+    let mut app = HeadlessApp::new().frame_time(100).start();
+    assert_eq!(notice_display(&mut app), Display::None);
+
+    app.world_mut().send_event(ScreenshotSaved {
+        path: PathBuf::from("screenshots/primitives/test.png"),
+    });
+    app.update();
+    assert_eq!(notice_display(&mut app), Display::Flex);
+
+    for _ in 0..(NOTICE_TIME / 0.1) as usize + 2 {
+        app.update();
+    }
+    assert_eq!(notice_display(&mut app), Display::None);
+}
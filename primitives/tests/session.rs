@@ -0,0 +1,112 @@
+//! セッションの保存と読み込みのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn};
+use primitives::quasi_random::RandomGenerator;
+use primitives::session::{Session, SessionFile, SessionPoint, read_session, write_session};
+use primitives::{PointCounter, RngSeed, SamplePoint, SamplingMode, SpawningMode, ViewMode};
+
+/// シード値を固定した手動モードのヘッドレスのAppを作る(保存先は一時ディレクトリ)
+fn headless_app(name: &str) -> App {
+    let mut app = HeadlessApp::new().seed(5).build();
+    let dir =
+        std::env::temp_dir().join(format!("primitives-session-{name}-{}", std::process::id()));
+    app.world_mut().resource_mut::<SessionFile>().0 = dir.join("session.bin");
+    app.update();
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    app
+}
+
+/// Ctrlキーを押したまま、キーを1フレームだけ押す
+fn tap_with_ctrl(app: &mut App, key: KeyCode) {
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.press(KeyCode::ControlLeft);
+    keyboard.press(key);
+    app.update();
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release(key);
+    keyboard.release(KeyCode::ControlLeft);
+    app.update();
+}
+
+/// 現在のポイントの位置(比べやすいように並べ替える)
+fn positions(app: &mut App) -> Vec<[u32; 3]> {
+    let mut positions: Vec<[u32; 3]> = app
+        .world_mut()
+        .query_filtered::<&Transform, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|transform| transform.translation.to_array().map(f32::to_bits))
+        .collect();
+    positions.sort();
+    positions
+}
+
+#[test]
+fn sessions_round_trip_through_the_binary_format() {
+    let points = vec![
+        SessionPoint {
+            shape: 0,
+            mode: SamplingMode::Interior,
+            position: Vec3::new(0.25, -0.5, 1.0),
+        },
+        SessionPoint {
+            shape: 7,
+            mode: SamplingMode::PoissonDisk,
+            position: Vec3::new(-1.0, 0.0, 0.125),
+        },
+    ];
+    let seed = RngSeed {
+        seed: 42,
+        reseed_on_reset: true,
+    };
+    let session = Session::new(
+        ViewMode::TwoD,
+        seed.clone(),
+        RandomGenerator::QuasiRandom,
+        points.clone(),
+    );
+
+    let mut bytes = Vec::new();
+    write_session(&session, &mut bytes).unwrap();
+    let loaded = read_session(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.view, ViewMode::TwoD);
+    assert_eq!(loaded.seed, seed);
+    assert_eq!(loaded.generator(), RandomGenerator::QuasiRandom);
+    assert_eq!(loaded.points, points);
+
+    // 別の種類のファイルや途中で切れたファイルは読み込まない
+    assert!(read_session(&mut &b"NOTASESSION"[..]).is_err());
+    assert!(read_session(&mut &bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn ctrl_s_and_ctrl_l_restore_the_points_and_the_random_state() {
+    let mut app = headless_app("restore");
+    spawn(&mut app, 30);
+    let saved_count = app.world().resource::<PointCounter>().0;
+
+    // Ctrlキーを押している間は、Sキーでポイントを生成しない
+    tap_with_ctrl(&mut app, KeyCode::KeyS);
+    assert_eq!(app.world().resource::<PointCounter>().0, saved_count);
+    let saved = positions(&mut app);
+    let path = app.world().resource::<SessionFile>().0.clone();
+    assert!(path.exists());
+
+    spawn(&mut app, 20);
+    let continued = positions(&mut app);
+
+    tap_with_ctrl(&mut app, KeyCode::KeyL);
+    assert_eq!(positions(&mut app), saved);
+    assert_eq!(app.world().resource::<PointCounter>().0, saved.len());
+    assert_eq!(
+        *app.world().resource::<SpawningMode>(),
+        SpawningMode::Manual
+    );
+
+    // 乱数は保存したときの続きから進むので、同じ操作で同じポイントになる
+    spawn(&mut app, 20);
+    assert_eq!(positions(&mut app), continued);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,83 @@
+//! 図形ごとのポイント数とラベルのテスト
+
+mod common;
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::shape_labels::{ShapeLabel, ShapePointCounts};
+use primitives::{PointCounter, SamplePoint, ShapeIndex, ShapeRoot, SpawnQueue};
+use sample_common::LocalizedText;
+use sample_common::locale::TextArg;
+
+/// 図形ごとのポイントの数を、ポイントのエンティティから直接数える
+fn counted_points(app: &mut App) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+    let mut points = app
+        .world_mut()
+        .query_filtered::<&ShapeIndex, With<SamplePoint>>();
+    for index in points.iter(app.world()) {
+        *counts.entry(index.0).or_default() += 1;
+    }
+    counts
+}
+
+/// 図形の数だけのラベルがあり、それぞれ図形のポイント数を表示していることを確かめる
+fn assert_labels_match(app: &mut App) {
+    let expected = counted_points(app);
+    let shapes = app
+        .world_mut()
+        .query::<&ShapeRoot>()
+        .iter(app.world())
+        .count();
+    let mut labels = app.world_mut().query::<(&ShapeLabel, &LocalizedText)>();
+    let labels: Vec<_> = labels.iter(app.world()).collect();
+    assert_eq!(labels.len(), shapes);
+
+    let counts = app.world().resource::<ShapePointCounts>();
+    for (label, text) in labels {
+        let count = expected.get(&label.0).copied().unwrap_or_default();
+        assert_eq!(counts.get(label.0), count, "図形{}", label.0);
+        assert_eq!(text.key, "labels.shape");
+        let shown = text.args.iter().find_map(|(name, value)| match value {
+            TextArg::Literal(value) if *name == "count" => Some(value.clone()),
+            _ => None,
+        });
+        assert_eq!(shown, Some(count.to_string()), "図形{}", label.0);
+    }
+}
+
+#[test]
+fn counts_follow_spawns_and_despawns() {
+    let mut app = HeadlessApp::new().manual().start();
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 300;
+    app.update();
+    app.update();
+    assert_labels_match(&mut app);
+    let total = app.world().resource::<ShapePointCounts>().total();
+    assert!(total >= 300);
+    assert_eq!(total, app.world().resource::<PointCounter>().0);
+
+    // リスタートですべてのポイントが削除アニメーションに移ると、数からも引く
+    tap(&mut app, KeyCode::KeyR);
+    assert_eq!(app.world().resource::<ShapePointCounts>().total(), 0);
+    assert_labels_match(&mut app);
+}
+
+#[test]
+fn switching_view_rebuilds_the_labels() {
+    let mut app = HeadlessApp::new().manual().start();
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 100;
+    app.update();
+
+    tap(&mut app, KeyCode::KeyV);
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 50;
+    app.update();
+    app.update();
+    assert_labels_match(&mut app);
+    assert_eq!(
+        app.world().resource::<ShapePointCounts>().total(),
+        app.world().resource::<PointCounter>().0
+    );
+}
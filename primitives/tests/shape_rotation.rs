@@ -0,0 +1,100 @@
+//! 図形をポイントごと回転させるテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::shape_rotation::ShapeRotation;
+use primitives::spatial_index::PointSpatialIndex;
+use primitives::{SamplePoint, ShapeIndex, ShapeRoot, SpawnQueue};
+
+/// ポイントごとの、図形の中心からの位置とワールド座標
+fn point_positions(app: &mut App) -> Vec<(Entity, Vec3, Vec3)> {
+    let mut positions: Vec<(Entity, Vec3, Vec3)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform, &GlobalTransform), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(entity, local, global)| (entity, local.translation, global.translation()))
+        .collect();
+    positions.sort_by_key(|(entity, _, _)| *entity);
+    positions
+}
+
+#[test]
+fn points_are_children_of_their_shape() {
+    let mut app = HeadlessApp::new().frame_time(50).points(100).start();
+    let mut points = app
+        .world_mut()
+        .query_filtered::<(&ShapeIndex, &ChildOf), With<SamplePoint>>();
+    let parents: Vec<(usize, Entity)> = points
+        .iter(app.world())
+        .map(|(index, child_of)| (index.0, child_of.parent()))
+        .collect();
+    assert!(!parents.is_empty());
+    for (index, parent) in parents {
+        let root = app
+            .world()
+            .get::<ShapeRoot>(parent)
+            .expect("図形のエンティティの子");
+        assert_eq!(root.0, index);
+    }
+}
+
+#[test]
+fn rotating_shapes_carry_their_points() {
+    let mut app = HeadlessApp::new().frame_time(50).points(100).start();
+    assert!(!app.world().resource::<ShapeRotation>().enabled);
+    let before = point_positions(&mut app);
+    assert!(!before.is_empty());
+
+    tap(&mut app, KeyCode::KeyY);
+    assert!(app.world().resource::<ShapeRotation>().enabled);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    // 図形の中心からの位置はそのままで、ワールド座標だけが図形と一緒に動く
+    let after = point_positions(&mut app);
+    assert_eq!(before.len(), after.len());
+    let mut moved = 0;
+    for ((entity, local, world), (_, local_after, world_after)) in before.iter().zip(&after) {
+        assert_eq!(local, local_after);
+        // Y軸の周りに回すので、高さは変わらない
+        assert!((world.y - world_after.y).abs() < 1e-4);
+        if world.distance(*world_after) > 1e-3 {
+            moved += 1;
+        }
+        // 空間ハッシュも動いた位置を指す(伝播する前のフレームの位置なので、少しだけずれてよい)
+        let indexed = app
+            .world()
+            .resource::<PointSpatialIndex>()
+            .position(*entity)
+            .unwrap();
+        assert!(indexed.distance(*world_after) < 0.1);
+    }
+    assert!(moved > 0);
+
+    // 止めると、その向きのまま動かなくなる
+    tap(&mut app, KeyCode::KeyY);
+    let stopped = point_positions(&mut app);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(point_positions(&mut app), stopped);
+}
+
+#[test]
+fn two_d_shapes_rotate_in_the_screen_plane() {
+    let mut app = HeadlessApp::new().frame_time(50).points(100).start();
+    tap(&mut app, KeyCode::KeyV);
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 100;
+    app.update();
+    app.world_mut().resource_mut::<ShapeRotation>().enabled = true;
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let positions = point_positions(&mut app);
+    assert!(!positions.is_empty());
+    assert!(positions.iter().all(|(_, _, world)| world.z.abs() < 1e-4));
+}
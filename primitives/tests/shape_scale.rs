@@ -0,0 +1,99 @@
+//! 注視している図形の大きさの変更のテスト
+
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn, tap};
+use primitives::demo_config::DemoConfig;
+use primitives::{SamplePoint, ShapeIndex, ShapeRoot};
+
+/// 設定ファイルの読み込みが終わるまで更新を繰り返す(後から読み込まれた値で上書きされないようにする)
+fn wait_for_config(app: &mut App) {
+    for _ in 0..200 {
+        app.update();
+        if !app.world().resource::<Assets<DemoConfig>>().is_empty() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    app.update();
+    app.update();
+}
+
+/// 図形ごとのライトの届く範囲(SampledShapesのインデックス順)
+fn light_ranges(app: &mut App) -> Vec<f32> {
+    let mut ranges: Vec<(usize, f32)> = app
+        .world_mut()
+        .query::<(&ChildOf, &PointLight)>()
+        .iter(app.world())
+        .map(|(child_of, light)| {
+            let root = app.world().get::<ShapeRoot>(child_of.parent()).unwrap();
+            (root.0, light.range)
+        })
+        .collect();
+    ranges.sort_by_key(|(index, _)| *index);
+    ranges.into_iter().map(|(_, range)| range).collect()
+}
+
+/// 図形の中心から最も遠いポイントまでの距離と、ポイントの数
+fn farthest_point(app: &mut App, index: usize) -> (f32, usize) {
+    app.world_mut()
+        .query_filtered::<(&Transform, &ShapeIndex), With<SamplePoint>>()
+        .iter(app.world())
+        .filter(|(_, shape)| shape.0 == index)
+        .fold((0.0, 0), |(farthest, count), (transform, _)| {
+            (farthest.max(transform.translation.length()), count + 1)
+        })
+}
+
+#[test]
+fn growing_the_focused_shape_resamples_it_larger() {
+    let mut app = HeadlessApp::new().seed(3).points(900).start();
+    let before = light_ranges(&mut app);
+    let original: Vec<(f32, usize)> = (0..before.len())
+        .map(|index| farthest_point(&mut app, index))
+        .collect();
+
+    tap(&mut app, KeyCode::Digit8);
+    let after = light_ranges(&mut app);
+    let changed: Vec<usize> = (0..before.len())
+        .filter(|index| before[*index] != after[*index])
+        .collect();
+    let [index] = changed[..] else {
+        panic!("注視している図形だけが変わる: {changed:?}");
+    };
+    assert!((after[index] / before[index] - 1.25).abs() < 1e-5);
+    // 元の大きさでサンプリングしたポイントは消える
+    assert_eq!(farthest_point(&mut app, index).1, 0);
+
+    // 上限(2倍)まで大きくしてから生成すると、元の大きさのときより遠くまでポイントが置かれる
+    let (original, count) = original[index];
+    assert!(count > 0);
+    for _ in 0..4 {
+        tap(&mut app, KeyCode::Digit8);
+    }
+    assert!((light_ranges(&mut app)[index] / before[index] - 2.0).abs() < 1e-5);
+    spawn(&mut app, 900);
+    let (scaled, _) = farthest_point(&mut app, index);
+    assert!(scaled > original * 1.5, "{scaled} <= {original} * 1.5");
+}
+
+#[test]
+fn points_can_be_kept_when_scaling() {
+    let mut app = HeadlessApp::new().seed(3).points(900).start();
+    wait_for_config(&mut app);
+    app.world_mut()
+        .resource_mut::<DemoConfig>()
+        .clear_points_on_scale = false;
+    let counts: Vec<usize> = (0..9)
+        .map(|index| farthest_point(&mut app, index).1)
+        .collect();
+
+    tap(&mut app, KeyCode::Digit7);
+    let kept: Vec<usize> = (0..9)
+        .map(|index| farthest_point(&mut app, index).1)
+        .collect();
+    assert_eq!(kept, counts);
+}
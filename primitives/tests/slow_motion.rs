@@ -0,0 +1,70 @@
+//! ポイントの生成・削除の一時停止とスローモーションのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::SamplePoint;
+use primitives::demo_config::DemoConfig;
+use sample_common::{PauseState, TimeScale};
+
+/// キーを押した状態で1フレーム進め、離す
+fn press(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(key);
+}
+
+/// 1フレームあたりに生成されるポイント数(設定のデフォルト値)
+fn points_per_frame() -> usize {
+    DemoConfig::default().points_per_frame
+}
+
+/// 現在シーンに存在するポイントの数を数える
+fn sample_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn space_pauses_spawning() {
+    let mut app = HeadlessApp::new().build();
+    for _ in 0..5 {
+        app.update();
+    }
+    let before = sample_count(&mut app);
+
+    press(&mut app, KeyCode::Space);
+    assert_eq!(*app.world().resource::<PauseState>(), PauseState::Paused);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(sample_count(&mut app), before);
+
+    press(&mut app, KeyCode::Space);
+    assert_eq!(sample_count(&mut app), before + points_per_frame());
+}
+
+#[test]
+fn brackets_scale_the_spawning_speed() {
+    let mut app = HeadlessApp::new().build();
+    app.update();
+
+    press(&mut app, KeyCode::BracketLeft);
+    assert_eq!(*app.world().resource::<TimeScale>(), TimeScale(0.5));
+    let before = sample_count(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(sample_count(&mut app), before + 5 * points_per_frame());
+
+    press(&mut app, KeyCode::BracketRight);
+    press(&mut app, KeyCode::BracketRight);
+    assert_eq!(*app.world().resource::<TimeScale>(), TimeScale(2.0));
+}
@@ -0,0 +1,162 @@
+//! ポイントの近傍探索のための空間ハッシュのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::SamplePoint;
+use primitives::picking::{PICK_RADIUS, pick_along_ray};
+use primitives::spatial_index::PointSpatialIndex;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// 乱数で散らばせたポイント(いくつかのセルにまたがる範囲)
+fn scattered_points(count: u32) -> Vec<(Entity, Vec3)> {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    (0..count)
+        .map(|i| {
+            let position = Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen()) * 3.0 - 1.5;
+            (Entity::from_raw(i), position)
+        })
+        .collect()
+}
+
+/// すべてのポイントを入れた空間ハッシュ
+fn index_of(points: &[(Entity, Vec3)]) -> PointSpatialIndex {
+    let mut index = PointSpatialIndex::default();
+    for (entity, position) in points {
+        index.insert(*entity, *position);
+    }
+    index
+}
+
+/// すべてのポイントとの距離を測って、近い順に並べる
+fn brute_force_nearest(points: &[(Entity, Vec3)], position: Vec3) -> Vec<(Entity, f32)> {
+    let mut sorted: Vec<(Entity, f32)> = points
+        .iter()
+        .map(|(entity, other)| (*entity, other.distance(position)))
+        .collect();
+    sorted.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    sorted
+}
+
+#[test]
+fn k_nearest_matches_brute_force() {
+    let points = scattered_points(500);
+    let index = index_of(&points);
+    assert_eq!(index.len(), points.len());
+
+    // ポイントのない遠い位置からも探せる
+    let queries = [
+        Vec3::ZERO,
+        Vec3::new(0.7, -1.2, 0.3),
+        Vec3::new(5.0, 4.0, -6.0),
+    ];
+    for position in queries {
+        for k in [1, 5, 40] {
+            let expected = brute_force_nearest(&points, position);
+            let found = index.k_nearest(position, k);
+            assert_eq!(found.len(), k);
+            for ((_, distance), (_, expected)) in found.iter().zip(&expected) {
+                assert!((distance - expected).abs() < 1e-5, "{position} k={k}");
+            }
+        }
+    }
+    assert_eq!(
+        index.nearest(points[3].1).map(|(entity, _)| entity),
+        Some(points[3].0)
+    );
+    // ポイントの数より多く求めても、あるだけを返す
+    assert_eq!(index.k_nearest(Vec3::ZERO, 1000).len(), points.len());
+    assert!(PointSpatialIndex::default().nearest(Vec3::ZERO).is_none());
+}
+
+#[test]
+fn within_radius_finds_every_close_point() {
+    let points = scattered_points(500);
+    let index = index_of(&points);
+
+    for radius in [0.05, 0.3, 1.0] {
+        let mut found: Vec<Entity> = index
+            .within_radius(Vec3::new(0.2, 0.1, -0.4), radius)
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect();
+        found.sort();
+        let mut expected: Vec<Entity> = points
+            .iter()
+            .filter(|(_, position)| position.distance(Vec3::new(0.2, 0.1, -0.4)) <= radius)
+            .map(|(entity, _)| *entity)
+            .collect();
+        expected.sort();
+        assert_eq!(found, expected, "radius={radius}");
+    }
+}
+
+#[test]
+fn ray_cast_matches_checking_every_point() {
+    let points = scattered_points(2000);
+    let index = index_of(&points);
+    let mut rng = ChaCha8Rng::seed_from_u64(11);
+
+    // 図形の外のカメラから、ポイントのある範囲へ向けたレイ
+    for _ in 0..200 {
+        let origin = Vec3::new(rng.gen_range(-4.0..4.0), rng.gen_range(-4.0..4.0), 6.0);
+        let target = Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen()) * 3.0 - 1.5;
+        let ray = Ray3d::new(origin, Dir3::new(target - origin).unwrap());
+
+        let expected = pick_along_ray(ray, points.iter().copied(), PICK_RADIUS);
+        let found = index.cast_ray(ray, PICK_RADIUS).map(|(entity, _)| entity);
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn removed_points_are_no_longer_found() {
+    let points = scattered_points(100);
+    let mut index = index_of(&points);
+    for (entity, _) in &points[1..] {
+        assert!(index.remove(*entity).is_some());
+    }
+    assert_eq!(index.len(), 1);
+    assert_eq!(
+        index.k_nearest(points[50].1, 3),
+        vec![(points[0].0, points[0].1.distance(points[50].1))]
+    );
+
+    // 同じポイントを入れ直すと、新しい位置に移る
+    index.insert(points[0].0, Vec3::splat(10.0));
+    assert_eq!(index.len(), 1);
+    assert!(index.within_radius(points[0].1, 0.01).is_empty());
+    assert_eq!(index.position(points[0].0), Some(Vec3::splat(10.0)));
+}
+
+#[test]
+fn index_follows_spawned_and_despawned_points() {
+    let mut app = HeadlessApp::new().points(300).start();
+
+    let points: Vec<(Entity, Vec3)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &GlobalTransform), With<SamplePoint>>()
+        .iter(app.world())
+        .map(|(entity, transform)| (entity, transform.translation()))
+        .collect();
+    assert!(!points.is_empty());
+    {
+        let index = app.world().resource::<PointSpatialIndex>();
+        assert_eq!(index.len(), points.len());
+        for (entity, position) in &points {
+            assert_eq!(index.position(*entity), Some(*position));
+        }
+    }
+
+    // 削除したポイントは、次のフレームで空間ハッシュからも取り除かれる
+    for (entity, _) in points.iter().step_by(3) {
+        app.world_mut().despawn(*entity);
+    }
+    app.update();
+    let index = app.world().resource::<PointSpatialIndex>();
+    for (i, (entity, _)) in points.iter().enumerate() {
+        assert_eq!(index.position(*entity).is_some(), i % 3 != 0);
+    }
+}
@@ -0,0 +1,83 @@
+//! 1フレームあたりの生成数を実行中に変えるキーのテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::demo_config::DemoConfig;
+use primitives::point_budget::PointBudget;
+use primitives::{POINTS_PER_FRAME_RANGE, SamplePoint, SpawnRate};
+
+/// 現在シーンに存在するポイントの数を数える
+fn sample_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+/// 1フレームで増えたポイントの数
+fn spawned_in_one_frame(app: &mut App) -> usize {
+    let before = sample_count(app);
+    app.update();
+    sample_count(app) - before
+}
+
+/// 今の1フレームあたりの生成数
+fn points_per_frame(app: &App) -> usize {
+    app.world().resource::<SpawnRate>().points_per_frame
+}
+
+#[test]
+fn keys_change_the_automatic_spawn_rate() {
+    let mut app = HeadlessApp::new().start();
+    let initial = DemoConfig::default().points_per_frame;
+    assert_eq!(spawned_in_one_frame(&mut app), initial);
+
+    tap(&mut app, KeyCode::PageUp);
+    assert_eq!(points_per_frame(&app), initial * 2);
+    assert_eq!(spawned_in_one_frame(&mut app), initial * 2);
+
+    // 0まで下げると自動モードでも生成しなくなる
+    while points_per_frame(&app) > 0 {
+        tap(&mut app, KeyCode::PageDown);
+    }
+    assert_eq!(spawned_in_one_frame(&mut app), 0);
+
+    // 0から上げると1個ずつ生成を再開する
+    tap(&mut app, KeyCode::PageUp);
+    assert_eq!(spawned_in_one_frame(&mut app), 1);
+}
+
+#[test]
+fn spawn_rate_stays_in_range() {
+    let mut rate = SpawnRate::default();
+    for _ in 0..20 {
+        rate.step(true);
+    }
+    assert_eq!(rate.points_per_frame, *POINTS_PER_FRAME_RANGE.end());
+    for _ in 0..20 {
+        rate.step(false);
+    }
+    assert_eq!(rate.points_per_frame, *POINTS_PER_FRAME_RANGE.start());
+    assert!(rate.user_set);
+}
+
+#[test]
+fn chosen_rate_is_kept_while_the_budget_is_lowered() {
+    let mut app = HeadlessApp::new().start();
+    app.world_mut().resource_mut::<PointBudget>().scale = 0.5;
+    let initial = DemoConfig::default().points_per_frame;
+
+    // キーで変えるまでは、自動調整の倍率をかけた数だけ生成する
+    let before = sample_count(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(sample_count(&mut app) - before, 10 * initial / 2);
+
+    // キーで選んだ生成数は、倍率を下げていても減らさない
+    tap(&mut app, KeyCode::PageUp);
+    assert_eq!(app.world().resource::<PointBudget>().scale, 0.5);
+    assert_eq!(spawned_in_one_frame(&mut app), initial * 2);
+}
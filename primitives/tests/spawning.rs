@@ -0,0 +1,218 @@
+//! ポイント生成・削除システムのテスト(ウィンドウなしでサンプル全体を動かす)
+
+mod common;
+
+use bevy::prelude::*;
+use common::HeadlessApp;
+use primitives::demo_config::DemoConfig;
+use primitives::instancing::{PointInstance, PointInstances};
+use primitives::{
+    PointCounter, RngSeed, SamplePoint, SamplingMode, ShapeIndex, SpawnQueue, SpawningMode,
+    ViewMode,
+};
+
+/// 1フレームあたりに生成されるポイント数(設定のデフォルト値)
+fn points_per_frame() -> usize {
+    DemoConfig::default().points_per_frame
+}
+
+/// 現在シーンに存在するポイントの数を数える
+fn sample_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<SamplePoint>>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn automatic_mode_spawns_points_every_frame() {
+    let mut app = HeadlessApp::new().build();
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert_eq!(sample_count(&mut app), 10 * points_per_frame());
+    assert_eq!(app.world().resource::<PointCounter>().0, 10 * points_per_frame());
+}
+
+#[test]
+fn manual_queue_spawns_exact_count() {
+    let mut app = HeadlessApp::new().build();
+    app.update(); // Startupでリソースを準備する
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    let before = sample_count(&mut app);
+
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 250;
+    app.update();
+
+    assert_eq!(sample_count(&mut app) - before, 250);
+    assert_eq!(app.world().resource::<SpawnQueue>().0, 0);
+}
+
+#[test]
+fn points_above_max_are_despawned() {
+    let mut app = HeadlessApp::new().build();
+    app.update();
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+
+    // 1フレームの生成数には上限があるので、数フレームかけて上限を超えるまで生成する
+    let max_points = DemoConfig::default().max_points;
+    app.world_mut().resource_mut::<SpawnQueue>().0 = max_points + 50;
+    while app.world().resource::<SpawnQueue>().0 > 0 {
+        app.update();
+    }
+    assert!(app.world().resource::<PointCounter>().0 > max_points);
+
+    // 自動モードに戻すと、上限を超えた分が削除対象になる
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Automatic;
+    app.update();
+    app.update();
+
+    let counter = app.world().resource::<PointCounter>().0;
+    assert!(counter <= max_points + points_per_frame());
+    assert_eq!(sample_count(&mut app), counter);
+}
+
+#[test]
+fn shapes_receive_points_in_proportion_to_their_volume() {
+    let mut app = HeadlessApp::new().build();
+    app.update();
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+
+    app.world_mut().resource_mut::<SpawnQueue>().0 = 20_000;
+    while app.world().resource::<SpawnQueue>().0 > 0 {
+        app.update();
+    }
+
+    let measures = ViewMode::ThreeD.measures(SamplingMode::Interior);
+    let mut counts = vec![0usize; measures.len()];
+    let mut indices = app
+        .world_mut()
+        .query_filtered::<&ShapeIndex, With<SamplePoint>>();
+    for index in indices.iter(app.world()) {
+        counts[index.0] += 1;
+    }
+
+    // 各図形に入ったポイントの割合は、体積の割合とほぼ同じになる
+    let total_measure: f32 = measures.iter().sum();
+    let total_count: usize = counts.iter().sum();
+    for (measure, count) in measures.iter().zip(&counts) {
+        let expected = measure / total_measure;
+        let actual = *count as f32 / total_count as f32;
+        assert!(
+            (expected - actual).abs() < 0.015,
+            "expected {expected}, actual {actual}"
+        );
+    }
+}
+
+#[test]
+fn view_switch_samples_points_on_plane() {
+    let mut app = HeadlessApp::new().build();
+    for _ in 0..5 {
+        app.update();
+    }
+
+    // Vキーで2Dの図形に切り替えると、それまでのポイントは消え、XY平面上にだけ生成される
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyV);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyV);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert_eq!(*app.world().resource::<ViewMode>(), ViewMode::TwoD);
+    assert_eq!(sample_count(&mut app), 6 * points_per_frame());
+    let mut samples = app
+        .world_mut()
+        .query_filtered::<&Transform, With<SamplePoint>>();
+    assert!(
+        samples
+            .iter(app.world())
+            .all(|transform| transform.translation.z == 0.0)
+    );
+}
+
+#[test]
+fn instances_follow_point_entities() {
+    let mut app = HeadlessApp::new().build();
+    for _ in 0..5 {
+        app.update();
+    }
+    // 生成を止めて1フレーム進め、最後に生成したポイントも一覧に入るようにする
+    *app.world_mut().resource_mut::<SpawningMode>() = SpawningMode::Manual;
+    app.update();
+
+    // ポイントはメッシュを持たず、1つのエンティティのインスタンスの一覧にまとめて描かれる
+    let instances: Vec<PointInstance> = app
+        .world_mut()
+        .query::<&PointInstances>()
+        .single(app.world())
+        .unwrap()
+        .0
+        .clone();
+    assert_eq!(instances.len(), sample_count(&mut app));
+
+    // 位置は図形の中心からの位置ではなく、ワールド座標で描く
+    let mut points = app
+        .world_mut()
+        .query_filtered::<(&Transform, &GlobalTransform), With<SamplePoint>>();
+    for (transform, global) in points.iter(app.world()) {
+        let position = global.translation().extend(transform.scale.x).to_array();
+        assert!(instances.iter().any(|instance| instance.position_scale == position));
+    }
+}
+
+/// 現在のポイントの位置を集める(順番によらず比べられるように並べ替える)
+fn sample_positions(app: &mut App) -> Vec<[f32; 3]> {
+    let mut positions: Vec<[f32; 3]> = app
+        .world_mut()
+        .query_filtered::<&Transform, With<SamplePoint>>()
+        .iter(app.world())
+        .map(|transform| transform.translation.to_array())
+        .collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    positions
+}
+
+#[test]
+fn seed_is_read_from_args() {
+    let args = ["--headless", "--seed", "123", "--reseed-on-reset"].map(String::from);
+    assert_eq!(
+        RngSeed::from_args(args.into_iter()),
+        RngSeed {
+            seed: 123,
+            reseed_on_reset: true,
+        }
+    );
+    assert_eq!(RngSeed::from_args(std::iter::empty()), RngSeed::default());
+}
+
+#[test]
+fn restart_with_reseed_repeats_the_same_points() {
+    let mut app = HeadlessApp::new()
+        .seed(7)
+        .reseed_on_reset()
+        .manual()
+        .start();
+
+    // Rキーでリスタートしてから20個生成する、を2回繰り返す
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.press(KeyCode::KeyR);
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(KeyCode::KeyR);
+        app.world_mut().resource_mut::<SpawnQueue>().0 = 20;
+        app.update();
+        runs.push(sample_positions(&mut app));
+    }
+
+    assert_eq!(runs[0].len(), 20);
+    assert_eq!(runs[0], runs[1]);
+}
@@ -0,0 +1,152 @@
+//! 図形ごとのポイントの重心と分散の集計のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use common::{HeadlessApp, spawn, tap};
+use primitives::statistics::{CentroidMarker, RunningStatistics, SampleStatistics};
+use primitives::{SamplePoint, SamplingMode};
+
+/// 平均と解析的な重心のずれとして許す、標準誤差の倍数
+const TOLERANCE_SIGMAS: f32 = 5.0;
+
+/// サンプリングモードのポイントの数
+fn sample_count(app: &mut App, mode: SamplingMode) -> u64 {
+    app.world_mut()
+        .query_filtered::<&SamplingMode, With<SamplePoint>>()
+        .iter(app.world())
+        .filter(|point| **point == mode)
+        .count() as u64
+}
+
+/// 図形ごとの平均が、解析的な重心から標準誤差の数倍以内にあることを確かめる
+fn assert_means_near_centroids(app: &App, mode: SamplingMode) {
+    let statistics = app.world().resource::<SampleStatistics>();
+    for shape in &statistics.shapes {
+        let samples = shape.samples(mode);
+        assert!(samples.count() > 100, "{} {mode:?}", shape.name_key);
+        let mean = samples.mean().unwrap();
+        let tolerance = samples.standard_error() * TOLERANCE_SIGMAS + Vec3::splat(1e-4);
+        let deviation = (mean - shape.centroid(mode)).abs();
+        assert!(
+            deviation.cmple(tolerance).all(),
+            "{} {mode:?}: 平均{mean} 重心{} 許容{tolerance}",
+            shape.name_key,
+            shape.centroid(mode)
+        );
+    }
+}
+
+#[test]
+fn running_statistics_match_direct_computation() {
+    let points = [
+        Vec3::new(1.0, 2.0, 0.0),
+        Vec3::new(-1.0, 0.0, 1.0),
+        Vec3::new(0.5, -1.0, 2.0),
+        Vec3::new(2.0, 1.0, -1.0),
+    ];
+    let mut statistics = RunningStatistics::default();
+    assert_eq!(statistics.mean(), None);
+    assert_eq!(statistics.covariance(), Mat3::ZERO);
+    for point in points {
+        statistics.add(point);
+    }
+    // 取り除いたポイントは最初から加えなかったのと同じになる
+    statistics.add(Vec3::splat(10.0));
+    statistics.remove(Vec3::splat(10.0));
+    assert_eq!(statistics.count(), 4);
+
+    let mean = points.iter().sum::<Vec3>() / 4.0;
+    assert!(statistics.mean().unwrap().abs_diff_eq(mean, 1e-6));
+    let covariance = points
+        .iter()
+        .map(|point| {
+            let d = *point - mean;
+            Mat3::from_cols(d * d.x, d * d.y, d * d.z)
+        })
+        .fold(Mat3::ZERO, |sum, outer| sum + outer)
+        * 0.25;
+    assert!(statistics.covariance().abs_diff_eq(covariance, 1e-5));
+    assert_eq!(
+        statistics.variance(),
+        Vec3::new(
+            statistics.covariance().x_axis.x,
+            statistics.covariance().y_axis.y,
+            statistics.covariance().z_axis.z
+        )
+    );
+}
+
+#[test]
+fn j_key_collects_existing_points_and_places_markers() {
+    let mut app = HeadlessApp::new().seed(7).manual().start();
+    spawn(&mut app, 50);
+    assert!(!app.world().resource::<SampleStatistics>().enabled);
+
+    tap(&mut app, KeyCode::KeyJ);
+    let collected: u64 = app
+        .world()
+        .resource::<SampleStatistics>()
+        .shapes
+        .iter()
+        .map(|shape| shape.samples(SamplingMode::Interior).count())
+        .sum();
+    assert_eq!(collected, sample_count(&mut app, SamplingMode::Interior));
+
+    // ポイントのある図形ごとに1つずつマーカーを置く
+    let with_points = app
+        .world()
+        .resource::<SampleStatistics>()
+        .shapes
+        .iter()
+        .filter(|shape| shape.samples(SamplingMode::Interior).count() > 0)
+        .count();
+    let markers = app
+        .world_mut()
+        .query::<&CentroidMarker>()
+        .iter(app.world())
+        .count();
+    assert_eq!(markers, with_points);
+
+    // 無効にするとマーカーを消す
+    tap(&mut app, KeyCode::KeyJ);
+    let markers = app
+        .world_mut()
+        .query::<&CentroidMarker>()
+        .iter(app.world())
+        .count();
+    assert_eq!(markers, 0);
+}
+
+#[test]
+fn means_approach_the_analytic_centroids() {
+    let mut app = HeadlessApp::new().seed(7).manual().start();
+    tap(&mut app, KeyCode::KeyJ);
+    spawn(&mut app, 30_000);
+    assert_means_near_centroids(&app, SamplingMode::Interior);
+
+    *app.world_mut().resource_mut::<SamplingMode>() = SamplingMode::Boundary;
+    spawn(&mut app, 30_000);
+    assert_means_near_centroids(&app, SamplingMode::Boundary);
+}
+
+#[test]
+fn removed_points_are_subtracted() {
+    let mut app = HeadlessApp::new().seed(7).manual().start();
+    tap(&mut app, KeyCode::KeyJ);
+    spawn(&mut app, 200);
+    let total = |app: &App| -> u64 {
+        app.world()
+            .resource::<SampleStatistics>()
+            .shapes
+            .iter()
+            .map(|shape| shape.samples(SamplingMode::Interior).count())
+            .sum()
+    };
+    assert_eq!(total(&app), sample_count(&mut app, SamplingMode::Interior));
+
+    // リスタートですべてのポイントが削除アニメーションに移ると、集計からも引く
+    tap(&mut app, KeyCode::KeyR);
+    assert_eq!(sample_count(&mut app, SamplingMode::Interior), 0);
+    assert_eq!(total(&app), 0);
+}
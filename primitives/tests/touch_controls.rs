@@ -0,0 +1,86 @@
+//! タッチ操作によるカメラの回転・ズームと図形の切り替えのテスト
+
+mod common;
+
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::prelude::*;
+use common::HeadlessApp;
+
+/// タッチの入力を送る(ヘッドレスなのでウィンドウはない)
+fn touch(app: &mut App, id: u64, phase: TouchPhase, position: Vec2) {
+    app.world_mut().send_event(TouchInput {
+        phase,
+        position,
+        window: Entity::PLACEHOLDER,
+        force: None,
+        id,
+    });
+}
+
+/// カメラの位置と向いている方向
+fn camera(app: &mut App) -> (Vec3, Vec3) {
+    let transform = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap();
+    (transform.translation, transform.forward().as_vec3())
+}
+
+#[test]
+fn one_finger_drag_rotates_the_camera() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let (_, start) = camera(&mut app);
+
+    touch(&mut app, 0, TouchPhase::Started, Vec2::new(100.0, 100.0));
+    app.update();
+    touch(&mut app, 0, TouchPhase::Moved, Vec2::new(190.0, 100.0));
+    app.update();
+    touch(&mut app, 0, TouchPhase::Ended, Vec2::new(190.0, 100.0));
+    app.update();
+
+    let (_, rotated) = camera(&mut app);
+    assert!(!rotated.abs_diff_eq(start, 1e-3), "{start} -> {rotated}");
+}
+
+#[test]
+fn pinching_out_zooms_in() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let (start_position, start_forward) = camera(&mut app);
+
+    // 2本の指の間隔を2倍に広げる
+    touch(&mut app, 0, TouchPhase::Started, Vec2::new(100.0, 100.0));
+    touch(&mut app, 1, TouchPhase::Started, Vec2::new(200.0, 100.0));
+    app.update();
+    touch(&mut app, 0, TouchPhase::Moved, Vec2::new(50.0, 100.0));
+    touch(&mut app, 1, TouchPhase::Moved, Vec2::new(250.0, 100.0));
+    app.update();
+    for _ in 0..30 {
+        app.update();
+    }
+
+    // 向きは変わらず、注視点へ近づく(カメラは前へ進む)
+    let (position, forward) = camera(&mut app);
+    assert!(forward.abs_diff_eq(start_forward, 1e-4));
+    assert!((position - start_position).dot(start_forward) > 0.1);
+}
+
+#[test]
+fn double_tap_moves_to_the_next_shape() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let (start, _) = camera(&mut app);
+
+    for _ in 0..2 {
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(300.0, 200.0));
+        app.update();
+        touch(&mut app, 0, TouchPhase::Ended, Vec2::new(302.0, 201.0));
+        app.update();
+    }
+    for _ in 0..30 {
+        app.update();
+    }
+
+    // 右隣の図形(+X側)へ注視点が移る
+    let (moved, _) = camera(&mut app);
+    assert!(moved.x > start.x + 0.5, "{start} -> {moved}");
+}
@@ -0,0 +1,74 @@
+//! カメラのターンテーブルモードのテスト
+
+mod common;
+
+use bevy::input::ButtonState;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::prelude::*;
+use common::{HeadlessApp, tap};
+use primitives::turntable::Turntable;
+
+/// マウスの左ボタンを押す・離す
+fn mouse_left(app: &mut App, state: ButtonState) {
+    app.world_mut().send_event(MouseButtonInput {
+        button: MouseButton::Left,
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+/// カメラの向き(ズームの補間では変わらない)
+fn camera_forward(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Camera3d>>()
+        .single(app.world())
+        .unwrap()
+        .forward()
+        .as_vec3()
+}
+
+#[test]
+fn x_key_orbits_the_camera_until_toggled_off() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    let still = camera_forward(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(camera_forward(&mut app).abs_diff_eq(still, 1e-5));
+
+    tap(&mut app, KeyCode::KeyX);
+    assert!(app.world().resource::<Turntable>().enabled);
+    let start = camera_forward(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    let orbited = camera_forward(&mut app);
+    assert!(!orbited.abs_diff_eq(start, 1e-3));
+    // 水平方向に回るので、上下の向きは変わらない
+    assert!((orbited.y - start.y).abs() < 1e-4);
+
+    tap(&mut app, KeyCode::KeyX);
+    let stopped = camera_forward(&mut app);
+    app.update();
+    assert!(camera_forward(&mut app).abs_diff_eq(stopped, 1e-5));
+}
+
+#[test]
+fn dragging_pauses_the_orbit() {
+    let mut app = HeadlessApp::new().frame_time(50).start();
+    tap(&mut app, KeyCode::KeyX);
+
+    mouse_left(&mut app, ButtonState::Pressed);
+    app.update();
+    let held = camera_forward(&mut app);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(camera_forward(&mut app).abs_diff_eq(held, 1e-5));
+
+    mouse_left(&mut app, ButtonState::Released);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(!camera_forward(&mut app).abs_diff_eq(held, 1e-3));
+}
@@ -0,0 +1,114 @@
+//! 図形のメッシュのワイヤーフレーム表示のテスト
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+use common::{HeadlessApp, tap};
+use primitives::ShapeRoot;
+use primitives::wireframe::{ShapeWireframe, WireframeOverlay, wireframe_mesh};
+
+/// 図形のワイヤーフレームを作り終えるまで、2フレーム進めたAppを作る
+fn headless_app() -> App {
+    let mut app = HeadlessApp::new().start();
+    app.update();
+    app
+}
+
+/// ワイヤーフレームごとの親と表示状態
+fn overlays(app: &mut App) -> Vec<(Entity, Visibility)> {
+    app.world_mut()
+        .query_filtered::<(&ChildOf, &Visibility), With<WireframeOverlay>>()
+        .iter(app.world())
+        .map(|(child_of, visibility)| (child_of.parent(), *visibility))
+        .collect()
+}
+
+/// 図形のエンティティの一覧
+fn roots(app: &mut App) -> Vec<Entity> {
+    let mut roots: Vec<Entity> = app
+        .world_mut()
+        .query_filtered::<Entity, With<ShapeRoot>>()
+        .iter(app.world())
+        .collect();
+    roots.sort();
+    roots
+}
+
+/// 図形ごとにワイヤーフレームが1つずつ付いているか確かめる
+fn assert_one_per_shape(app: &mut App) {
+    let roots = roots(app);
+    assert!(!roots.is_empty());
+    let mut parents: Vec<Entity> = overlays(app)
+        .into_iter()
+        .map(|(parent, _)| parent)
+        .collect();
+    parents.sort();
+    assert_eq!(parents, roots);
+}
+
+#[test]
+fn cuboid_wireframe_has_each_edge_once() {
+    let wireframe = wireframe_mesh(&Cuboid::default().mesh().build());
+    assert_eq!(wireframe.primitive_topology(), PrimitiveTopology::LineList);
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        wireframe.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("位置の属性がない");
+    };
+    // 12本の辺と、6つの面の対角線
+    assert_eq!(positions.len(), 18 * 2);
+}
+
+#[test]
+fn non_triangle_meshes_have_no_wireframe() {
+    let lines = wireframe_mesh(&Cuboid::default().mesh().build());
+    let wireframe = wireframe_mesh(&lines);
+    assert_eq!(wireframe.count_vertices(), 0);
+}
+
+#[test]
+fn every_shape_gets_a_hidden_wireframe() {
+    let mut app = headless_app();
+    assert!(!app.world().resource::<ShapeWireframe>().visible);
+    assert_one_per_shape(&mut app);
+    assert!(
+        overlays(&mut app)
+            .iter()
+            .all(|(_, visibility)| *visibility == Visibility::Hidden)
+    );
+
+    // 2Dに切り替えても、新しい図形に付け直す(古い図形のワイヤーフレームは一緒に消える)
+    tap(&mut app, KeyCode::KeyV);
+    assert_one_per_shape(&mut app);
+}
+
+#[test]
+fn w_key_toggles_wireframe() {
+    let mut app = headless_app();
+    tap(&mut app, KeyCode::KeyW);
+    assert!(app.world().resource::<ShapeWireframe>().visible);
+    assert!(
+        overlays(&mut app)
+            .iter()
+            .all(|(_, visibility)| *visibility == Visibility::Inherited)
+    );
+
+    // 表示中に切り替えた図形も表示される
+    tap(&mut app, KeyCode::KeyV);
+    let shown = overlays(&mut app);
+    assert!(!shown.is_empty());
+    assert!(
+        shown
+            .iter()
+            .all(|(_, visibility)| *visibility == Visibility::Inherited)
+    );
+
+    tap(&mut app, KeyCode::KeyW);
+    assert!(!app.world().resource::<ShapeWireframe>().visible);
+    assert!(
+        overlays(&mut app)
+            .iter()
+            .all(|(_, visibility)| *visibility == Visibility::Hidden)
+    );
+}
@@ -0,0 +1,172 @@
+//! キーボードとゲームパッドの入力を「アクション」にまとめる共通の入力レイヤー
+//!
+//! 各サンプルは自分用のアクションのenumを定義し、`ActionMap`でキーとボタンを割り当てる。
+//! システムは`ButtonInput<KeyCode>`の代わりに`Res<ButtonInput<A>>`を読めば、
+//! キーボードでもゲームパッドでも同じように動く。
+//! 移動や視点操作、トリガーの押し込み量のようなアナログ入力は`ActionAxes`で受け取る。
+//! `ctrl_key`で割り当てたアクションはCtrlキーと一緒に押したときだけ押され、Ctrlキーを押している間は
+//! `ctrl_key`と同じキーを`key`で割り当てたアクションは押されない(Ctrl+Sで保存するときに、Sキーの操作まで動かないようにする)。
+//! ほかのキーの割り当て(Ctrlキー自体や移動のキー)は、Ctrlキーを押していても押される。
+
+use std::hash::Hash;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+/// アクションとして使える型(`Copy`なenumを想定)
+pub trait Action: Copy + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Action for T {}
+
+/// キーとゲームパッドのボタンをアクションに割り当てる表
+#[derive(Resource, Clone)]
+pub struct ActionMap<A: Action> {
+    keys: Vec<(KeyCode, A)>,          // キーボードの割り当て
+    ctrl_keys: Vec<(KeyCode, A)>,     // Ctrlキーと一緒に押すキーの割り当て
+    buttons: Vec<(GamepadButton, A)>, // ゲームパッドのボタンの割り当て
+    movement: Option<[A; 4]>,         // 移動に使うアクション(上・下・左・右)
+}
+
+impl<A: Action> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            ctrl_keys: Vec::new(),
+            buttons: Vec::new(),
+            movement: None,
+        }
+    }
+}
+
+impl<A: Action> ActionMap<A> {
+    /// 空の割り当て表を作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// キーをアクションに割り当てる(同じアクションに複数のキーを割り当ててもよい)
+    pub fn key(mut self, action: A, key: KeyCode) -> Self {
+        self.keys.push((key, action));
+        self
+    }
+
+    /// Ctrlキーと一緒に押すキーをアクションに割り当てる(左右どちらのCtrlキーでもよい)
+    pub fn ctrl_key(mut self, action: A, key: KeyCode) -> Self {
+        self.ctrl_keys.push((key, action));
+        self
+    }
+
+    /// ゲームパッドのボタンをアクションに割り当てる
+    pub fn button(mut self, action: A, button: GamepadButton) -> Self {
+        self.buttons.push((button, action));
+        self
+    }
+
+    /// 上下左右のアクションを`ActionAxes::movement`に反映させる
+    /// 標準の割り当てとして、十字キーも同じアクションに割り当てる
+    /// (左スティックは常に`movement`に加算される)
+    pub fn movement(self, up: A, down: A, left: A, right: A) -> Self {
+        let mut map = self
+            .button(up, GamepadButton::DPadUp)
+            .button(down, GamepadButton::DPadDown)
+            .button(left, GamepadButton::DPadLeft)
+            .button(right, GamepadButton::DPadRight);
+        map.movement = Some([up, down, left, right]);
+        map
+    }
+}
+
+/// アナログ入力をまとめたリソース
+#[derive(Resource, Default, Debug)]
+pub struct ActionAxes {
+    pub movement: Vec2, // 移動入力(x: 右が正、y: 上・前が正)。長さは1以下
+    pub look: Vec2,     // 視点操作の入力(右スティック)
+    pub triggers: f32,  // 右トリガーから左トリガーを引いた値(-1〜1、押し込んだ量に比例する)
+}
+
+/// `ActionMap`に従ってアクションの入力状態を更新するプラグイン
+/// 1つのアプリにつき、アクションの型ごとに1つ追加する
+pub struct ActionsPlugin<A: Action> {
+    map: ActionMap<A>,
+}
+
+impl<A: Action> ActionsPlugin<A> {
+    /// 割り当て表を指定してプラグインを作成する
+    pub fn new(map: ActionMap<A>) -> Self {
+        Self { map }
+    }
+}
+
+impl<A: Action> Plugin for ActionsPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.map.clone())
+            .init_resource::<ButtonInput<A>>()
+            .init_resource::<ActionAxes>()
+            .add_systems(PreUpdate, update_actions::<A>.after(InputSystem));
+    }
+}
+
+/// キーボードとゲームパッドの状態からアクションの入力状態を更新するシステム
+fn update_actions<A: Action>(
+    map: Res<ActionMap<A>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<ButtonInput<A>>,
+    mut axes: ResMut<ActionAxes>,
+) {
+    // 前フレームのjust_pressed/just_releasedを消してから、今の状態を反映する
+    actions.clear();
+
+    // Ctrlキーを押している間は、Ctrlキーと一緒に押す割り当てを見て、同じキーの普通の割り当ては見ない
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let plain_keys = map
+        .keys
+        .iter()
+        .filter(|(key, _)| !ctrl || !map.ctrl_keys.iter().any(|(ctrl_key, _)| ctrl_key == key));
+    let ctrl_keys = map.ctrl_keys.iter().filter(|_| ctrl);
+    let key_actions = plain_keys
+        .chain(ctrl_keys)
+        .filter(|(key, _)| keyboard.pressed(*key))
+        .map(|(_, action)| *action);
+    let button_actions = map
+        .buttons
+        .iter()
+        .filter(|(button, _)| gamepads.iter().any(|gamepad| gamepad.pressed(*button)))
+        .map(|(_, action)| *action);
+    let pressed: Vec<A> = key_actions.chain(button_actions).collect();
+
+    let all_actions = map
+        .keys
+        .iter()
+        .chain(&map.ctrl_keys)
+        .map(|(_, action)| *action);
+    let all_actions = all_actions.chain(map.buttons.iter().map(|(_, action)| *action));
+    for action in all_actions {
+        let is_pressed = pressed.contains(&action);
+        if is_pressed && !actions.pressed(action) {
+            actions.press(action);
+        } else if !is_pressed && actions.pressed(action) {
+            actions.release(action);
+        }
+    }
+
+    // 移動はデジタル入力(キー・十字キー)と左スティックを合算する
+    let mut movement = Vec2::ZERO;
+    if let Some([up, down, left, right]) = map.movement {
+        let axis = |positive: A, negative: A| {
+            actions.pressed(positive) as i32 as f32 - actions.pressed(negative) as i32 as f32
+        };
+        movement += Vec2::new(axis(right, left), axis(up, down));
+    }
+    let mut look = Vec2::ZERO;
+    let mut triggers = 0.0;
+    for gamepad in gamepads.iter() {
+        movement += gamepad.left_stick();
+        look += gamepad.right_stick();
+        let trigger = |button: GamepadButton| gamepad.get(button).unwrap_or(0.0);
+        triggers += trigger(GamepadButton::RightTrigger2) - trigger(GamepadButton::LeftTrigger2);
+    }
+    axes.movement = movement.clamp_length_max(1.0);
+    axes.look = look.clamp_length_max(1.0);
+    axes.triggers = triggers.clamp(-1.0, 1.0);
+}
@@ -0,0 +1,221 @@
+//! メニュー・読み込み中・プレイ中・一時停止のアプリの状態
+//!
+//! 状態は`Menu → Loading → Playing ⇄ Paused`の順に進む。
+//! - Menu: タイトルと開始の案内を表示する。Enter(ゲームパッドではStartかA)で読み込みを始める
+//! - Loading: `LoadingAssets`に登録したアセットが依存関係も含めてすべて読み込まれるまで待つ
+//! - Playing / Paused: `TimeControlPlugin`の`PauseState`(Pキー)に合わせて切り替わる
+//!
+//! メニューと読み込み中の画面は画面全体を覆うので、起動直後にアセットが遅れて表示される様子は見えない。
+//! ゲームのシステムには`in_state(AppState::Playing)`を実行条件に付ける。
+//!
+//! 画面の文章は`LocalizedText`で表示するので、`LocalizationPlugin`と一緒に使う。
+
+use bevy::asset::{LoadState, UntypedAssetId};
+use bevy::prelude::*;
+
+use crate::locale::LocalizedText;
+use crate::time_control::PauseState;
+
+/// メニューとロード画面の背景色
+const OVERLAY_COLOR: Color = Color::srgb(0.05, 0.05, 0.08);
+
+/// アプリの状態
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[states(scoped_entities)] // StateScopedを付けた画面は状態を抜けると削除される
+pub enum AppState {
+    #[default]
+    Menu, // タイトルメニュー
+    Loading, // アセットの読み込み中
+    Playing, // プレイ中
+    Paused,  // 一時停止中
+}
+
+/// 読み込み中の画面で待つアセット
+/// `Startup`で読み込みを始めたアセットのハンドルを登録する
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    handles: Vec<UntypedHandle>,
+}
+
+impl LoadingAssets {
+    /// 読み込みを待つアセットを追加する
+    pub fn add(&mut self, handle: impl Into<UntypedHandle>) {
+        self.handles.push(handle.into());
+    }
+
+    /// 登録したアセットの数
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// 登録したアセットがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// 読み込みが終わったアセットの数(読み込みに失敗したものも終わったものとして数える)
+    pub fn finished(&self, asset_server: &AssetServer) -> usize {
+        self.handles
+            .iter()
+            .filter(|handle| is_finished(asset_server, handle.id()))
+            .count()
+    }
+}
+
+/// アセットの読み込みが終わったかどうか
+fn is_finished(asset_server: &AssetServer, id: UntypedAssetId) -> bool {
+    asset_server.is_loaded_with_dependencies(id)
+        || matches!(asset_server.load_state(id), LoadState::Failed(_))
+}
+
+/// アプリの状態とメニュー・ロード画面・ポーズ画面を提供するプラグイン
+pub struct AppStatePlugin {
+    title_key: &'static str, // メニューに表示するタイトルの対応表のキー
+}
+
+impl AppStatePlugin {
+    /// メニューのタイトルを各サンプルの対応表のキーで指定して作成する
+    pub fn new(title_key: &'static str) -> Self {
+        Self { title_key }
+    }
+}
+
+impl Plugin for AppStatePlugin {
+    fn build(&self, app: &mut App) {
+        let title_key = self.title_key;
+        app.init_state::<AppState>()
+            .init_resource::<LoadingAssets>()
+            .init_resource::<PauseState>()
+            .add_systems(
+                OnEnter(AppState::Menu),
+                move |commands: Commands| spawn_menu(commands, title_key),
+            )
+            .add_systems(OnEnter(AppState::Loading), spawn_loading_screen)
+            .add_systems(OnEnter(AppState::Paused), spawn_pause_overlay)
+            .add_systems(
+                Update,
+                (
+                    start_from_menu.run_if(in_state(AppState::Menu)),
+                    update_loading.run_if(in_state(AppState::Loading)),
+                    sync_pause_state.run_if(
+                        in_state(AppState::Playing)
+                            .or(in_state(AppState::Paused))
+                            .and(resource_changed::<PauseState>),
+                    ),
+                ),
+            );
+    }
+}
+
+/// メニュー画面を作成する
+fn spawn_menu(mut commands: Commands, title_key: &'static str) {
+    commands
+        .spawn((overlay_node(), BackgroundColor(OVERLAY_COLOR), StateScoped(AppState::Menu)))
+        .with_children(|parent| {
+            parent.spawn((
+                LocalizedText::new(title_key),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+            ));
+            parent.spawn(LocalizedText::new("app_state.press_start"));
+        });
+}
+
+/// 読み込み中の画面を作成する
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            overlay_node(),
+            BackgroundColor(OVERLAY_COLOR),
+            StateScoped(AppState::Loading),
+        ))
+        .with_children(|parent| {
+            parent.spawn((LocalizedText::new("app_state.loading"), LoadingText));
+        });
+}
+
+/// 一時停止中の表示を作成する(ゲーム画面が見えるように背景は半透明にする)
+fn spawn_pause_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            overlay_node(),
+            BackgroundColor(OVERLAY_COLOR.with_alpha(0.5)),
+            StateScoped(AppState::Paused),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LocalizedText::new("app_state.paused"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// 画面全体を覆い、中央に子要素を縦に並べるノード
+fn overlay_node() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(16.0),
+        ..default()
+    }
+}
+
+/// 読み込みの進み具合を表示するテキストのマーカーコンポーネント
+#[derive(Component)]
+struct LoadingText;
+
+/// Enter(ゲームパッドではStartかA)で読み込みを始めるシステム
+fn start_from_menu(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let gamepad_pressed = gamepads.iter().any(|gamepad| {
+        gamepad.any_just_pressed([GamepadButton::Start, GamepadButton::South])
+    });
+    if keyboard.just_pressed(KeyCode::Enter) || gamepad_pressed {
+        next_state.set(AppState::Loading);
+    }
+}
+
+/// 読み込みの進み具合を表示し、すべて読み込まれたらプレイを始めるシステム
+fn update_loading(
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+    mut texts: Query<&mut LocalizedText, With<LoadingText>>,
+    mut pause: ResMut<PauseState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let finished = loading.finished(&asset_server);
+    for mut text in texts.iter_mut() {
+        // 数が変わったときだけ更新する(毎フレーム文章を作り直さない)
+        text.set_if_neq(
+            LocalizedText::new("app_state.loading")
+                .with_arg("finished", finished)
+                .with_arg("total", loading.len()),
+        );
+    }
+
+    if finished == loading.len() {
+        info!("{}個のアセットを読み込みました", loading.len());
+        pause.set_if_neq(PauseState::Running); // メニューで一時停止していても、再開した状態で始める
+        next_state.set(AppState::Playing);
+    }
+}
+
+/// `PauseState`の変更に合わせて、プレイ中と一時停止中を切り替えるシステム
+fn sync_pause_state(pause: Res<PauseState>, mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(match *pause {
+        PauseState::Running => AppState::Playing,
+        PauseState::Paused => AppState::Paused,
+    });
+}
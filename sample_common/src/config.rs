@@ -0,0 +1,210 @@
+//! RONファイルから読み込む設定と、実行中の再読み込み
+//!
+//! 設定の型は`Asset`と`Resource`の両方として扱う。
+//! `ConfigPlugin`は起動時にデフォルト値のリソースを用意し、`assets/`以下の
+//! `*.config.ron`ファイルを読み込んだらその内容で置き換える。
+//! ネイティブではファイルを監視しているので、編集して保存すると再起動せずに反映される。
+//!
+//! 値が変わったフィールドだけをログに出し、変更がなければリソースは書き換えない。
+//! 各サンプルは`resource_changed::<T>`で、自分のリソースやコンポーネントに値を反映する。
+//!
+//! `LoadConfig`コマンドで、読み込むファイルを実行中に切り替えられる(レベルの切り替えなど)。
+
+use std::marker::PhantomData;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, ron};
+use bevy::prelude::*;
+use bevy::reflect::Struct;
+use serde::de::DeserializeOwned;
+
+/// 設定として使える型
+/// `#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Default)]`した構造体を想定
+pub trait Config:
+    Asset + Resource + Struct + Clone + PartialEq + Default + DeserializeOwned
+{
+}
+
+impl<T> Config for T where
+    T: Asset + Resource + Struct + Clone + PartialEq + Default + DeserializeOwned
+{
+}
+
+/// 設定ファイルを読み込み、変更を監視するプラグイン
+pub struct ConfigPlugin<T: Config> {
+    path: &'static str, // assetsディレクトリからの相対パス(拡張子は`.config.ron`)
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Config> ConfigPlugin<T> {
+    /// 読み込むファイルを指定してプラグインを作成する
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Config> Plugin for ConfigPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let path = self.path;
+        app.init_resource::<T>() // ファイルを読み込むまではデフォルト値を使う
+            .init_asset::<T>()
+            .register_asset_loader(ConfigLoader::<T>(PhantomData))
+            .add_systems(
+                PreStartup,
+                move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    let handle = asset_server.load(path);
+                    commands.insert_resource(ConfigHandle::<T> {
+                        current: handle.clone(),
+                        loaded: vec![handle],
+                    });
+                },
+            )
+            .add_systems(PreUpdate, apply_loaded_config::<T>);
+    }
+}
+
+/// 読み込み中・読み込み済みの設定ファイルのハンドル
+#[derive(Resource)]
+struct ConfigHandle<T: Config> {
+    current: Handle<T>,     // リソースに反映するファイル
+    loaded: Vec<Handle<T>>, // 切り替えたことのあるファイル(戻したときにすぐ反映できるように、読み込んだままにする)
+}
+
+/// 設定を読み込むファイルを切り替えるコマンド
+/// 切り替えた後は、そのファイルの読み込みと変更をリソースに反映する(前のファイルの変更は反映しない)。
+/// すでに読み込んであるファイルなら、すぐにリソースを置き換える
+pub struct LoadConfig<T: Config> {
+    path: String, // assetsディレクトリからの相対パス(拡張子は`.config.ron`)
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Config> LoadConfig<T> {
+    /// 切り替え先のファイルを指定してコマンドを作成する
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Config> Command for LoadConfig<T> {
+    fn apply(self, world: &mut World) {
+        let handle: Handle<T> = world.resource::<AssetServer>().load(self.path);
+        let loaded = world.resource::<Assets<T>>().get(&handle).cloned();
+        let mut config_handle = world.resource_mut::<ConfigHandle<T>>();
+        if !config_handle.loaded.contains(&handle) {
+            config_handle.loaded.push(handle.clone());
+        }
+        config_handle.current = handle;
+        if let Some(loaded) = loaded {
+            let mut current = world.resource_mut::<T>();
+            if *current != loaded {
+                *current = loaded;
+            }
+        }
+    }
+}
+
+/// RONファイルを設定の型として読み込むローダー
+struct ConfigLoader<T>(PhantomData<fn() -> T>);
+
+impl<T: Config> AssetLoader for ConfigLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = ConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, ConfigError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+/// 設定ファイルの読み込みの失敗
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),            // ファイルを読めなかった
+    Ron(ron::error::SpannedError), // RONとして解釈できなかった
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "設定ファイルを読めません: {err}"),
+            ConfigError::Ron(err) => write!(f, "設定ファイルの書式が正しくありません: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for ConfigError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        ConfigError::Ron(err)
+    }
+}
+
+/// 設定ファイルが読み込まれた・更新されたときに、リソースへ反映するシステム
+fn apply_loaded_config<T: Config>(
+    mut events: EventReader<AssetEvent<T>>,
+    handle: Res<ConfigHandle<T>>,
+    configs: Res<Assets<T>>,
+    mut current: ResMut<T>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event
+        else {
+            continue;
+        };
+        if id != handle.current.id() {
+            continue;
+        }
+        let Some(loaded) = configs.get(id) else {
+            continue;
+        };
+
+        let changed = changed_fields(&*current, loaded);
+        if changed.is_empty() {
+            continue; // 値が同じなら変更検知を発生させない
+        }
+        info!(
+            "{}を更新しました: {}",
+            T::short_type_path(),
+            changed.join(", ")
+        );
+        *current = loaded.clone();
+    }
+}
+
+/// 値が異なるフィールドを「名前: 旧 -> 新」の形式で列挙する
+fn changed_fields<T: Struct>(old: &T, new: &T) -> Vec<String> {
+    (0..new.field_len())
+        .filter_map(|index| {
+            let name = new.name_at(index)?;
+            let (old, new) = (old.field_at(index)?, new.field_at(index)?);
+            match old.reflect_partial_eq(new) {
+                Some(true) => None,
+                _ => Some(format!("{name}: {old:?} -> {new:?}")),
+            }
+        })
+        .collect()
+}
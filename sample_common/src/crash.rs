@@ -0,0 +1,297 @@
+//! panic時のクラッシュレポートとエラー画面
+//!
+//! `CrashReportPlugin`(`headless::default_plugins()`に含まれる)はpanicフックを設定し、
+//! panicしたときに`crash_reports/<アプリ名>/<日時>.txt`へレポートを書き出す。
+//! レポートにはpanicのメッセージと発生場所、バックトレース、Bevyのバージョン、実行環境、
+//! 直前のフレーム番号とエンティティ数、主なリソースの値(`Debug`表示)が入る。
+//! 各サンプル固有のリソースは`CrashWatchPlugin::<R>`で追加する。
+//!
+//! ウィンドウのあるネイティブ実行では、同じ実行ファイルを`--crash-report <パス>`付きで起動して
+//! エラー画面を表示し、閉じられるまで待ってから終了する。
+//! Webではファイルに書き出せないので、レポートをブラウザのコンソールに出力する。
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Write as _};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+
+use bevy::diagnostic::FrameCount;
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::app_state::AppState;
+use crate::locale::Locale;
+use crate::platform::QualityLevel;
+use crate::time_control::{PauseState, TimeScale};
+
+/// 使っているBevyのバージョン(Cargo.tomlのbevyの指定と合わせる)
+const BEVY_VERSION: &str = "0.16.1";
+
+/// レポートの保存先のルートディレクトリ(実行時のカレントディレクトリからの相対パス)
+const REPORT_ROOT: &str = "crash_reports";
+
+/// エラー画面を表示するプロセスに、レポートのパスを渡す引数
+const REPORT_ARG: &str = "--crash-report";
+
+/// panicしたときにレポートに書く、直前の状態
+struct CrashContext {
+    app_name: String,                          // 保存先ディレクトリに使うアプリ名
+    frame: u32,                                // 最後に記録したフレーム番号
+    entities: u32,                             // 最後に記録したエンティティ数
+    has_window: bool,                          // エラー画面を表示するかどうか
+    resources: BTreeMap<&'static str, String>, // リソースの型名とDebug表示
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    app_name: String::new(),
+    frame: 0,
+    entities: 0,
+    has_window: false,
+    resources: BTreeMap::new(),
+});
+
+/// panicフックを一度だけ設定するためのフラグ
+static INSTALL_HOOK: Once = Once::new();
+
+/// 複数のスレッドが続けてpanicしても、レポートは最初の1回だけにする
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// 直前の状態を取得する(panic中に取得することもあるので、ロックの汚染は無視する)
+fn context() -> MutexGuard<'static, CrashContext> {
+    CONTEXT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// panicフックを設定し、レポート用に状態を記録するプラグイン
+pub struct CrashReportPlugin {
+    app_name: String, // レポートの保存先に使うアプリ名
+}
+
+impl Default for CrashReportPlugin {
+    /// 実行ファイル名をアプリ名にする
+    fn default() -> Self {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "app".to_owned());
+        Self { app_name }
+    }
+}
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        context().app_name = self.app_name.clone();
+        INSTALL_HOOK.call_once(install_hook);
+
+        // どのサンプルにもある共通のリソース(存在しないものは記録しない)
+        app.add_systems(
+            Last,
+            (
+                record_frame,
+                record_resource::<PauseState>,
+                record_resource::<TimeScale>,
+                record_resource::<QualityLevel>,
+                record_resource::<Locale>,
+                record_resource::<State<AppState>>,
+            ),
+        );
+    }
+}
+
+/// 指定したリソースの値をクラッシュレポートに含めるプラグイン
+pub struct CrashWatchPlugin<R> {
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Default for CrashWatchPlugin<R> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Resource + Debug> Plugin for CrashWatchPlugin<R> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, record_resource::<R>);
+    }
+}
+
+/// フレーム番号とエンティティ数、ウィンドウの有無を記録するシステム
+fn record_frame(
+    frame: Res<FrameCount>,
+    entities: &Entities,
+    windows: Query<(), With<PrimaryWindow>>,
+) {
+    let mut context = context();
+    context.frame = frame.0;
+    context.entities = entities.len();
+    context.has_window = !windows.is_empty();
+}
+
+/// リソースが変わったときに、その値を記録するシステム
+fn record_resource<R: Resource + Debug>(resource: Option<Res<R>>) {
+    let Some(resource) = resource else {
+        return;
+    };
+    if resource.is_changed() {
+        context()
+            .resources
+            .insert(std::any::type_name::<R>(), format!("{:?}", *resource));
+    }
+}
+
+/// panicのメッセージに直前の状態を加えたレポートを作る(バックトレースは含まない)
+pub fn report_text(message: &str) -> String {
+    let context = context();
+    let mut report = String::new();
+    let now = chrono::Local::now().to_rfc3339();
+    let _ = writeln!(report, "{} crashed at {now}", context.app_name);
+    let _ = writeln!(report, "{message}");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Bevy: {BEVY_VERSION}");
+    let _ = writeln!(report, "OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "Args: {:?}", std::env::args().collect::<Vec<_>>());
+    let _ = writeln!(report, "Frame: {}", context.frame);
+    let _ = writeln!(report, "Entities: {}", context.entities);
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Resources:");
+    for (name, value) in &context.resources {
+        let _ = writeln!(report, "  {name}: {value}");
+    }
+    report
+}
+
+/// panicフックを設定する(標準のフックでメッセージを出力した後にレポートを作る)
+fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if REPORTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut report = report_text(&info.to_string());
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        let _ = write!(report, "\nBacktrace:\n{backtrace}");
+
+        #[cfg(target_arch = "wasm32")]
+        error!("{report}"); // Webではブラウザのコンソールに出力する
+
+        #[cfg(not(target_arch = "wasm32"))]
+        match save_report(&report) {
+            Ok(path) => {
+                eprintln!("クラッシュレポートを保存しました: {}", path.display());
+                if context().has_window {
+                    show_report_in_new_process(&path);
+                }
+            }
+            Err(err) => eprintln!("クラッシュレポートを保存できません: {err}\n{report}"),
+        }
+    }));
+}
+
+/// レポートをファイルに書き出し、そのパスを返す
+#[cfg(not(target_arch = "wasm32"))]
+fn save_report(report: &str) -> std::io::Result<PathBuf> {
+    let dir = PathBuf::from(REPORT_ROOT).join(&context().app_name);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}.txt",
+        chrono::Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// 同じ実行ファイルをエラー画面の表示用に起動し、閉じられるまで待つ
+#[cfg(not(target_arch = "wasm32"))]
+fn show_report_in_new_process(path: &Path) {
+    let result = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .arg(REPORT_ARG)
+            .arg(path)
+            .status()
+    });
+    if let Err(err) = result {
+        eprintln!("エラー画面を表示できません: {err}");
+    }
+}
+
+/// `--crash-report`で指定されたレポートのパスを返す
+pub fn report_path_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == REPORT_ARG {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// レポートの要約を表示するだけのアプリを実行する(Escかウィンドウを閉じると終了する)
+pub fn show_report(path: &Path) -> AppExit {
+    let report = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| format!("Failed to read {}: {err}", path.display()));
+    // バックトレースは長いので画面には出さない(ファイルを開けば読める)
+    let summary = report
+        .split("\nBacktrace:")
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let text = format!(
+        "The app crashed.\n\n{summary}\nThe full report with the backtrace was saved to {}\n\nPress Esc to close.",
+        path.display()
+    );
+
+    App::new()
+        .add_plugins(
+            crate::platform::windowed_plugins()
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Crash report".into(),
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .disable::<CrashReportPlugin>(), // エラー画面がpanicしても、さらに起動しない
+        )
+        .add_systems(Startup, move |commands: Commands| {
+            spawn_crash_overlay(commands, &text)
+        })
+        .add_systems(Update, close_on_escape)
+        .run()
+}
+
+/// エラー画面を作成する
+fn spawn_crash_overlay(mut commands: Commands, text: &str) {
+    commands.spawn(Camera2d);
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(24.0)),
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.35, 0.05, 0.05)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(text),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Escキーでエラー画面を閉じるシステム
+fn close_on_escape(keyboard: Res<ButtonInput<KeyCode>>, mut exit: EventWriter<AppExit>) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        exit.write(AppExit::Success);
+    }
+}
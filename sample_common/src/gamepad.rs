@@ -0,0 +1,126 @@
+//! ゲームパッドの共通処理
+//!
+//! - 接続・切断をログに出し、最後に操作されたゲームパッドを`ActiveGamepad`として覚える
+//! - 接続されたゲームパッドのスティックにデッドゾーンを設定する
+//! - `Rumble`イベントを送ると、操作中のゲームパッドを振動させる
+//!
+//! ボタンやスティックをアクションに割り当てる処理は`actions`モジュールが行う。
+
+use std::time::Duration;
+
+use bevy::input::InputSystem;
+use bevy::input::gamepad::{
+    AxisSettings, GamepadConnection, GamepadConnectionEvent, GamepadRumbleIntensity,
+    GamepadRumbleRequest, GamepadSettings,
+};
+use bevy::prelude::*;
+
+/// スティックのデッドゾーン(これより小さい傾きは0として扱う)
+const STICK_DEAD_ZONE: f32 = 0.15;
+
+/// ゲームパッドの接続管理・デッドゾーン・振動を提供するプラグイン
+pub struct GamepadSupportPlugin;
+
+impl Plugin for GamepadSupportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveGamepad>()
+            .add_event::<Rumble>()
+            .add_systems(
+                PreUpdate,
+                (handle_connections, track_active_gamepad)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(Update, forward_rumble);
+    }
+}
+
+/// 最後に操作されたゲームパッド(接続されていなければNone)
+#[derive(Resource, Default)]
+pub struct ActiveGamepad(pub Option<Entity>);
+
+/// 操作中のゲームパッドを振動させるイベント
+/// ゲームパッドが接続されていない場合や、振動に対応していない場合は何もしない
+#[derive(Event, Clone, Copy)]
+pub struct Rumble {
+    pub intensity: GamepadRumbleIntensity, // 振動の強さ
+    pub duration: Duration,                // 振動する時間
+}
+
+impl Rumble {
+    /// 弱いモーターで短く振動させる(ボタンを押した手応えなど)
+    pub fn light(seconds: f32) -> Self {
+        Self {
+            intensity: GamepadRumbleIntensity::weak_motor(0.5),
+            duration: Duration::from_secs_f32(seconds),
+        }
+    }
+
+    /// 両方のモーターを最大で振動させる(ダメージやゲームオーバーなど)
+    pub fn heavy(seconds: f32) -> Self {
+        Self {
+            intensity: GamepadRumbleIntensity::MAX,
+            duration: Duration::from_secs_f32(seconds),
+        }
+    }
+}
+
+/// 接続・切断を処理するシステム
+fn handle_connections(
+    mut commands: Commands,
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected { name, .. } => {
+                info!("ゲームパッドが接続されました: {name}");
+                commands.entity(event.gamepad).insert(GamepadSettings {
+                    default_axis_settings: stick_settings(),
+                    ..default()
+                });
+                // まだ操作中のゲームパッドがなければ、これを使う
+                active.0.get_or_insert(event.gamepad);
+            }
+            GamepadConnection::Disconnected => {
+                info!("ゲームパッドが切断されました");
+                if active.0 == Some(event.gamepad) {
+                    active.0 = None;
+                }
+            }
+        }
+    }
+}
+
+/// デッドゾーンを設定したスティックの設定
+fn stick_settings() -> AxisSettings {
+    AxisSettings::new(-1.0, -STICK_DEAD_ZONE, STICK_DEAD_ZONE, 1.0, 0.01)
+        .expect("デッドゾーンの設定値が不正です")
+}
+
+/// ボタンが押されたゲームパッドを操作中のものとして記録するシステム
+fn track_active_gamepad(gamepads: Query<(Entity, &Gamepad)>, mut active: ResMut<ActiveGamepad>) {
+    for (entity, gamepad) in gamepads.iter() {
+        if gamepad.get_just_pressed().next().is_some() && active.0 != Some(entity) {
+            active.0 = Some(entity);
+        }
+    }
+}
+
+/// `Rumble`イベントを操作中のゲームパッドへの振動リクエストに変換するシステム
+fn forward_rumble(
+    mut events: EventReader<Rumble>,
+    active: Res<ActiveGamepad>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    for rumble in events.read() {
+        let Some(gamepad) = active.0 else {
+            continue;
+        };
+        requests.write(GamepadRumbleRequest::Add {
+            gamepad,
+            intensity: rumble.intensity,
+            duration: rumble.duration,
+        });
+    }
+}
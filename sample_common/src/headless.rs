@@ -0,0 +1,115 @@
+//! ウィンドウなしでスケジュールだけを回すスモークテスト用の起動モード
+//!
+//! `--headless --frames N` を付けて起動すると、ウィンドウやGPUを使わずに
+//! Nフレームだけスケジュールを実行して終了する。
+//! システム内でpanicした場合は、そのまま非ゼロの終了コードでプロセスが終わるので、
+//! CIやスクリプトから各サンプルが最低限動くかを確認できる。
+
+use std::time::Duration;
+
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+
+/// `--frames` が指定されなかったときに実行するフレーム数
+const DEFAULT_HEADLESS_FRAMES: u32 = 60;
+
+/// コマンドライン引数を見て、通常のDefaultPlugins(`platform::windowed_plugins`)か
+/// ヘッドレス用のプラグイン群を返す
+/// 各サンプルの`main`では`DefaultPlugins`の代わりにこれを追加する
+pub fn default_plugins() -> PluginGroupBuilder {
+    // panic後に`--crash-report`付きで起動された場合は、エラー画面だけを表示して終了する
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = crate::crash::report_path_from_args(std::env::args().skip(1)) {
+        let exit = crate::crash::show_report(&path);
+        std::process::exit(if exit.is_success() { 0 } else { 1 });
+    }
+
+    match headless_frames_from_args(std::env::args().skip(1)) {
+        Some(frames) => headless_plugins(Some(frames)),
+        None => crate::platform::windowed_plugins(),
+    }
+}
+
+/// ウィンドウとレンダラーを無効にしたDefaultPluginsを返す
+/// `frames`を指定すると、そのフレーム数を実行した時点でアプリを終了する
+pub fn headless_plugins(frames: Option<u32>) -> PluginGroupBuilder {
+    let plugins = DefaultPlugins
+        .build()
+        .disable::<WinitPlugin>() // ウィンドウのイベントループは使わない
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .set(RenderPlugin {
+            // GPUを初期化しない(アセットやメッシュはメインワールドで扱える)
+            render_creation: RenderCreation::Automatic(WgpuSettings {
+                backends: None,
+                ..default()
+            }),
+            ..default()
+        })
+        .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO)) // 待ち時間なしでフレームを回す
+        .add(crate::QualityPlugin)
+        .add(crate::GamepadSupportPlugin)
+        .add(crate::CrashReportPlugin::default());
+
+    match frames {
+        Some(frames) => plugins.add(ExitAfterFramesPlugin { frames }),
+        None => plugins,
+    }
+}
+
+/// `--headless` が指定されていればフレーム数を返す
+fn headless_frames_from_args(mut args: impl Iterator<Item = String>) -> Option<u32> {
+    let mut headless = false;
+    let mut frames = DEFAULT_HEADLESS_FRAMES;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--frames" => {
+                frames = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--frames には正の整数を指定してください");
+            }
+            _ => {}
+        }
+    }
+
+    headless.then_some(frames)
+}
+
+/// 指定フレーム数を実行したらアプリを終了するプラグイン
+struct ExitAfterFramesPlugin {
+    frames: u32,
+}
+
+/// 実行済みのフレーム数を数えるリソース
+#[derive(Resource)]
+struct HeadlessFrames {
+    remaining: u32, // 終了までの残りフレーム数
+}
+
+impl Plugin for ExitAfterFramesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HeadlessFrames {
+            remaining: self.frames,
+        })
+        .add_systems(Last, exit_after_frames);
+    }
+}
+
+/// 残りフレーム数が0になったら正常終了する
+fn exit_after_frames(mut frames: ResMut<HeadlessFrames>, mut exit: EventWriter<AppExit>) {
+    frames.remaining = frames.remaining.saturating_sub(1);
+    if frames.remaining == 0 {
+        info!("ヘッドレス実行が完了しました");
+        exit.write(AppExit::Success);
+    }
+}
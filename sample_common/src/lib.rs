@@ -0,0 +1,28 @@
+//! サンプルアプリ共通のプラグイン集
+//!
+//! 各サンプルアプリから必要なプラグインだけを選んで追加して使う。
+
+pub mod actions;
+pub mod app_state;
+pub mod config;
+pub mod crash;
+pub mod gamepad;
+pub mod headless;
+pub mod locale;
+pub mod platform;
+pub mod post_fx;
+pub mod screenshot;
+pub mod snapshot;
+pub mod time_control;
+
+pub use actions::{ActionAxes, ActionMap, ActionsPlugin};
+pub use app_state::{AppState, AppStatePlugin, LoadingAssets};
+pub use config::{ConfigPlugin, LoadConfig};
+pub use crash::{CrashReportPlugin, CrashWatchPlugin};
+pub use gamepad::{GamepadSupportPlugin, Rumble};
+pub use locale::{Locale, LocalizationPlugin, LocalizedText};
+pub use platform::{QualityLevel, QualityPlugin};
+pub use post_fx::{PostFxKeys, PostFxPlugin, PostFxSettings};
+pub use screenshot::ScreenshotPlugin;
+pub use snapshot::Snapshot;
+pub use time_control::{PauseState, TimeControlPlugin, TimeScale};
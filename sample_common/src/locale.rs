@@ -0,0 +1,245 @@
+//! UIテキストの多言語対応(英語・日本語)
+//!
+//! 各サンプルは言語ごとに「キー → 文章」の対応表をRONファイルで用意し、
+//! `include_str!`で埋め込んで`LocalizationPlugin`に渡す。
+//! `sample_common`のプラグインが使う文章(`sample_common/assets/locale`)は最初から含まれている。
+//!
+//! ```ron
+//! {
+//!     "help": "Press {key} to restart",
+//! }
+//! ```
+//!
+//! UIのテキストには`Text`の代わりに`LocalizedText`を付ける。
+//! `Locale`が変わったときや`LocalizedText`が変更されたときに、`Text`が現在の言語の文章に更新される。
+//! 文章中の`{名前}`は`LocalizedText`の引数で置き換えられる。
+//!
+//! F2キーで言語を切り替える。
+//! 標準のフォントには日本語の文字がないので、日本語に切り替えたときは
+//! `assets/fonts/NotoSansJP-Regular.otf`を読み込んで使う(ファイルがなければ標準のフォントのまま)。
+
+use std::collections::HashMap;
+
+use bevy::asset::{LoadState, ron};
+use bevy::prelude::*;
+
+/// 日本語の表示に使うフォント(各サンプルのassetsディレクトリからの相対パス)
+const JAPANESE_FONT_PATH: &str = "fonts/NotoSansJP-Regular.otf";
+
+/// `sample_common`のプラグイン(メニューや設定パネルなど)が使う文章の対応表
+const COMMON_BUNDLES: [(Locale, &str); 2] = [
+    (Locale::En, include_str!("../assets/locale/en.ron")),
+    (Locale::Ja, include_str!("../assets/locale/ja.ron")),
+];
+
+/// 言語を切り替えるキー
+const SWITCH_KEY: KeyCode = KeyCode::F2;
+
+/// 表示する言語
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    En, // 英語
+    Ja, // 日本語
+}
+
+impl Default for Locale {
+    /// ネイティブでは環境変数`LANG`が日本語なら日本語、それ以外は英語
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if std::env::var("LANG").is_ok_and(|lang| lang.starts_with("ja")) {
+            return Locale::Ja;
+        }
+        Locale::En
+    }
+}
+
+impl Locale {
+    /// 次の言語(F2キーで切り替える順番)
+    pub fn next(self) -> Self {
+        match self {
+            Locale::En => Locale::Ja,
+            Locale::Ja => Locale::En,
+        }
+    }
+}
+
+/// 言語ごとの「キー → 文章」の対応表
+#[derive(Resource, Default)]
+pub struct Translations {
+    bundles: HashMap<Locale, HashMap<String, String>>,
+}
+
+impl Translations {
+    /// RON形式の対応表を追加する(同じキーは後から追加したもので上書きする)
+    pub fn add_bundle(&mut self, locale: Locale, source: &str) -> Result<(), ron::error::SpannedError> {
+        let bundle: HashMap<String, String> = ron::from_str(source)?;
+        self.bundles.entry(locale).or_default().extend(bundle);
+        Ok(())
+    }
+
+    /// キーに対応する文章を返す
+    /// その言語に文章がなければ英語の文章を、英語にもなければキーをそのまま返す
+    pub fn get<'a>(&'a self, locale: Locale, key: &'a str) -> &'a str {
+        [locale, Locale::En]
+            .iter()
+            .find_map(|locale| self.bundles.get(locale)?.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// 引数を埋め込んだ`LocalizedText`の文章を作る
+    pub fn format(&self, locale: Locale, text: &LocalizedText) -> String {
+        let mut result = self.get(locale, &text.key).to_owned();
+        for (name, arg) in &text.args {
+            let value = match arg {
+                TextArg::Literal(value) => value.as_str(),
+                TextArg::Key(key) => self.get(locale, key),
+            };
+            result = result.replace(&format!("{{{name}}}"), value);
+        }
+        result
+    }
+}
+
+/// 現在の言語の文章を表示するテキスト
+#[derive(Component, Clone, Debug, PartialEq)]
+#[require(Text)]
+pub struct LocalizedText {
+    pub key: String,                             // 対応表のキー
+    pub args: Vec<(&'static str, TextArg)>, // 文章中の`{名前}`を置き換える値
+}
+
+/// `LocalizedText`の引数
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextArg {
+    Literal(String), // そのまま埋め込む値(数値やファイル名など)
+    Key(String),     // 対応表のキー(現在の言語の文章を埋め込む)
+}
+
+impl LocalizedText {
+    /// キーを指定して作成する
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// `{name}`をそのままの値で置き換える
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, TextArg::Literal(value.to_string())));
+        self
+    }
+
+    /// `{name}`を別のキーの文章で置き換える
+    pub fn with_key_arg(mut self, name: &'static str, key: impl Into<String>) -> Self {
+        self.args.push((name, TextArg::Key(key.into())));
+        self
+    }
+}
+
+/// 多言語対応のプラグイン
+pub struct LocalizationPlugin {
+    bundles: Vec<(Locale, &'static str)>, // 言語とRON形式の対応表
+}
+
+impl LocalizationPlugin {
+    /// 共通の対応表だけを持つプラグインを作成する(サンプルの対応表は`bundle`で追加していく)
+    pub fn new() -> Self {
+        Self {
+            bundles: COMMON_BUNDLES.to_vec(),
+        }
+    }
+
+    /// 言語の対応表を追加する(`include_str!`で埋め込んだRONファイルを想定)
+    pub fn bundle(mut self, locale: Locale, source: &'static str) -> Self {
+        self.bundles.push((locale, source));
+        self
+    }
+}
+
+impl Default for LocalizationPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        let mut translations = Translations::default();
+        for (locale, source) in &self.bundles {
+            // 埋め込んだファイルの誤りなので、起動時に気付けるようにする
+            translations
+                .add_bundle(*locale, source)
+                .unwrap_or_else(|err| panic!("{locale:?}の対応表を読み込めません: {err}"));
+        }
+
+        app.insert_resource(translations)
+            .init_resource::<Locale>()
+            .init_resource::<JapaneseFont>()
+            .add_systems(
+                Update,
+                (switch_locale, load_japanese_font, update_localized_text).chain(),
+            );
+    }
+}
+
+/// 日本語のフォントの読み込み状態
+#[derive(Resource, Default)]
+enum JapaneseFont {
+    #[default]
+    NotLoaded, // まだ読み込んでいない
+    Loading(Handle<Font>), // 読み込み中、または読み込み済み
+    Missing, // ファイルがないので標準のフォントを使う
+}
+
+/// F2キーで言語を切り替えるシステム
+fn switch_locale(keyboard: Res<ButtonInput<KeyCode>>, mut locale: ResMut<Locale>) {
+    if keyboard.just_pressed(SWITCH_KEY) {
+        *locale = locale.next();
+        info!("言語を切り替えました: {:?}", *locale);
+    }
+}
+
+/// 日本語が選ばれたら、日本語のフォントを読み込むシステム
+fn load_japanese_font(
+    locale: Res<Locale>,
+    asset_server: Res<AssetServer>,
+    mut font: ResMut<JapaneseFont>,
+) {
+    match &*font {
+        JapaneseFont::NotLoaded if *locale == Locale::Ja => {
+            *font = JapaneseFont::Loading(asset_server.load(JAPANESE_FONT_PATH));
+        }
+        JapaneseFont::Loading(handle) => {
+            if let LoadState::Failed(_) = asset_server.load_state(handle) {
+                warn!("{JAPANESE_FONT_PATH}がないため、日本語の文字が表示されない場合があります");
+                *font = JapaneseFont::Missing;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 言語や`LocalizedText`が変わったときに、テキストを更新するシステム
+fn update_localized_text(
+    locale: Res<Locale>,
+    translations: Res<Translations>,
+    font: Res<JapaneseFont>,
+    mut texts: Query<(Ref<LocalizedText>, &mut Text, &mut TextFont)>,
+) {
+    let refresh_all = locale.is_changed() || translations.is_changed() || font.is_changed();
+    let font_handle = match (&*font, *locale) {
+        (JapaneseFont::Loading(handle), Locale::Ja) => handle.clone(),
+        _ => Handle::default(), // 標準のフォント
+    };
+
+    for (localized, mut text, mut text_font) in texts.iter_mut() {
+        if !refresh_all && !localized.is_changed() {
+            continue;
+        }
+        text.0 = translations.format(*locale, &localized);
+        if text_font.font != font_handle {
+            text_font.font = font_handle.clone();
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! 実行環境(ネイティブ / Web)ごとの違いを吸収する設定
+//!
+//! - Webではページ内の`<canvas id="bevy">`に描画し、ウィンドウサイズを親要素に合わせる
+//! - Webでは`.meta`ファイルを探しに行かない(存在しないファイルへのリクエストを減らす)
+//! - `QualityLevel`リソースで描画品質を切り替える。WebGL2では最初から低品質で始め、
+//!   ネイティブでもフレームレートが出ない場合は実行中に低品質へ落とす。
+//!   低品質では全ライトの影を切る。それ以外の調整は各サンプルが行う
+
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+/// 低品質に切り替えるフレーム時間のしきい値(秒)。30fps未満が続くと切り替える
+const LOW_QUALITY_FRAME_TIME: f32 = 1.0 / 30.0;
+
+/// しきい値を超えた状態がこの秒数続いたら低品質に切り替える
+const LOW_QUALITY_GRACE_SECONDS: f32 = 3.0;
+
+/// 起動直後はシェーダーのコンパイルなどで遅くなるので、この秒数は判定しない
+const WARMUP_SECONDS: f32 = 5.0;
+
+/// 実行環境に合わせて設定したDefaultPluginsを返す
+/// (`QualityPlugin`と`GamepadSupportPlugin`、`CrashReportPlugin`も含む)
+pub fn windowed_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .build()
+        .set(WindowPlugin {
+            primary_window: Some(primary_window()),
+            ..default()
+        })
+        .set(asset_plugin())
+        .add(QualityPlugin)
+        .add(crate::GamepadSupportPlugin)
+        .add(crate::CrashReportPlugin::default())
+}
+
+/// メインウィンドウの設定
+fn primary_window() -> Window {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Window {
+            canvas: Some("#bevy".into()),          // index.html内のcanvasに描画する
+            fit_canvas_to_parent: true,            // 親要素のサイズに合わせる
+            prevent_default_event_handling: false, // ブラウザのショートカットを奪わない
+            ..default()
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Window::default()
+    }
+}
+
+/// アセット読み込みの設定
+fn asset_plugin() -> AssetPlugin {
+    #[cfg(target_arch = "wasm32")]
+    {
+        AssetPlugin {
+            // Webサーバーには.metaファイルを置かないので確認しない
+            meta_check: bevy::asset::AssetMetaCheck::Never,
+            ..default()
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        AssetPlugin::default()
+    }
+}
+
+/// 描画品質を表すリソース
+/// 各サンプルは変更を検知して、影やボリューメトリック効果、表示数などを調整する
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QualityLevel {
+    High, // 通常品質
+    Low,  // 低品質(WebGL2や低速な環境向け)
+}
+
+impl Default for QualityLevel {
+    fn default() -> Self {
+        if cfg!(target_arch = "wasm32") {
+            QualityLevel::Low
+        } else {
+            QualityLevel::High
+        }
+    }
+}
+
+impl QualityLevel {
+    /// 低品質かどうか
+    pub fn is_low(&self) -> bool {
+        *self == QualityLevel::Low
+    }
+}
+
+/// `QualityLevel`を管理し、遅い環境では実行中に低品質へ切り替えるプラグイン
+pub struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QualityLevel>()
+            .init_resource::<SlowFrameTimer>()
+            .add_systems(
+                Update,
+                (
+                    fall_back_on_slow_frames,
+                    disable_shadows.run_if(resource_equals(QualityLevel::Low)),
+                ),
+            );
+    }
+}
+
+/// フレーム時間がしきい値を超え続けている秒数
+#[derive(Resource, Default)]
+struct SlowFrameTimer(f32);
+
+/// フレームレートが低い状態が続いたら低品質に切り替えるシステム
+fn fall_back_on_slow_frames(
+    time: Res<Time<Real>>,
+    mut slow: ResMut<SlowFrameTimer>,
+    mut quality: ResMut<QualityLevel>,
+) {
+    if quality.is_low() || time.elapsed_secs() < WARMUP_SECONDS {
+        return;
+    }
+
+    let dt = time.delta_secs();
+    if dt > LOW_QUALITY_FRAME_TIME {
+        slow.0 += dt;
+    } else {
+        slow.0 = 0.0;
+    }
+
+    if slow.0 > LOW_QUALITY_GRACE_SECONDS {
+        warn!("フレームレートが低いため、低品質モードに切り替えます");
+        *quality = QualityLevel::Low;
+    }
+}
+
+/// 低品質のときはすべてのライトの影を無効にするシステム
+fn disable_shadows(
+    mut directional_lights: Query<&mut DirectionalLight>,
+    mut point_lights: Query<&mut PointLight>,
+    mut spot_lights: Query<&mut SpotLight>,
+) {
+    // 変更検知を無駄に発生させないよう、影が有効なライトだけ書き換える
+    for mut light in directional_lights.iter_mut() {
+        if light.shadows_enabled {
+            light.shadows_enabled = false;
+        }
+    }
+    for mut light in point_lights.iter_mut() {
+        if light.shadows_enabled {
+            light.shadows_enabled = false;
+        }
+    }
+    for mut light in spot_lights.iter_mut() {
+        if light.shadows_enabled {
+            light.shadows_enabled = false;
+        }
+    }
+}
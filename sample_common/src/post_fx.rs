@@ -0,0 +1,317 @@
+//! ポストプロセス(ブルーム・トーンマッピング・MSAA・ビネット)の共通設定
+//!
+//! `PostFxSettings`リソースの値を、すべての3Dカメラにまとめて反映する。
+//! 各サンプルはカメラにBloomなどを直接付けず、初期値を変えたい場合は`PostFxSettings`を挿入する。
+//! ビネット(画面の周辺を暗くする効果)はUIの画像を画面全体に重ねて表現する。
+//!
+//! キー操作(各サンプル共通)
+//! - F3: 設定パネルの表示・非表示(F3を別の機能に使うサンプルは`PostFxKeys`で変える)
+//! - [ / ]: ブルームの強さを下げる・上げる(0で無効、他の操作と重なるサンプルは`PostFxKeys`で変える)
+//! - Shift + [ / ]: ブルームのしきい値を下げる・上げる(しきい値より暗い部分はにじませないので、明るい点が白飛びしにくくなる)
+//! - F4: トーンマッピングの方式を切り替える
+//! - F7: MSAAのサンプル数を切り替える
+//! - F8: ビネットの有効・無効
+
+use bevy::asset::RenderAssetUsages;
+use bevy::core_pipeline::bloom::{Bloom, BloomPrefilter};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::view::Msaa;
+
+use crate::locale::LocalizedText;
+
+/// ブルームの強さを1回のキー操作で変える量
+const BLOOM_STEP: f32 = 0.05;
+
+/// ブルームの強さの上限
+const MAX_BLOOM_INTENSITY: f32 = 1.0;
+
+/// ブルームのしきい値を1回のキー操作で変える量
+const THRESHOLD_STEP: f32 = 0.1;
+
+/// ブルームのしきい値の上限(HDRなので1より明るい部分もある)
+const MAX_BLOOM_THRESHOLD: f32 = 2.0;
+
+/// しきい値の前後でブルームを徐々に効かせる幅の割合(0ならしきい値で急に切り替わる)
+const THRESHOLD_SOFTNESS: f32 = 0.5;
+
+/// ビネットの画像の一辺のピクセル数(画面全体に引き伸ばすので小さくてよい)
+const VIGNETTE_SIZE: u32 = 128;
+
+/// ビネットの中心で最も暗くなる部分の不透明度
+const VIGNETTE_STRENGTH: f32 = 0.6;
+
+/// F4キーで切り替えるトーンマッピングの方式(順番)
+const TONEMAPPINGS: [Tonemapping; 8] = [
+    Tonemapping::None,
+    Tonemapping::Reinhard,
+    Tonemapping::ReinhardLuminance,
+    Tonemapping::AcesFitted,
+    Tonemapping::AgX,
+    Tonemapping::SomewhatBoringDisplayTransform,
+    Tonemapping::TonyMcMapface,
+    Tonemapping::BlenderFilmic,
+];
+
+/// F7キーで切り替えるMSAAのサンプル数(順番)
+const MSAA_LEVELS: [Msaa; 3] = [Msaa::Off, Msaa::Sample2, Msaa::Sample4];
+
+/// ポストプロセスの設定
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PostFxSettings {
+    pub bloom_intensity: f32,     // ブルームの強さ(0で無効)
+    pub bloom_threshold: f32,     // ブルームのしきい値(これより暗い部分はにじませない、0ですべて)
+    pub tonemapping: Tonemapping, // トーンマッピングの方式
+    pub msaa: Msaa,               // MSAAのサンプル数
+    pub vignette: bool,           // ビネットを表示するかどうか
+}
+
+impl Default for PostFxSettings {
+    fn default() -> Self {
+        Self {
+            bloom_intensity: Bloom::NATURAL.intensity,
+            bloom_threshold: Bloom::NATURAL.prefilter.threshold,
+            tonemapping: Tonemapping::TonyMcMapface,
+            msaa: Msaa::Sample4,
+            vignette: false,
+        }
+    }
+}
+
+/// ブルームの強さを変えるキーと、設定パネルを開閉するキー
+/// Shiftを押しながらブルームのキーを押すと、強さの代わりにしきい値を変える
+/// サンプル側の操作と重なる場合は、プラグインを追加した後に挿入して変える
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PostFxKeys {
+    pub bloom_down: KeyCode, // ブルームを弱めるキー
+    pub bloom_up: KeyCode,   // ブルームを強めるキー
+    pub label: &'static str, // 設定パネルに表示するブルームのキーの名前
+    pub panel: KeyCode,      // 設定パネルの表示・非表示を切り替えるキー
+}
+
+impl Default for PostFxKeys {
+    fn default() -> Self {
+        Self {
+            bloom_down: KeyCode::BracketLeft,
+            bloom_up: KeyCode::BracketRight,
+            label: "[ / ]",
+            panel: KeyCode::F3,
+        }
+    }
+}
+
+/// ポストプロセスの設定とキー操作、設定パネルを提供するプラグイン
+/// パネルの文章は`LocalizedText`で表示するので、`LocalizationPlugin`と一緒に使う
+pub struct PostFxPlugin;
+
+impl Plugin for PostFxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PostFxSettings>()
+            .init_resource::<PostFxKeys>()
+            .add_systems(Startup, spawn_overlays)
+            .add_systems(
+                Update,
+                (
+                    handle_post_fx_keys,
+                    apply_to_cameras,
+                    apply_vignette.run_if(resource_changed::<PostFxSettings>),
+                    update_panel.run_if(
+                        resource_changed::<PostFxSettings>.or(resource_changed::<PostFxKeys>),
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// ビネットの画像のマーカーコンポーネント
+#[derive(Component)]
+struct Vignette;
+
+/// 設定パネルのマーカーコンポーネント
+#[derive(Component)]
+struct PostFxPanel;
+
+/// ビネットと設定パネルを作成する
+fn spawn_overlays(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        ImageNode::new(images.add(vignette_image())),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        GlobalZIndex(-1), // 各サンプルのUIより奥に表示する
+        Visibility::Hidden,
+        Vignette,
+    ));
+
+    commands.spawn((
+        LocalizedText::new("post_fx.panel"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Visibility::Hidden,
+        PostFxPanel,
+    ));
+}
+
+/// 中心が透明で、周辺に向かって暗くなる画像を作る
+fn vignette_image() -> Image {
+    let mut data = Vec::with_capacity((VIGNETTE_SIZE * VIGNETTE_SIZE * 4) as usize);
+    let center = (VIGNETTE_SIZE as f32 - 1.0) / 2.0;
+    for y in 0..VIGNETTE_SIZE {
+        for x in 0..VIGNETTE_SIZE {
+            // 中心からの距離(画面の角で約1.4、辺の中央で1.0)
+            let offset = (Vec2::new(x as f32, y as f32) - center) / center;
+            let darkness = ((offset.length() - 0.6) / 0.8).clamp(0.0, 1.0);
+            let alpha = darkness * darkness * VIGNETTE_STRENGTH;
+            data.extend_from_slice(&[0, 0, 0, (alpha * 255.0) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// キー入力で設定を変えるシステム
+fn handle_post_fx_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    keys: Res<PostFxKeys>,
+    mut settings: ResMut<PostFxSettings>,
+    mut panels: Query<&mut Visibility, With<PostFxPanel>>,
+) {
+    if keyboard.just_pressed(keys.panel) {
+        for mut visibility in panels.iter_mut() {
+            visibility.toggle_visible_hidden();
+        }
+    }
+
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard.just_pressed(keys.bloom_down) {
+        if shift {
+            settings.bloom_threshold = (settings.bloom_threshold - THRESHOLD_STEP).max(0.0);
+        } else {
+            settings.bloom_intensity = (settings.bloom_intensity - BLOOM_STEP).max(0.0);
+        }
+    }
+    if keyboard.just_pressed(keys.bloom_up) {
+        if shift {
+            settings.bloom_threshold =
+                (settings.bloom_threshold + THRESHOLD_STEP).min(MAX_BLOOM_THRESHOLD);
+        } else {
+            settings.bloom_intensity =
+                (settings.bloom_intensity + BLOOM_STEP).min(MAX_BLOOM_INTENSITY);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::F4) {
+        settings.tonemapping = next_in(&TONEMAPPINGS, settings.tonemapping);
+    }
+    if keyboard.just_pressed(KeyCode::F7) {
+        settings.msaa = next_in(&MSAA_LEVELS, settings.msaa);
+    }
+    if keyboard.just_pressed(KeyCode::F8) {
+        settings.vignette = !settings.vignette;
+    }
+
+    if settings.is_changed() {
+        info!("ポストプロセスの設定: {:?}", *settings);
+    }
+}
+
+/// 一覧の中で次の値を返す(一覧にない値なら先頭)
+fn next_in<T: Copy + PartialEq>(values: &[T], current: T) -> T {
+    let index = values.iter().position(|value| *value == current);
+    values[index.map_or(0, |index| (index + 1) % values.len())]
+}
+
+/// 設定が変わったときと、カメラが追加されたときに、設定をカメラに反映するシステム
+fn apply_to_cameras(
+    mut commands: Commands,
+    settings: Res<PostFxSettings>,
+    mut cameras: Query<(Entity, &mut Camera, Ref<Camera3d>)>,
+) {
+    for (entity, mut camera, camera_3d) in cameras.iter_mut() {
+        if !settings.is_changed() && !camera_3d.is_added() {
+            continue;
+        }
+
+        let mut entity = commands.entity(entity);
+        entity.insert((settings.tonemapping, settings.msaa));
+        if settings.bloom_intensity > 0.0 {
+            camera.hdr = true; // ブルームにはHDRが必要
+            entity.insert(Bloom {
+                intensity: settings.bloom_intensity,
+                prefilter: BloomPrefilter {
+                    threshold: settings.bloom_threshold,
+                    threshold_softness: THRESHOLD_SOFTNESS,
+                },
+                ..Bloom::NATURAL
+            });
+        } else {
+            entity.remove::<Bloom>();
+        }
+    }
+}
+
+/// ビネットの表示を切り替えるシステム
+fn apply_vignette(
+    settings: Res<PostFxSettings>,
+    mut vignettes: Query<&mut Visibility, With<Vignette>>,
+) {
+    for mut visibility in vignettes.iter_mut() {
+        *visibility = if settings.vignette {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// 設定パネルの表示内容を更新するシステム
+fn update_panel(
+    settings: Res<PostFxSettings>,
+    keys: Res<PostFxKeys>,
+    mut panels: Query<&mut LocalizedText, With<PostFxPanel>>,
+) {
+    for mut text in panels.iter_mut() {
+        let mut panel = LocalizedText::new("post_fx.panel")
+            .with_arg("panel_key", format!("{:?}", keys.panel))
+            .with_arg("bloom_keys", keys.label)
+            .with_arg("bloom", format!("{:.2}", settings.bloom_intensity))
+            .with_arg("threshold", format!("{:.1}", settings.bloom_threshold))
+            .with_arg("tonemapping", format!("{:?}", settings.tonemapping))
+            .with_key_arg(
+                "vignette",
+                if settings.vignette {
+                    "post_fx.on"
+                } else {
+                    "post_fx.off"
+                },
+            );
+        panel = match settings.msaa {
+            Msaa::Off => panel.with_key_arg("msaa", "post_fx.off"),
+            msaa => panel.with_arg("msaa", format!("{}x", msaa.samples())),
+        };
+        *text = panel;
+    }
+}
@@ -0,0 +1,251 @@
+//! スクリーンショットと連番PNG録画の共通プラグイン
+//!
+//! `ScreenshotPlugin`を追加すると、F12キーで現在のフレームをPNGとして保存できる。
+//! 録画を有効にした場合はF11キーで開始・停止し、固定のタイムステップで
+//! 1フレームずつ連番PNGを書き出す(後から動画編集ソフトで繋げる想定)。
+//!
+//! 保存先は `screenshots/<アプリ名>/` で、ファイル名には撮影時刻が付く。
+//! Webではファイルシステムがないので、ブラウザのダウンロードとして保存される。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk};
+use bevy::time::TimeUpdateStrategy;
+
+/// 保存先のルートディレクトリ(実行時のカレントディレクトリからの相対パス)
+const OUTPUT_ROOT: &str = "screenshots";
+
+/// スクリーンショット撮影と連番録画を提供するプラグイン
+pub struct ScreenshotPlugin {
+    app_name: &'static str,      // 保存先ディレクトリとファイル名に使うアプリ名
+    recording_fps: Option<f64>, // 録画のフレームレート(Noneなら録画機能なし)
+}
+
+impl ScreenshotPlugin {
+    /// スクリーンショットのみを有効にしたプラグインを作成する
+    pub fn new(app_name: &'static str) -> Self {
+        Self {
+            app_name,
+            recording_fps: None,
+        }
+    }
+
+    /// 指定したフレームレートでの連番録画を有効にする
+    pub fn with_recorder(mut self, fps: f64) -> Self {
+        self.recording_fps = Some(fps);
+        self
+    }
+}
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScreenshotSettings {
+            app_name: self.app_name,
+            output_dir: PathBuf::from(OUTPUT_ROOT).join(self.app_name),
+            screenshot_key: KeyCode::F12,
+            record_key: KeyCode::F11,
+        })
+        .add_event::<ScreenshotSaved>()
+        .add_systems(Update, take_screenshot);
+
+        if let Some(fps) = self.recording_fps {
+            app.insert_resource(FrameRecorder::new(fps))
+                .add_systems(Update, (toggle_recording, record_frame).chain());
+        }
+    }
+}
+
+/// 保存先と操作キーの設定を保持するリソース
+#[derive(Resource)]
+pub struct ScreenshotSettings {
+    app_name: &'static str,
+    pub output_dir: PathBuf,    // 保存先ディレクトリ
+    pub screenshot_key: KeyCode, // スクリーンショットを撮るキー
+    pub record_key: KeyCode,     // 録画を開始・停止するキー
+}
+
+/// スクリーンショットの保存が完了したときに送られるイベント
+/// 画面上に確認メッセージを出したいアプリはこれを読む
+#[derive(Event)]
+pub struct ScreenshotSaved {
+    pub path: PathBuf,
+}
+
+/// 連番録画の状態を管理するリソース
+#[derive(Resource)]
+pub struct FrameRecorder {
+    fps: f64,                     // 書き出すフレームレート
+    session_dir: Option<PathBuf>, // 録画中の保存先(録画していなければNone)
+    frame: u32,                   // 次に書き出すフレーム番号
+}
+
+impl FrameRecorder {
+    fn new(fps: f64) -> Self {
+        Self {
+            fps,
+            session_dir: None,
+            frame: 0,
+        }
+    }
+
+    /// 現在録画中かどうか
+    pub fn is_recording(&self) -> bool {
+        self.session_dir.is_some()
+    }
+}
+
+/// ファイル名に使う現在時刻の文字列を作る
+fn timestamp() -> String {
+    chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+/// 保存先ディレクトリを作成する。失敗したらエラーを出してfalseを返す
+fn create_output_dir(dir: &Path) -> bool {
+    // Webではブラウザのダウンロードになるので、ディレクトリは不要
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        error!(
+            "保存先ディレクトリ {} を作成できません: {err}",
+            dir.display()
+        );
+        return false;
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = dir;
+    true
+}
+
+/// キー入力でスクリーンショットを撮るシステム
+fn take_screenshot(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<ScreenshotSettings>,
+) {
+    if !keyboard.just_pressed(settings.screenshot_key) {
+        return;
+    }
+
+    if !create_output_dir(&settings.output_dir) {
+        return;
+    }
+
+    let path = settings
+        .output_dir
+        .join(format!("{}-{}.png", settings.app_name, timestamp()));
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_and_notify(path));
+}
+
+/// 撮影した画像を保存し、成功したら`ScreenshotSaved`を送るオブザーバーを作る
+fn save_and_notify(
+    path: PathBuf,
+) -> impl FnMut(Trigger<ScreenshotCaptured>, EventWriter<ScreenshotSaved>) {
+    move |trigger, mut saved| match write_png(trigger.event().0.clone(), &path) {
+        Ok(()) => {
+            info!("スクリーンショットを保存しました: {}", path.display());
+            saved.write(ScreenshotSaved { path: path.clone() });
+        }
+        Err(err) => error!(
+            "スクリーンショット {} を保存できません: {err}",
+            path.display()
+        ),
+    }
+}
+
+/// 画像をPNGとして`path`に書き出す(Webではブラウザのダウンロードにする)
+fn write_png(image: Image, path: &Path) -> Result<(), String> {
+    // HDRのときにアルファに入る明るさは捨てる(そのままだと色がおかしく写る)
+    let image = image
+        .try_into_dynamic()
+        .map_err(|err| err.to_string())?
+        .to_rgb8();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        image
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|err| err.to_string())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|err| err.to_string())?;
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or("ファイル名が不正です")?;
+        download(&png.into_inner(), name).map_err(|err| format!("{err:?}"))
+    }
+}
+
+/// PNGのデータを、ブラウザに`name`としてダウンロードさせる
+#[cfg(target_arch = "wasm32")]
+fn download(png: &[u8], name: &str) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(png));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("documentがありません")?;
+    let link = document.create_element("a")?;
+    link.set_attribute("href", &url)?;
+    link.set_attribute("download", name)?;
+    link.dyn_into::<web_sys::HtmlElement>()?.click();
+    web_sys::Url::revoke_object_url(&url)
+}
+
+/// キー入力で連番録画を開始・停止するシステム
+fn toggle_recording(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<ScreenshotSettings>,
+    mut recorder: ResMut<FrameRecorder>,
+) {
+    if !keyboard.just_pressed(settings.record_key) {
+        return;
+    }
+
+    // 録画中なら停止して、時間の進み方を元に戻す
+    if recorder.session_dir.take().is_some() {
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        info!("録画を停止しました({}フレーム)", recorder.frame);
+        return;
+    }
+
+    let session_dir = settings
+        .output_dir
+        .join(format!("{}-{}", settings.app_name, timestamp()));
+    if !create_output_dir(&session_dir) {
+        return;
+    }
+
+    // 録画中は1フレームごとに固定時間だけ進める
+    // (書き出しが重くても、再生したときに正しい速さになる)
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(
+        Duration::from_secs_f64(1.0 / recorder.fps),
+    ));
+    info!("録画を開始しました: {}", session_dir.display());
+    recorder.session_dir = Some(session_dir);
+    recorder.frame = 0;
+}
+
+/// 録画中は毎フレームPNGを書き出すシステム
+fn record_frame(mut commands: Commands, mut recorder: ResMut<FrameRecorder>) {
+    let Some(session_dir) = recorder.session_dir.clone() else {
+        return;
+    };
+
+    let path = session_dir.join(format!("frame_{:05}.png", recorder.frame));
+    recorder.frame += 1;
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
@@ -0,0 +1,231 @@
+//! `DynamicScene`を使ったエンティティの保存・復元
+//!
+//! マーカーコンポーネントを持つエンティティのうち、許可したコンポーネントとリソースだけを
+//! RONファイルに書き出す。読み込み時は次のように現在のワールドへ反映する。
+//!
+//! - 同じ`Name`を持つ既存のエンティティには、保存したコンポーネントを上書きする
+//! - 対応するエンティティがなければ新しく生成する
+//! - ファイルに含まれていないマーカー付きのエンティティは削除する
+//!
+//! メッシュやマテリアル、コライダーなどのハンドルは保存できないので、
+//! 新しく生成されたエンティティには、各サンプルがマーカーの`OnAdd`オブザーバーで付け直す。
+//! 保存するコンポーネントとリソースは`Reflect`を実装し、型を登録しておく必要がある。
+//! Webではファイルを読み書きできないため、エラーになる。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::scene::ron;
+use bevy::scene::serde::SceneDeserializer;
+use bevy::scene::{DynamicSceneBuilder, SceneFilter, SceneSpawnError};
+use serde::de::DeserializeSeed;
+
+/// 保存先のルートディレクトリ(実行時のカレントディレクトリからの相対パス)
+const SAVE_ROOT: &str = "saves";
+
+/// 保存ファイルのパス(`saves/<アプリ名>/<名前>.scn.ron`)
+pub fn save_path(app_name: &str, name: &str) -> PathBuf {
+    PathBuf::from(SAVE_ROOT)
+        .join(app_name)
+        .join(format!("{name}.scn.ron"))
+}
+
+/// 保存・復元の失敗
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),     // ファイルの読み書きに失敗した
+    Ron(ron::Error),        // RONへの変換・RONからの変換に失敗した
+    Scene(SceneSpawnError), // ワールドへの書き込みに失敗した(型の登録漏れなど)
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "ファイルを読み書きできません: {err}"),
+            SnapshotError::Ron(err) => write!(f, "RONの変換に失敗しました: {err}"),
+            SnapshotError::Scene(err) => write!(f, "シーンを書き込めません: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<ron::Error> for SnapshotError {
+    fn from(err: ron::Error) -> Self {
+        SnapshotError::Ron(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for SnapshotError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        SnapshotError::Ron(err.code)
+    }
+}
+
+impl From<SceneSpawnError> for SnapshotError {
+    fn from(err: SceneSpawnError) -> Self {
+        SnapshotError::Scene(err)
+    }
+}
+
+/// 保存するコンポーネントとリソースの設定
+/// `Name`は復元時の対応付けに使うので、常に保存される
+#[derive(Clone)]
+pub struct Snapshot {
+    components: SceneFilter, // 保存するコンポーネント
+    resources: SceneFilter,  // 保存するリソース
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            components: SceneFilter::deny_all().allow::<Name>(),
+            resources: SceneFilter::deny_all(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// 何も保存しない設定を作成する(`allow`で保存するものを追加していく)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存するコンポーネントを追加する
+    pub fn allow<T: Component>(mut self) -> Self {
+        self.components = self.components.allow::<T>();
+        self
+    }
+
+    /// 保存するリソースを追加する
+    pub fn allow_resource<T: Resource>(mut self) -> Self {
+        self.resources = self.resources.allow::<T>();
+        self
+    }
+
+    /// マーカー`M`を持つエンティティをRONファイルに保存する
+    pub fn save<M: Component>(
+        &self,
+        world: &mut World,
+        path: impl AsRef<Path>,
+    ) -> Result<usize, SnapshotError> {
+        let entities: Vec<Entity> = world
+            .query_filtered::<Entity, With<M>>()
+            .iter(world)
+            .collect();
+
+        let scene = DynamicSceneBuilder::from_world(world)
+            .with_component_filter(self.components.clone().allow::<M>())
+            .with_resource_filter(self.resources.clone())
+            .extract_entities(entities.iter().copied())
+            .extract_resources()
+            .build();
+
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let serialized = scene.serialize(&registry)?;
+        drop(registry);
+
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serialized)?;
+        Ok(entities.len())
+    }
+
+    /// RONファイルを読み込み、マーカー`M`を持つエンティティとリソースに反映する
+    pub fn load<M: Component>(
+        &self,
+        world: &mut World,
+        path: impl AsRef<Path>,
+    ) -> Result<usize, SnapshotError> {
+        let text = std::fs::read_to_string(path)?;
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let scene = {
+            let registry = registry.read();
+            let mut deserializer = ron::Deserializer::from_str(&text)?;
+            SceneDeserializer {
+                type_registry: &registry,
+            }
+            .deserialize(&mut deserializer)
+            .map_err(|err| deserializer.span_error(err))?
+        };
+
+        // 今あるマーカー付きのエンティティを名前で引けるようにする
+        let mut existing: HashMap<String, Entity> = HashMap::new();
+        let mut unnamed = Vec::new();
+        for (entity, name) in world
+            .query_filtered::<(Entity, Option<&Name>), With<M>>()
+            .iter(world)
+        {
+            match name {
+                Some(name) => {
+                    existing.insert(name.as_str().to_owned(), entity);
+                }
+                None => unnamed.push(entity),
+            }
+        }
+
+        // 同じ名前のエンティティがあれば、そこに上書きするよう対応付ける
+        let mut entity_map = EntityHashMap::default();
+        for saved in &scene.entities {
+            let name = saved
+                .components
+                .iter()
+                .filter(|component| component.represents::<Name>())
+                .find_map(|component| Name::from_reflect(component.as_ref()));
+            if let Some(target) = name.and_then(|name| existing.remove(name.as_str())) {
+                entity_map.insert(saved.entity, target);
+            }
+        }
+
+        // ファイルに含まれていないエンティティは削除する
+        for entity in existing.into_values().chain(unnamed) {
+            world.despawn(entity);
+        }
+
+        scene.write_to_world(world, &mut entity_map)?;
+        Ok(scene.entities.len())
+    }
+
+    /// `save`を実行し、結果をログに出すコマンドを作る
+    pub fn save_command<M: Component>(
+        &self,
+        path: PathBuf,
+    ) -> impl FnOnce(&mut World) + Send + 'static {
+        let snapshot = self.clone();
+        move |world: &mut World| match snapshot.save::<M>(world, &path) {
+            Ok(count) => info!(
+                "{}個のエンティティを保存しました: {}",
+                count,
+                path.display()
+            ),
+            Err(err) => error!("{} を保存できません: {err}", path.display()),
+        }
+    }
+
+    /// `load`を実行し、結果をログに出すコマンドを作る
+    pub fn load_command<M: Component>(
+        &self,
+        path: PathBuf,
+    ) -> impl FnOnce(&mut World) + Send + 'static {
+        let snapshot = self.clone();
+        move |world: &mut World| match snapshot.load::<M>(world, &path) {
+            Ok(count) => info!(
+                "{}個のエンティティを復元しました: {}",
+                count,
+                path.display()
+            ),
+            Err(err) => error!("{} を読み込めません: {err}", path.display()),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! 一時停止とスローモーション(時間の倍率)の共通処理
+//!
+//! `TimeScale`と`PauseState`を`Time<Virtual>`に反映するので、`Res<Time>`の経過時間を使う
+//! システムや物理エンジンは、何もしなくても一時停止・スローモーションに従う。
+//! 入力の処理など、経過時間を使わないシステムは`is_running`を実行条件に付けて止める。
+//!
+//! キー操作(各サンプル共通)
+//! - P: 一時停止・再開
+//! - -(マイナス)/ =(イコール): 時間の倍率を半分・2倍にする
+//! - 0: 時間の倍率を1倍に戻す
+
+use bevy::prelude::*;
+
+/// 時間の倍率の下限
+pub const MIN_TIME_SCALE: f32 = 0.125;
+/// 時間の倍率の上限
+pub const MAX_TIME_SCALE: f32 = 4.0;
+
+/// 一時停止とスローモーションを提供するプラグイン
+pub struct TimeControlPlugin;
+
+impl Plugin for TimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .init_resource::<PauseState>()
+            .add_systems(
+                PreUpdate,
+                (
+                    handle_time_keys,
+                    apply_time_control
+                        .run_if(resource_changed::<TimeScale>.or(resource_changed::<PauseState>)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// ゲーム全体の時間の倍率(1.0で通常の速さ、0.5で半分の速さ)
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl TimeScale {
+    /// 倍率を半分にする(下限より小さくはしない)
+    pub fn halve(self) -> Self {
+        Self((self.0 * 0.5).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE))
+    }
+
+    /// 倍率を2倍にする(上限より大きくはしない)
+    pub fn double(self) -> Self {
+        Self((self.0 * 2.0).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE))
+    }
+}
+
+/// 一時停止しているかどうか
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PauseState {
+    #[default]
+    Running, // 通常の実行中
+    Paused, // 一時停止中
+}
+
+impl PauseState {
+    /// 一時停止と再開を切り替える
+    pub fn toggle(&mut self) {
+        *self = match self {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        };
+    }
+}
+
+/// 一時停止していないときだけシステムを実行する実行条件
+pub fn is_running(state: Res<PauseState>) -> bool {
+    *state == PauseState::Running
+}
+
+/// 一時停止中だけシステムを実行する実行条件(ポーズ画面の操作など)
+pub fn is_paused(state: Res<PauseState>) -> bool {
+    *state == PauseState::Paused
+}
+
+/// キー入力で一時停止と時間の倍率を切り替えるシステム
+fn handle_time_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<PauseState>,
+    mut scale: ResMut<TimeScale>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        state.toggle();
+        match *state {
+            PauseState::Running => info!("再開しました"),
+            PauseState::Paused => info!("一時停止しました"),
+        }
+    }
+
+    let new_scale = if keyboard.just_pressed(KeyCode::Minus) {
+        scale.halve()
+    } else if keyboard.just_pressed(KeyCode::Equal) {
+        scale.double()
+    } else if keyboard.just_pressed(KeyCode::Digit0) {
+        TimeScale::default()
+    } else {
+        return;
+    };
+    scale.set_if_neq(new_scale);
+    info!("時間の倍率: {}", scale.0);
+}
+
+/// 倍率と一時停止の状態を仮想時間に反映するシステム
+fn apply_time_control(
+    scale: Res<TimeScale>,
+    state: Res<PauseState>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.set_relative_speed(scale.0.max(0.0));
+    match *state {
+        PauseState::Running => time.unpause(),
+        PauseState::Paused => time.pause(),
+    }
+}
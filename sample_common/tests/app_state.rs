@@ -0,0 +1,62 @@
+//! `AppStatePlugin`がメニューから読み込み中を経てプレイ中に進み、一時停止に追従することを確認するテスト
+
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use sample_common::{AppState, AppStatePlugin, Locale, LocalizationPlugin, PauseState};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        StatesPlugin,
+        AppStatePlugin::new("title"),
+        LocalizationPlugin::new(),
+    ))
+    .init_resource::<ButtonInput<KeyCode>>()
+    .insert_resource(Locale::En);
+    app
+}
+
+fn state(app: &App) -> AppState {
+    *app.world().resource::<State<AppState>>().get()
+}
+
+#[test]
+fn enter_starts_loading_then_playing() {
+    let mut app = app();
+    app.update();
+    assert_eq!(state(&app), AppState::Menu);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Enter);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Loading);
+
+    // 待つアセットがないので、すぐにプレイが始まる
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+}
+
+#[test]
+fn pause_state_switches_between_playing_and_paused() {
+    let mut app = app();
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Playing);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+
+    *app.world_mut().resource_mut::<PauseState>() = PauseState::Paused;
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Paused);
+
+    *app.world_mut().resource_mut::<PauseState>() = PauseState::Running;
+    app.update();
+    app.update();
+    assert_eq!(state(&app), AppState::Playing);
+}
@@ -0,0 +1,137 @@
+//! `ConfigPlugin`が設定ファイルを読み込んでリソースに反映することを確認するテスト
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use sample_common::{ConfigPlugin, LoadConfig};
+use serde::Deserialize;
+
+/// テスト用の設定(ファイルにない項目はデフォルト値になる)
+#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+struct SpeedConfig {
+    speed: f32,
+    accel: f32,
+}
+
+impl Default for SpeedConfig {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            accel: 2.0,
+        }
+    }
+}
+
+#[test]
+fn loaded_file_replaces_defaults() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: "tests/assets".into(),
+            ..default()
+        },
+        ConfigPlugin::<SpeedConfig>::new("config/speed.config.ron"),
+    ));
+
+    // 読み込みは別スレッドで行われるので、反映されるまで更新を繰り返す
+    assert_eq!(
+        *app.world().resource::<SpeedConfig>(),
+        SpeedConfig::default()
+    );
+    for _ in 0..200 {
+        app.update();
+        if app.world().resource::<SpeedConfig>().speed != 1.0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        *app.world().resource::<SpeedConfig>(),
+        SpeedConfig {
+            speed: 8.0,
+            accel: 2.0,
+        }
+    );
+}
+
+#[test]
+fn modified_file_is_applied() {
+    let dir = std::env::temp_dir().join(format!("sample_common_config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("speed.config.ron"), "(speed: 3.0)").unwrap();
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: dir.to_string_lossy().into_owned(),
+            watch_for_changes_override: Some(true),
+            ..default()
+        },
+        ConfigPlugin::<SpeedConfig>::new("speed.config.ron"),
+    ));
+    for _ in 0..200 {
+        app.update();
+        if app.world().resource::<SpeedConfig>().speed == 3.0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.world().resource::<SpeedConfig>().speed, 3.0);
+
+    std::fs::write(dir.join("speed.config.ron"), "(speed: 4.0)").unwrap();
+    for _ in 0..500 {
+        app.update();
+        if app.world().resource::<SpeedConfig>().speed == 4.0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.world().resource::<SpeedConfig>().speed, 4.0);
+}
+
+/// `speed`が`expected`になるまで更新を繰り返す
+fn wait_for_speed(app: &mut App, expected: f32) {
+    for _ in 0..200 {
+        app.update();
+        if app.world().resource::<SpeedConfig>().speed == expected {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.world().resource::<SpeedConfig>().speed, expected);
+}
+
+#[test]
+fn load_config_switches_to_another_file() {
+    let dir = std::env::temp_dir().join(format!("sample_common_switch_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("first.config.ron"), "(speed: 5.0)").unwrap();
+    std::fs::write(dir.join("second.config.ron"), "(speed: 6.0)").unwrap();
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: dir.to_string_lossy().into_owned(),
+            ..default()
+        },
+        ConfigPlugin::<SpeedConfig>::new("first.config.ron"),
+    ));
+    wait_for_speed(&mut app, 5.0);
+
+    app.world_mut()
+        .commands()
+        .queue(LoadConfig::<SpeedConfig>::new("second.config.ron"));
+    wait_for_speed(&mut app, 6.0);
+
+    // 読み込んであるファイルに戻すときは、すぐに置き換わる
+    app.world_mut()
+        .commands()
+        .queue(LoadConfig::<SpeedConfig>::new("first.config.ron"));
+    app.world_mut().flush();
+    assert_eq!(app.world().resource::<SpeedConfig>().speed, 5.0);
+}
@@ -0,0 +1,42 @@
+//! クラッシュレポートに直前の状態が含まれることと、エラー画面の引数の読み取りを確認するテスト
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use sample_common::crash::{report_path_from_args, report_text};
+use sample_common::{CrashReportPlugin, CrashWatchPlugin};
+
+/// レポートに含めるリソース
+#[derive(Resource, Debug)]
+struct Score(u32);
+
+#[test]
+fn report_contains_watched_resources() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        CrashReportPlugin::default(),
+        CrashWatchPlugin::<Score>::default(),
+    ))
+    .insert_resource(Score(3));
+    app.update();
+
+    let report = report_text("panicked at src/main.rs:1:1");
+    assert!(report.contains("panicked at src/main.rs:1:1"));
+    assert!(report.contains("Score(3)"));
+
+    // 変更されたリソースは新しい値に置き換わる
+    app.world_mut().resource_mut::<Score>().0 = 7;
+    app.update();
+    assert!(report_text("").contains("Score(7)"));
+}
+
+#[test]
+fn crash_report_argument_is_parsed() {
+    let args = ["--crash-report", "crash_reports/app/1.txt"].map(String::from);
+    assert_eq!(
+        report_path_from_args(args.into_iter()),
+        Some(PathBuf::from("crash_reports/app/1.txt"))
+    );
+    assert_eq!(report_path_from_args(std::iter::empty()), None);
+}
@@ -0,0 +1,67 @@
+//! 対応表から現在の言語の文章が作られ、`Text`に反映されることを確認するテスト
+
+use bevy::prelude::*;
+use sample_common::locale::Translations;
+use sample_common::{Locale, LocalizationPlugin, LocalizedText};
+
+const EN: &str = r#"{
+    "greeting": "Hello, {name}!",
+    "state": "Light is {light}",
+    "on": "on",
+    "english_only": "Only in English",
+}"#;
+
+const JA: &str = r#"{
+    "greeting": "こんにちは、{name}さん!",
+    "state": "ライトは{light}",
+    "on": "オン",
+}"#;
+
+fn translations() -> Translations {
+    let mut translations = Translations::default();
+    translations.add_bundle(Locale::En, EN).unwrap();
+    translations.add_bundle(Locale::Ja, JA).unwrap();
+    translations
+}
+
+#[test]
+fn missing_keys_fall_back_to_english_then_key() {
+    let translations = translations();
+    assert_eq!(translations.get(Locale::Ja, "english_only"), "Only in English");
+    assert_eq!(translations.get(Locale::Ja, "unknown"), "unknown");
+}
+
+#[test]
+fn arguments_are_formatted_in_current_locale() {
+    let translations = translations();
+    let text = LocalizedText::new("state").with_key_arg("light", "on");
+    assert_eq!(translations.format(Locale::En, &text), "Light is on");
+    assert_eq!(translations.format(Locale::Ja, &text), "ライトはオン");
+
+    let text = LocalizedText::new("greeting").with_arg("name", 42);
+    assert_eq!(translations.format(Locale::Ja, &text), "こんにちは、42さん!");
+}
+
+#[test]
+fn text_follows_localized_text_changes() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        LocalizationPlugin::new().bundle(Locale::En, EN),
+    ))
+    .init_resource::<ButtonInput<KeyCode>>()
+    .insert_resource(Locale::En);
+
+    let entity = app
+        .world_mut()
+        .spawn(LocalizedText::new("greeting").with_arg("name", "Bevy"))
+        .id();
+    app.update();
+    assert_eq!(app.world().get::<Text>(entity).unwrap().0, "Hello, Bevy!");
+
+    *app.world_mut().get_mut::<LocalizedText>(entity).unwrap() =
+        LocalizedText::new("state").with_key_arg("light", "on");
+    app.update();
+    assert_eq!(app.world().get::<Text>(entity).unwrap().0, "Light is on");
+}
@@ -0,0 +1,148 @@
+//! `PostFxSettings`の値が3Dカメラに反映され、キー操作で切り替わることを確認するテスト
+
+use bevy::core_pipeline::bloom::Bloom;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::prelude::*;
+use bevy::render::view::Msaa;
+use sample_common::{
+    Locale, LocalizationPlugin, LocalizedText, PostFxKeys, PostFxPlugin, PostFxSettings,
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        LocalizationPlugin::new(),
+        PostFxPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_resource::<ButtonInput<KeyCode>>()
+    .insert_resource(Locale::En);
+    app
+}
+
+#[test]
+fn settings_are_applied_to_new_cameras() {
+    let mut app = app();
+    let camera = app.world_mut().spawn(Camera3d::default()).id();
+    app.update();
+
+    let settings = PostFxSettings::default();
+    let entity = app.world().entity(camera);
+    assert_eq!(entity.get::<Tonemapping>(), Some(&settings.tonemapping));
+    assert_eq!(entity.get::<Msaa>(), Some(&settings.msaa));
+    assert_eq!(
+        entity.get::<Bloom>().map(|bloom| bloom.intensity),
+        Some(settings.bloom_intensity)
+    );
+    assert!(entity.get::<Camera>().unwrap().hdr);
+}
+
+#[test]
+fn keys_change_settings_and_cameras() {
+    let mut app = app();
+    app.insert_resource(PostFxSettings {
+        bloom_intensity: 0.05,
+        ..default()
+    });
+    let camera = app.world_mut().spawn(Camera3d::default()).id();
+    app.update();
+
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.press(KeyCode::F4);
+    keyboard.press(KeyCode::F7);
+    keyboard.press(KeyCode::BracketLeft);
+    app.update();
+
+    let settings = app.world().resource::<PostFxSettings>().clone();
+    assert_eq!(settings.tonemapping, Tonemapping::BlenderFilmic);
+    assert_eq!(settings.msaa, Msaa::Off);
+    assert_eq!(settings.bloom_intensity, 0.0);
+
+    let entity = app.world().entity(camera);
+    assert_eq!(entity.get::<Tonemapping>(), Some(&Tonemapping::BlenderFilmic));
+    assert_eq!(entity.get::<Msaa>(), Some(&Msaa::Off));
+    assert!(entity.get::<Bloom>().is_none()); // 強さが0になるとブルームを外す
+}
+
+#[test]
+fn shift_with_bloom_keys_changes_the_threshold() {
+    let mut app = app();
+    let camera = app.world_mut().spawn(Camera3d::default()).id();
+    app.update();
+    let intensity = app.world().resource::<PostFxSettings>().bloom_intensity;
+
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.press(KeyCode::ShiftLeft);
+    keyboard.press(KeyCode::BracketRight);
+    app.update();
+
+    // Shiftを押している間は、強さは変えずにしきい値を上げる
+    let settings = app.world().resource::<PostFxSettings>().clone();
+    assert_eq!(settings.bloom_intensity, intensity);
+    assert!(settings.bloom_threshold > 0.0);
+    let bloom = app.world().entity(camera).get::<Bloom>().unwrap();
+    assert_eq!(bloom.prefilter.threshold, settings.bloom_threshold);
+
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release(KeyCode::BracketRight);
+    keyboard.press(KeyCode::BracketLeft);
+    app.update();
+    assert_eq!(app.world().resource::<PostFxSettings>().bloom_threshold, 0.0);
+}
+
+#[test]
+fn bloom_keys_can_be_changed() {
+    let mut app = app();
+    app.insert_resource(PostFxKeys {
+        bloom_down: KeyCode::Comma,
+        bloom_up: KeyCode::Period,
+        label: ", / .",
+        ..default()
+    });
+    app.update();
+    let before = app.world().resource::<PostFxSettings>().bloom_intensity;
+
+    // 元のキーでは変わらない
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.press(KeyCode::BracketRight);
+    app.update();
+    assert_eq!(app.world().resource::<PostFxSettings>().bloom_intensity, before);
+
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release(KeyCode::BracketRight);
+    keyboard.press(KeyCode::Period);
+    app.update();
+    assert!(app.world().resource::<PostFxSettings>().bloom_intensity > before);
+}
+
+#[test]
+fn panel_key_can_be_changed() {
+    let mut app = app();
+    app.insert_resource(PostFxKeys {
+        panel: KeyCode::F6,
+        ..default()
+    });
+    app.update();
+    let panel_visibility = |app: &mut App| {
+        app.world_mut()
+            .query::<(&LocalizedText, &Visibility)>()
+            .iter(app.world())
+            .find(|(text, _)| text.key == "post_fx.panel")
+            .map(|(_, visibility)| *visibility)
+            .unwrap()
+    };
+
+    // 元のF3キーでは開かない(サンプル側の操作に使える)
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.press(KeyCode::F3);
+    app.update();
+    assert_eq!(panel_visibility(&mut app), Visibility::Hidden);
+
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release(KeyCode::F3);
+    keyboard.press(KeyCode::F6);
+    app.update();
+    assert_eq!(panel_visibility(&mut app), Visibility::Visible);
+}
@@ -0,0 +1,113 @@
+//! `Snapshot`で保存したエンティティとリソースが復元されることを確認するテスト
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use sample_common::Snapshot;
+
+/// 保存対象のマーカー
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+struct Saved;
+
+/// 保存対象のリソース
+#[derive(Resource, Reflect, Default, PartialEq, Debug)]
+#[reflect(Resource, Default)]
+struct Score(u32);
+
+fn app() -> App {
+    let mut app = App::new();
+    app.register_type::<Saved>()
+        .register_type::<Score>()
+        .register_type::<Name>()
+        .register_type::<Transform>()
+        .insert_resource(Score(0));
+    app
+}
+
+fn snapshot() -> Snapshot {
+    Snapshot::new()
+        .allow::<Transform>()
+        .allow_resource::<Score>()
+}
+
+/// テストごとに別の保存先を使う
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("sample_common_snapshot_{}", std::process::id()))
+        .join(format!("{name}.scn.ron"))
+}
+
+fn translation_of(world: &mut World, name: &str) -> Option<Vec3> {
+    world
+        .query::<(&Name, &Transform)>()
+        .iter(world)
+        .find(|(entity_name, _)| entity_name.as_str() == name)
+        .map(|(_, transform)| transform.translation)
+}
+
+#[test]
+fn named_entities_are_restored_in_place() {
+    let mut app = app();
+    let world = app.world_mut();
+    let player = world
+        .spawn((
+            Saved,
+            Name::new("Player"),
+            Transform::from_xyz(1.0, 2.0, 3.0),
+        ))
+        .id();
+    world.resource_mut::<Score>().0 = 10;
+
+    let path = temp_path("in_place");
+    assert_eq!(snapshot().save::<Saved>(world, &path).unwrap(), 1);
+
+    // 保存後に状態を変えてから読み込む
+    world
+        .entity_mut(player)
+        .insert(Transform::from_xyz(9.0, 9.0, 9.0));
+    world.resource_mut::<Score>().0 = 99;
+    snapshot().load::<Saved>(world, &path).unwrap();
+
+    // 同じエンティティが保存時の位置に戻る
+    assert_eq!(
+        world.get::<Transform>(player).unwrap().translation,
+        Vec3::new(1.0, 2.0, 3.0)
+    );
+    assert_eq!(*world.resource::<Score>(), Score(10));
+    assert_eq!(world.query::<&Saved>().iter(world).count(), 1);
+}
+
+#[test]
+fn missing_entities_are_spawned_and_extra_ones_despawned() {
+    let mut app = app();
+    let world = app.world_mut();
+    let kept = world
+        .spawn((Saved, Name::new("Kept"), Transform::from_xyz(1.0, 0.0, 0.0)))
+        .id();
+    world.spawn((Saved, Transform::from_xyz(2.0, 0.0, 0.0)));
+
+    let path = temp_path("respawn");
+    assert_eq!(snapshot().save::<Saved>(world, &path).unwrap(), 2);
+
+    // 名前のない方を消し、保存時になかったエンティティを追加する
+    let unnamed: Vec<Entity> = world
+        .query_filtered::<Entity, (With<Saved>, Without<Name>)>()
+        .iter(world)
+        .collect();
+    for entity in unnamed {
+        world.despawn(entity);
+    }
+    world.spawn((Saved, Name::new("Extra"), Transform::default()));
+    snapshot().load::<Saved>(world, &path).unwrap();
+
+    assert_eq!(world.query::<&Saved>().iter(world).count(), 2);
+    assert!(world.get_entity(kept).is_ok());
+    assert_eq!(translation_of(world, "Extra"), None);
+    let restored: Vec<Vec3> = world
+        .query_filtered::<&Transform, (With<Saved>, Without<Name>)>()
+        .iter(world)
+        .map(|transform| transform.translation)
+        .collect();
+    assert_eq!(restored, vec![Vec3::new(2.0, 0.0, 0.0)]);
+}
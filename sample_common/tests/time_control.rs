@@ -0,0 +1,63 @@
+//! `TimeControlPlugin`が一時停止と時間の倍率を仮想時間に反映することを確認するテスト
+
+use bevy::prelude::*;
+use sample_common::time_control::{MAX_TIME_SCALE, MIN_TIME_SCALE, is_running};
+use sample_common::{PauseState, TimeControlPlugin, TimeScale};
+
+/// 一時停止していないフレームを数えるリソース
+#[derive(Resource, Default)]
+struct RunningFrames(u32);
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TimeControlPlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<RunningFrames>()
+        .add_systems(
+            Update,
+            (|mut frames: ResMut<RunningFrames>| frames.0 += 1).run_if(is_running),
+        );
+    app
+}
+
+#[test]
+fn pause_stops_virtual_time_and_gated_systems() {
+    let mut app = app();
+    app.update();
+    assert_eq!(app.world().resource::<RunningFrames>().0, 1);
+
+    *app.world_mut().resource_mut::<PauseState>() = PauseState::Paused;
+    app.update();
+    app.update();
+
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+    assert_eq!(app.world().resource::<RunningFrames>().0, 1);
+
+    app.world_mut().resource_mut::<PauseState>().toggle();
+    app.update();
+    assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+    assert_eq!(app.world().resource::<RunningFrames>().0, 2);
+}
+
+#[test]
+fn time_scale_sets_relative_speed() {
+    let mut app = app();
+    app.insert_resource(TimeScale(0.5));
+    app.update();
+
+    assert_eq!(
+        app.world().resource::<Time<Virtual>>().relative_speed(),
+        0.5
+    );
+}
+
+#[test]
+fn halving_and_doubling_stop_at_the_limits() {
+    assert_eq!(TimeScale(1.0).halve(), TimeScale(0.5));
+    assert_eq!(TimeScale(1.0).double(), TimeScale(2.0));
+    assert_eq!(TimeScale(MIN_TIME_SCALE).halve(), TimeScale(MIN_TIME_SCALE));
+    assert_eq!(
+        TimeScale(MAX_TIME_SCALE).double(),
+        TimeScale(MAX_TIME_SCALE)
+    );
+}
@@ -1,12 +1,36 @@
 use bevy:: {
-	core_pipeline::{bloom::Bloom, tonemapping::Tonemapping, Skybox},
+	core_pipeline::Skybox,
 	math::Vec3,
 	pbr::{FogVolume, VolumetricFog, VolumetricLight},
 	prelude::*,
 };
+use sample_common::{ActionAxes, ActionMap, ActionsPlugin, ConfigPlugin, Locale, LocalizationPlugin, LocalizedText, PostFxPlugin, QualityLevel, ScreenshotPlugin};
+use serde::Deserialize;
 
-// 光の動きの速度を定義
-const DIRECTIONAL_LIGHT_MOVEMENT_SPEED: f32 = 0.02;
+/// `assets/config/volumetric_fog.config.ron`から読み込む設定
+/// 実行中にファイルを保存すると、すぐに反映される
+#[derive(Asset, Resource, Reflect, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+struct FogConfig {
+	fog_density: f32, // 霧の濃さ(FogVolumeのdensity_factor)
+	point_light_intensity: f32, // PointLightの強度
+	spot_light_intensity: f32, // SpotLightの強度(ルーメンス)
+	light_movement_speed: f32, // DirectionalLightの向きを変える速度
+}
+
+impl Default for FogConfig {
+	fn default() -> Self {
+		Self {
+			fog_density: 0.1,
+			point_light_intensity: 1000.0,
+			spot_light_intensity: 5000.0,
+			light_movement_speed: 0.02,
+		}
+	}
+}
+
+/// 霧の散乱・吸収の係数を1秒間に変える量
+const FOG_COEFFICIENT_SPEED: f32 = 0.2;
 
 /// ユーザーが選んだ設定
 #[derive(Resource)]
@@ -15,19 +39,74 @@ struct AppSettings {
 	volumetric_spotlight: bool,
 	// ボリューメトリックポイントライトが有効かどうか
 	volumetric_pointlight: bool,
+	// 太陽光(DirectionalLight)のボリューメトリック効果が有効かどうか
+	volumetric_sunlight: bool,
+	// 霧が光を散乱させる割合(FogVolumeのscattering、0〜1)
+	scattering: f32,
+	// 霧が光を吸収する割合(FogVolumeのabsorption、0〜1)
+	absorption: f32,
 }
 
 /// 構造体の初期化
 impl Default for AppSettings {
 	fn default() -> Self {
-		// デフォルトではボリューメトリックスポットライトとポイントライトが有効
+		// デフォルトではすべてのライトのボリューメトリック効果が有効
+		// 散乱と吸収はFogVolumeのデフォルト値
+		let fog = FogVolume::default();
 		Self {
 			volumetric_spotlight: true,
 			volumetric_pointlight: true,
+			volumetric_sunlight: true,
+			scattering: fog.scattering,
+			absorption: fog.absorption,
 		}
 	}
 }
 
+/// ユーザーの操作(キーボードとゲームパッドの両方に割り当てる)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FogAction {
+	Up, // 光の向きを上に
+	Down, // 光の向きを下に
+	Left, // 光の向きを左に
+	Right, // 光の向きを右に
+	TogglePointLight, // PointLightのボリューメトリック効果を切り替え
+	ToggleSpotLight, // SpotLightのボリューメトリック効果を切り替え
+	ToggleSunLight, // DirectionalLightのボリューメトリック効果を切り替え
+	LessScattering, // 霧の散乱を弱める(押している間)
+	MoreScattering, // 霧の散乱を強める(押している間)
+	LessAbsorption, // 霧の吸収を弱める(押している間)
+	MoreAbsorption, // 霧の吸収を強める(押している間)
+}
+
+/// 操作の割り当て
+fn action_map() -> ActionMap<FogAction> {
+	ActionMap::new()
+		.key(FogAction::Up, KeyCode::KeyW)
+		.key(FogAction::Up, KeyCode::ArrowUp)
+		.key(FogAction::Down, KeyCode::KeyS)
+		.key(FogAction::Down, KeyCode::ArrowDown)
+		.key(FogAction::Left, KeyCode::KeyA)
+		.key(FogAction::Left, KeyCode::ArrowLeft)
+		.key(FogAction::Right, KeyCode::KeyD)
+		.key(FogAction::Right, KeyCode::ArrowRight)
+		.movement(FogAction::Up, FogAction::Down, FogAction::Left, FogAction::Right) // 十字キーと左スティックでも動かせる
+		.key(FogAction::TogglePointLight, KeyCode::KeyP)
+		.button(FogAction::TogglePointLight, GamepadButton::West)
+		.key(FogAction::ToggleSpotLight, KeyCode::KeyL)
+		.button(FogAction::ToggleSpotLight, GamepadButton::North)
+		.key(FogAction::ToggleSunLight, KeyCode::KeyU)
+		.button(FogAction::ToggleSunLight, GamepadButton::East)
+		.key(FogAction::LessScattering, KeyCode::KeyZ)
+		.button(FogAction::LessScattering, GamepadButton::LeftTrigger)
+		.key(FogAction::MoreScattering, KeyCode::KeyX)
+		.button(FogAction::MoreScattering, GamepadButton::RightTrigger)
+		.key(FogAction::LessAbsorption, KeyCode::KeyC)
+		.button(FogAction::LessAbsorption, GamepadButton::LeftTrigger2)
+		.key(FogAction::MoreAbsorption, KeyCode::KeyV)
+		.button(FogAction::MoreAbsorption, GamepadButton::RightTrigger2)
+}
+
 /// point lightの動きの範囲を定義
 #[derive(Component)]
 struct MoveBackAndForthHorizontally {
@@ -40,9 +119,18 @@ struct MoveBackAndForthHorizontally {
 }
 
 
-fn main() {
+fn main() -> AppExit {
 	App::new()
-		.add_plugins(DefaultPlugins) // デフォルトのプラグインを追加
+		.add_plugins(sample_common::headless::default_plugins()) // デフォルトのプラグインを追加(--headless時はウィンドウなし)
+		.add_plugins(ScreenshotPlugin::new("volumetric_fog").with_recorder(30.0)) // F12で撮影、F11で録画
+		.add_plugins(ActionsPlugin::new(action_map())) // キーボードとゲームパッドの操作
+		.add_plugins(ConfigPlugin::<FogConfig>::new("config/volumetric_fog.config.ron")) // 霧の濃さや光の強さ(編集すると実行中に反映される)
+		.add_plugins(
+			LocalizationPlugin::new()
+				.bundle(Locale::En, include_str!("../assets/locale/en.ron"))
+				.bundle(Locale::Ja, include_str!("../assets/locale/ja.ron")),
+		) // 操作説明の英語・日本語(F2で切り替え)
+		.add_plugins(PostFxPlugin) // 明暗調整(トーンマッピング)と光のにじみ(ブルーム)、F3で設定パネル
 		.insert_resource(ClearColor(Color::Srgba(Srgba {
 			red: 0.02,
 			green: 0.02,
@@ -61,8 +149,87 @@ fn main() {
 			move_point_light,
 			move_directional_light,
 		))
-		.add_systems(Update, adjust_app_settings)
-		.run();
+		.add_systems(Update, (adjust_app_settings, adjust_fog_coefficients))
+		.add_systems(Update, apply_quality.run_if(resource_changed::<QualityLevel>)) // 品質が変わったときだけ実行
+		.add_systems(Update, apply_config.run_if(resource_changed::<FogConfig>)) // 設定ファイルが変わったときだけ実行
+		.run()
+}
+
+/// 低品質モードではボリューメトリック効果を外し(WebGL2や低速な環境向け)、品質が戻ったら付け直す
+fn apply_quality(
+	mut commands: Commands,
+	quality: Res<QualityLevel>,
+	app_settings: Res<AppSettings>,
+	cameras: Query<Entity, With<Camera3d>>,
+	point_lights: Query<Entity, With<PointLight>>,
+	spot_lights: Query<Entity, With<SpotLight>>,
+	directional_lights: Query<Entity, With<DirectionalLight>>,
+) {
+	for camera in cameras.iter() {
+		if quality.is_low() {
+			commands.entity(camera).remove::<VolumetricFog>();
+		} else {
+			commands.entity(camera).insert(camera_fog());
+		}
+	}
+	update_volumetric_lights(
+		&mut commands,
+		&quality,
+		&app_settings,
+		&point_lights,
+		&spot_lights,
+		&directional_lights,
+	);
+}
+
+/// 各光源のボリューメトリック効果を、設定に合わせて付け外しする(低品質モードでは付けない)
+fn update_volumetric_lights(
+	commands: &mut Commands,
+	quality: &QualityLevel,
+	app_settings: &AppSettings,
+	point_lights: &Query<Entity, With<PointLight>>,
+	spot_lights: &Query<Entity, With<SpotLight>>,
+	directional_lights: &Query<Entity, With<DirectionalLight>>,
+) {
+	let lights = point_lights
+		.iter()
+		.map(|light| (light, app_settings.volumetric_pointlight))
+		.chain(spot_lights.iter().map(|light| (light, app_settings.volumetric_spotlight)))
+		.chain(directional_lights.iter().map(|light| (light, app_settings.volumetric_sunlight)));
+	for (light, enabled) in lights {
+		if enabled && !quality.is_low() {
+			commands.entity(light).insert(VolumetricLight); // 効果を追加
+		} else {
+			commands.entity(light).remove::<VolumetricLight>(); // 効果を削除
+		}
+	}
+}
+
+/// カメラに付ける立体的な霧効果
+fn camera_fog() -> VolumetricFog {
+	VolumetricFog {
+		// 環境光は無効化
+		ambient_intensity: 0.0, // 環境光の強度
+		..default()
+	}
+}
+
+/// 設定ファイルの値を霧と光源に反映するシステム
+fn apply_config(
+	config: Res<FogConfig>,
+	mut fog_volumes: Query<&mut FogVolume>,
+	mut point_lights: Query<&mut PointLight>,
+	mut spot_lights: Query<&mut SpotLight>,
+) {
+	for mut fog_volume in fog_volumes.iter_mut() {
+		fog_volume.density_factor = config.fog_density;
+	}
+	for mut point_light in point_lights.iter_mut() {
+		point_light.intensity = config.point_light_intensity;
+	}
+	for mut spot_light in spot_lights.iter_mut() {
+		spot_light.intensity = config.spot_light_intensity;
+	}
 }
 
 /// シーンのセットアップ
@@ -85,19 +252,13 @@ fn setup(
 			..default()
 		},
 		Transform::from_xyz(-1.7, 1.5, 4.5).looking_at(vec3(-1.5, 1.7, 3.5), Vec3::Y), // 注視点を設定
-		Tonemapping::TonyMcMapface, // 明暗調整
-    Bloom::default(), // 光のにじみ
 	))
 	.insert(Skybox { // 周囲の環境を示す背景
 		image: asset_server.load("environment_maps/pisa_specular_rgb9e5_zstd.ktx2"), // 環境マップを設定
     brightness: 1000.0,
     ..default()
   })
-	.insert(VolumetricFog { // 立体的な霧効果
-		// 環境光は無効化
-		ambient_intensity: 0.0, // 環境光の強度
-		..default()
-	});
+	.insert(camera_fog()); // 立体的な霧効果
 
 	// point lightを追加
 	commands.spawn((
@@ -151,56 +312,70 @@ fn setup(
 	));
 }
 
-/// UIテキストを作成する関数
-fn create_text(app_settings: &AppSettings) -> Text {
-    format!(
-        "{}\n{}\n{}",
-        "Press WASD or the arrow keys to change the direction of the directional light",
-        if app_settings.volumetric_pointlight {
-            "Press P to turn volumetric point light off"
-        } else {
-            "Press P to turn volumetric point light on"
-        },
-        if app_settings.volumetric_spotlight {
-            "Press L to turn volumetric spot light off"
-        } else {
-            "Press L to turn volumetric spot light on"
-        }
-    )
-    .into()
+/// UIテキストを作成する関数(文章はassets/locale以下の対応表から読み込む)
+fn create_text(app_settings: &AppSettings) -> LocalizedText {
+    LocalizedText::new("help")
+        .with_key_arg(
+            "sun",
+            if app_settings.volumetric_sunlight {
+                "sun_light_off"
+            } else {
+                "sun_light_on"
+            },
+        )
+        .with_arg("scattering", format!("{:.2}", app_settings.scattering))
+        .with_arg("absorption", format!("{:.2}", app_settings.absorption))
+        .with_key_arg(
+            "point",
+            if app_settings.volumetric_pointlight {
+                "point_light_off"
+            } else {
+                "point_light_on"
+            },
+        )
+        .with_key_arg(
+            "spot",
+            if app_settings.volumetric_spotlight {
+                "spot_light_off"
+            } else {
+                "spot_light_on"
+            },
+        )
 }
 
 /// シーン内で変更があったDirectionLightに対して影の有効化と光源効果を付与
+/// 光源効果はUキーで外せるので、設定で有効な場合だけ付ける
 fn tweak_scene(
 	mut commands: Commands,
 	mut lights: Query<(Entity, &mut DirectionalLight), Changed<DirectionalLight>>, // シーン内で変更されたDirectionalLightを取得
+	quality: Res<QualityLevel>,
+	app_settings: Res<AppSettings>,
 ) {
+	// 低品質モードでは影もボリューメトリック効果も付けない
+	if quality.is_low() {
+		return;
+	}
+
 	// 直前のフレームでなんらかの変更があった全てのDirectionalLightに対して...
 	for (light, mut directional_light) in lights.iter_mut() {
-		directional_light.shadows_enabled = true; // シャドウを有効化
-		commands.entity(light).insert(VolumetricLight); // 光の道筋が見える効果を付与
+		// 値が同じなら書き換えない(書き換えると次のフレームでも変更ありとして扱われる)
+		if !directional_light.shadows_enabled {
+			directional_light.shadows_enabled = true; // シャドウを有効化
+		}
+		if app_settings.volumetric_sunlight {
+			commands.entity(light).insert(VolumetricLight); // 光の道筋が見える効果を付与
+		}
 	}
 }
 
 /// ユーザーの入力に対して光の動きを調整するシステム
 fn move_directional_light(
-	input: Res<ButtonInput<KeyCode>>,
+	axes: Res<ActionAxes>, // WASD・矢印キー・十字キー・左スティックの入力
+	config: Res<FogConfig>,
 	mut directional_lights: Query<&mut Transform, With<DirectionalLight>>,
 ) {
-	let mut delta_theta = Vec2::ZERO; // 光の動きの変化量を初期化
-
-	if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
-        delta_theta.y += DIRECTIONAL_LIGHT_MOVEMENT_SPEED;
-    }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
-        delta_theta.y -= DIRECTIONAL_LIGHT_MOVEMENT_SPEED;
-    }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        delta_theta.x += DIRECTIONAL_LIGHT_MOVEMENT_SPEED;
-    }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        delta_theta.x -= DIRECTIONAL_LIGHT_MOVEMENT_SPEED;
-    }
+	// 光の動きの変化量(左右の入力は回転の向きが逆になる)
+	let delta_theta = Vec2::new(-axes.movement.x, axes.movement.y) * config.light_movement_speed;
 
     if delta_theta == Vec2::ZERO { // 何も入力されていない場合は即時に終了
         return;
@@ -253,53 +428,83 @@ fn move_point_light(
 /// ユーザーの入力に応じてアプリケーションの設定を調整するシステム
 fn adjust_app_settings(
 	mut commands: Commands,
-	keyboard_input: Res<ButtonInput<KeyCode>>,
+	actions: Res<ButtonInput<FogAction>>,
+	quality: Res<QualityLevel>,
 	mut app_settings: ResMut<AppSettings>, // アプリケーションの設定を可変可能な形で取得
-	mut point_lights: Query<Entity, With<PointLight>>,
-	mut spot_lights: Query<Entity, With<SpotLight>>,
-	mut text: Query<&mut Text>,
+	point_lights: Query<Entity, With<PointLight>>,
+	spot_lights: Query<Entity, With<SpotLight>>,
+	directional_lights: Query<Entity, With<DirectionalLight>>,
+	mut text: Query<&mut LocalizedText>,
 ) {
 
 	// 変更のフラグ
 	let mut any_changed = false;
 
-	if keyboard_input.just_pressed(KeyCode::KeyP) {
+	if actions.just_pressed(FogAction::TogglePointLight) {
 		// Pキーが押された場合、PointLightのボリューメトリック効果を切り替え(on/off)
 		app_settings.volumetric_pointlight = !app_settings.volumetric_pointlight;
 		any_changed = true;
 	}
-	if keyboard_input.just_pressed(KeyCode::KeyL) {
+	if actions.just_pressed(FogAction::ToggleSpotLight) {
 		// Lキーが押された場合、SpotLightのボリューメトリック効果を切り替え(on/off)
 		app_settings.volumetric_spotlight = !app_settings.volumetric_spotlight;
 		any_changed = true;
 	}
+	if actions.just_pressed(FogAction::ToggleSunLight) {
+		// Uキーが押された場合、DirectionalLightのボリューメトリック効果を切り替え(on/off)
+		app_settings.volumetric_sunlight = !app_settings.volumetric_sunlight;
+		any_changed = true;
+	}
 
 	// 変更がない場合終了
 	if !any_changed {
 		return;
 	}
 	
-	// PointLightのボリューメトリック効果を更新
-	for point_light in point_lights.iter_mut() {
-		if app_settings.volumetric_pointlight {
-			commands.entity(point_light).insert(VolumetricLight); // 効果を追加
-		} else {
-			commands.entity(point_light).remove::<VolumetricLight>(); // 効果を削除
-		}
+	// 各光源のボリューメトリック効果を更新(低品質モードでは付けない)
+	update_volumetric_lights(
+		&mut commands,
+		&quality,
+		&app_settings,
+		&point_lights,
+		&spot_lights,
+		&directional_lights,
+	);
+
+	// UIテキストを更新
+	for mut text in text.iter_mut() {
+		// テキストの内容を更新
+		*text = create_text(&app_settings);
 	}
+}
 
-	// SpotLightのボリューメトリック効果を更新
-	for spot_light in spot_lights.iter_mut() {
-		if app_settings.volumetric_spotlight {
-			commands.entity(spot_light).insert(VolumetricLight); // 効果を追加
-		} else {
-			commands.entity(spot_light).remove::<VolumetricLight>(); // 効果を削除
-		}
+/// 押している間、霧の散乱・吸収の係数を変えるシステム
+fn adjust_fog_coefficients(
+	time: Res<Time>,
+	actions: Res<ButtonInput<FogAction>>,
+	mut app_settings: ResMut<AppSettings>,
+	mut fog_volumes: Query<&mut FogVolume>,
+	mut text: Query<&mut LocalizedText>,
+) {
+	// 押しているキーに応じた変化の向き(-1、0、1)
+	let direction = |less: FogAction, more: FogAction| {
+		(actions.pressed(more) as i32 - actions.pressed(less) as i32) as f32
+	};
+	let scattering = direction(FogAction::LessScattering, FogAction::MoreScattering);
+	let absorption = direction(FogAction::LessAbsorption, FogAction::MoreAbsorption);
+	if scattering == 0.0 && absorption == 0.0 {
+		return;
 	}
 
-	// UIテキストを更新
+	let delta = FOG_COEFFICIENT_SPEED * time.delta_secs();
+	app_settings.scattering = (app_settings.scattering + scattering * delta).clamp(0.0, 1.0);
+	app_settings.absorption = (app_settings.absorption + absorption * delta).clamp(0.0, 1.0);
+
+	for mut fog_volume in fog_volumes.iter_mut() {
+		fog_volume.scattering = app_settings.scattering;
+		fog_volume.absorption = app_settings.absorption;
+	}
 	for mut text in text.iter_mut() {
-		// テキストの内容を更新
 		*text = create_text(&app_settings);
 	}
 }
\ No newline at end of file
@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+# サンプルをWebAssemblyとしてビルドし、web/dist/<名前>/ に公開用のファイルを出力する
+#
+# 使い方: web/build.sh <パッケージ名> [--release]
+# 必要なもの: rustup target add wasm32-unknown-unknown / cargo install wasm-bindgen-cli
+set -euo pipefail
+
+name="${1:?usage: web/build.sh <package> [--release]}"
+profile="debug"
+cargo_args=()
+if [[ "${2:-}" == "--release" ]]; then
+    profile="release"
+    cargo_args+=(--release)
+fi
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+out="$root/web/dist/$name"
+
+# dynamic_linkingやRapierのデバッグ表示などネイティブ専用の機能は外してビルドする
+# (RUSTFLAGSは依存先のgetrandom 0.3にブラウザの乱数を使わせるための指定)
+RUSTFLAGS='--cfg getrandom_backend="wasm_js"' \
+    cargo build -p "$name" --target wasm32-unknown-unknown --no-default-features "${cargo_args[@]}"
+
+target_dir="$(cargo metadata --format-version 1 --no-deps | sed -n 's/.*"target_directory":"\([^"]*\)".*/\1/p')"
+rm -rf "$out"
+mkdir -p "$out"
+wasm-bindgen --target web --no-typescript --out-name app --out-dir "$out" \
+    "$target_dir/wasm32-unknown-unknown/$profile/$name.wasm"
+
+# アセットはページと同じ場所の assets/ から読み込まれる
+if [[ -d "$root/$name/assets" ]]; then
+    cp -r "$root/$name/assets" "$out/assets"
+fi
+sed "s/{{TITLE}}/$name/" "$root/web/index.html" > "$out/index.html"
+
+echo "出力先: $out (例: python3 -m http.server -d $out)"